@@ -0,0 +1,105 @@
+//! Criterion benchmarks for the paths git-ai runs inside git hooks.
+//!
+//! Run with `cargo bench --bench hot_paths`. The same workloads back `git-ai bench`,
+//! which times them against a real repository instead of synthetic data.
+
+use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
+use git_ai::authorship::attribution_tracker::attributions_to_line_attributions;
+use git_ai::authorship::authorship_log_serialization::AuthorshipLog;
+use git_ai::authorship::imara_diff_utils::compute_line_changes;
+use git_ai::authorship::stats::stats_from_authorship_log;
+use git_ai::commands::bench::{
+    note_workload, refspec_decision, refspec_workload, synthetic_attributed_file_pair,
+    synthetic_file_pair,
+};
+use git_ai::commands::checkpoint::{make_entry_for_file, make_summary_entry_for_file};
+use std::collections::BTreeMap;
+
+fn bench_refspec_decision(c: &mut Criterion) {
+    let remotes = vec!["origin".to_string(), "upstream".to_string()];
+    let args = refspec_workload("upstream");
+    c.bench_function("refspec_decision", |b| {
+        b.iter(|| refspec_decision(black_box(&args), black_box(&remotes)))
+    });
+}
+
+fn bench_note_parse_serialize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("note");
+    for files in [1usize, 50, 500] {
+        let log = note_workload(files, 20);
+        let serialized = log.serialize_to_string().unwrap();
+        group.bench_with_input(BenchmarkId::new("parse", files), &serialized, |b, s| {
+            b.iter(|| AuthorshipLog::deserialize_from_string(black_box(s)).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("serialize", files), &log, |b, log| {
+            b.iter(|| black_box(log).serialize_to_string().unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_diff_attribution(c: &mut Criterion) {
+    let mut group = c.benchmark_group("diff_attribution");
+    for lines in [200usize, 2_000, 20_000] {
+        let (old, new) = synthetic_file_pair(lines);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(lines),
+            &(old, new),
+            |b, pair| b.iter(|| compute_line_changes(black_box(&pair.0), black_box(&pair.1)).len()),
+        );
+    }
+    group.finish();
+}
+
+/// A checkpoint's per-file work: character-level tracking against the summary-only fast path
+/// used for files over `checkpoint_summary_only_bytes`
+fn bench_checkpoint_entry(c: &mut Criterion) {
+    let mut group = c.benchmark_group("checkpoint_entry");
+    group.sample_size(10);
+    // Character-level tracking grows quadratically; 20,000 lines takes seconds per iteration
+    for lines in [2_000usize, 5_000] {
+        let (old, new, attributions) = synthetic_attributed_file_pair(lines);
+        group.bench_with_input(BenchmarkId::new("full", lines), &lines, |b, _| {
+            b.iter(|| make_entry_for_file("bench.rs", "", "ai", &old, &attributions, &new, 2))
+        });
+    }
+    for lines in [2_000usize, 20_000] {
+        let (old, new, attributions) = synthetic_attributed_file_pair(lines);
+        group.bench_with_input(BenchmarkId::new("summary", lines), &lines, |b, _| {
+            b.iter(|| {
+                make_summary_entry_for_file("bench.rs", "", "ai", &old, &attributions, &new, 2)
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_line_attributions(c: &mut Criterion) {
+    let mut group = c.benchmark_group("line_attributions");
+    for lines in [2_000usize, 20_000] {
+        let (old, _, attributions) = synthetic_attributed_file_pair(lines);
+        group.bench_with_input(BenchmarkId::from_parameter(lines), &lines, |b, _| {
+            b.iter(|| attributions_to_line_attributions(black_box(&attributions), &old).len())
+        });
+    }
+    group.finish();
+}
+
+fn bench_stats_walk(c: &mut Criterion) {
+    let log = note_workload(500, 20);
+    let by_tool = BTreeMap::new();
+    c.bench_function("stats_from_authorship_log", |b| {
+        b.iter(|| stats_from_authorship_log(Some(black_box(&log)), 10_000, 1_000, 5_000, &by_tool))
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_refspec_decision,
+    bench_note_parse_serialize,
+    bench_diff_attribution,
+    bench_checkpoint_entry,
+    bench_line_attributions,
+    bench_stats_walk
+);
+criterion_main!(benches);
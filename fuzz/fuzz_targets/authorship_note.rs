@@ -0,0 +1,19 @@
+#![no_main]
+
+use git_ai::authorship::authorship_log_serialization::AuthorshipLog;
+use libfuzzer_sys::fuzz_target;
+
+// Notes sync from arbitrary remotes; parsing must fail gracefully and anything that parses
+// must survive a serialize/deserialize round trip.
+fuzz_target!(|data: &[u8]| {
+    let Ok(content) = std::str::from_utf8(data) else {
+        return;
+    };
+    let Ok(log) = AuthorshipLog::deserialize_from_string(content) else {
+        return;
+    };
+    let serialized = log
+        .serialize_to_string()
+        .expect("parsed log should serialize");
+    AuthorshipLog::deserialize_from_string(&serialized).expect("serialized log should parse");
+});
@@ -0,0 +1,11 @@
+#![no_main]
+
+use git_ai::commands::checkpoint_agent::agent_presets::parse_hook_input;
+use libfuzzer_sys::fuzz_target;
+
+// Agent hook payloads are read from stdin inside git hooks and must never panic
+fuzz_target!(|data: &[u8]| {
+    if let Ok(input) = std::str::from_utf8(data) {
+        let _ = parse_hook_input::<serde_json::Value>(input);
+    }
+});
@@ -0,0 +1,119 @@
+//! Detectors for agents git-ai knows about out of the box. Tool names match the ones the
+//! hook-based presets record, so a Cursor edit looks the same whether it was checkpointed via
+//! `checkpoint cursor` or `checkpoint auto`.
+
+use super::{DetectedAgent, DetectionEnv, Detector};
+
+/// Explicit handshake: an IDE plugin or wrapper script exports these before invoking git-ai
+pub const HANDSHAKE_AGENT_ENV: &str = "GIT_AI_AGENT";
+pub const HANDSHAKE_MODEL_ENV: &str = "GIT_AI_AGENT_MODEL";
+pub const HANDSHAKE_SESSION_ENV: &str = "GIT_AI_AGENT_SESSION";
+
+pub fn builtin_detectors() -> Vec<Box<dyn Detector>> {
+    vec![
+        Box::new(ClaudeCodeDetector),
+        Box::new(CursorDetector),
+        Box::new(WindsurfDetector),
+        Box::new(CopilotDetector),
+        Box::new(AiderDetector),
+    ]
+}
+
+pub struct HandshakeDetector;
+
+impl Detector for HandshakeDetector {
+    fn tool(&self) -> &str {
+        "handshake"
+    }
+
+    fn detect(&self, env: &DetectionEnv) -> Option<DetectedAgent> {
+        let tool = env.var(HANDSHAKE_AGENT_ENV)?;
+        Some(
+            DetectedAgent::new(tool)
+                .with_model(env.var(HANDSHAKE_MODEL_ENV))
+                .with_session_id(env.var(HANDSHAKE_SESSION_ENV)),
+        )
+    }
+}
+
+pub struct ClaudeCodeDetector;
+
+impl Detector for ClaudeCodeDetector {
+    fn tool(&self) -> &str {
+        "claude"
+    }
+
+    fn detect(&self, env: &DetectionEnv) -> Option<DetectedAgent> {
+        // Claude Code sets CLAUDECODE=1 in the shells it spawns
+        if env.var("CLAUDECODE").is_none() && !env.has_ancestor("claude") {
+            return None;
+        }
+        Some(DetectedAgent::new(self.tool()).with_model(env.var("ANTHROPIC_MODEL")))
+    }
+}
+
+pub struct CursorDetector;
+
+impl Detector for CursorDetector {
+    fn tool(&self) -> &str {
+        "cursor"
+    }
+
+    fn detect(&self, env: &DetectionEnv) -> Option<DetectedAgent> {
+        let session = env.var("CURSOR_TRACE_ID");
+        if session.is_none()
+            && env.var("CURSOR_AGENT").is_none()
+            && !env.has_ancestor_prefix("cursor")
+        {
+            return None;
+        }
+        Some(DetectedAgent::new(self.tool()).with_session_id(session))
+    }
+}
+
+pub struct WindsurfDetector;
+
+impl Detector for WindsurfDetector {
+    fn tool(&self) -> &str {
+        "windsurf"
+    }
+
+    fn detect(&self, env: &DetectionEnv) -> Option<DetectedAgent> {
+        if !env.has_ancestor_prefix("windsurf") {
+            return None;
+        }
+        Some(DetectedAgent::new(self.tool()))
+    }
+}
+
+pub struct CopilotDetector;
+
+impl Detector for CopilotDetector {
+    fn tool(&self) -> &str {
+        "github-copilot"
+    }
+
+    fn detect(&self, env: &DetectionEnv) -> Option<DetectedAgent> {
+        // Covers the Copilot CLI and the language server (truncated to "copilot-languag" in
+        // Linux's 15-character process names)
+        if !env.has_ancestor_prefix("copilot") {
+            return None;
+        }
+        Some(DetectedAgent::new(self.tool()))
+    }
+}
+
+pub struct AiderDetector;
+
+impl Detector for AiderDetector {
+    fn tool(&self) -> &str {
+        "aider"
+    }
+
+    fn detect(&self, env: &DetectionEnv) -> Option<DetectedAgent> {
+        if !env.has_ancestor("aider") {
+            return None;
+        }
+        Some(DetectedAgent::new(self.tool()).with_model(env.var("AIDER_MODEL")))
+    }
+}
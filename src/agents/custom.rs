@@ -0,0 +1,79 @@
+//! Detectors declared in the `agent_detectors` config key, for agents git-ai has no built-in
+//! support for.
+
+use super::{DetectedAgent, DetectionEnv, Detector};
+use crate::config::AgentDetectorConfig;
+
+pub struct CustomDetector {
+    config: AgentDetectorConfig,
+}
+
+impl CustomDetector {
+    pub fn new(config: AgentDetectorConfig) -> Self {
+        Self { config }
+    }
+
+    fn env_matches(&self, env: &DetectionEnv) -> bool {
+        let Some(name) = &self.config.env_var else {
+            return false;
+        };
+        match (env.var(name), &self.config.env_value) {
+            (Some(actual), Some(expected)) => actual == expected,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+}
+
+impl Detector for CustomDetector {
+    fn tool(&self) -> &str {
+        &self.config.tool
+    }
+
+    fn detect(&self, env: &DetectionEnv) -> Option<DetectedAgent> {
+        let process_matches = self
+            .config
+            .process_name
+            .as_deref()
+            .is_some_and(|name| env.has_ancestor(name));
+        if !self.env_matches(env) && !process_matches {
+            return None;
+        }
+
+        let model = self
+            .config
+            .model_env
+            .as_deref()
+            .and_then(|name| env.var(name))
+            .or(self.config.model.as_deref());
+        Some(DetectedAgent::new(&self.config.tool).with_model(model))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_env_value_must_match_when_given() {
+        let detector = CustomDetector::new(AgentDetectorConfig {
+            tool: "zed".to_string(),
+            env_var: Some("TERM_PROGRAM".to_string()),
+            env_value: Some("zed".to_string()),
+            model: Some("zeta".to_string()),
+            ..Default::default()
+        });
+        let env_with = |value: &str| {
+            DetectionEnv::from_parts(
+                HashMap::from([("TERM_PROGRAM".to_string(), value.to_string())]),
+                vec![],
+            )
+        };
+
+        let detected = detector.detect(&env_with("zed")).unwrap();
+        assert_eq!(detected.tool, "zed");
+        assert_eq!(detected.model.as_deref(), Some("zeta"));
+        assert_eq!(detector.detect(&env_with("vscode")), None);
+    }
+}
@@ -0,0 +1,318 @@
+//! Detection of the AI agent driving the current git-ai invocation.
+//!
+//! Agents with hook integrations (`checkpoint claude`, `checkpoint cursor`, ...) identify
+//! themselves. `checkpoint auto` is for everything else: it asks a registry of detectors, each
+//! of which looks at the environment and the ancestor process chain, and records whichever
+//! agent matches first. Detectors normalise the tool and model names so that notes and metrics
+//! see the same values no matter how the agent was found.
+
+pub mod builtin;
+pub mod custom;
+
+use crate::authorship::working_log::AgentId;
+use crate::config::{AgentDetectorConfig, Config};
+use crate::utils::debug_log;
+use std::cell::OnceCell;
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Parent processes walked when looking for an agent's process
+const MAX_ANCESTOR_DEPTH: usize = 16;
+
+/// Recognises one agent from the environment git-ai was launched in
+pub trait Detector: Send + Sync {
+    /// Tool name recorded for this agent in notes and metrics
+    fn tool(&self) -> &str;
+
+    fn detect(&self, env: &DetectionEnv) -> Option<DetectedAgent>;
+}
+
+/// Snapshot of what detectors may inspect: environment variables and the names of ancestor
+/// processes (nearest first), which are only looked up if a detector asks for them
+pub struct DetectionEnv {
+    vars: HashMap<String, String>,
+    ancestors: OnceCell<Vec<String>>,
+}
+
+impl DetectionEnv {
+    pub fn current() -> Self {
+        Self {
+            vars: std::env::vars().collect(),
+            ancestors: OnceCell::new(),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn from_parts(vars: HashMap<String, String>, ancestors: Vec<String>) -> Self {
+        Self {
+            vars,
+            ancestors: OnceCell::from(ancestors),
+        }
+    }
+
+    /// Value of a non-empty environment variable
+    pub fn var(&self, name: &str) -> Option<&str> {
+        self.vars
+            .get(name)
+            .map(|v| v.trim())
+            .filter(|v| !v.is_empty())
+    }
+
+    pub fn ancestors(&self) -> &[String] {
+        self.ancestors.get_or_init(ancestor_process_names)
+    }
+
+    /// True if an ancestor process is named `name`, ignoring case and any `.exe` suffix
+    pub fn has_ancestor(&self, name: &str) -> bool {
+        self.ancestors()
+            .iter()
+            .any(|ancestor| process_name_matches(ancestor, name))
+    }
+
+    /// True if an ancestor process name starts with `prefix`, ignoring case. Electron apps
+    /// run their extension hosts as e.g. "Cursor Helper (Plugin)".
+    pub fn has_ancestor_prefix(&self, prefix: &str) -> bool {
+        let prefix = prefix.to_lowercase();
+        self.ancestors()
+            .iter()
+            .any(|ancestor| base_process_name(ancestor).starts_with(&prefix))
+    }
+}
+
+/// An agent found by a detector
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DetectedAgent {
+    pub tool: String,
+    pub model: Option<String>,
+    pub session_id: Option<String>,
+}
+
+impl DetectedAgent {
+    pub fn new(tool: &str) -> Self {
+        Self {
+            tool: normalize_name(tool),
+            model: None,
+            session_id: None,
+        }
+    }
+
+    pub fn with_model(mut self, model: Option<&str>) -> Self {
+        self.model = model
+            .map(normalize_name)
+            .filter(|m| !m.is_empty() && m != "unknown");
+        self
+    }
+
+    pub fn with_session_id(mut self, session_id: Option<&str>) -> Self {
+        self.session_id = session_id.map(str::to_string);
+        self
+    }
+
+    /// Agent identity for checkpoints; model falls back to "unknown" like the other presets
+    pub fn to_agent_id(&self) -> AgentId {
+        AgentId {
+            tool: self.tool.clone(),
+            id: self.session_id.clone().unwrap_or_else(|| {
+                format!(
+                    "{}-{}",
+                    self.tool,
+                    SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .map(|d| d.as_nanos())
+                        .unwrap_or(0)
+                )
+            }),
+            model: self.model.clone().unwrap_or_else(|| "unknown".to_string()),
+        }
+    }
+}
+
+/// Ordered list of detectors; the first match wins
+pub struct DetectorRegistry {
+    detectors: Vec<Box<dyn Detector>>,
+}
+
+impl DetectorRegistry {
+    pub fn empty() -> Self {
+        Self {
+            detectors: Vec::new(),
+        }
+    }
+
+    /// Built-ins plus detectors from the config file. Custom detectors are consulted after the
+    /// handshake but before the built-ins, so they can override how a known agent is reported.
+    pub fn from_config(config: &Config) -> Self {
+        Self::with_custom(config.agent_detectors())
+    }
+
+    /// The explicit handshake, then `custom` in order, then the built-in agents
+    pub fn with_custom(custom: &[AgentDetectorConfig]) -> Self {
+        let mut registry = Self::empty();
+        registry.register(Box::new(builtin::HandshakeDetector));
+        for detector in custom {
+            registry.register(Box::new(custom::CustomDetector::new(detector.clone())));
+        }
+        for detector in builtin::builtin_detectors() {
+            registry.register(detector);
+        }
+        registry
+    }
+
+    pub fn register(&mut self, detector: Box<dyn Detector>) {
+        self.detectors.push(detector);
+    }
+
+    pub fn detect(&self, env: &DetectionEnv) -> Option<DetectedAgent> {
+        self.detectors.iter().find_map(|detector| {
+            let detected = detector.detect(env)?;
+            debug_log(&format!(
+                "Detected agent {} ({} detector)",
+                detected.tool,
+                detector.tool()
+            ));
+            Some(detected)
+        })
+    }
+}
+
+/// Agent for the current process, using the configured registry
+pub fn detect_current_agent() -> Option<DetectedAgent> {
+    DetectorRegistry::from_config(Config::get()).detect(&DetectionEnv::current())
+}
+
+fn normalize_name(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+fn process_name_matches(process: &str, name: &str) -> bool {
+    base_process_name(process) == name.trim().to_lowercase()
+}
+
+fn base_process_name(process: &str) -> String {
+    let process = process.trim().to_lowercase();
+    // `ps` reports a full path on some platforms
+    let process = process.rsplit('/').next().unwrap_or(&process);
+    process.strip_suffix(".exe").unwrap_or(process).to_string()
+}
+
+#[cfg(target_os = "linux")]
+fn parent_of(pid: u32) -> Option<(u32, String)> {
+    let comm = std::fs::read_to_string(format!("/proc/{}/comm", pid)).ok()?;
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // The command name in stat is parenthesised and may itself contain spaces
+    let after_name = &stat[stat.rfind(')')? + 1..];
+    let ppid = after_name.split_whitespace().nth(1)?.parse().ok()?;
+    Some((ppid, comm.trim().to_string()))
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn parent_of(pid: u32) -> Option<(u32, String)> {
+    let output = std::process::Command::new("ps")
+        .args(["-o", "ppid=", "-o", "comm=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let line = stdout.trim();
+    let (ppid, comm) = line.split_once(char::is_whitespace)?;
+    Some((ppid.trim().parse().ok()?, comm.trim().to_string()))
+}
+
+#[cfg(not(unix))]
+fn parent_of(_pid: u32) -> Option<(u32, String)> {
+    None
+}
+
+/// Names of this process's ancestors, nearest first
+fn ancestor_process_names() -> Vec<String> {
+    let mut names = Vec::new();
+    let Some((mut pid, _)) = parent_of(std::process::id()) else {
+        return names;
+    };
+    while pid > 1 && names.len() < MAX_ANCESTOR_DEPTH {
+        let Some((ppid, name)) = parent_of(pid) else {
+            break;
+        };
+        names.push(name);
+        pid = ppid;
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(vars: &[(&str, &str)], ancestors: &[&str]) -> DetectionEnv {
+        DetectionEnv::from_parts(
+            vars.iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            ancestors.iter().map(|s| s.to_string()).collect(),
+        )
+    }
+
+    #[test]
+    fn test_builtin_detectors_cover_known_agents() {
+        let registry = DetectorRegistry::with_custom(&[]);
+        let cases: &[(DetectionEnv, &str)] = &[
+            (env(&[("CLAUDECODE", "1")], &["zsh"]), "claude"),
+            (env(&[("CURSOR_TRACE_ID", "abc")], &[]), "cursor"),
+            (env(&[], &["bash", "Cursor Helper"]), "cursor"),
+            (env(&[], &["copilot-language-server"]), "github-copilot"),
+            (env(&[], &["python3", "aider"]), "aider"),
+            (env(&[], &["Windsurf.exe"]), "windsurf"),
+        ];
+        for (detection_env, tool) in cases {
+            let detected = registry.detect(detection_env);
+            assert_eq!(detected.map(|d| d.tool).as_deref(), Some(*tool));
+        }
+        assert_eq!(registry.detect(&env(&[], &["bash", "sshd"])), None);
+    }
+
+    #[test]
+    fn test_handshake_takes_precedence() {
+        let registry = DetectorRegistry::with_custom(&[]);
+        let detected = registry
+            .detect(&env(
+                &[
+                    ("CLAUDECODE", "1"),
+                    ("GIT_AI_AGENT", "My-Agent"),
+                    ("GIT_AI_AGENT_MODEL", "GPT-5"),
+                    ("GIT_AI_AGENT_SESSION", "s-1"),
+                ],
+                &[],
+            ))
+            .unwrap();
+        assert_eq!(detected.tool, "my-agent");
+        let agent_id = detected.to_agent_id();
+        assert_eq!(agent_id.model, "gpt-5");
+        assert_eq!(agent_id.id, "s-1");
+    }
+
+    #[test]
+    fn test_custom_detectors_run_before_builtins() {
+        let registry = DetectorRegistry::with_custom(&[AgentDetectorConfig {
+            tool: "cline".to_string(),
+            process_name: Some("code".to_string()),
+            model_env: Some("CLINE_MODEL".to_string()),
+            ..Default::default()
+        }]);
+        let detected = registry
+            .detect(&env(&[("CLINE_MODEL", "sonnet")], &["code", "aider"]))
+            .unwrap();
+        assert_eq!(detected.tool, "cline");
+        assert_eq!(detected.model.as_deref(), Some("sonnet"));
+        assert_eq!(
+            registry.detect(&env(&[], &["aider"])).map(|d| d.tool),
+            Some("aider".to_string())
+        );
+    }
+
+    #[test]
+    fn test_missing_model_is_recorded_as_unknown() {
+        let agent_id = DetectedAgent::new("Aider").with_model(None).to_agent_id();
+        assert_eq!(agent_id.tool, "aider");
+        assert_eq!(agent_id.model, "unknown");
+        assert!(agent_id.id.starts_with("aider-"));
+    }
+}
@@ -0,0 +1,196 @@
+//! Paths left out of attribution: `.gitaiignore` and generated-file detection.
+//!
+//! Lockfiles, vendored dependencies and build output are rewritten wholesale by tools, so
+//! whoever ran the tool would be credited with every line. Checkpoints skip these paths, notes
+//! carry no attestations for them, and stats, blame and metrics don't count them.
+//!
+//! The patterns come from three places, in increasing precedence:
+//! 1. `GENERATED_PATTERNS`, unless `detect_generated_files` is off in the config
+//! 2. `.gitattributes` entries setting `linguist-generated` or `linguist-vendored`, the way
+//!    GitHub's linguist marks generated code (unset or `=false` re-includes the path)
+//! 3. `.gitaiignore` at the repository root, in gitignore syntax, where `!pattern` re-includes
+//!
+//! They use the same matching as `git ai stats --ignore` (see `should_ignore_file`): the last
+//! matching pattern decides, so a repository can re-include a lockfile it does want counted.
+
+use crate::authorship::range_authorship::should_ignore_file;
+use crate::config::Config;
+use crate::git::repository::Repository;
+
+pub const GITAIIGNORE_FILE: &str = ".gitaiignore";
+
+/// Paths treated as generated when `detect_generated_files` is on
+pub const GENERATED_PATTERNS: &[&str] = &[
+    // Lockfiles
+    "Cargo.lock",
+    "package-lock.json",
+    "npm-shrinkwrap.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "bun.lockb",
+    "Gemfile.lock",
+    "poetry.lock",
+    "Pipfile.lock",
+    "uv.lock",
+    "composer.lock",
+    "go.sum",
+    "mix.lock",
+    "pubspec.lock",
+    "Podfile.lock",
+    "flake.lock",
+    // Vendored code
+    "node_modules/",
+    "vendor/",
+    "third_party/",
+    "bower_components/",
+    // Build output
+    "dist/",
+    "build/",
+    "target/",
+    ".next/",
+    "__pycache__/",
+    // Generated sources and minified assets
+    "*.min.js",
+    "*.min.css",
+    "*.js.map",
+    "*.pb.go",
+    "*_pb2.py",
+    "*_pb2_grpc.py",
+    "*.generated.*",
+    "*.g.dart",
+];
+
+/// The repository's attribution ignore patterns, lowest precedence first
+pub fn repo_ignore_patterns(repo: &Repository) -> Vec<String> {
+    let mut patterns = Vec::new();
+    if Config::get().detect_generated_files() {
+        patterns.extend(GENERATED_PATTERNS.iter().map(|p| p.to_string()));
+    }
+    if let Some(contents) = read_repo_file(repo, ".gitattributes") {
+        patterns.extend(parse_gitattributes(&contents));
+    }
+    if let Some(contents) = read_repo_file(repo, GITAIIGNORE_FILE) {
+        patterns.extend(parse_gitaiignore(&contents));
+    }
+    patterns
+}
+
+/// The repository's patterns followed by `extra`, so patterns passed on the command line win
+pub fn effective_ignore_patterns(repo: &Repository, extra: &[String]) -> Vec<String> {
+    let mut patterns = repo_ignore_patterns(repo);
+    patterns.extend(extra.iter().cloned());
+    patterns
+}
+
+/// Whether attribution skips `path` in this repository
+pub fn is_ignored(repo: &Repository, path: &str) -> bool {
+    should_ignore_file(path, &repo_ignore_patterns(repo))
+}
+
+/// Patterns from a `.gitaiignore` file: one per line, `#` comments and blank lines skipped
+pub fn parse_gitaiignore(contents: &str) -> Vec<String> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Patterns from `.gitattributes` lines that set or unset `linguist-generated` or
+/// `linguist-vendored`. Unset and `=false` become `!` patterns.
+pub fn parse_gitattributes(contents: &str) -> Vec<String> {
+    let mut patterns = Vec::new();
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut fields = line.split_whitespace();
+        let Some(pattern) = fields.next() else {
+            continue;
+        };
+        for attr in fields {
+            let generated = match attr {
+                "linguist-generated"
+                | "linguist-generated=true"
+                | "linguist-vendored"
+                | "linguist-vendored=true" => true,
+                "-linguist-generated"
+                | "linguist-generated=false"
+                | "-linguist-vendored"
+                | "linguist-vendored=false" => false,
+                _ => continue,
+            };
+            patterns.push(if generated {
+                pattern.to_string()
+            } else {
+                format!("!{}", pattern)
+            });
+        }
+    }
+    patterns
+}
+
+/// A file at the repository root, from the working tree or, in bare repositories, from HEAD
+fn read_repo_file(repo: &Repository, name: &str) -> Option<String> {
+    let workdir = repo.workdir().ok()?;
+    if workdir.join(".git").exists() {
+        return std::fs::read_to_string(workdir.join(name)).ok();
+    }
+    repo.git(&["cat-file", "blob", &format!("HEAD:{}", name)])
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ignored(path: &str, patterns: &[String]) -> bool {
+        should_ignore_file(path, patterns)
+    }
+
+    #[test]
+    fn test_generated_patterns() {
+        let patterns: Vec<String> = GENERATED_PATTERNS.iter().map(|p| p.to_string()).collect();
+        assert!(ignored("Cargo.lock", &patterns));
+        assert!(ignored("web/package-lock.json", &patterns));
+        assert!(ignored("vendor/github.com/pkg/errors/errors.go", &patterns));
+        assert!(ignored("web/node_modules/react/index.js", &patterns));
+        assert!(ignored("dist/app.js", &patterns));
+        assert!(ignored("api/service.pb.go", &patterns));
+        assert!(ignored("static/app.min.js", &patterns));
+        assert!(!ignored("src/main.rs", &patterns));
+        assert!(!ignored("src/vendors.rs", &patterns));
+        assert!(!ignored("Cargo.toml", &patterns));
+    }
+
+    #[test]
+    fn test_gitaiignore_negation_reincludes() {
+        let mut patterns: Vec<String> = GENERATED_PATTERNS.iter().map(|p| p.to_string()).collect();
+        patterns.extend(parse_gitaiignore(
+            "# generated clients\n\nclients/\n!Cargo.lock\n/schema.sql\n",
+        ));
+        assert!(ignored("clients/go/client.go", &patterns));
+        assert!(!ignored("Cargo.lock", &patterns));
+        assert!(ignored("schema.sql", &patterns));
+        // A leading slash anchors the pattern to the repository root
+        assert!(!ignored("db/schema.sql", &patterns));
+    }
+
+    #[test]
+    fn test_parse_gitattributes_linguist() {
+        let patterns = parse_gitattributes(
+            "*.rs text eol=lf\n\
+             gen/** linguist-generated\n\
+             docs/api.md linguist-generated=true\n\
+             deps/** linguist-vendored\n\
+             dist/** -linguist-generated\n",
+        );
+        assert_eq!(
+            patterns,
+            vec!["gen/**", "docs/api.md", "deps/**", "!dist/**"]
+        );
+        assert!(ignored("gen/models/user.ts", &patterns));
+        assert!(!ignored("src/models/user.ts", &patterns));
+    }
+}
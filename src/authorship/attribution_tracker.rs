@@ -3,6 +3,7 @@
 //! This library maintains attribution ranges as files are edited, preserving
 //! authorship information even through moves, edits, and whitespace changes.
 
+use crate::authorship::authorship_log::CharRange;
 use crate::authorship::imara_diff_utils::{ByteDiff, ByteDiffOp, DiffOp, capture_diff_slices};
 use crate::authorship::move_detection::{DeletedLine, InsertedLine, detect_moves};
 use crate::authorship::working_log::CheckpointKind;
@@ -1719,13 +1720,33 @@ pub fn attributions_to_line_attributions(
         return Vec::new();
     }
 
-    // For each line, determine the dominant author
+    // For each line, determine the dominant author. Lines and attributions are swept in order
+    // of position so each line only looks at the attributions that can overlap it, which keeps
+    // files with tens of thousands of lines and attributions linear rather than quadratic.
+    let mut by_start: Vec<usize> = (0..attributions.len()).collect();
+    by_start.sort_by_key(|&idx| attributions[idx].start);
+    let mut next = 0;
+    let mut active: Vec<usize> = Vec::new();
+
     let mut line_authors: Vec<Option<(String, Option<String>)>> =
         Vec::with_capacity(line_count as usize);
 
     for line_num in 1..=line_count {
+        let (line_start, line_end) = boundaries.get_line_range(line_num).unwrap();
+        while next < by_start.len() && attributions[by_start[next]].start < line_end {
+            active.push(by_start[next]);
+            next += 1;
+        }
+        active.retain(|&idx| attributions[idx].end > line_start);
+
+        // Candidates keep their original order, which breaks ties between authors
+        let mut overlapping = active.clone();
+        overlapping.sort_unstable();
+        let overlapping: Vec<&Attribution> =
+            overlapping.into_iter().map(|idx| &attributions[idx]).collect();
+
         let (author, overrode) =
-            find_dominant_author_for_line(line_num, &boundaries, attributions, content);
+            find_dominant_author_for_line(line_num, &boundaries, &overlapping, content);
         line_authors.push(Some((author, overrode)));
     }
 
@@ -1743,15 +1764,15 @@ pub fn attributions_to_line_attributions(
 fn find_dominant_author_for_line(
     line_num: u32,
     boundaries: &LineBoundaries,
-    attributions: &Vec<Attribution>,
+    attributions: &[&Attribution],
     full_content: &str,
 ) -> (String, Option<String>) {
     let (line_start, line_end) = boundaries.get_line_range(line_num).unwrap();
     let line_content = &full_content[line_start..line_end];
     let is_line_empty = line_content.is_empty() || line_content.chars().all(|c| c.is_whitespace());
 
-    let mut candidate_attrs = Vec::new();
-    for attribution in attributions {
+    let mut candidate_attrs: Vec<&Attribution> = Vec::new();
+    for &attribution in attributions {
         if !attribution.overlaps(line_start, line_end) {
             continue;
         }
@@ -1782,7 +1803,7 @@ fn find_dominant_author_for_line(
         // deleted content at this position, so they should influence line attribution
         let is_deletion_marker = attribution.start == attribution.end;
         if attr_non_whitespace_count > 0 || is_line_empty || is_deletion_marker {
-            candidate_attrs.push(attribution.clone());
+            candidate_attrs.push(attribution);
         } else {
             // If the attribution is only whitespace, discard it
             continue;
@@ -1884,6 +1905,71 @@ fn merge_consecutive_line_attributions(
 
     result
 }
+/// Character spans `author_id` wrote on each of `line_nums` (1-indexed), for lines they only
+/// partly own. Each character goes to the covering attribution with the latest timestamp and
+/// uncovered text counts as human, matching `find_dominant_author_for_line`. Lines where the
+/// author wrote all of the non-whitespace text, or none of it, yield no spans since whole-line
+/// attribution is already exact for them.
+pub fn partial_line_char_ranges(
+    attributions: &[Attribution],
+    content: &str,
+    line_nums: &[u32],
+    author_id: &str,
+) -> Vec<CharRange> {
+    let boundaries = LineBoundaries::new(content);
+    let mut result = Vec::new();
+
+    for &line_num in line_nums {
+        let Some((line_start, line_end)) = boundaries.get_line_range(line_num) else {
+            continue;
+        };
+        let line_content = content[line_start..line_end].trim_end_matches(['\n', '\r']);
+        let line_attrs: Vec<&Attribution> = attributions
+            .iter()
+            .filter(|a| a.start < a.end && a.overlaps(line_start, line_start + line_content.len()))
+            .collect();
+
+        let mut spans: Vec<(u32, u32, bool)> = Vec::new();
+        let mut wrote_some = false;
+        let mut wrote_all = true;
+        for (col, (offset, ch)) in line_content.char_indices().enumerate() {
+            let pos = line_start + offset;
+            let owned = line_attrs
+                .iter()
+                .filter(|a| a.start <= pos && pos < a.end)
+                .max_by_key(|a| a.ts)
+                .is_some_and(|a| a.author_id == author_id);
+            if !ch.is_whitespace() {
+                wrote_some |= owned;
+                wrote_all &= owned;
+            }
+            if !owned {
+                continue;
+            }
+            let col = col as u32;
+            match spans.last_mut() {
+                Some((_, end, has_text)) if *end == col => {
+                    *end = col + 1;
+                    *has_text |= !ch.is_whitespace();
+                }
+                _ => spans.push((col, col + 1, !ch.is_whitespace())),
+            }
+        }
+
+        if wrote_some && !wrote_all {
+            result.extend(spans.into_iter().filter(|(_, _, has_text)| *has_text).map(
+                |(start, end, _)| CharRange {
+                    line: line_num,
+                    start,
+                    end,
+                },
+            ));
+        }
+    }
+
+    result
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2222,4 +2308,65 @@ mod tests {
         assert_eq!(ai_block.start_line, 2);
         assert_eq!(ai_block.end_line, 17);
     }
+
+    #[test]
+    fn test_partial_line_char_ranges_after_human_edit() {
+        let tracker = AttributionTracker::new();
+        let old = "fn main() {\n    let total = compute(a, b);\n}\n";
+        let new = "fn main() {\n    let total = sum_all(a, b);\n}\n";
+        let ai_attrs = vec![Attribution::new(0, old.len(), "ai".into(), TEST_TS)];
+        let updated = tracker
+            .update_attributions(old, new, &ai_attrs, "human", TEST_TS + 1)
+            .unwrap();
+
+        let ranges = partial_line_char_ranges(&updated, new, &[1, 2, 3], "ai");
+        assert!(ranges.iter().all(|r| r.line == 2), "{:?}", ranges);
+        assert!(!ranges.is_empty());
+
+        // Everything the human typed is left out; the rest of the line is still AI
+        let edited_start = "    let total = ".chars().count() as u32;
+        let edited_end = edited_start + "sum_all".chars().count() as u32;
+        for range in &ranges {
+            assert!(
+                range.end <= edited_start || range.start >= edited_end,
+                "{:?} overlaps the human edit",
+                range
+            );
+        }
+        assert_eq!(ranges.first().unwrap().start, 0);
+        assert_eq!(
+            ranges.last().unwrap().end,
+            "    let total = sum_all(a, b);".chars().count() as u32
+        );
+    }
+
+    #[test]
+    fn test_partial_line_char_ranges_skips_whole_lines() {
+        let content = "ai line\nhuman line\n";
+        let attrs = vec![
+            Attribution::new(0, 8, "ai".into(), TEST_TS),
+            Attribution::new(8, 19, "human".into(), TEST_TS),
+        ];
+        assert!(partial_line_char_ranges(&attrs, content, &[1, 2], "ai").is_empty());
+    }
+
+    #[test]
+    fn test_partial_line_char_ranges_uses_char_columns_and_latest_ts() {
+        // "é" is two bytes; columns must count it once
+        let content = "é = ai_value + human\n";
+        let human_start = content.find("human").unwrap();
+        let attrs = vec![
+            Attribution::new(0, content.len(), "ai".into(), TEST_TS),
+            Attribution::new(human_start, human_start + 5, "human".into(), TEST_TS + 1),
+        ];
+        let ranges = partial_line_char_ranges(&attrs, content, &[1], "ai");
+        assert_eq!(
+            ranges,
+            vec![CharRange {
+                line: 1,
+                start: 0,
+                end: 15
+            }]
+        );
+    }
 }
@@ -1,6 +1,8 @@
+use crate::authorship::token_usage::TokenUsage;
 use crate::authorship::transcript::Message;
 use crate::authorship::working_log::AgentId;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt;
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -184,6 +186,71 @@ impl fmt::Display for LineRange {
     }
 }
 
+/// Character span within a single line, for lines only partly written by a prompt.
+/// Columns are 0-based Unicode characters and `end` is exclusive. Serialized as `"line:start-end"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CharRange {
+    pub line: u32,
+    pub start: u32,
+    pub end: u32,
+}
+
+impl fmt::Display for CharRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}-{}", self.line, self.start, self.end)
+    }
+}
+
+impl std::str::FromStr for CharRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid character range: {}", s);
+        let (line, cols) = s.split_once(':').ok_or_else(invalid)?;
+        let (start, end) = cols.split_once('-').ok_or_else(invalid)?;
+        let range = CharRange {
+            line: line.parse().map_err(|_| invalid())?,
+            start: start.parse().map_err(|_| invalid())?,
+            end: end.parse().map_err(|_| invalid())?,
+        };
+        if range.line == 0 || range.start >= range.end {
+            return Err(invalid());
+        }
+        Ok(range)
+    }
+}
+
+impl Serialize for CharRange {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for CharRange {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// AI lines a revert commit removed, recorded in the revert's note
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RevertedLines {
+    /// The commit that was reverted
+    pub commit: String,
+    pub ai_lines: u32,
+    /// Per tool and model, largest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub agents: Vec<RevertedAgentLines>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RevertedAgentLines {
+    pub tool: String,
+    pub model: String,
+    pub lines: u32,
+}
+
 /// Prompt session details stored in the top-level prompts map keyed by short hash (agent_id + tool)
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PromptRecord {
@@ -196,11 +263,27 @@ pub struct PromptRecord {
     pub total_deletions: u32,
     #[serde(default)]
     pub accepted_lines: u32,
-    #[serde(default)]
+    /// Spelled as shipped in 3.0.0 (spec erratum E-001); notes using the corrected name still read
+    #[serde(default, alias = "overridden_lines")]
     pub overriden_lines: u32,
     /// Full URL to CAS-stored messages (format: {api_base_url}/cas/{hash})
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub messages_url: Option<String>,
+    /// Tokens and cost the session spent while working on this commit, when the agent exposes them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_usage: Option<TokenUsage>,
+    /// The session's user prompts whose edits this commit kept, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub turns: Vec<PromptTurn>,
+}
+
+/// One user prompt of a session and the committed lines its edits produced
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PromptTurn {
+    /// Short hash of the prompt's text; the text is in `messages` when the note keeps them
+    pub prompt_hash: String,
+    /// File path -> lines, formatted like attestation ranges (`"1-3,7"`)
+    pub files: BTreeMap<String, String>,
 }
 
 impl Eq for PromptRecord {}
@@ -254,6 +337,8 @@ mod tests {
             accepted_lines: 0,
             overriden_lines: 0,
             messages_url: None,
+            token_usage: None,
+            turns: Vec::new(),
         }
     }
 
@@ -281,4 +366,30 @@ mod tests {
                 || records[1].total_deletions > 0
         );
     }
+
+    #[test]
+    fn test_char_range_roundtrip() {
+        let range = CharRange {
+            line: 12,
+            start: 4,
+            end: 19,
+        };
+        assert_eq!(range.to_string(), "12:4-19");
+        assert_eq!("12:4-19".parse::<CharRange>().unwrap(), range);
+
+        let json = serde_json::to_string(&range).unwrap();
+        assert_eq!(json, "\"12:4-19\"");
+        assert_eq!(serde_json::from_str::<CharRange>(&json).unwrap(), range);
+    }
+
+    #[test]
+    fn test_char_range_rejects_malformed() {
+        for bad in ["", "3", "3:4", "0:1-2", "3:5-5", "3:6-2", "a:1-2", "3:1-x"] {
+            assert!(
+                bad.parse::<CharRange>().is_err(),
+                "{} should not parse",
+                bad
+            );
+        }
+    }
 }
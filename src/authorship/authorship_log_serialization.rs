@@ -1,4 +1,6 @@
-use crate::authorship::authorship_log::{Author, LineRange, PromptRecord};
+use crate::authorship::authorship_log::{
+    Author, CharRange, LineRange, PromptRecord, RevertedLines,
+};
 use crate::authorship::working_log::CheckpointKind;
 use crate::git::repository::Repository;
 use serde::{Deserialize, Serialize};
@@ -11,6 +13,11 @@ use std::time::{SystemTime, UNIX_EPOCH};
 /// Authorship log format version identifier
 pub const AUTHORSHIP_LOG_VERSION: &str = "authorship/3.0.0";
 
+/// Notes arrive from arbitrary remotes, so anything past these bounds is rejected as malformed
+/// rather than parsed. Real notes are orders of magnitude smaller.
+pub const MAX_AUTHORSHIP_NOTE_BYTES: usize = 16 * 1024 * 1024;
+pub const MAX_ATTESTED_LINE_NUMBER: u32 = 10_000_000;
+
 #[cfg(all(debug_assertions, test))]
 pub const GIT_AI_VERSION: &str = "development";
 
@@ -23,10 +30,20 @@ pub const GIT_AI_VERSION: &str = env!("CARGO_PKG_VERSION");
 /// Metadata section that goes below the divider as JSON
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AuthorshipMetadata {
+    /// Empty for notes written before the field existed; see `note_schema` for what is readable
+    #[serde(default)]
     pub schema_version: String,
     pub git_ai_version: Option<String>,
     pub base_commit_sha: String,
     pub prompts: BTreeMap<String, PromptRecord>,
+    /// file path -> prompt hash -> character spans that prompt wrote on lines a human also edited.
+    /// Each span refines a line the same prompt attests above the divider; attested lines without
+    /// spans are wholly AI. Omitted when empty, so line-only notes read the same in both directions.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub partial_lines: BTreeMap<String, BTreeMap<String, Vec<CharRange>>>,
+    /// Set on revert commits: the AI lines the reverted commit added that this commit removed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reverted: Option<RevertedLines>,
 }
 
 impl AuthorshipMetadata {
@@ -36,6 +53,8 @@ impl AuthorshipMetadata {
             git_ai_version: Some(GIT_AI_VERSION.to_string()),
             base_commit_sha: String::new(),
             prompts: BTreeMap::new(),
+            partial_lines: BTreeMap::new(),
+            reverted: None,
         }
     }
 }
@@ -179,12 +198,48 @@ impl AuthorshipLog {
         output.push_str("---\n");
 
         // Write JSON metadata section
-        let json_str = serde_json::to_string_pretty(&self.metadata).map_err(|_| fmt::Error)?;
+        let json_str = if self.metadata.partial_lines.is_empty() {
+            serde_json::to_string_pretty(&self.metadata)
+        } else {
+            let mut metadata = self.metadata.clone();
+            metadata.partial_lines = self.attested_partial_lines();
+            serde_json::to_string_pretty(&metadata)
+        }
+        .map_err(|_| fmt::Error)?;
         output.push_str(&json_str);
 
         Ok(output)
     }
 
+    /// Character spans whose line is still attested to the same prompt. Attestations get trimmed
+    /// and remapped after the spans are computed, so stale spans are dropped rather than written.
+    fn attested_partial_lines(&self) -> BTreeMap<String, BTreeMap<String, Vec<CharRange>>> {
+        let mut result = BTreeMap::new();
+        for (file_path, by_hash) in &self.metadata.partial_lines {
+            let Some(file) = self.attestations.iter().find(|f| &f.file_path == file_path) else {
+                continue;
+            };
+            let mut kept_by_hash = BTreeMap::new();
+            for (hash, ranges) in by_hash {
+                let Some(entry) = file.entries.iter().find(|e| &e.hash == hash) else {
+                    continue;
+                };
+                let kept: Vec<CharRange> = ranges
+                    .iter()
+                    .filter(|r| entry.line_ranges.iter().any(|lr| lr.contains(r.line)))
+                    .copied()
+                    .collect();
+                if !kept.is_empty() {
+                    kept_by_hash.insert(hash.clone(), kept);
+                }
+            }
+            if !kept_by_hash.is_empty() {
+                result.insert(file_path.clone(), kept_by_hash);
+            }
+        }
+        result
+    }
+
     /// Write to a writer in the new format
     pub fn _serialize_to_writer<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
         let content = self
@@ -196,6 +251,15 @@ impl AuthorshipLog {
 
     /// Deserialize from the new text format
     pub fn deserialize_from_string(content: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        if content.len() > MAX_AUTHORSHIP_NOTE_BYTES {
+            return Err(format!(
+                "Authorship log is {} bytes, exceeding the {} byte limit",
+                content.len(),
+                MAX_AUTHORSHIP_NOTE_BYTES
+            )
+            .into());
+        }
+
         let lines: Vec<&str> = content.lines().collect();
 
         // Find the divider
@@ -504,7 +568,7 @@ impl Default for AuthorshipLog {
 
 /// Format line ranges as comma-separated values with ranges as "start-end"
 /// Sorts ranges first: Single ranges by their value, Range ones by their lowest bound
-fn format_line_ranges(ranges: &[LineRange]) -> String {
+pub(crate) fn format_line_ranges(ranges: &[LineRange]) -> String {
     let mut sorted_ranges = ranges.to_vec();
     sorted_ranges.sort_by(|a, b| {
         let a_start = match a {
@@ -530,7 +594,7 @@ fn format_line_ranges(ranges: &[LineRange]) -> String {
 
 /// Parse line ranges from a string like "1,2,19-222"
 /// No spaces are expected in the format
-fn parse_line_ranges(input: &str) -> Result<Vec<LineRange>, Box<dyn std::error::Error>> {
+pub(crate) fn parse_line_ranges(input: &str) -> Result<Vec<LineRange>, Box<dyn std::error::Error>> {
     let mut ranges = Vec::new();
 
     for part in input.split(',') {
@@ -542,12 +606,15 @@ fn parse_line_ranges(input: &str) -> Result<Vec<LineRange>, Box<dyn std::error::
             // Range format: "start-end"
             let start_str = &part[..dash_pos];
             let end_str = &part[dash_pos + 1..];
-            let start: u32 = start_str.parse()?;
-            let end: u32 = end_str.parse()?;
+            let start = parse_line_number(start_str)?;
+            let end = parse_line_number(end_str)?;
+            if start > end {
+                return Err(format!("Invalid line range: {}", part).into());
+            }
             ranges.push(LineRange::Range(start, end));
         } else {
             // Single line format: "line"
-            let line: u32 = part.parse()?;
+            let line = parse_line_number(part)?;
             ranges.push(LineRange::Single(line));
         }
     }
@@ -555,6 +622,15 @@ fn parse_line_ranges(input: &str) -> Result<Vec<LineRange>, Box<dyn std::error::
     Ok(ranges)
 }
 
+/// Parse a 1-based line number, rejecting 0 and values no real file reaches
+fn parse_line_number(input: &str) -> Result<u32, Box<dyn std::error::Error>> {
+    let line: u32 = input.parse()?;
+    if line == 0 || line > MAX_ATTESTED_LINE_NUMBER {
+        return Err(format!("Line number out of range: {}", line).into());
+    }
+    Ok(line)
+}
+
 /// Parse the attestation section (before the divider)
 fn parse_attestation_section(
     lines: &[&str],
@@ -578,6 +654,11 @@ fn parse_attestation_section(
                 let hash = entry_line[..space_pos].to_string();
                 let ranges_str = &entry_line[space_pos + 1..];
                 let line_ranges = parse_line_ranges(ranges_str)?;
+                if line_ranges.is_empty() {
+                    return Err(
+                        format!("Attestation entry has no line ranges: {}", entry_line).into(),
+                    );
+                }
 
                 let entry = AttestationEntry::new(hash, line_ranges);
 
@@ -598,13 +679,16 @@ fn parse_attestation_section(
             }
 
             // Parse file path, handling quoted paths
-            let file_path = if line.starts_with('"') && line.ends_with('"') {
+            let file_path = if line.len() >= 2 && line.starts_with('"') && line.ends_with('"') {
                 // Quoted path - remove quotes (no unescaping needed since quotes aren't allowed in file names)
                 line[1..line.len() - 1].to_string()
             } else {
                 // Unquoted path
                 line.to_string()
             };
+            if file_path.is_empty() {
+                return Err("Empty file path in attestation section".into());
+            }
 
             current_file = Some(FileAttestation::new(file_path));
         }
@@ -635,6 +719,13 @@ pub fn generate_short_hash(agent_id: &str, tool: &str) -> String {
     format!("{:x}", result)[..16].to_string()
 }
 
+/// Generate a short hash of a user prompt's text, the same length as session hashes
+pub fn generate_prompt_hash(prompt: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(prompt.as_bytes());
+    format!("{:x}", hasher.finalize())[..16].to_string()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -699,6 +790,24 @@ mod tests {
         assert_debug_snapshot!(deserialized);
     }
 
+    #[test]
+    fn test_deserialize_crlf_line_endings() {
+        let mut log = AuthorshipLog::new();
+        log.metadata.base_commit_sha = "abc123".to_string();
+        let mut file = FileAttestation::new("src/file.xyz".to_string());
+        file.add_entry(AttestationEntry::new(
+            "xyzAbc".to_string(),
+            vec![LineRange::Single(1), LineRange::Range(3, 5)],
+        ));
+        log.attestations.push(file);
+
+        // A note checked out or edited with core.autocrlf on Windows
+        let serialized = log.serialize_to_string().unwrap().replace('\n', "\r\n");
+        let deserialized = AuthorshipLog::deserialize_from_string(&serialized).unwrap();
+        assert_eq!(deserialized.attestations, log.attestations);
+        assert_eq!(deserialized.metadata.base_commit_sha, "abc123");
+    }
+
     #[test]
     fn test_expected_format() {
         let mut log = AuthorshipLog::new();
@@ -775,6 +884,8 @@ mod tests {
                 accepted_lines: 0,
                 overriden_lines: 0,
                 messages_url: None,
+                token_usage: None,
+                turns: Vec::new(),
             },
         );
 
@@ -842,6 +953,8 @@ mod tests {
                 accepted_lines: 0,
                 overriden_lines: 0,
                 messages_url: None,
+                token_usage: None,
+                turns: Vec::new(),
             },
         );
 
@@ -891,6 +1004,8 @@ mod tests {
                 accepted_lines: 0,
                 overriden_lines: 0,
                 messages_url: None,
+                token_usage: None,
+                turns: Vec::new(),
             },
         );
 
@@ -908,6 +1023,70 @@ mod tests {
         assert_eq!(deserialized.attestations.len(), 0);
     }
 
+    #[test]
+    fn test_malformed_notes_error_instead_of_panicking() {
+        let metadata = r#"{"schema_version":"authorship/3.0.0","base_commit_sha":"","prompts":{}}"#;
+        let cases = [
+            "\"\n  abc 1\n---\n".to_string(),
+            format!("file.rs\n  abc 0\n---\n{}", metadata),
+            format!("file.rs\n  abc 9-3\n---\n{}", metadata),
+            format!("file.rs\n  abc 1-4294967295\n---\n{}", metadata),
+            format!("file.rs\n  abc 1--2\n---\n{}", metadata),
+            format!("file.rs\n  abc ,\n---\n{}", metadata),
+            format!("\"\"\n  abc 1\n---\n{}", metadata),
+            format!("  abc 1\n---\n{}", metadata),
+            format!("file.rs\n---\n{}{}", "[".repeat(10_000), "]".repeat(10_000)),
+            "no divider".to_string(),
+        ];
+        for case in &cases {
+            assert!(
+                AuthorshipLog::deserialize_from_string(case).is_err(),
+                "expected error for {:?}",
+                case
+            );
+        }
+
+        let oversized = format!(
+            "file.rs\n---\n{}{}",
+            metadata,
+            " ".repeat(MAX_AUTHORSHIP_NOTE_BYTES)
+        );
+        assert!(AuthorshipLog::deserialize_from_string(&oversized).is_err());
+    }
+
+    #[test]
+    fn test_mutated_notes_never_panic() {
+        use rand::{Rng, SeedableRng, rngs::StdRng};
+
+        let mut log = AuthorshipLog::new();
+        log.metadata.base_commit_sha = "abc123".to_string();
+        log.get_or_create_file("src/my file.rs")
+            .add_entry(AttestationEntry::new(
+                "abcd1234".to_string(),
+                vec![LineRange::Range(1, 10), LineRange::Single(42)],
+            ));
+        let seed = log.serialize_to_string().unwrap().into_bytes();
+        let alphabet = b"\"-,0123456789 \n{}[]:abc";
+
+        // Deterministic stand-in for the cargo-fuzz target in fuzz/, so CI catches regressions
+        let mut rng = StdRng::seed_from_u64(0x61_69);
+        for _ in 0..2_000 {
+            let mut bytes = seed.clone();
+            for _ in 0..rng.gen_range(1..8) {
+                let pos = rng.gen_range(0..bytes.len());
+                match rng.gen_range(0..3) {
+                    0 => bytes[pos] = alphabet[rng.gen_range(0..alphabet.len())],
+                    1 => {
+                        bytes.remove(pos);
+                    }
+                    _ => bytes.insert(pos, alphabet[rng.gen_range(0..alphabet.len())]),
+                }
+            }
+            let input = String::from_utf8_lossy(&bytes);
+            let _ = AuthorshipLog::deserialize_from_string(&input);
+        }
+    }
+
     #[test]
     fn test_remove_line_ranges_complete_removal() {
         let mut entry =
@@ -1070,6 +1249,8 @@ mod tests {
                 accepted_lines: 11,
                 overriden_lines: 0,
                 messages_url: None,
+                token_usage: None,
+                turns: Vec::new(),
             },
         );
 
@@ -1241,6 +1422,8 @@ mod tests {
                 accepted_lines: 10,
                 overriden_lines: 0,
                 messages_url: None,
+                token_usage: None,
+                turns: Vec::new(),
             },
         );
 
@@ -1265,6 +1448,8 @@ mod tests {
                 accepted_lines: 20,
                 overriden_lines: 0,
                 messages_url: None,
+                token_usage: None,
+                turns: Vec::new(),
             },
         );
 
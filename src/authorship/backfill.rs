@@ -0,0 +1,467 @@
+//! Best-effort authorship notes for commits made before git-ai was installed.
+//!
+//! Nothing recorded which lines an agent wrote back then, so a commit is judged as a whole:
+//! when an imported mapping, a known agent author, a `Co-authored-by` trailer naming an agent
+//! or an agent's commit message footer says AI was involved, every line the commit added is
+//! attributed to that agent. Other commits get a note attributing nothing to AI, as commits made
+//! with git-ai installed do. Commits that already have a note are never touched.
+
+use crate::authorship::authorship_log::{LineRange, PromptRecord};
+use crate::authorship::authorship_log_serialization::{
+    AttestationEntry, AuthorshipLog, generate_short_hash,
+};
+use crate::authorship::staged::EMPTY_TREE_HASH;
+use crate::authorship::working_log::AgentId;
+use crate::error::GitAiError;
+use crate::git::refs::{noted_commits, notes_add};
+use crate::git::repository::Repository;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Prefix of the agent id given to backfilled prompts, so they can be told apart later
+pub const BACKFILL_AGENT_ID_PREFIX: &str = "backfill-";
+
+const UNKNOWN_MODEL: &str = "unknown";
+
+/// Lowercased names, emails and GitHub logins agents commit or co-author as, and their tool
+const KNOWN_AGENTS: &[(&str, &str)] = &[
+    ("copilot", "github-copilot"),
+    ("copilot-swe-agent[bot]", "github-copilot"),
+    ("claude", "claude"),
+    ("claude[bot]", "claude"),
+    ("noreply@anthropic.com", "claude"),
+    ("cursor agent", "cursor"),
+    ("cursoragent@cursor.com", "cursor"),
+    ("devin-ai-integration[bot]", "devin"),
+    ("aider", "aider"),
+    ("noreply@aider.chat", "aider"),
+    ("codex", "codex"),
+    ("chatgpt-codex-connector[bot]", "codex"),
+    ("google-labs-jules[bot]", "jules"),
+    ("gemini-code-assist[bot]", "gemini"),
+    ("opencode", "opencode"),
+];
+
+/// Footers agents add to the commit messages they write
+const KNOWN_MESSAGE_MARKERS: &[(&str, &str)] = &[
+    ("generated with [claude code]", "claude"),
+    ("generated with claude code", "claude"),
+    ("generated with [opencode]", "opencode"),
+];
+
+/// Why a commit was attributed to an agent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackfillSource {
+    Mapping,
+    Author,
+    Trailer,
+    Message,
+}
+
+impl BackfillSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BackfillSource::Mapping => "mapping",
+            BackfillSource::Author => "author",
+            BackfillSource::Trailer => "trailer",
+            BackfillSource::Message => "message",
+        }
+    }
+}
+
+/// The agent a commit is attributed to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackfillAgent {
+    pub tool: String,
+    pub model: String,
+    pub source: BackfillSource,
+}
+
+/// An imported attribution; tool `human` marks a commit as written without AI
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct MappingEntry {
+    pub commit: String,
+    pub tool: String,
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BackfillOptions {
+    /// Revision whose history is backfilled, or a range
+    pub range: String,
+    /// Full commit sha -> imported attribution
+    pub mapping: HashMap<String, MappingEntry>,
+    /// Skip commits with no sign of AI instead of giving them a human-only note
+    pub ai_only: bool,
+    pub dry_run: bool,
+}
+
+/// One commit that was (or, in a dry run, would be) given a note
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackfilledCommit {
+    pub sha: String,
+    pub agent: Option<BackfillAgent>,
+    /// Lines attributed to the agent
+    pub ai_lines: u32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BackfillReport {
+    pub backfilled: Vec<BackfilledCommit>,
+    /// Commits in range that already had a note
+    pub already_noted: usize,
+    /// Commits with no sign of AI, skipped because of `ai_only`
+    pub skipped_human: usize,
+}
+
+struct HistoricalCommit {
+    sha: String,
+    parent: Option<String>,
+    author: String,
+    message: String,
+}
+
+/// Write notes for every non-merge commit in `options.range` that lacks one, oldest first
+pub fn backfill(
+    repo: &Repository,
+    options: &BackfillOptions,
+) -> Result<BackfillReport, GitAiError> {
+    let noted = noted_commits(repo)?;
+    let mut report = BackfillReport::default();
+
+    for commit in historical_commits(repo, &options.range)? {
+        if noted.contains(&commit.sha) {
+            report.already_noted += 1;
+            continue;
+        }
+        let agent = match options.mapping.get(&commit.sha) {
+            Some(entry) if entry.tool.eq_ignore_ascii_case("human") => None,
+            Some(entry) => Some(BackfillAgent {
+                tool: entry.tool.clone(),
+                model: entry
+                    .model
+                    .clone()
+                    .unwrap_or_else(|| UNKNOWN_MODEL.to_string()),
+                source: BackfillSource::Mapping,
+            }),
+            None => detect_agent(&commit.author, &commit.message),
+        };
+        if agent.is_none() && options.ai_only {
+            report.skipped_human += 1;
+            continue;
+        }
+
+        let parent = commit.parent.as_deref().unwrap_or(EMPTY_TREE_HASH);
+        let added_lines = match &agent {
+            Some(_) => repo.diff_added_lines(parent, &commit.sha, None)?,
+            None => HashMap::new(),
+        };
+        let log = backfilled_note(&commit, agent.as_ref(), &added_lines);
+        if !options.dry_run {
+            let content = log
+                .serialize_to_string()
+                .map_err(|_| GitAiError::Generic("Failed to serialize authorship log".into()))?;
+            notes_add(repo, &commit.sha, &content)?;
+        }
+        report.backfilled.push(BackfilledCommit {
+            sha: commit.sha,
+            agent,
+            ai_lines: added_lines.values().map(|lines| lines.len() as u32).sum(),
+        });
+    }
+    Ok(report)
+}
+
+/// Non-merge commits in `range` (or reachable from it), oldest first
+fn historical_commits(repo: &Repository, range: &str) -> Result<Vec<HistoricalCommit>, GitAiError> {
+    let output = repo.git(&[
+        "log",
+        "--no-merges",
+        "--reverse",
+        "--format=%H%x00%P%x00%an <%ae>%x00%B%x1e",
+        range,
+        "--",
+    ])?;
+    Ok(output
+        .split('\x1e')
+        .filter_map(|record| {
+            let mut fields = record.trim_start_matches('\n').splitn(4, '\0');
+            let sha = fields.next().filter(|sha| !sha.is_empty())?.to_string();
+            let parent = fields.next()?.split_whitespace().next().map(str::to_string);
+            let author = fields.next()?.to_string();
+            let message = fields.next().unwrap_or_default().to_string();
+            Some(HistoricalCommit {
+                sha,
+                parent,
+                author,
+                message,
+            })
+        })
+        .collect())
+}
+
+fn backfilled_note(
+    commit: &HistoricalCommit,
+    agent: Option<&BackfillAgent>,
+    added_lines: &HashMap<String, Vec<u32>>,
+) -> AuthorshipLog {
+    let mut log = AuthorshipLog::new();
+    log.metadata.base_commit_sha = commit.sha.clone();
+    let Some(agent) = agent else {
+        return log;
+    };
+
+    let agent_id = AgentId {
+        tool: agent.tool.clone(),
+        id: format!("{}{}", BACKFILL_AGENT_ID_PREFIX, commit.sha),
+        model: agent.model.clone(),
+    };
+    let hash = generate_short_hash(&agent_id.id, &agent_id.tool);
+    let mut files: Vec<_> = added_lines
+        .iter()
+        .filter(|(_, lines)| !lines.is_empty())
+        .collect();
+    files.sort_by(|a, b| a.0.cmp(b.0));
+    for (file, lines) in files {
+        log.get_or_create_file(file)
+            .add_entry(AttestationEntry::new(
+                hash.clone(),
+                LineRange::compress_lines(lines),
+            ));
+    }
+
+    let added: u32 = added_lines.values().map(|lines| lines.len() as u32).sum();
+    log.metadata.prompts.insert(
+        hash,
+        PromptRecord {
+            agent_id,
+            human_author: Some(commit.author.clone()),
+            messages: Vec::new(),
+            total_additions: added,
+            total_deletions: 0,
+            accepted_lines: added,
+            overriden_lines: 0,
+            messages_url: None,
+            token_usage: None,
+            turns: Vec::new(),
+        },
+    );
+    log
+}
+
+/// The agent a commit's author, `Co-authored-by` trailers or message footer point to
+pub fn detect_agent(author: &str, message: &str) -> Option<BackfillAgent> {
+    if let Some((tool, model)) = known_agent(author) {
+        return Some(BackfillAgent {
+            tool,
+            model,
+            source: BackfillSource::Author,
+        });
+    }
+    let trailer = message.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        key.trim()
+            .eq_ignore_ascii_case("co-authored-by")
+            .then(|| known_agent(value))
+            .flatten()
+    });
+    if let Some((tool, model)) = trailer {
+        return Some(BackfillAgent {
+            tool,
+            model,
+            source: BackfillSource::Trailer,
+        });
+    }
+    let message = message.to_lowercase();
+    KNOWN_MESSAGE_MARKERS
+        .iter()
+        .find(|(marker, _)| message.contains(marker))
+        .map(|(_, tool)| BackfillAgent {
+            tool: tool.to_string(),
+            model: UNKNOWN_MODEL.to_string(),
+            source: BackfillSource::Message,
+        })
+}
+
+/// Tool and model of a `Name <email>` identity belonging to a known agent. A parenthesized
+/// suffix is the model (`aider (gpt-4o)`), or the agent itself (`Jane Doe (aider)`).
+fn known_agent(identity: &str) -> Option<(String, String)> {
+    let identity = identity.trim().to_lowercase();
+    let (name, email) = match (identity.find('<'), identity.rfind('>')) {
+        (Some(start), Some(end)) if start < end => {
+            (identity[..start].trim(), identity[start + 1..end].trim())
+        }
+        _ => (identity.as_str(), ""),
+    };
+    let (base, suffix) = match (name.rfind('('), name.strip_suffix(')')) {
+        (Some(open), Some(inner)) => (name[..open].trim(), Some(inner[open + 1..].trim())),
+        _ => (name, None),
+    };
+    // 12345+Copilot@users.noreply.github.com
+    let login = email
+        .strip_suffix("@users.noreply.github.com")
+        .map(|local| local.rsplit('+').next().unwrap_or(local));
+
+    let tool_for = |key: &str| {
+        KNOWN_AGENTS
+            .iter()
+            .find(|(known, _)| *known == key)
+            .map(|(_, tool)| tool.to_string())
+    };
+    let model = |suffix: Option<&str>| {
+        suffix
+            .filter(|s| !s.is_empty())
+            .unwrap_or(UNKNOWN_MODEL)
+            .to_string()
+    };
+    if let Some(tool) = [Some(base), Some(email), login]
+        .into_iter()
+        .flatten()
+        .filter(|key| !key.is_empty())
+        .find_map(tool_for)
+    {
+        return Some((tool, model(suffix)));
+    }
+    suffix
+        .and_then(tool_for)
+        .map(|tool| (tool, UNKNOWN_MODEL.to_string()))
+}
+
+/// Read an attribution mapping: a JSON array of `{"commit", "tool", "model"}` objects, or CSV
+/// with `commit,tool[,model]` rows and an optional header. Commits are resolved to full shas;
+/// ones not in this repository are reported and skipped.
+pub fn load_mapping(
+    repo: &Repository,
+    path: &Path,
+) -> Result<HashMap<String, MappingEntry>, GitAiError> {
+    let content = std::fs::read_to_string(path)?;
+    let entries = if path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+    {
+        serde_json::from_str::<Vec<MappingEntry>>(&content)?
+    } else {
+        parse_mapping_csv(&content)?
+    };
+
+    let mut mapping = HashMap::new();
+    for entry in entries {
+        let resolved = repo
+            .git(&[
+                "rev-parse",
+                "--verify",
+                "--quiet",
+                &format!("{}^{{commit}}", entry.commit),
+            ])
+            .map(|sha| sha.trim().to_string());
+        match resolved {
+            Ok(sha) if !sha.is_empty() => {
+                mapping.insert(sha, entry);
+            }
+            _ => eprintln!(
+                "Warning: commit {} from the mapping is not in this repository",
+                entry.commit
+            ),
+        }
+    }
+    Ok(mapping)
+}
+
+fn parse_mapping_csv(content: &str) -> Result<Vec<MappingEntry>, GitAiError> {
+    let mut entries = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        if index == 0 && fields[0].eq_ignore_ascii_case("commit") {
+            continue;
+        }
+        let (commit, tool) = match fields[..] {
+            [commit, tool, ..] if !commit.is_empty() && !tool.is_empty() => (commit, tool),
+            _ => {
+                return Err(GitAiError::Generic(format!(
+                    "Invalid mapping line {}: expected commit,tool[,model]",
+                    index + 1
+                )));
+            }
+        };
+        entries.push(MappingEntry {
+            commit: commit.to_string(),
+            tool: tool.to_string(),
+            model: fields
+                .get(2)
+                .filter(|model| !model.is_empty())
+                .map(|model| model.to_string()),
+        });
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool(author: &str, message: &str) -> Option<(String, String, BackfillSource)> {
+        detect_agent(author, message).map(|agent| (agent.tool, agent.model, agent.source))
+    }
+
+    #[test]
+    fn test_detect_agent_from_bot_authors() {
+        assert_eq!(
+            tool(
+                "Copilot <198982749+Copilot@users.noreply.github.com>",
+                "Fix bug"
+            ),
+            Some((
+                "github-copilot".to_string(),
+                "unknown".to_string(),
+                BackfillSource::Author
+            ))
+        );
+        assert_eq!(
+            tool("Jane Doe (aider) <jane@example.com>", "Refactor").map(|t| t.0),
+            Some("aider".to_string())
+        );
+        assert_eq!(tool("Jane Doe <jane@example.com>", "Refactor"), None);
+    }
+
+    #[test]
+    fn test_detect_agent_from_trailers_and_footers() {
+        let message = "Add parser\n\nCo-authored-by: aider (gpt-4o) <noreply@aider.chat>\n";
+        assert_eq!(
+            tool("Jane Doe <jane@example.com>", message),
+            Some((
+                "aider".to_string(),
+                "gpt-4o".to_string(),
+                BackfillSource::Trailer
+            ))
+        );
+        let message = "Add parser\n\nCo-Authored-By: Claude <noreply@anthropic.com>\n";
+        assert_eq!(
+            tool("Jane Doe <jane@example.com>", message).map(|t| t.0),
+            Some("claude".to_string())
+        );
+        let message = "Add parser\n\nCo-authored-by: Bob <bob@example.com>\n";
+        assert_eq!(tool("Jane Doe <jane@example.com>", message), None);
+        let message = "Add parser\n\n🤖 Generated with [Claude Code](https://claude.com)\n";
+        assert_eq!(
+            tool("Jane Doe <jane@example.com>", message).map(|t| t.2),
+            Some(BackfillSource::Message)
+        );
+    }
+
+    #[test]
+    fn test_parse_mapping_csv() {
+        let entries =
+            parse_mapping_csv("commit,tool,model\nabc123,cursor,gpt-5\n\ndef456, human\n").unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].model.as_deref(), Some("gpt-5"));
+        assert_eq!(entries[1].tool, "human");
+        assert_eq!(entries[1].model, None);
+        assert!(parse_mapping_csv("abc123\n").is_err());
+    }
+}
@@ -0,0 +1,159 @@
+//! CODEOWNERS parsing and matching, for reporting AI authorship by owned area.
+//!
+//! Follows GitHub's rules, which GitLab shares: patterns use gitignore syntax, the last
+//! matching rule wins, and a rule with no owners leaves its paths unowned. GitLab section
+//! headers (`[Section]`) are skipped, so every rule in the file competes as one list.
+
+use crate::error::GitAiError;
+use crate::git::repository::Repository;
+use glob::{MatchOptions, Pattern};
+
+/// Where CODEOWNERS is looked up, in the order GitHub and GitLab search
+pub const CODEOWNERS_PATHS: &[&str] = &[
+    ".github/CODEOWNERS",
+    "CODEOWNERS",
+    "docs/CODEOWNERS",
+    ".gitlab/CODEOWNERS",
+];
+
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+#[derive(Debug, Clone)]
+pub struct CodeOwnersRule {
+    /// The pattern as written in the file
+    pub pattern: String,
+    pub owners: Vec<String>,
+    glob: Pattern,
+    /// Trailing `/`: matches only what's under a directory
+    dir_only: bool,
+    /// Trailing `/*`: matches a directory's files but not its subdirectories
+    direct_children_only: bool,
+}
+
+impl CodeOwnersRule {
+    fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.split_whitespace();
+        let pattern = fields.next()?.to_string();
+        let owners = fields.map(str::to_string).collect();
+
+        let mut glob = pattern.as_str();
+        let dir_only = glob.ends_with('/') && glob.len() > 1;
+        glob = glob.trim_end_matches('/');
+        // A slash anywhere but the end anchors the pattern to the repository root
+        let anchored = glob.contains('/');
+        glob = glob.trim_start_matches('/');
+        if glob.is_empty() {
+            return None;
+        }
+        let direct_children_only = anchored && glob.ends_with("/*");
+        let glob = if anchored {
+            glob.to_string()
+        } else {
+            format!("**/{}", glob)
+        };
+
+        Some(CodeOwnersRule {
+            direct_children_only,
+            owners,
+            glob: Pattern::new(&glob).ok()?,
+            dir_only,
+            pattern,
+        })
+    }
+
+    /// Whether the rule covers `path`, either directly or through a directory containing it
+    pub fn matches(&self, path: &str) -> bool {
+        if !self.dir_only && self.glob.matches_with(path, MATCH_OPTIONS) {
+            return true;
+        }
+        if self.direct_children_only {
+            return false;
+        }
+        path.match_indices('/')
+            .any(|(end, _)| self.glob.matches_with(&path[..end], MATCH_OPTIONS))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CodeOwners {
+    pub rules: Vec<CodeOwnersRule>,
+}
+
+impl CodeOwners {
+    /// Parse a CODEOWNERS file. Lines that aren't valid patterns are skipped, as GitHub does.
+    pub fn parse(contents: &str) -> Self {
+        let rules = contents
+            .lines()
+            .map(|line| line.split_once('#').map_or(line, |(rule, _)| rule).trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('[') && !line.starts_with("^["))
+            .filter_map(CodeOwnersRule::parse)
+            .collect();
+        CodeOwners { rules }
+    }
+
+    /// The first CODEOWNERS file in `CODEOWNERS_PATHS` as of `rev`, with its path
+    pub fn load(repo: &Repository, rev: &str) -> Result<Option<(String, CodeOwners)>, GitAiError> {
+        for path in CODEOWNERS_PATHS {
+            let spec = format!("{}:{}", rev, path);
+            if repo.git(&["cat-file", "-e", &spec]).is_err() {
+                continue;
+            }
+            let contents = repo.git(&["cat-file", "blob", &spec])?;
+            return Ok(Some((path.to_string(), CodeOwners::parse(&contents))));
+        }
+        Ok(None)
+    }
+
+    /// The rule that decides who owns `path`: the last one matching it
+    pub fn rule_for(&self, path: &str) -> Option<&CodeOwnersRule> {
+        self.rules.iter().rev().find(|rule| rule.matches(path))
+    }
+
+    /// Owners of `path`, empty when no rule matches or the matching rule lists no owners
+    pub fn owners_of(&self, path: &str) -> &[String] {
+        self.rule_for(path)
+            .map(|rule| rule.owners.as_slice())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codeowners_last_match_wins() {
+        let owners = CodeOwners::parse(
+            "# Default owners\n\
+             *       @org/everyone\n\
+             *.rs    @org/rust  # Rust sources\n\
+             /docs/  @org/docs\n\
+             [Frontend]\n\
+             apps/web/* @org/web\n\
+             apps/web/vendor/\n",
+        );
+        assert_eq!(owners.rules.len(), 5);
+        assert_eq!(owners.owners_of("README.md"), ["@org/everyone"]);
+        assert_eq!(owners.owners_of("src/deep/lib.rs"), ["@org/rust"]);
+        assert_eq!(owners.owners_of("docs/guide/intro.rs"), ["@org/docs"]);
+        assert_eq!(owners.owners_of("apps/web/index.ts"), ["@org/web"]);
+        // `apps/web/*` doesn't reach into subdirectories
+        assert_eq!(owners.owners_of("apps/web/src/app.ts"), ["@org/everyone"]);
+        // A rule without owners leaves its paths unowned
+        assert!(owners.owners_of("apps/web/vendor/lib.js").is_empty());
+    }
+
+    #[test]
+    fn test_codeowners_unanchored_directory_matches_at_any_depth() {
+        let owners = CodeOwners::parse("build/ @org/infra\n/root.txt @org/root\n");
+        assert_eq!(owners.owners_of("build/out.txt"), ["@org/infra"]);
+        assert_eq!(owners.owners_of("pkg/a/build/out.txt"), ["@org/infra"]);
+        assert!(owners.owners_of("build").is_empty());
+        assert_eq!(owners.owners_of("root.txt"), ["@org/root"]);
+        assert!(owners.owners_of("sub/root.txt").is_empty());
+    }
+}
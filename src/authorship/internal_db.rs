@@ -153,6 +153,8 @@ impl PromptDbRecord {
             accepted_lines: self.accepted_lines.unwrap_or(0),
             overriden_lines: self.overridden_lines.unwrap_or(0),
             messages_url: None,
+            token_usage: None,
+            turns: Vec::new(),
         }
     }
 
@@ -1010,6 +1012,12 @@ impl InternalDatabase {
 
         Ok(())
     }
+
+    /// Rebuild the database file to reclaim space freed by deletes
+    pub fn vacuum(&mut self) -> Result<(), GitAiError> {
+        self.conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE); VACUUM;")?;
+        Ok(())
+    }
 }
 
 /// Calculate next retry timestamp based on attempt number
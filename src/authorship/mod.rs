@@ -1,16 +1,27 @@
+pub mod attribution_ignore;
 pub mod attribution_tracker;
 pub mod authorship_log;
 pub mod authorship_log_serialization;
+pub mod backfill;
+pub mod codeowners;
 pub mod diff_ai_accepted;
 pub mod imara_diff_utils;
 pub mod internal_db;
 pub mod move_detection;
+pub mod note_schema;
+pub mod policy;
 pub mod post_commit;
 pub mod pre_commit;
+pub mod prompt_turns;
 pub mod range_authorship;
 pub mod rebase_authorship;
+pub mod revert;
 pub mod secrets;
+pub mod squash_merge;
+pub mod staged;
 pub mod stats;
+pub mod stats_cache;
+pub mod token_usage;
 pub mod transcript;
 pub mod virtual_attribution;
 pub mod working_log;
@@ -0,0 +1,198 @@
+//! Which authorship note schemas this build can read, and rewriting old ones to the current one.
+//!
+//! Notes are shared through `refs/notes/ai`, so one repository holds notes written by every
+//! git-ai version its contributors ran. Readers accept any note whose attestation layout they
+//! understand, which is every 3.x schema, including notes that predate the canonical
+//! `authorship/3.0.0` string or spell fields differently. `git-ai migrate-notes` rewrites those
+//! to the current schema so a later format change only has to handle one shape.
+
+use crate::authorship::authorship_log_serialization::{AUTHORSHIP_LOG_VERSION, AuthorshipLog};
+use crate::error::GitAiError;
+use crate::git::authorship_traversal::{batch_read_blobs, get_notes_list};
+use crate::git::refs::notes_add;
+use crate::git::repository::Repository;
+use serde::Serialize;
+
+/// How a note's `schema_version` relates to the one this build writes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SchemaStatus {
+    /// Exactly `AUTHORSHIP_LOG_VERSION`
+    Current,
+    /// Readable, but written before the current schema: missing or short version strings and
+    /// older 3.x releases. `migrate-notes` rewrites these.
+    Legacy,
+    /// A later 3.x release. Readable, since minor versions only add fields, but left alone
+    Newer,
+    /// A different major version, or not a version at all
+    Unsupported,
+}
+
+/// (major, minor, patch) of `authorship/<major>.<minor>.<patch>`. The prefix and trailing
+/// components may be missing, as in notes that recorded just "3".
+fn parse_version(version: &str) -> Option<(u32, u32, u32)> {
+    let number = version.strip_prefix("authorship/").unwrap_or(version);
+    let mut parts = number.split('.').map(|p| p.parse::<u32>());
+    let major = parts.next()?.ok()?;
+    let minor = parts.next().transpose().ok()?.unwrap_or(0);
+    let patch = parts.next().transpose().ok()?.unwrap_or(0);
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((major, minor, patch))
+}
+
+pub fn schema_status(version: &str) -> SchemaStatus {
+    if version == AUTHORSHIP_LOG_VERSION {
+        return SchemaStatus::Current;
+    }
+    // Notes from before the field was written
+    if version.is_empty() {
+        return SchemaStatus::Legacy;
+    }
+    let current = parse_version(AUTHORSHIP_LOG_VERSION).expect("current schema version parses");
+    match parse_version(version) {
+        Some(v) if v.0 != current.0 => SchemaStatus::Unsupported,
+        Some(v) if v > current => SchemaStatus::Newer,
+        Some(_) => SchemaStatus::Legacy,
+        None => SchemaStatus::Unsupported,
+    }
+}
+
+/// Whether notes with this `schema_version` can be read as authorship logs
+pub fn can_read(version: &str) -> bool {
+    schema_status(version) != SchemaStatus::Unsupported
+}
+
+/// The note rewritten to the current schema, or None when it needs no rewrite or can't be
+/// read safely
+pub fn migrate_note(content: &str) -> Result<Option<String>, GitAiError> {
+    let mut log = AuthorshipLog::deserialize_from_string(content)
+        .map_err(|e| GitAiError::Generic(format!("Note does not parse: {}", e)))?;
+    match schema_status(&log.metadata.schema_version) {
+        SchemaStatus::Legacy => {}
+        SchemaStatus::Current | SchemaStatus::Newer => return Ok(None),
+        SchemaStatus::Unsupported => {
+            return Err(GitAiError::Generic(format!(
+                "Unsupported schema version '{}'",
+                log.metadata.schema_version
+            )));
+        }
+    }
+    log.metadata.schema_version = AUTHORSHIP_LOG_VERSION.to_string();
+    let migrated = log
+        .serialize_to_string()
+        .map_err(|_| GitAiError::Generic("Failed to serialize authorship log".to_string()))?;
+    Ok(Some(migrated))
+}
+
+/// What happened to one note
+#[derive(Debug, Clone, Serialize)]
+pub struct NoteMigration {
+    pub commit: String,
+    /// The `schema_version` the note had, empty when it had none
+    pub from: String,
+    /// Why the note was left alone, when it couldn't be migrated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MigrationReport {
+    pub checked: usize,
+    /// Notes already at the current schema, or at a newer 3.x one
+    pub up_to_date: usize,
+    /// Notes rewritten, or that would be with `--dry-run`
+    pub migrated: Vec<NoteMigration>,
+    /// Notes that don't parse or use an unsupported schema
+    pub skipped: Vec<NoteMigration>,
+}
+
+/// Rewrite every legacy note on `refs/notes/ai` to the current schema, or with `dry_run` just
+/// report which would be
+pub fn migrate_notes(repo: &Repository, dry_run: bool) -> Result<MigrationReport, GitAiError> {
+    let global_args = repo.global_args_for_exec();
+    let (blobs, commits): (Vec<String>, Vec<String>) =
+        get_notes_list(&global_args)?.into_iter().unzip();
+    let contents = batch_read_blobs(&global_args, &blobs)?;
+
+    let mut report = MigrationReport {
+        checked: commits.len(),
+        ..MigrationReport::default()
+    };
+    for (commit, content) in commits.into_iter().zip(contents) {
+        let from = AuthorshipLog::deserialize_from_string(&content)
+            .map(|log| log.metadata.schema_version)
+            .unwrap_or_default();
+        match migrate_note(&content) {
+            Ok(None) => report.up_to_date += 1,
+            Ok(Some(migrated)) => {
+                if !dry_run {
+                    notes_add(repo, &commit, &migrated)?;
+                }
+                report.migrated.push(NoteMigration {
+                    commit,
+                    from,
+                    error: None,
+                });
+            }
+            Err(e) => report.skipped.push(NoteMigration {
+                commit,
+                from,
+                error: Some(e.to_string()),
+            }),
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_status() {
+        assert_eq!(schema_status("authorship/3.0.0"), SchemaStatus::Current);
+        assert_eq!(schema_status(""), SchemaStatus::Legacy);
+        assert_eq!(schema_status("3"), SchemaStatus::Legacy);
+        assert_eq!(schema_status("authorship/3.0"), SchemaStatus::Legacy);
+        assert_eq!(schema_status("authorship/3.1.0"), SchemaStatus::Newer);
+        assert_eq!(schema_status("authorship/2.0.0"), SchemaStatus::Unsupported);
+        assert_eq!(schema_status("authorship/4.0.0"), SchemaStatus::Unsupported);
+        assert_eq!(schema_status("authorship/v3"), SchemaStatus::Unsupported);
+        assert!(can_read("3"));
+        assert!(!can_read("authorship/4.0.0"));
+    }
+
+    #[test]
+    fn test_migrate_note_rewrites_legacy_notes_only() {
+        let legacy = r#"src/lib.rs
+  abcd1234abcd1234 1-3
+---
+{
+  "base_commit_sha": "0123456",
+  "prompts": {
+    "abcd1234abcd1234": {
+      "agent_id": {"tool": "cursor", "id": "s1", "model": "gpt-5"},
+      "human_author": null,
+      "messages": [],
+      "total_additions": 3,
+      "total_deletions": 0,
+      "accepted_lines": 3,
+      "overridden_lines": 1
+    }
+  }
+}"#;
+        let migrated = migrate_note(legacy).unwrap().unwrap();
+        let log = AuthorshipLog::deserialize_from_string(&migrated).unwrap();
+        assert_eq!(log.metadata.schema_version, AUTHORSHIP_LOG_VERSION);
+        assert_eq!(log.metadata.prompts["abcd1234abcd1234"].overriden_lines, 1);
+        assert_eq!(log.attestations[0].file_path, "src/lib.rs");
+
+        // Already current: nothing to do
+        assert_eq!(migrate_note(&migrated).unwrap(), None);
+
+        let future = migrated.replace(AUTHORSHIP_LOG_VERSION, "authorship/4.0.0");
+        assert!(migrate_note(&future).is_err());
+    }
+}
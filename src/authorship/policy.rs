@@ -0,0 +1,859 @@
+//! Repository policy on AI-generated content, read from `policy.toml` at the repository root
+//! (or the `policy_file` configuration key).
+//!
+//! Each `[[rules]]` entry applies to the added lines under its `paths` and can cap the share of
+//! AI-written lines, deny tools or models, or require a human review trailer on commits that
+//! contain AI lines. The commit hook checks the staged changes and the push hook checks every
+//! commit being pushed. A rule either blocks the command or only warns, and setting
+//! `GIT_AI_POLICY_OVERRIDE=1` lets a blocked command through.
+
+use crate::authorship::authorship_log::LineRange;
+use crate::authorship::authorship_log_serialization::AuthorshipLog;
+use crate::authorship::staged::{
+    EMPTY_TREE_HASH, StagedAuthorship, staged_authorship, working_tree_authorship,
+};
+use crate::config::Config;
+use crate::error::GitAiError;
+use crate::git::refs::get_authorship;
+use crate::git::repository::Repository;
+use glob::Pattern;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use toml_edit::{DocumentMut, Item, Table};
+
+/// Policy file, relative to the repository root, unless `policy_file` is configured
+pub const POLICY_FILE: &str = "policy.toml";
+
+/// Set to anything but `0` to let a command through that the policy would block
+pub const POLICY_OVERRIDE_ENV: &str = "GIT_AI_POLICY_OVERRIDE";
+
+/// Trailer that marks a commit's AI lines as reviewed, unless the policy names another
+pub const DEFAULT_REVIEW_TRAILER: &str = "AI-Reviewed-by";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Enforcement {
+    Block,
+    Warn,
+}
+
+/// Which hook a rule is enforced in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyHook {
+    Commit,
+    Push,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicyRule {
+    pub name: String,
+    /// Globs or directory prefixes (`security/`); empty matches every path
+    pub paths: Vec<String>,
+    /// Highest allowed percentage of added lines written by AI
+    pub max_ai_percent: Option<f64>,
+    /// Commits with AI lines under `paths` must carry the review trailer. Only checked on push,
+    /// since the commit message doesn't exist yet when the commit hook runs.
+    pub require_review: bool,
+    /// Tool globs whose lines aren't allowed under `paths`
+    pub deny_tools: Vec<String>,
+    /// Model globs whose lines aren't allowed under `paths`
+    pub deny_models: Vec<String>,
+    pub enforcement: Enforcement,
+    pub hooks: Vec<PolicyHook>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Policy {
+    pub review_trailer: String,
+    pub rules: Vec<PolicyRule>,
+}
+
+/// Which limit of a rule was broken
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ViolationKind {
+    MaxAiPercent,
+    DeniedTool,
+    DeniedModel,
+    ReviewRequired,
+}
+
+impl ViolationKind {
+    /// Rule ID in SARIF output
+    pub fn rule_id(&self) -> &'static str {
+        match self {
+            ViolationKind::MaxAiPercent => "policy/max-ai-percent",
+            ViolationKind::DeniedTool => "policy/denied-tool",
+            ViolationKind::DeniedModel => "policy/denied-model",
+            ViolationKind::ReviewRequired => "policy/review-required",
+        }
+    }
+}
+
+/// A rule a change broke
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    pub rule: String,
+    pub kind: ViolationKind,
+    pub enforcement: Enforcement,
+    /// "staged changes" or the short SHA of a commit
+    pub subject: String,
+    /// Full SHA, when the subject is a commit
+    pub commit: Option<String>,
+    /// The first offending AI line, to anchor annotations on
+    pub path: Option<String>,
+    pub line: Option<u32>,
+    pub message: String,
+}
+
+/// The added lines of one change (the staged changes or a commit) and who wrote them
+pub struct PolicySubject<'a> {
+    pub label: String,
+    /// Full SHA, when the subject is a commit
+    pub commit: Option<&'a str>,
+    pub authorship_log: &'a AuthorshipLog,
+    pub added_lines: &'a HashMap<String, Vec<u32>>,
+    /// The commit message; None for changes not committed yet
+    pub message: Option<&'a str>,
+}
+
+impl Policy {
+    /// The repository's policy, or None when it has no policy file
+    pub fn load(repo: &Repository) -> Result<Option<Policy>, GitAiError> {
+        let file = Config::get().policy_file().unwrap_or(POLICY_FILE);
+        let path = repo.workdir()?.join(file);
+        match std::fs::read_to_string(&path) {
+            Ok(content) => Policy::parse(&content).map(Some),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(GitAiError::IoError(e)),
+        }
+    }
+
+    pub fn parse(content: &str) -> Result<Policy, GitAiError> {
+        let doc: DocumentMut = content
+            .parse()
+            .map_err(|e| GitAiError::Generic(format!("Invalid {}: {}", POLICY_FILE, e)))?;
+
+        let mut policy = Policy {
+            review_trailer: DEFAULT_REVIEW_TRAILER.to_string(),
+            rules: Vec::new(),
+        };
+        for (key, item) in doc.as_table().iter() {
+            match key {
+                "review_trailer" => {
+                    policy.review_trailer = item
+                        .as_str()
+                        .filter(|s| !s.trim().is_empty())
+                        .ok_or_else(|| invalid("review_trailer must be a non-empty string"))?
+                        .trim()
+                        .to_string();
+                }
+                "rules" => {
+                    let rules = item
+                        .as_array_of_tables()
+                        .ok_or_else(|| invalid("rules must be written as [[rules]] tables"))?;
+                    for (i, table) in rules.iter().enumerate() {
+                        policy.rules.push(parse_rule(table, i + 1)?);
+                    }
+                }
+                other => return Err(invalid(&format!("unknown key `{}`", other))),
+            }
+        }
+        Ok(policy)
+    }
+
+    pub fn rules_for(&self, hook: PolicyHook) -> impl Iterator<Item = &PolicyRule> {
+        self.rules
+            .iter()
+            .filter(move |rule| rule.hooks.contains(&hook))
+    }
+
+    /// Every rule enforced in `hook` that `subject` breaks
+    pub fn evaluate(&self, hook: PolicyHook, subject: &PolicySubject) -> Vec<Violation> {
+        let lines = AuthoredLines::from_subject(subject);
+        let mut violations = Vec::new();
+
+        for rule in self.rules_for(hook) {
+            let files: Vec<&String> = lines
+                .added
+                .keys()
+                .filter(|path| rule.matches_path(path))
+                .collect();
+            if files.is_empty() {
+                continue;
+            }
+            let added: usize = files.iter().map(|path| lines.added[*path]).sum();
+            let ai_lines: Vec<&AiLine> = lines
+                .ai
+                .iter()
+                .filter(|line| files.contains(&&line.file))
+                .collect();
+            let first_ai_line = ai_lines.first().map(|line| (line.file.clone(), line.line));
+            let mut violate = |kind: ViolationKind, at: Option<(String, u32)>, message: String| {
+                let (path, line) = at.unzip();
+                violations.push(Violation {
+                    rule: rule.name.clone(),
+                    kind,
+                    enforcement: rule.enforcement,
+                    subject: subject.label.clone(),
+                    commit: subject.commit.map(str::to_string),
+                    path,
+                    line,
+                    message,
+                })
+            };
+
+            if let Some(max) = rule.max_ai_percent {
+                let percent = ai_lines.len() as f64 * 100.0 / added as f64;
+                if added > 0 && percent > max {
+                    violate(
+                        ViolationKind::MaxAiPercent,
+                        first_ai_line.clone(),
+                        format!(
+                            "{:.0}% of the lines added{} are AI-generated ({} of {}), above the {}% limit",
+                            percent,
+                            rule.scope(),
+                            ai_lines.len(),
+                            added,
+                            max
+                        ),
+                    );
+                }
+            }
+
+            // (file, tool or model, kind) -> first line it wrote
+            let mut denied: BTreeMap<(String, String, ViolationKind), u32> = BTreeMap::new();
+            for line in &ai_lines {
+                if matches_any(&rule.deny_tools, &line.tool) {
+                    let key = (
+                        line.file.clone(),
+                        line.tool.clone(),
+                        ViolationKind::DeniedTool,
+                    );
+                    let first = denied.entry(key).or_insert(line.line);
+                    *first = (*first).min(line.line);
+                }
+                if matches_any(&rule.deny_models, &line.model) {
+                    let key = (
+                        line.file.clone(),
+                        line.model.clone(),
+                        ViolationKind::DeniedModel,
+                    );
+                    let first = denied.entry(key).or_insert(line.line);
+                    *first = (*first).min(line.line);
+                }
+            }
+            for ((file, name, kind), line) in denied {
+                let what = if kind == ViolationKind::DeniedTool {
+                    "tool"
+                } else {
+                    "model"
+                };
+                let message = format!("{} has lines written by {} {}", file, what, name);
+                violate(kind, Some((file, line)), message);
+            }
+
+            if rule.require_review
+                && !ai_lines.is_empty()
+                && let Some(message) = subject.message
+                && !has_trailer(message, &self.review_trailer)
+            {
+                violate(
+                    ViolationKind::ReviewRequired,
+                    first_ai_line,
+                    format!(
+                        "AI-generated lines{} need human review ({} of {} added); add `{}: <name>` to the commit message",
+                        rule.scope(),
+                        ai_lines.len(),
+                        added,
+                        self.review_trailer
+                    ),
+                );
+            }
+        }
+
+        violations
+    }
+}
+
+impl PolicyRule {
+    pub fn matches_path(&self, path: &str) -> bool {
+        self.paths.is_empty() || self.paths.iter().any(|pattern| path_matches(pattern, path))
+    }
+
+    /// " in security/" for messages, or nothing when the rule covers every path
+    fn scope(&self) -> String {
+        if self.paths.is_empty() {
+            String::new()
+        } else {
+            format!(" in {}", self.paths.join(", "))
+        }
+    }
+}
+
+fn parse_rule(table: &Table, index: usize) -> Result<PolicyRule, GitAiError> {
+    let name = match table.get("name") {
+        Some(item) => item
+            .as_str()
+            .ok_or_else(|| invalid(&format!("rule {}: name must be a string", index)))?
+            .to_string(),
+        None => format!("rule {}", index),
+    };
+    let mut rule = PolicyRule {
+        name,
+        paths: Vec::new(),
+        max_ai_percent: None,
+        require_review: false,
+        deny_tools: Vec::new(),
+        deny_models: Vec::new(),
+        enforcement: Enforcement::Block,
+        hooks: vec![PolicyHook::Commit, PolicyHook::Push],
+    };
+
+    for (key, item) in table.iter() {
+        let name = rule.name.clone();
+        let field = |what: &str| invalid(&format!("{}: {} {}", name, key, what));
+        match key {
+            "name" => {}
+            "paths" => rule.paths = string_list(item).ok_or_else(|| field("must be strings"))?,
+            "max_ai_percent" => {
+                let max = item
+                    .as_float()
+                    .or_else(|| item.as_integer().map(|n| n as f64))
+                    .filter(|max| (0.0..=100.0).contains(max))
+                    .ok_or_else(|| field("must be a number from 0 to 100"))?;
+                rule.max_ai_percent = Some(max);
+            }
+            "require_review" => {
+                rule.require_review = item
+                    .as_bool()
+                    .ok_or_else(|| field("must be true or false"))?
+            }
+            "deny_tools" => {
+                rule.deny_tools = string_list(item).ok_or_else(|| field("must be strings"))?
+            }
+            "deny_models" => {
+                rule.deny_models = string_list(item).ok_or_else(|| field("must be strings"))?
+            }
+            "enforcement" => {
+                rule.enforcement = match item.as_str() {
+                    Some("block") => Enforcement::Block,
+                    Some("warn") => Enforcement::Warn,
+                    _ => return Err(field("must be \"block\" or \"warn\"")),
+                }
+            }
+            "hooks" => {
+                rule.hooks = string_list(item)
+                    .ok_or_else(|| field("must be strings"))?
+                    .iter()
+                    .map(|hook| match hook.as_str() {
+                        "commit" => Ok(PolicyHook::Commit),
+                        "push" => Ok(PolicyHook::Push),
+                        _ => Err(field("must be \"commit\" and/or \"push\"")),
+                    })
+                    .collect::<Result<_, _>>()?;
+            }
+            other => return Err(invalid(&format!("{}: unknown key `{}`", rule.name, other))),
+        }
+    }
+
+    for pattern in rule
+        .paths
+        .iter()
+        .chain(&rule.deny_tools)
+        .chain(&rule.deny_models)
+    {
+        Pattern::new(pattern).map_err(|e| {
+            invalid(&format!(
+                "{}: invalid pattern `{}`: {}",
+                rule.name, pattern, e
+            ))
+        })?;
+    }
+    Ok(rule)
+}
+
+fn string_list(item: &Item) -> Option<Vec<String>> {
+    item.as_array()?
+        .iter()
+        .map(|value| value.as_str().map(str::to_string))
+        .collect()
+}
+
+fn invalid(message: &str) -> GitAiError {
+    GitAiError::Generic(format!("Invalid {}: {}", POLICY_FILE, message))
+}
+
+/// `security/` matches everything under that directory, anything else is a glob matched against
+/// the whole path (`**/*.sql`), or a directory name matching everything under it
+fn path_matches(pattern: &str, path: &str) -> bool {
+    if let Some(dir) = pattern.strip_suffix('/') {
+        return path.starts_with(&format!("{}/", dir));
+    }
+    path.starts_with(&format!("{}/", pattern))
+        || Pattern::new(pattern).is_ok_and(|glob| glob.matches(path))
+}
+
+fn matches_any(patterns: &[String], value: &str) -> bool {
+    patterns
+        .iter()
+        .any(|pattern| Pattern::new(pattern).is_ok_and(|glob| glob.matches(value)))
+}
+
+fn has_trailer(message: &str, trailer: &str) -> bool {
+    let prefix = format!("{}:", trailer.to_lowercase());
+    message.lines().any(|line| {
+        let line = line.trim().to_lowercase();
+        line.starts_with(&prefix) && !line[prefix.len()..].trim().is_empty()
+    })
+}
+
+struct AiLine {
+    file: String,
+    line: u32,
+    tool: String,
+    model: String,
+}
+
+/// A subject's added lines per file, and which of them AI wrote
+struct AuthoredLines {
+    added: HashMap<String, usize>,
+    ai: Vec<AiLine>,
+}
+
+impl AuthoredLines {
+    fn from_subject(subject: &PolicySubject) -> Self {
+        let log = subject.authorship_log;
+        let mut added = HashMap::new();
+        let mut ai = Vec::new();
+
+        for (path, lines) in subject.added_lines {
+            let lines: HashSet<u32> = lines.iter().copied().collect();
+            if lines.is_empty() {
+                continue;
+            }
+            added.insert(path.clone(), lines.len());
+
+            let Some(attestation) = log.attestations.iter().find(|a| &a.file_path == path) else {
+                continue;
+            };
+            let mut seen: HashSet<u32> = HashSet::new();
+            for entry in &attestation.entries {
+                let prompt = log.metadata.prompts.get(&entry.hash);
+                for range in &entry.line_ranges {
+                    let (start, end) = match range {
+                        LineRange::Single(line) => (*line, *line),
+                        LineRange::Range(start, end) => (*start, *end),
+                    };
+                    for line in (start..=end).filter(|l| lines.contains(l)) {
+                        if seen.insert(line) {
+                            ai.push(AiLine {
+                                file: path.clone(),
+                                line,
+                                tool: prompt.map_or("unknown", |p| &p.agent_id.tool).to_string(),
+                                model: prompt.map_or("unknown", |p| &p.agent_id.model).to_string(),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        AuthoredLines { added, ai }
+    }
+}
+
+fn override_requested() -> bool {
+    std::env::var(POLICY_OVERRIDE_ENV).is_ok_and(|value| !value.is_empty() && value != "0")
+}
+
+/// Print the violations and decide whether the command may go ahead
+fn report(command: &str, violations: &[Violation]) -> bool {
+    if violations.is_empty() {
+        return true;
+    }
+    let blocking = violations
+        .iter()
+        .any(|v| v.enforcement == Enforcement::Block);
+
+    for violation in violations {
+        let level = match violation.enforcement {
+            Enforcement::Block => "error",
+            Enforcement::Warn => "warning",
+        };
+        eprintln!(
+            "git-ai policy {}: [{}] {}: {}",
+            level, violation.rule, violation.subject, violation.message
+        );
+    }
+    if !blocking {
+        return true;
+    }
+    if override_requested() {
+        eprintln!(
+            "git-ai policy: {} allowed because {} is set",
+            command, POLICY_OVERRIDE_ENV
+        );
+        return true;
+    }
+    eprintln!(
+        "git-ai policy: {} blocked by {}. Set {}=1 to override.",
+        command, POLICY_FILE, POLICY_OVERRIDE_ENV
+    );
+    false
+}
+
+/// Report a policy file that can't be read; it blocks, so that a typo can't switch the policy off
+fn report_load_error(command: &str, error: &GitAiError) -> bool {
+    eprintln!("git-ai policy error: {}", error);
+    if override_requested() {
+        return true;
+    }
+    eprintln!(
+        "git-ai policy: {} blocked. Fix {} or set {}=1 to override.",
+        command, POLICY_FILE, POLICY_OVERRIDE_ENV
+    );
+    false
+}
+
+/// Check the changes about to be committed. `all` is `git commit -a`, which commits the working
+/// tree's tracked changes rather than the index. Returns false when the commit must not go ahead.
+pub fn check_commit(repo: &Repository, all: bool) -> bool {
+    let policy = match Policy::load(repo) {
+        Ok(Some(policy)) => policy,
+        Ok(None) => return true,
+        Err(e) => return report_load_error("commit", &e),
+    };
+    if policy.rules_for(PolicyHook::Commit).next().is_none() {
+        return true;
+    }
+
+    match evaluate_staged(repo, &policy, all) {
+        Ok(violations) => report("commit", &violations),
+        Err(e) => {
+            crate::utils::debug_log(&format!(
+                "policy: could not attribute staged changes: {}",
+                e
+            ));
+            true
+        }
+    }
+}
+
+/// The commit rules `policy` finds broken by the staged changes, or by the working tree's
+/// tracked changes when `all`
+pub fn evaluate_staged(
+    repo: &Repository,
+    policy: &Policy,
+    all: bool,
+) -> Result<Vec<Violation>, GitAiError> {
+    let staged: StagedAuthorship = if all {
+        working_tree_authorship(repo)?
+    } else {
+        staged_authorship(repo)?
+    };
+    let subject = PolicySubject {
+        label: "staged changes".to_string(),
+        commit: None,
+        authorship_log: &staged.authorship_log,
+        added_lines: &staged.added_lines,
+        message: None,
+    };
+    Ok(policy.evaluate(PolicyHook::Commit, &subject))
+}
+
+/// Check every non-merge commit reachable from `revs` that `remote` doesn't have yet. Returns
+/// false when the push must not go ahead.
+pub fn check_push(repo: &Repository, remote: &str, revs: &[String]) -> bool {
+    let policy = match Policy::load(repo) {
+        Ok(Some(policy)) => policy,
+        Ok(None) => return true,
+        Err(e) => return report_load_error("push", &e),
+    };
+    if policy.rules_for(PolicyHook::Push).next().is_none() || revs.is_empty() {
+        return true;
+    }
+
+    let commits = match commits_to_push(repo, remote, revs) {
+        Ok(commits) => commits,
+        Err(e) => {
+            crate::utils::debug_log(&format!("policy: could not list pushed commits: {}", e));
+            return true;
+        }
+    };
+
+    report(
+        "push",
+        &evaluate_commits(repo, &policy, PolicyHook::Push, &commits),
+    )
+}
+
+/// Every rule enforced in `hook` that one of `commits` breaks, judged by the commit's note
+pub fn evaluate_commits(
+    repo: &Repository,
+    policy: &Policy,
+    hook: PolicyHook,
+    commits: &[String],
+) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    for commit in commits {
+        // Commits without a note have no AI lines to check
+        let Some(log) = get_authorship(repo, commit) else {
+            continue;
+        };
+        if log.attestations.is_empty() {
+            continue;
+        }
+        let parent = repo
+            .git(&["rev-parse", "--verify", "--quiet", &format!("{}^", commit)])
+            .map(|parent| parent.trim().to_string())
+            .unwrap_or_else(|_| EMPTY_TREE_HASH.to_string());
+        let Ok(added_lines) = repo.diff_added_lines(&parent, commit, None) else {
+            continue;
+        };
+        let message = repo
+            .git(&["show", "-s", "--format=%B", commit])
+            .ok()
+            .map(|message| message.trim().to_string());
+        let subject = PolicySubject {
+            label: format!("commit {}", &commit[..commit.len().min(7)]),
+            commit: Some(commit),
+            authorship_log: &log,
+            added_lines: &added_lines,
+            message: message.as_deref(),
+        };
+        violations.extend(policy.evaluate(hook, &subject));
+    }
+    violations
+}
+
+/// Non-merge commits reachable from `revs` that no ref of `remote` has yet
+pub(crate) fn commits_to_push(
+    repo: &Repository,
+    remote: &str,
+    revs: &[String],
+) -> Result<Vec<String>, GitAiError> {
+    let mut args = vec!["rev-list".to_string(), "--no-merges".to_string()];
+    args.extend(revs.iter().cloned());
+    args.push("--not".to_string());
+    args.push(format!("--remotes={}", remote));
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    Ok(repo.git(&args)?.lines().map(str::to_string).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authorship::authorship_log::PromptRecord;
+    use crate::authorship::authorship_log_serialization::{AttestationEntry, FileAttestation};
+    use crate::authorship::working_log::AgentId;
+
+    fn log_with_ai_lines(
+        file: &str,
+        tool: &str,
+        model: &str,
+        lines: Vec<LineRange>,
+    ) -> AuthorshipLog {
+        let mut log = AuthorshipLog::new();
+        log.metadata.prompts.insert(
+            "aaaaaaa".to_string(),
+            PromptRecord {
+                agent_id: AgentId {
+                    tool: tool.to_string(),
+                    id: "session".to_string(),
+                    model: model.to_string(),
+                },
+                human_author: None,
+                messages: vec![],
+                total_additions: 0,
+                total_deletions: 0,
+                accepted_lines: 0,
+                overriden_lines: 0,
+                messages_url: None,
+                token_usage: None,
+                turns: Vec::new(),
+            },
+        );
+        let mut attestation = FileAttestation::new(file.to_string());
+        attestation.add_entry(AttestationEntry::new("aaaaaaa".to_string(), lines));
+        log.attestations.push(attestation);
+        log
+    }
+
+    fn evaluate(
+        policy: &str,
+        hook: PolicyHook,
+        log: &AuthorshipLog,
+        added_lines: &[(&str, Vec<u32>)],
+        message: Option<&str>,
+    ) -> Vec<Violation> {
+        let added_lines: HashMap<String, Vec<u32>> = added_lines
+            .iter()
+            .map(|(path, lines)| (path.to_string(), lines.clone()))
+            .collect();
+        let subject = PolicySubject {
+            label: "staged changes".to_string(),
+            commit: None,
+            authorship_log: log,
+            added_lines: &added_lines,
+            message,
+        };
+        Policy::parse(policy).unwrap().evaluate(hook, &subject)
+    }
+
+    #[test]
+    fn test_parse_policy() {
+        let policy = Policy::parse(
+            r#"
+review_trailer = "Reviewed-by"
+
+[[rules]]
+name = "security"
+paths = ["security/", "**/*.sql"]
+max_ai_percent = 50
+require_review = true
+enforcement = "warn"
+hooks = ["push"]
+
+[[rules]]
+deny_models = ["gpt-3.5*"]
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(policy.review_trailer, "Reviewed-by");
+        assert_eq!(policy.rules.len(), 2);
+        let security = &policy.rules[0];
+        assert_eq!(security.name, "security");
+        assert_eq!(security.max_ai_percent, Some(50.0));
+        assert!(security.require_review);
+        assert_eq!(security.enforcement, Enforcement::Warn);
+        assert_eq!(security.hooks, vec![PolicyHook::Push]);
+        assert_eq!(policy.rules[1].name, "rule 2");
+        assert_eq!(policy.rules[1].enforcement, Enforcement::Block);
+        assert_eq!(
+            policy.rules[1].hooks,
+            vec![PolicyHook::Commit, PolicyHook::Push]
+        );
+    }
+
+    #[test]
+    fn test_parse_policy_rejects_mistakes() {
+        for content in [
+            "[[rules]]\nmax_ai_percent = 150",
+            "[[rules]]\nmax_ai_pct = 50",
+            "[[rules]]\nenforcement = \"deny\"",
+            "[[rules]]\nhooks = [\"merge\"]",
+            "[[rules]]\npaths = \"security/\"",
+            "rule = []",
+            "[[rules]\n",
+        ] {
+            assert!(Policy::parse(content).is_err(), "{}", content);
+        }
+    }
+
+    #[test]
+    fn test_path_matches() {
+        assert!(path_matches("security/", "security/auth.rs"));
+        assert!(path_matches("security", "security/nested/auth.rs"));
+        assert!(!path_matches("security/", "src/security.rs"));
+        assert!(path_matches("**/*.sql", "db/migrations/001.sql"));
+        assert!(path_matches("*.md", "README.md"));
+    }
+
+    #[test]
+    fn test_max_ai_percent_only_counts_matching_paths() {
+        let log = log_with_ai_lines(
+            "security/auth.rs",
+            "cursor",
+            "gpt-4o",
+            vec![LineRange::Range(1, 3)],
+        );
+        let policy = "[[rules]]\nname = \"security\"\npaths = [\"security/\"]\nmax_ai_percent = 50";
+
+        let violations = evaluate(
+            policy,
+            PolicyHook::Commit,
+            &log,
+            &[
+                ("security/auth.rs", vec![1, 2, 3, 4]),
+                ("src/lib.rs", vec![1, 2, 3, 4, 5, 6]),
+            ],
+            None,
+        );
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].rule, "security");
+        assert_eq!(
+            violations[0].message,
+            "75% of the lines added in security/ are AI-generated (3 of 4), above the 50% limit"
+        );
+        assert_eq!(violations[0].kind, ViolationKind::MaxAiPercent);
+        assert_eq!(violations[0].path.as_deref(), Some("security/auth.rs"));
+
+        // Half is not above the limit
+        let violations = evaluate(
+            policy,
+            PolicyHook::Commit,
+            &log,
+            &[("security/auth.rs", vec![1, 2, 3, 4, 5, 6])],
+            None,
+        );
+        assert!(violations.is_empty());
+    }
+
+    #[test]
+    fn test_deny_tools_and_models() {
+        let log = log_with_ai_lines(
+            "src/lib.rs",
+            "cursor",
+            "gpt-3.5-turbo",
+            vec![LineRange::Single(2)],
+        );
+        let added = [("src/lib.rs", vec![1, 2])];
+
+        let violations = evaluate(
+            "[[rules]]\ndeny_models = [\"gpt-3.5*\"]",
+            PolicyHook::Commit,
+            &log,
+            &added,
+            None,
+        );
+        assert_eq!(violations.len(), 1);
+        assert_eq!(
+            violations[0].message,
+            "src/lib.rs has lines written by model gpt-3.5-turbo"
+        );
+        assert_eq!(violations[0].kind, ViolationKind::DeniedModel);
+        assert_eq!(violations[0].path.as_deref(), Some("src/lib.rs"));
+        assert_eq!(violations[0].line, Some(2));
+
+        let violations = evaluate(
+            "[[rules]]\ndeny_tools = [\"claude\"]",
+            PolicyHook::Commit,
+            &log,
+            &added,
+            None,
+        );
+        assert!(violations.is_empty());
+    }
+
+    #[test]
+    fn test_require_review_checks_trailer_on_push_only() {
+        let log = log_with_ai_lines("src/lib.rs", "cursor", "gpt-4o", vec![LineRange::Single(1)]);
+        let added = [("src/lib.rs", vec![1])];
+        let policy = "[[rules]]\nrequire_review = true";
+
+        assert_eq!(
+            evaluate(policy, PolicyHook::Push, &log, &added, Some("Add lib\n")).len(),
+            1
+        );
+        assert!(
+            evaluate(
+                policy,
+                PolicyHook::Push,
+                &log,
+                &added,
+                Some("Add lib\n\nAI-Reviewed-by: Sam <sam@example.com>\n")
+            )
+            .is_empty()
+        );
+        assert!(evaluate(policy, PolicyHook::Commit, &log, &added, None).is_empty());
+    }
+}
@@ -1,6 +1,10 @@
 use crate::api::{ApiClient, ApiContext};
+use crate::authorship::attribution_ignore::repo_ignore_patterns;
 use crate::authorship::authorship_log_serialization::AuthorshipLog;
+use crate::authorship::prompt_turns::attach_prompt_turns;
 use crate::authorship::prompt_utils::{update_prompt_from_tool, PromptUpdateResult};
+use crate::authorship::range_authorship::should_ignore_file;
+use crate::authorship::revert::reverted_lines;
 use crate::authorship::secrets::{redact_secrets_from_prompts, strip_prompt_messages};
 use crate::authorship::stats::{stats_for_commit_stats, write_stats_to_terminal};
 use crate::authorship::virtual_attribution::VirtualAttributions;
@@ -88,6 +92,30 @@ pub fn post_commit(
 
     authorship_log.metadata.base_commit_sha = commit_sha.clone();
 
+    // Files checkpointed before they were ignored carry no attribution into the note
+    let ignore_patterns = repo_ignore_patterns(repo);
+    authorship_log
+        .attestations
+        .retain(|a| !should_ignore_file(&a.file_path, &ignore_patterns));
+
+    // A revert's note records the AI lines it rolled back
+    match reverted_lines(repo, &commit_sha) {
+        Ok(reverted) => authorship_log.metadata.reverted = reverted,
+        Err(e) => debug_log(&format!("Failed to count reverted AI lines: {}", e)),
+    }
+
+    // Split each session's lines by the prompt that produced them
+    attach_prompt_turns(
+        &mut authorship_log,
+        &filtered_working_log,
+        |sha| working_log.get_file_version(sha).ok(),
+        |path| {
+            repo.get_file_content(path, &commit_sha)
+                .ok()
+                .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+        },
+    );
+
     // Handle prompts based on prompt_storage setting and exclusion rules
     let should_exclude = Config::get().should_exclude_prompts(&Some(repo.clone()));
     let prompt_storage = Config::get().prompt_storage();
@@ -402,7 +430,7 @@ fn enqueue_prompt_messages_to_cas(
 
 /// Record metrics for a committed change.
 /// This is a best-effort operation - failures are silently ignored.
-fn record_commit_metrics(
+pub(crate) fn record_commit_metrics(
     repo: &Repository,
     commit_sha: &str,
     parent_sha: &str,
@@ -411,7 +439,29 @@ fn record_commit_metrics(
     stats: &crate::authorship::stats::CommitStats,
     checkpoints: &[Checkpoint],
 ) {
-    use crate::metrics::{record, CommittedValues, EventAttributes};
+    let (values, attrs) = committed_event(
+        repo,
+        commit_sha,
+        parent_sha,
+        human_author,
+        authorship_log,
+        stats,
+        checkpoints,
+    );
+    crate::metrics::record(values, attrs);
+}
+
+/// The values and attributes of the `committed` metric event for a commit
+pub(crate) fn committed_event(
+    repo: &Repository,
+    commit_sha: &str,
+    parent_sha: &str,
+    human_author: &str,
+    authorship_log: &AuthorshipLog,
+    stats: &crate::authorship::stats::CommitStats,
+    checkpoints: &[Checkpoint],
+) -> (crate::metrics::CommittedValues, crate::metrics::EventAttributes) {
+    use crate::metrics::{CommittedValues, EventAttributes};
 
     // Build parallel arrays: index 0 = "all" (aggregate), index 1+ = per tool/model
     let mut tool_model_pairs: Vec<String> = vec!["all".to_string()];
@@ -446,6 +496,12 @@ fn record_commit_metrics(
         .total_ai_deletions(total_ai_deletions)
         .time_waiting_for_ai(time_waiting_for_ai);
 
+    // Only revert commits carry this
+    let values = match &authorship_log.metadata.reverted {
+        Some(reverted) => values.ai_reverted(reverted.ai_lines),
+        None => values,
+    };
+
     // Add first checkpoint timestamp (null if no checkpoints)
     let values = if let Some(first) = checkpoints.first() {
         values.first_checkpoint_ts(first.timestamp)
@@ -492,8 +548,7 @@ fn record_commit_metrics(
         }
     }
 
-    // Record the metric
-    record(values, attrs);
+    (values, attrs)
 }
 
 #[cfg(test)]
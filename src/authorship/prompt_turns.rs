@@ -0,0 +1,271 @@
+//! Per-prompt attribution: which of a session's user prompts produced each committed AI line.
+//!
+//! Attestations only tie lines to a session. Each AI checkpoint also records a hash of the
+//! prompt the agent was answering, so at commit time the lines a session added at each checkpoint
+//! are matched by content against the committed file and grouped into [`PromptTurn`]s.
+
+use crate::authorship::authorship_log::LineRange;
+use crate::authorship::authorship_log::PromptTurn;
+use crate::authorship::authorship_log_serialization::{
+    AuthorshipLog, format_line_ranges, generate_short_hash,
+};
+use crate::authorship::working_log::Checkpoint;
+use std::collections::{BTreeMap, HashMap};
+
+/// Fill `turns` on every prompt record whose session's checkpoints carry prompt hashes.
+///
+/// `load_blob` returns a checkpoint's file snapshot by blob sha, `committed` the committed
+/// content of a file. A line goes to the latest prompt whose checkpoint added a line with the
+/// same content; lines no checkpoint explains (e.g. from checkpoints older than prompt hashes)
+/// stay attributed to the session alone.
+pub fn attach_prompt_turns(
+    log: &mut AuthorshipLog,
+    checkpoints: &[Checkpoint],
+    load_blob: impl Fn(&str) -> Option<String>,
+    committed: impl Fn(&str) -> Option<String>,
+) {
+    if checkpoints.iter().all(|c| c.prompt_hash.is_none()) {
+        return;
+    }
+
+    let mut blobs: HashMap<String, Option<String>> = HashMap::new();
+    // session -> prompt hash -> file -> lines
+    let mut turn_lines: HashMap<String, BTreeMap<String, BTreeMap<String, Vec<u32>>>> =
+        HashMap::new();
+
+    for file in &log.attestations {
+        let Some(content) = committed(&file.file_path) else {
+            continue;
+        };
+        let committed_lines: Vec<&str> = content.lines().collect();
+
+        for entry in &file.entries {
+            if !log.metadata.prompts.contains_key(&entry.hash) {
+                continue;
+            }
+            let owners = prompt_owners(
+                checkpoints,
+                &file.file_path,
+                &entry.hash,
+                &mut blobs,
+                &load_blob,
+            );
+            if owners.is_empty() {
+                continue;
+            }
+            for range in &entry.line_ranges {
+                for line in range.expand() {
+                    let Some(text) = (line as usize)
+                        .checked_sub(1)
+                        .and_then(|i| committed_lines.get(i))
+                    else {
+                        continue;
+                    };
+                    if let Some(prompt_hash) = owners.get(*text) {
+                        turn_lines
+                            .entry(entry.hash.clone())
+                            .or_default()
+                            .entry(prompt_hash.clone())
+                            .or_default()
+                            .entry(file.file_path.clone())
+                            .or_default()
+                            .push(line);
+                    }
+                }
+            }
+        }
+    }
+
+    for (session, mut by_prompt) in turn_lines {
+        let Some(record) = log.metadata.prompts.get_mut(&session) else {
+            continue;
+        };
+        // Oldest prompt first, in the order the session's checkpoints saw them
+        let mut turns = Vec::new();
+        for checkpoint in checkpoints {
+            if checkpoint_session(checkpoint).as_deref() != Some(session.as_str()) {
+                continue;
+            }
+            let Some(prompt_hash) = &checkpoint.prompt_hash else {
+                continue;
+            };
+            let Some(files) = by_prompt.remove(prompt_hash) else {
+                continue;
+            };
+            turns.push(PromptTurn {
+                prompt_hash: prompt_hash.clone(),
+                files: files
+                    .into_iter()
+                    .map(|(path, mut lines)| {
+                        lines.sort_unstable();
+                        lines.dedup();
+                        (path, format_line_ranges(&LineRange::compress_lines(&lines)))
+                    })
+                    .collect(),
+            });
+        }
+        record.turns = turns;
+    }
+}
+
+/// Line content -> hash of the latest prompt of `session` whose checkpoint added such a line
+/// to `file`. A checkpoint adds a line when the session owns more lines with that content after
+/// it than after the previous checkpoint touching the file.
+fn prompt_owners(
+    checkpoints: &[Checkpoint],
+    file: &str,
+    session: &str,
+    blobs: &mut HashMap<String, Option<String>>,
+    load_blob: &impl Fn(&str) -> Option<String>,
+) -> HashMap<String, String> {
+    let mut owners = HashMap::new();
+    let mut previous: HashMap<String, usize> = HashMap::new();
+
+    for checkpoint in checkpoints {
+        let Some(entry) = checkpoint.entries.iter().find(|e| e.file == file) else {
+            continue;
+        };
+        if entry.blob_sha.is_empty() {
+            continue;
+        }
+        let content = blobs
+            .entry(entry.blob_sha.clone())
+            .or_insert_with(|| load_blob(&entry.blob_sha));
+        let Some(content) = content else {
+            continue;
+        };
+        let lines: Vec<&str> = content.lines().collect();
+
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for attribution in entry
+            .line_attributions
+            .iter()
+            .filter(|a| a.author_id == session)
+        {
+            for line in attribution.start_line..=attribution.end_line {
+                if let Some(text) = (line as usize).checked_sub(1).and_then(|i| lines.get(i)) {
+                    *counts.entry(text.to_string()).or_default() += 1;
+                }
+            }
+        }
+
+        if checkpoint_session(checkpoint).as_deref() == Some(session)
+            && let Some(prompt_hash) = &checkpoint.prompt_hash
+        {
+            for (text, count) in &counts {
+                if *count > previous.get(text).copied().unwrap_or(0) {
+                    owners.insert(text.clone(), prompt_hash.clone());
+                }
+            }
+        }
+        previous = counts;
+    }
+
+    owners
+}
+
+fn checkpoint_session(checkpoint: &Checkpoint) -> Option<String> {
+    checkpoint
+        .agent_id
+        .as_ref()
+        .map(|agent_id| generate_short_hash(&agent_id.id, &agent_id.tool))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authorship::attribution_tracker::LineAttribution;
+    use crate::authorship::authorship_log::PromptRecord;
+    use crate::authorship::authorship_log_serialization::{AttestationEntry, FileAttestation};
+    use crate::authorship::working_log::{AgentId, CheckpointKind, WorkingLogEntry};
+
+    fn agent() -> AgentId {
+        AgentId {
+            tool: "claude".to_string(),
+            id: "session-1".to_string(),
+            model: "sonnet".to_string(),
+        }
+    }
+
+    fn checkpoint(blob: &str, ai_lines: &[(u32, u32)], prompt: Option<&str>) -> Checkpoint {
+        let session = generate_short_hash(&agent().id, &agent().tool);
+        let line_attributions = ai_lines
+            .iter()
+            .map(|(start, end)| LineAttribution {
+                start_line: *start,
+                end_line: *end,
+                author_id: session.clone(),
+                overrode: None,
+            })
+            .collect();
+        let entry = WorkingLogEntry::new(
+            "lib.rs".to_string(),
+            blob.to_string(),
+            Vec::new(),
+            line_attributions,
+        );
+        let kind = if prompt.is_some() {
+            CheckpointKind::AiAgent
+        } else {
+            CheckpointKind::Human
+        };
+        let mut checkpoint = Checkpoint::new(kind, String::new(), "test".to_string(), vec![entry]);
+        if let Some(prompt) = prompt {
+            checkpoint.agent_id = Some(agent());
+            checkpoint.prompt_hash = Some(prompt.to_string());
+        }
+        checkpoint
+    }
+
+    #[test]
+    fn test_lines_go_to_the_prompt_that_added_them() {
+        let session = generate_short_hash(&agent().id, &agent().tool);
+        let blobs: HashMap<&str, &str> = HashMap::from([
+            ("b1", "fn a() {}\n"),
+            ("b2", "fn a() {}\nfn b() {}\n"),
+            ("b3", "// human\nfn a() {}\nfn b() {}\n"),
+        ]);
+        let checkpoints = vec![
+            checkpoint("b1", &[(1, 1)], Some("p1")),
+            checkpoint("b2", &[(1, 2)], Some("p2")),
+            checkpoint("b3", &[(2, 3)], None),
+        ];
+
+        let mut log = AuthorshipLog::new();
+        let mut file = FileAttestation::new("lib.rs".to_string());
+        file.add_entry(AttestationEntry::new(
+            session.clone(),
+            vec![LineRange::Range(2, 3)],
+        ));
+        log.attestations.push(file);
+        log.metadata.prompts.insert(
+            session.clone(),
+            PromptRecord {
+                agent_id: agent(),
+                human_author: None,
+                messages: Vec::new(),
+                total_additions: 2,
+                total_deletions: 0,
+                accepted_lines: 2,
+                overriden_lines: 0,
+                messages_url: None,
+                token_usage: None,
+                turns: Vec::new(),
+            },
+        );
+
+        attach_prompt_turns(
+            &mut log,
+            &checkpoints,
+            |sha| blobs.get(sha).map(|s| s.to_string()),
+            |_| Some("// human\nfn a() {}\nfn b() {}\n".to_string()),
+        );
+
+        let turns = &log.metadata.prompts[&session].turns;
+        assert_eq!(turns.len(), 2);
+        assert_eq!(turns[0].prompt_hash, "p1");
+        assert_eq!(turns[0].files["lib.rs"], "2");
+        assert_eq!(turns[1].prompt_hash, "p2");
+        assert_eq!(turns[1].files["lib.rs"], "3");
+    }
+}
@@ -5,7 +5,10 @@ use serde::Deserialize;
 use serde::Serialize;
 
 use crate::authorship::diff_ai_accepted::diff_ai_accepted_stats;
-use crate::authorship::stats::{CommitStats, stats_for_commit_stats, stats_from_authorship_log};
+use crate::authorship::attribution_ignore::effective_ignore_patterns;
+use crate::authorship::stats::{
+    CommitStats, stats_for_commit_with_patterns, stats_from_authorship_log,
+};
 use crate::error::GitAiError;
 use crate::git::refs::{CommitAuthorship, get_commits_with_notes_from_list};
 use crate::git::repository::{CommitRange, Repository};
@@ -19,24 +22,62 @@ const EMPTY_TREE_HASH: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
 
 /// Check if a file path should be ignored based on the provided patterns
 /// Supports both exact matches and glob patterns (e.g., "*.lock", "**/*.generated.js")
+///
+/// Patterns follow gitignore conventions: they are checked in order and the last match wins,
+/// `!pattern` re-includes a path, a trailing `/` only matches directories and a leading `/`
+/// anchors the pattern to the repository root. Matching a directory ignores everything in it.
 pub fn should_ignore_file(path: &str, ignore_patterns: &[String]) -> bool {
+    let path = path.strip_prefix("./").unwrap_or(path);
+    let mut ignored = false;
+    for pattern in ignore_patterns {
+        let (negated, pattern) = match pattern.strip_prefix('!') {
+            Some(pattern) => (true, pattern),
+            None => (false, pattern.as_str()),
+        };
+        // Only a pattern that would flip the outcome needs checking
+        if ignored != negated {
+            continue;
+        }
+        if ignore_pattern_matches(path, pattern) {
+            ignored = !negated;
+        }
+    }
+    ignored
+}
+
+fn ignore_pattern_matches(path: &str, pattern: &str) -> bool {
     use glob::Pattern;
 
-    let filename = std::path::Path::new(path)
-        .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("");
-
-    ignore_patterns.iter().any(|pattern| {
-        // Try to parse as glob pattern
-        if let Ok(glob_pattern) = Pattern::new(pattern) {
-            // Match against both the full path and just the filename
-            // This allows patterns like "*.lock" (filename) and "**/target/**" (path)
-            glob_pattern.matches(path) || glob_pattern.matches(filename)
-        } else {
-            // Fallback to exact filename match if pattern is invalid
-            filename == pattern
+    let (dir_only, pattern) = match pattern.strip_suffix('/') {
+        Some(pattern) => (true, pattern),
+        None => (false, pattern),
+    };
+    let (anchored, pattern) = match pattern.strip_prefix('/') {
+        Some(pattern) => (true, pattern),
+        None => (false, pattern),
+    };
+    if pattern.is_empty() {
+        return false;
+    }
+
+    let glob_pattern = Pattern::new(pattern);
+    let matches = |candidate: &str| match &glob_pattern {
+        Ok(glob_pattern) => glob_pattern.matches(candidate),
+        // Fallback to exact match if pattern is invalid
+        Err(_) => candidate == pattern,
+    };
+    // Match against both the path and just the name, for the file and each directory above it
+    // This allows patterns like "*.lock" (filename) and "**/target/**" (path)
+    // Patterns containing a slash only match paths
+    let match_names = !anchored && !pattern.contains('/');
+
+    let components: Vec<&str> = path.split('/').collect();
+    (1..=components.len()).any(|depth| {
+        if dir_only && depth == components.len() {
+            return false;
         }
+        matches(&components[..depth].join("/"))
+            || (match_names && matches(components[depth - 1]))
     })
 }
 
@@ -53,16 +94,21 @@ pub struct RangeAuthorshipStatsData {
     pub authors_not_committing_authorship: HashSet<String>,
     pub commits_without_authorship: Vec<String>,
     pub commits_without_authorship_with_authors: Vec<(String, String)>, // (sha, git_author)
+    /// Commits by configured bot identities left out of the numbers above
+    #[serde(default)]
+    pub bot_commits_excluded: usize,
 }
 
 pub fn range_authorship(
     commit_range: CommitRange,
     pre_fetch_contents: bool,
     ignore_patterns: &[String],
+    include_bots: bool,
 ) -> Result<RangeAuthorshipStats, GitAiError> {
     if let Err(e) = commit_range.is_valid() {
         return Err(e);
     }
+    let ignore_patterns = &effective_ignore_patterns(commit_range.repo(), ignore_patterns);
 
     // Fetch the branch if pre_fetch_contents is true
     if pre_fetch_contents {
@@ -128,11 +174,47 @@ pub fn range_authorship(
         .into_iter()
         .map(|c| c.id().to_string())
         .collect();
-    let commit_authorship = get_commits_with_notes_from_list(repository, &commit_shas)?;
+    let mut commit_authorship = get_commits_with_notes_from_list(repository, &commit_shas)?;
+
+    // Drop bot commits (dependabot, renovate, ...) so they don't inflate the human side
+    let mut bot_commit_shas = Vec::new();
+    if !include_bots {
+        let config = crate::config::Config::get();
+        commit_authorship.retain(|ca| {
+            let (sha, git_author) = match ca {
+                CommitAuthorship::Log {
+                    sha, git_author, ..
+                } => (sha, git_author),
+                CommitAuthorship::NoLog { sha, git_author } => (sha, git_author),
+            };
+            if config.is_bot_author(git_author) {
+                bot_commit_shas.push(sha.clone());
+                false
+            } else {
+                true
+            }
+        });
+    }
 
     // Calculate range stats - now just pass start, end, and commits
-    let range_stats =
-        calculate_range_stats_direct(repository, commit_range_clone, ignore_patterns)?;
+    let range_stats = if bot_commit_shas.is_empty() {
+        calculate_range_stats_direct(repository, commit_range_clone, ignore_patterns)?
+    } else {
+        // Later commits can rewrite a bot's lines, so the range's diff minus the bot commits'
+        // diffs isn't what the other commits changed. Total the other commits instead.
+        let mut total = CommitStats::default();
+        for ca in &commit_authorship {
+            let sha = match ca {
+                CommitAuthorship::Log { sha, .. } | CommitAuthorship::NoLog { sha, .. } => sha,
+            };
+            total.add(&stats_for_commit_with_patterns(
+                repository,
+                sha,
+                ignore_patterns,
+            )?);
+        }
+        total
+    };
 
     Ok(RangeAuthorshipStats {
         authorship_stats: RangeAuthorshipStatsData {
@@ -171,6 +253,7 @@ pub fn range_authorship(
                     _ => None,
                 })
                 .collect(),
+            bot_commits_excluded: bot_commit_shas.len(),
         },
         range_stats,
     })
@@ -215,12 +298,16 @@ fn create_authorship_log_for_range(
             crate::authorship::authorship_log_serialization::AuthorshipLog {
                 attestations: Vec::new(),
                 metadata: crate::authorship::authorship_log_serialization::AuthorshipMetadata {
-                    schema_version: "3".to_string(),
+                    schema_version:
+                        crate::authorship::authorship_log_serialization::AUTHORSHIP_LOG_VERSION
+                            .to_string(),
                     git_ai_version: Some(
                         crate::authorship::authorship_log_serialization::GIT_AI_VERSION.to_string(),
                     ),
                     base_commit_sha: end_sha.to_string(),
                     prompts: std::collections::BTreeMap::new(),
+                    partial_lines: std::collections::BTreeMap::new(),
+                    reverted: None,
                 },
             },
         );
@@ -410,7 +497,7 @@ fn calculate_range_stats_direct(
     let end_sha = commit_range.end_oid.clone();
     // Special case: single commit range (start == end)
     if start_sha == end_sha {
-        return stats_for_commit_stats(repo, &end_sha, ignore_patterns);
+        return stats_for_commit_with_patterns(repo, &end_sha, ignore_patterns);
     }
 
     // Step 1: Get git diff stats between start and end
@@ -525,7 +612,7 @@ mod tests {
             "package-lock.json".to_string(),
             "yarn.lock".to_string(),
         ];
-        let stats = range_authorship(commit_range, false, &lockfile_patterns).unwrap();
+        let stats = range_authorship(commit_range, false, &lockfile_patterns, false).unwrap();
 
         // Verify stats
         assert_eq!(stats.authorship_stats.total_commits, 1);
@@ -569,7 +656,7 @@ mod tests {
             "package-lock.json".to_string(),
             "yarn.lock".to_string(),
         ];
-        let stats = range_authorship(commit_range, false, &lockfile_patterns).unwrap();
+        let stats = range_authorship(commit_range, false, &lockfile_patterns, false).unwrap();
 
         // Verify stats - should include all commits from beginning
         assert_eq!(stats.authorship_stats.total_commits, 2);
@@ -613,7 +700,7 @@ mod tests {
             "package-lock.json".to_string(),
             "yarn.lock".to_string(),
         ];
-        let stats = range_authorship(commit_range, false, &lockfile_patterns).unwrap();
+        let stats = range_authorship(commit_range, false, &lockfile_patterns, false).unwrap();
 
         // For single commit, should use stats_for_commit_stats
         assert_eq!(stats.authorship_stats.total_commits, 1);
@@ -670,7 +757,7 @@ mod tests {
             "package-lock.json".to_string(),
             "yarn.lock".to_string(),
         ];
-        let stats = range_authorship(commit_range, false, &lockfile_patterns).unwrap();
+        let stats = range_authorship(commit_range, false, &lockfile_patterns, false).unwrap();
 
         // Verify stats
         assert_eq!(stats.authorship_stats.total_commits, 3);
@@ -708,7 +795,7 @@ mod tests {
             "package-lock.json".to_string(),
             "yarn.lock".to_string(),
         ];
-        let stats = range_authorship(commit_range, false, &lockfile_patterns).unwrap();
+        let stats = range_authorship(commit_range, false, &lockfile_patterns, false).unwrap();
 
         // Should have 1 commit but no diffs since start == end
         assert_eq!(stats.authorship_stats.total_commits, 1);
@@ -747,7 +834,7 @@ mod tests {
             "package-lock.json".to_string(),
             "yarn.lock".to_string(),
         ];
-        let stats = range_authorship(commit_range, false, &lockfile_patterns).unwrap();
+        let stats = range_authorship(commit_range, false, &lockfile_patterns, false).unwrap();
 
         // Verify all files are included
         assert_eq!(stats.authorship_stats.total_commits, 1);
@@ -807,7 +894,7 @@ mod tests {
             "package-lock.json".to_string(),
             "yarn.lock".to_string(),
         ];
-        let stats = range_authorship(commit_range, false, &lockfile_patterns).unwrap();
+        let stats = range_authorship(commit_range, false, &lockfile_patterns, false).unwrap();
 
         // Verify lockfile is excluded: only 2 lines added (from main.rs), not 1000+ from lockfile
         assert_eq!(stats.authorship_stats.total_commits, 1);
@@ -878,7 +965,7 @@ mod tests {
             "package-lock.json".to_string(),
             "yarn.lock".to_string(),
         ];
-        let stats = range_authorship(commit_range, false, &lockfile_patterns).unwrap();
+        let stats = range_authorship(commit_range, false, &lockfile_patterns, false).unwrap();
 
         // Key assertion: git_diff should only count lib.rs changes (3 lines), not package-lock.json (3000 lines)
         assert_eq!(stats.authorship_stats.total_commits, 2);
@@ -943,7 +1030,7 @@ mod tests {
             "poetry.lock".to_string(),
             "go.sum".to_string(),
         ];
-        let stats = range_authorship(commit_range, false, &lockfile_patterns).unwrap();
+        let stats = range_authorship(commit_range, false, &lockfile_patterns, false).unwrap();
 
         // Verify: only the 1 README line is counted, all lockfiles excluded (2000 lines ignored)
         assert_eq!(stats.authorship_stats.total_commits, 1);
@@ -999,7 +1086,7 @@ mod tests {
             "package-lock.json".to_string(),
             "yarn.lock".to_string(),
         ];
-        let stats = range_authorship(commit_range, false, &lockfile_patterns).unwrap();
+        let stats = range_authorship(commit_range, false, &lockfile_patterns, false).unwrap();
 
         // Verify: no lines counted since only lockfiles changed
         assert_eq!(stats.authorship_stats.total_commits, 1);
@@ -1389,6 +1476,26 @@ mod tests {
         assert!(!should_ignore_file("test.456", &patterns));
     }
 
+    #[test]
+    fn test_should_ignore_file_gitignore_semantics() {
+        // The last matching pattern wins and `!` re-includes
+        let patterns = vec!["*.lock".to_string(), "!Cargo.lock".to_string()];
+        assert!(should_ignore_file("yarn.lock", &patterns));
+        assert!(!should_ignore_file("Cargo.lock", &patterns));
+        assert!(!should_ignore_file("backend/Cargo.lock", &patterns));
+
+        // A trailing slash only matches directories, at any depth
+        let patterns = vec!["vendor/".to_string()];
+        assert!(should_ignore_file("vendor/lib/a.go", &patterns));
+        assert!(should_ignore_file("web/vendor/b.js", &patterns));
+        assert!(!should_ignore_file("vendor", &patterns));
+
+        // A leading slash anchors to the repository root
+        let patterns = vec!["/build".to_string()];
+        assert!(should_ignore_file("build/out.js", &patterns));
+        assert!(!should_ignore_file("web/build/out.js", &patterns));
+    }
+
     #[test]
     fn test_range_authorship_with_glob_patterns() {
         let tmp_repo = TmpRepo::new().unwrap();
@@ -1440,7 +1547,7 @@ mod tests {
             "*lock.json".to_string(), // Matches package-lock.json
             "*.generated.*".to_string(),
         ];
-        let stats = range_authorship(commit_range, false, &glob_patterns).unwrap();
+        let stats = range_authorship(commit_range, false, &glob_patterns, false).unwrap();
 
         // Should only count the 1 line in main.rs, ignoring 1700 lines in lockfiles and generated files
         assert_eq!(stats.range_stats.git_diff_added_lines, 1);
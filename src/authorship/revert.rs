@@ -0,0 +1,187 @@
+//! Negative attribution for `git revert`.
+//!
+//! A revert removes the lines the reverted commit added, so the AI lines in the reverted commit's
+//! note are the AI lines the revert rolls back. The revert's note records them under
+//! `metadata.reverted`, and its committed event carries the total as `ai_reverted`, so how much
+//! AI-written code ends up rolled back can be tracked like any other commit metric.
+
+use crate::authorship::authorship_log::{RevertedAgentLines, RevertedLines};
+use crate::authorship::authorship_log_serialization::AuthorshipLog;
+use crate::authorship::post_commit::record_commit_metrics;
+use crate::authorship::stats::stats_for_commit_stats;
+use crate::authorship::stats_cache::FileAgentLines;
+use crate::error::GitAiError;
+use crate::git::refs::{get_authorship, notes_add, show_authorship_note};
+use crate::git::repository::Repository;
+use std::collections::{BTreeMap, HashMap};
+
+/// Reverted SHA from the last `This reverts commit <sha>.` line in a commit message
+pub fn reverted_commit(message: &str) -> Option<String> {
+    message.lines().rev().find_map(|line| {
+        let rest = line.trim().strip_prefix("This reverts commit ")?;
+        let sha: String = rest.chars().take_while(char::is_ascii_hexdigit).collect();
+        (sha.len() >= 7).then_some(sha)
+    })
+}
+
+/// The AI lines `revert_sha` removed, when its message names a reverted commit that has a note
+pub fn reverted_lines(
+    repo: &Repository,
+    revert_sha: &str,
+) -> Result<Option<RevertedLines>, GitAiError> {
+    let message = repo.find_commit(revert_sha.to_string())?.body()?;
+    let Some(reverted) = reverted_commit(&message) else {
+        return Ok(None);
+    };
+    let Some(log) = get_authorship(repo, &reverted) else {
+        return Ok(None);
+    };
+    let deleted = deleted_lines_by_file(repo, revert_sha)?;
+    Ok(Some(count_reverted_lines(
+        reverted,
+        &FileAgentLines::from_log(&log),
+        &deleted,
+    )))
+}
+
+/// Attach `metadata.reverted` to a revert commit nothing else wrote a note for, such as one made
+/// by plain `git revert` outside the wrapper, and record its committed event. Returns whether a
+/// note was written.
+pub fn record_revert_note(
+    repo: &Repository,
+    revert_sha: &str,
+    human_author: &str,
+) -> Result<bool, GitAiError> {
+    if show_authorship_note(repo, revert_sha).is_some() {
+        return Ok(false);
+    }
+    let Some(reverted) = reverted_lines(repo, revert_sha)? else {
+        return Ok(false);
+    };
+
+    let mut log = AuthorshipLog::new();
+    log.metadata.base_commit_sha = revert_sha.to_string();
+    log.metadata.reverted = Some(reverted);
+    let note = log
+        .serialize_to_string()
+        .map_err(|_| GitAiError::Generic("Failed to serialize authorship log".to_string()))?;
+    notes_add(repo, revert_sha, &note)?;
+
+    let parent = repo
+        .git(&["rev-parse", &format!("{}^", revert_sha)])
+        .map(|sha| sha.trim().to_string())
+        .unwrap_or_else(|_| "initial".to_string());
+    let stats = stats_for_commit_stats(repo, revert_sha, &[])?;
+    record_commit_metrics(repo, revert_sha, &parent, human_author, &log, &stats, &[]);
+    Ok(true)
+}
+
+/// Lines each file lost in `commit`, from its numstat
+fn deleted_lines_by_file(
+    repo: &Repository,
+    commit: &str,
+) -> Result<HashMap<String, u64>, GitAiError> {
+    let output = repo.git(&["show", "--numstat", "--no-renames", "--format=", commit])?;
+    Ok(output
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(3, '\t');
+            let _added = parts.next()?;
+            // Binary files show "-" and have no lines to attribute
+            let deleted = parts.next()?.parse::<u64>().ok()?;
+            Some((parts.next()?.to_string(), deleted))
+        })
+        .collect())
+}
+
+/// Sum the reverted commit's attested lines per tool and model. A revert can't remove more of a
+/// file than it deletes, which matters when conflicts were resolved by keeping some lines.
+fn count_reverted_lines(
+    reverted: String,
+    ai_lines: &[FileAgentLines],
+    deleted: &HashMap<String, u64>,
+) -> RevertedLines {
+    let mut remaining = deleted.clone();
+    let mut by_agent: BTreeMap<(&str, &str), u64> = BTreeMap::new();
+    for entry in ai_lines {
+        let Some(left) = remaining.get_mut(&entry.path) else {
+            continue;
+        };
+        let lines = entry.lines.min(*left);
+        *left -= lines;
+        *by_agent
+            .entry((entry.tool.as_str(), entry.model.as_str()))
+            .or_default() += lines;
+    }
+
+    let mut agents: Vec<RevertedAgentLines> = by_agent
+        .into_iter()
+        .filter(|(_, lines)| *lines > 0)
+        .map(|((tool, model), lines)| RevertedAgentLines {
+            tool: tool.to_string(),
+            model: model.to_string(),
+            lines: u32::try_from(lines).unwrap_or(u32::MAX),
+        })
+        .collect();
+    agents.sort_by_key(|agent| std::cmp::Reverse(agent.lines));
+    RevertedLines {
+        commit: reverted,
+        ai_lines: agents.iter().map(|a| a.lines).sum(),
+        agents,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn agent_lines(path: &str, tool: &str, model: &str, lines: u64) -> FileAgentLines {
+        FileAgentLines {
+            path: path.to_string(),
+            tool: tool.to_string(),
+            model: model.to_string(),
+            lines,
+        }
+    }
+
+    #[test]
+    fn test_reverted_commit_reads_last_revert_line() {
+        let sha = "0123456789abcdef0123456789abcdef01234567";
+        let message = format!(
+            "Revert \"Add parser\"\n\nThis reverts commit {}.\n\nReason: broke the build\n",
+            sha
+        );
+        assert_eq!(reverted_commit(&message), Some(sha.to_string()));
+        // Merge reverts continue the sentence on the same line
+        let merge = format!(
+            "This reverts commit {}, reversing\nchanges made to deadbeef.",
+            sha
+        );
+        assert_eq!(reverted_commit(&merge), Some(sha.to_string()));
+        assert_eq!(reverted_commit("Fix typo\n\nThis reverts nothing."), None);
+    }
+
+    #[test]
+    fn test_count_reverted_lines_caps_by_deleted_lines() {
+        let ai_lines = vec![
+            agent_lines("a.rs", "cursor", "gpt-5", 10),
+            agent_lines("a.rs", "claude", "sonnet", 4),
+            agent_lines("b.rs", "claude", "sonnet", 3),
+            // Untouched by the revert
+            agent_lines("c.rs", "cursor", "gpt-5", 8),
+        ];
+        let deleted = HashMap::from([("a.rs".to_string(), 12), ("b.rs".to_string(), 3)]);
+
+        let reverted = count_reverted_lines("abc1234".to_string(), &ai_lines, &deleted);
+        assert_eq!(reverted.commit, "abc1234");
+        assert_eq!(reverted.ai_lines, 15);
+        assert_eq!(
+            reverted
+                .agents
+                .iter()
+                .map(|a| (a.tool.as_str(), a.lines))
+                .collect::<Vec<_>>(),
+            vec![("cursor", 10), ("claude", 5)]
+        );
+    }
+}
@@ -0,0 +1,252 @@
+//! Detection of squash merges that happened outside this clone (e.g. the GitHub
+//! "Squash and merge" button), so the AI attribution from the local source
+//! branch can be carried over to the squash commit when it is pulled in.
+
+use crate::authorship::rebase_authorship::rewrite_authorship_after_squash_or_rebase;
+use crate::error::GitAiError;
+use crate::git::authorship_traversal::get_notes_list;
+use crate::git::repository::{Repository, exec_git, exec_git_stdin};
+use crate::utils::debug_log;
+use std::collections::{HashMap, HashSet};
+
+/// Upper bound on incoming commits inspected per pull, so pulling a long-stale
+/// branch doesn't diff every new commit against every local branch
+const MAX_INCOMING_COMMITS: usize = 50;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DetectedSquashMerge {
+    /// Local branch whose changes were squashed
+    pub source_branch: String,
+    pub source_head: String,
+    /// Incoming commit that contains the squashed changes
+    pub squash_commit: String,
+}
+
+/// Find incoming commits in `old_head..new_head` that are squashes of a local
+/// branch carrying authorship notes. A commit matches a branch when its diff
+/// has the same stable patch-id as the branch's diff from its merge base.
+pub fn detect_squash_merges(
+    repo: &Repository,
+    old_head: &str,
+    new_head: &str,
+) -> Result<Vec<DetectedSquashMerge>, GitAiError> {
+    let global_args = repo.global_args_for_exec();
+    let noted: HashSet<String> = get_notes_list(&global_args)?
+        .into_iter()
+        .map(|(_, commit_sha)| commit_sha)
+        .collect();
+
+    let range = format!("{}..{}", old_head, new_head);
+    let incoming: Vec<String> = git_lines(
+        repo,
+        &[
+            "rev-list",
+            "--no-merges",
+            "--reverse",
+            &format!("--max-count={}", MAX_INCOMING_COMMITS),
+            &range,
+        ],
+    )?
+    .into_iter()
+    .filter(|sha| !noted.contains(sha))
+    .collect();
+    if incoming.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut candidates = candidate_branches(repo, new_head, &noted)?;
+    if candidates.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut branch_patch_ids: HashMap<(String, String), Option<String>> = HashMap::new();
+    let mut detected = Vec::new();
+    for commit in incoming {
+        let parent = format!("{}^", commit);
+        let Some(commit_patch_id) = patch_id(repo, &parent, &commit)? else {
+            continue;
+        };
+
+        let mut matched = None;
+        for (index, (branch, tip)) in candidates.iter().enumerate() {
+            let Ok(merge_base) = repo.merge_base(tip.clone(), parent.clone()) else {
+                continue;
+            };
+            let key = (merge_base.clone(), tip.clone());
+            if !branch_patch_ids.contains_key(&key) {
+                let id = patch_id(repo, &merge_base, tip)?;
+                branch_patch_ids.insert(key.clone(), id);
+            }
+            if branch_patch_ids[&key].as_deref() == Some(commit_patch_id.as_str()) {
+                matched = Some(index);
+                detected.push(DetectedSquashMerge {
+                    source_branch: branch.clone(),
+                    source_head: tip.clone(),
+                    squash_commit: commit.clone(),
+                });
+                break;
+            }
+        }
+        if let Some(index) = matched {
+            candidates.remove(index);
+        }
+    }
+
+    Ok(detected)
+}
+
+/// Detect squash merges among newly pulled commits and write a consolidated
+/// authorship note for each one, reconstructed from the source branch's notes
+pub fn propagate_squash_merges(
+    repo: &Repository,
+    target_ref: &str,
+    old_head: &str,
+    new_head: &str,
+) {
+    let detected = match detect_squash_merges(repo, old_head, new_head) {
+        Ok(detected) => detected,
+        Err(e) => {
+            debug_log(&format!("Squash merge detection failed: {}", e));
+            return;
+        }
+    };
+
+    for squash in detected {
+        debug_log(&format!(
+            "Detected squash merge of {} ({}) as {}",
+            squash.source_branch, squash.source_head, squash.squash_commit
+        ));
+        if let Err(e) = rewrite_authorship_after_squash_or_rebase(
+            repo,
+            "",
+            target_ref,
+            &squash.source_head,
+            &squash.squash_commit,
+            true,
+        ) {
+            debug_log(&format!(
+                "Failed to propagate authorship for squash of {}: {}",
+                squash.source_branch, e
+            ));
+        }
+    }
+}
+
+/// Local branches not already merged into `new_head` with at least one noted
+/// commit of their own, as (branch name, tip sha) pairs
+fn candidate_branches(
+    repo: &Repository,
+    new_head: &str,
+    noted: &HashSet<String>,
+) -> Result<Vec<(String, String)>, GitAiError> {
+    let refs = git_lines(
+        repo,
+        &[
+            "for-each-ref",
+            "--format=%(refname:short) %(objectname)",
+            "--no-merged",
+            new_head,
+            "refs/heads",
+        ],
+    )?;
+
+    let mut candidates = Vec::new();
+    for line in refs {
+        let Some((branch, tip)) = line.split_once(' ') else {
+            continue;
+        };
+        let range = format!("{}..{}", new_head, tip);
+        let own_commits = git_lines(repo, &["rev-list", &range])?;
+        if own_commits.iter().any(|sha| noted.contains(sha)) {
+            candidates.push((branch.to_string(), tip.to_string()));
+        }
+    }
+    Ok(candidates)
+}
+
+/// Stable patch-id of the diff between two revisions, or None for an empty diff
+fn patch_id(repo: &Repository, from: &str, to: &str) -> Result<Option<String>, GitAiError> {
+    let mut diff_args = repo.global_args_for_exec();
+    diff_args.extend(
+        [
+            "diff",
+            "--no-color",
+            "--no-ext-diff",
+            "--full-index",
+            from,
+            to,
+        ]
+        .iter()
+        .map(|s| s.to_string()),
+    );
+    let diff = exec_git(&diff_args)?.stdout;
+    if diff.is_empty() {
+        return Ok(None);
+    }
+
+    let mut args = repo.global_args_for_exec();
+    args.extend(["patch-id", "--stable"].iter().map(|s| s.to_string()));
+    let output = exec_git_stdin(&args, &diff)?;
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .next()
+        .map(str::to_string))
+}
+
+fn git_lines(repo: &Repository, args: &[&str]) -> Result<Vec<String>, GitAiError> {
+    let mut full_args = repo.global_args_for_exec();
+    full_args.extend(args.iter().map(|s| s.to_string()));
+    let output = exec_git(&full_args)?;
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_utils::TmpRepo;
+
+    #[test]
+    fn test_detect_squash_merges_matches_branch_by_patch_id() {
+        let tmp_repo = TmpRepo::new().unwrap();
+        let repo = tmp_repo.gitai_repo();
+        tmp_repo.write_file("a.txt", "base\n", true).unwrap();
+        tmp_repo.commit_with_message("base").unwrap();
+        let base = tmp_repo.get_head_commit_sha().unwrap();
+        let main = repo.git(&["symbolic-ref", "--short", "HEAD"]).unwrap();
+
+        repo.git(&["checkout", "-q", "-b", "feature"]).unwrap();
+        tmp_repo.write_file("a.txt", "base\none\n", true).unwrap();
+        tmp_repo.commit_with_message("one").unwrap();
+        tmp_repo
+            .write_file("a.txt", "base\none\ntwo\n", true)
+            .unwrap();
+        tmp_repo.commit_with_message("two").unwrap();
+        let feature_tip = tmp_repo.get_head_commit_sha().unwrap();
+
+        // An unrelated noted branch that must not match
+        repo.git(&["checkout", "-q", "-b", "other", &base]).unwrap();
+        tmp_repo.write_file("b.txt", "other\n", true).unwrap();
+        tmp_repo.commit_with_message("other").unwrap();
+
+        repo.git(&["checkout", "-q", main.trim()]).unwrap();
+        repo.git(&["merge", "--squash", "-q", "feature"]).unwrap();
+        tmp_repo.commit_with_message("feature (#1)").unwrap();
+        let squash = tmp_repo.get_head_commit_sha().unwrap();
+        // Squash commits made elsewhere arrive without a note
+        repo.git(&["notes", "--ref=ai", "remove", &squash]).unwrap();
+
+        let detected = detect_squash_merges(repo, &base, &squash).unwrap();
+        assert_eq!(
+            detected,
+            vec![DetectedSquashMerge {
+                source_branch: "feature".to_string(),
+                source_head: feature_tip,
+                squash_commit: squash,
+            }]
+        );
+    }
+}
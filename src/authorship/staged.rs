@@ -0,0 +1,386 @@
+//! Attribution of the staged (or unstaged) changes before they are committed.
+//!
+//! Attribution is computed against commits, so the index or working tree is snapshotted as a
+//! commit object that no ref points to and the pending checkpoints are projected onto it the same
+//! way post-commit projects them onto the real commit.
+
+use crate::authorship::authorship_log::LineRange;
+use crate::authorship::authorship_log_serialization::AuthorshipLog;
+use crate::authorship::virtual_attribution::VirtualAttributions;
+use crate::commands::hooks::commit_hooks::get_commit_default_author;
+use crate::error::GitAiError;
+use crate::git::repository::{Repository, exec_git, exec_git_stdin_with_env};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Git's empty tree, the parent side of the diff for a repository's first commit
+pub const EMPTY_TREE_HASH: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+
+/// The authorship log the staged changes would get if committed now
+pub struct StagedAuthorship {
+    /// HEAD, or "initial" in a repository without commits
+    pub parent_sha: String,
+    /// The commit object the index or working tree was snapshotted as
+    pub snapshot_sha: String,
+    /// Empty when no checkpoints have been recorded since HEAD
+    pub authorship_log: AuthorshipLog,
+    /// Line numbers (in the staged file) added relative to HEAD, by path
+    pub added_lines: HashMap<String, Vec<u32>>,
+}
+
+pub fn staged_authorship(repo: &Repository) -> Result<StagedAuthorship, GitAiError> {
+    let parent_sha = head_or_initial(repo);
+    let staged_commit = commit_index_snapshot(repo, &parent_sha)?;
+    snapshot_authorship(repo, parent_sha, staged_commit)
+}
+
+/// Like [`staged_authorship`], but for everything in the working tree, including untracked
+/// files that aren't ignored
+pub fn working_tree_authorship(repo: &Repository) -> Result<StagedAuthorship, GitAiError> {
+    let parent_sha = head_or_initial(repo);
+    let worktree_commit = commit_worktree_snapshot(repo, &parent_sha)?;
+    snapshot_authorship(repo, parent_sha, worktree_commit)
+}
+
+fn head_or_initial(repo: &Repository) -> String {
+    match repo.head().and_then(|head| head.target()) {
+        Ok(sha) => sha,
+        Err(_) => "initial".to_string(),
+    }
+}
+
+fn snapshot_authorship(
+    repo: &Repository,
+    parent_sha: String,
+    staged_commit: String,
+) -> Result<StagedAuthorship, GitAiError> {
+    let diff_base = if parent_sha == "initial" {
+        EMPTY_TREE_HASH
+    } else {
+        parent_sha.as_str()
+    };
+    let added_lines = repo.diff_added_lines(diff_base, &staged_commit, None)?;
+
+    let checkpoints = repo
+        .storage
+        .working_log_for_base_commit(&parent_sha)
+        .read_all_checkpoints()?;
+    if checkpoints.is_empty() {
+        return Ok(StagedAuthorship {
+            parent_sha,
+            snapshot_sha: staged_commit,
+            authorship_log: AuthorshipLog::new(),
+            added_lines,
+        });
+    }
+    let pathspecs: HashSet<String> = checkpoints
+        .iter()
+        .flat_map(|cp| cp.entries.iter().map(|e| e.file.clone()))
+        .collect();
+
+    let working_va = VirtualAttributions::from_just_working_log(
+        repo.clone(),
+        parent_sha.clone(),
+        Some(get_commit_default_author(repo, &[])),
+    )?;
+    let (authorship_log, _) = working_va.to_authorship_log_and_initial_working_log(
+        repo,
+        &parent_sha,
+        &staged_commit,
+        Some(&pathspecs),
+    )?;
+
+    Ok(StagedAuthorship {
+        parent_sha,
+        snapshot_sha: staged_commit,
+        authorship_log,
+        added_lines,
+    })
+}
+
+/// Write the index as a tree and wrap it in a commit object that no ref points to
+fn commit_index_snapshot(repo: &Repository, parent_sha: &str) -> Result<String, GitAiError> {
+    let mut args = repo.global_args_for_exec();
+    args.push("write-tree".to_string());
+    let tree = String::from_utf8(exec_git(&args)?.stdout)?
+        .trim()
+        .to_string();
+
+    commit_tree_snapshot(repo, tree, parent_sha, "git-ai staged snapshot")
+}
+
+/// Stage the whole working tree into a throwaway index, leaving the real one untouched, and wrap
+/// the resulting tree in a commit object that no ref points to
+fn commit_worktree_snapshot(repo: &Repository, parent_sha: &str) -> Result<String, GitAiError> {
+    let index_path = repo
+        .path()
+        .join(format!("git-ai-worktree-index-{}", std::process::id()));
+    let env = vec![(
+        "GIT_INDEX_FILE".to_string(),
+        index_path.to_string_lossy().to_string(),
+    )];
+
+    let tree = write_worktree_tree(repo, parent_sha, &env);
+    let _ = std::fs::remove_file(&index_path);
+    commit_tree_snapshot(repo, tree?, parent_sha, "git-ai working tree snapshot")
+}
+
+fn write_worktree_tree(
+    repo: &Repository,
+    parent_sha: &str,
+    env: &Vec<(String, String)>,
+) -> Result<String, GitAiError> {
+    if parent_sha != "initial" {
+        let mut args = repo.global_args_for_exec();
+        args.push("read-tree".to_string());
+        args.push(parent_sha.to_string());
+        exec_git_stdin_with_env(&args, env, &[])?;
+    }
+
+    let mut args = repo.global_args_for_exec();
+    args.push("add".to_string());
+    args.push("-A".to_string());
+    exec_git_stdin_with_env(&args, env, &[])?;
+
+    let mut args = repo.global_args_for_exec();
+    args.push("write-tree".to_string());
+    let output = exec_git_stdin_with_env(&args, env, &[])?;
+    Ok(String::from_utf8(output.stdout)?.trim().to_string())
+}
+
+fn commit_tree_snapshot(
+    repo: &Repository,
+    tree: String,
+    parent_sha: &str,
+    message: &str,
+) -> Result<String, GitAiError> {
+    let mut args = repo.global_args_for_exec();
+    args.push("commit-tree".to_string());
+    args.push(tree);
+    if parent_sha != "initial" {
+        args.push("-p".to_string());
+        args.push(parent_sha.to_string());
+    }
+    args.push("-m".to_string());
+    args.push(message.to_string());
+    Ok(String::from_utf8(exec_git(&args)?.stdout)?
+        .trim()
+        .to_string())
+}
+
+/// AI/human makeup of the staged changes, as served to editors by `git-ai status --staged` and
+/// the daemon's `staged` method
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StagedComposition {
+    pub head: String,
+    pub added_lines: u32,
+    pub ai_lines: u32,
+    pub human_lines: u32,
+    /// Lines per agent session, most lines first
+    pub agents: Vec<AgentLines>,
+    /// Staged files with added lines, by path
+    pub files: Vec<FileComposition>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AgentLines {
+    /// Short hash of the prompt record in the authorship log
+    pub prompt: String,
+    pub tool: String,
+    pub model: String,
+    pub session: String,
+    pub lines: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileComposition {
+    pub path: String,
+    pub added_lines: u32,
+    pub ai_lines: u32,
+    pub human_lines: u32,
+    pub hunks: Vec<AiHunk>,
+}
+
+/// A run of staged lines written by one agent session. Lines are 1-based and inclusive, in the
+/// staged version of the file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AiHunk {
+    pub start_line: u32,
+    pub end_line: u32,
+    pub prompt: String,
+    pub tool: String,
+    pub model: String,
+}
+
+impl StagedComposition {
+    pub fn from_staged(staged: &StagedAuthorship) -> Self {
+        let log = &staged.authorship_log;
+        let mut agent_lines: HashMap<&str, u32> = HashMap::new();
+        let mut files: BTreeMap<&str, FileComposition> = BTreeMap::new();
+
+        for (path, lines) in &staged.added_lines {
+            let added: HashSet<u32> = lines.iter().copied().collect();
+            let mut file = FileComposition {
+                path: path.clone(),
+                added_lines: added.len() as u32,
+                ai_lines: 0,
+                human_lines: 0,
+                hunks: Vec::new(),
+            };
+
+            if let Some(attestation) = log.attestations.iter().find(|a| &a.file_path == path) {
+                for entry in &attestation.entries {
+                    let (tool, model) = log
+                        .metadata
+                        .prompts
+                        .get(&entry.hash)
+                        .map(|p| (p.agent_id.tool.clone(), p.agent_id.model.clone()))
+                        .unwrap_or_else(|| ("unknown".to_string(), "unknown".to_string()));
+                    for range in &entry.line_ranges {
+                        let (start, end) = match range {
+                            LineRange::Single(line) => (*line, *line),
+                            LineRange::Range(start, end) => (*start, *end),
+                        };
+                        let lines = (start..=end).filter(|l| added.contains(l)).count() as u32;
+                        if lines == 0 {
+                            continue;
+                        }
+                        file.ai_lines += lines;
+                        *agent_lines.entry(entry.hash.as_str()).or_default() += lines;
+                        file.hunks.push(AiHunk {
+                            start_line: start,
+                            end_line: end,
+                            prompt: entry.hash.clone(),
+                            tool: tool.clone(),
+                            model: model.clone(),
+                        });
+                    }
+                }
+            }
+
+            file.ai_lines = file.ai_lines.min(file.added_lines);
+            file.human_lines = file.added_lines - file.ai_lines;
+            file.hunks.sort_by_key(|hunk| hunk.start_line);
+            if file.added_lines > 0 {
+                files.insert(path.as_str(), file);
+            }
+        }
+
+        let mut agents: Vec<AgentLines> = agent_lines
+            .into_iter()
+            .map(|(hash, lines)| {
+                let prompt = log.metadata.prompts.get(hash);
+                AgentLines {
+                    prompt: hash.to_string(),
+                    tool: prompt.map_or("unknown", |p| &p.agent_id.tool).to_string(),
+                    model: prompt.map_or("unknown", |p| &p.agent_id.model).to_string(),
+                    session: prompt.map_or("", |p| &p.agent_id.id).to_string(),
+                    lines,
+                }
+            })
+            .collect();
+        agents.sort_by(|a, b| b.lines.cmp(&a.lines).then_with(|| a.prompt.cmp(&b.prompt)));
+
+        let files: Vec<FileComposition> = files.into_values().collect();
+        let added_lines = files.iter().map(|f| f.added_lines).sum();
+        let ai_lines = files.iter().map(|f| f.ai_lines).sum();
+        StagedComposition {
+            head: staged.parent_sha.clone(),
+            added_lines,
+            ai_lines,
+            human_lines: added_lines - ai_lines,
+            agents,
+            files,
+        }
+    }
+}
+
+pub fn staged_composition(repo: &Repository) -> Result<StagedComposition, GitAiError> {
+    Ok(StagedComposition::from_staged(&staged_authorship(repo)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authorship::authorship_log::PromptRecord;
+    use crate::authorship::authorship_log_serialization::{AttestationEntry, FileAttestation};
+    use crate::authorship::working_log::AgentId;
+
+    fn prompt(tool: &str, model: &str, session: &str) -> PromptRecord {
+        PromptRecord {
+            agent_id: AgentId {
+                tool: tool.to_string(),
+                id: session.to_string(),
+                model: model.to_string(),
+            },
+            human_author: None,
+            messages: vec![],
+            total_additions: 0,
+            total_deletions: 0,
+            accepted_lines: 0,
+            overriden_lines: 0,
+            messages_url: None,
+            token_usage: None,
+            turns: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_composition_splits_files_and_hunks_by_agent() {
+        let mut log = AuthorshipLog::new();
+        log.metadata
+            .prompts
+            .insert("aaaaaaa".to_string(), prompt("cursor", "gpt-4o", "s1"));
+        log.metadata.prompts.insert(
+            "bbbbbbb".to_string(),
+            prompt("claude", "claude-sonnet-4", "s2"),
+        );
+        let mut file = FileAttestation::new("src/lib.rs".to_string());
+        file.add_entry(AttestationEntry::new(
+            "bbbbbbb".to_string(),
+            vec![LineRange::Range(8, 9)],
+        ));
+        file.add_entry(AttestationEntry::new(
+            "aaaaaaa".to_string(),
+            vec![LineRange::Range(1, 3), LineRange::Single(10)],
+        ));
+        log.attestations.push(file);
+
+        let staged = StagedAuthorship {
+            parent_sha: "abc".to_string(),
+            snapshot_sha: "def".to_string(),
+            authorship_log: log,
+            added_lines: HashMap::from([
+                ("src/lib.rs".to_string(), (1..=10).collect()),
+                ("README.md".to_string(), vec![1, 2]),
+                ("deleted_only.rs".to_string(), vec![]),
+            ]),
+        };
+        let composition = StagedComposition::from_staged(&staged);
+
+        assert_eq!(composition.added_lines, 12);
+        assert_eq!(composition.ai_lines, 6);
+        assert_eq!(composition.human_lines, 6);
+        let agents: Vec<(&str, u32)> = composition
+            .agents
+            .iter()
+            .map(|a| (a.tool.as_str(), a.lines))
+            .collect();
+        assert_eq!(agents, vec![("cursor", 4), ("claude", 2)]);
+
+        let paths: Vec<&str> = composition.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["README.md", "src/lib.rs"]);
+        assert_eq!(composition.files[0].human_lines, 2);
+        let lib = &composition.files[1];
+        assert_eq!((lib.ai_lines, lib.human_lines), (6, 4));
+        let hunks: Vec<(u32, u32, &str)> = lib
+            .hunks
+            .iter()
+            .map(|h| (h.start_line, h.end_line, h.tool.as_str()))
+            .collect();
+        assert_eq!(
+            hunks,
+            vec![(1, 3, "cursor"), (8, 9, "claude"), (10, 10, "cursor")]
+        );
+    }
+}
@@ -1,5 +1,9 @@
+use crate::authorship::attribution_ignore::effective_ignore_patterns;
 use crate::authorship::diff_ai_accepted::diff_ai_accepted_stats;
+use crate::authorship::stats_cache::with_stats_cache;
+use crate::authorship::token_usage::TokenUsage;
 use crate::authorship::transcript::Message;
+use crate::commands::machine_output::{self, OutputFormat};
 use crate::error::GitAiError;
 use crate::git::refs::get_authorship;
 use crate::git::repository::Repository;
@@ -47,6 +51,9 @@ pub struct CommitStats {
     pub git_diff_added_lines: u32,
     #[serde(default)]
     pub tool_model_breakdown: BTreeMap<String, ToolModelHeadlineStats>,
+    /// Tokens and cost spent by agents while working on this commit, if any agent reported them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_usage: Option<TokenUsage>,
 }
 
 impl Default for CommitStats {
@@ -62,6 +69,34 @@ impl Default for CommitStats {
             git_diff_deleted_lines: 0,
             git_diff_added_lines: 0,
             tool_model_breakdown: BTreeMap::new(),
+            token_usage: None,
+        }
+    }
+}
+
+impl CommitStats {
+    /// Add another commit's numbers to these, totalling the two commits
+    pub fn add(&mut self, other: &CommitStats) {
+        self.human_additions += other.human_additions;
+        self.mixed_additions += other.mixed_additions;
+        self.ai_additions += other.ai_additions;
+        self.ai_accepted += other.ai_accepted;
+        self.total_ai_additions += other.total_ai_additions;
+        self.total_ai_deletions += other.total_ai_deletions;
+        self.time_waiting_for_ai += other.time_waiting_for_ai;
+        self.git_diff_deleted_lines += other.git_diff_deleted_lines;
+        self.git_diff_added_lines += other.git_diff_added_lines;
+        for (key, stats) in &other.tool_model_breakdown {
+            let total = self.tool_model_breakdown.entry(key.clone()).or_default();
+            total.ai_additions += stats.ai_additions;
+            total.mixed_additions += stats.mixed_additions;
+            total.ai_accepted += stats.ai_accepted;
+            total.total_ai_additions += stats.total_ai_additions;
+            total.total_ai_deletions += stats.total_ai_deletions;
+            total.time_waiting_for_ai += stats.time_waiting_for_ai;
+        }
+        if let Some(usage) = &other.token_usage {
+            self.token_usage.get_or_insert_default().add(usage);
         }
     }
 }
@@ -69,7 +104,7 @@ impl Default for CommitStats {
 pub fn stats_command(
     repo: &Repository,
     commit_sha: Option<&str>,
-    json: bool,
+    format: OutputFormat,
     ignore_patterns: &[String],
 ) -> Result<(), GitAiError> {
     let (target, refname) = if let Some(sha) = commit_sha {
@@ -98,13 +133,12 @@ pub fn stats_command(
         target, refname
     ));
 
-    let stats = stats_for_commit_stats(repo, &target, ignore_patterns)?;
+    let stats = cached_stats_for_commit(repo, &target, ignore_patterns)?;
 
-    if json {
-        let json_str = serde_json::to_string(&stats)?;
-        println!("{}", json_str);
-    } else {
+    if format.is_text() {
         write_stats_to_terminal(&stats, true);
+    } else {
+        machine_output::print(format, &stats)?;
     }
 
     Ok(())
@@ -309,6 +343,14 @@ pub fn write_stats_to_terminal(stats: &CommitStats, print: bool) -> String {
         if print {
             println!("{}", ai_acceptance_str);
         }
+        if let Some(usage) = &stats.token_usage {
+            let usage_str = format!("     \x1b[90m{}\x1b[0m", format_token_usage(usage));
+            output.push_str(&usage_str);
+            output.push('\n');
+            if print {
+                println!("{}", usage_str);
+            }
+        }
     }
     return output;
 }
@@ -458,12 +500,36 @@ pub fn write_stats_to_markdown(stats: &CommitStats) -> String {
             ));
         }
     }
+    if let Some(usage) = &stats.token_usage {
+        output.push_str(&format!("- {}\n", format_token_usage(usage)));
+    }
 
     output.push_str("\n</details>");
 
     return output;
 }
 
+/// e.g. "12.3k tokens in / 1.2k out | ~$0.42"
+pub fn format_token_usage(usage: &TokenUsage) -> String {
+    let mut formatted = format!(
+        "{} tokens in / {} out",
+        format_token_count(usage.input_tokens),
+        format_token_count(usage.output_tokens)
+    );
+    if let Some(cost) = usage.cost_usd {
+        formatted.push_str(&format!(" | ~${:.2}", cost));
+    }
+    formatted
+}
+
+fn format_token_count(tokens: u64) -> String {
+    match tokens {
+        0..1_000 => tokens.to_string(),
+        1_000..1_000_000 => format!("{:.1}k", tokens as f64 / 1_000.0),
+        _ => format!("{:.1}M", tokens as f64 / 1_000_000.0),
+    }
+}
+
 /// Calculate commit stats from an authorship log
 /// This helper can work with both fetched and in-memory authorship logs
 pub fn stats_from_authorship_log(
@@ -482,6 +548,7 @@ pub fn stats_from_authorship_log(
         total_ai_deletions: 0,
         time_waiting_for_ai: 0,
         tool_model_breakdown: BTreeMap::new(),
+        token_usage: None,
         git_diff_deleted_lines,
         git_diff_added_lines,
     };
@@ -510,6 +577,13 @@ pub fn stats_from_authorship_log(
             let waiting = calculate_waiting_time(&transcript);
             commit_stats.time_waiting_for_ai += waiting;
             tool_stats.time_waiting_for_ai += waiting;
+
+            if let Some(usage) = &prompt_record.token_usage {
+                commit_stats
+                    .token_usage
+                    .get_or_insert_with(TokenUsage::default)
+                    .add(usage);
+            }
         }
     }
 
@@ -548,6 +622,17 @@ pub fn stats_for_commit_stats(
     repo: &Repository,
     commit_sha: &str,
     ignore_patterns: &[String],
+) -> Result<CommitStats, GitAiError> {
+    let ignore_patterns = effective_ignore_patterns(repo, ignore_patterns);
+    stats_for_commit_with_patterns(repo, commit_sha, &ignore_patterns)
+}
+
+/// `stats_for_commit_stats` with `ignore_patterns` used as given, without adding the
+/// repository's `.gitaiignore` and generated-file patterns
+pub(crate) fn stats_for_commit_with_patterns(
+    repo: &Repository,
+    commit_sha: &str,
+    ignore_patterns: &[String],
 ) -> Result<CommitStats, GitAiError> {
     // Step 1: get the diff between this commit and its parent ON refname (if more than one parent)
     // If initial than everything is additions
@@ -579,6 +664,30 @@ pub fn stats_for_commit_stats(
     ))
 }
 
+/// `stats_for_commit_stats`, answered from the stats cache unless the commit's note changed
+/// since it was last computed. Cache failures fall back to computing the stats.
+pub fn cached_stats_for_commit(
+    repo: &Repository,
+    commit_sha: &str,
+    ignore_patterns: &[String],
+) -> Result<CommitStats, GitAiError> {
+    // Keyed on the effective patterns so editing .gitaiignore invalidates cached stats
+    let ignore_patterns = effective_ignore_patterns(repo, ignore_patterns);
+    let cached = with_stats_cache(repo, |cache| {
+        Ok(cache.commit_stats(commit_sha, &ignore_patterns))
+    });
+    if let Ok(Some(stats)) = cached {
+        return Ok(stats);
+    }
+
+    let stats = stats_for_commit_with_patterns(repo, commit_sha, &ignore_patterns)?;
+    let _ = with_stats_cache(repo, |cache| {
+        cache.set_commit_stats(commit_sha, &ignore_patterns, stats.clone());
+        Ok(())
+    });
+    Ok(stats)
+}
+
 /// Get git diff statistics between commit and its parent
 pub fn get_git_diff_stats(
     repo: &Repository,
@@ -713,6 +822,7 @@ mod tests {
             total_ai_additions: 100,
             total_ai_deletions: 0,
             tool_model_breakdown: BTreeMap::new(),
+            token_usage: None,
         };
 
         let mixed_output = write_stats_to_terminal(&stats, true);
@@ -730,6 +840,7 @@ mod tests {
             total_ai_additions: 100,
             total_ai_deletions: 0,
             tool_model_breakdown: BTreeMap::new(),
+            token_usage: None,
         };
 
         let ai_only_output = write_stats_to_terminal(&ai_stats, true);
@@ -747,6 +858,7 @@ mod tests {
             total_ai_additions: 0,
             total_ai_deletions: 0,
             tool_model_breakdown: BTreeMap::new(),
+            token_usage: None,
         };
 
         let human_only_output = write_stats_to_terminal(&human_stats, true);
@@ -764,6 +876,7 @@ mod tests {
             total_ai_additions: 100,
             total_ai_deletions: 0,
             tool_model_breakdown: BTreeMap::new(),
+            token_usage: None,
         };
 
         let minimal_human_output = write_stats_to_terminal(&minimal_human_stats, true);
@@ -781,6 +894,7 @@ mod tests {
             total_ai_additions: 0,
             total_ai_deletions: 0,
             tool_model_breakdown: BTreeMap::new(),
+            token_usage: None,
         };
 
         let deletion_only_output = write_stats_to_terminal(&deletion_only_stats, true);
@@ -801,6 +915,7 @@ mod tests {
             total_ai_additions: 100,
             total_ai_deletions: 0,
             tool_model_breakdown: BTreeMap::new(),
+            token_usage: None,
         };
 
         let mixed_output = write_stats_to_markdown(&stats);
@@ -818,6 +933,7 @@ mod tests {
             total_ai_additions: 100,
             total_ai_deletions: 0,
             tool_model_breakdown: BTreeMap::new(),
+            token_usage: None,
         };
 
         let ai_only_output = write_stats_to_markdown(&ai_stats);
@@ -835,6 +951,7 @@ mod tests {
             total_ai_additions: 0,
             total_ai_deletions: 0,
             tool_model_breakdown: BTreeMap::new(),
+            token_usage: None,
         };
 
         let human_only_output = write_stats_to_markdown(&human_stats);
@@ -852,6 +969,7 @@ mod tests {
             total_ai_additions: 100,
             total_ai_deletions: 0,
             tool_model_breakdown: BTreeMap::new(),
+            token_usage: None,
         };
 
         let minimal_human_output = write_stats_to_markdown(&minimal_human_stats);
@@ -869,6 +987,7 @@ mod tests {
             total_ai_additions: 0,
             total_ai_deletions: 0,
             tool_model_breakdown: BTreeMap::new(),
+            token_usage: None,
         };
 
         let deletion_only_output = write_stats_to_markdown(&deletion_only_stats);
@@ -1029,9 +1148,9 @@ mod tests {
 
         let head_sha = tmp_repo.get_head_commit_sha().unwrap();
 
-        // Test WITHOUT ignore - should count lockfile
+        // Test WITHOUT ignore (not even the generated-file defaults) - should count lockfile
         let stats_with_lockfile =
-            stats_for_commit_stats(&tmp_repo.gitai_repo(), &head_sha, &[]).unwrap();
+            stats_for_commit_with_patterns(&tmp_repo.gitai_repo(), &head_sha, &[]).unwrap();
         assert_eq!(stats_with_lockfile.git_diff_added_lines, 1001); // 1 source + 1000 lockfile
 
         // Test WITH ignore - should exclude lockfile
@@ -1076,7 +1195,8 @@ mod tests {
         let head_sha = tmp_repo.get_head_commit_sha().unwrap();
 
         // Test WITHOUT ignore - counts all files (1501 lines)
-        let stats_all = stats_for_commit_stats(&tmp_repo.gitai_repo(), &head_sha, &[]).unwrap();
+        let stats_all =
+            stats_for_commit_with_patterns(&tmp_repo.gitai_repo(), &head_sha, &[]).unwrap();
         assert_eq!(stats_all.git_diff_added_lines, 1501);
 
         // Test WITH ignore - only counts README (1 line)
@@ -1116,7 +1236,8 @@ mod tests {
         let head_sha = tmp_repo.get_head_commit_sha().unwrap();
 
         // Test WITHOUT ignore - shows 2000 lines
-        let stats_with = stats_for_commit_stats(&tmp_repo.gitai_repo(), &head_sha, &[]).unwrap();
+        let stats_with =
+            stats_for_commit_with_patterns(&tmp_repo.gitai_repo(), &head_sha, &[]).unwrap();
         assert_eq!(stats_with.git_diff_added_lines, 2000);
 
         // Test WITH ignore - shows 0 lines (lockfile-only commit)
@@ -1128,6 +1249,50 @@ mod tests {
         assert_eq!(stats_without.human_additions, 0);
     }
 
+    #[test]
+    fn test_stats_skip_generated_files_and_gitaiignore() {
+        let tmp_repo = TmpRepo::new().unwrap();
+
+        tmp_repo
+            .write_file("src/lib.rs", "pub fn foo() {}\n", true)
+            .unwrap();
+        tmp_repo
+            .write_file(".gitaiignore", "fixtures/\n", true)
+            .unwrap();
+        tmp_repo
+            .trigger_checkpoint_with_author("test_user")
+            .unwrap();
+        tmp_repo.commit_with_message("Initial commit").unwrap();
+
+        // AI edits source, a lockfile and an ignored fixture
+        tmp_repo
+            .write_file("src/lib.rs", "pub fn foo() {}\npub fn bar() {}\n", true)
+            .unwrap();
+        tmp_repo
+            .write_file("Cargo.lock", "# lock\n".repeat(100).as_str(), true)
+            .unwrap();
+        tmp_repo
+            .write_file("fixtures/data.json", "{}\n".repeat(50).as_str(), true)
+            .unwrap();
+        tmp_repo
+            .trigger_checkpoint_with_ai("Claude", Some("claude-3-sonnet"), Some("cursor"))
+            .unwrap();
+        let authorship_log = tmp_repo.commit_with_message("Add bar").unwrap();
+
+        // Only the source file is attested in the note
+        let attested: Vec<&str> = authorship_log
+            .attestations
+            .iter()
+            .map(|a| a.file_path.as_str())
+            .collect();
+        assert_eq!(attested, vec!["src/lib.rs"]);
+
+        let head_sha = tmp_repo.get_head_commit_sha().unwrap();
+        let stats = stats_for_commit_stats(&tmp_repo.gitai_repo(), &head_sha, &[]).unwrap();
+        assert_eq!(stats.git_diff_added_lines, 1);
+        assert_eq!(stats.ai_additions, 1);
+    }
+
     #[test]
     fn test_stats_empty_ignore_patterns() {
         let tmp_repo = TmpRepo::new().unwrap();
@@ -1201,7 +1366,8 @@ mod tests {
         let head_sha = tmp_repo.get_head_commit_sha().unwrap();
 
         // Test WITHOUT ignore - all files included (2001 lines)
-        let stats_all = stats_for_commit_stats(&tmp_repo.gitai_repo(), &head_sha, &[]).unwrap();
+        let stats_all =
+            stats_for_commit_with_patterns(&tmp_repo.gitai_repo(), &head_sha, &[]).unwrap();
         assert_eq!(stats_all.git_diff_added_lines, 2001);
 
         // Test WITH glob patterns - only source code (1 line)
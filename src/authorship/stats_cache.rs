@@ -0,0 +1,406 @@
+//! Per-commit stats cached under `.git/ai/cache`, so that `git-ai stats` and blame don't re-read
+//! every note on large repositories.
+//!
+//! The cache records the `refs/notes/ai` tip it was built against. When the tip moves, only the
+//! commits whose notes changed between the two tips (`git diff-tree`) are dropped; everything else
+//! is reused. If the old tip can't be diffed (notes were rewritten or pruned) the cache starts over.
+
+use crate::authorship::authorship_log::LineRange;
+use crate::authorship::authorship_log_serialization::AuthorshipLog;
+use crate::authorship::stats::CommitStats;
+use crate::error::GitAiError;
+use crate::git::authorship_traversal::{batch_read_blobs, get_notes_list};
+use crate::git::repository::Repository;
+use crate::utils::debug_log;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Git config key controlling the cache (`git config git-ai.statsCache true`)
+pub const STATS_CACHE_CONFIG_KEY: &str = "git-ai.statsCache";
+
+/// With the default `auto` policy the cache is only written to disk once the repository has at
+/// least this many notes; below that, reading the notes directly is already fast
+pub const AUTO_CACHE_MIN_NOTES: usize = 5_000;
+
+/// Bumped whenever the cached aggregates change shape, which discards older cache files
+const CACHE_VERSION: u32 = 1;
+
+const CACHE_FILE_NAME: &str = "stats.json";
+
+const NOTES_REF: &str = "refs/notes/ai";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatsCachePolicy {
+    /// Persist the cache once the repository has `AUTO_CACHE_MIN_NOTES` notes (the default)
+    Auto,
+    Always,
+    Never,
+}
+
+impl StatsCachePolicy {
+    pub fn parse(value: &str) -> Self {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "true" | "always" | "on" | "yes" | "1" => StatsCachePolicy::Always,
+            "false" | "never" | "off" | "no" | "0" => StatsCachePolicy::Never,
+            _ => StatsCachePolicy::Auto,
+        }
+    }
+
+    pub fn for_repository(repo: &Repository) -> Self {
+        repo.config_get_str(STATS_CACHE_CONFIG_KEY)
+            .ok()
+            .flatten()
+            .map(|value| Self::parse(&value))
+            .unwrap_or(StatsCachePolicy::Auto)
+    }
+}
+
+/// Lines a note attests to one (file, tool, model)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileAgentLines {
+    pub path: String,
+    pub tool: String,
+    pub model: String,
+    pub lines: u64,
+}
+
+impl FileAgentLines {
+    /// One entry per attestation entry in the log, in log order
+    pub fn from_log(log: &AuthorshipLog) -> Vec<FileAgentLines> {
+        let mut result = Vec::new();
+        for file in &log.attestations {
+            for entry in &file.entries {
+                let lines: u64 = entry
+                    .line_ranges
+                    .iter()
+                    .map(|range| match range {
+                        LineRange::Single(_) => 1,
+                        LineRange::Range(start, end) => u64::from(end.saturating_sub(*start)) + 1,
+                    })
+                    .sum();
+                let (tool, model) = log
+                    .metadata
+                    .prompts
+                    .get(&entry.hash)
+                    .map(|p| (p.agent_id.tool.as_str(), p.agent_id.model.as_str()))
+                    .unwrap_or(("unknown", "unknown"));
+                result.push(FileAgentLines {
+                    path: file.file_path.clone(),
+                    tool: tool.to_string(),
+                    model: model.to_string(),
+                    lines,
+                });
+            }
+        }
+        result
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct CachedCommit {
+    /// Attested lines from the commit's note; None until a stats report has needed them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ai_lines: Option<Vec<FileAgentLines>>,
+    /// The note couldn't be parsed; kept so it isn't re-read every time
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    unparseable_note: bool,
+    /// `git-ai stats` results, keyed by the ignore patterns they were computed with
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    stats: BTreeMap<String, CommitStats>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheFile {
+    version: u32,
+    notes_tip: Option<String>,
+    /// Commit sha -> note blob, for every note at `notes_tip`
+    notes: HashMap<String, String>,
+    commits: HashMap<String, CachedCommit>,
+}
+
+pub struct StatsCache {
+    path: PathBuf,
+    policy: StatsCachePolicy,
+    data: CacheFile,
+    dirty: bool,
+}
+
+/// Caches opened by this process, by cache path, so that commands which blame many files only
+/// parse the cache file once
+static OPEN_CACHES: Mutex<Option<HashMap<PathBuf, StatsCache>>> = Mutex::new(None);
+
+/// Run `f` against the repository's cache, brought up to date with the current notes tip, and
+/// write it back if `f` (or the update) changed it.
+///
+/// The cache is locked while `f` runs, so `f` must not call back into `with_stats_cache`;
+/// compute expensive values outside and store them with a second call.
+pub fn with_stats_cache<T>(
+    repo: &Repository,
+    f: impl FnOnce(&mut StatsCache) -> Result<T, GitAiError>,
+) -> Result<T, GitAiError> {
+    let path = repo.storage.cache.join(CACHE_FILE_NAME);
+    let mut open = OPEN_CACHES.lock().unwrap_or_else(|e| e.into_inner());
+    let caches = open.get_or_insert_with(HashMap::new);
+    let cache = caches
+        .entry(path.clone())
+        .or_insert_with(|| StatsCache::load(path, StatsCachePolicy::for_repository(repo)));
+
+    cache.sync(repo)?;
+    let result = f(cache)?;
+    if let Err(e) = cache.save() {
+        debug_log(&format!("Failed to write stats cache: {}", e));
+    }
+    Ok(result)
+}
+
+impl StatsCache {
+    fn load(path: PathBuf, policy: StatsCachePolicy) -> Self {
+        let data = if policy == StatsCachePolicy::Never {
+            None
+        } else {
+            fs::read_to_string(&path)
+                .ok()
+                .and_then(|content| serde_json::from_str::<CacheFile>(&content).ok())
+                .filter(|data| data.version == CACHE_VERSION)
+        };
+        StatsCache {
+            path,
+            policy,
+            data: data.unwrap_or_else(|| CacheFile {
+                version: CACHE_VERSION,
+                ..Default::default()
+            }),
+            dirty: false,
+        }
+    }
+
+    /// Bring the cache up to date with the current notes tip
+    fn sync(&mut self, repo: &Repository) -> Result<(), GitAiError> {
+        let tip = notes_tip(repo);
+        if tip == self.data.notes_tip {
+            return Ok(());
+        }
+
+        let changes = match (&self.data.notes_tip, &tip) {
+            (Some(old), Some(new)) => changed_notes(repo, old, new).ok(),
+            _ => None,
+        };
+        match changes {
+            Some(changes) => {
+                for (commit, blob) in changes {
+                    self.data.commits.remove(&commit);
+                    match blob {
+                        Some(blob) => self.data.notes.insert(commit, blob),
+                        None => self.data.notes.remove(&commit),
+                    };
+                }
+            }
+            None => {
+                self.data.commits.clear();
+                self.data.notes = match tip {
+                    Some(_) => get_notes_list(&repo.global_args_for_exec())?
+                        .into_iter()
+                        .map(|(blob, commit)| (commit, blob))
+                        .collect(),
+                    None => HashMap::new(),
+                };
+            }
+        }
+        self.data.notes_tip = tip;
+        self.dirty = true;
+        Ok(())
+    }
+
+    fn persistent(&self) -> bool {
+        match self.policy {
+            StatsCachePolicy::Always => true,
+            StatsCachePolicy::Never => false,
+            StatsCachePolicy::Auto => self.data.notes.len() >= AUTO_CACHE_MIN_NOTES,
+        }
+    }
+
+    fn save(&mut self) -> Result<(), GitAiError> {
+        if !self.dirty || !self.persistent() {
+            return Ok(());
+        }
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // Write then rename so a concurrent reader never sees a partial file
+        let tmp = self
+            .path
+            .with_extension(format!("json.{}", std::process::id()));
+        fs::write(&tmp, serde_json::to_vec(&self.data)?)?;
+        fs::rename(&tmp, &self.path)?;
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// The blob holding `commit`'s note, if it has one
+    pub fn note_blob(&self, commit: &str) -> Option<&str> {
+        self.data.notes.get(commit).map(String::as_str)
+    }
+
+    /// Parsed notes for those of `commits` that have one, read in one `cat-file --batch`.
+    /// Notes that fail to parse are left out.
+    pub fn authorship_logs(
+        &self,
+        repo: &Repository,
+        commits: &[String],
+    ) -> Result<HashMap<String, AuthorshipLog>, GitAiError> {
+        let (shas, blobs): (Vec<&String>, Vec<String>) = commits
+            .iter()
+            .filter_map(|sha| self.note_blob(sha).map(|blob| (sha, blob.to_string())))
+            .unzip();
+        let contents = batch_read_blobs(&repo.global_args_for_exec(), &blobs)?;
+        Ok(shas
+            .into_iter()
+            .zip(contents)
+            .filter_map(|(sha, content)| {
+                AuthorshipLog::deserialize_from_string(&content)
+                    .ok()
+                    .map(|log| (sha.clone(), log))
+            })
+            .collect())
+    }
+
+    /// Attested lines per commit for those of `commits` that have a parseable note. Notes not
+    /// seen before are read in one batch and added to the cache.
+    pub fn ai_lines(
+        &mut self,
+        repo: &Repository,
+        commits: &[String],
+    ) -> Result<HashMap<String, Vec<FileAgentLines>>, GitAiError> {
+        let missing: Vec<String> = commits
+            .iter()
+            .filter(|sha| self.data.notes.contains_key(*sha))
+            .filter(|sha| {
+                self.data
+                    .commits
+                    .get(*sha)
+                    .is_none_or(|cached| cached.ai_lines.is_none())
+            })
+            .cloned()
+            .collect();
+        if !missing.is_empty() {
+            let logs = self.authorship_logs(repo, &missing)?;
+            for sha in missing {
+                let log = logs.get(&sha);
+                let cached = self.data.commits.entry(sha).or_default();
+                cached.ai_lines = Some(log.map(FileAgentLines::from_log).unwrap_or_default());
+                cached.unparseable_note = log.is_none();
+            }
+            self.dirty = true;
+        }
+
+        Ok(commits
+            .iter()
+            .filter_map(|sha| {
+                let cached = self.data.commits.get(sha)?;
+                if cached.unparseable_note || !self.data.notes.contains_key(sha) {
+                    return None;
+                }
+                Some((sha.clone(), cached.ai_lines.clone()?))
+            })
+            .collect())
+    }
+
+    pub fn commit_stats(&self, commit: &str, ignore_patterns: &[String]) -> Option<CommitStats> {
+        self.data
+            .commits
+            .get(commit)?
+            .stats
+            .get(&ignore_patterns.join("\n"))
+            .cloned()
+    }
+
+    pub fn set_commit_stats(
+        &mut self,
+        commit: &str,
+        ignore_patterns: &[String],
+        stats: CommitStats,
+    ) {
+        self.data
+            .commits
+            .entry(commit.to_string())
+            .or_default()
+            .stats
+            .insert(ignore_patterns.join("\n"), stats);
+        self.dirty = true;
+    }
+}
+
+fn notes_tip(repo: &Repository) -> Option<String> {
+    repo.git(&["rev-parse", "--verify", "--quiet", NOTES_REF])
+        .ok()
+        .map(|sha| sha.trim().to_string())
+        .filter(|sha| !sha.is_empty())
+}
+
+/// Notes added, changed or removed between two notes commits, as commit sha -> new blob (None
+/// when the note was removed)
+fn changed_notes(
+    repo: &Repository,
+    old_tip: &str,
+    new_tip: &str,
+) -> Result<Vec<(String, Option<String>)>, GitAiError> {
+    let output = repo.git(&["diff-tree", "-r", "--no-renames", old_tip, new_tip])?;
+    Ok(parse_diff_tree(&output))
+}
+
+/// Parse `git diff-tree -r` raw lines (`:<mode> <mode> <old> <new> <status>\t<path>`) on a notes
+/// tree, where paths are commit shas with optional `ab/` fanout
+fn parse_diff_tree(output: &str) -> Vec<(String, Option<String>)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (meta, path) = line.strip_prefix(':')?.split_once('\t')?;
+            let fields: Vec<&str> = meta.split_whitespace().collect();
+            let [_, _, _, new_blob, status] = fields[..] else {
+                return None;
+            };
+            let commit = path.replace('/', "");
+            let blob = (!status.starts_with('D')).then(|| new_blob.to_string());
+            Some((commit, blob))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_diff_tree_handles_fanout_and_deletions() {
+        let output = "\
+:000000 100644 0000000000000000000000000000000000000000 1111111111111111111111111111111111111111 A\tab/cdef
+:100644 100644 2222222222222222222222222222222222222222 3333333333333333333333333333333333333333 M\t0123abcd
+:100644 000000 4444444444444444444444444444444444444444 0000000000000000000000000000000000000000 D\tff/00
+";
+        assert_eq!(
+            parse_diff_tree(output),
+            vec![
+                (
+                    "abcdef".to_string(),
+                    Some("1111111111111111111111111111111111111111".to_string())
+                ),
+                (
+                    "0123abcd".to_string(),
+                    Some("3333333333333333333333333333333333333333".to_string())
+                ),
+                ("ff00".to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_policy_parse() {
+        assert_eq!(StatsCachePolicy::parse("true"), StatsCachePolicy::Always);
+        assert_eq!(StatsCachePolicy::parse("Never"), StatsCachePolicy::Never);
+        assert_eq!(StatsCachePolicy::parse("auto"), StatsCachePolicy::Auto);
+        assert_eq!(StatsCachePolicy::parse("bogus"), StatsCachePolicy::Auto);
+    }
+}
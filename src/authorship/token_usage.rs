@@ -0,0 +1,193 @@
+//! Token counts and estimated cost of agent interactions.
+//!
+//! Agents that expose usage report running totals for the whole session. Presets store those
+//! totals in the checkpoint's `agent_metadata`. Each AI checkpoint then records the usage spent
+//! since the session's previous checkpoint, so summing checkpoints, notes or metric events never
+//! counts a token twice.
+
+use crate::authorship::working_log::{AgentId, Checkpoint};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// `agent_metadata` keys holding the session's running totals
+pub const INPUT_TOKENS_KEY: &str = "usage_input_tokens";
+pub const OUTPUT_TOKENS_KEY: &str = "usage_output_tokens";
+pub const COST_USD_KEY: &str = "usage_cost_usd";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    /// Prompt tokens, including tokens read from or written to a prompt cache
+    #[serde(default)]
+    pub input_tokens: u64,
+    /// Completion tokens
+    #[serde(default)]
+    pub output_tokens: u64,
+    /// Estimated cost in US dollars, when the agent reports one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
+}
+
+impl TokenUsage {
+    pub fn total_tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total_tokens() == 0 && self.cost_usd.is_none_or(|cost| cost == 0.0)
+    }
+
+    pub fn add(&mut self, other: &TokenUsage) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cost_usd = match (self.cost_usd, other.cost_usd) {
+            (None, None) => None,
+            (a, b) => Some(a.unwrap_or(0.0) + b.unwrap_or(0.0)),
+        };
+    }
+
+    /// Usage added since `previous`, both being running totals for the same session. Totals
+    /// that went backwards mean the agent restarted its count, so everything is new.
+    pub fn since(&self, previous: &TokenUsage) -> TokenUsage {
+        if self.input_tokens < previous.input_tokens || self.output_tokens < previous.output_tokens
+        {
+            return *self;
+        }
+        TokenUsage {
+            input_tokens: self.input_tokens - previous.input_tokens,
+            output_tokens: self.output_tokens - previous.output_tokens,
+            cost_usd: self
+                .cost_usd
+                .map(|cost| (cost - previous.cost_usd.unwrap_or(0.0)).max(0.0)),
+        }
+    }
+
+    /// Running totals stored in `metadata`, if the preset recorded any
+    pub fn from_metadata(metadata: &HashMap<String, String>) -> Option<TokenUsage> {
+        let parse_u64 = |key: &str| metadata.get(key).and_then(|v| v.parse::<u64>().ok());
+        let input_tokens = parse_u64(INPUT_TOKENS_KEY);
+        let output_tokens = parse_u64(OUTPUT_TOKENS_KEY);
+        let cost_usd = metadata
+            .get(COST_USD_KEY)
+            .and_then(|v| v.parse::<f64>().ok())
+            .filter(|cost| cost.is_finite());
+        if input_tokens.is_none() && output_tokens.is_none() && cost_usd.is_none() {
+            return None;
+        }
+        Some(TokenUsage {
+            input_tokens: input_tokens.unwrap_or(0),
+            output_tokens: output_tokens.unwrap_or(0),
+            cost_usd,
+        })
+    }
+
+    /// Store these running totals in `metadata`
+    pub fn write_to_metadata(&self, metadata: &mut HashMap<String, String>) {
+        metadata.insert(INPUT_TOKENS_KEY.to_string(), self.input_tokens.to_string());
+        metadata.insert(
+            OUTPUT_TOKENS_KEY.to_string(),
+            self.output_tokens.to_string(),
+        );
+        if let Some(cost) = self.cost_usd {
+            metadata.insert(COST_USD_KEY.to_string(), cost.to_string());
+        }
+    }
+}
+
+/// Usage spent in the interaction that produced `checkpoint`: its session totals minus the
+/// totals at the session's latest entry in `previous_checkpoints`, or else `fallback_totals`
+/// (the totals last stored for the session, e.g. before the previous commit).
+pub fn interaction_usage(
+    checkpoint: &Checkpoint,
+    previous_checkpoints: &[Checkpoint],
+    fallback_totals: Option<TokenUsage>,
+) -> Option<TokenUsage> {
+    let agent_id = checkpoint.agent_id.as_ref()?;
+    let totals = TokenUsage::from_metadata(checkpoint.agent_metadata.as_ref()?)?;
+    let previous = previous_checkpoints
+        .iter()
+        .rev()
+        .find(|previous| same_session(previous.agent_id.as_ref(), agent_id))
+        .and_then(|previous| previous.agent_metadata.as_ref())
+        .and_then(TokenUsage::from_metadata)
+        .or(fallback_totals)
+        .unwrap_or_default();
+    Some(totals.since(&previous)).filter(|usage| !usage.is_empty())
+}
+
+fn same_session(other: Option<&AgentId>, agent_id: &AgentId) -> bool {
+    other.is_some_and(|other| other.tool == agent_id.tool && other.id == agent_id.id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authorship::working_log::CheckpointKind;
+
+    fn checkpoint(session: &str, totals: Option<TokenUsage>) -> Checkpoint {
+        let mut checkpoint = Checkpoint::new(
+            CheckpointKind::AiAgent,
+            String::new(),
+            "ai".to_string(),
+            vec![],
+        );
+        checkpoint.agent_id = Some(AgentId {
+            tool: "claude".to_string(),
+            id: session.to_string(),
+            model: "claude-sonnet-4".to_string(),
+        });
+        checkpoint.agent_metadata = totals.map(|totals| {
+            let mut metadata = HashMap::new();
+            totals.write_to_metadata(&mut metadata);
+            metadata
+        });
+        checkpoint
+    }
+
+    fn usage(input_tokens: u64, output_tokens: u64, cost_usd: Option<f64>) -> TokenUsage {
+        TokenUsage {
+            input_tokens,
+            output_tokens,
+            cost_usd,
+        }
+    }
+
+    #[test]
+    fn test_metadata_roundtrip() {
+        let totals = usage(1500, 200, Some(0.25));
+        let mut metadata = HashMap::new();
+        totals.write_to_metadata(&mut metadata);
+        assert_eq!(TokenUsage::from_metadata(&metadata), Some(totals));
+        assert_eq!(TokenUsage::from_metadata(&HashMap::new()), None);
+    }
+
+    #[test]
+    fn test_interaction_usage_is_the_delta_since_the_sessions_last_checkpoint() {
+        let previous = vec![
+            checkpoint("s1", Some(usage(1000, 100, Some(0.1)))),
+            checkpoint("s2", Some(usage(9000, 900, None))),
+        ];
+        let current = checkpoint("s1", Some(usage(1600, 150, Some(0.15))));
+        let delta = interaction_usage(&current, &previous, None).unwrap();
+        assert_eq!(delta.input_tokens, 600);
+        assert_eq!(delta.output_tokens, 50);
+        assert!((delta.cost_usd.unwrap() - 0.05).abs() < 1e-9);
+
+        // First checkpoint since the last commit falls back to the stored totals
+        let delta = interaction_usage(&current, &[], Some(usage(1500, 120, None))).unwrap();
+        assert_eq!((delta.input_tokens, delta.output_tokens), (100, 30));
+
+        // Nothing new, nothing recorded
+        assert_eq!(interaction_usage(&current, &[current.clone()], None), None);
+        // Agents that don't expose usage record nothing
+        assert_eq!(
+            interaction_usage(&checkpoint("s1", None), &previous, None),
+            None
+        );
+    }
+
+    #[test]
+    fn test_restarted_counts_are_all_new() {
+        let totals = usage(50, 5, None);
+        assert_eq!(totals.since(&usage(1000, 100, None)), totals);
+    }
+}
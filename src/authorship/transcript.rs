@@ -117,6 +117,14 @@ impl AiTranscript {
         &self.messages
     }
 
+    /// Text of the most recent user message, the prompt the agent is currently answering
+    pub fn last_user_message(&self) -> Option<&str> {
+        self.messages.iter().rev().find_map(|msg| match msg {
+            Message::User { text, .. } => Some(text.as_str()),
+            _ => None,
+        })
+    }
+
     /// Filter out tool use messages
     #[allow(dead_code)]
     pub fn without_tool_use(&self) -> Self {
@@ -1,11 +1,14 @@
 use crate::authorship::attribution_tracker::{
-    Attribution, LineAttribution, line_attributions_to_attributions,
+    Attribution, LineAttribution, line_attributions_to_attributions, partial_line_char_ranges,
 };
 use crate::authorship::authorship_log::{LineRange, PromptRecord};
+use crate::authorship::token_usage::TokenUsage;
 use crate::authorship::working_log::CheckpointKind;
 use crate::commands::blame::{GitAiBlameOptions, OLDEST_AI_BLAME_DATE};
+use crate::config::Config;
 use crate::error::GitAiError;
 use crate::git::repository::Repository;
+use sha2::{Digest, Sha256};
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -17,6 +20,9 @@ pub struct VirtualAttributions {
     pub attributions: HashMap<String, (Vec<Attribution>, Vec<LineAttribution>)>,
     // Maps file path -> file content
     file_contents: HashMap<String, String>,
+    // Maps file path -> character attributions recorded by the last checkpoint, kept only while the
+    // working copy still matches that checkpoint. Used to find partly AI-written lines.
+    checkpoint_char_attributions: HashMap<String, Vec<Attribution>>,
     // Prompt records mapping prompt_id -> (commit_sha -> PromptRecord)
     // Same prompt can appear in multiple commits, allowing us to track and sort them
     pub prompts: BTreeMap<String, BTreeMap<String, PromptRecord>>,
@@ -43,6 +49,7 @@ impl VirtualAttributions {
             base_commit,
             attributions: HashMap::new(),
             file_contents: HashMap::new(),
+            checkpoint_char_attributions: HashMap::new(),
             prompts: BTreeMap::new(),
             ts,
             blame_start_commit,
@@ -82,7 +89,7 @@ impl VirtualAttributions {
             return Ok(());
         }
 
-        // Load prompts in parallel using the established MAX_CONCURRENT pattern
+        // Load prompts in parallel bounded by max_concurrency
         let prompts = smol::block_on(async { self.load_prompts_concurrent(&missing_ids).await })?;
 
         // Insert loaded prompts into our map
@@ -97,14 +104,12 @@ impl VirtualAttributions {
         Ok(())
     }
 
-    /// Load multiple prompts concurrently using MAX_CONCURRENT limit
+    /// Load multiple prompts concurrently, up to the configured max_concurrency
     async fn load_prompts_concurrent(
         &self,
         missing_ids: &[String],
     ) -> Result<Vec<(String, String, PromptRecord)>, GitAiError> {
-        const MAX_CONCURRENT: usize = 30;
-
-        let semaphore = Arc::new(smol::lock::Semaphore::new(MAX_CONCURRENT));
+        let semaphore = Arc::new(smol::lock::Semaphore::new(Config::get().max_concurrency()));
         let mut tasks = Vec::new();
 
         for missing_id in missing_ids {
@@ -178,9 +183,7 @@ impl VirtualAttributions {
 
     /// Add multiple pathspecs concurrently
     async fn add_pathspecs_concurrent(&mut self, pathspecs: &[String]) -> Result<(), GitAiError> {
-        const MAX_CONCURRENT: usize = 30;
-
-        let semaphore = Arc::new(smol::lock::Semaphore::new(MAX_CONCURRENT));
+        let semaphore = Arc::new(smol::lock::Semaphore::new(Config::get().max_concurrency()));
         let mut tasks = Vec::new();
 
         for pathspec in pathspecs {
@@ -307,10 +310,12 @@ impl VirtualAttributions {
             HashMap::new();
         let mut prompts = BTreeMap::new();
         let mut file_contents: HashMap<String, String> = HashMap::new();
+        let mut checkpoint_char_attributions: HashMap<String, Vec<Attribution>> = HashMap::new();
 
         // Track additions and deletions per session_id for metrics
         let mut session_additions: HashMap<String, u32> = HashMap::new();
         let mut session_deletions: HashMap<String, u32> = HashMap::new();
+        let mut session_token_usage: HashMap<String, TokenUsage> = HashMap::new();
 
         // Add prompts from INITIAL attributions
         // These are uncommitted prompts, so we use an empty string as the commit_sha
@@ -364,6 +369,8 @@ impl VirtualAttributions {
                     accepted_lines: 0,
                     overriden_lines: 0,
                     messages_url: None,
+                    token_usage: None,
+                    turns: Vec::new(),
                 };
 
                 prompts
@@ -376,6 +383,12 @@ impl VirtualAttributions {
                     checkpoint.line_stats.additions;
                 *session_deletions.entry(author_id.clone()).or_insert(0) +=
                     checkpoint.line_stats.deletions;
+                if let Some(usage) = &checkpoint.token_usage {
+                    session_token_usage
+                        .entry(author_id.clone())
+                        .or_default()
+                        .add(usage);
+                }
             }
 
             // Collect attributions from checkpoint entries
@@ -396,6 +409,17 @@ impl VirtualAttributions {
                 let file_content = file_contents.get(&entry.file).cloned().unwrap_or_default();
                 let char_attrs = line_attributions_to_attributions(&line_attrs, &file_content, 0);
 
+                // The checkpoint's own character attributions stay exact while the file is
+                // unchanged since then
+                if !entry.attributions.is_empty()
+                    && entry.blob_sha == format!("{:x}", Sha256::digest(file_content.as_bytes()))
+                {
+                    checkpoint_char_attributions
+                        .insert(entry.file.clone(), entry.attributions.clone());
+                } else {
+                    checkpoint_char_attributions.remove(&entry.file);
+                }
+
                 attributions.insert(entry.file.clone(), (char_attrs, line_attrs));
             }
         }
@@ -408,11 +432,20 @@ impl VirtualAttributions {
             &session_deletions,
         );
 
+        // Usage counts towards the commit it was spent on, so prompts carried over in INITIAL
+        // start again from what their checkpoints since then recorded
+        for (session_id, commits) in prompts.iter_mut() {
+            for prompt_record in commits.values_mut() {
+                prompt_record.token_usage = session_token_usage.get(session_id).copied();
+            }
+        }
+
         Ok(VirtualAttributions {
             repo,
             base_commit,
             attributions,
             file_contents,
+            checkpoint_char_attributions,
             prompts,
             ts: 0,
             blame_start_commit: None,
@@ -472,6 +505,7 @@ impl VirtualAttributions {
             base_commit,
             attributions,
             file_contents,
+            checkpoint_char_attributions: HashMap::new(),
             prompts: BTreeMap::new(),
             ts,
             blame_start_commit: None,
@@ -491,6 +525,7 @@ impl VirtualAttributions {
             base_commit,
             attributions,
             file_contents,
+            checkpoint_char_attributions: HashMap::new(),
             prompts,
             ts,
             blame_start_commit: None,
@@ -822,9 +857,15 @@ impl VirtualAttributions {
             // so we need to convert to commit coordinates before comparing with committed hunks
             let mut committed_lines_map: StdHashMap<String, Vec<u32>> = StdHashMap::new();
             let mut uncommitted_lines_map: StdHashMap<String, Vec<u32>> = StdHashMap::new();
+            // Committed lines per author as (commit line, working directory line), for partial-line spans
+            let mut committed_workdir_lines: StdHashMap<String, Vec<(u32, u32)>> =
+                StdHashMap::new();
 
-            // Get the committed hunks for this file (if any) - these are in commit coordinates
-            let file_committed_hunks = committed_hunks.get(file_path);
+            // Get the committed lines for this file (if any) - these are in commit coordinates
+            let file_committed_lines: HashSet<u32> = committed_hunks
+                .get(file_path)
+                .map(|hunks| hunks.iter().flat_map(|hunk| hunk.expand()).collect())
+                .unwrap_or_default();
 
             for line_attr in line_attrs {
                 // Check each line individually
@@ -842,18 +883,13 @@ impl VirtualAttributions {
                     } else {
                         // Convert working directory line number to commit line number
                         // by subtracting the count of unstaged lines before this line
-                        let adjustment = unstaged_lines
-                            .iter()
-                            .filter(|&&l| l < workdir_line_num)
-                            .count() as u32;
+                        // (sorted, so a binary search rather than a scan per line)
+                        let adjustment =
+                            unstaged_lines.partition_point(|&l| l < workdir_line_num) as u32;
                         let commit_line_num = workdir_line_num - adjustment;
 
                         // Check if this commit line number is in any committed hunk
-                        let is_committed = if let Some(hunks) = file_committed_hunks {
-                            hunks.iter().any(|hunk| hunk.contains(commit_line_num))
-                        } else {
-                            false
-                        };
+                        let is_committed = file_committed_lines.contains(&commit_line_num);
 
                         if is_committed {
                             // Line was committed in this commit (use commit coordinates)
@@ -861,6 +897,10 @@ impl VirtualAttributions {
                                 .entry(line_attr.author_id.clone())
                                 .or_default()
                                 .push(commit_line_num);
+                            committed_workdir_lines
+                                .entry(line_attr.author_id.clone())
+                                .or_default()
+                                .push((commit_line_num, workdir_line_num));
                         } else {
                         }
                         // Note: Lines that are neither unstaged nor in committed_hunks are lines that
@@ -921,6 +961,35 @@ impl VirtualAttributions {
                         ));
                     }
 
+                    // Lines a human partly rewrote keep the character spans the AI still owns
+                    if let (Some(content), Some(char_attrs), Some(line_pairs)) = (
+                        self.file_contents.get(file_path),
+                        self.checkpoint_char_attributions.get(file_path),
+                        committed_workdir_lines.get(&author_id),
+                    ) {
+                        let workdir_lines: Vec<u32> = line_pairs.iter().map(|(_, w)| *w).collect();
+                        let to_commit_line: StdHashMap<u32, u32> =
+                            line_pairs.iter().map(|(c, w)| (*w, *c)).collect();
+                        let mut char_ranges = partial_line_char_ranges(
+                            char_attrs,
+                            content,
+                            &workdir_lines,
+                            &author_id,
+                        );
+                        for range in &mut char_ranges {
+                            range.line = to_commit_line[&range.line];
+                        }
+                        if !char_ranges.is_empty() {
+                            char_ranges.sort();
+                            authorship_log
+                                .metadata
+                                .partial_lines
+                                .entry(file_path.clone())
+                                .or_default()
+                                .insert(author_id.clone(), char_ranges);
+                        }
+                    }
+
                     let entry =
                         crate::authorship::authorship_log_serialization::AttestationEntry::new(
                             author_id, ranges,
@@ -1045,11 +1114,12 @@ impl VirtualAttributions {
                 continue;
             }
 
-            // Get the committed hunks for this file (if any)
-            let file_committed_hunks = match committed_hunks.get(file_path) {
-                Some(hunks) => hunks,
-                None => continue, // No committed hunks for this file, skip
-            };
+            // Get the committed lines for this file (if any)
+            let file_committed_lines: std::collections::HashSet<u32> =
+                match committed_hunks.get(file_path) {
+                    Some(hunks) => hunks.iter().flat_map(|hunk| hunk.expand()).collect(),
+                    None => continue, // No committed hunks for this file, skip
+                };
 
             // Map author_id -> line numbers (in commit coordinates)
             let mut committed_lines_map: StdHashMap<String, Vec<u32>> = StdHashMap::new();
@@ -1059,9 +1129,7 @@ impl VirtualAttributions {
                 // are already in the right coordinates (working log coordinates = commit coordinates)
                 for line_num in line_attr.start_line..=line_attr.end_line {
                     // Check if this line is in any committed hunk
-                    let is_committed = file_committed_hunks
-                        .iter()
-                        .any(|hunk| hunk.contains(line_num));
+                    let is_committed = file_committed_lines.contains(&line_num);
 
                     if is_committed {
                         committed_lines_map
@@ -1336,6 +1404,7 @@ pub fn merge_attributions_favoring_first(
         base_commit,
         attributions: HashMap::new(),
         file_contents: HashMap::new(),
+        checkpoint_char_attributions: HashMap::new(),
         prompts: merged_prompts,
         ts,
         blame_start_commit: None,
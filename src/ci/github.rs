@@ -1,9 +1,11 @@
 use crate::ci::ci_context::{CiContext, CiEvent};
+use crate::ci::pr_metrics::MergedPullRequest;
 use crate::error::GitAiError;
 use crate::git::repository::exec_git;
 use crate::git::repository::find_repository_in_path;
 use serde::{Deserialize, Serialize};
 use std::fs;
+use std::io::Write;
 use std::path::PathBuf;
 
 const GITHUB_CI_TEMPLATE_YAML: &str = include_str!("workflow_templates/github.yaml");
@@ -21,6 +23,10 @@ struct GithubCiPullRequest {
     head: GithubCiPullRequestReference,
     merged: bool,
     merge_commit_sha: Option<String>,
+    #[serde(default)]
+    created_at: Option<String>,
+    #[serde(default)]
+    merged_at: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
@@ -28,7 +34,9 @@ struct GithubCiPullRequestReference {
     #[serde(rename = "ref")]
     ref_name: String,
     sha: String,
-    repo: GithubCiRepository,
+    /// Null when the fork a pull request came from has been deleted
+    #[serde(default)]
+    repo: Option<GithubCiRepository>,
 }
 
 #[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
@@ -61,7 +69,11 @@ pub fn get_github_ci_context() -> Result<Option<CiContext>, GitAiError> {
     let head_ref = pull_request.head.ref_name;
     let head_sha = pull_request.head.sha;
     let base_ref = pull_request.base.ref_name;
-    let clone_url = pull_request.base.repo.clone_url.clone();
+    let clone_url = pull_request
+        .base
+        .repo
+        .map(|repo| repo.clone_url)
+        .unwrap_or_default();
 
     let clone_dir = "git-ai-ci-clone".to_string();
 
@@ -112,6 +124,315 @@ pub fn get_github_ci_context() -> Result<Option<CiContext>, GitAiError> {
     }))
 }
 
+/// The pull request an Actions run was triggered for, read from the event payload
+#[derive(Debug, Clone, PartialEq)]
+pub struct GithubPullRequestRefs {
+    pub number: u32,
+    pub base_ref: String,
+    pub base_sha: String,
+    pub head_sha: String,
+}
+
+impl From<GithubCiPullRequest> for GithubPullRequestRefs {
+    fn from(pull_request: GithubCiPullRequest) -> Self {
+        GithubPullRequestRefs {
+            number: pull_request.number,
+            base_ref: pull_request.base.ref_name,
+            base_sha: pull_request.base.sha,
+            head_sha: pull_request.head.sha,
+        }
+    }
+}
+
+pub fn get_github_pull_request_refs() -> Result<Option<GithubPullRequestRefs>, GitAiError> {
+    let env_event_name = std::env::var("GITHUB_EVENT_NAME").unwrap_or_default();
+    if env_event_name != "pull_request" && env_event_name != "pull_request_target" {
+        return Ok(None);
+    }
+    let Ok(env_event_path) = std::env::var("GITHUB_EVENT_PATH") else {
+        return Ok(None);
+    };
+
+    let event_payload =
+        serde_json::from_str::<GithubCiEventPayload>(&std::fs::read_to_string(env_event_path)?)
+            .unwrap_or_default();
+    Ok(event_payload.pull_request.map(GithubPullRequestRefs::from))
+}
+
+/// The merged pull request an Actions run was triggered for, if any, with the timestamps the
+/// `pr_merged` metric needs
+pub fn get_github_merged_pull_request() -> Result<Option<MergedPullRequest>, GitAiError> {
+    let env_event_name = std::env::var("GITHUB_EVENT_NAME").unwrap_or_default();
+    if env_event_name != "pull_request" && env_event_name != "pull_request_target" {
+        return Ok(None);
+    }
+    let Ok(env_event_path) = std::env::var("GITHUB_EVENT_PATH") else {
+        return Ok(None);
+    };
+
+    let event_payload =
+        serde_json::from_str::<GithubCiEventPayload>(&std::fs::read_to_string(env_event_path)?)
+            .unwrap_or_default();
+    Ok(event_payload
+        .pull_request
+        .and_then(merged_pull_request_from_payload))
+}
+
+fn merged_pull_request_from_payload(
+    pull_request: GithubCiPullRequest,
+) -> Option<MergedPullRequest> {
+    if !pull_request.merged {
+        return None;
+    }
+    let timestamp = |value: Option<String>| {
+        chrono::DateTime::parse_from_rfc3339(&value?)
+            .ok()
+            .map(|t| t.timestamp())
+    };
+    Some(MergedPullRequest {
+        merge_commit_sha: pull_request.merge_commit_sha?,
+        base_sha: pull_request.base.sha,
+        head_sha: pull_request.head.sha,
+        head_ref: Some(pull_request.head.ref_name),
+        number: Some(pull_request.number),
+        opened_at: timestamp(pull_request.created_at),
+        merged_at: timestamp(pull_request.merged_at),
+    })
+}
+
+/// Append markdown to the job summary. Returns false when not running under GitHub Actions.
+pub fn write_github_step_summary(markdown: &str) -> Result<bool, GitAiError> {
+    let summary_path = std::env::var("GITHUB_STEP_SUMMARY").unwrap_or_default();
+    if summary_path.is_empty() {
+        return Ok(false);
+    }
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&summary_path)?;
+    writeln!(file, "{}", markdown)?;
+    Ok(true)
+}
+
+#[derive(Debug, Deserialize)]
+struct GithubIssueComment {
+    id: u64,
+    #[serde(default)]
+    body: Option<String>,
+}
+
+/// REST API access for one repository, authenticated with GITHUB_TOKEN
+struct GithubApi {
+    api_url: String,
+    repository: String,
+    authorization: String,
+    user_agent: String,
+}
+
+impl GithubApi {
+    /// `repository` is `owner/name`; defaults to GITHUB_REPOSITORY
+    fn new(repository: Option<&str>) -> Result<Self, GitAiError> {
+        let token = std::env::var("GITHUB_TOKEN").map_err(|_| {
+            GitAiError::Generic("GITHUB_TOKEN environment variable not set".to_string())
+        })?;
+        let repository = match repository {
+            Some(repository) => repository.to_string(),
+            None => std::env::var("GITHUB_REPOSITORY").map_err(|_| {
+                GitAiError::Generic("GITHUB_REPOSITORY environment variable not set".to_string())
+            })?,
+        };
+        let api_url = std::env::var("GITHUB_API_URL")
+            .unwrap_or_else(|_| "https://api.github.com".to_string());
+        Ok(GithubApi {
+            api_url: api_url.trim_end_matches('/').to_string(),
+            repository,
+            authorization: format!("Bearer {}", token),
+            user_agent: format!("git-ai/{}", env!("CARGO_PKG_VERSION")),
+        })
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/repos/{}/{}", self.api_url, self.repository, path)
+    }
+
+    /// Send `request` and check for `expected_status`; `action` names it in errors
+    fn send(
+        &self,
+        request: minreq::Request,
+        expected_status: i32,
+        action: &str,
+    ) -> Result<minreq::Response, GitAiError> {
+        let response = request
+            .with_header("Authorization", &self.authorization)
+            .with_header("Accept", "application/vnd.github+json")
+            .with_header("User-Agent", &self.user_agent)
+            .with_timeout(30)
+            .send()
+            .map_err(|e| GitAiError::Generic(format!("GitHub API request failed: {}", e)))?;
+        if response.status_code != expected_status {
+            return Err(GitAiError::Generic(format!(
+                "GitHub API returned status {} {}: {}",
+                response.status_code,
+                action,
+                response.as_str().unwrap_or("unknown error")
+            )));
+        }
+        Ok(response)
+    }
+
+    fn get<T: serde::de::DeserializeOwned>(
+        &self,
+        path: &str,
+        action: &str,
+    ) -> Result<T, GitAiError> {
+        let response = self.send(minreq::get(self.url(path)), 200, action)?;
+        serde_json::from_str(response.as_str().unwrap_or_default())
+            .map_err(|e| GitAiError::Generic(format!("Failed to parse GitHub API response: {}", e)))
+    }
+
+    fn send_json(
+        &self,
+        request: minreq::Request,
+        payload: &serde_json::Value,
+        expected_status: i32,
+        action: &str,
+    ) -> Result<(), GitAiError> {
+        let request = request
+            .with_header("Content-Type", "application/json")
+            .with_body(payload.to_string());
+        self.send(request, expected_status, action).map(|_| ())
+    }
+}
+
+/// Create the git-ai comment on a pull request, or update the one left by a previous run
+/// (found by `marker`) so pushes to the PR don't pile up comments. Returns true when an
+/// existing comment was updated.
+pub fn upsert_github_pr_comment(
+    pr_number: u32,
+    body: &str,
+    marker: &str,
+) -> Result<bool, GitAiError> {
+    let api = GithubApi::new(None)?;
+    let comments: Vec<GithubIssueComment> = api.get(
+        &format!("issues/{}/comments?per_page=100", pr_number),
+        "listing comments",
+    )?;
+    let existing = comments
+        .iter()
+        .find(|comment| comment.body.as_deref().is_some_and(|b| b.contains(marker)));
+
+    let payload = serde_json::json!({ "body": body });
+    match existing {
+        Some(comment) => api.send_json(
+            minreq::patch(api.url(&format!("issues/comments/{}", comment.id))),
+            &payload,
+            200,
+            "writing comment",
+        )?,
+        None => api.send_json(
+            minreq::post(api.url(&format!("issues/{}/comments", pr_number))),
+            &payload,
+            201,
+            "writing comment",
+        )?,
+    }
+
+    Ok(existing.is_some())
+}
+
+/// Look up a pull request's base and head commits. `repository` defaults to GITHUB_REPOSITORY.
+pub fn get_github_pull_request(
+    repository: Option<&str>,
+    pr_number: u32,
+) -> Result<GithubPullRequestRefs, GitAiError> {
+    let api = GithubApi::new(repository)?;
+    let pull_request: GithubCiPullRequest = api.get(
+        &format!("pulls/{}", pr_number),
+        &format!("reading pull request #{}", pr_number),
+    )?;
+    Ok(pull_request.into())
+}
+
+/// A line comment on the new side of a pull request's diff, spanning `start_line..=line`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GithubReviewComment {
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_line: Option<u32>,
+    /// Null in API responses for comments GitHub considers outdated
+    pub line: Option<u32>,
+    pub body: String,
+}
+
+impl GithubReviewComment {
+    fn payload(&self) -> serde_json::Value {
+        let mut payload = serde_json::json!({
+            "path": self.path,
+            "line": self.line,
+            "side": "RIGHT",
+            "body": self.body,
+        });
+        if let Some(start_line) = self.start_line {
+            payload["start_line"] = start_line.into();
+            payload["start_side"] = "RIGHT".into();
+        }
+        payload
+    }
+}
+
+/// Review comments already on a pull request (first 100)
+pub fn list_github_pr_review_comments(
+    repository: Option<&str>,
+    pr_number: u32,
+) -> Result<Vec<GithubReviewComment>, GitAiError> {
+    let api = GithubApi::new(repository)?;
+    api.get(
+        &format!("pulls/{}/comments?per_page=100", pr_number),
+        "listing review comments",
+    )
+}
+
+/// Submit one non-blocking review on `commit_id` holding all of `comments`
+pub fn create_github_pr_review(
+    repository: Option<&str>,
+    pr_number: u32,
+    commit_id: &str,
+    body: &str,
+    comments: &[GithubReviewComment],
+) -> Result<(), GitAiError> {
+    let api = GithubApi::new(repository)?;
+    let payload = serde_json::json!({
+        "commit_id": commit_id,
+        "event": "COMMENT",
+        "body": body,
+        "comments": comments.iter().map(GithubReviewComment::payload).collect::<Vec<_>>(),
+    });
+    api.send_json(
+        minreq::post(api.url(&format!("pulls/{}/reviews", pr_number))),
+        &payload,
+        200,
+        "creating review",
+    )
+}
+
+/// Post `comment` as a standalone review comment on `commit_id`
+pub fn create_github_pr_review_comment(
+    repository: Option<&str>,
+    pr_number: u32,
+    commit_id: &str,
+    comment: &GithubReviewComment,
+) -> Result<(), GitAiError> {
+    let api = GithubApi::new(repository)?;
+    let mut payload = comment.payload();
+    payload["commit_id"] = commit_id.into();
+    api.send_json(
+        minreq::post(api.url(&format!("pulls/{}/comments", pr_number))),
+        &payload,
+        201,
+        "creating review comment",
+    )
+}
+
 /// Install or update the GitHub Actions workflow in the current repository
 /// Writes the embedded template to .github/workflows/git-ai.yaml at the repo root
 pub fn install_github_ci_workflow() -> Result<PathBuf, GitAiError> {
@@ -131,3 +452,36 @@ pub fn install_github_ci_workflow() -> Result<PathBuf, GitAiError> {
 
     Ok(dest_path)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merged_pull_request_from_payload() {
+        let payload: GithubCiEventPayload = serde_json::from_value(serde_json::json!({
+            "pull_request": {
+                "number": 12,
+                "merged": true,
+                "merge_commit_sha": "m123",
+                "created_at": "2026-01-01T00:00:00Z",
+                "merged_at": "2026-01-01T02:30:00Z",
+                "base": { "ref": "main", "sha": "b123" },
+                "head": { "ref": "feature", "sha": "h123" }
+            }
+        }))
+        .unwrap();
+        let pull_request = payload.pull_request.unwrap();
+
+        let merged = merged_pull_request_from_payload(pull_request.clone()).unwrap();
+        assert_eq!(merged.number, Some(12));
+        assert_eq!(merged.head_ref.as_deref(), Some("feature"));
+        assert_eq!(merged.merged_at.unwrap() - merged.opened_at.unwrap(), 9000);
+
+        let open = GithubCiPullRequest {
+            merged: false,
+            ..pull_request
+        };
+        assert!(merged_pull_request_from_payload(open).is_none());
+    }
+}
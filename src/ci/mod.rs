@@ -1,5 +1,9 @@
 pub mod ci_context;
 pub mod github;
 pub mod gitlab;
+pub mod pr_annotations;
+pub mod pr_metrics;
+pub mod pr_summary;
+pub mod sarif;
 
 pub use ci_context::{CiContext, CiEvent, CiRunResult};
\ No newline at end of file
@@ -0,0 +1,235 @@
+//! AI-authored hunks of a pull request, and the review comments that point them out.
+
+use crate::authorship::authorship_log::PromptRecord;
+use crate::ci::github::GithubReviewComment;
+use crate::commands::blame::GitAiBlameOptions;
+use crate::commands::diff::get_diff_with_line_numbers;
+use crate::error::GitAiError;
+use crate::git::repository::Repository;
+use std::collections::{BTreeMap, HashMap};
+
+/// Hidden marker in every review comment and review git-ai posts
+pub const PR_ANNOTATION_MARKER: &str = "<!-- git-ai:annotate-pr -->";
+
+/// Consecutive lines added by a pull request that came from the same prompt
+#[derive(Debug, Clone, PartialEq)]
+pub struct AiHunk {
+    pub file_path: String,
+    pub start_line: u32,
+    pub end_line: u32,
+    pub tool: String,
+    pub model: String,
+}
+
+/// The AI-authored hunks among the lines added between the fork point of `base` and `head`,
+/// ordered by file and line
+pub fn pr_ai_hunks(repo: &Repository, base: &str, head: &str) -> Result<Vec<AiHunk>, GitAiError> {
+    let merge_base = repo.merge_base(base.to_string(), head.to_string())?;
+    let mut added: BTreeMap<String, Vec<u32>> = BTreeMap::new();
+    for hunk in get_diff_with_line_numbers(repo, &merge_base, head)? {
+        added
+            .entry(hunk.file_path)
+            .or_default()
+            .extend(hunk.added_lines);
+    }
+
+    let mut hunks = Vec::new();
+    for (file_path, mut lines) in added {
+        if lines.is_empty() {
+            continue;
+        }
+        lines.sort_unstable();
+        lines.dedup();
+
+        let options = GitAiBlameOptions {
+            oldest_commit: Some(merge_base.clone()),
+            newest_commit: Some(head.to_string()),
+            line_ranges: line_ranges(&lines),
+            use_prompt_hashes_as_names: true,
+            no_output: true,
+            ..Default::default()
+        };
+        // Deleted files and binary files have nothing to blame
+        let Ok((line_authors, prompts)) = repo.blame(&file_path, &options) else {
+            continue;
+        };
+        hunks.extend(group_ai_lines(&file_path, &lines, &line_authors, &prompts));
+    }
+    Ok(hunks)
+}
+
+/// Split `lines` into runs of consecutive lines attributed to the same prompt. Lines whose
+/// author isn't a prompt hash are human.
+fn group_ai_lines(
+    file_path: &str,
+    lines: &[u32],
+    line_authors: &HashMap<u32, String>,
+    prompts: &HashMap<String, PromptRecord>,
+) -> Vec<AiHunk> {
+    let mut hunks: Vec<AiHunk> = Vec::new();
+    let mut last: Option<(u32, &str)> = None;
+    for &line in lines {
+        let Some((hash, prompt)) = line_authors
+            .get(&line)
+            .and_then(|hash| prompts.get(hash).map(|prompt| (hash.as_str(), prompt)))
+        else {
+            last = None;
+            continue;
+        };
+        match (last, hunks.last_mut()) {
+            (Some((previous, previous_hash)), Some(hunk))
+                if previous + 1 == line && previous_hash == hash =>
+            {
+                hunk.end_line = line;
+            }
+            _ => hunks.push(AiHunk {
+                file_path: file_path.to_string(),
+                start_line: line,
+                end_line: line,
+                tool: prompt.agent_id.tool.clone(),
+                model: prompt.agent_id.model.clone(),
+            }),
+        }
+        last = Some((line, hash));
+    }
+    hunks
+}
+
+fn line_ranges(lines: &[u32]) -> Vec<(u32, u32)> {
+    let mut ranges: Vec<(u32, u32)> = Vec::new();
+    for &line in lines {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == line => *end = line,
+            _ => ranges.push((line, line)),
+        }
+    }
+    ranges
+}
+
+fn agent(hunk: &AiHunk) -> String {
+    if hunk.model.is_empty() {
+        hunk.tool.clone()
+    } else {
+        format!("{} ({})", hunk.tool, hunk.model)
+    }
+}
+
+/// The review comment marking `hunk`
+pub fn hunk_review_comment(hunk: &AiHunk) -> GithubReviewComment {
+    let lines = hunk.end_line - hunk.start_line + 1;
+    GithubReviewComment {
+        path: hunk.file_path.clone(),
+        start_line: (hunk.start_line < hunk.end_line).then_some(hunk.start_line),
+        line: Some(hunk.end_line),
+        body: format!(
+            "{}\n🤖 {} AI-generated {} by **{}**",
+            PR_ANNOTATION_MARKER,
+            lines,
+            if lines == 1 { "line" } else { "lines" },
+            agent(hunk)
+        ),
+    }
+}
+
+/// Body of the review holding the hunk comments
+pub fn render_review_body(hunks: &[AiHunk]) -> String {
+    let mut by_agent: BTreeMap<String, u32> = BTreeMap::new();
+    for hunk in hunks {
+        *by_agent.entry(agent(hunk)).or_default() += hunk.end_line - hunk.start_line + 1;
+    }
+    let mut body = format!(
+        "{}\n### git-ai: AI-authored hunks\n\n| Tool (model) | AI lines |\n|---|---:|\n",
+        PR_ANNOTATION_MARKER
+    );
+    for (agent, lines) in by_agent {
+        body.push_str(&format!("| {} | {} |\n", agent, lines));
+    }
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authorship::working_log::AgentId;
+
+    fn prompt(tool: &str, model: &str) -> PromptRecord {
+        PromptRecord {
+            agent_id: AgentId {
+                tool: tool.to_string(),
+                id: "session".to_string(),
+                model: model.to_string(),
+            },
+            human_author: None,
+            messages: vec![],
+            total_additions: 0,
+            total_deletions: 0,
+            accepted_lines: 0,
+            overriden_lines: 0,
+            messages_url: None,
+            token_usage: None,
+            turns: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_group_ai_lines_splits_on_gaps_and_prompts() {
+        let prompts = HashMap::from([
+            ("p1".to_string(), prompt("cursor", "gpt-5")),
+            ("p2".to_string(), prompt("claude", "")),
+        ]);
+        let line_authors = HashMap::from([
+            (1, "p1".to_string()),
+            (2, "p1".to_string()),
+            (3, "Test User".to_string()),
+            (4, "p1".to_string()),
+            (5, "p2".to_string()),
+        ]);
+        let hunks = group_ai_lines("lib.rs", &[1, 2, 3, 4, 5], &line_authors, &prompts);
+
+        let spans: Vec<(u32, u32, &str)> = hunks
+            .iter()
+            .map(|h| (h.start_line, h.end_line, h.tool.as_str()))
+            .collect();
+        assert_eq!(
+            spans,
+            vec![(1, 2, "cursor"), (4, 4, "cursor"), (5, 5, "claude")]
+        );
+    }
+
+    #[test]
+    fn test_hunk_review_comment_and_body() {
+        let hunks = vec![
+            AiHunk {
+                file_path: "src/lib.rs".to_string(),
+                start_line: 3,
+                end_line: 5,
+                tool: "cursor".to_string(),
+                model: "gpt-5".to_string(),
+            },
+            AiHunk {
+                file_path: "src/main.rs".to_string(),
+                start_line: 7,
+                end_line: 7,
+                tool: "claude".to_string(),
+                model: String::new(),
+            },
+        ];
+
+        let comment = hunk_review_comment(&hunks[0]);
+        assert_eq!(comment.start_line, Some(3));
+        assert_eq!(comment.line, Some(5));
+        assert!(comment.body.starts_with(PR_ANNOTATION_MARKER));
+        assert!(
+            comment
+                .body
+                .ends_with("3 AI-generated lines by **cursor (gpt-5)**")
+        );
+        let single = hunk_review_comment(&hunks[1]);
+        assert_eq!(single.start_line, None);
+        assert!(single.body.ends_with("1 AI-generated line by **claude**"));
+
+        let body = render_review_body(&hunks);
+        assert!(body.contains("| cursor (gpt-5) | 3 |"));
+        assert!(body.contains("| claude | 1 |"));
+    }
+}
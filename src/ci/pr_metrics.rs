@@ -0,0 +1,213 @@
+//! `pr_merged` metric events.
+//!
+//! A pull request's size, AI share and review time are computed from the commits it brought in:
+//! everything reachable from its head but not from the base it merged into. Reverts among those
+//! commits are listed by the commit they undo, so a later revert can be joined back to the pull
+//! request that introduced the code.
+
+use crate::authorship::revert::reverted_commit;
+use crate::ci::pr_summary::pr_range_stats;
+use crate::error::GitAiError;
+use crate::git::repository::{Repository, exec_git};
+use crate::metrics::{EventAttributes, PrMergedValues};
+use crate::utils::debug_log;
+
+/// A pull request (or merged branch) that just landed
+#[derive(Debug, Clone, Default)]
+pub struct MergedPullRequest {
+    pub merge_commit_sha: String,
+    /// The base branch tip the pull request merged into
+    pub base_sha: String,
+    pub head_sha: String,
+    pub head_ref: Option<String>,
+    pub number: Option<u32>,
+    /// When the pull request was opened; defaults to its oldest commit's author time
+    pub opened_at: Option<i64>,
+    /// When it merged; defaults to the merge commit's committer time
+    pub merged_at: Option<i64>,
+}
+
+/// Record the `pr_merged` event for `pr`. Best effort: failures are only logged.
+pub fn record_pr_merged(repo: &Repository, pr: &MergedPullRequest) {
+    match pr_merged_event(repo, pr) {
+        Ok((values, attrs)) => crate::metrics::record(values, attrs),
+        Err(e) => debug_log(&format!(
+            "Skipping pr_merged event for {}: {}",
+            pr.merge_commit_sha, e
+        )),
+    }
+}
+
+/// The values and attributes of the `pr_merged` event for `pr`
+pub fn pr_merged_event(
+    repo: &Repository,
+    pr: &MergedPullRequest,
+) -> Result<(PrMergedValues, EventAttributes), GitAiError> {
+    let merge_base = repo.merge_base(pr.base_sha.clone(), pr.head_sha.clone())?;
+    let commits = pr_commits(repo, &merge_base, &pr.head_sha)?;
+    if commits.is_empty() {
+        return Err(GitAiError::Generic(format!(
+            "{} brings in no commits",
+            pr.head_sha
+        )));
+    }
+    let stats = pr_range_stats(repo, &pr.base_sha, &pr.head_sha, &[])?;
+    let range = &stats.range_stats;
+
+    let mut values = PrMergedValues::new()
+        .commits(commits.len() as u32)
+        .git_diff_added_lines(range.git_diff_added_lines)
+        .git_diff_deleted_lines(range.git_diff_deleted_lines)
+        .ai_additions(range.ai_additions)
+        .human_additions(range.human_additions)
+        .reverted_commits(
+            commits
+                .iter()
+                .filter_map(|c| reverted_commit(&c.message))
+                .collect(),
+        );
+    values = match pr.number {
+        Some(number) => values.pr_number(number),
+        None => values.pr_number_null(),
+    };
+    values = match ai_ratio(range.ai_additions, range.human_additions) {
+        Some(ratio) => values.ai_ratio(ratio),
+        None => values.ai_ratio_null(),
+    };
+
+    let opened_at = pr
+        .opened_at
+        .or_else(|| commits.iter().map(|c| c.authored_at).min());
+    let merged_at = match pr.merged_at {
+        Some(merged_at) => Some(merged_at),
+        None => repo
+            .find_commit(pr.merge_commit_sha.clone())?
+            .time()
+            .ok()
+            .map(|t| t.seconds()),
+    };
+    values = match (opened_at, merged_at) {
+        (Some(opened), Some(merged)) => {
+            values.review_duration(merged.saturating_sub(opened).max(0) as u64)
+        }
+        _ => values.review_duration_null(),
+    };
+
+    let mut attrs = EventAttributes::with_version(env!("CARGO_PKG_VERSION"))
+        .commit_sha(&pr.merge_commit_sha)
+        .base_commit_sha(&pr.base_sha);
+    if let Some(author) = commits.first().map(|c| c.author.as_str()) {
+        attrs = attrs.author(author);
+    }
+    if let Some(head_ref) = &pr.head_ref {
+        attrs = attrs.branch(head_ref);
+    }
+    let repo_url = repo
+        .get_default_remote()
+        .ok()
+        .flatten()
+        .and_then(|remote_name| {
+            repo.remotes_with_urls()
+                .ok()?
+                .into_iter()
+                .find(|(name, _)| name == &remote_name)
+                .and_then(|(_, url)| crate::repo_url::normalize_repo_url(&url).ok())
+        });
+    if let Some(repo_url) = repo_url {
+        attrs = attrs.repo_url(repo_url);
+    }
+
+    Ok((values, attrs))
+}
+
+/// AI additions over AI plus human additions, or None when there are neither
+fn ai_ratio(ai_additions: u32, human_additions: u32) -> Option<f64> {
+    let total = ai_additions + human_additions;
+    (total > 0).then(|| f64::from(ai_additions) / f64::from(total))
+}
+
+struct PrCommit {
+    author: String,
+    authored_at: i64,
+    message: String,
+}
+
+/// The commits in `base..head`, newest first
+fn pr_commits(repo: &Repository, base: &str, head: &str) -> Result<Vec<PrCommit>, GitAiError> {
+    let mut args = repo.global_args_for_exec();
+    args.push("log".to_string());
+    args.push("-z".to_string());
+    args.push("--no-notes".to_string());
+    // %aN/%aE apply .mailmap, as the other author-keyed reports do
+    args.push("--format=%aN <%aE>%n%at%n%B".to_string());
+    args.push(format!("{}..{}", base, head));
+    let output = exec_git(&args)?;
+    let stdout = String::from_utf8(output.stdout)?;
+    Ok(stdout
+        .split('\0')
+        .filter(|record| !record.trim().is_empty())
+        .filter_map(|record| {
+            let mut lines = record.splitn(3, '\n');
+            let author = lines.next()?.to_string();
+            let authored_at = lines.next()?.trim().parse().ok()?;
+            Some(PrCommit {
+                author,
+                authored_at,
+                message: lines.next().unwrap_or_default().to_string(),
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_utils::TmpRepo;
+    use crate::metrics::PosEncoded;
+    use crate::metrics::events::pr_merged_pos;
+
+    #[test]
+    fn test_ai_ratio() {
+        assert_eq!(ai_ratio(0, 0), None);
+        assert_eq!(ai_ratio(3, 1), Some(0.75));
+    }
+
+    #[test]
+    fn test_pr_merged_event_for_feature_branch() {
+        let tmp_repo = TmpRepo::new().unwrap();
+        tmp_repo.write_file("a.txt", "base\n", true).unwrap();
+        tmp_repo.commit_with_message("Base").unwrap();
+        let base = tmp_repo.head_commit_sha().unwrap();
+
+        tmp_repo.write_file("b.txt", "one\ntwo\n", true).unwrap();
+        tmp_repo.commit_with_message("Add b").unwrap();
+        tmp_repo.write_file("a.txt", "base\nmore\n", true).unwrap();
+        tmp_repo
+            .commit_with_message("Revert \"Old\"\n\nThis reverts commit 1234567890abcdef.")
+            .unwrap();
+        let head = tmp_repo.head_commit_sha().unwrap();
+
+        let repo = tmp_repo.gitai_repo();
+        let pr = MergedPullRequest {
+            merge_commit_sha: head.clone(),
+            base_sha: base.clone(),
+            head_sha: head.clone(),
+            number: Some(7),
+            opened_at: Some(1_000),
+            merged_at: Some(4_600),
+            ..MergedPullRequest::default()
+        };
+        let (values, attrs) = pr_merged_event(repo, &pr).unwrap();
+        let sparse = PosEncoded::to_sparse(&values);
+
+        assert_eq!(values.pr_number, Some(Some(7)));
+        assert_eq!(values.commits, Some(Some(2)));
+        assert_eq!(values.git_diff_added_lines, Some(Some(3)));
+        assert_eq!(values.review_duration, Some(Some(3_600)));
+        assert_eq!(
+            sparse[&pr_merged_pos::REVERTED_COMMITS.to_string()],
+            serde_json::json!(["1234567890abcdef"])
+        );
+        assert!(attrs.commit_sha.is_some());
+    }
+}
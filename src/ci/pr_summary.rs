@@ -0,0 +1,163 @@
+//! AI/human line statistics for the commits in a pull request, rendered as markdown for CI job
+//! summaries and PR comments.
+
+use crate::authorship::range_authorship::{RangeAuthorshipStats, range_authorship};
+use crate::error::GitAiError;
+use crate::git::repository::{CommitRange, Repository};
+
+/// Hidden marker used to find (and update) the comment git-ai posted on a previous run
+pub const PR_SUMMARY_MARKER: &str = "<!-- git-ai:pr-summary -->";
+
+/// Compute authorship stats for the commits reachable from `head` but not from `base`
+pub fn pr_range_stats(
+    repo: &Repository,
+    base: &str,
+    head: &str,
+    ignore_patterns: &[String],
+) -> Result<RangeAuthorshipStats, GitAiError> {
+    // Diff from the fork point so commits merged into the base branch after the PR was opened
+    // don't show up as part of the PR
+    let merge_base = repo.merge_base(base.to_string(), head.to_string())?;
+    let range = CommitRange::new(repo, merge_base, head.to_string(), head.to_string())?;
+    range_authorship(range, false, ignore_patterns, false)
+}
+
+pub fn render_pr_summary_markdown(stats: &RangeAuthorshipStats) -> String {
+    let range = &stats.range_stats;
+    let data = &stats.authorship_stats;
+    let mut out = String::new();
+
+    out.push_str(PR_SUMMARY_MARKER);
+    out.push('\n');
+    out.push_str("### git-ai authorship\n\n");
+
+    if data.commits_with_authorship == 0 {
+        out.push_str(&format!(
+            "None of the {} {} in this pull request carry git-ai authorship notes.\n",
+            data.total_commits,
+            plural(data.total_commits, "commit", "commits")
+        ));
+        return out;
+    }
+
+    let total = range.human_additions + range.ai_additions;
+    out.push_str("| | Lines | Share |\n");
+    out.push_str("|---|---:|---:|\n");
+    out.push_str(&format!(
+        "| AI | {} | {} |\n",
+        range.ai_additions,
+        percent(range.ai_additions, total)
+    ));
+    out.push_str(&format!(
+        "| Human | {} | {} |\n",
+        range.human_additions,
+        percent(range.human_additions, total)
+    ));
+    out.push_str(&format!(
+        "| AI, edited by a human | {} | {} |\n",
+        range.mixed_additions,
+        percent(range.mixed_additions, total)
+    ));
+    out.push('\n');
+
+    out.push_str(&format!(
+        "Commits with authorship notes: **{}** of **{}**. +{} / -{} lines.\n",
+        data.commits_with_authorship,
+        data.total_commits,
+        range.git_diff_added_lines,
+        range.git_diff_deleted_lines
+    ));
+    if data.bot_commits_excluded > 0 {
+        out.push_str(&format!(
+            "{} bot {} excluded.\n",
+            data.bot_commits_excluded,
+            plural(data.bot_commits_excluded, "commit", "commits")
+        ));
+    }
+
+    if !range.tool_model_breakdown.is_empty() {
+        out.push_str("\n<details><summary>By tool and model</summary>\n\n");
+        out.push_str("| Tool / model | AI lines | Accepted unedited |\n");
+        out.push_str("|---|---:|---:|\n");
+        for (tool_model, tool_stats) in &range.tool_model_breakdown {
+            out.push_str(&format!(
+                "| `{}` | {} | {} |\n",
+                tool_model, tool_stats.ai_additions, tool_stats.ai_accepted
+            ));
+        }
+        out.push_str("\n</details>\n");
+    }
+
+    if !data.commits_without_authorship_with_authors.is_empty() {
+        out.push_str("\n<details><summary>Commits without authorship notes</summary>\n\n");
+        for (sha, author) in &data.commits_without_authorship_with_authors {
+            out.push_str(&format!("- `{}` {}\n", &sha[..sha.len().min(7)], author));
+        }
+        out.push_str("\n</details>\n");
+    }
+
+    out
+}
+
+fn percent(part: u32, total: u32) -> String {
+    if total == 0 {
+        return "0%".to_string();
+    }
+    format!("{:.0}%", part as f64 / total as f64 * 100.0)
+}
+
+fn plural<'a>(count: usize, one: &'a str, many: &'a str) -> &'a str {
+    if count == 1 { one } else { many }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authorship::range_authorship::RangeAuthorshipStatsData;
+    use crate::authorship::stats::CommitStats;
+    use std::collections::HashSet;
+
+    fn stats(with_authorship: usize, total: usize) -> RangeAuthorshipStats {
+        RangeAuthorshipStats {
+            authorship_stats: RangeAuthorshipStatsData {
+                total_commits: total,
+                commits_with_authorship: with_authorship,
+                authors_committing_authorship: HashSet::new(),
+                authors_not_committing_authorship: HashSet::new(),
+                commits_without_authorship: vec![],
+                commits_without_authorship_with_authors: vec![(
+                    "0123456789abcdef".to_string(),
+                    "Test User <test@example.com>".to_string(),
+                )],
+                bot_commits_excluded: 0,
+            },
+            range_stats: CommitStats {
+                human_additions: 10,
+                ai_additions: 30,
+                mixed_additions: 4,
+                git_diff_added_lines: 40,
+                git_diff_deleted_lines: 2,
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_render_pr_summary_markdown() {
+        let markdown = render_pr_summary_markdown(&stats(2, 3));
+
+        assert!(markdown.starts_with(PR_SUMMARY_MARKER));
+        assert!(markdown.contains("| AI | 30 | 75% |"));
+        assert!(markdown.contains("| Human | 10 | 25% |"));
+        assert!(markdown.contains("| AI, edited by a human | 4 | 10% |"));
+        assert!(markdown.contains("Commits with authorship notes: **2** of **3**"));
+        assert!(markdown.contains("- `0123456` Test User <test@example.com>"));
+    }
+
+    #[test]
+    fn test_render_pr_summary_markdown_without_notes() {
+        let markdown = render_pr_summary_markdown(&stats(0, 1));
+        assert!(markdown.contains("None of the 1 commit in this pull request"));
+        assert!(!markdown.contains("| AI |"));
+    }
+}
@@ -0,0 +1,319 @@
+//! SARIF 2.1.0 output for git-ai findings.
+//!
+//! Policy violations and attribution verification failures are reported as SARIF
+//! results so GitHub code scanning (and any other SARIF consumer) can render them
+//! as annotations instead of leaving them buried in CI logs.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+pub const SARIF_VERSION: &str = "2.1.0";
+pub const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+pub const SARIF_TOOL_NAME: &str = "git-ai";
+pub const SARIF_TOOL_URI: &str = "https://github.com/git-ai-project/git-ai";
+
+/// Severity of a finding, mapped 1:1 onto the SARIF `level` property
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SarifLevel {
+    Error,
+    Warning,
+    Note,
+}
+
+/// A rule that findings can reference (e.g. `verify/missing-note`, `policy/max-ai-ratio`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SarifRule {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub default_level: SarifLevel,
+}
+
+impl SarifRule {
+    pub fn new(id: &str, name: &str, description: &str, default_level: SarifLevel) -> Self {
+        Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            description: description.to_string(),
+            default_level,
+        }
+    }
+}
+
+/// A single finding produced by `verify` or the policy engine
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SarifFinding {
+    pub rule_id: String,
+    pub level: SarifLevel,
+    pub message: String,
+    /// Repo-relative file path, if the finding is tied to a file
+    pub path: Option<String>,
+    /// 1-based inclusive line range within `path`
+    pub lines: Option<(u32, u32)>,
+    /// Commit the finding was raised against
+    pub commit_sha: Option<String>,
+}
+
+impl SarifFinding {
+    pub fn new(rule_id: &str, level: SarifLevel, message: impl Into<String>) -> Self {
+        Self {
+            rule_id: rule_id.to_string(),
+            level,
+            message: message.into(),
+            path: None,
+            lines: None,
+            commit_sha: None,
+        }
+    }
+
+    pub fn path(mut self, path: impl Into<String>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    pub fn lines(mut self, start: u32, end: u32) -> Self {
+        self.lines = Some((start, end.max(start)));
+        self
+    }
+
+    pub fn commit_sha(mut self, sha: impl Into<String>) -> Self {
+        self.commit_sha = Some(sha.into());
+        self
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SarifLog {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<SarifRun>,
+}
+
+#[derive(Debug, Serialize)]
+struct SarifRun {
+    tool: SarifTool,
+    results: Vec<SarifResult>,
+}
+
+#[derive(Debug, Serialize)]
+struct SarifTool {
+    driver: SarifDriver,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifDriver {
+    name: &'static str,
+    version: &'static str,
+    information_uri: &'static str,
+    rules: Vec<SarifReportingDescriptor>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifReportingDescriptor {
+    id: String,
+    name: String,
+    short_description: SarifMessage,
+    default_configuration: SarifDefaultConfiguration,
+}
+
+#[derive(Debug, Serialize)]
+struct SarifDefaultConfiguration {
+    level: SarifLevel,
+}
+
+#[derive(Debug, Serialize)]
+struct SarifMessage {
+    text: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    rule_id: String,
+    rule_index: usize,
+    level: SarifLevel,
+    message: SarifMessage,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    locations: Vec<SarifLocation>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    partial_fingerprints: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    properties: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifLocation {
+    physical_location: SarifPhysicalLocation,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifPhysicalLocation {
+    artifact_location: SarifArtifactLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    region: Option<SarifRegion>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifArtifactLocation {
+    uri: String,
+    uri_base_id: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifRegion {
+    start_line: u32,
+    end_line: u32,
+}
+
+impl SarifLog {
+    /// Build a single-run SARIF log from a rule catalogue and the findings raised against it.
+    /// Findings that reference a rule missing from the catalogue get a synthesized rule entry
+    /// so the output always validates.
+    pub fn new(rules: &[SarifRule], findings: &[SarifFinding]) -> Self {
+        let mut descriptors: Vec<SarifReportingDescriptor> = rules
+            .iter()
+            .map(|rule| SarifReportingDescriptor {
+                id: rule.id.clone(),
+                name: rule.name.clone(),
+                short_description: SarifMessage {
+                    text: rule.description.clone(),
+                },
+                default_configuration: SarifDefaultConfiguration {
+                    level: rule.default_level,
+                },
+            })
+            .collect();
+
+        let mut results = Vec::with_capacity(findings.len());
+        for finding in findings {
+            let rule_index = match descriptors.iter().position(|d| d.id == finding.rule_id) {
+                Some(index) => index,
+                None => {
+                    descriptors.push(SarifReportingDescriptor {
+                        id: finding.rule_id.clone(),
+                        name: finding.rule_id.clone(),
+                        short_description: SarifMessage {
+                            text: finding.rule_id.clone(),
+                        },
+                        default_configuration: SarifDefaultConfiguration {
+                            level: finding.level,
+                        },
+                    });
+                    descriptors.len() - 1
+                }
+            };
+
+            let locations = finding
+                .path
+                .as_ref()
+                .map(|path| {
+                    vec![SarifLocation {
+                        physical_location: SarifPhysicalLocation {
+                            artifact_location: SarifArtifactLocation {
+                                uri: path.replace('\\', "/"),
+                                uri_base_id: "%SRCROOT%",
+                            },
+                            region: finding.lines.map(|(start, end)| SarifRegion {
+                                start_line: start.max(1),
+                                end_line: end.max(1),
+                            }),
+                        },
+                    }]
+                })
+                .unwrap_or_default();
+
+            // Fingerprints let code scanning track the same finding across runs
+            let mut partial_fingerprints = BTreeMap::new();
+            if let Some(sha) = &finding.commit_sha {
+                partial_fingerprints.insert("commitSha".to_string(), sha.clone());
+            }
+
+            results.push(SarifResult {
+                rule_id: finding.rule_id.clone(),
+                rule_index,
+                level: finding.level,
+                message: SarifMessage {
+                    text: finding.message.clone(),
+                },
+                locations,
+                partial_fingerprints,
+                properties: finding
+                    .commit_sha
+                    .as_ref()
+                    .map(|sha| serde_json::json!({ "commit": sha })),
+            });
+        }
+
+        SarifLog {
+            schema: SARIF_SCHEMA,
+            version: SARIF_VERSION,
+            runs: vec![SarifRun {
+                tool: SarifTool {
+                    driver: SarifDriver {
+                        name: SARIF_TOOL_NAME,
+                        version: env!("CARGO_PKG_VERSION"),
+                        information_uri: SARIF_TOOL_URI,
+                        rules: descriptors,
+                    },
+                },
+                results,
+            }],
+        }
+    }
+
+    pub fn to_json_string(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sarif_log_structure() {
+        let rules = vec![SarifRule::new(
+            "verify/missing-note",
+            "MissingAuthorshipNote",
+            "Commit has no authorship note in refs/notes/ai",
+            SarifLevel::Error,
+        )];
+        let findings = vec![
+            SarifFinding::new("verify/missing-note", SarifLevel::Error, "No note for abc123")
+                .commit_sha("abc123"),
+            SarifFinding::new("policy/max-ai-ratio", SarifLevel::Warning, "Too much AI")
+                .path("src\\security\\auth.rs")
+                .lines(10, 4),
+        ];
+
+        let log = SarifLog::new(&rules, &findings);
+        let value: serde_json::Value =
+            serde_json::from_str(&log.to_json_string().unwrap()).unwrap();
+
+        assert_eq!(value["version"], "2.1.0");
+        let run = &value["runs"][0];
+        assert_eq!(run["tool"]["driver"]["name"], "git-ai");
+        // Unknown rule gets synthesized so every ruleIndex resolves
+        assert_eq!(run["tool"]["driver"]["rules"].as_array().unwrap().len(), 2);
+
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results[0]["ruleIndex"], 0);
+        assert!(results[0].get("locations").is_none());
+        assert_eq!(results[0]["partialFingerprints"]["commitSha"], "abc123");
+
+        assert_eq!(results[1]["ruleIndex"], 1);
+        assert_eq!(results[1]["level"], "warning");
+        let location = &results[1]["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "src/security/auth.rs");
+        assert_eq!(location["region"]["startLine"], 10);
+        assert_eq!(location["region"]["endLine"], 10);
+    }
+}
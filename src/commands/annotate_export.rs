@@ -0,0 +1,432 @@
+use crate::authorship::authorship_log_serialization::AuthorshipLog;
+use crate::commands::machine_output::{self, OutputFormat};
+use crate::commands::trace::{attested_prompt_id, blame_lines, repo_relative_path};
+use crate::error::GitAiError;
+use crate::git::find_repository;
+use crate::git::refs::get_authorship;
+use crate::git::repository::{Repository, exec_git};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Text every provenance comment starts with, after the comment opener
+pub const ANNOTATION_MARKER: &str = "ai-generated:";
+
+/// Length of the commit SHAs written into annotations
+const SHORT_SHA_LEN: usize = 12;
+
+#[derive(Debug, Serialize)]
+pub struct AnnotateReport {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rev: Option<String>,
+    pub files: Vec<AnnotatedFile>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AnnotatedFile {
+    pub path: String,
+    /// Where the result was written; absent when the file was skipped
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    /// Annotations added (export) or removed (strip)
+    pub annotations: usize,
+    /// AI lines covered by the added annotations
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ai_lines: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skipped: Option<String>,
+}
+
+struct Options {
+    rev: Option<String>,
+    output: Option<PathBuf>,
+    format: OutputFormat,
+    paths: Vec<String>,
+}
+
+fn parse_options(args: &[String], command: &str, allow_rev: bool) -> Options {
+    let mut options = Options {
+        rev: None,
+        output: None,
+        format: OutputFormat::Text,
+        paths: Vec::new(),
+    };
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--rev" if allow_rev => {
+                let Some(rev) = args.get(i + 1) else {
+                    eprintln!("--rev requires a commit");
+                    std::process::exit(1);
+                };
+                options.rev = Some(rev.clone());
+                i += 2;
+            }
+            "--output" | "-o" => {
+                let Some(dir) = args.get(i + 1) else {
+                    eprintln!("--output requires a directory");
+                    std::process::exit(1);
+                };
+                options.output = Some(PathBuf::from(dir));
+                i += 2;
+            }
+            "--format" => {
+                options.format = OutputFormat::from_flag_value(args, i);
+                i += 2;
+            }
+            "--json" => {
+                options.format = OutputFormat::Json;
+                i += 1;
+            }
+            "--porcelain" => {
+                options.format = OutputFormat::Porcelain;
+                i += 1;
+            }
+            "--help" | "-h" => print_annotate_help_and_exit(),
+            "--" => {
+                options.paths.extend(args[i + 1..].iter().cloned());
+                break;
+            }
+            arg if !arg.starts_with('-') => {
+                options.paths.push(arg.to_string());
+                i += 1;
+            }
+            other => {
+                eprintln!("Unknown {} argument: {}", command, other);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if options.paths.is_empty() {
+        eprintln!("{} requires at least one file", command);
+        std::process::exit(1);
+    }
+    options
+}
+
+pub fn handle_annotate_export(args: &[String]) {
+    let options = parse_options(args, "annotate-export", true);
+    let repo = repository_or_exit();
+    let rev = options.rev.clone().unwrap_or_else(|| "HEAD".to_string());
+    if options.rev.is_some() && options.output.is_none() {
+        eprintln!("--rev needs --output: the working tree may not match that revision");
+        std::process::exit(1);
+    }
+
+    let mut report = AnnotateReport {
+        rev: Some(rev.clone()),
+        files: Vec::new(),
+    };
+    let mut logs: HashMap<String, Option<AuthorshipLog>> = HashMap::new();
+    for path in &options.paths {
+        let file = repo_relative_path(&repo, path);
+        let result = annotate_file(&repo, &rev, &file, &mut logs).and_then(|annotated| {
+            let Some((content, annotations, ai_lines)) = annotated else {
+                return Ok(skipped(&file, "unsupported file type or not UTF-8"));
+            };
+            let target = match &options.output {
+                Some(dir) => dir.join(&file),
+                None => {
+                    // In place only when that doesn't discard uncommitted changes
+                    let target = repo.workdir()?.join(&file);
+                    let committed = show_file(&repo, &rev, &file)?;
+                    if std::fs::read(&target).ok().as_deref() != Some(committed.as_bytes()) {
+                        return Ok(skipped(
+                            &file,
+                            "has uncommitted changes; commit them or use --output",
+                        ));
+                    }
+                    target
+                }
+            };
+            write_file(&target, &content)?;
+            Ok(AnnotatedFile {
+                path: file.clone(),
+                output: Some(target.display().to_string()),
+                annotations,
+                ai_lines: Some(ai_lines),
+                skipped: None,
+            })
+        });
+        match result {
+            Ok(annotated) => report.files.push(annotated),
+            Err(e) => report.files.push(skipped(&file, &e.to_string())),
+        }
+    }
+
+    print_report(&report, options.format, "added");
+}
+
+pub fn handle_annotate_strip(args: &[String]) {
+    let options = parse_options(args, "annotate-strip", false);
+    let repo = repository_or_exit();
+
+    let mut report = AnnotateReport {
+        rev: None,
+        files: Vec::new(),
+    };
+    for path in &options.paths {
+        let file = repo_relative_path(&repo, path);
+        let result = std::fs::read_to_string(path)
+            .map_err(GitAiError::from)
+            .and_then(|content| {
+                let (stripped, removed) = strip_annotations(&content);
+                let target = match &options.output {
+                    Some(dir) => dir.join(&file),
+                    None => PathBuf::from(path),
+                };
+                write_file(&target, &stripped)?;
+                Ok(AnnotatedFile {
+                    path: file.clone(),
+                    output: Some(target.display().to_string()),
+                    annotations: removed,
+                    ai_lines: None,
+                    skipped: None,
+                })
+            });
+        match result {
+            Ok(stripped) => report.files.push(stripped),
+            Err(e) => report.files.push(skipped(&file, &e.to_string())),
+        }
+    }
+
+    print_report(&report, options.format, "removed");
+}
+
+fn print_annotate_help_and_exit() -> ! {
+    eprintln!("Usage: git-ai annotate-export [--rev <commit>] [--output <dir>] <file>...");
+    eprintln!("       git-ai annotate-strip [--output <dir>] <file>...");
+    eprintln!();
+    eprintln!("Embed authorship as comments above each block of AI-authored lines, e.g.");
+    eprintln!("  // ai-generated: tool=cursor model=gpt-4o commit=abc123def456 lines=12");
+    eprintln!("for handing code to places without git notes. annotate-strip removes them.");
+    eprintln!();
+    eprintln!("  --rev <commit>        Annotate the files as of <commit> (needs --output)");
+    eprintln!("  --output, -o <dir>    Write results under <dir> instead of in place");
+    eprintln!("  --format <fmt>        text (default), json or porcelain");
+    std::process::exit(0);
+}
+
+fn repository_or_exit() -> Repository {
+    match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn skipped(file: &str, reason: &str) -> AnnotatedFile {
+    AnnotatedFile {
+        path: file.to_string(),
+        output: None,
+        annotations: 0,
+        ai_lines: None,
+        skipped: Some(reason.to_string()),
+    }
+}
+
+fn print_report(report: &AnnotateReport, format: OutputFormat, verb: &str) {
+    if !format.is_text() {
+        machine_output::print(format, report).unwrap();
+    } else {
+        for file in &report.files {
+            match &file.skipped {
+                Some(reason) => eprintln!("{}: skipped, {}", file.path, reason),
+                None => println!(
+                    "{}: {} annotation{} {}",
+                    file.path,
+                    file.annotations,
+                    if file.annotations == 1 { "" } else { "s" },
+                    verb
+                ),
+            }
+        }
+    }
+    if report.files.iter().any(|f| f.skipped.is_some()) {
+        std::process::exit(1);
+    }
+}
+
+fn show_file(repo: &Repository, rev: &str, file: &str) -> Result<String, GitAiError> {
+    let mut args = repo.global_args_for_exec();
+    args.extend(["show".to_string(), format!("{}:{}", rev, file)]);
+    let output = exec_git(&args)?;
+    String::from_utf8(output.stdout)
+        .map_err(|_| GitAiError::Generic(format!("{} is not UTF-8", file)))
+}
+
+fn write_file(target: &Path, content: &str) -> Result<(), GitAiError> {
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(target, content)?;
+    Ok(())
+}
+
+/// `file` as of `rev` with a provenance comment above each run of AI lines from the same
+/// session and commit, plus the number of comments and AI lines. None when the file's language
+/// has no known comment syntax or it isn't text.
+fn annotate_file(
+    repo: &Repository,
+    rev: &str,
+    file: &str,
+    logs: &mut HashMap<String, Option<AuthorshipLog>>,
+) -> Result<Option<(String, usize, usize)>, GitAiError> {
+    let Some(syntax) = comment_syntax(file) else {
+        return Ok(None);
+    };
+    let Ok(content) = show_file(repo, rev, file) else {
+        return Ok(None);
+    };
+    if content.lines().any(is_annotation) {
+        return Err(GitAiError::Generic(
+            "already annotated; run annotate-strip first".to_string(),
+        ));
+    }
+    let blamed = blame_lines(repo, rev, file, None)?;
+
+    // (tool, model, commit) for each AI line, None for everything else
+    let owners: Vec<Option<(String, String, String)>> = blamed
+        .iter()
+        .map(|line| {
+            let log = logs
+                .entry(line.commit.clone())
+                .or_insert_with(|| get_authorship(repo, &line.commit))
+                .as_ref()?;
+            let prompt_id = attested_prompt_id(log, &line.commit_file, line.commit_line)?;
+            let (tool, model) = log
+                .metadata
+                .prompts
+                .get(&prompt_id)
+                .map(|record| (record.agent_id.tool.clone(), record.agent_id.model.clone()))
+                .unwrap_or_else(|| ("unknown".to_string(), "unknown".to_string()));
+            Some((tool, model, line.commit.clone()))
+        })
+        .collect();
+
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let mut annotated = String::with_capacity(content.len());
+    let mut annotations = 0;
+    let mut ai_lines = 0;
+    let mut idx = 0;
+    while idx < lines.len() {
+        let Some(owner) = owners.get(idx).cloned().flatten() else {
+            annotated.push_str(lines[idx]);
+            idx += 1;
+            continue;
+        };
+        let run = owners[idx..]
+            .iter()
+            .take_while(|o| o.as_ref() == Some(&owner))
+            .count()
+            .min(lines.len() - idx);
+
+        let (tool, model, commit) = owner;
+        let first = lines[idx];
+        let indent: String = first
+            .chars()
+            .take_while(|c| *c == ' ' || *c == '\t')
+            .collect();
+        let eol = if first.ends_with("\r\n") {
+            "\r\n"
+        } else {
+            "\n"
+        };
+        annotated.push_str(&format!(
+            "{}{} {} tool={} model={} commit={} lines={}{}{}",
+            indent,
+            syntax.0,
+            ANNOTATION_MARKER,
+            tool.replace(char::is_whitespace, "_"),
+            model.replace(char::is_whitespace, "_"),
+            &commit[..commit.len().min(SHORT_SHA_LEN)],
+            run,
+            syntax.1,
+            eol
+        ));
+        for line in &lines[idx..idx + run] {
+            annotated.push_str(line);
+        }
+        annotations += 1;
+        ai_lines += run;
+        idx += run;
+    }
+
+    Ok(Some((annotated, annotations, ai_lines)))
+}
+
+/// Comment opener and closer for `file`'s language, by extension or file name
+fn comment_syntax(file: &str) -> Option<(&'static str, &'static str)> {
+    let name = Path::new(file).file_name()?.to_str()?;
+    if matches!(
+        name,
+        "Dockerfile" | "Makefile" | "makefile" | "CMakeLists.txt"
+    ) {
+        return Some(("#", ""));
+    }
+    let extension = Path::new(name).extension()?.to_str()?.to_ascii_lowercase();
+    let syntax = match extension.as_str() {
+        "rs" | "c" | "h" | "cc" | "cpp" | "cxx" | "hpp" | "hh" | "m" | "mm" | "java" | "kt"
+        | "kts" | "scala" | "swift" | "go" | "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "cs"
+        | "dart" | "php" | "groovy" | "gradle" | "proto" | "zig" | "sol" | "v" => ("//", ""),
+        "py" | "rb" | "sh" | "bash" | "zsh" | "fish" | "pl" | "pm" | "r" | "yaml" | "yml"
+        | "toml" | "tf" | "hcl" | "ex" | "exs" | "nix" | "ps1" | "jl" | "cmake" => ("#", ""),
+        "sql" | "lua" | "hs" | "elm" | "ada" => ("--", ""),
+        "lisp" | "clj" | "cljs" | "el" | "scm" | "ini" | "asm" => (";", ""),
+        "css" | "scss" | "less" => ("/*", " */"),
+        "html" | "htm" | "xml" | "svg" | "vue" | "svelte" | "md" => ("<!--", " -->"),
+        _ => return None,
+    };
+    Some(syntax)
+}
+
+/// Whether `line` is a comment written by annotate-export, in any supported syntax
+fn is_annotation(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    ["//", "#", "--", ";", "/*", "<!--"].iter().any(|opener| {
+        trimmed
+            .strip_prefix(opener)
+            .is_some_and(|rest| rest.trim_start().starts_with(ANNOTATION_MARKER))
+    })
+}
+
+/// `content` without annotate-export's comment lines, and how many there were
+pub fn strip_annotations(content: &str) -> (String, usize) {
+    let mut stripped = String::with_capacity(content.len());
+    let mut removed = 0;
+    for line in content.split_inclusive('\n') {
+        if is_annotation(line) {
+            removed += 1;
+        } else {
+            stripped.push_str(line);
+        }
+    }
+    (stripped, removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_comment_syntax_by_extension() {
+        assert_eq!(comment_syntax("src/lib.rs"), Some(("//", "")));
+        assert_eq!(comment_syntax("app/Main.PY"), Some(("#", "")));
+        assert_eq!(comment_syntax("docker/Dockerfile"), Some(("#", "")));
+        assert_eq!(comment_syntax("web/index.html"), Some(("<!--", " -->")));
+        assert_eq!(comment_syntax("image.png"), None);
+        assert_eq!(comment_syntax("LICENSE"), None);
+    }
+
+    #[test]
+    fn test_strip_annotations_removes_only_marker_comments() {
+        let content = "fn a() {}\n    // ai-generated: tool=cursor model=gpt-4o commit=abc lines=1\n    let x = 1;\n// a normal comment\n<!-- ai-generated: tool=t model=m commit=c lines=2 -->\r\n# ai-generated: tool=t model=m commit=c lines=1";
+        let (stripped, removed) = strip_annotations(content);
+        assert_eq!(removed, 3);
+        assert_eq!(stripped, "fn a() {}\n    let x = 1;\n// a normal comment\n");
+    }
+}
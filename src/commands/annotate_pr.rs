@@ -0,0 +1,271 @@
+//! `git-ai annotate-pr`: mark the AI-authored hunks of a GitHub pull request with review comments
+//! naming the tool and model that wrote them.
+//!
+//! The pull request's commits and the notes on them are fetched from the remote, the lines the
+//! pull request adds are blamed like `git-ai diff` does, and consecutive AI lines from the same
+//! prompt become one comment. Comments posted by an earlier run are skipped, so re-running
+//! after new pushes only adds comments for new hunks.
+
+use crate::ci::github::{
+    GithubReviewComment, create_github_pr_review, create_github_pr_review_comment,
+    get_github_pull_request, list_github_pr_review_comments,
+};
+use crate::ci::pr_annotations::{
+    PR_ANNOTATION_MARKER, hunk_review_comment, pr_ai_hunks, render_review_body,
+};
+use crate::error::GitAiError;
+use crate::git::find_repository;
+use crate::git::repository::Repository;
+use crate::git::sync_authorship::fetch_authorship_notes;
+use crate::repo_url::normalize_repo_url;
+
+#[derive(Debug, Default)]
+struct AnnotatePrOptions {
+    pr_number: u32,
+    /// `owner/name`; defaults to GITHUB_REPOSITORY, then the remote's URL
+    repository: Option<String>,
+    remote: Option<String>,
+    /// Post each hunk as its own review comment instead of one review holding them all
+    separate_comments: bool,
+    dry_run: bool,
+}
+
+pub fn handle_annotate_pr(args: &[String]) {
+    let options = parse_args(args);
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    };
+    if let Err(e) = annotate_pr(&repo, &options) {
+        eprintln!("git-ai annotate-pr failed: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn print_annotate_pr_help_and_exit() -> ! {
+    eprintln!("Usage: git-ai annotate-pr <number> [options]");
+    eprintln!();
+    eprintln!("Comment on the AI-authored hunks of a GitHub pull request. Needs GITHUB_TOKEN.");
+    eprintln!();
+    eprintln!("  --repo <owner/name>  Repository (default: GITHUB_REPOSITORY, then the remote)");
+    eprintln!("  --remote <name>      Remote to fetch the pull request from (default: origin)");
+    eprintln!("  --comments           Post one review comment per hunk instead of one review");
+    eprintln!("  --dry-run            Print the comments instead of posting them");
+    std::process::exit(0);
+}
+
+fn parse_args(args: &[String]) -> AnnotatePrOptions {
+    let mut options = AnnotatePrOptions::default();
+    let mut pr_number = None;
+    let mut i = 0;
+    while i < args.len() {
+        let value = |name: &str| -> String {
+            match args.get(i + 1) {
+                Some(v) => v.clone(),
+                None => {
+                    eprintln!("Missing value for flag {}", name);
+                    std::process::exit(1);
+                }
+            }
+        };
+        match args[i].as_str() {
+            "--repo" => {
+                options.repository = Some(value("--repo"));
+                i += 2;
+            }
+            "--remote" => {
+                options.remote = Some(value("--remote"));
+                i += 2;
+            }
+            "--comments" => {
+                options.separate_comments = true;
+                i += 1;
+            }
+            "--dry-run" => {
+                options.dry_run = true;
+                i += 1;
+            }
+            "--help" | "-h" => print_annotate_pr_help_and_exit(),
+            other if !other.starts_with('-') && pr_number.is_none() => {
+                let number = other.trim_start_matches('#');
+                pr_number = match number.parse::<u32>() {
+                    Ok(n) => Some(n),
+                    Err(_) => {
+                        eprintln!("Invalid pull request number '{}'", other);
+                        std::process::exit(1);
+                    }
+                };
+                i += 1;
+            }
+            other => {
+                eprintln!("Unknown annotate-pr argument: {}", other);
+                print_annotate_pr_help_and_exit();
+            }
+        }
+    }
+    let Some(pr_number) = pr_number else {
+        eprintln!("Error: annotate-pr requires a pull request number");
+        print_annotate_pr_help_and_exit();
+    };
+    options.pr_number = pr_number;
+    options
+}
+
+fn annotate_pr(repo: &Repository, options: &AnnotatePrOptions) -> Result<(), GitAiError> {
+    let remote = match &options.remote {
+        Some(remote) => remote.clone(),
+        None => repo
+            .get_default_remote()?
+            .ok_or_else(|| GitAiError::Generic("Repository has no remotes".to_string()))?,
+    };
+    let repository = match &options.repository {
+        Some(repository) => Some(repository.clone()),
+        None if std::env::var("GITHUB_REPOSITORY").is_ok() => None,
+        None => Some(github_repository_for_remote(repo, &remote)?),
+    };
+    let repository = repository.as_deref();
+    let pr_number = options.pr_number;
+
+    let pull_request = get_github_pull_request(repository, pr_number)?;
+    // The head may come from a fork, but GitHub mirrors it as pull/<number>/head
+    repo.git(&[
+        "fetch",
+        "--quiet",
+        "--no-tags",
+        &remote,
+        &format!(
+            "+refs/heads/{0}:refs/remotes/{1}/{0}",
+            pull_request.base_ref, remote
+        ),
+        &format!("+pull/{0}/head:refs/github/pr/{0}", pr_number),
+    ])?;
+    fetch_authorship_notes(repo, &remote)?;
+
+    let hunks = pr_ai_hunks(repo, &pull_request.base_sha, &pull_request.head_sha)?;
+    let existing = if options.dry_run {
+        Vec::new()
+    } else {
+        list_github_pr_review_comments(repository, pr_number)?
+    };
+    let (comments, already_posted) =
+        new_comments(hunks.iter().map(hunk_review_comment).collect(), &existing);
+
+    if options.dry_run {
+        for comment in &comments {
+            let start = comment.start_line.or(comment.line).unwrap_or_default();
+            println!(
+                "{}:{}-{}: {}",
+                comment.path,
+                start,
+                comment.line.unwrap_or_default(),
+                comment.body.trim_start_matches(PR_ANNOTATION_MARKER).trim()
+            );
+        }
+        println!(
+            "Would annotate {} AI-authored hunk(s) on PR #{}",
+            comments.len(),
+            pr_number
+        );
+        return Ok(());
+    }
+
+    if comments.is_empty() {
+        println!(
+            "No new AI-authored hunks to annotate on PR #{} ({} already annotated)",
+            pr_number, already_posted
+        );
+        return Ok(());
+    }
+    if options.separate_comments {
+        for comment in &comments {
+            create_github_pr_review_comment(
+                repository,
+                pr_number,
+                &pull_request.head_sha,
+                comment,
+            )?;
+        }
+    } else {
+        create_github_pr_review(
+            repository,
+            pr_number,
+            &pull_request.head_sha,
+            &render_review_body(&hunks),
+            &comments,
+        )?;
+    }
+    println!(
+        "Annotated {} AI-authored hunk(s) on PR #{}",
+        comments.len(),
+        pr_number
+    );
+    Ok(())
+}
+
+/// `comments` minus those a previous run already posted, and how many those were
+fn new_comments(
+    comments: Vec<GithubReviewComment>,
+    existing: &[GithubReviewComment],
+) -> (Vec<GithubReviewComment>, usize) {
+    let total = comments.len();
+    let fresh: Vec<GithubReviewComment> = comments
+        .into_iter()
+        .filter(|comment| {
+            !existing.iter().any(|posted| {
+                posted.path == comment.path
+                    && posted.line == comment.line
+                    && posted.body == comment.body
+            })
+        })
+        .collect();
+    let already_posted = total - fresh.len();
+    (fresh, already_posted)
+}
+
+/// `owner/name` from a GitHub remote URL
+fn github_repository_for_remote(repo: &Repository, remote: &str) -> Result<String, GitAiError> {
+    let url = repo
+        .remotes_with_urls()?
+        .into_iter()
+        .find(|(name, _)| name == remote)
+        .map(|(_, url)| url)
+        .ok_or_else(|| GitAiError::Generic(format!("No remote named {}", remote)))?;
+    let normalized = normalize_repo_url(&url).map_err(GitAiError::Generic)?;
+    normalized
+        .strip_prefix("https://")
+        .and_then(|rest| rest.split_once('/'))
+        .map(|(_, path)| path.to_string())
+        .filter(|path| path.split('/').count() == 2)
+        .ok_or_else(|| {
+            GitAiError::Generic(format!(
+                "Can't tell the GitHub repository from {}; pass --repo <owner/name>",
+                url
+            ))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comment(line: u32, body: &str) -> GithubReviewComment {
+        GithubReviewComment {
+            path: "lib.rs".to_string(),
+            start_line: None,
+            line: Some(line),
+            body: body.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_new_comments_skips_posted_ones() {
+        let existing = vec![comment(3, "ai"), comment(9, "someone else")];
+        let (fresh, already_posted) =
+            new_comments(vec![comment(3, "ai"), comment(9, "ai")], &existing);
+        assert_eq!(fresh, vec![comment(9, "ai")]);
+        assert_eq!(already_posted, 1);
+    }
+}
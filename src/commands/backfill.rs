@@ -0,0 +1,136 @@
+use crate::authorship::backfill::{BackfillOptions, backfill, load_mapping};
+use crate::git::find_repository;
+use std::path::PathBuf;
+
+pub fn handle_backfill(args: &[String]) {
+    let mut options = BackfillOptions {
+        range: "HEAD".to_string(),
+        ..Default::default()
+    };
+    let mut range: Option<String> = None;
+    let mut mapping_path: Option<PathBuf> = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--mapping" => {
+                let Some(path) = args.get(i + 1) else {
+                    eprintln!("--mapping requires a file path");
+                    std::process::exit(1);
+                };
+                mapping_path = Some(PathBuf::from(path));
+                i += 2;
+            }
+            "--ai-only" => {
+                options.ai_only = true;
+                i += 1;
+            }
+            "--dry-run" => {
+                options.dry_run = true;
+                i += 1;
+            }
+            "--help" | "-h" => print_backfill_help_and_exit(),
+            arg if arg.starts_with('-') => {
+                eprintln!("Unknown backfill argument: {}", arg);
+                std::process::exit(1);
+            }
+            arg => {
+                if range.is_some() {
+                    eprintln!("backfill accepts a single revision or range");
+                    std::process::exit(1);
+                }
+                range = Some(arg.to_string());
+                i += 1;
+            }
+        }
+    }
+    if let Some(range) = range {
+        options.range = range;
+    }
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if let Some(path) = mapping_path {
+        options.mapping = match load_mapping(&repo, &path) {
+            Ok(mapping) => mapping,
+            Err(e) => {
+                eprintln!("Failed to read mapping {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        };
+    }
+
+    let report = match backfill(&repo, &options) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Backfill failed: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    for commit in &report.backfilled {
+        if let Some(agent) = &commit.agent {
+            println!(
+                "{}  {} ({})  {} line{}  [{}]",
+                &commit.sha[..commit.sha.len().min(8)],
+                agent.tool,
+                agent.model,
+                commit.ai_lines,
+                if commit.ai_lines == 1 { "" } else { "s" },
+                agent.source.as_str()
+            );
+        }
+    }
+    let total = report.backfilled.len();
+    let ai_commits = report
+        .backfilled
+        .iter()
+        .filter(|commit| commit.agent.is_some())
+        .count();
+    let verb = if options.dry_run {
+        "Would backfill"
+    } else {
+        "Backfilled"
+    };
+    println!(
+        "{} {} commit{}: {} AI-assisted, {} human ({} already had notes)",
+        verb,
+        total,
+        if total == 1 { "" } else { "s" },
+        ai_commits,
+        total - ai_commits,
+        report.already_noted
+    );
+    if report.skipped_human > 0 {
+        println!(
+            "Skipped {} commit{} with no sign of AI",
+            report.skipped_human,
+            if report.skipped_human == 1 { "" } else { "s" }
+        );
+    }
+}
+
+fn print_backfill_help_and_exit() -> ! {
+    eprintln!("Usage: git-ai backfill [<rev>|<range>] [--mapping <file>] [--ai-only] [--dry-run]");
+    eprintln!();
+    eprintln!("Write best-effort authorship notes for commits made before git-ai was installed.");
+    eprintln!("A commit is attributed to an agent as a whole when its author is a known agent,");
+    eprintln!("a Co-authored-by trailer names one, or the mapping file says so. Commits that");
+    eprintln!("already have a note are left alone.");
+    eprintln!();
+    eprintln!(
+        "  <rev>|<range>        History to backfill (default: everything reachable from HEAD)"
+    );
+    eprintln!("  --mapping <file>     Imported attributions: CSV rows of commit,tool[,model] or a");
+    eprintln!("                       JSON array of {{\"commit\", \"tool\", \"model\"}}; tool");
+    eprintln!("                       \"human\" marks a commit as written without AI");
+    eprintln!("  --ai-only            Only write notes for commits attributed to an agent");
+    eprintln!("  --dry-run            Show what would be written without writing notes");
+    std::process::exit(0);
+}
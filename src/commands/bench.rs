@@ -0,0 +1,337 @@
+use crate::authorship::attribution_tracker::Attribution;
+use crate::authorship::authorship_log::{LineRange, PromptRecord};
+use crate::authorship::authorship_log_serialization::{AttestationEntry, AuthorshipLog};
+use crate::authorship::imara_diff_utils::compute_line_changes;
+use crate::authorship::stats::{stats_for_commit_stats, stats_from_authorship_log};
+use crate::authorship::working_log::AgentId;
+use crate::commands::checkpoint::{make_entry_for_file, make_summary_entry_for_file};
+use crate::commands::hooks::push_hooks::extract_remote_from_push_args;
+use crate::git::cli_parser::parse_git_cli_args;
+use crate::git::find_repository_in_path;
+use crate::git::refs::show_authorship_note;
+use crate::git::repository::Repository;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const DEFAULT_ITERATIONS: usize = 20;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchResult {
+    pub name: String,
+    pub iterations: usize,
+    pub median_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+}
+
+pub fn handle_bench(args: &[String]) {
+    let mut iterations = DEFAULT_ITERATIONS;
+    let mut json_output = false;
+    let mut max_ms: Option<f64> = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--iterations" | "-n" => {
+                iterations = match args.get(i + 1).and_then(|v| v.parse().ok()) {
+                    Some(n) if n > 0 => n,
+                    _ => {
+                        eprintln!("--iterations requires a positive integer");
+                        std::process::exit(1);
+                    }
+                };
+                i += 2;
+            }
+            "--max-ms" => {
+                max_ms = match args.get(i + 1).and_then(|v| v.parse().ok()) {
+                    Some(ms) => Some(ms),
+                    None => {
+                        eprintln!("--max-ms requires a number of milliseconds");
+                        std::process::exit(1);
+                    }
+                };
+                i += 2;
+            }
+            "--json" => {
+                json_output = true;
+                i += 1;
+            }
+            "--help" | "-h" => print_bench_help_and_exit(),
+            other => {
+                eprintln!("Unknown bench argument: {}", other);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let repo = match find_repository_in_path(".") {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let results = run_repo_benchmarks(&repo, iterations);
+
+    if json_output {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&results).unwrap_or_else(|_| "[]".to_string())
+        );
+    } else {
+        println!(
+            "{:<24} {:>6} {:>12} {:>12} {:>12}",
+            "benchmark", "iters", "median (ms)", "p95 (ms)", "max (ms)"
+        );
+        for r in &results {
+            println!(
+                "{:<24} {:>6} {:>12.3} {:>12.3} {:>12.3}",
+                r.name, r.iterations, r.median_ms, r.p95_ms, r.max_ms
+            );
+        }
+    }
+
+    // Regression gate: fail if any hot path's p95 exceeds the budget
+    if let Some(budget) = max_ms {
+        let over: Vec<&BenchResult> = results.iter().filter(|r| r.p95_ms > budget).collect();
+        if !over.is_empty() {
+            for r in over {
+                eprintln!(
+                    "{} exceeded budget: p95 {:.3}ms > {:.3}ms",
+                    r.name, r.p95_ms, budget
+                );
+            }
+            std::process::exit(1);
+        }
+    }
+}
+
+fn print_bench_help_and_exit() -> ! {
+    eprintln!("Usage: git-ai bench [--iterations <n>] [--max-ms <ms>] [--json]");
+    eprintln!();
+    eprintln!("Time git-ai hot paths against the current repository.");
+    eprintln!();
+    eprintln!("  --iterations, -n <n>  Iterations per benchmark (default: 20)");
+    eprintln!("  --max-ms <ms>         Exit 1 if any benchmark's p95 exceeds this budget");
+    eprintln!("  --json                Output results as JSON");
+    std::process::exit(0);
+}
+
+/// Run every hot-path benchmark, preferring real data from `repo` over synthetic workloads.
+pub fn run_repo_benchmarks(repo: &Repository, iterations: usize) -> Vec<BenchResult> {
+    let mut results = Vec::new();
+
+    let remotes = repo.remotes().unwrap_or_default();
+    let push_args = refspec_workload(remotes.first().map(String::as_str).unwrap_or("origin"));
+    results.push(measure("refspec_decision", iterations, || {
+        refspec_decision(&push_args, &remotes)
+    }));
+
+    let head_sha = repo.head().ok().and_then(|h| h.target().ok());
+    let note = head_sha
+        .as_deref()
+        .and_then(|sha| show_authorship_note(repo, sha))
+        .unwrap_or_else(|| {
+            note_workload(50, 20)
+                .serialize_to_string()
+                .unwrap_or_default()
+        });
+    results.push(measure("note_parse", iterations, || {
+        AuthorshipLog::deserialize_from_string(&note).ok()
+    }));
+
+    let log =
+        AuthorshipLog::deserialize_from_string(&note).unwrap_or_else(|_| note_workload(50, 20));
+    results.push(measure("note_serialize", iterations, || {
+        log.serialize_to_string().ok()
+    }));
+
+    let (old, new) = synthetic_file_pair(2_000);
+    results.push(measure("diff_attribution", iterations, || {
+        compute_line_changes(&old, &new).len()
+    }));
+
+    let (old, new, attributions) = synthetic_attributed_file_pair(2_000);
+    results.push(measure("checkpoint_entry", iterations, || {
+        make_entry_for_file("bench.rs", "", "ai", &old, &attributions, &new, 2).ok()
+    }));
+
+    let (old, new, attributions) = synthetic_attributed_file_pair(20_000);
+    results.push(measure("checkpoint_summary_entry", iterations, || {
+        make_summary_entry_for_file("bench.rs", "", "ai", &old, &attributions, &new, 2).ok()
+    }));
+
+    results.push(measure("stats_from_note", iterations, || {
+        stats_from_authorship_log(Some(&log), 1_000, 100, 500, &BTreeMap::new())
+    }));
+
+    if let Some(sha) = head_sha.as_deref() {
+        results.push(measure("stats_walk_head", iterations, || {
+            stats_for_commit_stats(repo, sha, &[]).ok()
+        }));
+    }
+
+    results
+}
+
+/// Time `f` over `iterations` runs after a single warmup.
+pub fn measure<T, F: FnMut() -> T>(name: &str, iterations: usize, mut f: F) -> BenchResult {
+    black_box(f());
+    let mut samples: Vec<Duration> = (0..iterations.max(1))
+        .map(|_| {
+            let start = Instant::now();
+            black_box(f());
+            start.elapsed()
+        })
+        .collect();
+    samples.sort();
+
+    let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+    let p95_index = ((samples.len() as f64) * 0.95).ceil() as usize;
+    BenchResult {
+        name: name.to_string(),
+        iterations: samples.len(),
+        median_ms: ms(samples[samples.len() / 2]),
+        p95_ms: ms(samples[p95_index.clamp(1, samples.len()) - 1]),
+        max_ms: ms(samples[samples.len() - 1]),
+    }
+}
+
+/// The pre-push decision of which remote receives refs/notes/ai
+pub fn refspec_decision(args: &[String], remotes: &[String]) -> Option<String> {
+    let parsed = parse_git_cli_args(args);
+    extract_remote_from_push_args(&parsed.command_args, remotes)
+}
+
+pub fn refspec_workload(remote: &str) -> Vec<String> {
+    [
+        "-c",
+        "core.quotepath=false",
+        "push",
+        "--force-with-lease",
+        "-u",
+        remote,
+        "HEAD:refs/heads/feature/bench",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect()
+}
+
+/// A note with `files` files, each holding `ranges_per_file` AI ranges from a handful of
+/// prompts, measured when HEAD has no note
+pub fn note_workload(files: usize, ranges_per_file: usize) -> AuthorshipLog {
+    let mut log = AuthorshipLog::new();
+    let prompt_hashes: Vec<String> = (0..4).map(|i| format!("{:016x}", 0xabc0 + i)).collect();
+
+    for hash in &prompt_hashes {
+        log.metadata.prompts.insert(
+            hash.clone(),
+            PromptRecord {
+                agent_id: AgentId {
+                    tool: "bench".to_string(),
+                    id: hash.clone(),
+                    model: "bench-model".to_string(),
+                },
+                human_author: Some("Bench <bench@example.com>".to_string()),
+                messages: Vec::new(),
+                total_additions: 100,
+                total_deletions: 10,
+                accepted_lines: 90,
+                overriden_lines: 5,
+                messages_url: None,
+                token_usage: None,
+                turns: Vec::new(),
+            },
+        );
+    }
+
+    for f in 0..files {
+        let file = log.get_or_create_file(&format!("src/module_{}/file_{}.rs", f % 10, f));
+        for r in 0..ranges_per_file {
+            let start = (r as u32) * 10 + 1;
+            file.add_entry(AttestationEntry::new(
+                prompt_hashes[r % prompt_hashes.len()].clone(),
+                vec![
+                    LineRange::Range(start, start + 4),
+                    LineRange::Single(start + 7),
+                ],
+            ));
+        }
+    }
+
+    log
+}
+
+/// Two versions of a `lines`-line file with scattered edits, for line-level diffing
+pub fn synthetic_file_pair(lines: usize) -> (String, String) {
+    let mut old = String::new();
+    let mut new = String::new();
+    for i in 0..lines {
+        let line = format!("let value_{} = compute({}, \"payload\");\n", i, i * 7);
+        old.push_str(&line);
+        match i % 17 {
+            0 => new.push_str(&format!("let value_{} = compute_v2({});\n", i, i)),
+            5 => {
+                new.push_str(&line);
+                new.push_str(&format!("// inserted after {}\n", i));
+            }
+            11 => {}
+            _ => new.push_str(&line),
+        }
+    }
+    (old, new)
+}
+
+/// `synthetic_file_pair` plus character attributions for the old version, with an AI author
+/// on every other block of 10 lines, as a checkpoint would find them
+pub fn synthetic_attributed_file_pair(lines: usize) -> (String, String, Vec<Attribution>) {
+    let (old, new) = synthetic_file_pair(lines);
+    let mut attributions = Vec::new();
+    let mut offset = 0;
+    for (i, line) in old.split_inclusive('\n').enumerate() {
+        let author = if (i / 10) % 2 == 0 { "human" } else { "ai" };
+        attributions.push(Attribution::new(
+            offset,
+            offset + line.len(),
+            author.to_string(),
+            1,
+        ));
+        offset += line.len();
+    }
+    (old, new, attributions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measure_reports_ordered_percentiles() {
+        let result = measure("noop", 10, || 1 + 1);
+        assert_eq!(result.iterations, 10);
+        assert!(result.median_ms <= result.p95_ms);
+        assert!(result.p95_ms <= result.max_ms);
+    }
+
+    #[test]
+    fn test_note_workload_round_trips() {
+        let log = note_workload(3, 4);
+        let serialized = log.serialize_to_string().unwrap();
+        let parsed = AuthorshipLog::deserialize_from_string(&serialized).unwrap();
+        assert_eq!(parsed.attestations.len(), 3);
+        assert_eq!(parsed.metadata.prompts.len(), 4);
+    }
+
+    #[test]
+    fn test_refspec_decision_picks_push_remote() {
+        let remotes = vec!["origin".to_string(), "upstream".to_string()];
+        assert_eq!(
+            refspec_decision(&refspec_workload("upstream"), &remotes),
+            Some("upstream".to_string())
+        );
+    }
+}
@@ -1,6 +1,10 @@
+use crate::authorship::attribution_ignore::is_ignored;
 use crate::authorship::authorship_log::PromptRecord;
 use crate::authorship::authorship_log_serialization::AuthorshipLog;
+use crate::authorship::note_schema;
+use crate::authorship::stats_cache::with_stats_cache;
 use crate::authorship::working_log::CheckpointKind;
+use crate::commands::machine_output::{self, OutputFormat};
 use crate::error::GitAiError;
 use crate::git::refs::get_reference_as_authorship_log_v3;
 use crate::git::repository::Repository;
@@ -9,7 +13,7 @@ use crate::git::repository::{exec_git, exec_git_stdin};
 use crate::utils::normalize_to_posix;
 use chrono::{DateTime, FixedOffset, TimeZone, Utc};
 use serde::Serialize;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::io::{self, IsTerminal, Write};
 use std::sync::LazyLock;
@@ -97,6 +101,11 @@ pub struct GitAiBlameOptions {
     // Color options
     pub color_lines: bool,
     pub color_by_age: bool,
+    /// Highlight AI-authored lines. None means auto (color when stdout is a terminal).
+    pub color: Option<bool>,
+
+    /// Show "tool/model" instead of just the tool for AI-authored lines
+    pub show_model: bool,
 
     // Progress options
     pub progress: bool,
@@ -171,6 +180,8 @@ impl Default for GitAiBlameOptions {
             no_ignore_revs_file: false,
             color_lines: false,
             color_by_age: false,
+            color: None,
+            show_model: false,
             progress: false,
             date_format: None,
             contents_file: None,
@@ -350,7 +361,7 @@ impl Repository {
         }
 
         // Step 2: Overlay AI authorship information
-        let (line_authors, prompt_records, authorship_logs, prompt_commits) =
+        let (line_authors, prompt_records, authorship_logs, prompt_commits, ai_lines) =
             overlay_ai_authorship(self, &all_blame_hunks, &relative_file_path, &options)?;
 
         if options.no_output {
@@ -388,6 +399,7 @@ impl Repository {
             output_default_format(
                 self,
                 &line_authors,
+                &ai_lines,
                 &relative_file_path,
                 &lines,
                 &line_ranges,
@@ -812,10 +824,12 @@ fn overlay_ai_authorship(
         HashMap<String, PromptRecord>,
         Vec<AuthorshipLog>,
         HashMap<String, Vec<String>>, // prompt_hash -> commit_shas
+        HashSet<u32>,                 // current line numbers attributed to AI
     ),
     GitAiError,
 > {
     let mut line_authors: HashMap<u32, String> = HashMap::new();
+    let mut ai_lines: HashSet<u32> = HashSet::new();
     let mut prompt_records: HashMap<String, PromptRecord> = HashMap::new();
     // Track which commits contain each prompt hash
     let mut prompt_commits: HashMap<String, std::collections::HashSet<String>> = HashMap::new();
@@ -825,9 +839,31 @@ fn overlay_ai_authorship(
     // Cache for foreign prompts to avoid repeated grepping
     let mut foreign_prompts_cache: HashMap<String, Option<PromptRecord>> = HashMap::new();
 
+    // Read the notes of every blamed commit in one batch. If the stats cache is unavailable,
+    // the loop below falls back to reading them one at a time.
+    let mut blamed_commits: Vec<String> =
+        blame_hunks.iter().map(|h| h.commit_sha.clone()).collect();
+    blamed_commits.sort();
+    blamed_commits.dedup();
+    if let Ok(mut logs) =
+        with_stats_cache(repo, |cache| cache.authorship_logs(repo, &blamed_commits))
+    {
+        for sha in blamed_commits {
+            let log = logs
+                .remove(&sha)
+                .filter(|log| note_schema::can_read(&log.metadata.schema_version));
+            commit_authorship_cache.insert(sha, log);
+        }
+    }
+
+    // Paths excluded from attribution blame to their git authors, whatever older notes say
+    let ignored = is_ignored(repo, file_path);
+
     for hunk in blame_hunks {
         // Check if we've already looked up this commit's authorship
-        let authorship_log = if let Some(cached) = commit_authorship_cache.get(&hunk.commit_sha) {
+        let authorship_log = if ignored {
+            None
+        } else if let Some(cached) = commit_authorship_cache.get(&hunk.commit_sha) {
             cached.clone()
         } else {
             // Try to get authorship log for this commit
@@ -864,10 +900,19 @@ fn overlay_ai_authorship(
                             .insert(hunk.commit_sha.clone());
                         if options.use_prompt_hashes_as_names {
                             line_authors.insert(current_line_num, prompt_hash.clone());
+                        } else if options.show_model && !prompt_record.agent_id.model.is_empty() {
+                            line_authors.insert(
+                                current_line_num,
+                                format!(
+                                    "{}/{}",
+                                    prompt_record.agent_id.tool, prompt_record.agent_id.model
+                                ),
+                            );
                         } else {
                             line_authors
                                 .insert(current_line_num, prompt_record.agent_id.tool.clone());
                         }
+                        ai_lines.insert(current_line_num);
                         prompt_records.insert(prompt_hash, prompt_record.clone());
                     } else {
                         // Has authorship log but line not AI = human-authored
@@ -926,6 +971,7 @@ fn overlay_ai_authorship(
         prompt_records,
         authorship_logs,
         prompt_commits_vec,
+        ai_lines,
     ))
 }
 
@@ -1057,7 +1103,7 @@ fn output_json_format(
         prompts: filtered_prompts,
     };
 
-    let json_str = serde_json::to_string_pretty(&output)
+    let json_str = serde_json::to_string_pretty(&machine_output::versioned(&output))
         .map_err(|e| GitAiError::Generic(format!("Failed to serialize JSON output: {}", e)))?;
 
     println!("{}", json_str);
@@ -1271,12 +1317,16 @@ fn output_incremental_format(
 fn output_default_format(
     repo: &Repository,
     line_authors: &HashMap<u32, String>,
+    ai_lines: &HashSet<u32>,
     file_path: &str,
     lines: &[&str],
     line_ranges: &[(u32, u32)],
     options: &GitAiBlameOptions,
 ) -> Result<(), GitAiError> {
     let mut output = String::new();
+    let use_color = options
+        .color
+        .unwrap_or_else(|| io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none());
 
     // Use options that don't split hunks for formatting purposes
     let mut no_split_options = options.clone();
@@ -1374,6 +1424,17 @@ fn output_default_format(
                     author_display
                 };
 
+                // Color after padding so escape codes don't count towards the width
+                let padded_author = if !use_color {
+                    padded_author
+                } else if ai_lines.contains(&line_num) {
+                    format!("\x1b[35m{}\x1b[0m", padded_author) // Magenta for AI
+                } else if options.mark_unknown && author == "Unknown" {
+                    format!("\x1b[90m{}\x1b[0m", padded_author) // Gray for unknown
+                } else {
+                    padded_author
+                };
+
                 let _filename_display = if options.show_name {
                     format!("{} ", file_path)
                 } else {
@@ -1459,10 +1520,12 @@ fn output_default_format(
         print!("{}", output);
     } else if io::stdout().is_terminal() {
         // Try to use the specified pager
-        match std::process::Command::new(&pager)
-            .stdin(std::process::Stdio::piped())
-            .spawn()
-        {
+        let mut pager_cmd = std::process::Command::new(&pager);
+        // Same default as git, so less passes colors through
+        if std::env::var_os("LESS").is_none() {
+            pager_cmd.env("LESS", "FRX");
+        }
+        match pager_cmd.stdin(std::process::Stdio::piped()).spawn() {
             Ok(mut child) => {
                 if let Some(stdin) = child.stdin.as_mut() {
                     if stdin.write_all(output.as_bytes()).is_ok() {
@@ -1675,6 +1738,22 @@ pub fn parse_blame_args(args: &[String]) -> Result<(String, GitAiBlameOptions),
                 options.color_by_age = true;
                 i += 1;
             }
+            "--color" | "--color=always" => {
+                options.color = Some(true);
+                i += 1;
+            }
+            "--no-color" | "--color=never" => {
+                options.color = Some(false);
+                i += 1;
+            }
+            "--color=auto" => {
+                options.color = None;
+                i += 1;
+            }
+            "--show-model" => {
+                options.show_model = true;
+                i += 1;
+            }
 
             // Progress options
             "--progress" => {
@@ -1772,6 +1851,26 @@ pub fn parse_blame_args(args: &[String]) -> Result<(String, GitAiBlameOptions),
                 options.json = true;
                 i += 1;
             }
+            // text, json, or porcelain (git's --porcelain)
+            "--format" => {
+                if i + 1 >= args.len() {
+                    return Err(GitAiError::Generic(
+                        "Missing argument for --format".to_string(),
+                    ));
+                }
+                match OutputFormat::parse(&args[i + 1]) {
+                    Some(OutputFormat::Text) => {}
+                    Some(OutputFormat::Json) => options.json = true,
+                    Some(OutputFormat::Porcelain) => options.porcelain = true,
+                    None => {
+                        return Err(GitAiError::Generic(format!(
+                            "--format must be text, json or porcelain (got {})",
+                            args[i + 1]
+                        )));
+                    }
+                }
+                i += 2;
+            }
 
             // Mark unknown authorship
             "--mark-unknown" => {
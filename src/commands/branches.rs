@@ -0,0 +1,199 @@
+use crate::error::GitAiError;
+use crate::git::find_repository_in_path;
+use crate::git::refs::noted_commits;
+use crate::git::repository::Repository;
+use serde::Serialize;
+
+/// Local-only namespace used to keep archived branch tips (and therefore their notes) reachable
+pub const AI_ARCHIVE_REF_PREFIX: &str = "refs/ai-archive/";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StaleBranch {
+    pub branch: String,
+    pub tip: String,
+    /// Commits only reachable from this branch that carry an authorship note
+    pub noted_commits: Vec<String>,
+    pub last_commit_unix: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archived_ref: Option<String>,
+}
+
+pub fn handle_branches(args: &[String]) {
+    let mut stale = false;
+    let mut archive = false;
+    let mut json = false;
+
+    for arg in args {
+        match arg.as_str() {
+            "--stale" => stale = true,
+            "--archive" => archive = true,
+            "--json" => json = true,
+            "--help" | "-h" => print_branches_help_and_exit(),
+            _ => {
+                eprintln!("Unknown branches argument: {}", arg);
+                print_branches_help_and_exit();
+            }
+        }
+    }
+
+    if !stale {
+        print_branches_help_and_exit();
+    }
+
+    let repo = match find_repository_in_path(".") {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let mut branches = match find_stale_branches(&repo) {
+        Ok(branches) => branches,
+        Err(e) => {
+            eprintln!("Failed to analyze branches: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if archive {
+        for branch in branches.iter_mut() {
+            match archive_branch_notes(&repo, branch) {
+                Ok(archived_ref) => branch.archived_ref = Some(archived_ref),
+                Err(e) => {
+                    eprintln!("Failed to archive branch {}: {}", branch.branch, e);
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&branches).unwrap_or_else(|_| "[]".to_string())
+        );
+        return;
+    }
+
+    if branches.is_empty() {
+        println!("No branches hold AI attribution that is unreachable from elsewhere.");
+        return;
+    }
+
+    println!("Branches whose AI attribution would become unreachable if deleted:");
+    for branch in &branches {
+        let age = chrono::DateTime::from_timestamp(branch.last_commit_unix, 0)
+            .map(|d| d.format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+        print!(
+            "  {}  {} noted commit{} not merged or pushed (last commit {})",
+            branch.branch,
+            branch.noted_commits.len(),
+            if branch.noted_commits.len() == 1 {
+                ""
+            } else {
+                "s"
+            },
+            age
+        );
+        match &branch.archived_ref {
+            Some(archived_ref) => println!("  -> archived to {}", archived_ref),
+            None => println!(),
+        }
+    }
+    if !archive {
+        println!();
+        println!(
+            "Run `git-ai branches --stale --archive` to preserve these notes before deleting."
+        );
+    }
+}
+
+fn print_branches_help_and_exit() -> ! {
+    eprintln!("Usage: git-ai branches --stale [--archive] [--json]");
+    eprintln!();
+    eprintln!("  --stale     List branches holding AI-noted commits not reachable from any other");
+    eprintln!("              branch, tag, or remote-tracking ref");
+    eprintln!("  --archive   Keep those commits and their notes reachable under refs/ai-archive/");
+    eprintln!("  --json      Output in JSON format");
+    std::process::exit(0);
+}
+
+/// Find local branches whose unique commits carry authorship notes.
+pub fn find_stale_branches(repo: &Repository) -> Result<Vec<StaleBranch>, GitAiError> {
+    let noted = noted_commits(repo)?;
+    if noted.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let listing = repo.git(&[
+        "for-each-ref",
+        "--format=%(refname:short)%09%(objectname)%09%(committerdate:unix)",
+        "refs/heads/",
+    ])?;
+
+    let mut stale = Vec::new();
+    for line in listing.lines() {
+        let mut parts = line.split('\t');
+        let (Some(branch), Some(tip), Some(date)) = (parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        let noted_commits: Vec<String> = commits_unique_to_branch(repo, branch)?
+            .into_iter()
+            .filter(|sha| noted.contains(sha))
+            .collect();
+        if noted_commits.is_empty() {
+            continue;
+        }
+        stale.push(StaleBranch {
+            branch: branch.to_string(),
+            tip: tip.to_string(),
+            noted_commits,
+            last_commit_unix: date.parse().unwrap_or(0),
+            archived_ref: None,
+        });
+    }
+
+    Ok(stale)
+}
+
+/// Noted commits that would become unreachable if `branch` were deleted.
+pub fn unreachable_noted_commits_for_branch(
+    repo: &Repository,
+    branch: &str,
+) -> Result<Vec<String>, GitAiError> {
+    let noted = noted_commits(repo)?;
+    if noted.is_empty() {
+        return Ok(Vec::new());
+    }
+    Ok(commits_unique_to_branch(repo, branch)?
+        .into_iter()
+        .filter(|sha| noted.contains(sha))
+        .collect())
+}
+
+/// Point refs/ai-archive/<branch> at the branch tip so its commits and notes survive deletion and gc.
+pub fn archive_branch_notes(repo: &Repository, branch: &StaleBranch) -> Result<String, GitAiError> {
+    let archived_ref = format!("{}{}", AI_ARCHIVE_REF_PREFIX, branch.branch);
+    repo.git(&["update-ref", &archived_ref, &branch.tip])?;
+    Ok(archived_ref)
+}
+
+fn commits_unique_to_branch(repo: &Repository, branch: &str) -> Result<Vec<String>, GitAiError> {
+    let branch_ref = format!("refs/heads/{}", branch);
+    // --exclude patterns for --branches are matched without the refs/heads/ prefix
+    let exclude = format!("--exclude={}", branch);
+    let output = repo.git(&[
+        "rev-list",
+        &branch_ref,
+        "--not",
+        &exclude,
+        "--branches",
+        "--tags",
+        "--remotes",
+        &format!("--glob={}*", AI_ARCHIVE_REF_PREFIX),
+    ])?;
+    Ok(output.lines().map(|line| line.trim().to_string()).collect())
+}
@@ -0,0 +1,427 @@
+//! `git-ai bundle`: carry authorship notes through `git bundle` files and emailed patches.
+//!
+//! A bundle gets an extra ref, refs/notes/ai-bundle, holding a notes commit with just the notes
+//! for the bundled commits; `apply` fetches it and merges it into refs/notes/ai like notes
+//! fetched from a remote. Patches written by `format-patch` carry each commit's note in the
+//! commentary after the `---` line, which `git am` ignores; `apply` runs `git am` and attaches
+//! the notes to the commits it creates.
+
+use crate::error::GitAiError;
+use crate::git::authorship_traversal::get_notes_list;
+use crate::git::find_repository;
+use crate::git::notes_merge::merge_staged_notes;
+use crate::git::refs::{notes_add, show_authorship_note};
+use crate::git::repository::{Repository, exec_git, exec_git_stdin};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Temporary ref the bundled notes are written to and fetched into
+const BUNDLE_NOTES_REF: &str = "refs/notes/ai-bundle";
+/// Starts a note block in a patch; the note follows, one line per line indented by 4 spaces
+pub const PATCH_NOTE_HEADER: &str = "Git-AI-Authorship-Note:";
+const PATCH_NOTE_INDENT: &str = "    ";
+
+pub fn handle_bundle(args: &[String]) {
+    let Some(subcommand) = args.first() else {
+        print_bundle_help_and_exit();
+    };
+
+    let result = match subcommand.as_str() {
+        "create" => handle_create(&args[1..]),
+        "apply" => handle_apply(&args[1..]),
+        "--help" | "-h" | "help" => print_bundle_help_and_exit(),
+        other => {
+            eprintln!("Unknown bundle subcommand: {}", other);
+            print_bundle_help_and_exit();
+        }
+    };
+
+    if let Err(e) = result {
+        eprintln!("git-ai bundle {} failed: {}", subcommand, e);
+        std::process::exit(1);
+    }
+}
+
+fn print_bundle_help_and_exit() -> ! {
+    eprintln!("Usage: git-ai bundle <create|apply> [options]");
+    eprintln!();
+    eprintln!("  create <file> <rev>...      Write a git bundle with the commits and their notes");
+    eprintln!("  create --patches <dir> <rev>...");
+    eprintln!("                              Write format-patch files with each commit's note");
+    eprintln!("  apply <bundle>              Import the notes in a bundle into refs/notes/ai");
+    eprintln!("  apply <patch|dir>...        Apply patches with git am and restore their notes");
+    eprintln!();
+    eprintln!("Commits in a bundle are fetched as usual, e.g. git pull <bundle> <branch>.");
+    std::process::exit(0);
+}
+
+fn open_repository() -> Repository {
+    match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Paths are given relative to where the command runs, but git runs at the repository root
+fn absolute(path: &str) -> PathBuf {
+    std::path::absolute(path).unwrap_or_else(|_| PathBuf::from(path))
+}
+
+fn handle_create(args: &[String]) -> Result<(), GitAiError> {
+    let mut patches_dir: Option<PathBuf> = None;
+    let mut positional: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--patches" => {
+                let Some(dir) = args.get(i + 1) else {
+                    eprintln!("Error: --patches requires a directory");
+                    std::process::exit(1);
+                };
+                patches_dir = Some(absolute(dir));
+                i += 2;
+            }
+            _ => {
+                positional.push(args[i].clone());
+                i += 1;
+            }
+        }
+    }
+
+    let repo = open_repository();
+    match patches_dir {
+        Some(dir) => {
+            if positional.is_empty() {
+                eprintln!("Error: bundle create --patches requires a revision range");
+                std::process::exit(1);
+            }
+            let patches = create_patches(&repo, &dir, &positional)?;
+            let noted = patches.iter().filter(|patch| patch.noted).count();
+            for patch in &patches {
+                println!("{}", patch.path.display());
+            }
+            println!(
+                "Wrote {} patch(es), {} with authorship notes",
+                patches.len(),
+                noted
+            );
+        }
+        None => {
+            let Some((file, revs)) = positional.split_first() else {
+                eprintln!("Error: bundle create requires a file and a revision range");
+                std::process::exit(1);
+            };
+            if revs.is_empty() {
+                eprintln!("Error: bundle create requires a revision range");
+                std::process::exit(1);
+            }
+            let summary = create_bundle(&repo, &absolute(file), revs)?;
+            println!(
+                "Bundled {} commit(s) with {} authorship note(s) into {}",
+                summary.commits, summary.notes, file
+            );
+        }
+    }
+    Ok(())
+}
+
+fn handle_apply(args: &[String]) -> Result<(), GitAiError> {
+    if args.is_empty() {
+        eprintln!("Error: bundle apply requires a bundle or patch files");
+        std::process::exit(1);
+    }
+    let repo = open_repository();
+
+    if args.len() == 1 && is_bundle(Path::new(&args[0])) {
+        let imported = apply_bundle(&repo, &absolute(&args[0]))?;
+        println!("Imported {} authorship note(s) from {}", imported, args[0]);
+        return Ok(());
+    }
+
+    let mut patches: Vec<PathBuf> = Vec::new();
+    for arg in args {
+        let path = absolute(arg);
+        if path.is_dir() {
+            let mut entries: Vec<PathBuf> = std::fs::read_dir(&path)?
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|entry| entry.extension().is_some_and(|ext| ext == "patch"))
+                .collect();
+            entries.sort();
+            patches.extend(entries);
+        } else {
+            patches.push(path);
+        }
+    }
+    let restored = apply_patches(&repo, &patches)?;
+    println!(
+        "Applied {} patch(es), restored {} authorship note(s)",
+        patches.len(),
+        restored
+    );
+    Ok(())
+}
+
+pub struct BundleSummary {
+    pub commits: usize,
+    pub notes: usize,
+}
+
+/// Write a git bundle of `revs` to `file`, with the notes for its commits
+pub fn create_bundle(
+    repo: &Repository,
+    file: &Path,
+    revs: &[String],
+) -> Result<BundleSummary, GitAiError> {
+    let mut rev_list = vec!["rev-list".to_string()];
+    rev_list.extend(revs.iter().cloned());
+    let commits: HashSet<String> = git_lines(repo, &rev_list)?.into_iter().collect();
+
+    let notes: Vec<(String, String)> = get_notes_list(&repo.global_args_for_exec())?
+        .into_iter()
+        .filter(|(_, commit)| commits.contains(commit))
+        .collect();
+
+    let mut args = repo.global_args_for_exec();
+    args.push("bundle".to_string());
+    args.push("create".to_string());
+    args.push("--quiet".to_string());
+    args.push(file.to_string_lossy().to_string());
+    args.extend(revs.iter().cloned());
+    if notes.is_empty() {
+        exec_git(&args)?;
+    } else {
+        write_notes_commit(repo, &notes)?;
+        args.push(BUNDLE_NOTES_REF.to_string());
+        let result = exec_git(&args);
+        repo.git(&["update-ref", "-d", BUNDLE_NOTES_REF])?;
+        result?;
+    }
+
+    Ok(BundleSummary {
+        commits: commits.len(),
+        notes: notes.len(),
+    })
+}
+
+/// Point BUNDLE_NOTES_REF at a new parentless notes commit holding just `notes`
+fn write_notes_commit(repo: &Repository, notes: &[(String, String)]) -> Result<(), GitAiError> {
+    let entries: String = notes
+        .iter()
+        .map(|(blob, commit)| format!("100644 blob {}\t{}\n", blob, commit))
+        .collect();
+    let mut args = repo.global_args_for_exec();
+    args.push("mktree".to_string());
+    let tree = String::from_utf8(exec_git_stdin(&args, entries.as_bytes())?.stdout)?
+        .trim()
+        .to_string();
+    let commit = repo
+        .git(&["commit-tree", &tree, "-m", "Notes bundled by git-ai"])?
+        .trim()
+        .to_string();
+    repo.git(&["update-ref", BUNDLE_NOTES_REF, &commit])?;
+    Ok(())
+}
+
+fn is_bundle(path: &Path) -> bool {
+    let mut header = [0u8; 16];
+    std::fs::File::open(path)
+        .and_then(|mut file| std::io::Read::read_exact(&mut file, &mut header))
+        .is_ok_and(|_| {
+            header.starts_with(b"# v2 git bundle") || header.starts_with(b"# v3 git bundle")
+        })
+}
+
+/// Merge the notes in a bundle written by [`create_bundle`] into refs/notes/ai. Returns how
+/// many notes the bundle carried.
+pub fn apply_bundle(repo: &Repository, file: &Path) -> Result<usize, GitAiError> {
+    let file = file.to_string_lossy().to_string();
+    let heads = repo.git(&["bundle", "list-heads", &file])?;
+    if !heads
+        .lines()
+        .any(|line| line.split_whitespace().nth(1) == Some(BUNDLE_NOTES_REF))
+    {
+        return Ok(0);
+    }
+
+    let refspec = format!("+{}:{}", BUNDLE_NOTES_REF, BUNDLE_NOTES_REF);
+    repo.git(&["fetch", "--quiet", "--no-tags", &file, &refspec])?;
+    let count = repo
+        .git(&["ls-tree", "-r", BUNDLE_NOTES_REF])?
+        .lines()
+        .count();
+    let result = merge_staged_notes(repo, BUNDLE_NOTES_REF);
+    repo.git(&["update-ref", "-d", BUNDLE_NOTES_REF])?;
+    result?;
+    Ok(count)
+}
+
+pub struct WrittenPatch {
+    pub path: PathBuf,
+    pub noted: bool,
+}
+
+/// Run `git format-patch -o <dir> <revs>` and add each commit's note to its patch
+pub fn create_patches(
+    repo: &Repository,
+    dir: &Path,
+    revs: &[String],
+) -> Result<Vec<WrittenPatch>, GitAiError> {
+    let mut args = vec![
+        "format-patch".to_string(),
+        "-o".to_string(),
+        dir.to_string_lossy().to_string(),
+    ];
+    args.extend(revs.iter().cloned());
+
+    let mut patches = Vec::new();
+    for path in git_lines(repo, &args)? {
+        let path = PathBuf::from(path);
+        let content = std::fs::read_to_string(&path)?;
+        let note = patch_commit(&content).and_then(|sha| show_authorship_note(repo, sha));
+        let noted = match note.and_then(|note| embed_note(&content, &note)) {
+            Some(with_note) => {
+                std::fs::write(&path, with_note)?;
+                true
+            }
+            None => false,
+        };
+        patches.push(WrittenPatch { path, noted });
+    }
+    Ok(patches)
+}
+
+/// Apply `patches` with `git am`, then attach the notes they carry to the commits `am` made.
+/// Returns how many notes were restored.
+pub fn apply_patches(repo: &Repository, patches: &[PathBuf]) -> Result<usize, GitAiError> {
+    let mut notes = Vec::new();
+    for patch in patches {
+        notes.push(extract_note(&std::fs::read_to_string(patch)?));
+    }
+
+    let head_before = repo
+        .git(&["rev-parse", "--verify", "--quiet", "HEAD"])
+        .ok()
+        .map(|sha| sha.trim().to_string());
+    let mut am_args = vec!["am".to_string()];
+    am_args.extend(
+        patches
+            .iter()
+            .map(|patch| patch.to_string_lossy().to_string()),
+    );
+    let am_result = git_lines(repo, &am_args);
+
+    // Even if `am` stopped partway, the patches it did apply keep their notes
+    let range = match &head_before {
+        Some(before) => format!("{}..HEAD", before),
+        None => "HEAD".to_string(),
+    };
+    let applied = git_lines(
+        repo,
+        &[
+            "rev-list".to_string(),
+            "--reverse".to_string(),
+            "--no-merges".to_string(),
+            range,
+        ],
+    )
+    .unwrap_or_default();
+    let mut restored = 0;
+    for (commit, note) in applied.iter().zip(&notes) {
+        if let Some(note) = note {
+            notes_add(repo, commit, note)?;
+            restored += 1;
+        }
+    }
+
+    am_result?;
+    Ok(restored)
+}
+
+/// The commit a format-patch file was made from, from its "From <sha> <date>" first line
+fn patch_commit(content: &str) -> Option<&str> {
+    let sha = content
+        .lines()
+        .next()?
+        .strip_prefix("From ")?
+        .split(' ')
+        .next()?;
+    sha.chars().all(|c| c.is_ascii_hexdigit()).then_some(sha)
+}
+
+/// Insert a note block right after the patch's `---` separator
+fn embed_note(content: &str, note: &str) -> Option<String> {
+    let separator = content.find("\n---\n")? + "\n---\n".len();
+    let mut block = format!("{}\n", PATCH_NOTE_HEADER);
+    for line in note.lines() {
+        block.push_str(PATCH_NOTE_INDENT);
+        block.push_str(line);
+        block.push('\n');
+    }
+    block.push('\n');
+    Some(format!(
+        "{}{}{}",
+        &content[..separator],
+        block,
+        &content[separator..]
+    ))
+}
+
+/// The note embedded by [`embed_note`], if the patch has one
+fn extract_note(content: &str) -> Option<String> {
+    let mut lines = content
+        .lines()
+        .skip_while(|line| *line != "---")
+        .skip_while(|line| *line != PATCH_NOTE_HEADER)
+        .skip(1);
+    let mut note = String::new();
+    for line in lines.by_ref() {
+        let Some(line) = line.strip_prefix(PATCH_NOTE_INDENT) else {
+            break;
+        };
+        note.push_str(line);
+        note.push('\n');
+    }
+    (!note.is_empty()).then_some(note)
+}
+
+fn git_lines(repo: &Repository, args: &[String]) -> Result<Vec<String>, GitAiError> {
+    let mut full_args = repo.global_args_for_exec();
+    full_args.extend(args.iter().cloned());
+    let output = exec_git(&full_args)?;
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::to_string)
+        .filter(|line| !line.is_empty())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATCH: &str = "From 3f9c2a1b0d4e5f60718293a4b5c6d7e8f9012345 Mon Sep 17 00:00:00 2001\n\
+        From: Test User <test@example.com>\n\
+        Subject: [PATCH] Add lib\n\
+        \n\
+        ---\n \
+        lib.rs | 1 +\n\
+        \n\
+        diff --git a/lib.rs b/lib.rs\n";
+
+    #[test]
+    fn test_note_round_trips_through_patch() {
+        assert_eq!(
+            patch_commit(PATCH),
+            Some("3f9c2a1b0d4e5f60718293a4b5c6d7e8f9012345")
+        );
+        let note = "lib.rs\n  abcd 1\n---\n{\n  \"schema_version\": \"authorship/3.0.0\"\n}\n";
+        let patch = embed_note(PATCH, note).unwrap();
+        assert!(patch.contains("---\nGit-AI-Authorship-Note:\n    lib.rs\n"));
+        assert!(patch.contains("}\n\n lib.rs | 1 +"));
+        assert_eq!(extract_note(&patch).as_deref(), Some(note));
+        assert_eq!(extract_note(PATCH), None);
+    }
+}
@@ -1,9 +1,12 @@
+use crate::authorship::attribution_ignore::repo_ignore_patterns;
 use crate::authorship::attribution_tracker::{
     Attribution, AttributionTracker, INITIAL_ATTRIBUTION_TS, LineAttribution,
 };
 use crate::authorship::authorship_log::PromptRecord;
-use crate::authorship::authorship_log_serialization::generate_short_hash;
+use crate::authorship::authorship_log_serialization::{generate_prompt_hash, generate_short_hash};
 use crate::authorship::imara_diff_utils::{LineChangeTag, compute_line_changes};
+use crate::authorship::range_authorship::should_ignore_file;
+use crate::authorship::token_usage::{TokenUsage, interaction_usage};
 use crate::authorship::working_log::CheckpointKind;
 use crate::authorship::working_log::{Checkpoint, WorkingLogEntry};
 use crate::commands::blame::{GitAiBlameOptions, OLDEST_AI_BLAME_DATE};
@@ -13,6 +16,7 @@ use crate::error::GitAiError;
 use crate::git::repo_storage::{PersistedWorkingLog, RepoStorage};
 use crate::git::repository::Repository;
 use crate::git::status::{EntryKind, StatusCode};
+use crate::observability::spans::HookSpan;
 use crate::utils::{debug_log, normalize_to_posix};
 use futures::stream::{self, StreamExt};
 use sha2::{Digest, Sha256};
@@ -22,7 +26,7 @@ use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
 /// Per-file line statistics (in-memory only, not persisted)
 #[derive(Debug, Clone, Default)]
-struct FileLineStats {
+pub struct FileLineStats {
     additions: u32,
     deletions: u32,
     additions_sloc: u32,
@@ -82,6 +86,48 @@ pub fn run(
     agent_run_result: Option<AgentRunResult>,
     is_pre_commit: bool,
 ) -> Result<(usize, usize, usize), GitAiError> {
+    let mut span = HookSpan::start("checkpoint");
+    span.set_attribute("checkpoint.kind", format!("{:?}", kind));
+    if let Some(tool) = agent_run_result.as_ref().map(|r| &r.agent_id.tool) {
+        span.set_attribute("checkpoint.tool", tool);
+    }
+    span.record_result(run_inner(
+        repo,
+        author,
+        CheckpointOptions {
+            kind,
+            show_working_log,
+            reset,
+            quiet,
+            agent_run_result,
+            is_pre_commit,
+        },
+    ))
+}
+
+/// What [`run`] was asked to do, handed through to [`run_inner`] inside the hook span
+struct CheckpointOptions {
+    kind: CheckpointKind,
+    show_working_log: bool,
+    reset: bool,
+    quiet: bool,
+    agent_run_result: Option<AgentRunResult>,
+    is_pre_commit: bool,
+}
+
+fn run_inner(
+    repo: &Repository,
+    author: &str,
+    options: CheckpointOptions,
+) -> Result<(usize, usize, usize), GitAiError> {
+    let CheckpointOptions {
+        kind,
+        show_working_log,
+        reset,
+        quiet,
+        agent_run_result,
+        is_pre_commit,
+    } = options;
     let checkpoint_start = Instant::now();
     debug_log(&format!("[BENCHMARK] Starting checkpoint run"));
 
@@ -366,6 +412,13 @@ pub fn run(
             checkpoint.transcript = Some(agent_run.transcript.clone().unwrap_or_default());
             checkpoint.agent_id = Some(agent_run.agent_id.clone());
             checkpoint.agent_metadata = agent_run.agent_metadata.clone();
+            checkpoint.prompt_hash = checkpoint
+                .transcript
+                .as_ref()
+                .and_then(|t| t.last_user_message())
+                .map(generate_prompt_hash);
+            checkpoint.token_usage =
+                interaction_usage(&checkpoint, &checkpoints, stored_session_usage(&checkpoint));
         }
         debug_log(&format!(
             "[BENCHMARK] Checkpoint creation took {:?}",
@@ -402,6 +455,11 @@ pub fn run(
             "[BENCHMARK] Appending checkpoint to working log took {:?}",
             append_start.elapsed()
         ));
+        crate::storage::record_checkpoint(&crate::storage::StoredCheckpoint::from_checkpoint(
+            &checkpoint,
+            &working_log.repo_workdir.to_string_lossy(),
+            &base_commit,
+        ));
         checkpoints.push(checkpoint.clone());
 
         // Build common attributes once (reused for all events)
@@ -410,7 +468,10 @@ pub fn run(
         // Record agent usage metric for AI checkpoints
         if kind != CheckpointKind::Human {
             if checkpoint.agent_id.is_some() {
-                let values = crate::metrics::AgentUsageValues::new();
+                let mut values = crate::metrics::AgentUsageValues::new();
+                if let Some(usage) = &checkpoint.token_usage {
+                    values = values.token_usage(usage);
+                }
                 crate::metrics::record(values, attrs.clone());
             }
         }
@@ -672,6 +733,20 @@ fn get_all_tracked_files(
         }
     }
 
+    // Leave out paths excluded from attribution (.gitaiignore, generated files)
+    let ignore_patterns = repo_ignore_patterns(repo);
+    if !ignore_patterns.is_empty() {
+        results_for_tracked_files.retain(|path| {
+            let relative = repo_workdir
+                .as_ref()
+                .and_then(|workdir| std::path::Path::new(path).strip_prefix(workdir).ok())
+                .and_then(|relative| relative.to_str())
+                .map(normalize_to_posix)
+                .unwrap_or_else(|| path.clone());
+            !should_ignore_file(&relative, &ignore_patterns)
+        });
+    }
+
     Ok(results_for_tracked_files)
 }
 
@@ -686,9 +761,11 @@ fn save_current_file_states(
     let repo_workdir = working_log.repo_workdir.clone();
     let dirty_files = working_log.dirty_files.clone();
 
-    // Process files concurrently with a semaphore limiting to 8 at a time
+    // Process files concurrently, at most 8 (or max_concurrency, if lower) at a time
     let file_content_hashes = smol::block_on(async {
-        let semaphore = Arc::new(smol::lock::Semaphore::new(8));
+        let semaphore = Arc::new(smol::lock::Semaphore::new(
+            Config::get().max_concurrency().min(8),
+        ));
         let blobs_dir = Arc::new(blobs_dir);
         let repo_workdir = Arc::new(repo_workdir);
         let dirty_files = Arc::new(dirty_files);
@@ -941,7 +1018,19 @@ fn get_checkpoint_entry_for_file(
         return Ok(None);
     }
 
-    let (entry, stats) = make_entry_for_file(
+    // Character-level tracking is quadratic in the worst case, so very large files get
+    // line-level attribution instead
+    let summary_only_bytes = Config::get().checkpoint_summary_only_bytes();
+    let make_entry = if previous_content.len().max(current_content.len()) > summary_only_bytes {
+        debug_log(&format!(
+            "[BENCHMARK] {} is over {} bytes, attributing lines only",
+            file_path, summary_only_bytes
+        ));
+        make_summary_entry_for_file
+    } else {
+        make_entry_for_file
+    };
+    let (entry, stats) = make_entry(
         &file_path,
         &file_content_hash,
         author_id.as_ref(),
@@ -1007,10 +1096,8 @@ async fn get_checkpoint_entries(
         .and_then(|c| c.tree().ok())
         .map(|t| t.id().to_string());
 
-    const MAX_CONCURRENT: usize = 30;
-
     // Create a semaphore to limit concurrent tasks
-    let semaphore = Arc::new(smol::lock::Semaphore::new(MAX_CONCURRENT));
+    let semaphore = Arc::new(smol::lock::Semaphore::new(Config::get().max_concurrency()));
 
     // Move checkpoint data to Arc once, outside the loop to avoid repeated allocations
     let previous_checkpoints = Arc::new(previous_checkpoints.to_vec());
@@ -1109,7 +1196,7 @@ async fn get_checkpoint_entries(
     Ok((entries, file_stats))
 }
 
-fn make_entry_for_file(
+pub fn make_entry_for_file(
     file_path: &str,
     blob_sha: &str,
     author_id: &str,
@@ -1181,6 +1268,99 @@ fn make_entry_for_file(
     Ok((entry, line_stats))
 }
 
+/// Line-level stand-in for `make_entry_for_file`, for files too large to track character by
+/// character. Unchanged lines keep their previous author and every inserted or modified line goes
+/// to `author_id`, so AI line counts are an upper bound: a one-character AI edit claims the whole
+/// line. Needs a single diff, which also yields the line stats.
+pub fn make_summary_entry_for_file(
+    file_path: &str,
+    blob_sha: &str,
+    author_id: &str,
+    previous_content: &str,
+    previous_attributions: &Vec<Attribution>,
+    content: &str,
+    ts: u128,
+) -> Result<(WorkingLogEntry, FileLineStats), GitAiError> {
+    let human = CheckpointKind::Human.to_str();
+
+    // Author (and overridden author) of each previous line; None is human
+    let mut previous_authors: Vec<Option<(&str, Option<&str>)>> =
+        vec![None; previous_content.lines().count()];
+    let previous_line_attributions =
+        crate::authorship::attribution_tracker::attributions_to_line_attributions(
+            previous_attributions,
+            previous_content,
+        );
+    for attr in &previous_line_attributions {
+        for line in attr.start_line..=attr.end_line {
+            if let Some(slot) = previous_authors.get_mut(line as usize - 1) {
+                *slot = Some((attr.author_id.as_str(), attr.overrode.as_deref()));
+            }
+        }
+    }
+
+    let mut stats = FileLineStats::default();
+    let mut authors: Vec<Option<(&str, Option<&str>)>> = Vec::new();
+    let mut previous_line = 0;
+    for change in compute_line_changes(previous_content, content) {
+        let is_sloc = !change.value().trim().is_empty() as u32;
+        match change.tag() {
+            LineChangeTag::Equal => {
+                authors.push(previous_authors.get(previous_line).copied().flatten());
+                previous_line += 1;
+            }
+            LineChangeTag::Delete => {
+                stats.deletions += 1;
+                stats.deletions_sloc += is_sloc;
+                previous_line += 1;
+            }
+            LineChangeTag::Insert => {
+                stats.additions += 1;
+                stats.additions_sloc += is_sloc;
+                authors.push((author_id != human).then_some((author_id, None)));
+            }
+        }
+    }
+
+    // Merge runs of lines with the same author
+    let mut line_attributions: Vec<LineAttribution> = Vec::new();
+    for (idx, author) in authors.iter().enumerate() {
+        let line = idx as u32 + 1;
+        let Some((author, overrode)) = author else {
+            continue;
+        };
+        match line_attributions.last_mut() {
+            Some(last)
+                if last.end_line + 1 == line
+                    && last.author_id == *author
+                    && last.overrode.as_deref() == *overrode =>
+            {
+                last.end_line = line;
+            }
+            _ => line_attributions.push(LineAttribution::new(
+                line,
+                line,
+                author.to_string(),
+                overrode.map(str::to_string),
+            )),
+        }
+    }
+
+    let attributions = crate::authorship::attribution_tracker::line_attributions_to_attributions(
+        &line_attributions,
+        content,
+        ts,
+    );
+    let entry = WorkingLogEntry::new(
+        file_path.to_string(),
+        blob_sha.to_string(),
+        attributions,
+        line_attributions,
+    );
+
+    Ok((entry, stats))
+}
+
 /// Compute line statistics for a single file by diffing previous and current content
 fn compute_file_line_stats(previous_content: &str, current_content: &str) -> FileLineStats {
     let mut stats = FileLineStats::default();
@@ -1569,6 +1749,67 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_summary_entry_matches_full_entry_for_whole_line_edits() {
+        let previous = "fn a() {}\nfn b() {}\n\nfn c() {}\n";
+        let current = "fn a() {}\nfn b2() {}\nfn new() {}\n\nfn c() {}\n";
+        // fn b() was written by an earlier AI session
+        let previous_attributions = vec![
+            Attribution::new(0, 10, "human".to_string(), 1),
+            Attribution::new(10, 20, "earlier".to_string(), 1),
+            Attribution::new(20, 31, "human".to_string(), 1),
+        ];
+
+        let (full, full_stats) = make_entry_for_file(
+            "f.rs",
+            "",
+            "agent",
+            previous,
+            &previous_attributions,
+            current,
+            2,
+        )
+        .unwrap();
+        let (summary, summary_stats) = make_summary_entry_for_file(
+            "f.rs",
+            "",
+            "agent",
+            previous,
+            &previous_attributions,
+            current,
+            2,
+        )
+        .unwrap();
+
+        assert_eq!(
+            summary.line_attributions,
+            vec![LineAttribution::new(2, 3, "agent".to_string(), None)]
+        );
+        assert_eq!(summary.line_attributions, full.line_attributions);
+        assert_eq!(summary_stats.additions, full_stats.additions);
+        assert_eq!(summary_stats.deletions, full_stats.deletions);
+        assert_eq!(summary_stats.additions_sloc, full_stats.additions_sloc);
+
+        // Unchanged AI lines keep their author
+        let (kept, _) = make_summary_entry_for_file(
+            "f.rs",
+            "",
+            "agent",
+            previous,
+            &previous_attributions,
+            "fn a() {}\nfn b() {}\n\nfn c() {}\nfn d() {}\n",
+            2,
+        )
+        .unwrap();
+        assert_eq!(
+            kept.line_attributions,
+            vec![
+                LineAttribution::new(2, 2, "earlier".to_string(), None),
+                LineAttribution::new(5, 5, "agent".to_string(), None),
+            ]
+        );
+    }
+
     #[test]
     fn test_compute_line_stats_ignores_whitespace_only_lines() {
         let (tmp_repo, _lines_file, _alphabet_file) = TmpRepo::new_with_base_commit().unwrap();
@@ -1720,6 +1961,24 @@ fn is_text_file_in_head(repo: &Repository, path: &str) -> bool {
     }
 }
 
+/// Token usage totals last stored in the internal database for the checkpoint's session.
+/// Used as the baseline for the session's first checkpoint since the working log was reset.
+fn stored_session_usage(checkpoint: &Checkpoint) -> Option<TokenUsage> {
+    use crate::authorship::internal_db::InternalDatabase;
+
+    let agent_metadata = checkpoint.agent_metadata.as_ref()?;
+    let agent_id = checkpoint.agent_id.as_ref()?;
+    // Agents that don't expose usage never need the lookup
+    TokenUsage::from_metadata(agent_metadata)?;
+
+    let db = InternalDatabase::global().ok()?;
+    let db_guard = db.lock().ok()?;
+    let record = db_guard
+        .get_prompt(&generate_short_hash(&agent_id.id, &agent_id.tool))
+        .ok()??;
+    TokenUsage::from_metadata(record.agent_metadata.as_ref()?)
+}
+
 /// Upsert a checkpoint prompt to the internal database
 fn upsert_checkpoint_prompt_to_db(
     checkpoint: &Checkpoint,
@@ -1,5 +1,6 @@
 use crate::{
     authorship::{
+        token_usage::TokenUsage,
         transcript::{AiTranscript, Message},
         working_log::{AgentId, CheckpointKind},
     },
@@ -10,7 +11,8 @@ use chrono::{TimeZone, Utc};
 use dirs;
 use rusqlite::{Connection, OpenFlags};
 use serde::Deserialize;
-use std::collections::HashMap;
+use serde::de::DeserializeOwned;
+use std::collections::{HashMap, HashSet};
 use std::env;
 use std::path::{Path, PathBuf};
 
@@ -34,6 +36,24 @@ pub trait AgentCheckpointPreset {
     fn run(&self, flags: AgentCheckpointFlags) -> Result<AgentRunResult, GitAiError>;
 }
 
+/// Upper bound on hook payloads. Real payloads are a few KB; anything larger is rejected
+/// before it reaches serde.
+pub const MAX_HOOK_INPUT_BYTES: usize = 4 * 1024 * 1024;
+
+/// Parse an agent hook payload. Oversized or malformed input is an error, never a panic;
+/// nesting depth is bounded by serde_json's recursion limit.
+pub fn parse_hook_input<T: DeserializeOwned>(input: &str) -> Result<T, GitAiError> {
+    if input.len() > MAX_HOOK_INPUT_BYTES {
+        return Err(GitAiError::PresetError(format!(
+            "hook_input is {} bytes, exceeding the {} byte limit",
+            input.len(),
+            MAX_HOOK_INPUT_BYTES
+        )));
+    }
+    serde_json::from_str(input)
+        .map_err(|e| GitAiError::PresetError(format!("Invalid JSON in hook_input: {}", e)))
+}
+
 // Claude Code to checkpoint preset
 pub struct ClaudePreset;
 
@@ -44,8 +64,7 @@ impl AgentCheckpointPreset for ClaudePreset {
             GitAiError::PresetError("hook_input is required for Claude preset".to_string())
         })?;
 
-        let hook_data: serde_json::Value = serde_json::from_str(&stdin_json)
-            .map_err(|e| GitAiError::PresetError(format!("Invalid JSON in hook_input: {}", e)))?;
+        let hook_data: serde_json::Value = parse_hook_input(&stdin_json)?;
 
         // Extract transcript_path and cwd from the JSON
         let transcript_path = hook_data
@@ -107,8 +126,11 @@ impl AgentCheckpointPreset for ClaudePreset {
             .map(|path| vec![path.to_string()]);
 
         // Store transcript_path in metadata
-        let agent_metadata =
+        let mut agent_metadata =
             HashMap::from([("transcript_path".to_string(), transcript_path.to_string())]);
+        if let Some(usage) = ClaudePreset::token_usage_from_claude_code_jsonl(transcript_path) {
+            usage.write_to_metadata(&mut agent_metadata);
+        }
 
         // Check if this is a PreToolUse event (human checkpoint)
         let hook_event_name = hook_data.get("hook_event_name").and_then(|v| v.as_str());
@@ -142,6 +164,45 @@ impl AgentCheckpointPreset for ClaudePreset {
 }
 
 impl ClaudePreset {
+    /// Running token totals for a Claude Code session. Each API response is logged once per
+    /// content block with the same usage, so responses are counted once by message id. Cache
+    /// reads and writes count as input tokens. Cost is only known when entries carry `costUSD`.
+    pub fn token_usage_from_claude_code_jsonl(transcript_path: &str) -> Option<TokenUsage> {
+        let jsonl_content = std::fs::read_to_string(transcript_path).ok()?;
+        let mut seen_messages = HashSet::new();
+        let mut usage = TokenUsage::default();
+        let mut found = false;
+
+        for line in jsonl_content.lines() {
+            let Ok(raw_entry) = serde_json::from_str::<serde_json::Value>(line) else {
+                continue;
+            };
+            if raw_entry["type"].as_str() != Some("assistant") {
+                continue;
+            }
+            if let Some(message_id) = raw_entry["message"]["id"].as_str()
+                && !seen_messages.insert(message_id.to_string())
+            {
+                continue;
+            }
+            let entry_usage = &raw_entry["message"]["usage"];
+            if entry_usage.is_object() {
+                found = true;
+                let tokens = |key: &str| entry_usage[key].as_u64().unwrap_or(0);
+                usage.input_tokens += tokens("input_tokens")
+                    + tokens("cache_creation_input_tokens")
+                    + tokens("cache_read_input_tokens");
+                usage.output_tokens += tokens("output_tokens");
+            }
+            if let Some(cost) = raw_entry["costUSD"].as_f64() {
+                found = true;
+                usage.cost_usd = Some(usage.cost_usd.unwrap_or(0.0) + cost);
+            }
+        }
+
+        found.then_some(usage)
+    }
+
     /// Parse a Claude Code JSONL file into a transcript and extract model info
     pub fn transcript_and_model_from_claude_code_jsonl(
         transcript_path: &str,
@@ -257,8 +318,7 @@ impl AgentCheckpointPreset for GeminiPreset {
             GitAiError::PresetError("hook_input is required for Gemini preset".to_string())
         })?;
 
-        let hook_data: serde_json::Value = serde_json::from_str(&stdin_json)
-            .map_err(|e| GitAiError::PresetError(format!("Invalid JSON in hook_input: {}", e)))?;
+        let hook_data: serde_json::Value = parse_hook_input(&stdin_json)?;
 
         let session_id = hook_data
             .get("session_id")
@@ -457,8 +517,7 @@ impl AgentCheckpointPreset for ContinueCliPreset {
             GitAiError::PresetError("hook_input is required for Continue CLI preset".to_string())
         })?;
 
-        let hook_data: serde_json::Value = serde_json::from_str(&stdin_json)
-            .map_err(|e| GitAiError::PresetError(format!("Invalid JSON in hook_input: {}", e)))?;
+        let hook_data: serde_json::Value = parse_hook_input(&stdin_json)?;
 
         let session_id = hook_data
             .get("session_id")
@@ -675,8 +734,7 @@ impl AgentCheckpointPreset for CursorPreset {
             GitAiError::PresetError("hook_input is required for Cursor preset".to_string())
         })?;
 
-        let hook_data: serde_json::Value = serde_json::from_str(&hook_input_json)
-            .map_err(|e| GitAiError::PresetError(format!("Invalid JSON in hook_input: {}", e)))?;
+        let hook_data: serde_json::Value = parse_hook_input(&hook_input_json)?;
 
         // Extract conversation_id and workspace_roots from the JSON
         let conversation_id = hook_data
@@ -1151,8 +1209,7 @@ impl AgentCheckpointPreset for GithubCopilotPreset {
             GitAiError::PresetError("hook_input is required for GitHub Copilot preset".to_string())
         })?;
 
-        let hook_data: serde_json::Value = serde_json::from_str(&hook_input_json)
-            .map_err(|e| GitAiError::PresetError(format!("Invalid JSON in hook_input: {}", e)))?;
+        let hook_data: serde_json::Value = parse_hook_input(&hook_input_json)?;
 
         // Extract hook_event_name to determine checkpoint type
         // Fallback to "after_edit" if not set (for older versions of the VS Code extension)
@@ -1326,8 +1383,7 @@ impl AgentCheckpointPreset for DroidPreset {
             GitAiError::PresetError("hook_input is required for Droid preset".to_string())
         })?;
 
-        let hook_data: serde_json::Value = serde_json::from_str(&hook_input_json)
-            .map_err(|e| GitAiError::PresetError(format!("Invalid JSON in hook_input: {}", e)))?;
+        let hook_data: serde_json::Value = parse_hook_input(&hook_input_json)?;
 
         // Extract common fields from Droid hook input
         // Note: Droid may use either snake_case or camelCase field names
@@ -1909,8 +1965,7 @@ impl AgentCheckpointPreset for AiTabPreset {
             GitAiError::PresetError("hook_input is required for ai_tab preset".to_string())
         })?;
 
-        let hook_input: AiTabHookInput = serde_json::from_str(&hook_input_json)
-            .map_err(|e| GitAiError::PresetError(format!("Invalid JSON in hook_input: {}", e)))?;
+        let hook_input: AiTabHookInput = parse_hook_input(&hook_input_json)?;
 
         let AiTabHookInput {
             hook_event_name,
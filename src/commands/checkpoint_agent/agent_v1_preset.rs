@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     authorship::{
+        token_usage::TokenUsage,
         transcript::AiTranscript,
         working_log::{AgentId, CheckpointKind},
     },
@@ -30,6 +31,9 @@ enum AgentV1Input {
         conversation_id: String,
         #[serde(default)]
         dirty_files: Option<HashMap<String, String>>,
+        /// Running token totals for the conversation, if the agent tracks them
+        #[serde(default)]
+        usage: Option<TokenUsage>,
     },
     // AiTab
 }
@@ -80,13 +84,18 @@ impl AgentCheckpointPreset for AgentV1Preset {
                 conversation_id,
                 repo_working_dir,
                 dirty_files,
+                usage,
             } => Ok(AgentRunResult {
                 agent_id: AgentId {
                     tool: agent_name,
                     id: conversation_id,
                     model,
                 },
-                agent_metadata: None,
+                agent_metadata: usage.map(|usage| {
+                    let mut metadata = HashMap::new();
+                    usage.write_to_metadata(&mut metadata);
+                    metadata
+                }),
                 repo_working_dir: Some(repo_working_dir),
                 transcript: Some(transcript),
                 checkpoint_kind: CheckpointKind::AiAgent,
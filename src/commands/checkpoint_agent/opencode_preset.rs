@@ -3,7 +3,9 @@ use crate::{
         transcript::{AiTranscript, Message},
         working_log::{AgentId, CheckpointKind},
     },
-    commands::checkpoint_agent::agent_presets::{AgentCheckpointFlags, AgentCheckpointPreset, AgentRunResult},
+    commands::checkpoint_agent::agent_presets::{
+        AgentCheckpointFlags, AgentCheckpointPreset, AgentRunResult, parse_hook_input,
+    },
     error::GitAiError,
     observability::log_error,
 };
@@ -132,8 +134,7 @@ impl AgentCheckpointPreset for OpenCodePreset {
             GitAiError::PresetError("hook_input is required for OpenCode preset".to_string())
         })?;
 
-        let hook_input: OpenCodeHookInput = serde_json::from_str(&hook_input_json)
-            .map_err(|e| GitAiError::PresetError(format!("Invalid JSON in hook_input: {}", e)))?;
+        let hook_input: OpenCodeHookInput = parse_hook_input(&hook_input_json)?;
 
         let OpenCodeHookInput {
             hook_event_name,
@@ -1,6 +1,11 @@
 use crate::ci::ci_context::{CiContext, CiEvent, CiRunResult};
-use crate::ci::github::{get_github_ci_context, install_github_ci_workflow};
+use crate::ci::github::{
+    get_github_ci_context, get_github_merged_pull_request, get_github_pull_request_refs,
+    install_github_ci_workflow, upsert_github_pr_comment, write_github_step_summary,
+};
 use crate::ci::gitlab::{get_gitlab_ci_context, print_gitlab_ci_yaml};
+use crate::ci::pr_metrics::record_pr_merged;
+use crate::ci::pr_summary::{PR_SUMMARY_MARKER, pr_range_stats, render_pr_summary_markdown};
 use crate::git::repository::find_repository_in_path;
 use crate::utils::debug_log;
 
@@ -66,6 +71,9 @@ fn handle_ci_github(args: &[String]) {
                         Ok(result) => {
                             debug_log(&format!("GitHub CI result: {:?}", result));
                             print_ci_result(&result, "GitHub CI");
+                            if let Ok(Some(pull_request)) = get_github_merged_pull_request() {
+                                record_pr_merged(&ci_context.repo, &pull_request);
+                            }
                         }
                         Err(e) => {
                             eprintln!("Error running GitHub CI context: {}", e);
@@ -93,6 +101,9 @@ fn handle_ci_github(args: &[String]) {
                 }
             }
         }
+        "summary" => {
+            handle_ci_github_summary(&args[1..]);
+        }
         "install" => match install_github_ci_workflow() {
             Ok(path) => {
                 println!("Installed GitHub Actions workflow to {}", path.display());
@@ -110,6 +121,121 @@ fn handle_ci_github(args: &[String]) {
     }
 }
 
+fn handle_ci_github_summary(args: &[String]) {
+    let mut base: Option<String> = None;
+    let mut head: Option<String> = None;
+    let mut pr_number: Option<u32> = None;
+    let mut comment = false;
+    let mut ignore_patterns: Vec<String> = Vec::new();
+
+    let mut i = 0;
+    while i < args.len() {
+        let value = |name: &str| -> String {
+            match args.get(i + 1) {
+                Some(v) => v.clone(),
+                None => {
+                    eprintln!("Missing value for flag {}", name);
+                    std::process::exit(1);
+                }
+            }
+        };
+        match args[i].as_str() {
+            "--base" => {
+                base = Some(value("--base"));
+                i += 2;
+            }
+            "--head" => {
+                head = Some(value("--head"));
+                i += 2;
+            }
+            "--pr" => {
+                let raw = value("--pr");
+                pr_number = match raw.parse::<u32>() {
+                    Ok(n) => Some(n),
+                    Err(_) => {
+                        eprintln!("Invalid --pr value '{}'", raw);
+                        std::process::exit(1);
+                    }
+                };
+                i += 2;
+            }
+            "--ignore" => {
+                ignore_patterns.push(value("--ignore"));
+                i += 2;
+            }
+            "--comment" => {
+                comment = true;
+                i += 1;
+            }
+            other => {
+                eprintln!("Unknown ci github summary argument: {}", other);
+                print_ci_github_help_and_exit();
+            }
+        }
+    }
+
+    // Fill in whatever wasn't passed explicitly from the pull_request event payload
+    let event_refs = match get_github_pull_request_refs() {
+        Ok(refs) => refs,
+        Err(e) => {
+            eprintln!("Failed to read GitHub event payload: {}", e);
+            std::process::exit(1);
+        }
+    };
+    if let Some(refs) = &event_refs {
+        base.get_or_insert_with(|| refs.base_sha.clone());
+        head.get_or_insert_with(|| refs.head_sha.clone());
+        pr_number.get_or_insert(refs.number);
+    }
+    let Some(base) = base else {
+        eprintln!("--base is required outside of a pull_request workflow run");
+        std::process::exit(1);
+    };
+    let head = head.unwrap_or_else(|| "HEAD".to_string());
+
+    let repo = match find_repository_in_path(".") {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("Failed to open repository in current directory: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let stats = match pr_range_stats(&repo, &base, &head, &ignore_patterns) {
+        Ok(stats) => stats,
+        Err(e) => {
+            eprintln!("Failed to compute authorship for {}..{}: {}", base, head, e);
+            std::process::exit(1);
+        }
+    };
+    let markdown = render_pr_summary_markdown(&stats);
+
+    match write_github_step_summary(&markdown) {
+        Ok(true) => debug_log("Wrote git-ai summary to GITHUB_STEP_SUMMARY"),
+        Ok(false) => println!("{}", markdown),
+        Err(e) => {
+            eprintln!("Failed to write job summary: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    if comment {
+        let Some(pr_number) = pr_number else {
+            eprintln!("--comment needs a pull request number (--pr <number>)");
+            std::process::exit(1);
+        };
+        match upsert_github_pr_comment(pr_number, &markdown, PR_SUMMARY_MARKER) {
+            Ok(true) => println!("Updated git-ai comment on PR #{}", pr_number),
+            Ok(false) => println!("Posted git-ai comment on PR #{}", pr_number),
+            Err(e) => {
+                eprintln!("Failed to comment on PR #{}: {}", pr_number, e);
+                std::process::exit(1);
+            }
+        }
+    }
+    std::process::exit(0);
+}
+
 fn handle_ci_gitlab(args: &[String]) {
     if args.is_empty() {
         print_ci_gitlab_help_and_exit();
@@ -283,6 +409,7 @@ fn print_ci_help_and_exit() -> ! {
     eprintln!("Subcommands:");
     eprintln!("  github           GitHub CI");
     eprintln!("    run [--no-cleanup]  Run GitHub CI in current repo");
+    eprintln!("    summary        Write PR authorship stats to the job summary");
     eprintln!("    install        Install/update workflow in current repo");
     eprintln!("  gitlab           GitLab CI");
     eprintln!("    run [--no-cleanup]  Run GitLab CI in current repo");
@@ -316,6 +443,21 @@ fn print_ci_github_help_and_exit() -> ! {
     eprintln!("Subcommands:");
     eprintln!("  run [--no-cleanup]   Run GitHub CI in current repo");
     eprintln!("                       --no-cleanup  Skip teardown after run");
+    eprintln!("  summary              Write AI/human line stats for a pull request to the job");
+    eprintln!("                       summary (stdout outside GitHub Actions)");
+    eprintln!(
+        "                       --base <ref>    Base of the PR (default: from event payload)"
+    );
+    eprintln!(
+        "                       --head <ref>    Head of the PR (default: from event payload)"
+    );
+    eprintln!(
+        "                       --comment       Post or update a PR comment (needs GITHUB_TOKEN)"
+    );
+    eprintln!(
+        "                       --pr <number>   PR to comment on (default: from event payload)"
+    );
+    eprintln!("                       --ignore <pattern>  Exclude matching files (repeatable)");
     eprintln!("  install              Install/update workflow in current repo");
     std::process::exit(1);
 }
@@ -0,0 +1,523 @@
+use crate::commands::machine_output::{self, OutputFormat};
+use crate::commands::reporting::{percent, round_tenth};
+use crate::commands::stats_report::{
+    HUMAN_ROW_KEY, ReportGrouping, ReportOptions, StatsReport, build_stats_report,
+};
+use crate::error::GitAiError;
+use crate::git::find_repository;
+use crate::git::repository::Repository;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// What `git ai compare` contrasts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompareSpec {
+    /// `A..B`: the history reachable from A against the history reachable from B, plus the
+    /// commits in between
+    Range { before: String, after: String },
+    /// Two revisions or ranges, each standing for the commits git log lists for it
+    Pair { before: String, after: String },
+}
+
+impl CompareSpec {
+    pub fn parse(specs: &[String]) -> Result<Self, String> {
+        match specs {
+            [range] => {
+                if range.contains("...") {
+                    return Err(format!(
+                        "{} is a symmetric difference; use <A>..<B> or pass two ranges",
+                        range
+                    ));
+                }
+                match range.split_once("..") {
+                    Some((before, after)) if !before.is_empty() && !after.is_empty() => {
+                        Ok(CompareSpec::Range {
+                            before: before.to_string(),
+                            after: after.to_string(),
+                        })
+                    }
+                    _ => Err(format!(
+                        "{} is not a range; use <A>..<B> or pass two revisions",
+                        range
+                    )),
+                }
+            }
+            [before, after] => Ok(CompareSpec::Pair {
+                before: before.clone(),
+                after: after.clone(),
+            }),
+            _ => Err("compare takes <A>..<B> or two revisions or ranges".to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CompareSide {
+    pub rev: String,
+    pub commits: usize,
+    pub ai_lines: u64,
+    pub human_lines: u64,
+    pub ai_percent: f64,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct AgentChange {
+    pub agent: String,
+    pub before_lines: u64,
+    pub after_lines: u64,
+    pub change: i64,
+    /// Change relative to `before_lines`; absent for agents new on the after side
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub growth_percent: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct DirectoryChange {
+    pub directory: String,
+    pub before_lines: u64,
+    pub after_lines: u64,
+    pub before_ai_percent: f64,
+    pub after_ai_percent: f64,
+    /// Percentage points
+    pub change: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CompareReport {
+    pub before: CompareSide,
+    pub after: CompareSide,
+    /// The commits between the two revisions, for `A..B`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range: Option<CompareSide>,
+    /// Percentage points
+    pub ai_percent_change: f64,
+    /// Biggest change first
+    pub agents: Vec<AgentChange>,
+    /// Biggest shift in AI share first
+    pub directories: Vec<DirectoryChange>,
+}
+
+pub fn handle_compare(args: &[String]) {
+    let mut specs = Vec::new();
+    let mut include_bots = false;
+    let mut format = OutputFormat::Text;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--include-bots" => {
+                include_bots = true;
+                i += 1;
+            }
+            "--format" => {
+                format = OutputFormat::from_flag_value(args, i);
+                i += 2;
+            }
+            "--json" => {
+                format = OutputFormat::Json;
+                i += 1;
+            }
+            "--porcelain" => {
+                format = OutputFormat::Porcelain;
+                i += 1;
+            }
+            "--help" | "-h" => print_compare_help_and_exit(),
+            arg if !arg.starts_with('-') => {
+                specs.push(arg.to_string());
+                i += 1;
+            }
+            other => {
+                eprintln!("Unknown compare argument: {}", other);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let spec = match CompareSpec::parse(&specs) {
+        Ok(spec) => spec,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let report = match build_compare_report(&repo, &spec, include_bots) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Compare failed: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if format.is_text() {
+        print_compare_table(&report);
+    } else {
+        machine_output::print(format, &report).unwrap();
+    }
+}
+
+fn print_compare_help_and_exit() -> ! {
+    eprintln!("Usage: git-ai compare <A>..<B> [options]");
+    eprintln!("       git-ai compare <before> <after> [options]");
+    eprintln!();
+    eprintln!("Compare AI vs human lines between two points in history. <A>..<B> contrasts the");
+    eprintln!("history reachable from A with the history reachable from B, for example");
+    eprintln!("main@{{1.month}}..main. Two arguments are each a revision or range, compared as");
+    eprintln!("the commits git log lists for them.");
+    eprintln!();
+    eprintln!("  --include-bots        Count commits by bot_authors identities");
+    eprintln!("  --format <fmt>        text (default), json or porcelain");
+    std::process::exit(0);
+}
+
+pub fn build_compare_report(
+    repo: &Repository,
+    spec: &CompareSpec,
+    include_bots: bool,
+) -> Result<CompareReport, GitAiError> {
+    let (before_rev, after_rev, range_rev) = match spec {
+        CompareSpec::Range { before, after } => {
+            for rev in [before, after] {
+                verify_commit(repo, rev)?;
+            }
+            (
+                before.clone(),
+                after.clone(),
+                Some(format!("{}..{}", before, after)),
+            )
+        }
+        CompareSpec::Pair { before, after } => (before.clone(), after.clone(), None),
+    };
+
+    let (before, before_agents, before_dirs) = summarize(repo, &before_rev, include_bots)?;
+    let (after, after_agents, after_dirs) = summarize(repo, &after_rev, include_bots)?;
+    let range = match range_rev {
+        Some(rev) => Some(summarize(repo, &rev, include_bots)?.0),
+        None => None,
+    };
+
+    Ok(CompareReport {
+        ai_percent_change: round_tenth(after.ai_percent - before.ai_percent),
+        agents: agent_changes(&before_agents, &after_agents),
+        directories: directory_changes(&before_dirs, &after_dirs),
+        before,
+        after,
+        range,
+    })
+}
+
+/// Fails unless `rev` names a commit, so a typo isn't reported as an empty history
+fn verify_commit(repo: &Repository, rev: &str) -> Result<(), GitAiError> {
+    repo.git(&[
+        "rev-parse",
+        "--verify",
+        "--quiet",
+        &format!("{}^{{commit}}", rev),
+    ])
+    .map(|_| ())
+    .map_err(|_| GitAiError::Generic(format!("{} is not a commit", rev)))
+}
+
+type AgentLines = BTreeMap<String, u64>;
+type DirectoryLines = BTreeMap<String, (u64, u64)>;
+
+/// Totals for the commits `rev` selects, with AI lines per agent and lines per top-level
+/// directory
+fn summarize(
+    repo: &Repository,
+    rev: &str,
+    include_bots: bool,
+) -> Result<(CompareSide, AgentLines, DirectoryLines), GitAiError> {
+    let report_for = |by| {
+        build_stats_report(
+            repo,
+            &ReportOptions {
+                rev: rev.to_string(),
+                since: None,
+                until: None,
+                by,
+                include_bots,
+            },
+        )
+    };
+    let by_tool: StatsReport = report_for(ReportGrouping::Tool)?;
+    let by_directory = report_for(ReportGrouping::Directory)?;
+
+    let side = CompareSide {
+        rev: rev.to_string(),
+        commits: by_tool.total_commits,
+        ai_lines: by_tool.ai_lines,
+        human_lines: by_tool.human_lines,
+        ai_percent: by_tool.ai_percent,
+    };
+    let agents = by_tool
+        .rows
+        .into_iter()
+        .filter(|row| row.key != HUMAN_ROW_KEY)
+        .map(|row| (row.key, row.ai_lines))
+        .collect();
+    let directories = by_directory
+        .rows
+        .into_iter()
+        .map(|row| (row.key, (row.ai_lines, row.human_lines)))
+        .collect();
+    Ok((side, agents, directories))
+}
+
+fn agent_changes(before: &AgentLines, after: &AgentLines) -> Vec<AgentChange> {
+    let agents: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    let mut rows: Vec<AgentChange> = agents
+        .into_iter()
+        .map(|agent| {
+            let before_lines = before.get(agent).copied().unwrap_or(0);
+            let after_lines = after.get(agent).copied().unwrap_or(0);
+            let change = after_lines as i64 - before_lines as i64;
+            AgentChange {
+                agent: agent.clone(),
+                before_lines,
+                after_lines,
+                change,
+                growth_percent: (before_lines > 0)
+                    .then(|| round_tenth(change as f64 * 100.0 / before_lines as f64)),
+            }
+        })
+        .collect();
+    rows.sort_by(|a, b| {
+        b.change
+            .abs()
+            .cmp(&a.change.abs())
+            .then(a.agent.cmp(&b.agent))
+    });
+    rows
+}
+
+fn directory_changes(before: &DirectoryLines, after: &DirectoryLines) -> Vec<DirectoryChange> {
+    let directories: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    let mut rows: Vec<DirectoryChange> = directories
+        .into_iter()
+        .map(|directory| {
+            let (before_ai, before_human) = before.get(directory).copied().unwrap_or_default();
+            let (after_ai, after_human) = after.get(directory).copied().unwrap_or_default();
+            let before_ai_percent = percent(before_ai, before_human);
+            let after_ai_percent = percent(after_ai, after_human);
+            DirectoryChange {
+                directory: directory.clone(),
+                before_lines: before_ai + before_human,
+                after_lines: after_ai + after_human,
+                before_ai_percent,
+                after_ai_percent,
+                change: round_tenth(after_ai_percent - before_ai_percent),
+            }
+        })
+        .collect();
+    rows.sort_by(|a, b| {
+        b.change
+            .abs()
+            .total_cmp(&a.change.abs())
+            .then(a.directory.cmp(&b.directory))
+    });
+    rows
+}
+
+fn signed(value: i64) -> String {
+    if value > 0 {
+        format!("+{}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+fn signed_points(value: f64) -> String {
+    if value > 0.0 {
+        format!("+{:.1}", value)
+    } else {
+        format!("{:.1}", value)
+    }
+}
+
+fn print_compare_table(report: &CompareReport) {
+    let (before, after) = (&report.before, &report.after);
+    println!(
+        "Comparing {} ({} commits) with {} ({} commits)",
+        before.rev, before.commits, after.rev, after.commits
+    );
+    println!();
+    println!(
+        "{:<12}  {:>10}  {:>10}  {:>10}",
+        "", "BEFORE", "AFTER", "CHANGE"
+    );
+    for (label, before_lines, after_lines) in [
+        ("AI lines", before.ai_lines, after.ai_lines),
+        ("Human lines", before.human_lines, after.human_lines),
+    ] {
+        println!(
+            "{:<12}  {:>10}  {:>10}  {:>10}",
+            label,
+            before_lines,
+            after_lines,
+            signed(after_lines as i64 - before_lines as i64)
+        );
+    }
+    println!(
+        "{:<12}  {:>9.1}%  {:>9.1}%  {:>7} pp",
+        "AI %",
+        before.ai_percent,
+        after.ai_percent,
+        signed_points(report.ai_percent_change)
+    );
+    if let Some(range) = &report.range {
+        println!();
+        println!(
+            "{}: {} commits, {} AI and {} human lines ({:.1}% AI)",
+            range.rev, range.commits, range.ai_lines, range.human_lines, range.ai_percent
+        );
+    }
+
+    if !report.agents.is_empty() {
+        println!();
+        let width = column_width(report.agents.iter().map(|row| &row.agent), "AGENT");
+        println!(
+            "{:<width$}  {:>10}  {:>10}  {:>10}  {:>8}",
+            "AGENT",
+            "BEFORE",
+            "AFTER",
+            "CHANGE",
+            "GROWTH",
+            width = width
+        );
+        for row in &report.agents {
+            let growth = match row.growth_percent {
+                Some(growth) => format!("{}%", signed_points(growth)),
+                None => "new".to_string(),
+            };
+            println!(
+                "{:<width$}  {:>10}  {:>10}  {:>10}  {:>8}",
+                row.agent,
+                row.before_lines,
+                row.after_lines,
+                signed(row.change),
+                growth,
+                width = width
+            );
+        }
+    }
+
+    if !report.directories.is_empty() {
+        println!();
+        let width = column_width(
+            report.directories.iter().map(|row| &row.directory),
+            "DIRECTORY",
+        );
+        println!(
+            "{:<width$}  {:>10}  {:>10}  {:>10}",
+            "DIRECTORY",
+            "BEFORE AI%",
+            "AFTER AI%",
+            "CHANGE",
+            width = width
+        );
+        for row in &report.directories {
+            println!(
+                "{:<width$}  {:>9.1}%  {:>9.1}%  {:>7} pp",
+                row.directory,
+                row.before_ai_percent,
+                row.after_ai_percent,
+                signed_points(row.change),
+                width = width
+            );
+        }
+    }
+}
+
+fn column_width<'a>(values: impl Iterator<Item = &'a String>, header: &str) -> usize {
+    values
+        .map(|value| value.chars().count())
+        .chain([header.len()])
+        .max()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_compare_spec_parse() {
+        assert_eq!(
+            CompareSpec::parse(&strings(&["main@{1.month}..main"])),
+            Ok(CompareSpec::Range {
+                before: "main@{1.month}".to_string(),
+                after: "main".to_string(),
+            })
+        );
+        assert_eq!(
+            CompareSpec::parse(&strings(&["v1..v2", "v2..v3"])),
+            Ok(CompareSpec::Pair {
+                before: "v1..v2".to_string(),
+                after: "v2..v3".to_string(),
+            })
+        );
+        assert!(CompareSpec::parse(&strings(&["main"])).is_err());
+        assert!(CompareSpec::parse(&strings(&["..main"])).is_err());
+        assert!(CompareSpec::parse(&strings(&["a...b"])).is_err());
+        assert!(CompareSpec::parse(&[]).is_err());
+    }
+
+    #[test]
+    fn test_agent_and_directory_changes_rank_biggest_first() {
+        let before: AgentLines = [("claude".to_string(), 100), ("cursor".to_string(), 40)]
+            .into_iter()
+            .collect();
+        let after: AgentLines = [
+            ("claude".to_string(), 150),
+            ("cursor".to_string(), 30),
+            ("codex".to_string(), 200),
+        ]
+        .into_iter()
+        .collect();
+        let agents = agent_changes(&before, &after);
+        let summary: Vec<(&str, i64, Option<f64>)> = agents
+            .iter()
+            .map(|row| (row.agent.as_str(), row.change, row.growth_percent))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("codex", 200, None),
+                ("claude", 50, Some(50.0)),
+                ("cursor", -10, Some(-25.0)),
+            ]
+        );
+
+        let before: DirectoryLines = [("src/".to_string(), (10, 90)), (".".to_string(), (0, 5))]
+            .into_iter()
+            .collect();
+        let after: DirectoryLines = [
+            ("src/".to_string(), (40, 110)),
+            (".".to_string(), (0, 5)),
+            ("docs/".to_string(), (1, 1)),
+        ]
+        .into_iter()
+        .collect();
+        let directories = directory_changes(&before, &after);
+        let summary: Vec<(&str, f64)> = directories
+            .iter()
+            .map(|row| (row.directory.as_str(), row.change))
+            .collect();
+        assert_eq!(summary, [("docs/", 50.0), ("src/", 16.7), (".", 0.0)]);
+        assert_eq!(directories[1].before_lines, 100);
+        assert_eq!(directories[1].after_lines, 150);
+    }
+}
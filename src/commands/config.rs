@@ -1,6 +1,7 @@
 use serde_json::Value;
 use dirs;
 
+use crate::config_layers::{self, ConfigScope, LayeredConfig, Setting};
 use crate::git::repository::find_repository_in_path;
 
 /// Determines the type of pattern value provided
@@ -89,10 +90,21 @@ fn print_config_help() {
     eprintln!("Usage:");
     eprintln!("  git-ai config                Show all config as formatted JSON");
     eprintln!("  git-ai config <key>          Show specific config value");
+    eprintln!("  git-ai config get <key> [--show-origin]  Show a value and the layer it came from");
+    eprintln!("  git-ai config list [--show-origin]       List every configured value, by layer");
+    eprintln!("  git-ai config validate       Check every layer against the schema");
     eprintln!("  git-ai config set <key> <value>          Set a config value");
     eprintln!("  git-ai config set <key> <value> --add    Add to array (extends existing)");
     eprintln!("  git-ai config --add <key> <value>        Add to array or upsert into object");
     eprintln!("  git-ai config unset <key>    Remove config value (reverts to default)");
+    eprintln!("  git-ai config set|unset ... --repo|--system");
+    eprintln!("                               Edit .git/git-ai.toml or the system git-ai.toml");
+    eprintln!("  git-ai config export-bundle [--output <file>] [--key-file <file>]");
+    eprintln!("                               Export team settings as a signed bundle");
+    eprintln!(
+        "  git-ai config import-bundle <file> [--key-file <file>] [--allow-unsigned] [--dry-run]"
+    );
+    eprintln!("                               Apply a bundle's team settings to this machine");
     eprintln!("");
     eprintln!("Configuration Keys:");
     eprintln!("  git_path                     Path to git binary");
@@ -104,10 +116,34 @@ fn print_config_help() {
     eprintln!("  disable_version_checks       Disable version checks (bool)");
     eprintln!("  disable_auto_updates         Disable auto updates (bool)");
     eprintln!("  update_channel               Update channel (latest/next)");
+    eprintln!("  required_version             git-ai versions hooks accept, e.g. >=1.4.0 or =1.4.2");
+    eprintln!("  release_signing_key          SSH public key self-update requires on SHA256SUMS");
     eprintln!("  feature_flags                Feature flags (object)");
     eprintln!("  api_key                      API key for X-API-Key header");
     eprintln!("  prompt_storage               Prompt storage mode (default/notes/local)");
     eprintln!("  quiet                        Suppress chart output after commits (bool)");
+    eprintln!("  bot_authors                  Author globs excluded from aggregate stats (array)");
+    eprintln!("  mirror_summary_notes         Mirror a one-line summary into refs/notes/commits (bool)");
+    eprintln!("  prometheus_enabled           Record Prometheus metrics (bool)");
+    eprintln!("  metrics_sink                 Extra metrics sinks, comma-separated (statsd, webhook)");
+    eprintln!("  metrics_routes               Event types and sample rate per sink (array of objects)");
+    eprintln!("  telemetry_privacy            Redaction preset for exported metric attributes (strict/default/off/aggregate)");
+    eprintln!("  telemetry_redact             Per-attribute redaction rules, e.g. author=hash,branch=drop");
+    eprintln!("  telemetry_allow              Metric attributes to export, comma-separated; others are dropped");
+    eprintln!("  max_concurrency              Max concurrent git subprocesses/threads (default: 30)");
+    eprintln!("  background_nice              Nice level for background processes, 0-19 (default: 10)");
+    eprintln!("  background_io_idle           Run background processes in the idle IO class (bool)");
+    eprintln!("  agent_detectors              Custom agent detectors for `checkpoint auto` (array of objects)");
+    eprintln!("  otel_*                       OpenTelemetry export: enabled, endpoint, protocol, export_interval_secs,");
+    eprintln!("                               auth_header, traces_enabled, queue_capacity, shutdown_grace_ms");
+    eprintln!("  webhook_url, webhook_secret  Webhook metrics sink");
+    eprintln!("  notes_sync_remotes           Remotes that receive notes (git config git-ai.syncRemotes wins)");
+    eprintln!("  sign_notes, verify_notes     Note signing (git config git-ai.signNotes/verifyNotes win)");
+    eprintln!("  policy_file                  Policy file relative to the repository root (default: policy.toml)");
+    eprintln!("  detect_generated_files       Leave lockfiles, vendored code and build output out of attribution (bool)");
+    eprintln!("");
+    eprintln!("Layers (later wins): /etc/git-ai/git-ai.toml, ~/.git-ai/config.json,");
+    eprintln!("  ~/.git-ai/git-ai.toml, .git/git-ai.toml, then GIT_AI_* environment variables");
     eprintln!("");
     eprintln!("Repository Patterns:");
     eprintln!("  For exclude/allow/exclude_prompts_in_repositories, you can provide:");
@@ -123,7 +159,14 @@ fn print_config_help() {
     eprintln!("  git-ai config --add exclude_repositories \"temp/*\"");
     eprintln!("  git-ai config --add allow_repositories ~/projects/my-repo");
     eprintln!("  git-ai config --add feature_flags.my_flag true");
+    eprintln!(
+        "  git-ai config --add agent_detectors '{{\"tool\":\"cline\",\"process_name\":\"cline\"}}'"
+    );
     eprintln!("  git-ai config unset exclude_repositories");
+    eprintln!("  git-ai config set otel_endpoint http://collector:4317 --repo");
+    eprintln!("  git-ai config get otel_endpoint --show-origin");
+    eprintln!("  GIT_AI_BUNDLE_KEY=... git-ai config export-bundle -o team.json");
+    eprintln!("  git-ai config import-bundle team.json --key-file ~/.team-bundle-key");
     eprintln!("");
     std::process::exit(0);
 }
@@ -144,6 +187,51 @@ pub fn handle_config(args: &[String]) {
         return;
     }
 
+    match args[0].as_str() {
+        "export-bundle" => {
+            crate::commands::config_bundle::handle_export_bundle(&args[1..]);
+            return;
+        }
+        "import-bundle" => {
+            crate::commands::config_bundle::handle_import_bundle(&args[1..]);
+            return;
+        }
+        "get" => {
+            handle_get(&args[1..]);
+            return;
+        }
+        "list" => {
+            handle_list(&args[1..]);
+            return;
+        }
+        "validate" => {
+            handle_validate();
+            return;
+        }
+        _ => {}
+    }
+
+    // --repo / --system write git-ai.toml at that layer instead of ~/.git-ai/config.json
+    let scope = if args.iter().any(|a| a == "--repo") {
+        Some(ConfigScope::Repo)
+    } else if args.iter().any(|a| a == "--system") {
+        Some(ConfigScope::System)
+    } else {
+        None
+    };
+    let args: Vec<String> = args
+        .iter()
+        .filter(|a| *a != "--repo" && *a != "--system")
+        .cloned()
+        .collect();
+    if let Some(scope) = scope {
+        if let Err(e) = handle_layer_edit(scope, &args) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     // Check for --add flag anywhere in args
     let is_add_mode = args.iter().any(|a| a == "--add");
     let filtered_args: Vec<&String> = args.iter().filter(|a| *a != "--add").collect();
@@ -206,8 +294,129 @@ pub fn handle_config(args: &[String]) {
     }
 }
 
+fn handle_get(args: &[String]) {
+    let show_origin = args.iter().any(|a| a == "--show-origin");
+    let keys: Vec<&String> = args.iter().filter(|a| *a != "--show-origin").collect();
+    let [key] = keys.as_slice() else {
+        eprintln!("Usage: git-ai config get <key> [--show-origin]");
+        std::process::exit(1);
+    };
+
+    let value = match config_value(key) {
+        Ok(value) => value,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+    if !show_origin {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&value).unwrap_or_default()
+        );
+        return;
+    }
+    let top_level = parse_key_path(key).remove(0);
+    let origin = LayeredConfig::load()
+        .get(&top_level)
+        .map_or_else(|| "default".to_string(), |entry| entry.origin.to_string());
+    println!("{}\t{}", origin, value_text(&value));
+}
+
+fn handle_list(args: &[String]) {
+    let mut show_origin = false;
+    for arg in args {
+        match arg.as_str() {
+            "--show-origin" => show_origin = true,
+            other => {
+                eprintln!("Unknown config list argument: {}", other);
+                eprintln!("Usage: git-ai config list [--show-origin]");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // Like `git config --list`, every layer's entries in load order: the last one for a key wins
+    for entry in &LayeredConfig::load().entries {
+        let value = match config_layers::find_setting(&entry.key) {
+            Some(setting) => masked(setting, entry.value.clone()),
+            None => entry.value.clone(),
+        };
+        if show_origin {
+            println!("{}\t{}={}", entry.origin, entry.key, value_text(&value));
+        } else {
+            println!("{}={}", entry.key, value_text(&value));
+        }
+    }
+}
+
+fn handle_validate() {
+    let config = LayeredConfig::load();
+    if config.problems.is_empty() {
+        println!("Configuration is valid");
+        return;
+    }
+    for problem in &config.problems {
+        eprintln!("{}", problem);
+    }
+    std::process::exit(1);
+}
+
+/// `set`/`unset` against the git-ai.toml of `scope`
+fn handle_layer_edit(scope: ConfigScope, args: &[String]) -> Result<(), String> {
+    let path = config_layers::layer_path(scope)
+        .ok_or_else(|| format!("--{} must be run inside a git repository", scope.as_str()))?;
+    if args.iter().any(|a| a == "--add") {
+        return Err(format!(
+            "--add isn't supported with --{}; set the whole value instead",
+            scope.as_str()
+        ));
+    }
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        ["set", key, value] => {
+            let value = config_layers::set_in_file(&path, key, value)?;
+            let setting = config_layers::lookup_setting(key)?;
+            eprintln!(
+                "[{}]: {} ({})",
+                key,
+                value_text(&masked(setting, value)),
+                path.display()
+            );
+            Ok(())
+        }
+        ["unset", key] => {
+            if config_layers::unset_in_file(&path, key)? {
+                eprintln!("- [{}] ({})", key, path.display());
+                Ok(())
+            } else {
+                Err(format!("{} is not set in {}", key, path.display()))
+            }
+        }
+        _ => Err(format!(
+            "--{} works with `set <key> <value>` and `unset <key>`",
+            scope.as_str()
+        )),
+    }
+}
+
+/// Strings as-is, everything else as compact JSON
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn masked(setting: &Setting, value: Value) -> Value {
+    match value {
+        Value::String(s) if setting.secret => Value::String(mask_api_key(&s)),
+        other => other,
+    }
+}
+
 fn show_all_config() -> Result<(), String> {
-    let file_config = crate::config::load_file_config_public()?;
+    let file_config = crate::config::effective_file_config();
 
     // Build a complete effective config representation
     let mut effective_config = serde_json::Map::new();
@@ -289,6 +498,77 @@ fn show_all_config() -> Result<(), String> {
         Value::Bool(runtime_config.is_quiet()),
     );
 
+    effective_config.insert(
+        "bot_authors".to_string(),
+        effective_bot_authors(&file_config),
+    );
+
+    effective_config.insert(
+        "mirror_summary_notes".to_string(),
+        Value::Bool(runtime_config.mirror_summary_notes()),
+    );
+
+    effective_config.insert(
+        "prometheus_enabled".to_string(),
+        Value::Bool(runtime_config.is_prometheus_enabled()),
+    );
+
+    effective_config.insert(
+        "metrics_sink".to_string(),
+        Value::String(runtime_config.metrics_sinks().join(",")),
+    );
+
+    effective_config.insert(
+        "telemetry_privacy".to_string(),
+        runtime_config
+            .telemetry_privacy()
+            .map_or(Value::Null, |v| Value::String(v.to_string())),
+    );
+
+    effective_config.insert(
+        "telemetry_redact".to_string(),
+        runtime_config
+            .telemetry_redact()
+            .map_or(Value::Null, |v| Value::String(v.to_string())),
+    );
+
+    effective_config.insert(
+        "telemetry_allow".to_string(),
+        runtime_config
+            .telemetry_allow()
+            .map_or(Value::Null, |v| Value::String(v.to_string())),
+    );
+
+    effective_config.insert(
+        "max_concurrency".to_string(),
+        Value::from(runtime_config.max_concurrency()),
+    );
+
+    effective_config.insert(
+        "background_nice".to_string(),
+        Value::from(runtime_config.background_nice()),
+    );
+
+    effective_config.insert(
+        "background_io_idle".to_string(),
+        Value::Bool(runtime_config.background_io_idle()),
+    );
+
+    effective_config.insert(
+        "agent_detectors".to_string(),
+        serde_json::to_value(runtime_config.agent_detectors()).unwrap(),
+    );
+
+    effective_config.insert(
+        "metrics_routes".to_string(),
+        serde_json::to_value(runtime_config.metrics_routes()).unwrap(),
+    );
+
+    effective_config.insert(
+        "detect_generated_files".to_string(),
+        Value::Bool(runtime_config.detect_generated_files()),
+    );
+
     // Feature flags - show effective flags with defaults applied
     let flags_value = serde_json::to_value(runtime_config.get_feature_flags())
         .unwrap_or_else(|_| Value::Object(serde_json::Map::new()));
@@ -300,6 +580,17 @@ fn show_all_config() -> Result<(), String> {
         effective_config.insert("api_key".to_string(), Value::String(masked));
     }
 
+    // Remaining keys (OTel, webhooks, notes, policy) when they're set
+    for setting in config_layers::SETTINGS {
+        if setting.key == "telemetry_oss" || effective_config.contains_key(setting.key) {
+            continue;
+        }
+        let value = config_value(setting.key)?;
+        if !value.is_null() {
+            effective_config.insert(setting.key.to_string(), value);
+        }
+    }
+
     let json = serde_json::to_string_pretty(&effective_config)
         .map_err(|e| format!("Failed to serialize config: {}", e))?;
 
@@ -308,7 +599,16 @@ fn show_all_config() -> Result<(), String> {
 }
 
 fn get_config_value(key: &str) -> Result<(), String> {
-    let file_config = crate::config::load_file_config_public()?;
+    let value = config_value(key)?;
+    let json = serde_json::to_string_pretty(&value)
+        .map_err(|e| format!("Failed to serialize value: {}", e))?;
+    println!("{}", json);
+    Ok(())
+}
+
+/// The effective value of `key`, with defaults applied and secrets masked
+fn config_value(key: &str) -> Result<Value, String> {
+    let file_config = crate::config::effective_file_config();
     let runtime_config = crate::config::Config::get();
 
     let key_path = parse_key_path(key);
@@ -363,13 +663,35 @@ fn get_config_value(key: &str) -> Result<(), String> {
             }
             "prompt_storage" => Value::String(runtime_config.prompt_storage().to_string()),
             "quiet" => Value::Bool(runtime_config.is_quiet()),
-            _ => return Err(format!("Unknown config key: {}", key)),
+            "bot_authors" => effective_bot_authors(&file_config),
+            "mirror_summary_notes" => Value::Bool(runtime_config.mirror_summary_notes()),
+            "prometheus_enabled" => Value::Bool(runtime_config.is_prometheus_enabled()),
+            "metrics_sink" => Value::String(runtime_config.metrics_sinks().join(",")),
+            "telemetry_privacy" => runtime_config
+                .telemetry_privacy()
+                .map_or(Value::Null, |v| Value::String(v.to_string())),
+            "telemetry_redact" => runtime_config
+                .telemetry_redact()
+                .map_or(Value::Null, |v| Value::String(v.to_string())),
+            "telemetry_allow" => runtime_config
+                .telemetry_allow()
+                .map_or(Value::Null, |v| Value::String(v.to_string())),
+            "max_concurrency" => Value::from(runtime_config.max_concurrency()),
+            "background_nice" => Value::from(runtime_config.background_nice()),
+            "background_io_idle" => Value::Bool(runtime_config.background_io_idle()),
+            "agent_detectors" => serde_json::to_value(runtime_config.agent_detectors()).unwrap(),
+            "metrics_routes" => serde_json::to_value(runtime_config.metrics_routes()).unwrap(),
+            "detect_generated_files" => Value::Bool(runtime_config.detect_generated_files()),
+            other => {
+                let setting = config_layers::lookup_setting(other)?;
+                let value = serde_json::to_value(&file_config)
+                    .ok()
+                    .and_then(|config| config.get(other).cloned())
+                    .unwrap_or(Value::Null);
+                masked(setting, value)
+            }
         };
-
-        let json = serde_json::to_string_pretty(&value)
-            .map_err(|e| format!("Failed to serialize value: {}", e))?;
-        println!("{}", json);
-        return Ok(());
+        return Ok(value);
     }
 
     // Handle nested keys (dot notation)
@@ -384,11 +706,7 @@ fn get_config_value(key: &str) -> Result<(), String> {
                 .get(segment)
                 .ok_or_else(|| format!("Config key not found: {}", key))?;
         }
-
-        let json = serde_json::to_string_pretty(current)
-            .map_err(|e| format!("Failed to serialize value: {}", e))?;
-        println!("{}", json);
-        return Ok(());
+        return Ok(current.clone());
     }
 
     Err(format!("Nested keys are only supported for feature_flags"))
@@ -498,7 +816,101 @@ fn set_config_value(key: &str, value: &str, add_mode: bool) -> Result<(), String
                 crate::config::save_file_config(&file_config)?;
                 eprintln!("[quiet]: {}", bool_value);
             }
-            _ => return Err(format!("Unknown config key: {}", key)),
+            "mirror_summary_notes" => {
+                let bool_value = parse_bool(value)?;
+                file_config.mirror_summary_notes = Some(bool_value);
+                crate::config::save_file_config(&file_config)?;
+                eprintln!("[mirror_summary_notes]: {}", bool_value);
+            }
+            "prometheus_enabled" => {
+                let bool_value = parse_bool(value)?;
+                file_config.prometheus_enabled = Some(bool_value);
+                crate::config::save_file_config(&file_config)?;
+                eprintln!("[prometheus_enabled]: {}", bool_value);
+            }
+            "metrics_sink" => {
+                file_config.metrics_sink = Some(value.to_string());
+                crate::config::save_file_config(&file_config)?;
+                eprintln!("[metrics_sink]: {}", value);
+            }
+            "telemetry_privacy" => {
+                file_config.telemetry_privacy = Some(value.to_string());
+                crate::config::save_file_config(&file_config)?;
+                eprintln!("[telemetry_privacy]: {}", value);
+            }
+            "telemetry_redact" => {
+                file_config.telemetry_redact = Some(value.to_string());
+                crate::config::save_file_config(&file_config)?;
+                eprintln!("[telemetry_redact]: {}", value);
+            }
+            "telemetry_allow" => {
+                file_config.telemetry_allow = Some(value.to_string());
+                crate::config::save_file_config(&file_config)?;
+                eprintln!("[telemetry_allow]: {}", value);
+            }
+            "max_concurrency" => {
+                let limit = match value.parse::<usize>() {
+                    Ok(n) if n > 0 => n,
+                    _ => {
+                        return Err(format!(
+                            "Invalid max_concurrency value '{}'. Expected a positive integer",
+                            value
+                        ));
+                    }
+                };
+                file_config.max_concurrency = Some(limit);
+                crate::config::save_file_config(&file_config)?;
+                eprintln!("[max_concurrency]: {}", limit);
+            }
+            "background_nice" => {
+                let nice = match value.parse::<i32>() {
+                    Ok(n) if (0..=19).contains(&n) => n,
+                    _ => {
+                        return Err(format!(
+                            "Invalid background_nice value '{}'. Expected an integer from 0 to 19",
+                            value
+                        ));
+                    }
+                };
+                file_config.background_nice = Some(nice);
+                crate::config::save_file_config(&file_config)?;
+                eprintln!("[background_nice]: {}", nice);
+            }
+            "background_io_idle" => {
+                let bool_value = parse_bool(value)?;
+                file_config.background_io_idle = Some(bool_value);
+                crate::config::save_file_config(&file_config)?;
+                eprintln!("[background_io_idle]: {}", bool_value);
+            }
+            "bot_authors" => {
+                // --add extends the defaults rather than replacing them
+                if add_mode && file_config.bot_authors.is_none() {
+                    file_config.bot_authors = Some(
+                        crate::config::DEFAULT_BOT_AUTHORS
+                            .iter()
+                            .map(|s| s.to_string())
+                            .collect(),
+                    );
+                }
+                let added = set_string_array_field(&mut file_config.bot_authors, value, add_mode)?;
+                crate::config::save_file_config(&file_config)?;
+                log_array_changes(&added, add_mode);
+            }
+            "agent_detectors" => {
+                let detectors = parse_agent_detectors(value)?;
+                let added: Vec<String> = detectors.iter().map(|d| d.tool.clone()).collect();
+                if add_mode {
+                    file_config
+                        .agent_detectors
+                        .get_or_insert_with(Vec::new)
+                        .extend(detectors);
+                } else {
+                    file_config.agent_detectors = Some(detectors);
+                }
+                crate::config::save_file_config(&file_config)?;
+                log_array_changes(&added, add_mode);
+            }
+            other => set_schema_value(&mut file_config, other, value)?,
         }
 
         return Ok(());
@@ -655,7 +1067,85 @@ fn unset_config_value(key: &str) -> Result<(), String> {
                     eprintln!("- [quiet]: {}", v);
                 }
             }
-            _ => return Err(format!("Unknown config key: {}", key)),
+            "mirror_summary_notes" => {
+                let old_value = file_config.mirror_summary_notes.take();
+                crate::config::save_file_config(&file_config)?;
+                if let Some(v) = old_value {
+                    eprintln!("- [mirror_summary_notes]: {}", v);
+                }
+            }
+            "prometheus_enabled" => {
+                let old_value = file_config.prometheus_enabled.take();
+                crate::config::save_file_config(&file_config)?;
+                if let Some(v) = old_value {
+                    eprintln!("- [prometheus_enabled]: {}", v);
+                }
+            }
+            "metrics_sink" => {
+                let old_value = file_config.metrics_sink.take();
+                crate::config::save_file_config(&file_config)?;
+                if let Some(v) = old_value {
+                    eprintln!("- [metrics_sink]: {}", v);
+                }
+            }
+            "telemetry_privacy" => {
+                let old_value = file_config.telemetry_privacy.take();
+                crate::config::save_file_config(&file_config)?;
+                if let Some(v) = old_value {
+                    eprintln!("- [telemetry_privacy]: {}", v);
+                }
+            }
+            "telemetry_redact" => {
+                let old_value = file_config.telemetry_redact.take();
+                crate::config::save_file_config(&file_config)?;
+                if let Some(v) = old_value {
+                    eprintln!("- [telemetry_redact]: {}", v);
+                }
+            }
+            "telemetry_allow" => {
+                let old_value = file_config.telemetry_allow.take();
+                crate::config::save_file_config(&file_config)?;
+                if let Some(v) = old_value {
+                    eprintln!("- [telemetry_allow]: {}", v);
+                }
+            }
+            "max_concurrency" => {
+                let old_value = file_config.max_concurrency.take();
+                crate::config::save_file_config(&file_config)?;
+                if let Some(v) = old_value {
+                    eprintln!("- [max_concurrency]: {}", v);
+                }
+            }
+            "background_nice" => {
+                let old_value = file_config.background_nice.take();
+                crate::config::save_file_config(&file_config)?;
+                if let Some(v) = old_value {
+                    eprintln!("- [background_nice]: {}", v);
+                }
+            }
+            "background_io_idle" => {
+                let old_value = file_config.background_io_idle.take();
+                crate::config::save_file_config(&file_config)?;
+                if let Some(v) = old_value {
+                    eprintln!("- [background_io_idle]: {}", v);
+                }
+            }
+            "bot_authors" => {
+                let old_values = file_config.bot_authors.take();
+                crate::config::save_file_config(&file_config)?;
+                if let Some(items) = old_values {
+                    log_array_removals(&items);
+                }
+            }
+            "agent_detectors" => {
+                let old_values = file_config.agent_detectors.take();
+                crate::config::save_file_config(&file_config)?;
+                if let Some(items) = old_values {
+                    let tools: Vec<String> = items.into_iter().map(|d| d.tool).collect();
+                    log_array_removals(&tools);
+                }
+            }
+            other => unset_schema_value(&mut file_config, other)?,
         }
 
         return Ok(());
@@ -718,6 +1208,43 @@ fn unset_config_value(key: &str) -> Result<(), String> {
     Err(format!("Nested keys are only supported for feature_flags"))
 }
 
+/// Set a key that needs no special handling, validated against the schema
+fn set_schema_value(
+    file_config: &mut crate::config::FileConfig,
+    key: &str,
+    value: &str,
+) -> Result<(), String> {
+    let setting = config_layers::lookup_setting(key)?;
+    let parsed = setting
+        .parse_text(value)
+        .map_err(|e| format!("{}: {}", key, e))?;
+    let mut json = serde_json::to_value(&*file_config)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    json[key] = parsed.clone();
+    *file_config =
+        serde_json::from_value(json).map_err(|e| format!("Failed to update config: {}", e))?;
+    crate::config::save_file_config(file_config)?;
+    eprintln!("[{}]: {}", key, value_text(&masked(setting, parsed)));
+    Ok(())
+}
+
+fn unset_schema_value(
+    file_config: &mut crate::config::FileConfig,
+    key: &str,
+) -> Result<(), String> {
+    let setting = config_layers::lookup_setting(key)?;
+    let mut json = serde_json::to_value(&*file_config)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    let old_value = json.as_object_mut().and_then(|map| map.remove(key));
+    *file_config =
+        serde_json::from_value(json).map_err(|e| format!("Failed to update config: {}", e))?;
+    crate::config::save_file_config(file_config)?;
+    if let Some(v) = old_value {
+        eprintln!("- [{}]: {}", key, value_text(&masked(setting, v)));
+    }
+    Ok(())
+}
+
 fn parse_key_path(key: &str) -> Vec<String> {
     key.split('.').map(|s| s.to_string()).collect()
 }
@@ -774,6 +1301,66 @@ fn set_repository_array_field(
     }
 }
 
+/// Set a plain string array field (no repository path resolution).
+/// Accepts a JSON array in set mode; otherwise stores/appends the single value.
+fn set_string_array_field(
+    field: &mut Option<Vec<String>>,
+    value: &str,
+    add_mode: bool,
+) -> Result<Vec<String>, String> {
+    let values: Vec<String> = if value.starts_with('[') {
+        serde_json::from_str(value)
+            .map_err(|e| format!("Expected a JSON array of strings: {}", e))?
+    } else {
+        vec![value.to_string()]
+    };
+
+    if add_mode {
+        let mut arr = field.take().unwrap_or_default();
+        arr.extend(values.clone());
+        *field = Some(arr);
+    } else {
+        *field = Some(values.clone());
+    }
+    Ok(values)
+}
+
+/// bot_authors falls back to the built-in defaults when unset
+fn effective_bot_authors(file_config: &crate::config::FileConfig) -> Value {
+    match file_config.bot_authors {
+        Some(ref authors) => serde_json::to_value(authors).unwrap(),
+        None => serde_json::to_value(crate::config::DEFAULT_BOT_AUTHORS).unwrap(),
+    }
+}
+
+/// Parse one detector object or an array of them from a JSON value
+fn parse_agent_detectors(value: &str) -> Result<Vec<crate::config::AgentDetectorConfig>, String> {
+    let json_value: Value = serde_json::from_str(value)
+        .map_err(|e| format!("Invalid JSON for agent_detectors: {}", e))?;
+    let items = match json_value {
+        Value::Array(items) => items,
+        object @ Value::Object(_) => vec![object],
+        _ => return Err("agent_detectors must be a JSON object or array of objects".to_string()),
+    };
+
+    let mut detectors = Vec::new();
+    for item in items {
+        let detector: crate::config::AgentDetectorConfig =
+            serde_json::from_value(item).map_err(|e| format!("Invalid agent detector: {}", e))?;
+        if detector.tool.trim().is_empty() {
+            return Err("Agent detector requires a non-empty tool".to_string());
+        }
+        if detector.process_name.is_none() && detector.env_var.is_none() {
+            return Err(format!(
+                "Agent detector '{}' requires a process_name or env_var",
+                detector.tool
+            ));
+        }
+        detectors.push(detector);
+    }
+    Ok(detectors)
+}
+
 /// Resolve a repository value - returns the actual patterns to store
 /// For file paths, resolves to repository remote URLs
 /// For URLs/patterns, returns as-is
@@ -908,4 +1495,25 @@ mod tests {
         assert!(err.contains("Invalid boolean value"));
         assert!(err.contains("invalid"));
     }
+
+    #[test]
+    fn test_parse_agent_detectors_accepts_object_or_array() {
+        let single = parse_agent_detectors(r#"{"tool":"cline","process_name":"cline"}"#).unwrap();
+        assert_eq!(single.len(), 1);
+        assert_eq!(single[0].process_name.as_deref(), Some("cline"));
+
+        let many = parse_agent_detectors(
+            r#"[{"tool":"a","env_var":"A_AGENT"},{"tool":"b","process_name":"b","model":"m"}]"#,
+        )
+        .unwrap();
+        assert_eq!(many.len(), 2);
+        assert_eq!(many[1].model.as_deref(), Some("m"));
+    }
+
+    #[test]
+    fn test_parse_agent_detectors_rejects_unmatchable_detector() {
+        assert!(parse_agent_detectors(r#"{"tool":"cline"}"#).is_err());
+        assert!(parse_agent_detectors(r#"{"tool":"","env_var":"X"}"#).is_err());
+        assert!(parse_agent_detectors("\"cline\"").is_err());
+    }
 }
@@ -0,0 +1,433 @@
+use crate::authorship::authorship_log_serialization::GIT_AI_VERSION;
+use crate::config::{FileConfig, load_file_config_public, save_file_config};
+use crate::utils::{decode_hex, encode_hex};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+
+pub const CONFIG_BUNDLE_VERSION: u32 = 1;
+/// Environment variable holding the shared team key used to sign and verify bundles
+pub const CONFIG_BUNDLE_KEY_ENV: &str = "GIT_AI_BUNDLE_KEY";
+
+const SIGNATURE_HMAC_SHA256: &str = "hmac-sha256";
+const SIGNATURE_SHA256: &str = "sha256";
+
+/// Team settings that travel in a bundle. Personal or machine-specific values (api_key,
+/// git_path, quiet, otel_auth_header) are never exported, and neither is release_signing_key:
+/// a bundle that could set it alongside api_base_url could point self-update at any release.
+const BUNDLED_KEYS: &[&str] = &[
+    "exclude_prompts_in_repositories",
+    "allow_repositories",
+    "exclude_repositories",
+    "telemetry_oss",
+    "telemetry_enterprise_dsn",
+    "disable_version_checks",
+    "disable_auto_updates",
+    "update_channel",
+    "feature_flags",
+    "api_base_url",
+    "prompt_storage",
+    "otel_enabled",
+    "otel_endpoint",
+    "otel_export_interval_secs",
+    "otel_protocol",
+    "prometheus_enabled",
+    "metrics_sink",
+    "webhook_url",
+    "telemetry_privacy",
+    "telemetry_redact",
+    "telemetry_allow",
+    "bot_authors",
+    "mirror_summary_notes",
+    "max_concurrency",
+    "background_nice",
+    "background_io_idle",
+    "agent_detectors",
+    "metrics_routes",
+    "required_version",
+    "detect_generated_files",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigBundle {
+    pub bundle_version: u32,
+    pub created_at: String,
+    pub git_ai_version: String,
+    pub config: serde_json::Map<String, serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<BundleSignature>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleSignature {
+    /// "hmac-sha256" when signed with a team key, "sha256" for an integrity checksum only
+    pub algorithm: String,
+    pub value: String,
+}
+
+pub fn handle_export_bundle(args: &[String]) {
+    let mut output: Option<String> = None;
+    let mut key_file: Option<String> = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--output" | "-o" if i + 1 < args.len() => {
+                output = Some(args[i + 1].clone());
+                i += 2;
+            }
+            "--key-file" if i + 1 < args.len() => {
+                key_file = Some(args[i + 1].clone());
+                i += 2;
+            }
+            other => {
+                eprintln!("Unknown export-bundle argument: {}", other);
+                eprintln!(
+                    "Usage: git-ai config export-bundle [--output <file>] [--key-file <file>]"
+                );
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let result = load_bundle_key(key_file.as_deref()).and_then(|key| {
+        let file_config = load_file_config_public()?;
+        let bundle = build_bundle(&file_config, key.as_deref())?;
+        serde_json::to_string_pretty(&bundle)
+            .map(|json| (json, key.is_some()))
+            .map_err(|e| format!("Failed to serialize bundle: {}", e))
+    });
+
+    match result {
+        Ok((json, signed)) => match output {
+            Some(path) => {
+                if let Err(e) = fs::write(&path, json + "\n") {
+                    eprintln!("Error: Failed to write {}: {}", path, e);
+                    std::process::exit(1);
+                }
+                eprintln!(
+                    "Wrote {} config bundle to {}",
+                    if signed { "signed" } else { "unsigned" },
+                    path
+                );
+            }
+            None => println!("{}", json),
+        },
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+pub fn handle_import_bundle(args: &[String]) {
+    let mut path: Option<String> = None;
+    let mut key_file: Option<String> = None;
+    let mut allow_unsigned = false;
+    let mut dry_run = false;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--key-file" if i + 1 < args.len() => {
+                key_file = Some(args[i + 1].clone());
+                i += 2;
+            }
+            "--allow-unsigned" => {
+                allow_unsigned = true;
+                i += 1;
+            }
+            "--dry-run" => {
+                dry_run = true;
+                i += 1;
+            }
+            other if path.is_none() && !other.starts_with('-') => {
+                path = Some(other.to_string());
+                i += 1;
+            }
+            other => {
+                eprintln!("Unknown import-bundle argument: {}", other);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let Some(path) = path else {
+        eprintln!(
+            "Usage: git-ai config import-bundle <file> [--key-file <file>] [--allow-unsigned] [--dry-run]"
+        );
+        std::process::exit(1);
+    };
+
+    let result = (|| -> Result<Vec<String>, String> {
+        let key = load_bundle_key(key_file.as_deref())?;
+        let data = if path == "-" {
+            std::io::read_to_string(std::io::stdin())
+                .map_err(|e| format!("Failed to read stdin: {}", e))?
+        } else {
+            fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?
+        };
+        let bundle: ConfigBundle =
+            serde_json::from_str(&data).map_err(|e| format!("Invalid config bundle: {}", e))?;
+        verify_bundle(&bundle, key.as_deref(), allow_unsigned)?;
+
+        let mut file_config = load_file_config_public()?;
+        let changed = apply_bundle(&mut file_config, &bundle)?;
+        if !dry_run && !changed.is_empty() {
+            save_file_config(&file_config)?;
+        }
+        Ok(changed)
+    })();
+
+    match result {
+        Ok(changed) if changed.is_empty() => eprintln!("Config already matches the bundle."),
+        Ok(changed) => {
+            let verb = if dry_run { "Would update" } else { "Updated" };
+            for key in &changed {
+                eprintln!("{} {}", verb, key);
+            }
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Resolve the signing key from `--key-file` or `GIT_AI_BUNDLE_KEY`
+fn load_bundle_key(key_file: Option<&str>) -> Result<Option<Vec<u8>>, String> {
+    let key = match key_file {
+        Some(path) => Some(
+            fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read key file {}: {}", path, e))?,
+        ),
+        None => std::env::var(CONFIG_BUNDLE_KEY_ENV).ok(),
+    };
+    match key.map(|k| k.trim().to_string()) {
+        Some(k) if k.is_empty() => Err("Bundle key is empty".to_string()),
+        Some(k) => Ok(Some(k.into_bytes())),
+        None => Ok(None),
+    }
+}
+
+/// Build a bundle from the shareable subset of `file_config`, signed when `key` is given
+pub fn build_bundle(file_config: &FileConfig, key: Option<&[u8]>) -> Result<ConfigBundle, String> {
+    let all = match serde_json::to_value(file_config) {
+        Ok(serde_json::Value::Object(map)) => map,
+        _ => return Err("Failed to serialize config".to_string()),
+    };
+    let config = all
+        .into_iter()
+        .filter(|(k, _)| BUNDLED_KEYS.contains(&k.as_str()))
+        .collect();
+
+    let mut bundle = ConfigBundle {
+        bundle_version: CONFIG_BUNDLE_VERSION,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        git_ai_version: GIT_AI_VERSION.to_string(),
+        config,
+        signature: None,
+    };
+    bundle.signature = Some(sign_bundle(&bundle, key)?);
+    Ok(bundle)
+}
+
+/// Check the bundle's version and signature. A keyed bundle needs the same key; a
+/// checksum-only bundle is accepted only with `allow_unsigned`.
+pub fn verify_bundle(
+    bundle: &ConfigBundle,
+    key: Option<&[u8]>,
+    allow_unsigned: bool,
+) -> Result<(), String> {
+    if bundle.bundle_version > CONFIG_BUNDLE_VERSION {
+        return Err(format!(
+            "Bundle version {} is newer than this git-ai supports ({}); upgrade git-ai first",
+            bundle.bundle_version, CONFIG_BUNDLE_VERSION
+        ));
+    }
+    let signature = bundle
+        .signature
+        .as_ref()
+        .ok_or_else(|| "Bundle has no signature".to_string())?;
+
+    match signature.algorithm.as_str() {
+        SIGNATURE_HMAC_SHA256 => {
+            let key = key.ok_or_else(|| {
+                format!(
+                    "Bundle is signed; pass --key-file or set {}",
+                    CONFIG_BUNDLE_KEY_ENV
+                )
+            })?;
+            let expected = decode_hex(&signature.value)
+                .ok_or_else(|| "Malformed bundle signature".to_string())?;
+            let mut mac = new_mac(key)?;
+            mac.update(canonical_payload(bundle)?.as_bytes());
+            mac.verify_slice(&expected)
+                .map_err(|_| "Bundle signature does not match; it was modified or signed with a different key".to_string())
+        }
+        SIGNATURE_SHA256 => {
+            if sign_bundle(bundle, None)? != *signature {
+                return Err("Bundle checksum does not match; the file was modified".to_string());
+            }
+            if !allow_unsigned {
+                return Err(
+                    "Bundle is not signed with a team key; pass --allow-unsigned to import it anyway"
+                        .to_string(),
+                );
+            }
+            Ok(())
+        }
+        other => Err(format!("Unsupported bundle signature algorithm: {}", other)),
+    }
+}
+
+/// Overwrite the bundled keys in `file_config`, leaving personal settings alone.
+/// Returns the keys whose values changed.
+pub fn apply_bundle(
+    file_config: &mut FileConfig,
+    bundle: &ConfigBundle,
+) -> Result<Vec<String>, String> {
+    let mut current = match serde_json::to_value(&*file_config) {
+        Ok(serde_json::Value::Object(map)) => map,
+        _ => return Err("Failed to serialize config".to_string()),
+    };
+
+    let mut changed = Vec::new();
+    for (key, value) in &bundle.config {
+        if !BUNDLED_KEYS.contains(&key.as_str()) {
+            return Err(format!(
+                "Bundle contains a key that cannot be imported: {}",
+                key
+            ));
+        }
+        if current.get(key) != Some(value) {
+            current.insert(key.clone(), value.clone());
+            changed.push(key.clone());
+        }
+    }
+
+    *file_config = serde_json::from_value(serde_json::Value::Object(current))
+        .map_err(|e| format!("Bundle contains invalid config values: {}", e))?;
+    Ok(changed)
+}
+
+fn sign_bundle(bundle: &ConfigBundle, key: Option<&[u8]>) -> Result<BundleSignature, String> {
+    let payload = canonical_payload(bundle)?;
+    Ok(match key {
+        Some(key) => {
+            let mut mac = new_mac(key)?;
+            mac.update(payload.as_bytes());
+            BundleSignature {
+                algorithm: SIGNATURE_HMAC_SHA256.to_string(),
+                value: encode_hex(&mac.finalize().into_bytes()),
+            }
+        }
+        None => BundleSignature {
+            algorithm: SIGNATURE_SHA256.to_string(),
+            value: encode_hex(&Sha256::digest(payload.as_bytes())),
+        },
+    })
+}
+
+/// RFC 8785 canonical JSON of everything but the signature, so formatting changes don't
+/// invalidate it
+fn canonical_payload(bundle: &ConfigBundle) -> Result<String, String> {
+    let unsigned = ConfigBundle {
+        signature: None,
+        ..bundle.clone()
+    };
+    serde_json_canonicalizer::to_string(&unsigned)
+        .map_err(|e| format!("Failed to canonicalize bundle: {}", e))
+}
+
+fn new_mac(key: &[u8]) -> Result<Hmac<Sha256>, String> {
+    Hmac::<Sha256>::new_from_slice(key).map_err(|e| format!("Invalid bundle key: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn team_config() -> FileConfig {
+        FileConfig {
+            exclude_repositories: Some(vec!["https://github.com/acme/secret-*".to_string()]),
+            prompt_storage: Some("notes".to_string()),
+            api_key: Some("personal-key".to_string()),
+            git_path: Some("/opt/git/bin/git".to_string()),
+            ..FileConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_bundle_excludes_personal_settings() {
+        let bundle = build_bundle(&team_config(), None).unwrap();
+        assert!(bundle.config.contains_key("exclude_repositories"));
+        assert!(bundle.config.contains_key("prompt_storage"));
+        assert!(!bundle.config.contains_key("api_key"));
+        assert!(!bundle.config.contains_key("git_path"));
+    }
+
+    #[test]
+    fn test_signed_bundle_round_trip_and_tamper_detection() {
+        let key = b"team-secret".as_slice();
+        let bundle = build_bundle(&team_config(), Some(key)).unwrap();
+        let json = serde_json::to_string_pretty(&bundle).unwrap();
+        let parsed: ConfigBundle = serde_json::from_str(&json).unwrap();
+
+        assert!(verify_bundle(&parsed, Some(key), false).is_ok());
+        assert!(verify_bundle(&parsed, Some(b"wrong".as_slice()), false).is_err());
+        assert!(verify_bundle(&parsed, None, true).is_err());
+
+        let mut tampered = parsed.clone();
+        tampered
+            .config
+            .insert("prompt_storage".to_string(), "local".into());
+        assert!(verify_bundle(&tampered, Some(key), false).is_err());
+    }
+
+    #[test]
+    fn test_unsigned_bundle_requires_opt_in() {
+        let bundle = build_bundle(&team_config(), None).unwrap();
+        assert!(verify_bundle(&bundle, None, false).is_err());
+        assert!(verify_bundle(&bundle, None, true).is_ok());
+    }
+
+    #[test]
+    fn test_apply_bundle_keeps_personal_settings() {
+        let bundle = build_bundle(&team_config(), None).unwrap();
+        let mut mine = FileConfig {
+            api_key: Some("my-key".to_string()),
+            prompt_storage: Some("local".to_string()),
+            ..FileConfig::default()
+        };
+
+        let changed = apply_bundle(&mut mine, &bundle).unwrap();
+        assert_eq!(changed, vec!["exclude_repositories", "prompt_storage"]);
+        assert_eq!(mine.api_key.as_deref(), Some("my-key"));
+        assert_eq!(mine.prompt_storage.as_deref(), Some("notes"));
+        assert!(apply_bundle(&mut mine, &bundle).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_bundle_cannot_set_release_signing_key() {
+        let with_key = FileConfig {
+            release_signing_key: Some("ssh-ed25519 AAAA team".to_string()),
+            ..team_config()
+        };
+        let mut bundle = build_bundle(&with_key, None).unwrap();
+        assert!(!bundle.config.contains_key("release_signing_key"));
+
+        bundle.config.insert(
+            "release_signing_key".to_string(),
+            "ssh-ed25519 AAAA attacker".into(),
+        );
+        bundle
+            .config
+            .insert("api_base_url".to_string(), "https://evil.example".into());
+        let mut mine = FileConfig::default();
+        assert!(apply_bundle(&mut mine, &bundle).is_err());
+        assert!(mine.release_signing_key.is_none());
+        assert!(mine.api_base_url.is_none());
+    }
+}
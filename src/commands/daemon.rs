@@ -0,0 +1,186 @@
+use crate::daemon::rpc::{self, Response};
+use crate::daemon::{DEFAULT_BATCH_INTERVAL, DaemonConfig, default_endpoint, serve};
+use crate::git::workspace::load_workspace_or_exit;
+use serde_json::Value;
+use std::path::PathBuf;
+use std::time::Duration;
+
+pub fn handle_daemon(args: &[String]) {
+    let Some(subcommand) = args.first() else {
+        print_daemon_help_and_exit();
+    };
+
+    let mut endpoint = default_endpoint();
+    let mut repositories: Vec<PathBuf> = Vec::new();
+    let mut batch_interval = DEFAULT_BATCH_INTERVAL;
+    let mut positional: Vec<String> = Vec::new();
+
+    let mut i = 1;
+    while i < args.len() {
+        let value = args.get(i + 1);
+        match args[i].as_str() {
+            "--socket" | "--repo" | "--batch-ms" if value.is_none() => {
+                eprintln!("{} requires a value", args[i]);
+                std::process::exit(1);
+            }
+            "--socket" => {
+                endpoint = PathBuf::from(value.unwrap());
+                i += 2;
+            }
+            "--repo" => {
+                let dir = PathBuf::from(value.unwrap());
+                repositories.push(dir.canonicalize().unwrap_or(dir));
+                i += 2;
+            }
+            "--workspace" => {
+                for repo in load_workspace_or_exit().repositories {
+                    repositories.push(repo.path.canonicalize().unwrap_or(repo.path));
+                }
+                i += 1;
+            }
+            "--batch-ms" => {
+                batch_interval = match value.unwrap().parse::<u64>() {
+                    Ok(ms) => Duration::from_millis(ms),
+                    Err(_) => {
+                        eprintln!("--batch-ms must be a number of milliseconds");
+                        std::process::exit(1);
+                    }
+                };
+                i += 2;
+            }
+            other => {
+                positional.push(other.to_string());
+                i += 1;
+            }
+        }
+    }
+
+    match subcommand.as_str() {
+        "run" => {
+            let config = DaemonConfig {
+                endpoint,
+                repositories,
+                batch_interval,
+            };
+            eprintln!("git-ai daemon listening on {}", config.endpoint.display());
+            if let Err(e) = serve(&config) {
+                eprintln!("git-ai daemon failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+        "start" => start_daemon(&args[1..], &endpoint),
+        "stop" => {
+            print_response(call_or_exit(&endpoint, "shutdown", Value::Null));
+        }
+        "status" => {
+            print_response(call_or_exit(&endpoint, "status", Value::Null));
+        }
+        "call" => {
+            let Some(method) = positional.first() else {
+                eprintln!("Usage: git-ai daemon call <method> [<params-json>]");
+                std::process::exit(1);
+            };
+            let params = match positional.get(1) {
+                Some(json) => match serde_json::from_str(json) {
+                    Ok(params) => params,
+                    Err(e) => {
+                        eprintln!("Invalid params JSON: {}", e);
+                        std::process::exit(1);
+                    }
+                },
+                None => Value::Null,
+            };
+            print_response(call_or_exit(&endpoint, method, params));
+        }
+        "--help" | "-h" | "help" => print_daemon_help_and_exit(),
+        other => {
+            eprintln!("Unknown daemon subcommand: {}", other);
+            print_daemon_help_and_exit();
+        }
+    }
+}
+
+/// Launch `git-ai daemon run` in the background and wait for it to answer
+fn start_daemon(run_args: &[String], endpoint: &std::path::Path) {
+    if rpc::call(endpoint, "ping", Value::Null).is_ok() {
+        eprintln!("git-ai daemon already running on {}", endpoint.display());
+        return;
+    }
+
+    let mut cmd = match crate::utils::current_git_ai_exe() {
+        Ok(exe) => std::process::Command::new(exe),
+        Err(e) => {
+            eprintln!("Failed to locate git-ai: {}", e);
+            std::process::exit(1);
+        }
+    };
+    cmd.arg("daemon")
+        .arg("run")
+        .args(run_args)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null());
+    if let Err(e) = cmd.spawn() {
+        eprintln!("Failed to start git-ai daemon: {}", e);
+        std::process::exit(1);
+    }
+
+    if !rpc::wait_until_ready(endpoint, Duration::from_secs(5)) {
+        eprintln!(
+            "git-ai daemon did not come up on {} within 5s",
+            endpoint.display()
+        );
+        std::process::exit(1);
+    }
+    eprintln!("git-ai daemon started on {}", endpoint.display());
+}
+
+fn call_or_exit(endpoint: &std::path::Path, method: &str, params: Value) -> Response {
+    match rpc::call(endpoint, method, params) {
+        Ok(response) => response,
+        Err(e) => {
+            eprintln!(
+                "Failed to reach git-ai daemon on {}: {}",
+                endpoint.display(),
+                e
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+fn print_response(response: Response) {
+    if let Some(error) = response.error {
+        eprintln!("Error {}: {}", error.code, error.message);
+        std::process::exit(1);
+    }
+    let result = response.result.unwrap_or(Value::Null);
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&result).unwrap_or_default()
+    );
+}
+
+fn print_daemon_help_and_exit() -> ! {
+    eprintln!("Usage: git-ai daemon <run|start|stop|status|call> [options]");
+    eprintln!();
+    eprintln!("Long-running process that accepts checkpoints from editor plugins over a local");
+    eprintln!("JSON-RPC socket and batches them. See docs/daemon.md for the API.");
+    eprintln!();
+    eprintln!("  run                   Serve in the foreground");
+    eprintln!("  start                 Serve in the background and wait until it answers");
+    eprintln!("    --repo <path>         Only accept checkpoints for this repository (repeatable)");
+    eprintln!("    --workspace           Only accept checkpoints for the workspace's repositories");
+    eprintln!(
+        "    --batch-ms <ms>       Wait this long for more events before checkpointing (default: {})",
+        DEFAULT_BATCH_INTERVAL.as_millis()
+    );
+    eprintln!("  stop                  Process queued checkpoints and exit");
+    eprintln!("  status                Print queue and repository counters as JSON");
+    eprintln!("  call <method> [json]  Send one JSON-RPC request and print the result");
+    eprintln!();
+    eprintln!(
+        "  --socket <path>       Socket to use (default: $GIT_AI_DAEMON_SOCKET or ~/.git-ai/internal/daemon.sock)"
+    );
+    std::process::exit(0);
+}
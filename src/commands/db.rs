@@ -0,0 +1,241 @@
+//! `git-ai db`: maintenance for git-ai's local SQLite databases.
+
+use crate::authorship::internal_db::InternalDatabase;
+use crate::error::GitAiError;
+use crate::git::find_repository;
+use crate::metrics::db::MetricsDatabase;
+use crate::storage::LocalStore;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub fn handle_db(args: &[String]) {
+    let Some(subcommand) = args.first() else {
+        print_db_help_and_exit();
+    };
+
+    let result = match subcommand.as_str() {
+        "vacuum" => handle_vacuum(&args[1..]),
+        "info" => handle_info(&args[1..]),
+        "events" => handle_events(&args[1..]),
+        "checkpoints" => handle_checkpoints(&args[1..]),
+        "--help" | "-h" | "help" => print_db_help_and_exit(),
+        other => {
+            eprintln!("Unknown db subcommand: {}", other);
+            print_db_help_and_exit();
+        }
+    };
+
+    if let Err(e) = result {
+        eprintln!("git-ai db {} failed: {}", subcommand, e);
+        std::process::exit(1);
+    }
+}
+
+fn print_db_help_and_exit() -> ! {
+    eprintln!("Usage: git-ai db <vacuum|info|events|checkpoints> [options]");
+    eprintln!();
+    eprintln!("  vacuum             Compact git-ai's local databases");
+    eprintln!(
+        "    --older-than <days>  First delete stored events and checkpoints older than this"
+    );
+    eprintln!("  info               Show the local store's location, row counts and size");
+    eprintln!("    --json               Output in JSON format");
+    eprintln!("  events             Print stored metric events as JSON lines");
+    eprintln!("    --since <days>       Only events from the last N days");
+    eprintln!("  checkpoints        Print checkpoints stored for the current repository");
+    eprintln!("    --since <days>       Only checkpoints from the last N days");
+    eprintln!("    --json               Output as JSON lines");
+    std::process::exit(0);
+}
+
+fn handle_vacuum(args: &[String]) -> Result<(), GitAiError> {
+    let mut older_than_days: Option<u64> = None;
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--older-than" => {
+                let Some(value) = args.get(i + 1) else {
+                    eprintln!("Error: --older-than requires a number of days");
+                    std::process::exit(1);
+                };
+                older_than_days = match value.trim_end_matches('d').parse::<u64>() {
+                    Ok(days) => Some(days),
+                    Err(_) => {
+                        eprintln!("Error: invalid --older-than value '{}'", value);
+                        std::process::exit(1);
+                    }
+                };
+                i += 2;
+            }
+            other => {
+                eprintln!("Unknown argument: {}", other);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let store = LocalStore::global()?;
+    let mut store = store
+        .lock()
+        .map_err(|e| GitAiError::Generic(format!("Local store lock poisoned: {}", e)))?;
+    let before = store.stats()?;
+
+    if let Some(days) = older_than_days {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let (events, checkpoints) = store.prune_before(now.saturating_sub(days * 86_400))?;
+        eprintln!(
+            "Deleted {} metric events and {} checkpoints older than {} days",
+            events, checkpoints, days
+        );
+    }
+
+    store.vacuum()?;
+    let after = store.stats()?;
+    eprintln!(
+        "Vacuumed {}: {} -> {}",
+        store.path().display(),
+        format_bytes(before.size_bytes),
+        format_bytes(after.size_bytes)
+    );
+
+    // The prompt and metrics-buffer databases churn too (uploads delete rows)
+    if let Ok(db) = MetricsDatabase::global()
+        && let Ok(mut db) = db.lock()
+    {
+        db.vacuum()?;
+    }
+    if let Ok(db) = InternalDatabase::global()
+        && let Ok(mut db) = db.lock()
+    {
+        db.vacuum()?;
+    }
+    Ok(())
+}
+
+fn handle_info(args: &[String]) -> Result<(), GitAiError> {
+    let json = args.iter().any(|a| a == "--json");
+
+    let store = LocalStore::global()?;
+    let store = store
+        .lock()
+        .map_err(|e| GitAiError::Generic(format!("Local store lock poisoned: {}", e)))?;
+    let stats = store.stats()?;
+
+    if json {
+        let value = serde_json::json!({
+            "path": store.path().display().to_string(),
+            "metric_events": stats.metric_events,
+            "checkpoints": stats.checkpoints,
+            "otel_spilled": stats.otel_spilled,
+            "size_bytes": stats.size_bytes,
+        });
+        println!("{}", serde_json::to_string_pretty(&value)?);
+    } else {
+        println!("path:           {}", store.path().display());
+        println!("metric events:  {}", stats.metric_events);
+        println!("checkpoints:    {}", stats.checkpoints);
+        println!("otel spilled:   {}", stats.otel_spilled);
+        println!("size:           {}", format_bytes(stats.size_bytes));
+    }
+    Ok(())
+}
+
+fn handle_events(args: &[String]) -> Result<(), GitAiError> {
+    let since = parse_since_days(args).unwrap_or(0);
+
+    let store = LocalStore::global()?;
+    let store = store
+        .lock()
+        .map_err(|e| GitAiError::Generic(format!("Local store lock poisoned: {}", e)))?;
+    for event in store.metric_events_since(since.min(u32::MAX as u64) as u32, None)? {
+        println!("{}", serde_json::to_string(&event)?);
+    }
+    Ok(())
+}
+
+fn handle_checkpoints(args: &[String]) -> Result<(), GitAiError> {
+    let since = parse_since_days(args).unwrap_or(0);
+    let json = args.iter().any(|a| a == "--json");
+
+    let repo = find_repository(&Vec::<String>::new())?;
+    let workdir = repo.workdir()?.to_string_lossy().to_string();
+
+    let store = LocalStore::global()?;
+    let store = store
+        .lock()
+        .map_err(|e| GitAiError::Generic(format!("Local store lock poisoned: {}", e)))?;
+    for checkpoint in store.checkpoints_for_repo(&workdir, since)? {
+        if json {
+            let value = serde_json::json!({
+                "timestamp": checkpoint.timestamp,
+                "base_commit": checkpoint.base_commit,
+                "kind": checkpoint.kind,
+                "author": checkpoint.author,
+                "tool": checkpoint.tool,
+                "model": checkpoint.model,
+                "external_id": checkpoint.external_id,
+                "files": checkpoint.files,
+                "lines_added": checkpoint.lines_added,
+                "lines_deleted": checkpoint.lines_deleted,
+            });
+            println!("{}", serde_json::to_string(&value)?);
+        } else {
+            println!(
+                "{} {:<8} {:<16} +{} -{} {}",
+                checkpoint.timestamp,
+                checkpoint.kind,
+                checkpoint.tool.as_deref().unwrap_or("-"),
+                checkpoint.lines_added,
+                checkpoint.lines_deleted,
+                checkpoint.files.join(",")
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Unix timestamp N days ago from `--since <days>`
+fn parse_since_days(args: &[String]) -> Option<u64> {
+    let position = args.iter().position(|a| a == "--since")?;
+    let Some(days) = args
+        .get(position + 1)
+        .and_then(|v| v.trim_end_matches('d').parse::<u64>().ok())
+    else {
+        eprintln!("Error: --since requires a number of days");
+        std::process::exit(1);
+    };
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    Some(now.saturating_sub(days * 86_400))
+}
+
+pub(crate) fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(5 * 1024 * 1024), "5.0 MiB");
+    }
+}
@@ -1,5 +1,8 @@
 use crate::authorship::authorship_log::{LineRange, PromptRecord};
+use crate::authorship::authorship_log_serialization::AuthorshipLog;
+use crate::authorship::staged::{EMPTY_TREE_HASH, staged_authorship, working_tree_authorship};
 use crate::commands::blame::GitAiBlameOptions;
+use crate::commands::status::checkpoint_human_edits;
 use crate::error::GitAiError;
 use crate::git::repository::{Repository, exec_git};
 use serde::{Deserialize, Serialize, Serializer};
@@ -14,6 +17,8 @@ use std::io::IsTerminal;
 pub enum DiffSpec {
     SingleCommit(String),      // SHA
     TwoCommit(String, String), // start..end
+    Staged,                    // HEAD..index, attributed from checkpoints
+    WorkingTree,               // HEAD..working tree, attributed from checkpoints
 }
 
 pub enum DiffFormat {
@@ -83,12 +88,18 @@ pub fn handle_diff(repo: &Repository, args: &[String]) -> Result<(), GitAiError>
         eprintln!("Error: diff requires a commit or commit range argument");
         eprintln!("Usage: git-ai diff <commit>");
         eprintln!("       git-ai diff <commit1>..<commit2>");
+        eprintln!("       git-ai diff --staged");
+        eprintln!("       git-ai diff --working");
         std::process::exit(1);
     }
 
     let (spec, format) = parse_diff_args(args)?;
     let output = execute_diff(repo, spec, format)?;
     print!("{}", output);
+    // JSON output is a single line without a terminator
+    if !output.is_empty() && !output.ends_with('\n') {
+        println!();
+    }
 
     Ok(())
 }
@@ -98,14 +109,29 @@ pub fn handle_diff(repo: &Repository, args: &[String]) -> Result<(), GitAiError>
 // ============================================================================
 
 pub fn parse_diff_args(args: &[String]) -> Result<(DiffSpec, DiffFormat), GitAiError> {
-    let arg = &args[0];
-
     let format = if args.iter().any(|arg| arg == "--json") {
         DiffFormat::Json
     } else {
         DiffFormat::GitCompatibleTerminal
     };
 
+    // Uncommitted changes
+    if args
+        .iter()
+        .any(|arg| arg == "--staged" || arg == "--cached")
+    {
+        return Ok((DiffSpec::Staged, format));
+    }
+    if args.iter().any(|arg| arg == "--working") {
+        return Ok((DiffSpec::WorkingTree, format));
+    }
+
+    let Some(arg) = args.iter().find(|arg| !arg.starts_with("--")) else {
+        return Err(GitAiError::Generic(
+            "diff requires a commit, a commit range, --staged or --working".to_string(),
+        ));
+    };
+
     // Check for commit range (start..end)
     if arg.contains("..") {
         let parts: Vec<&str> = arg.split("..").collect();
@@ -148,6 +174,8 @@ pub fn execute_diff(
             let from = resolve_parent(repo, &to)?;
             (from, to)
         }
+        DiffSpec::Staged => return execute_uncommitted_diff(repo, true, format),
+        DiffSpec::WorkingTree => return execute_uncommitted_diff(repo, false, format),
     };
 
     // Step 1: Get diff hunks with line numbers
@@ -159,7 +187,8 @@ pub fn execute_diff(
     // Step 3: Format and output annotated diff
     let output = match format {
         DiffFormat::Json => {
-            let diff_json = build_diff_json(repo, &from_commit, &to_commit, &hunks, &attributions)?;
+            let diff_json =
+                build_diff_json(repo, &from_commit, &to_commit, &hunks, &attributions, None)?;
             serde_json::to_string(&diff_json)
                 .map_err(|e| GitAiError::Generic(format!("Failed to serialize JSON: {}", e)))?
         }
@@ -171,6 +200,53 @@ pub fn execute_diff(
     Ok(output)
 }
 
+/// Diff HEAD against the index (`staged`) or the working tree. There are no notes for
+/// uncommitted work yet, so lines are attributed from the working log's checkpoints instead.
+fn execute_uncommitted_diff(
+    repo: &Repository,
+    staged: bool,
+    format: DiffFormat,
+) -> Result<String, GitAiError> {
+    let human = checkpoint_human_edits(repo);
+    let uncommitted = if staged {
+        staged_authorship(repo)?
+    } else {
+        working_tree_authorship(repo)?
+    };
+
+    // The snapshot is a real commit object, so the committed-diff machinery applies unchanged
+    let from_commit = if uncommitted.parent_sha == "initial" {
+        EMPTY_TREE_HASH.to_string()
+    } else {
+        uncommitted.parent_sha.clone()
+    };
+    let to_commit = &uncommitted.snapshot_sha;
+    let log = &uncommitted.authorship_log;
+
+    let hunks = get_diff_with_line_numbers(repo, &from_commit, to_commit)?;
+    let attributions = attributions_from_authorship_log(log, &hunks, &human);
+
+    let output = match format {
+        DiffFormat::Json => {
+            let diff_json = build_diff_json(
+                repo,
+                &from_commit,
+                to_commit,
+                &hunks,
+                &attributions,
+                Some(log),
+            )?;
+            serde_json::to_string(&diff_json)
+                .map_err(|e| GitAiError::Generic(format!("Failed to serialize JSON: {}", e)))?
+        }
+        DiffFormat::GitCompatibleTerminal => {
+            format_annotated_diff(repo, &from_commit, to_commit, &attributions)?
+        }
+    };
+
+    Ok(output)
+}
+
 // ============================================================================
 // Commit Resolution
 // ============================================================================
@@ -444,6 +520,48 @@ pub fn overlay_diff_attributions(
     Ok(attributions)
 }
 
+/// Attribute added lines from an authorship log; lines no prompt attests are the human's
+fn attributions_from_authorship_log(
+    log: &AuthorshipLog,
+    hunks: &[DiffHunk],
+    human: &str,
+) -> HashMap<DiffLineKey, Attribution> {
+    let mut attributions = HashMap::new();
+
+    for hunk in hunks {
+        let attestation = log
+            .attestations
+            .iter()
+            .find(|a| a.file_path == hunk.file_path);
+        for &line in &hunk.added_lines {
+            let prompt_hash = attestation.and_then(|a| {
+                a.entries
+                    .iter()
+                    .find(|entry| entry.line_ranges.iter().any(|range| range.contains(line)))
+                    .map(|entry| &entry.hash)
+            });
+            let attribution = match prompt_hash {
+                Some(hash) => Attribution::Ai(
+                    log.metadata
+                        .prompts
+                        .get(hash)
+                        .map_or("unknown", |p| &p.agent_id.tool)
+                        .to_string(),
+                ),
+                None => Attribution::Human(human.to_string()),
+            };
+            let key = DiffLineKey {
+                file: hunk.file_path.clone(),
+                line,
+                side: LineSide::New,
+            };
+            attributions.insert(key, attribution);
+        }
+    }
+
+    attributions
+}
+
 /// Convert a sorted list of line numbers to contiguous ranges
 /// e.g., [1, 2, 3, 5, 6, 10] -> [(1, 3), (5, 6), (10, 10)]
 fn lines_to_ranges(lines: &[u32]) -> Vec<(u32, u32)> {
@@ -477,13 +595,15 @@ fn lines_to_ranges(lines: &[u32]) -> Vec<(u32, u32)> {
 // JSON Output Building
 // ============================================================================
 
-/// Build the DiffJson structure for --json output
+/// Build the DiffJson structure for --json output. Annotations come from `authorship_log` when
+/// given (uncommitted changes), otherwise from blame.
 fn build_diff_json(
     repo: &Repository,
     from_commit: &str,
     to_commit: &str,
     hunks: &[DiffHunk],
     attributions: &HashMap<DiffLineKey, Attribution>,
+    authorship_log: Option<&AuthorshipLog>,
 ) -> Result<DiffJson, GitAiError> {
     let mut files: BTreeMap<String, FileDiffJson> = BTreeMap::new();
     let mut all_prompts: BTreeMap<String, PromptRecord> = BTreeMap::new();
@@ -499,8 +619,10 @@ fn build_diff_json(
     // For each file, collect annotations, diff, and base content
     for file_path in &unique_files {
         // Get annotations for this file (lines attributed to AI prompts)
-        let file_annotations =
-            collect_file_annotations(repo, from_commit, to_commit, file_path, hunks)?;
+        let file_annotations = match authorship_log {
+            Some(log) => collect_file_annotations_from_log(log, file_path, hunks),
+            None => collect_file_annotations(repo, from_commit, to_commit, file_path, hunks)?,
+        };
 
         // Merge prompt records into the global map
         for (hash, prompt_record) in &file_annotations.1 {
@@ -678,6 +800,47 @@ fn collect_file_annotations(
     Ok((annotations, prompt_records))
 }
 
+/// Like [`collect_file_annotations`], but reading an authorship log instead of running blame
+fn collect_file_annotations_from_log(
+    log: &AuthorshipLog,
+    file_path: &str,
+    hunks: &[DiffHunk],
+) -> (
+    BTreeMap<String, Vec<LineRange>>,
+    HashMap<String, PromptRecord>,
+) {
+    let mut annotations: BTreeMap<String, Vec<LineRange>> = BTreeMap::new();
+    let mut prompt_records: HashMap<String, PromptRecord> = HashMap::new();
+
+    let added_lines: Vec<u32> = hunks
+        .iter()
+        .filter(|hunk| hunk.file_path == file_path)
+        .flat_map(|hunk| hunk.added_lines.iter().copied())
+        .collect();
+    let Some(attestation) = log.attestations.iter().find(|a| a.file_path == file_path) else {
+        return (annotations, prompt_records);
+    };
+
+    for entry in &attestation.entries {
+        let mut lines: Vec<u32> = added_lines
+            .iter()
+            .copied()
+            .filter(|&line| entry.line_ranges.iter().any(|range| range.contains(line)))
+            .collect();
+        if lines.is_empty() {
+            continue;
+        }
+        lines.sort_unstable();
+        lines.dedup();
+        annotations.insert(entry.hash.clone(), LineRange::compress_lines(&lines));
+        if let Some(prompt) = log.metadata.prompts.get(&entry.hash) {
+            prompt_records.insert(entry.hash.clone(), prompt.clone());
+        }
+    }
+
+    (annotations, prompt_records)
+}
+
 // ============================================================================
 // Output Formatting
 // ============================================================================
@@ -962,7 +1125,8 @@ pub fn get_diff_json_filtered(
     let attributions = overlay_diff_attributions(repo, &from_commit, &to_commit, &hunks)?;
 
     // Build the full DiffJson structure
-    let mut diff_json = build_diff_json(repo, &from_commit, &to_commit, &hunks, &attributions)?;
+    let mut diff_json =
+        build_diff_json(repo, &from_commit, &to_commit, &hunks, &attributions, None)?;
 
     // Apply filtering if requested
     if options.filter_to_attributed_files {
@@ -1038,6 +1202,25 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_parse_diff_args_uncommitted() {
+        let args = vec!["--staged".to_string()];
+        let (spec, _) = parse_diff_args(&args).unwrap();
+        assert!(matches!(spec, DiffSpec::Staged));
+
+        let args = vec!["--json".to_string(), "--cached".to_string()];
+        let (spec, format) = parse_diff_args(&args).unwrap();
+        assert!(matches!(spec, DiffSpec::Staged));
+        assert!(matches!(format, DiffFormat::Json));
+
+        let args = vec!["--working".to_string()];
+        let (spec, _) = parse_diff_args(&args).unwrap();
+        assert!(matches!(spec, DiffSpec::WorkingTree));
+
+        let args = vec!["--json".to_string()];
+        assert!(parse_diff_args(&args).is_err());
+    }
+
     #[test]
     fn test_parse_hunk_line_basic() {
         let line = "@@ -10,3 +15,5 @@ fn main() {";
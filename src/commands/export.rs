@@ -0,0 +1,143 @@
+use crate::export::{ExportOptions, build_export};
+use crate::git::find_repository;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExportFormat {
+    Csv,
+    Parquet,
+}
+
+impl ExportFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Parquet => "parquet",
+        }
+    }
+}
+
+pub fn handle_export(args: &[String]) {
+    let mut options = ExportOptions {
+        range: "HEAD".to_string(),
+        since: None,
+        until: None,
+        include_metrics: true,
+    };
+    let mut format = ExportFormat::Csv;
+    let mut output = PathBuf::from("git-ai-export");
+
+    let mut i = 0;
+    while i < args.len() {
+        let value = args.get(i + 1);
+        match args[i].as_str() {
+            "--since" | "--until" | "--format" | "--output" | "-o" if value.is_none() => {
+                eprintln!("{} requires a value", args[i]);
+                std::process::exit(1);
+            }
+            "--since" => {
+                options.since = value.cloned();
+                i += 2;
+            }
+            "--until" => {
+                options.until = value.cloned();
+                i += 2;
+            }
+            "--format" => {
+                format = match value.map(String::as_str) {
+                    Some("csv") => ExportFormat::Csv,
+                    Some("parquet") => ExportFormat::Parquet,
+                    _ => {
+                        eprintln!("--format must be one of: csv, parquet");
+                        std::process::exit(1);
+                    }
+                };
+                i += 2;
+            }
+            "--output" | "-o" => {
+                output = PathBuf::from(value.unwrap());
+                i += 2;
+            }
+            "--no-metrics" => {
+                options.include_metrics = false;
+                i += 1;
+            }
+            "--schema" => {
+                print_schema();
+                return;
+            }
+            "--help" | "-h" => print_export_help_and_exit(),
+            arg if !arg.starts_with('-') => {
+                options.range = arg.to_string();
+                i += 1;
+            }
+            other => {
+                eprintln!("Unknown export argument: {}", other);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let tables = match build_export(&repo, &options) {
+        Ok(tables) => tables,
+        Err(e) => {
+            eprintln!("Export failed: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if let Err(e) = std::fs::create_dir_all(&output) {
+        eprintln!("Failed to create {}: {}", output.display(), e);
+        std::process::exit(1);
+    }
+
+    for table in &tables {
+        let path = output.join(format!("{}.{}", table.name, format.extension()));
+        let bytes = match format {
+            ExportFormat::Csv => crate::export::csv::write_table(table).into_bytes(),
+            ExportFormat::Parquet => crate::export::parquet::write_table(table),
+        };
+        if let Err(e) = std::fs::write(&path, bytes) {
+            eprintln!("Failed to write {}: {}", path.display(), e);
+            std::process::exit(1);
+        }
+        println!("{:>8} rows  {}", table.rows.len(), path.display());
+    }
+}
+
+fn print_schema() {
+    for (table, columns) in crate::export::TABLES {
+        println!("{}", table);
+        for column in *columns {
+            println!(
+                "  {:<20} {:<10} {}",
+                column.name,
+                column.kind.as_str(),
+                column.description
+            );
+        }
+    }
+}
+
+fn print_export_help_and_exit() -> ! {
+    eprintln!("Usage: git-ai export [<rev|range>] [options]");
+    eprintln!();
+    eprintln!("Write authorship notes and metric events for the commits reachable from <rev>");
+    eprintln!("(default: HEAD) or in <range> as one file per table. See docs/export.md.");
+    eprintln!();
+    eprintln!("  --format <fmt>        csv (default) or parquet");
+    eprintln!("  -o, --output <dir>    Directory to write into (default: git-ai-export)");
+    eprintln!("  --since <date>        Only commits after this date (any format git log accepts)");
+    eprintln!("  --until <date>        Only commits before this date");
+    eprintln!("  --no-metrics          Skip the metric_events table");
+    eprintln!("  --schema              Print every table's columns and exit");
+    std::process::exit(0);
+}
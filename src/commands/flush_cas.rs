@@ -5,10 +5,8 @@ use std::collections::HashMap;
 
 /// Spawn a background process to flush CAS objects to the server
 pub fn spawn_background_cas_flush() {
-    use std::process::Command;
-
-    if let Ok(exe) = crate::utils::current_git_ai_exe() {
-        let _ = Command::new(exe)
+    if let Ok(mut cmd) = crate::utils::background_git_ai_command() {
+        let _ = cmd
             .arg("flush-cas")
             .stdout(std::process::Stdio::null())
             .stderr(std::process::Stdio::null())
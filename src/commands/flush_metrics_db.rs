@@ -11,10 +11,8 @@ const MAX_BATCH_SIZE: usize = 250;
 
 /// Spawn a background process to flush metrics DB
 pub fn spawn_background_metrics_db_flush() {
-    use std::process::Command;
-
-    if let Ok(exe) = crate::utils::current_git_ai_exe() {
-        let _ = Command::new(exe)
+    if let Ok(mut cmd) = crate::utils::background_git_ai_command() {
+        let _ = cmd
             .arg("flush-metrics-db")
             .stdout(std::process::Stdio::null())
             .stderr(std::process::Stdio::null())
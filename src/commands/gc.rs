@@ -0,0 +1,187 @@
+use crate::commands::db::format_bytes;
+use crate::git::checkpoint_gc::{RetentionPolicy, collect_garbage};
+use crate::git::find_repository;
+use crate::git::notes_gc::{ARCHIVE_REFNAME, NotesGcPolicy, prune_stale_notes};
+use crate::git::repository::Repository;
+use std::time::Duration;
+
+pub fn handle_gc(args: &[String]) {
+    let mut policy = RetentionPolicy::from_config();
+    let mut notes_policy = NotesGcPolicy::from_config();
+    let mut dry_run = false;
+    let mut notes = false;
+    // Flags that only apply to one mode, to reject mixing them
+    let mut log_flag: Option<&str> = None;
+    let mut notes_flag: Option<&str> = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--dry-run" | "-n" => {
+                dry_run = true;
+                i += 1;
+            }
+            "--max-age" => {
+                let days = parse_limit(args.get(i + 1), "--max-age", "a number of days");
+                policy.max_age = days.map(|days| Duration::from_secs(days * 24 * 60 * 60));
+                log_flag = Some("--max-age");
+                i += 2;
+            }
+            "--max-count" => {
+                let count = parse_limit(args.get(i + 1), "--max-count", "a number of logs");
+                policy.max_count = count.map(|count| count as usize);
+                log_flag = Some("--max-count");
+                i += 2;
+            }
+            "--prune-uncommitted" => {
+                policy.keep_until_committed = false;
+                log_flag = Some("--prune-uncommitted");
+                i += 1;
+            }
+            "--notes" => {
+                notes = true;
+                i += 1;
+            }
+            "--archive" => {
+                notes_policy.archive = true;
+                notes_flag = Some("--archive");
+                i += 1;
+            }
+            "--grace" => {
+                let days = parse_limit(args.get(i + 1), "--grace", "a number of days");
+                notes_policy.grace = days.map(|days| Duration::from_secs(days * 24 * 60 * 60));
+                notes_flag = Some("--grace");
+                i += 2;
+            }
+            "--help" | "-h" => print_gc_help_and_exit(),
+            arg => {
+                eprintln!("Unknown gc argument: {}", arg);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if let Some(flag) = log_flag.filter(|_| notes) {
+        eprintln!(
+            "{} applies to working logs and can't be combined with --notes",
+            flag
+        );
+        std::process::exit(1);
+    }
+    if let Some(flag) = notes_flag.filter(|_| !notes) {
+        eprintln!("{} requires --notes", flag);
+        std::process::exit(1);
+    }
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if notes {
+        gc_notes(&repo, &notes_policy, dry_run);
+        return;
+    }
+
+    let report = match collect_garbage(&repo, &policy, dry_run) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("gc failed: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let verb = if dry_run { "Would remove" } else { "Removed" };
+    for (log, reason) in &report.removed {
+        println!(
+            "{} working log {} ({}, {} checkpoint{}, {})",
+            verb,
+            &log.base[..log.base.len().min(12)],
+            reason.as_str(),
+            log.checkpoints,
+            if log.checkpoints == 1 { "" } else { "s" },
+            format_bytes(log.bytes)
+        );
+    }
+    let removed = report.removed.len();
+    println!(
+        "{} {} working log{} ({}), kept {}",
+        verb,
+        removed,
+        if removed == 1 { "" } else { "s" },
+        format_bytes(report.bytes_freed()),
+        report.kept
+    );
+}
+
+fn gc_notes(repo: &Repository, policy: &NotesGcPolicy, dry_run: bool) {
+    let report = match prune_stale_notes(repo, policy, dry_run) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("gc failed: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let verb = match (dry_run, policy.archive) {
+        (true, true) => "Would archive",
+        (true, false) => "Would prune",
+        (false, true) => "Archived",
+        (false, false) => "Pruned",
+    };
+    for commit in &report.pruned {
+        println!("{} note for {}", verb, &commit[..commit.len().min(12)]);
+    }
+    let pruned = report.pruned.len();
+    println!(
+        "{} {} note{} of unreachable commits, kept {}",
+        verb,
+        pruned,
+        if pruned == 1 { "" } else { "s" },
+        report.kept
+    );
+    if policy.archive && !dry_run && pruned > 0 {
+        println!("Archived notes are in refs/notes/{}", ARCHIVE_REFNAME);
+    }
+}
+
+/// A non-negative limit, where 0 means no limit
+fn parse_limit(value: Option<&String>, flag: &str, expected: &str) -> Option<u64> {
+    match value.map(|v| v.parse::<u64>()) {
+        Some(Ok(0)) => None,
+        Some(Ok(limit)) => Some(limit),
+        _ => {
+            eprintln!("{} requires {}", flag, expected);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn print_gc_help_and_exit() -> ! {
+    eprintln!(
+        "Usage: git-ai gc [--dry-run] [--max-age <days>] [--max-count <n>] [--prune-uncommitted]"
+    );
+    eprintln!("       git-ai gc --notes [--dry-run] [--archive] [--grace <days>]");
+    eprintln!();
+    eprintln!("Remove checkpoint working logs that can no longer be committed. The log for HEAD");
+    eprintln!("is always kept. Defaults come from checkpoint_max_age_days (30),");
+    eprintln!("checkpoint_max_count (20) and checkpoint_keep_until_committed (true).");
+    eprintln!();
+    eprintln!("  -n, --dry-run         List what would be removed without removing it");
+    eprintln!("  --max-age <days>      Remove logs untouched for this many days (0 = no limit)");
+    eprintln!("  --max-count <n>       Keep at most n logs besides protected ones (0 = no limit)");
+    eprintln!("  --prune-uncommitted   Also prune logs based on local branch tips");
+    eprintln!();
+    eprintln!("With --notes, remove authorship notes of commits no ref reaches instead. Commits a");
+    eprintln!("reflog entry reaches keep their notes for notes_gc_grace_days (30).");
+    eprintln!();
+    eprintln!(
+        "  --archive             Move the notes to refs/notes/{} instead",
+        ARCHIVE_REFNAME
+    );
+    eprintln!("  --grace <days>        Override notes_gc_grace_days (0 = ignore reflogs)");
+    std::process::exit(0);
+}
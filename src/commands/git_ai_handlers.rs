@@ -6,9 +6,11 @@ use crate::commands;
 use crate::commands::checkpoint_agent::agent_presets::{
     AgentCheckpointFlags, AgentCheckpointPreset, AgentRunResult, AiTabPreset, ClaudePreset,
     ContinueCliPreset, CursorPreset, DroidPreset, GeminiPreset, GithubCopilotPreset,
+    MAX_HOOK_INPUT_BYTES,
 };
 use crate::commands::checkpoint_agent::agent_v1_preset::AgentV1Preset;
 use crate::commands::checkpoint_agent::opencode_preset::OpenCodePreset;
+use crate::commands::machine_output::{self, OutputFormat};
 use crate::config;
 use crate::git::find_repository;
 use crate::git::find_repository_in_path;
@@ -69,6 +71,9 @@ pub fn handle_git_ai(args: &[String]) {
         "status" => {
             commands::status::handle_status(&args[1..]);
         }
+        "top" => {
+            commands::top::handle_top(&args[1..]);
+        }
         "show" => {
             commands::show::handle_show(&args[1..]);
         }
@@ -98,6 +103,27 @@ pub fn handle_git_ai(args: &[String]) {
             println!("{}", config.git_cmd());
             std::process::exit(0);
         }
+        "init" => {
+            commands::init::handle_init(&args[1..]);
+        }
+        "export" => {
+            commands::export::handle_export(&args[1..]);
+        }
+        "backfill" => {
+            commands::backfill::handle_backfill(&args[1..]);
+        }
+        "gc" => {
+            commands::gc::handle_gc(&args[1..]);
+        }
+        "migrate-notes" => {
+            commands::migrate_notes::handle_migrate_notes(&args[1..]);
+        }
+        "ownership" => {
+            commands::ownership::handle_ownership(&args[1..]);
+        }
+        "compare" => {
+            commands::compare::handle_compare(&args[1..]);
+        }
         "install-hooks" | "install" => match commands::install_hooks::run(&args[1..]) {
             Ok(statuses) => {
                 if let Ok(statuses_value) = serde_json::to_value(&statuses) {
@@ -120,6 +146,63 @@ pub fn handle_git_ai(args: &[String]) {
                 std::process::exit(1);
             }
         },
+        "uninstall" => {
+            commands::uninstall::handle_uninstall(&args[1..]);
+        }
+        "bench" => {
+            commands::bench::handle_bench(&args[1..]);
+        }
+        "branches" => {
+            commands::branches::handle_branches(&args[1..]);
+        }
+        "bundle" => {
+            commands::bundle::handle_bundle(&args[1..]);
+        }
+        "annotate-pr" => {
+            commands::annotate_pr::handle_annotate_pr(&args[1..]);
+        }
+        "annotate-export" => {
+            commands::annotate_export::handle_annotate_export(&args[1..]);
+        }
+        "annotate-strip" => {
+            commands::annotate_export::handle_annotate_strip(&args[1..]);
+        }
+        "server-hook" => {
+            commands::server_hook::handle_server_hook(&args[1..]);
+        }
+        "prometheus" => {
+            commands::prometheus::handle_prometheus(&args[1..]);
+        }
+        "github-app" => {
+            commands::github_app::handle_github_app(&args[1..]);
+        }
+        "prepare-commit-msg" => {
+            commands::prepare_commit_msg::handle_prepare_commit_msg(&args[1..]);
+        }
+        "post-rewrite" => {
+            commands::post_rewrite::handle_post_rewrite(&args[1..]);
+        }
+        "daemon" => {
+            commands::daemon::handle_daemon(&args[1..]);
+        }
+        "mcp" => {
+            commands::mcp::handle_mcp(&args[1..]);
+        }
+        "simulate" => {
+            commands::simulate::handle_simulate(&args[1..]);
+        }
+        "db" => {
+            commands::db::handle_db(&args[1..]);
+        }
+        "verify" => {
+            commands::verify::handle_verify(&args[1..]);
+        }
+        "policy" => {
+            commands::policy::handle_policy(&args[1..]);
+        }
+        "workspace" => {
+            commands::workspace::handle_workspace(&args[1..]);
+        }
         "squash-authorship" => {
             commands::squash_authorship::handle_squash_authorship(&args[1..]);
         }
@@ -129,6 +212,9 @@ pub fn handle_git_ai(args: &[String]) {
         "upgrade" => {
             commands::upgrade::run_with_args(&args[1..]);
         }
+        "self-update" => {
+            commands::self_update::handle_self_update(&args[1..]);
+        }
         "flush-logs" => {
             commands::flush_logs::handle_flush_logs(&args[1..]);
         }
@@ -153,9 +239,18 @@ pub fn handle_git_ai(args: &[String]) {
         "show-prompt" => {
             commands::show_prompt::handle_show_prompt(&args[1..]);
         }
+        "trace" => {
+            commands::trace::handle_trace(&args[1..]);
+        }
         "share" => {
             commands::share::handle_share(&args[1..]);
         }
+        "sync" => {
+            commands::sync::handle_sync(&args[1..]);
+        }
+        "notes-merge-driver" => {
+            commands::notes_merge_driver::handle_notes_merge_driver(&args[1..]);
+        }
         "sync-prompts" => {
             commands::sync_prompts::handle_sync_prompts(&args[1..]);
         }
@@ -181,6 +276,9 @@ fn print_help() {
     eprintln!("Commands:");
     eprintln!("  checkpoint         Checkpoint working changes and attribute author");
     eprintln!("    Presets: claude, continue-cli, cursor, gemini, github-copilot, ai_tab, mock_ai");
+    eprintln!(
+        "    auto [paths...]             Detect the running agent (see agent_detectors config)"
+    );
     eprintln!(
         "    --hook-input <json|stdin>   JSON payload required by presets, or 'stdin' to read from stdin"
     );
@@ -188,21 +286,87 @@ fn print_help() {
     eprintln!("    --reset                     Reset working log");
     eprintln!("    mock_ai [pathspecs...]      Test preset accepting optional file pathspecs");
     eprintln!("  blame <file>       Git blame with AI authorship overlay");
+    eprintln!("    --json                Output AI line ranges and their prompts as JSON");
+    eprintln!("    --format <fmt>        text (default), json, or porcelain (git blame's)");
+    eprintln!("    --show-model          Show tool/model for AI lines instead of just the tool");
+    eprintln!("    --color[=<when>]      Highlight AI lines (always/never/auto, default: auto)");
     eprintln!("  diff <commit|range>  Show diff with AI authorship annotations");
     eprintln!("    <commit>              Diff from commit's parent to commit");
     eprintln!("    <commit1>..<commit2>  Diff between two commits");
+    eprintln!("    --staged              Diff HEAD to the index, attributed from checkpoints");
+    eprintln!("    --working             Diff HEAD to the working tree, including untracked files");
+    eprintln!("    --json                Output diffs, line annotations and prompts as JSON");
     eprintln!("  stats [commit]     Show AI authorship statistics for a commit");
     eprintln!("    --json                 Output in JSON format");
+    eprintln!("    --format <fmt>         text (default), json or porcelain");
+    eprintln!("    --include-bots         Count commits by bot_authors identities in range stats");
+    eprintln!("  stats report [rev]   Summarize AI vs human lines across history");
+    eprintln!("    --since/--until <date> Limit commits by date");
+    eprintln!("    --by <key>             Group by author, tool, model, directory, week or month");
+    eprintln!("    --format <fmt>         Output as table (default), json or csv");
+    eprintln!("    --workspace            Across every repository in the workspace");
+    eprintln!("  stats --reverts [rev] AI lines rolled back by git revert, per tool");
+    eprintln!("    --since/--until <date> Limit commits by date");
+    eprintln!("  ownership [rev]    AI vs human lines by directory and CODEOWNERS owner");
+    eprintln!("    --depth <n>            Directory levels per area (default: 2)");
+    eprintln!("    --files                One area per file");
+    eprintln!("    --codeowners <file>    Use this CODEOWNERS file instead of the repository's");
+    eprintln!("    --format <fmt>         text (default), json or porcelain");
+    eprintln!("  compare <A>..<B>   Compare AI share, agents and directories between two revisions");
+    eprintln!("    <before> <after>       Compare two revisions or ranges instead");
+    eprintln!("    --format <fmt>         text (default), json or porcelain");
+    eprintln!("  export [rev|range] Write authorship notes and metric events as CSV or Parquet");
+    eprintln!("    --format <fmt>         csv (default) or parquet");
+    eprintln!("    -o, --output <dir>     Directory to write into (default: git-ai-export)");
+    eprintln!("  backfill [rev|range] Write best-effort notes for commits made before git-ai");
+    eprintln!("    --mapping <file>       Imported commit,tool[,model] attributions (CSV or JSON)");
+    eprintln!("    --ai-only              Skip commits with no sign of AI");
+    eprintln!("    --dry-run              Show what would be written");
+    eprintln!("  gc                 Prune checkpoint working logs that can no longer be committed");
+    eprintln!("    --dry-run              List what would be removed");
+    eprintln!("    --max-age <days>       Override checkpoint_max_age_days (0 = no limit)");
+    eprintln!("    --max-count <n>        Override checkpoint_max_count (0 = no limit)");
+    eprintln!("    --prune-uncommitted    Also prune logs based on local branch tips");
+    eprintln!("    --notes                Prune notes of unreachable commits instead");
+    eprintln!("    --archive              With --notes, move them to refs/notes/ai-archive");
+    eprintln!("    --grace <days>         Override notes_gc_grace_days (0 = ignore reflogs)");
+    eprintln!("  mcp                Run an MCP server agents can report their edits to");
+    eprintln!("    --agent <name>         Agent name for declared edits (default: the client's name)");
+    eprintln!("    --model <model>        Model for declared edits");
+    eprintln!("    --repo <dir>           Default repository (default: current directory)");
+    eprintln!("  simulate <commit|push> [git args...]  Show what the hooks would do, changing nothing");
+    eprintln!("    --format <fmt>         text (default), json or porcelain");
+    eprintln!("  migrate-notes      Rewrite authorship notes with an older schema to the current one");
+    eprintln!("    --dry-run              List the notes that would be rewritten");
+    eprintln!("    --format <fmt>         text (default), json or porcelain");
     eprintln!("  status             Show uncommitted AI authorship status (debug)");
     eprintln!("    --json                 Output in JSON format");
+    eprintln!("    --format <fmt>         text (default), json or porcelain");
+    eprintln!("    --staged               Staged lines by AI vs human, per file and agent");
+    eprintln!("    --json --watch         Stream the staged makeup as JSON lines as it changes");
+    eprintln!("  top                Live dashboard of uncommitted AI lines, agents and notes sync");
+    eprintln!("    --interval <ms>        Refresh interval (default: 2000)");
+    eprintln!("    --json                 Print one snapshot as JSON and exit");
     eprintln!("  show <rev|range>   Display authorship logs for a revision or range");
     eprintln!("  show-prompt <id>   Display a prompt record by its ID");
     eprintln!("    --commit <rev>        Look in a specific commit only");
     eprintln!(
         "    --offset <n>          Skip n occurrences (0 = most recent, mutually exclusive with --commit)"
     );
+    eprintln!("  trace [<commit>] <file>:<line>  Show the commit, agent session and prompt behind a line");
+    eprintln!("    --format <fmt>        text (default), json or porcelain");
     eprintln!("  share <id>         Share a prompt by creating a bundle");
     eprintln!("    --title <title>       Custom title for the bundle (default: auto-generated)");
+    eprintln!("  sync [remote...]   Fetch, merge and push authorship notes across remotes");
+    eprintln!("    --all-remotes         Every configured remote, not just git-ai.syncRemotes");
+    eprintln!("    --fetch-only          Fetch and merge notes without pushing");
+    eprintln!("    --retry               Retry queued notes pushes that failed earlier");
+    eprintln!("    --workspace           Every repository in the workspace, with per-repo health");
+    eprintln!("    --format <fmt>        text (default), json or porcelain");
+    eprintln!(
+        "  notes-merge-driver [ref] Merge a notes ref into refs/notes/ai by unioning attributions"
+    );
+    eprintln!("                          Without a ref, resolves a conflicted `git notes merge`");
     eprintln!("  sync-prompts       Update prompts in database to latest versions");
     eprintln!("    --since <time>        Only sync prompts updated after this time");
     eprintln!(
@@ -215,10 +379,80 @@ fn print_help() {
     eprintln!("    set <key> <value>     Set a config value (arrays: single value = [value])");
     eprintln!("    --add <key> <value>   Add to array or upsert into object");
     eprintln!("    unset <key>           Remove config value (reverts to default)");
+    eprintln!("    get <key> --show-origin  Show a value and the layer it came from");
+    eprintln!("    list [--show-origin]  List values from every layer (system, user, repo, env)");
+    eprintln!("    validate              Check every layer against the schema");
+    eprintln!("    --repo | --system     With set/unset: edit .git/git-ai.toml or the system file");
+    eprintln!("  init               Guided setup of agent/repo hooks, notes and metrics export");
+    eprintln!("    --yes                 Don't prompt; use flags or defaults (see init --help)");
     eprintln!("  install-hooks      Install git hooks for AI authorship tracking");
     eprintln!("  uninstall-hooks    Remove git-ai hooks from all detected tools");
+    eprintln!("  uninstall          Remove git-ai hooks and local state, restoring chained hooks");
+    eprintln!("    --notes <action>      keep (default), archive or delete refs/notes/ai");
+    eprintln!("    --remote <name>       Also archive/delete the notes on <name> (repeatable)");
+    eprintln!("    --keep-metrics        Don't clear cached metric events");
+    eprintln!("    --keep-agent-hooks    Don't remove agent/editor hooks");
+    eprintln!("    --dry-run             Only print what would be removed");
     eprintln!("  ci                 Continuous integration utilities");
     eprintln!("    github                 GitHub CI helpers");
+    eprintln!("  bench              Time git-ai hot paths against the current repository");
+    eprintln!("    --iterations <n>      Iterations per benchmark (default: 20)");
+    eprintln!("    --max-ms <ms>         Exit non-zero if any p95 exceeds this budget");
+    eprintln!("    --json                Output in JSON format");
+    eprintln!("  branches --stale   List branches whose AI-noted commits are not merged or pushed");
+    eprintln!(
+        "    --archive             Preserve those notes under refs/ai-archive/ before deletion"
+    );
+    eprintln!("    --json                Output in JSON format");
+    eprintln!("  bundle             Carry authorship notes in git bundles and format-patch files");
+    eprintln!("    create <file> <rev>...   Write a bundle with the commits and their notes");
+    eprintln!("    create --patches <dir> <rev>...  Write patches with each commit's note");
+    eprintln!("    apply <bundle|patch...>  Import a bundle's notes, or git am the patches");
+    eprintln!("  annotate-pr <n>    Comment on a GitHub PR's AI-authored hunks (needs GITHUB_TOKEN)");
+    eprintln!("    --repo <owner/name>   Repository (default: GITHUB_REPOSITORY, then the remote)");
+    eprintln!("    --comments            One review comment per hunk instead of a single review");
+    eprintln!("    --dry-run             Print the comments instead of posting them");
+    eprintln!("  annotate-export <file>...  Add `ai-generated:` comments above AI-authored lines");
+    eprintln!("    --rev <commit>        Annotate the files as of <commit> (needs --output)");
+    eprintln!("    --output <dir>        Write the annotated files under <dir> instead of in place");
+    eprintln!("  annotate-strip <file>...   Remove the comments annotate-export added");
+    eprintln!("  server-hook        Enforce authorship notes on a server-side repository");
+    eprintln!("    install [<repo>]      Install the receive hook into a (bare) repository");
+    eprintln!("    --mode <reject|tag>   Reject unnoted pushes, or mark them unverified");
+    eprintln!("  prometheus         Expose metrics to Prometheus (`prometheus` build feature)");
+    eprintln!("    serve                 Serve metrics on GET /metrics");
+    eprintln!("    path                  Print the node_exporter textfile path");
+    eprintln!("  github-app         Collect notes org-wide from GitHub App push webhooks");
+    eprintln!("    serve                 Receive webhooks (--listen <addr>)");
+    eprintln!("    ingest <payload|->    Ingest a saved push payload");
+    eprintln!("  post-rewrite       Keep authorship notes on commits rewritten by plain git");
+    eprintln!("                     and fetch notes after plain git checkouts, merges and pulls");
+    eprintln!("    install [--force]     Install the post-rewrite, post-commit, post-checkout and");
+    eprintln!("                          post-merge hooks in this repository");
+    eprintln!("    --hooks-path <dir>    Install into <dir> and point core.hooksPath at it");
+    eprintln!("    --run-existing <when> Chain existing hooks to run before (default) or after");
+    eprintln!("    --recurse-submodules  Also install into every checked-out submodule");
+    eprintln!("  prepare-commit-msg Append an AI-Assisted trailer with AI/total lines to commits");
+    eprintln!("    install [--force]     Install the prepare-commit-msg hook in this repository");
+    eprintln!("  verify             Check authorship notes for missing, corrupt or mismatched entries");
+    eprintln!("    [<commit>|<range>]    Commits to check (default: @{{upstream}}..HEAD, or HEAD)");
+    eprintln!(
+        "    --format <fmt>        text (default), json, porcelain, or sarif; exits 1 on failures"
+    );
+    eprintln!("  policy             Evaluate policy.toml outside the commit and push hooks");
+    eprintln!("    check [<commit>|<range>]  Commits to check (default: @{{upstream}}..HEAD, or HEAD)");
+    eprintln!("    --staged              Check staged changes against the commit rules instead");
+    eprintln!(
+        "    --format <fmt>        text (default), json, porcelain, or sarif; exits 1 when blocked"
+    );
+    eprintln!("  daemon             Accept editor checkpoints over a local JSON-RPC socket");
+    eprintln!("    run | start | stop | status | call <method> [json]");
+    eprintln!("  workspace          Manage the repositories --workspace operates on");
+    eprintln!("    add <path>... [--name <name>] | remove <name|path>... | list");
+    eprintln!("  db                 Maintain git-ai's local SQLite store of events and checkpoints");
+    eprintln!("    vacuum [--older-than <days>]  Prune old rows and compact the databases");
+    eprintln!("    info [--json]         Show the store's location, row counts and size");
+    eprintln!("    events | checkpoints  Dump stored metric events or this repo's checkpoints");
     eprintln!("  squash-authorship  Generate authorship log for squashed commits");
     eprintln!(
         "    <base_branch> <new_sha> <old_sha>  Required: base branch, new commit SHA, old commit SHA"
@@ -227,6 +461,11 @@ fn print_help() {
     eprintln!("  git-path           Print the path to the underlying git executable");
     eprintln!("  upgrade            Check for updates and install if available");
     eprintln!("    --force               Reinstall latest version even if already up to date");
+    eprintln!("  self-update        Verify and install the release channel's binary in place");
+    eprintln!("    --check               Only report whether an update is available");
+    eprintln!("    --force               Reinstall the channel's release even if already on it");
+    eprintln!("    --channel <name>      Release channel (default: update_channel)");
+    eprintln!("    --format <fmt>        text (default), json or porcelain");
     eprintln!("  prompts            Create local SQLite database for prompt analysis");
     eprintln!("    --since <time>        Only include prompts after this time (default: 30d)");
     eprintln!("    --author <name>       Filter by human author (default: current git user)");
@@ -270,12 +509,21 @@ fn handle_checkpoint(args: &[String]) {
                 if i + 1 < args.len() {
                     hook_input = Some(args[i + 1].clone());
                     if hook_input.as_ref().unwrap() == "stdin" {
-                        let mut stdin = std::io::stdin();
+                        // Read one byte past the limit so oversized payloads are detected
+                        // without buffering an unbounded stream
+                        let mut stdin = std::io::stdin().take(MAX_HOOK_INPUT_BYTES as u64 + 1);
                         let mut buffer = String::new();
                         if let Err(e) = stdin.read_to_string(&mut buffer) {
                             eprintln!("Failed to read stdin for hook input: {}", e);
                             std::process::exit(0);
                         }
+                        if buffer.len() > MAX_HOOK_INPUT_BYTES {
+                            eprintln!(
+                                "Hook input exceeds {} bytes; skipping checkpoint.",
+                                MAX_HOOK_INPUT_BYTES
+                            );
+                            std::process::exit(0);
+                        }
                         if !buffer.trim().is_empty() {
                             hook_input = Some(buffer);
                         } else {
@@ -484,6 +732,32 @@ fn handle_checkpoint(args: &[String]) {
                     dirty_files: None,
                 });
             }
+            "auto" => {
+                // Without a detected agent this falls through to a human checkpoint
+                if let Some(detected) = crate::agents::detect_current_agent() {
+                    let paths: Vec<String> = args[1..]
+                        .iter()
+                        .filter(|arg| !arg.starts_with("--"))
+                        .cloned()
+                        .collect();
+                    let edited_filepaths = if paths.is_empty() {
+                        Some(get_all_files_for_mock_ai(&repository_working_dir))
+                    } else {
+                        Some(paths)
+                    };
+
+                    agent_run_result = Some(AgentRunResult {
+                        agent_id: detected.to_agent_id(),
+                        agent_metadata: None,
+                        checkpoint_kind: CheckpointKind::AiAgent,
+                        transcript: None,
+                        repo_working_dir: None,
+                        edited_filepaths,
+                        will_edit_filepaths: None,
+                        dirty_files: None,
+                    });
+                }
+            }
             _ => {}
         }
     }
@@ -859,6 +1133,16 @@ fn handle_ai_diff(args: &[String]) {
 }
 
 fn handle_stats(args: &[String]) {
+    if args.first().map(String::as_str) == Some("report") {
+        commands::stats_report::handle_stats_report(&args[1..]);
+        return;
+    }
+    if args.iter().any(|arg| arg == "--reverts") {
+        let rest: Vec<String> = args.iter().filter(|arg| *arg != "--reverts").cloned().collect();
+        commands::stats_reverts::handle_stats_reverts(&rest);
+        return;
+    }
+
     // Find the git repository
     let repo = match find_repository(&Vec::<String>::new()) {
         Ok(repo) => repo,
@@ -868,16 +1152,29 @@ fn handle_stats(args: &[String]) {
         }
     };
     // Parse stats-specific arguments
-    let mut json_output = false;
+    let mut format = OutputFormat::Text;
     let mut commit_sha = None;
     let mut commit_range: Option<CommitRange> = None;
     let mut ignore_patterns: Vec<String> = Vec::new();
+    let mut include_bots = false;
 
     let mut i = 0;
     while i < args.len() {
         match args[i].as_str() {
             "--json" => {
-                json_output = true;
+                format = OutputFormat::Json;
+                i += 1;
+            }
+            "--porcelain" => {
+                format = OutputFormat::Porcelain;
+                i += 1;
+            }
+            "--format" => {
+                format = OutputFormat::from_flag_value(args, i);
+                i += 2;
+            }
+            "--include-bots" => {
+                include_bots = true;
                 i += 1;
             }
             "--ignore" => {
@@ -948,13 +1245,12 @@ fn handle_stats(args: &[String]) {
 
     // Handle commit range if detected
     if let Some(range) = commit_range {
-        match range_authorship::range_authorship(range, false, &ignore_patterns) {
+        match range_authorship::range_authorship(range, false, &ignore_patterns, include_bots) {
             Ok(stats) => {
-                if json_output {
-                    let json_str = serde_json::to_string(&stats).unwrap();
-                    println!("{}", json_str);
-                } else {
+                if format.is_text() {
                     range_authorship::print_range_authorship_stats(&stats);
+                } else {
+                    machine_output::print(format, &stats).unwrap();
                 }
             }
             Err(e) => {
@@ -965,7 +1261,7 @@ fn handle_stats(args: &[String]) {
         return;
     }
 
-    if let Err(e) = stats_command(&repo, commit_sha.as_deref(), json_output, &ignore_patterns) {
+    if let Err(e) = stats_command(&repo, commit_sha.as_deref(), format, &ignore_patterns) {
         match e {
             crate::error::GitAiError::Generic(msg) if msg.starts_with("No commit found:") => {
                 eprintln!("{}", msg);
@@ -1,13 +1,16 @@
 use crate::authorship::virtual_attribution::VirtualAttributions;
+use crate::commands::hooks::branch_hooks;
 use crate::commands::hooks::checkout_hooks;
 use crate::commands::hooks::cherry_pick_hooks;
 use crate::commands::hooks::clone_hooks;
 use crate::commands::hooks::commit_hooks;
 use crate::commands::hooks::fetch_hooks;
 use crate::commands::hooks::merge_hooks;
+use crate::commands::hooks::notes_hooks;
 use crate::commands::hooks::push_hooks;
 use crate::commands::hooks::rebase_hooks;
 use crate::commands::hooks::reset_hooks;
+use crate::commands::hooks::revert_hooks;
 use crate::commands::hooks::stash_hooks;
 use crate::commands::hooks::switch_hooks;
 use crate::config;
@@ -15,18 +18,25 @@ use crate::git::cli_parser::{ParsedGitInvocation, parse_git_cli_args};
 use crate::git::find_repository;
 use crate::git::repository::Repository;
 use crate::observability;
+use crate::observability::logging;
+use crate::observability::spans::HookSpan;
 
 use crate::observability::wrapper_performance_targets::log_performance_target_if_violated;
 use crate::utils::debug_log;
+use std::io::{Read, Write};
 #[cfg(unix)]
 use std::os::unix::process::CommandExt;
 #[cfg(unix)]
 use std::os::unix::process::ExitStatusExt;
-use std::process::Command;
+use std::process::{Command, Stdio};
 #[cfg(unix)]
 use std::sync::atomic::{AtomicI32, Ordering};
 use std::time::Instant;
 
+/// Set on every git process the wrapper spawns, so git-ai's own git hooks can tell the wrapper
+/// will handle authorship once the command exits
+pub const WRAPPED_GIT_ENV: &str = "GIT_AI_WRAPPED";
+
 #[cfg(unix)]
 static CHILD_PGID: AtomicI32 = AtomicI32::new(0);
 
@@ -84,6 +94,12 @@ pub struct CommandHooksContext {
     /// VirtualAttributions captured before a pull --rebase --autostash operation.
     /// Used to preserve uncommitted AI attributions that git's internal stash would lose.
     pub stashed_va: Option<VirtualAttributions>,
+    /// Set by a post-command hook that re-ran the command, e.g. a push retried without the
+    /// notes refspecs the remote rejected
+    pub exit_status_override: Option<std::process::ExitStatus>,
+    /// Set to an empty string by a pre-command hook that needs what git wrote to stderr, e.g. a
+    /// push's per-ref rejections; holds it once the command has run
+    pub git_stderr: Option<String>,
 }
 
 pub fn handle_git(args: &[String]) {
@@ -91,7 +107,7 @@ pub fn handle_git(args: &[String]) {
     // and delegate directly to the real git so existing completion scripts work.
     if in_shell_completion_context() {
         let orig_args: Vec<String> = std::env::args().skip(1).collect();
-        proxy_to_git(&orig_args, true);
+        proxy_to_git(&orig_args, true, None);
         return;
     }
 
@@ -113,7 +129,7 @@ pub fn handle_git(args: &[String]) {
 
     // Handle clone separately since repo doesn't exist before the command
     if parsed_args.command.as_deref() == Some("clone") && !parsed_args.is_help && !skip_hooks {
-        let exit_status = proxy_to_git(&parsed_args.to_invocation_vec(), false);
+        let exit_status = proxy_to_git(&parsed_args.to_invocation_vec(), false, None);
         clone_hooks::post_clone_hook(&parsed_args, exit_status);
         exit_with_status(exit_status);
     }
@@ -128,16 +144,27 @@ pub fn handle_git(args: &[String]) {
             stash_sha: None,
             push_authorship_handle: None,
             stashed_va: None,
+            exit_status_override: None,
+            git_stderr: None,
         };
 
         let repository = repository_option.as_mut().unwrap();
 
+        let mut span = HookSpan::start(format!(
+            "git {}",
+            parsed_args.command.as_deref().unwrap_or("unknown")
+        ));
+
         let pre_command_start = Instant::now();
         run_pre_command_hooks(&mut command_hooks_context, &mut parsed_args, repository);
         let pre_command_duration = pre_command_start.elapsed();
 
         let git_start = Instant::now();
-        let exit_status = proxy_to_git(&parsed_args.to_invocation_vec(), false);
+        let exit_status = proxy_to_git(
+            &parsed_args.to_invocation_vec(),
+            false,
+            command_hooks_context.git_stderr.as_mut(),
+        );
         let git_duration = git_start.elapsed();
 
         let post_command_start = Instant::now();
@@ -148,7 +175,21 @@ pub fn handle_git(args: &[String]) {
             repository,
         );
         let post_command_duration = post_command_start.elapsed();
-
+        let exit_status = command_hooks_context
+            .exit_status_override
+            .unwrap_or(exit_status);
+
+        logging::trace(
+            "git",
+            &format!(
+                "git {} exited with {:?} (pre-command {}ms, git {}ms, post-command {}ms)",
+                parsed_args.to_invocation_vec().join(" "),
+                exit_status.code(),
+                pre_command_duration.as_millis(),
+                git_duration.as_millis(),
+                post_command_duration.as_millis()
+            ),
+        );
         log_performance_target_if_violated(
             &parsed_args.command.as_deref().unwrap_or("unknown"),
             pre_command_duration,
@@ -156,10 +197,16 @@ pub fn handle_git(args: &[String]) {
             post_command_duration,
         );
 
+        span.set_attribute("git.duration_ms", git_duration.as_millis());
+        if let Some(code) = exit_status.code() {
+            span.set_attribute("git.exit_code", code);
+        }
+        drop(span);
+
         exit_status
     } else {
         // run without hooks
-        proxy_to_git(&parsed_args.to_invocation_vec(), false)
+        proxy_to_git(&parsed_args.to_invocation_vec(), false, None)
     };
     exit_with_status(exit_status);
 }
@@ -169,6 +216,11 @@ fn run_pre_command_hooks(
     parsed_args: &mut ParsedGitInvocation,
     repository: &mut Repository,
 ) {
+    let mut span = HookSpan::start("hooks.pre_command");
+    span.set_attribute(
+        "git.command",
+        parsed_args.command.as_deref().unwrap_or("unknown"),
+    );
     let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
         // Pre-command hooks
         match parsed_args.command.as_deref() {
@@ -183,6 +235,12 @@ fn run_pre_command_hooks(
             Some("reset") => {
                 reset_hooks::pre_reset_hook(parsed_args, repository);
             }
+            Some("revert") => {
+                revert_hooks::pre_revert_hook(repository);
+            }
+            Some("merge") => {
+                merge_hooks::pre_merge_hook(repository);
+            }
             Some("cherry-pick") => {
                 cherry_pick_hooks::pre_cherry_pick_hook(
                     parsed_args,
@@ -193,6 +251,9 @@ fn run_pre_command_hooks(
             Some("push") => {
                 command_hooks_context.push_authorship_handle =
                     push_hooks::push_pre_command_hook(parsed_args, repository);
+                if push_hooks::needs_push_stderr(parsed_args) {
+                    command_hooks_context.git_stderr = Some(String::new());
+                }
             }
             Some("fetch") => {
                 command_hooks_context.fetch_authorship_handle =
@@ -214,6 +275,12 @@ fn run_pre_command_hooks(
             Some("switch") => {
                 switch_hooks::pre_switch_hook(parsed_args, repository, command_hooks_context);
             }
+            Some("branch") => {
+                branch_hooks::pre_branch_hook(parsed_args, repository);
+            }
+            Some("notes") => {
+                notes_hooks::pre_notes_hook(parsed_args, repository);
+            }
             _ => {}
         }
     }));
@@ -235,6 +302,7 @@ fn run_pre_command_hooks(
         });
 
         debug_log(&error_message);
+        span.set_error(&error_message);
         observability::log_error(&HookPanicError(error_message.clone()), Some(context));
     }
 }
@@ -245,6 +313,11 @@ fn run_post_command_hooks(
     exit_status: std::process::ExitStatus,
     repository: &mut Repository,
 ) {
+    let mut span = HookSpan::start("hooks.post_command");
+    span.set_attribute(
+        "git.command",
+        parsed_args.command.as_deref().unwrap_or("unknown"),
+    );
     let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
         // Post-command hooks
         match parsed_args.command.as_deref() {
@@ -273,6 +346,7 @@ fn run_post_command_hooks(
                 command_hooks_context,
             ),
             Some("reset") => reset_hooks::post_reset_hook(parsed_args, repository, exit_status),
+            Some("revert") => revert_hooks::post_revert_hook(parsed_args, exit_status, repository),
             Some("merge") => merge_hooks::post_merge_hook(parsed_args, exit_status, repository),
             Some("rebase") => rebase_hooks::handle_rebase_post_command(
                 command_hooks_context,
@@ -327,11 +401,18 @@ fn run_post_command_hooks(
         });
 
         debug_log(&error_message);
+        span.set_error(&error_message);
         observability::log_error(&HookPanicError(error_message.clone()), Some(context));
     }
 }
 
-fn proxy_to_git(args: &[String], exit_on_completion: bool) -> std::process::ExitStatus {
+/// Run git with our stdio. With `stderr_copy`, git's stderr is passed through a pipe and
+/// everything it wrote is also left in `stderr_copy`.
+fn proxy_to_git(
+    args: &[String],
+    exit_on_completion: bool,
+    stderr_copy: Option<&mut String>,
+) -> std::process::ExitStatus {
     // debug_log(&format!("proxying to git with args: {:?}", args));
     // debug_log(&format!("prepended global args: {:?}", prepend_global(args)));
     // Use spawn for interactive commands
@@ -345,7 +426,10 @@ fn proxy_to_git(args: &[String], exit_on_completion: bool) -> std::process::Exit
             let should_setpgid = !is_interactive;
 
             let mut cmd = Command::new(config::Config::get().git_cmd());
-            cmd.args(args);
+            cmd.args(args).env(WRAPPED_GIT_ENV, "1");
+            if stderr_copy.is_some() {
+                cmd.stderr(Stdio::piped());
+            }
             unsafe {
                 let setpgid_flag = should_setpgid;
                 cmd.pre_exec(move || {
@@ -364,9 +448,12 @@ fn proxy_to_git(args: &[String], exit_on_completion: bool) -> std::process::Exit
         }
         #[cfg(not(unix))]
         {
-            Command::new(config::Config::get().git_cmd())
-                .args(args)
-                .spawn()
+            let mut cmd = Command::new(config::Config::get().git_cmd());
+            cmd.args(args).env(WRAPPED_GIT_ENV, "1");
+            if stderr_copy.is_some() {
+                cmd.stderr(Stdio::piped());
+            }
+            cmd.spawn()
         }
     };
 
@@ -382,7 +469,14 @@ fn proxy_to_git(args: &[String], exit_on_completion: bool) -> std::process::Exit
                     install_forwarding_handlers();
                 }
             }
+            let tee = child
+                .stderr
+                .take()
+                .map(|pipe| std::thread::spawn(move || tee_stderr(pipe)));
             let status = child.wait();
+            if let (Some(copy), Some(tee)) = (stderr_copy, tee) {
+                *copy = tee.join().unwrap_or_default();
+            }
             match status {
                 Ok(status) => {
                     #[cfg(unix)]
@@ -419,7 +513,14 @@ fn proxy_to_git(args: &[String], exit_on_completion: bool) -> std::process::Exit
     #[cfg(not(unix))]
     match child {
         Ok(mut child) => {
+            let tee = child
+                .stderr
+                .take()
+                .map(|pipe| std::thread::spawn(move || tee_stderr(pipe)));
             let status = child.wait();
+            if let (Some(copy), Some(tee)) = (stderr_copy, tee) {
+                *copy = tee.join().unwrap_or_default();
+            }
             match status {
                 Ok(status) => {
                     if exit_on_completion {
@@ -440,8 +541,29 @@ fn proxy_to_git(args: &[String], exit_on_completion: bool) -> std::process::Exit
     }
 }
 
+/// Copy a child's stderr to ours as it arrives, returning everything that passed through
+fn tee_stderr(mut pipe: impl Read) -> String {
+    let mut stderr = std::io::stderr();
+    let mut seen = Vec::new();
+    let mut buf = [0u8; 8192];
+    loop {
+        match pipe.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => {
+                let _ = stderr.write_all(&buf[..n]);
+                seen.extend_from_slice(&buf[..n]);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(_) => break,
+        }
+    }
+    String::from_utf8_lossy(&seen).into_owned()
+}
+
 // Exit mirroring the child's termination: same signal if signaled, else exit code
 fn exit_with_status(status: std::process::ExitStatus) -> ! {
+    // process::exit skips destructors, so queued metrics and batched spans must be handled here
+    observability::otel::shutdown_otel();
     #[cfg(unix)]
     {
         if let Some(sig) = status.signal() {
@@ -0,0 +1,141 @@
+use crate::commands::machine_output::{self, OutputFormat};
+use crate::github_app::auth::Credentials;
+use crate::github_app::ingest::ingest_push;
+use crate::github_app::{GithubAppConfig, PushEvent, serve};
+use std::io::Read;
+
+pub fn handle_github_app(args: &[String]) {
+    let Some(subcommand) = args.first() else {
+        print_github_app_help_and_exit();
+    };
+
+    match subcommand.as_str() {
+        "serve" => {
+            let mut config = GithubAppConfig::from_env();
+            let mut i = 1;
+            while i < args.len() {
+                match args[i].as_str() {
+                    "--listen" => {
+                        let Some(addr) = args.get(i + 1) else {
+                            eprintln!("--listen requires an address (e.g. 0.0.0.0:8787)");
+                            std::process::exit(1);
+                        };
+                        config.listen_addr = addr.clone();
+                        i += 2;
+                    }
+                    other => {
+                        eprintln!("Unknown github-app serve argument: {}", other);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            if let Err(e) = serve(&config) {
+                eprintln!("Failed to receive webhooks: {}", e);
+                std::process::exit(1);
+            }
+        }
+        "ingest" => handle_ingest(&args[1..]),
+        "--help" | "-h" | "help" => print_github_app_help_and_exit(),
+        other => {
+            eprintln!("Unknown github-app subcommand: {}", other);
+            print_github_app_help_and_exit();
+        }
+    }
+}
+
+/// Ingest a saved push payload, as if GitHub had just delivered it
+fn handle_ingest(args: &[String]) {
+    let mut format = OutputFormat::Text;
+    let mut payload_path: Option<String> = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--format" => {
+                format = OutputFormat::from_flag_value(args, i);
+                i += 2;
+            }
+            "--json" => {
+                format = OutputFormat::Json;
+                i += 1;
+            }
+            "--porcelain" => {
+                format = OutputFormat::Porcelain;
+                i += 1;
+            }
+            arg if arg == "-" || !arg.starts_with('-') => {
+                payload_path = Some(arg.to_string());
+                i += 1;
+            }
+            other => {
+                eprintln!("Unknown github-app ingest argument: {}", other);
+                std::process::exit(1);
+            }
+        }
+    }
+    let Some(payload_path) = payload_path else {
+        eprintln!("github-app ingest requires a push payload file (or - for stdin)");
+        std::process::exit(1);
+    };
+
+    let mut payload = Vec::new();
+    let read = if payload_path == "-" {
+        std::io::stdin().read_to_end(&mut payload).map(|_| ())
+    } else {
+        std::fs::read(&payload_path).map(|bytes| payload = bytes)
+    };
+    if let Err(e) = read {
+        eprintln!("Cannot read {}: {}", payload_path, e);
+        std::process::exit(1);
+    }
+    let event = match PushEvent::parse(&payload) {
+        Ok(event) => event,
+        Err(e) => {
+            eprintln!("Invalid push payload: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let config = GithubAppConfig::from_env();
+    let mut credentials = Credentials::new(config.clone());
+    let report = credentials
+        .token_for(event.installation.as_ref().map(|i| i.id))
+        .and_then(|token| ingest_push(&config, &event, token.as_deref()));
+    let report = match report {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Ingest failed: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if format.is_text() {
+        println!(
+            "{}: {} commits recorded, {} waiting for notes, {} expired",
+            report.repository,
+            report.recorded.len(),
+            report.pending.len(),
+            report.expired.len()
+        );
+    } else {
+        machine_output::print(format, &report).unwrap();
+    }
+    if !report.events.is_empty() {
+        crate::observability::log_metrics(report.events);
+    }
+}
+
+fn print_github_app_help_and_exit() -> ! {
+    eprintln!("Usage: git-ai github-app <serve|ingest>");
+    eprintln!();
+    eprintln!("Collect authorship notes org-wide from a GitHub App's push webhooks and record");
+    eprintln!("them as metrics. Configure with GIT_AI_GITHUB_APP_* environment variables.");
+    eprintln!();
+    eprintln!("  serve                 Receive webhooks on POST /webhook");
+    eprintln!(
+        "    --listen <addr>       Listen address (default: GIT_AI_GITHUB_APP_LISTEN or 127.0.0.1:8787)"
+    );
+    eprintln!("  ingest <payload|->    Ingest a saved push payload");
+    eprintln!("    --format <fmt>        text (default), json or porcelain");
+    std::process::exit(0);
+}
@@ -0,0 +1,696 @@
+//! Installing git-ai's repository hooks next to hooks that are already there.
+//!
+//! A hook git-ai didn't write is kept rather than replaced: it's renamed to `<hook>.pre-git-ai`
+//! and the git-ai script runs it with the same arguments and stdin, before or after git-ai's own
+//! work, passing on its exit status. Hook managers get special treatment:
+//!
+//! - husky keeps the real hook bodies in `.husky/<hook>`, so the git-ai command is appended there
+//! - lefthook rewrites the hooks it manages on every `lefthook install`, so installation stops and
+//!   prints the lefthook.yml entries to add instead
+//! - pre-commit's generated hooks are chained like any other hook; pre-commit chains existing
+//!   hooks the same way (`<hook>.legacy`), so either can be installed first
+
+use crate::error::GitAiError;
+use crate::git::repository::{exec_git, find_repository, find_repository_in_path};
+use crate::utils::{hook_script_path, normalize_to_posix, strip_verbatim_prefix};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Suffix an existing hook is renamed with when git-ai's hook takes its place
+pub const CHAINED_HOOK_SUFFIX: &str = ".pre-git-ai";
+
+/// Hooks git feeds on stdin; the input is captured once and replayed to both hooks
+const STDIN_HOOKS: [&str; 3] = ["post-rewrite", "pre-push", "reference-transaction"];
+
+const LEFTHOOK_CONFIG_FILES: [&str; 4] = [
+    "lefthook.yml",
+    ".lefthook.yml",
+    "lefthook.yaml",
+    ".lefthook.yaml",
+];
+
+/// When a chained hook runs relative to git-ai
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChainOrder {
+    Before,
+    After,
+}
+
+#[derive(Debug, Clone)]
+pub struct HookInstallOptions {
+    /// Replace existing hooks instead of chaining them, and ignore hook managers
+    pub force: bool,
+    /// Install into this directory and point core.hooksPath at it
+    pub hooks_path: Option<PathBuf>,
+    pub existing_hooks: ChainOrder,
+    /// Also install into every checked-out submodule, and theirs, in their own hooks directories
+    pub recurse_submodules: bool,
+}
+
+impl Default for HookInstallOptions {
+    fn default() -> Self {
+        HookInstallOptions {
+            force: false,
+            hooks_path: None,
+            existing_hooks: ChainOrder::Before,
+            recurse_submodules: false,
+        }
+    }
+}
+
+impl HookInstallOptions {
+    /// Parse the flags shared by the `install` subcommands: `--force`, `--hooks-path <dir>`,
+    /// `--run-existing <before|after>` and `--recurse-submodules`
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = HookInstallOptions::default();
+        let mut i = 0;
+        while i < args.len() {
+            match args[i].as_str() {
+                "--force" => {
+                    options.force = true;
+                    i += 1;
+                }
+                "--recurse-submodules" => {
+                    options.recurse_submodules = true;
+                    i += 1;
+                }
+                "--hooks-path" => {
+                    let dir = args.get(i + 1).ok_or("--hooks-path requires a directory")?;
+                    options.hooks_path = Some(PathBuf::from(dir));
+                    i += 2;
+                }
+                "--run-existing" => {
+                    options.existing_hooks = match args.get(i + 1).map(String::as_str) {
+                        Some("before") => ChainOrder::Before,
+                        Some("after") => ChainOrder::After,
+                        _ => return Err("--run-existing must be before or after".to_string()),
+                    };
+                    i += 2;
+                }
+                other => return Err(format!("Unknown argument: {}", other)),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// Hook managers that own a repository's hooks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HookManager {
+    /// Directory holding the husky hook scripts (`.husky`)
+    Husky(PathBuf),
+    /// The lefthook config file
+    Lefthook(PathBuf),
+    PreCommit,
+}
+
+impl HookManager {
+    pub fn detect(workdir: &Path, hooks_dir: &Path) -> Option<Self> {
+        let husky_dir = workdir.join(".husky");
+        let hooks_dir_str = normalize_to_posix(&hooks_dir.to_string_lossy());
+        if hooks_dir_str.contains(".husky") || husky_dir.is_dir() {
+            return Some(HookManager::Husky(husky_dir));
+        }
+
+        if let Some(config) = LEFTHOOK_CONFIG_FILES
+            .iter()
+            .map(|name| workdir.join(name))
+            .find(|path| path.is_file())
+        {
+            return Some(HookManager::Lefthook(config));
+        }
+
+        let generated_by_pre_commit = std::fs::read_dir(hooks_dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .any(|entry| {
+                std::fs::read_to_string(entry.path())
+                    .is_ok_and(|script| script.contains("File generated by pre-commit"))
+            });
+        if workdir.join(".pre-commit-config.yaml").is_file() || generated_by_pre_commit {
+            return Some(HookManager::PreCommit);
+        }
+        None
+    }
+}
+
+/// A hook script written by `install_hook_scripts`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstalledHook {
+    pub path: PathBuf,
+    /// The pre-existing hook the script runs, if any
+    pub chained: Option<PathBuf>,
+}
+
+impl fmt::Display for InstalledHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path.display())?;
+        if let Some(chained) = &self.chained {
+            write!(f, " (chained to existing hook {})", chained.display())?;
+        }
+        Ok(())
+    }
+}
+
+/// Write one script per `(hook name, arguments)` in `hooks`, each running
+/// `git-ai <subcommand> <arguments>`. Scripts carry `marker` so a later install can replace
+/// them; any other hook already in place is chained, or replaced with `force`.
+///
+/// Linked worktrees share the main checkout's hooks directory, so installing from any of them
+/// covers all. Submodules have their own, which `recurse_submodules` installs into as well.
+pub(crate) fn install_hook_scripts(
+    subcommand: &str,
+    marker: &str,
+    hooks: &[(&str, &str)],
+    options: &HookInstallOptions,
+) -> Result<Vec<InstalledHook>, GitAiError> {
+    install_hook_scripts_recursively(None, subcommand, marker, hooks, options)
+}
+
+fn install_hook_scripts_recursively(
+    repo_dir: Option<&Path>,
+    subcommand: &str,
+    marker: &str,
+    hooks: &[(&str, &str)],
+    options: &HookInstallOptions,
+) -> Result<Vec<InstalledHook>, GitAiError> {
+    let mut installed = install_hook_scripts_in(repo_dir, subcommand, marker, hooks, options)?;
+    if !options.recurse_submodules {
+        return Ok(installed);
+    }
+
+    let repo = match repo_dir {
+        Some(dir) => find_repository_in_path(&dir.to_string_lossy())?,
+        None => find_repository(&Vec::new())?,
+    };
+    // Each submodule keeps its hooks in its own git dir, not in --hooks-path
+    let submodule_options = HookInstallOptions {
+        hooks_path: None,
+        ..options.clone()
+    };
+    for submodule in repo.submodule_workdirs()? {
+        installed.extend(install_hook_scripts_recursively(
+            Some(&submodule),
+            subcommand,
+            marker,
+            hooks,
+            &submodule_options,
+        )?);
+    }
+    Ok(installed)
+}
+
+/// `install_hook_scripts` for the repository at `repo_dir`, or the current one
+fn install_hook_scripts_in(
+    repo_dir: Option<&Path>,
+    subcommand: &str,
+    marker: &str,
+    hooks: &[(&str, &str)],
+    options: &HookInstallOptions,
+) -> Result<Vec<InstalledHook>, GitAiError> {
+    let run_dir = repo_dir.map(Path::to_path_buf).unwrap_or_default();
+    let git = |args: &[&str]| -> Result<String, GitAiError> {
+        let mut full_args = Vec::new();
+        if let Some(dir) = repo_dir {
+            full_args.push("-C".to_string());
+            full_args.push(dir.to_string_lossy().to_string());
+        }
+        full_args.extend(args.iter().map(|arg| arg.to_string()));
+        Ok(String::from_utf8_lossy(&exec_git(&full_args)?.stdout)
+            .trim()
+            .to_string())
+    };
+
+    // Already honors core.hooksPath when it's set. Relative to where git ran.
+    let current_hooks_dir = run_dir.join(git(&["rev-parse", "--git-path", "hooks"])?);
+
+    if options.hooks_path.is_none() && !options.force {
+        let workdir = PathBuf::from(git(&["rev-parse", "--show-toplevel"])?);
+        match HookManager::detect(&workdir, &current_hooks_dir) {
+            Some(HookManager::Husky(husky_dir)) => {
+                return install_husky_hooks(&husky_dir, subcommand, marker, hooks);
+            }
+            Some(HookManager::Lefthook(config)) => {
+                return Err(GitAiError::Generic(lefthook_instructions(
+                    &config, subcommand, hooks,
+                )));
+            }
+            Some(HookManager::PreCommit) | None => {}
+        }
+    }
+
+    let hooks_dir = match &options.hooks_path {
+        Some(dir) => {
+            std::fs::create_dir_all(dir)?;
+            let dir = PathBuf::from(strip_verbatim_prefix(
+                &dir.canonicalize()?.to_string_lossy(),
+            ));
+            git(&[
+                "config",
+                "core.hooksPath",
+                &normalize_to_posix(&dir.to_string_lossy()),
+            ])?;
+            dir
+        }
+        None => current_hooks_dir.clone(),
+    };
+    std::fs::create_dir_all(&hooks_dir)?;
+
+    // Decide what each hook chains to before touching anything, so a conflict leaves every
+    // hook as it was
+    let mut planned: Vec<(PathBuf, Option<PathBuf>, bool)> = Vec::new();
+    for (name, _) in hooks {
+        let path = hooks_dir.join(name);
+        let existing = std::fs::read_to_string(&path).ok();
+        let (chained, rename) = match &existing {
+            Some(script) if script.contains(marker) => (chained_hook_in_script(script), false),
+            Some(_) if options.force => (None, false),
+            Some(_) => {
+                let renamed = hooks_dir.join(format!("{}{}", name, CHAINED_HOOK_SUFFIX));
+                if renamed.exists() {
+                    return Err(GitAiError::Generic(format!(
+                        "{} already exists, so {} can't be chained; move one of them or re-run with --force to replace the hook",
+                        renamed.display(),
+                        path.display()
+                    )));
+                }
+                (Some(renamed), true)
+            }
+            // Moving core.hooksPath would silently turn off the hook in the old directory
+            None if options.force => (None, false),
+            None => {
+                let previous = current_hooks_dir.join(name);
+                let previous_is_foreign =
+                    std::fs::read_to_string(&previous).is_ok_and(|script| !script.contains(marker));
+                let chained = (hooks_dir != current_hooks_dir && previous_is_foreign)
+                    .then(|| std::path::absolute(&previous).unwrap_or(previous));
+                (chained, false)
+            }
+        };
+        planned.push((path, chained, rename));
+    }
+
+    let exe = crate::utils::current_git_ai_exe()?;
+    let mut installed = Vec::new();
+    for ((name, hook_args), (path, chained, rename)) in hooks.iter().zip(planned) {
+        if let (true, Some(chained)) = (rename, &chained) {
+            std::fs::rename(&path, chained)?;
+        }
+        let git_ai = format!("{} {} {}", hook_script_path(&exe), subcommand, hook_args);
+        let script = match &chained {
+            Some(chained) => chained_script(
+                name,
+                marker,
+                &git_ai,
+                &hook_script_path(chained),
+                options.existing_hooks,
+            ),
+            // GIT_AI=git (debug builds' proxy switch) can leak in from git-ai itself. Git for
+            // Windows runs extensionless sh hooks too, so no .bat/.ps1 variant is needed.
+            None => format!("#!/bin/sh\n{}\nunset GIT_AI\nexec {}\n", marker, git_ai),
+        };
+        std::fs::write(&path, script)?;
+        set_executable(&path)?;
+        installed.push(InstalledHook { path, chained });
+    }
+
+    Ok(installed)
+}
+
+/// Script running the hook that was there before git-ai (`chained`, already quoted) as well as
+/// `git_ai`, with the same arguments and stdin. Exits with the chained hook's status.
+fn chained_script(
+    name: &str,
+    marker: &str,
+    git_ai: &str,
+    chained: &str,
+    order: ChainOrder,
+) -> String {
+    let takes_stdin = STDIN_HOOKS.contains(&name);
+    let replay = if takes_stdin {
+        "printf '%s\\n' \"$hook_input\" | "
+    } else {
+        ""
+    };
+    let run_chained = format!("{}\"$chained_hook\" \"$@\"", replay);
+    let run_git_ai = format!("{}(unset GIT_AI; exec {})", replay, git_ai);
+
+    let mut script = format!("#!/bin/sh\n{}\nchained_hook={}\n", marker, chained);
+    if takes_stdin {
+        script.push_str("hook_input=$(cat)\n");
+    }
+    match order {
+        ChainOrder::Before => {
+            script.push_str(&format!(
+                "{}\nstatus=$?\n{}\nexit $status\n",
+                run_chained, run_git_ai
+            ));
+        }
+        ChainOrder::After => {
+            script.push_str(&format!("{}\n{}\n", run_git_ai, run_chained));
+        }
+    }
+    script
+}
+
+/// The chained hook path recorded in a script written by `chained_script`
+fn chained_hook_in_script(script: &str) -> Option<PathBuf> {
+    let quoted = script
+        .lines()
+        .find_map(|line| line.strip_prefix("chained_hook="))?;
+    let unquoted = quoted
+        .strip_prefix('\'')?
+        .strip_suffix('\'')?
+        .replace(r"'\''", "'");
+    Some(PathBuf::from(unquoted))
+}
+
+/// Append the git-ai command to each `.husky/<hook>` script, or update the one added before.
+/// The command is skipped on machines without git-ai, since husky scripts are committed.
+fn install_husky_hooks(
+    husky_dir: &Path,
+    subcommand: &str,
+    marker: &str,
+    hooks: &[(&str, &str)],
+) -> Result<Vec<InstalledHook>, GitAiError> {
+    std::fs::create_dir_all(husky_dir)?;
+    let mut installed = Vec::new();
+    for (name, hook_args) in hooks {
+        let path = husky_dir.join(name);
+        let command = format!(
+            "if command -v git-ai >/dev/null 2>&1; then (unset GIT_AI; git-ai {} {}); fi",
+            subcommand, hook_args
+        );
+        let existing = std::fs::read_to_string(&path).unwrap_or_default();
+
+        let mut lines: Vec<String> = existing.lines().map(str::to_string).collect();
+        match lines.iter().position(|line| line == marker) {
+            Some(index) if index + 1 < lines.len() => lines[index + 1] = command,
+            Some(_) => lines.push(command),
+            None => {
+                if lines.last().is_some_and(|line| !line.trim().is_empty()) {
+                    lines.push(String::new());
+                }
+                lines.push(marker.to_string());
+                lines.push(command);
+            }
+        }
+        std::fs::write(&path, lines.join("\n") + "\n")?;
+        set_executable(&path)?;
+        installed.push(InstalledHook {
+            path,
+            chained: None,
+        });
+    }
+    Ok(installed)
+}
+
+/// Start of the marker line in every hook script and husky block git-ai writes
+pub const GIT_AI_HOOK_MARKER_PREFIX: &str = "# Installed by git-ai";
+
+/// What `uninstall_hook_scripts` did, or would do, to one hook
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HookRemoval {
+    Removed(PathBuf),
+    /// The hook git-ai had chained to was moved back into place
+    Restored {
+        path: PathBuf,
+        from: PathBuf,
+    },
+    /// git-ai's command was taken out of a husky script
+    Unhooked(PathBuf),
+    /// core.hooksPath pointed at a directory that only held git-ai's hooks
+    UnsetHooksPath(PathBuf),
+}
+
+impl fmt::Display for HookRemoval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HookRemoval::Removed(path) => write!(f, "Removed {}", path.display()),
+            HookRemoval::Restored { path, from } => {
+                write!(f, "Restored {} from {}", path.display(), from.display())
+            }
+            HookRemoval::Unhooked(path) => write!(f, "Removed git-ai from {}", path.display()),
+            HookRemoval::UnsetHooksPath(dir) => {
+                write!(f, "Unset core.hooksPath (was {})", dir.display())
+            }
+        }
+    }
+}
+
+/// Take out every hook git-ai installed in the current repository: scripts in the hooks
+/// directory (and in .git/hooks when core.hooksPath points elsewhere) and husky blocks. Chained
+/// hooks are moved back into place. With `dry_run`, only report what would change.
+pub(crate) fn uninstall_hook_scripts(dry_run: bool) -> Result<Vec<HookRemoval>, GitAiError> {
+    let git_path = |args: &[&str]| -> Result<PathBuf, GitAiError> {
+        let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        Ok(PathBuf::from(
+            String::from_utf8_lossy(&exec_git(&args)?.stdout).trim(),
+        ))
+    };
+    let current_hooks_dir = git_path(&["rev-parse", "--git-path", "hooks"])?;
+    let default_hooks_dir = git_path(&["rev-parse", "--git-common-dir"])?.join("hooks");
+    let workdir = git_path(&["rev-parse", "--show-toplevel"])?;
+
+    let mut removals = Vec::new();
+    let mut removed_from_current = 0;
+    let mut hook_dirs = vec![current_hooks_dir.clone()];
+    if !same_file(&default_hooks_dir, &current_hooks_dir) {
+        hook_dirs.push(default_hooks_dir);
+    }
+    for dir in &hook_dirs {
+        let is_current = same_file(dir, &current_hooks_dir);
+        for path in hook_files(dir) {
+            let Ok(script) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            if !script
+                .lines()
+                .any(|line| line.starts_with(GIT_AI_HOOK_MARKER_PREFIX))
+            {
+                continue;
+            }
+            let mut renamed = path.clone().into_os_string();
+            renamed.push(CHAINED_HOOK_SUFFIX);
+            let renamed = PathBuf::from(renamed);
+            let chained_here = chained_hook_in_script(&script)
+                .is_some_and(|chained| same_file(&chained, &renamed))
+                && renamed.is_file();
+
+            if chained_here {
+                if !dry_run {
+                    std::fs::rename(&renamed, &path)?;
+                }
+                removals.push(HookRemoval::Restored {
+                    path,
+                    from: renamed,
+                });
+            } else {
+                if !dry_run {
+                    std::fs::remove_file(&path)?;
+                }
+                if is_current {
+                    removed_from_current += 1;
+                }
+                removals.push(HookRemoval::Removed(path));
+            }
+        }
+    }
+
+    let husky_dir = workdir.join(".husky");
+    for path in hook_files(&husky_dir) {
+        let Ok(script) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let Some(remaining) = remove_husky_block(&script) else {
+            continue;
+        };
+        if remaining.trim().is_empty() {
+            if !dry_run {
+                std::fs::remove_file(&path)?;
+            }
+            removals.push(HookRemoval::Removed(path));
+        } else {
+            if !dry_run {
+                std::fs::write(&path, remaining)?;
+            }
+            removals.push(HookRemoval::Unhooked(path));
+        }
+    }
+
+    // A core.hooksPath directory left empty was only there for git-ai (`install --hooks-path`)
+    let hooks_path_config = exec_git(&["config".to_string(), "core.hooksPath".to_string()])
+        .ok()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|value| !value.is_empty());
+    if hooks_path_config.is_some()
+        && removed_from_current > 0
+        && hook_files(&current_hooks_dir).len() == if dry_run { removed_from_current } else { 0 }
+    {
+        if !dry_run {
+            exec_git(&[
+                "config".to_string(),
+                "--unset".to_string(),
+                "core.hooksPath".to_string(),
+            ])?;
+        }
+        removals.push(HookRemoval::UnsetHooksPath(current_hooks_dir));
+    }
+
+    Ok(removals)
+}
+
+/// Hook scripts in `dir`, leaving out the `.pre-git-ai` hooks chained from them
+fn hook_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter(|path| !path.to_string_lossy().ends_with(CHAINED_HOOK_SUFFIX))
+        .collect();
+    files.sort();
+    files
+}
+
+/// `script` without the blocks `install_husky_hooks` added, or None if it has none
+fn remove_husky_block(script: &str) -> Option<String> {
+    let lines: Vec<&str> = script.lines().collect();
+    if !lines
+        .iter()
+        .any(|line| line.starts_with(GIT_AI_HOOK_MARKER_PREFIX))
+    {
+        return None;
+    }
+
+    let mut kept: Vec<&str> = Vec::new();
+    let mut skip_next = false;
+    for line in lines {
+        if skip_next {
+            skip_next = false;
+        } else if line.starts_with(GIT_AI_HOOK_MARKER_PREFIX) {
+            skip_next = true;
+        } else {
+            kept.push(line);
+        }
+    }
+    // install_husky_hooks separates its block from the script with a blank line
+    while kept.last().is_some_and(|line| line.trim().is_empty()) {
+        kept.pop();
+    }
+    Some(if kept.is_empty() {
+        String::new()
+    } else {
+        kept.join("\n") + "\n"
+    })
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Error text for repositories whose hooks lefthook owns, with the config to add instead
+fn lefthook_instructions(config: &Path, subcommand: &str, hooks: &[(&str, &str)]) -> String {
+    let mut message = format!(
+        "lefthook manages this repository's hooks and replaces them on `lefthook install`.\nAdd git-ai to {} instead:\n\n",
+        config.display()
+    );
+    for (name, hook_args) in hooks {
+        // lefthook's {0} expands to all of the hook's arguments
+        let args = hook_args.replace("\"$@\"", "{0}");
+        message.push_str(&format!(
+            "{}:\n  commands:\n    git-ai:\n      run: git-ai {} {}\n",
+            name, subcommand, args
+        ));
+        if STDIN_HOOKS.contains(name) {
+            message.push_str("      use_stdin: true\n");
+        }
+    }
+    message.push_str("\nor re-run with --hooks-path <dir> or --force to install git hooks anyway");
+    message
+}
+
+fn set_executable(path: &Path) -> Result<(), GitAiError> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chained_script_replays_stdin_and_keeps_chained_status() {
+        let script = chained_script(
+            "post-rewrite",
+            "# marker",
+            "'/bin/git-ai' post-rewrite \"$@\"",
+            "'/repo/.git/hooks/post-rewrite.pre-git-ai'",
+            ChainOrder::Before,
+        );
+        assert_eq!(
+            script,
+            "#!/bin/sh\n# marker\nchained_hook='/repo/.git/hooks/post-rewrite.pre-git-ai'\nhook_input=$(cat)\nprintf '%s\\n' \"$hook_input\" | \"$chained_hook\" \"$@\"\nstatus=$?\nprintf '%s\\n' \"$hook_input\" | (unset GIT_AI; exec '/bin/git-ai' post-rewrite \"$@\")\nexit $status\n"
+        );
+        assert_eq!(
+            chained_hook_in_script(&script),
+            Some(PathBuf::from("/repo/.git/hooks/post-rewrite.pre-git-ai"))
+        );
+
+        let after = chained_script(
+            "post-commit",
+            "# marker",
+            "'/bin/git-ai' post-rewrite cherry-pick",
+            "'/it'\\''s/post-commit'",
+            ChainOrder::After,
+        );
+        assert!(!after.contains("hook_input"));
+        assert!(after.ends_with("(unset GIT_AI; exec '/bin/git-ai' post-rewrite cherry-pick)\n\"$chained_hook\" \"$@\"\n"));
+        assert_eq!(
+            chained_hook_in_script(&after),
+            Some(PathBuf::from("/it's/post-commit"))
+        );
+    }
+
+    #[test]
+    fn test_parse_install_options() {
+        let args: Vec<String> = ["--run-existing", "after", "--hooks-path", "hooks"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let options = HookInstallOptions::parse(&args).unwrap();
+        assert_eq!(options.existing_hooks, ChainOrder::After);
+        assert_eq!(options.hooks_path, Some(PathBuf::from("hooks")));
+        assert!(!options.force);
+
+        assert!(HookInstallOptions::parse(&["--run-existing".to_string()]).is_err());
+        assert!(HookInstallOptions::parse(&["--bogus".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_remove_husky_block() {
+        let script = "npx lint-staged\n\n# Installed by git-ai post-rewrite\nif command -v git-ai >/dev/null 2>&1; then (unset GIT_AI; git-ai post-rewrite cherry-pick); fi\n";
+        assert_eq!(
+            remove_husky_block(script),
+            Some("npx lint-staged\n".to_string())
+        );
+        assert_eq!(
+            remove_husky_block(
+                "# Installed by git-ai post-rewrite\nif command -v git-ai; then git-ai; fi\n"
+            ),
+            Some(String::new())
+        );
+        assert_eq!(remove_husky_block("npx lint-staged\n"), None);
+    }
+}
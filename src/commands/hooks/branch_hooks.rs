@@ -0,0 +1,87 @@
+use crate::commands::branches::unreachable_noted_commits_for_branch;
+use crate::git::cli_parser::ParsedGitInvocation;
+use crate::git::repository::Repository;
+use crate::utils::debug_log;
+
+/// Warn before `git branch -d/-D` drops the only path to commits that carry AI authorship notes.
+/// The deletion itself is never blocked.
+pub fn pre_branch_hook(parsed_args: &ParsedGitInvocation, repository: &Repository) {
+    let Some(branches) = branches_being_deleted(&parsed_args.command_args) else {
+        return;
+    };
+
+    for branch in branches {
+        match unreachable_noted_commits_for_branch(repository, &branch) {
+            Ok(commits) if !commits.is_empty() => {
+                eprintln!(
+                    "git-ai: warning: deleting '{}' leaves {} commit{} with AI authorship notes unreachable.",
+                    branch,
+                    commits.len(),
+                    if commits.len() == 1 { "" } else { "s" }
+                );
+                eprintln!(
+                    "git-ai: run `git-ai branches --stale --archive` first to preserve them."
+                );
+            }
+            Ok(_) => {}
+            Err(e) => debug_log(&format!(
+                "Failed to check notes reachability for branch {}: {}",
+                branch, e
+            )),
+        }
+    }
+}
+
+fn branches_being_deleted(command_args: &[String]) -> Option<Vec<String>> {
+    let mut deleting = false;
+    let mut names = Vec::new();
+    for arg in command_args {
+        match arg.as_str() {
+            "-d" | "-D" | "--delete" => deleting = true,
+            "-r" | "--remotes" => return None,
+            // Combined short flags like -df
+            s if s.starts_with('-') && !s.starts_with("--") && s.len() > 2 => {
+                if s.contains('r') {
+                    return None;
+                }
+                if s.contains('d') || s.contains('D') {
+                    deleting = true;
+                }
+            }
+            s if s.starts_with('-') => {}
+            s => names.push(s.to_string()),
+        }
+    }
+    if deleting && !names.is_empty() {
+        Some(names)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_branches_being_deleted() {
+        assert_eq!(
+            branches_being_deleted(&args(&["-D", "feature", "other"])),
+            Some(args(&["feature", "other"]))
+        );
+        assert_eq!(
+            branches_being_deleted(&args(&["-df", "feature"])),
+            Some(args(&["feature"]))
+        );
+        assert_eq!(
+            branches_being_deleted(&args(&["-dr", "origin/feature"])),
+            None
+        );
+        assert_eq!(branches_being_deleted(&args(&["feature"])), None);
+        assert_eq!(branches_being_deleted(&args(&["-d"])), None);
+    }
+}
@@ -1,8 +1,10 @@
-use crate::authorship::pre_commit;
+use crate::authorship::{policy, pre_commit};
 use crate::commands::git_handlers::CommandHooksContext;
+use crate::commands::self_update;
 use crate::git::cli_parser::{ParsedGitInvocation, is_dry_run};
 use crate::git::repository::Repository;
 use crate::git::rewrite_log::RewriteLogEvent;
+use crate::observability::logging;
 use crate::utils::debug_log;
 
 pub fn commit_pre_command_hook(
@@ -13,6 +15,8 @@ pub fn commit_pre_command_hook(
         return false;
     }
 
+    self_update::warn_if_required_version_unmet();
+
     // store HEAD context for post-command hook
     repository.require_pre_command_head();
 
@@ -23,17 +27,46 @@ pub fn commit_pre_command_hook(
         if e.to_string()
             .contains("Cannot run checkpoint on bare repositories")
         {
-            eprintln!(
-                "Cannot run checkpoint on bare repositories (skipping git-ai pre-commit hook)"
+            logging::warn(
+                "hooks::commit",
+                "Cannot run checkpoint on bare repositories (skipping git-ai pre-commit hook)",
             );
             return false;
         }
-        eprintln!("Pre-commit failed: {}", e);
+        logging::error("hooks::commit", &format!("Pre-commit failed: {}", e));
+        std::process::exit(1);
+    }
+
+    if !policy::check_commit(repository, commits_all_tracked(&parsed_args.command_args)) {
         std::process::exit(1);
     }
     return true;
 }
 
+/// Whether `git commit` was given `-a`/`--all`, including in a cluster like `-am`
+pub(crate) fn commits_all_tracked(args: &[String]) -> bool {
+    for arg in args {
+        if arg == "--" {
+            break;
+        }
+        if arg == "--all" {
+            return true;
+        }
+        if arg.starts_with("--") || !arg.starts_with('-') {
+            continue;
+        }
+        for c in arg.chars().skip(1) {
+            match c {
+                'a' => return true,
+                // The rest of the cluster is this flag's value
+                'm' | 'F' | 'C' | 'c' | 't' | 'u' | 'S' => break,
+                _ => {}
+            }
+        }
+    }
+    false
+}
+
 pub fn commit_post_command_hook(
     parsed_args: &ParsedGitInvocation,
     exit_status: std::process::ExitStatus,
@@ -85,6 +118,9 @@ pub fn commit_post_command_hook(
         );
     }
 
+    // Prune working logs left behind by other bases, at most once a day
+    crate::git::checkpoint_gc::maybe_auto_gc(repository);
+
     // Flush logs and metrics after commit
     crate::observability::spawn_background_flush();
 }
@@ -165,7 +201,10 @@ pub fn get_commit_default_author(repo: &Repository, args: &[String]) -> String {
         (Some(name), None) => name,
         (None, Some(email)) => email,
         (None, None) => {
-            eprintln!("Warning: No author information found. Using 'unknown' as author.");
+            logging::warn(
+                "hooks::commit",
+                "No author information found. Using 'unknown' as author.",
+            );
             "unknown".to_string()
         }
     }
@@ -1,12 +1,15 @@
+use crate::authorship::squash_merge::propagate_squash_merges;
 use crate::authorship::virtual_attribution::{VirtualAttributions, restore_stashed_va};
 use crate::commands::git_handlers::CommandHooksContext;
 use crate::commands::hooks::commit_hooks::get_commit_default_author;
 use crate::commands::hooks::rebase_hooks::build_rebase_commit_mappings;
 use crate::commands::upgrade;
 use crate::git::cli_parser::{ParsedGitInvocation, is_dry_run};
+use crate::git::pending_pushes::retry_pending_pushes;
 use crate::git::repository::{Repository, exec_git, find_repository};
 use crate::git::rewrite_log::RewriteLogEvent;
 use crate::git::sync_authorship::{fetch_authorship_notes, fetch_remote_from_args};
+use crate::observability::spans;
 use crate::utils::debug_log;
 
 pub fn fetch_pull_pre_command_hook(
@@ -33,6 +36,7 @@ pub fn fetch_pull_pre_command_hook(
 
     // Clone what we need for the background thread
     let global_args = repository.global_args_for_exec();
+    let parent_span = spans::current_context();
 
     // Spawn background thread to fetch authorship notes in parallel with main fetch
     Some(std::thread::spawn(move || {
@@ -42,8 +46,13 @@ pub fn fetch_pull_pre_command_hook(
         ));
         // Recreate repository in the background thread
         if let Ok(repo) = find_repository(&global_args) {
-            if let Err(e) = fetch_authorship_notes(&repo, &remote) {
-                debug_log(&format!("authorship fetch failed: {}", e));
+            let result = spans::with_parent(parent_span, || fetch_authorship_notes(&repo, &remote));
+            match result {
+                // The remote is reachable again: push notes that earlier pushes left behind
+                Ok(_) => {
+                    spans::with_parent(parent_span, || retry_pending_pushes(&repo, &[]));
+                }
+                Err(e) => debug_log(&format!("authorship fetch failed: {}", e)),
             }
         } else {
             debug_log("failed to open repository for authorship fetch");
@@ -130,9 +139,10 @@ pub fn fetch_pull_post_command_hook(
 }
 
 /// Post-command hook for git pull.
-/// Handles two scenarios:
+/// Handles three scenarios:
 /// 1. Restores AI attributions after a pull --rebase --autostash operation.
 /// 2. Renames working log for fast-forward pulls to preserve attributions.
+/// 3. Propagates local branch attributions onto pulled squash-merge commits.
 pub fn pull_post_command_hook(
     repository: &mut Repository,
     parsed_args: &ParsedGitInvocation,
@@ -178,6 +188,7 @@ pub fn pull_post_command_hook(
             old_head, new_head
         ));
         let _ = repository.storage.rename_working_log(&old_head, &new_head);
+        propagate_pulled_squash_merges(repository, &old_head, &new_head);
         return;
     }
 
@@ -186,6 +197,21 @@ pub fn pull_post_command_hook(
     if config.is_rebase {
         process_completed_pull_rebase(repository, &old_head, &new_head);
     }
+
+    propagate_pulled_squash_merges(repository, &old_head, &new_head);
+}
+
+/// Attach authorship notes to pulled commits that squash-merged one of our local
+/// branches upstream (e.g. GitHub's "Squash and merge"), which arrive without notes.
+fn propagate_pulled_squash_merges(repository: &Repository, old_head: &str, new_head: &str) {
+    let Some(target_ref) = repository
+        .head()
+        .ok()
+        .and_then(|head| head.name().map(|name| name.to_string()))
+    else {
+        return;
+    };
+    propagate_squash_merges(repository, &target_ref, old_head, new_head);
 }
 
 /// Check if the most recent reflog entry indicates a fast-forward pull operation.
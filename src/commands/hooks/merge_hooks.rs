@@ -1,4 +1,5 @@
 use crate::{
+    ci::pr_metrics::{MergedPullRequest, record_pr_merged},
     commands::hooks::commit_hooks::get_commit_default_author,
     git::{
         cli_parser::{ParsedGitInvocation, is_dry_run},
@@ -7,6 +8,10 @@ use crate::{
     },
 };
 
+pub fn pre_merge_hook(repository: &mut Repository) {
+    repository.require_pre_command_head();
+}
+
 pub fn post_merge_hook(
     parsed_args: &ParsedGitInvocation,
     exit_status: std::process::ExitStatus,
@@ -45,5 +50,40 @@ pub fn post_merge_hook(
             false,
             true,
         );
+    } else if exit_status.success() && !is_dry_run(&parsed_args.command_args) {
+        record_merge_commit_metrics(parsed_args, repository);
     }
 }
+
+/// Record a `pr_merged` event when the merge landed a branch as a new merge commit.
+/// Fast-forwards and "Already up to date" leave no merge commit to attribute it to.
+fn record_merge_commit_metrics(parsed_args: &ParsedGitInvocation, repository: &Repository) {
+    let Some(old_head) = repository.pre_command_base_commit.clone() else {
+        return;
+    };
+    let Ok(merge_commit) = repository.head().and_then(|h| h.target()) else {
+        return;
+    };
+    if merge_commit == old_head {
+        return;
+    }
+    let Ok(commit) = repository.find_commit(merge_commit.clone()) else {
+        return;
+    };
+    let parents: Vec<String> = commit.parents().map(|p| p.id()).collect();
+    // Octopus merges don't map to a single pull request
+    if parents.len() != 2 || parents[0] != old_head {
+        return;
+    }
+
+    record_pr_merged(
+        repository,
+        &MergedPullRequest {
+            merge_commit_sha: merge_commit,
+            base_sha: old_head,
+            head_sha: parents[1].clone(),
+            head_ref: parsed_args.pos_command(0),
+            ..MergedPullRequest::default()
+        },
+    );
+}
@@ -1,11 +1,14 @@
+pub mod branch_hooks;
 pub mod checkout_hooks;
 pub mod cherry_pick_hooks;
 pub mod clone_hooks;
 pub mod commit_hooks;
 pub mod fetch_hooks;
 pub mod merge_hooks;
+pub mod notes_hooks;
 pub mod push_hooks;
 pub mod rebase_hooks;
 pub mod reset_hooks;
+pub mod revert_hooks;
 pub mod stash_hooks;
 pub mod switch_hooks;
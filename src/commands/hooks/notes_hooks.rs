@@ -0,0 +1,120 @@
+use crate::git::cli_parser::ParsedGitInvocation;
+use crate::git::notes_merge::{
+    NotesMergeOutcome, expand_notes_ref, merge_staged_notes, union_conflicted_notes,
+};
+use crate::git::refs::AI_AUTHORSHIP_REFNAME;
+use crate::git::repository::Repository;
+use crate::utils::debug_log;
+
+/// What a `git notes merge` invocation on refs/notes/ai asks for
+#[derive(Debug, PartialEq, Eq)]
+enum AiNotesMerge {
+    /// Merge this notes ref into refs/notes/ai
+    Merge(String),
+    /// Commit a conflicted merge
+    Commit,
+}
+
+/// Act as the merge driver for `git notes --ref=ai merge`. Merging another ref is done here
+/// with the authorship-aware union, which leaves git nothing to merge; `merge --commit` gets
+/// any conflicts still marked in NOTES_MERGE_WORKTREE resolved the same way first. An explicit
+/// `-s <strategy>` is left to git, and a failure here falls back to git's own merge.
+pub fn pre_notes_hook(parsed_args: &ParsedGitInvocation, repository: &Repository) {
+    match ai_notes_merge(&parsed_args.command_args) {
+        Some(AiNotesMerge::Merge(notes_ref)) => {
+            match merge_staged_notes(repository, &expand_notes_ref(&notes_ref)) {
+                Ok(NotesMergeOutcome::Merged { unioned }) => eprintln!(
+                    "git-ai: merged {} into refs/notes/{} ({} note{} combined)",
+                    notes_ref,
+                    AI_AUTHORSHIP_REFNAME,
+                    unioned,
+                    if unioned == 1 { "" } else { "s" }
+                ),
+                Ok(outcome) => debug_log(&format!("notes merge of {}: {:?}", notes_ref, outcome)),
+                Err(e) => debug_log(&format!("Failed to merge notes from {}: {}", notes_ref, e)),
+            }
+        }
+        Some(AiNotesMerge::Commit) => match union_conflicted_notes(repository) {
+            Ok(resolved) if resolved > 0 => eprintln!(
+                "git-ai: combined {} conflicting authorship note{}",
+                resolved,
+                if resolved == 1 { "" } else { "s" }
+            ),
+            Ok(_) => {}
+            Err(e) => debug_log(&format!("Failed to resolve conflicted notes: {}", e)),
+        },
+        None => {}
+    }
+}
+
+fn ai_notes_merge(command_args: &[String]) -> Option<AiNotesMerge> {
+    let mut notes_ref: Option<&str> = None;
+    let mut args = command_args.iter();
+    // `git notes [--ref <ref>] merge ...`
+    loop {
+        let arg = args.next()?;
+        match arg.as_str() {
+            "--ref" => notes_ref = Some(args.next()?),
+            s if s.starts_with("--ref=") => notes_ref = Some(&s["--ref=".len()..]),
+            "merge" => break,
+            _ => return None,
+        }
+    }
+    if expand_notes_ref(notes_ref?) != format!("refs/notes/{}", AI_AUTHORSHIP_REFNAME) {
+        return None;
+    }
+
+    let mut commit = false;
+    let mut source = None;
+    for arg in args {
+        match arg.as_str() {
+            "--commit" => commit = true,
+            "-q" | "--quiet" | "-v" | "--verbose" => {}
+            // --abort, -s/--strategy and anything else we don't know
+            s if s.starts_with('-') => return None,
+            s => source = Some(s.to_string()),
+        }
+    }
+    match (commit, source) {
+        (true, None) => Some(AiNotesMerge::Commit),
+        (false, Some(source)) => Some(AiNotesMerge::Merge(source)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_ai_notes_merge_detection() {
+        assert_eq!(
+            ai_notes_merge(&args(&["--ref=ai", "merge", "other"])),
+            Some(AiNotesMerge::Merge("other".to_string()))
+        );
+        assert_eq!(
+            ai_notes_merge(&args(&["--ref", "refs/notes/ai", "merge", "-q", "x"])),
+            Some(AiNotesMerge::Merge("x".to_string()))
+        );
+        assert_eq!(
+            ai_notes_merge(&args(&["--ref=ai", "merge", "--commit"])),
+            Some(AiNotesMerge::Commit)
+        );
+        // Other refs, explicit strategies and other subcommands are git's business
+        assert_eq!(ai_notes_merge(&args(&["merge", "other"])), None);
+        assert_eq!(ai_notes_merge(&args(&["--ref=review", "merge", "x"])), None);
+        assert_eq!(
+            ai_notes_merge(&args(&["--ref=ai", "merge", "-s", "ours", "x"])),
+            None
+        );
+        assert_eq!(
+            ai_notes_merge(&args(&["--ref=ai", "merge", "--abort"])),
+            None
+        );
+        assert_eq!(ai_notes_merge(&args(&["--ref=ai", "show", "HEAD"])), None);
+    }
+}
@@ -1,8 +1,20 @@
+use crate::authorship::policy;
 use crate::commands::git_handlers::CommandHooksContext;
-use crate::commands::upgrade;
+use crate::commands::{self_update, upgrade};
+use crate::config::Config;
+use crate::error::GitAiError;
 use crate::git::cli_parser::{ParsedGitInvocation, is_dry_run};
+use crate::git::pending_pushes::{
+    pending_pushes, record_push_outcome, retry_pending_pushes, warn_pending_pushes,
+};
+use crate::git::refs::noted_commits;
 use crate::git::repository::{Repository, find_repository};
-use crate::git::sync_authorship::push_authorship_notes;
+use crate::git::rewrite_log::RewriteLogEvent;
+use crate::git::sync_authorship::{
+    NotesSyncPolicy, RemoteProvider, find_rebased_notes, push_authorship_notes,
+    retire_rebased_notes,
+};
+use crate::observability::spans;
 use crate::utils::debug_log;
 
 pub fn push_pre_command_hook(
@@ -10,50 +22,38 @@ pub fn push_pre_command_hook(
     repository: &Repository,
 ) -> Option<std::thread::JoinHandle<()>> {
     upgrade::maybe_schedule_background_update_check();
+    self_update::warn_if_required_version_unmet();
 
-    // Early returns for cases where we shouldn't push authorship notes
-    if is_dry_run(&parsed_args.command_args)
-        || parsed_args
-            .command_args
-            .iter()
-            .any(|a| a == "-d" || a == "--delete")
-        || parsed_args.command_args.iter().any(|a| a == "--mirror")
+    if should_sync_notes(parsed_args)
+        && let Some(remote) = resolve_push_remote(parsed_args, repository)
+        && !policy::check_push(repository, &remote, &pushed_revs(parsed_args))
     {
-        return None;
+        std::process::exit(1);
     }
 
-    let remotes = repository.remotes().ok();
-    let remote_names: Vec<String> = remotes
-        .as_ref()
-        .map(|r| {
-            (0..r.len())
-                .filter_map(|i| r.get(i).map(|s| s.to_string()))
-                .collect()
-        })
-        .unwrap_or_default();
-
-    // Push authorship refs to the appropriate remote
-    let positional_remote = extract_remote_from_push_args(&parsed_args.command_args, &remote_names);
-
-    let specified_remote = positional_remote.or_else(|| {
-        parsed_args
-            .command_args
-            .iter()
-            .find(|a| remote_names.iter().any(|r| r == *a))
-            .cloned()
-    });
+    // Early returns for cases where we shouldn't push authorship notes
+    if !should_sync_notes(parsed_args) {
+        return None;
+    }
 
-    let remote = specified_remote
-        .or_else(|| repository.upstream_remote().ok().flatten())
-        .or_else(|| repository.get_default_remote().ok().flatten());
+    let remote = resolve_push_remote(parsed_args, repository);
 
     if let Some(remote) = remote {
+        let targets = NotesSyncPolicy::for_repository(repository).push_targets(repository, &remote);
+        if targets.is_empty() {
+            debug_log(&format!(
+                "git-ai.syncRemotes excludes remote {}; not pushing authorship notes",
+                remote
+            ));
+            return None;
+        }
         debug_log(&format!(
-            "started pushing authorship notes to remote: {}",
-            remote
+            "started pushing authorship notes to remotes: {}",
+            targets.join(", ")
         ));
         // Clone what we need for the background thread
         let global_args = repository.global_args_for_exec();
+        let parent_span = spans::current_context();
 
         crate::observability::spawn_background_flush();
 
@@ -66,9 +66,15 @@ pub fn push_pre_command_hook(
         Some(std::thread::spawn(move || {
             // Recreate repository in the background thread
             if let Ok(repo) = find_repository(&global_args) {
-                if let Err(e) = push_authorship_notes(&repo, &remote) {
-                    debug_log(&format!("authorship push failed: {}", e));
+                for target in &targets {
+                    let result =
+                        spans::with_parent(parent_span, || push_authorship_notes(&repo, target));
+                    if let Err(e) = result {
+                        debug_log(&format!("authorship push to {} failed: {}", target, e));
+                    }
                 }
+                // Remotes whose notes an earlier push left behind
+                spans::with_parent(parent_span, || retry_pending_pushes(&repo, &targets));
             } else {
                 debug_log("failed to open repository for authorship push");
             }
@@ -81,9 +87,9 @@ pub fn push_pre_command_hook(
 }
 
 pub fn push_post_command_hook(
-    _repository: &Repository,
-    _parsed_args: &ParsedGitInvocation,
-    _exit_status: std::process::ExitStatus,
+    repository: &Repository,
+    parsed_args: &ParsedGitInvocation,
+    exit_status: std::process::ExitStatus,
     command_hooks_context: &mut CommandHooksContext,
 ) {
     // Always wait for the authorship push thread to complete if it was started,
@@ -91,10 +97,287 @@ pub fn push_post_command_hook(
     // This ensures proper cleanup of the background thread.
     if let Some(handle) = command_hooks_context.push_authorship_handle.take() {
         let _ = handle.join();
+        warn_pending_pushes(repository);
+    }
+
+    if !exit_status.success() {
+        if should_sync_notes(parsed_args)
+            && let Some(status) = push_without_rejected_notes(
+                repository,
+                parsed_args,
+                command_hooks_context
+                    .git_stderr
+                    .as_deref()
+                    .unwrap_or_default(),
+            )
+        {
+            command_hooks_context.exit_status_override = Some(status);
+        }
+        return;
+    }
+    if !should_sync_notes(parsed_args) {
+        return;
+    }
+    if let Some(remote) = resolve_push_remote(parsed_args, repository) {
+        let targets = NotesSyncPolicy::for_repository(repository).push_targets(repository, &remote);
+        if !targets.is_empty() && may_have_rebased_notes(repository, parsed_args) {
+            retire_rebased_notes_on_remote(repository, &remote, &targets);
+        }
     }
 }
 
-fn extract_remote_from_push_args(args: &[String], known_remotes: &[String]) -> Option<String> {
+/// Notes are only left on pre-rebase commits by a push that rewrites the remote branch, or by
+/// one after a rebase or amend (recorded in the rewrite log by the wrapper or post-rewrite)
+/// whose original commits still carry notes. Anything else skips the reflog scan.
+fn may_have_rebased_notes(repository: &Repository, parsed_args: &ParsedGitInvocation) -> bool {
+    if is_forced_push(parsed_args) {
+        return true;
+    }
+    let rewritten: Vec<String> = repository
+        .storage
+        .read_rewrite_events()
+        .unwrap_or_default()
+        .into_iter()
+        .flat_map(|event| match event {
+            RewriteLogEvent::RebaseComplete { rebase_complete } => rebase_complete.original_commits,
+            RewriteLogEvent::CommitAmend { commit_amend } => vec![commit_amend.original_commit],
+            _ => Vec::new(),
+        })
+        .collect();
+    if rewritten.is_empty() {
+        return false;
+    }
+    match noted_commits(repository) {
+        Ok(noted) => rewritten.iter().any(|sha| noted.contains(sha)),
+        Err(_) => false,
+    }
+}
+
+/// `--force`, `--force-with-lease`, a combined short flag like `-uf`, or a `+` refspec
+fn is_forced_push(parsed_args: &ParsedGitInvocation) -> bool {
+    let forced_flag = parsed_args.command_args.iter().any(|arg| {
+        arg == "--force"
+            || arg.starts_with("--force-with-lease")
+            || (arg.starts_with('-')
+                && !arg.starts_with("--")
+                // `-o` takes the rest of the cluster as its value
+                && arg[1..].chars().take_while(|c| *c != 'o').any(|c| c == 'f'))
+    });
+    forced_flag
+        || (1..)
+            .map_while(|n| parsed_args.pos_command(n))
+            .any(|refspec| refspec.starts_with('+'))
+}
+
+/// After a rewritten branch (e.g. from `pull --rebase`) is pushed, the remote still holds notes
+/// for the pre-rebase commits. Move them onto the rewritten commits and push the cleanup to
+/// every remote in `targets`.
+fn retire_rebased_notes_on_remote(repository: &Repository, remote: &str, targets: &[String]) {
+    let rebased = match find_rebased_notes(repository, remote) {
+        Ok(rebased) if !rebased.is_empty() => rebased,
+        Ok(_) => return,
+        Err(e) => {
+            debug_log(&format!("rebased note detection failed: {}", e));
+            return;
+        }
+    };
+
+    match retire_rebased_notes(repository, &rebased) {
+        Ok(0) => {}
+        Ok(retired) => {
+            debug_log(&format!(
+                "retired {} notes left on pre-rebase commits",
+                retired
+            ));
+            for target in targets {
+                if let Err(e) = push_authorship_notes(repository, target) {
+                    debug_log(&format!(
+                        "authorship push to {} after retiring notes failed: {}",
+                        target, e
+                    ));
+                }
+            }
+        }
+        Err(e) => debug_log(&format!("retiring rebased notes failed: {}", e)),
+    }
+}
+
+/// Whether the push's stderr is needed afterwards, to tell which of its notes refspecs the
+/// remote rejected
+pub fn needs_push_stderr(parsed_args: &ParsedGitInvocation) -> bool {
+    should_sync_notes(parsed_args) && !notes_refspecs(parsed_args).is_empty()
+}
+
+/// Hosts that protect refs/notes/* reject a push naming a notes refspec, and when they check
+/// every ref before accepting any, the branches in it are rejected too. If git reported the
+/// remote rejecting a notes ref the push named, push the rest again without it, queue
+/// refs/notes/ai for a later retry and explain. Returns the retried push's status when it
+/// succeeded.
+fn push_without_rejected_notes(
+    repository: &Repository,
+    parsed_args: &ParsedGitInvocation,
+    push_stderr: &str,
+) -> Option<std::process::ExitStatus> {
+    let notes = notes_refspecs(parsed_args);
+    if notes.is_empty() {
+        return None;
+    }
+    // Without another refspec, git would push the default branch instead
+    let others = (1..)
+        .map_while(|n| parsed_args.pos_command(n))
+        .filter(|refspec| !notes.contains(refspec))
+        .count();
+    let pushes_branches = ["--all", "--branches", "--tags"]
+        .iter()
+        .any(|flag| parsed_args.has_command_flag(flag));
+    if others == 0 && !pushes_branches {
+        return None;
+    }
+
+    let rejected_by_remote = rejected_refs(push_stderr);
+    let rejected: Vec<&String> = notes
+        .iter()
+        .filter(|refspec| rejected_by_remote.contains(&refspec_destination(refspec)))
+        .collect();
+    if rejected.is_empty() {
+        // The remote took the notes or never saw them (e.g. a pre-push hook failed), so
+        // something else failed the push
+        return None;
+    }
+    let remote = resolve_push_remote(parsed_args, repository)?;
+
+    let rejected_refs: Vec<&str> = rejected
+        .iter()
+        .map(|refspec| refspec_destination(refspec))
+        .collect();
+    eprintln!(
+        "git-ai: {} did not accept {}; pushing again without it",
+        remote,
+        rejected_refs.join(", ")
+    );
+    let mut retry = parsed_args.clone();
+    retry.command_args.retain(|arg| !notes.contains(arg));
+    let status = std::process::Command::new(Config::get().git_cmd())
+        .args(retry.to_invocation_vec())
+        .status()
+        .ok()?;
+    if !status.success() {
+        return None;
+    }
+
+    if !pending_pushes(repository)
+        .iter()
+        .any(|pending| pending.remote == remote)
+    {
+        let error =
+            GitAiError::Generic(format!("{} rejected {}", remote, rejected_refs.join(", ")));
+        record_push_outcome(repository, &remote, &Err(error));
+    }
+    eprintln!(
+        "git-ai: hint: {}",
+        RemoteProvider::for_remote(repository, &remote).notes_protection_hint()
+    );
+    eprintln!(
+        "git-ai: authorship notes are queued and will be retried on the next push or fetch, or run `git-ai sync --retry`."
+    );
+    Some(status)
+}
+
+/// The push's refspecs that update a notes ref, as given
+fn notes_refspecs(parsed_args: &ParsedGitInvocation) -> Vec<String> {
+    (1..)
+        .map_while(|n| parsed_args.pos_command(n))
+        .filter(|refspec| refspec_destination(refspec).starts_with("refs/notes/"))
+        .collect()
+}
+
+fn refspec_destination(refspec: &str) -> &str {
+    let refspec = refspec.trim_start_matches('+');
+    refspec
+        .split_once(':')
+        .map_or(refspec, |(_, destination)| destination)
+}
+
+/// Destinations of the refs a push's stderr reports as rejected, from lines such as
+/// ` ! [remote rejected] refs/notes/ai -> refs/notes/ai (pre-receive hook declined)`
+fn rejected_refs(push_stderr: &str) -> Vec<&str> {
+    push_stderr
+        .lines()
+        .filter_map(|line| line.trim_start().strip_prefix("! ["))
+        .filter_map(|line| line.split_once("rejected] "))
+        .filter_map(|(_, refs)| refs.split_once(" -> "))
+        .map(|(_, destination)| {
+            destination
+                .split_once(" (")
+                .map_or(destination, |(destination, _)| destination)
+                .trim()
+        })
+        .collect()
+}
+
+/// Revisions whose commits a push sends: the source side of each refspec, or HEAD without any
+pub(crate) fn pushed_revs(parsed_args: &ParsedGitInvocation) -> Vec<String> {
+    if parsed_args.has_command_flag("--all") || parsed_args.has_command_flag("--branches") {
+        return vec!["--branches".to_string()];
+    }
+    let mut revs = Vec::new();
+    for refspec in (1..).map_while(|n| parsed_args.pos_command(n)) {
+        if refspec == "tag" {
+            continue;
+        }
+        let source = refspec.trim_start_matches('+');
+        let source = source.split_once(':').map_or(source, |(source, _)| source);
+        // An empty source deletes the destination
+        if !source.is_empty() {
+            revs.push(source.to_string());
+        }
+    }
+    if revs.is_empty() && parsed_args.pos_command(1).is_none() {
+        revs.push("HEAD".to_string());
+    }
+    revs
+}
+
+pub(crate) fn should_sync_notes(parsed_args: &ParsedGitInvocation) -> bool {
+    !(is_dry_run(&parsed_args.command_args)
+        || parsed_args
+            .command_args
+            .iter()
+            .any(|a| a == "-d" || a == "--delete")
+        || parsed_args.command_args.iter().any(|a| a == "--mirror"))
+}
+
+pub(crate) fn resolve_push_remote(
+    parsed_args: &ParsedGitInvocation,
+    repository: &Repository,
+) -> Option<String> {
+    let remotes = repository.remotes().ok();
+    let remote_names: Vec<String> = remotes
+        .as_ref()
+        .map(|r| {
+            (0..r.len())
+                .filter_map(|i| r.get(i).map(|s| s.to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // Push authorship refs to the appropriate remote
+    let positional_remote = extract_remote_from_push_args(&parsed_args.command_args, &remote_names);
+
+    let specified_remote = positional_remote.or_else(|| {
+        parsed_args
+            .command_args
+            .iter()
+            .find(|a| remote_names.iter().any(|r| r == *a))
+            .cloned()
+    });
+
+    specified_remote
+        .or_else(|| repository.upstream_remote().ok().flatten())
+        .or_else(|| repository.get_default_remote().ok().flatten())
+}
+
+pub fn extract_remote_from_push_args(args: &[String], known_remotes: &[String]) -> Option<String> {
     let mut i = 0;
     while i < args.len() {
         let arg = &args[i];
@@ -149,3 +432,31 @@ fn option_consumes_separate_value(arg: &str) -> bool {
         "--repo" | "--receive-pack" | "--exec" | "-o" | "--push-option" | "-c" | "-C"
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::cli_parser::parse_git_cli_args;
+
+    fn push(args: &[&str]) -> ParsedGitInvocation {
+        let mut full = vec!["push".to_string()];
+        full.extend(args.iter().map(|s| s.to_string()));
+        parse_git_cli_args(&full)
+    }
+
+    #[test]
+    fn test_is_forced_push() {
+        assert!(is_forced_push(&push(&["--force", "origin", "main"])));
+        assert!(is_forced_push(&push(&[
+            "--force-with-lease=main",
+            "origin"
+        ])));
+        assert!(is_forced_push(&push(&["-uf", "origin", "main"])));
+        assert!(is_forced_push(&push(&["origin", "+main"])));
+        assert!(is_forced_push(&push(&["origin", "+HEAD:refs/heads/main"])));
+
+        assert!(!is_forced_push(&push(&["origin", "main"])));
+        assert!(!is_forced_push(&push(&["-u", "origin", "main"])));
+        assert!(!is_forced_push(&push(&["-ofeature", "origin", "main"])));
+    }
+}
@@ -0,0 +1,55 @@
+use crate::commands::hooks::commit_hooks::get_commit_default_author;
+use crate::git::cli_parser::ParsedGitInvocation;
+use crate::git::repository::Repository;
+use crate::git::rewrite_log::RewriteLogEvent;
+use crate::utils::debug_log;
+
+pub fn pre_revert_hook(repository: &mut Repository) {
+    repository.require_pre_command_head();
+}
+
+/// Run post-commit for each commit the revert made, so their notes record the AI lines they
+/// removed. `--no-commit` and conflicted reverts leave HEAD alone; the `git commit` or
+/// `git revert --continue` that finishes them is handled then.
+pub fn post_revert_hook(
+    parsed_args: &ParsedGitInvocation,
+    exit_status: std::process::ExitStatus,
+    repository: &mut Repository,
+) {
+    if !exit_status.success() {
+        return;
+    }
+    let Some(old_head) = repository.pre_command_base_commit.clone() else {
+        return;
+    };
+    let Some(new_head) = repository.head().ok().and_then(|h| h.target().ok()) else {
+        return;
+    };
+    if old_head == new_head {
+        return;
+    }
+
+    let range = format!("{}..{}", old_head, new_head);
+    let commits = match repository.git(&["rev-list", "--reverse", "--first-parent", &range]) {
+        Ok(output) => output.lines().map(str::to_string).collect::<Vec<_>>(),
+        Err(e) => {
+            debug_log(&format!("revert: failed to list new commits: {}", e));
+            return;
+        }
+    };
+    debug_log(&format!("revert created {} commit(s)", commits.len()));
+
+    let commit_author = get_commit_default_author(repository, &parsed_args.command_args);
+    let mut parent = old_head;
+    for commit in commits {
+        repository.handle_rewrite_log_event(
+            RewriteLogEvent::commit(Some(parent), commit.clone()),
+            commit_author.clone(),
+            true,
+            true,
+        );
+        parent = commit;
+    }
+
+    crate::observability::spawn_background_flush();
+}
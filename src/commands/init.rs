@@ -0,0 +1,385 @@
+//! `git-ai init`: first-time setup in one command.
+//!
+//! Detects installed AI agents and offers to install their hooks, installs the repository hooks
+//! that keep notes on rewritten commits, picks which notes `git log` shows, and enables OTel or
+//! Prometheus export. Repository choices go to `.git/config`; everything else goes to
+//! `~/.git-ai/config.json`. Each question can be answered up front with a flag, and `--yes`
+//! (or a non-interactive stdin) takes the defaults for the rest.
+
+use crate::commands::hook_chain::HookInstallOptions;
+use crate::commands::install_hooks;
+use crate::commands::post_rewrite::install_post_rewrite_hooks;
+use crate::config::{load_file_config_public, save_file_config};
+use crate::error::GitAiError;
+use crate::git::find_repository;
+use crate::git::refs::AI_AUTHORSHIP_REFNAME;
+use crate::git::repository::{Repository, exec_git};
+use crate::mdm::agents::get_all_installers;
+use crate::mdm::hook_installer::HookInstallerParams;
+use crate::mdm::utils::get_current_binary_path;
+use crate::utils::is_interactive_terminal;
+use std::io::{BufRead, Write};
+
+/// Which notes `git log` shows alongside commits. Authorship notes are always written to
+/// refs/notes/ai; this only changes what is displayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotesDisplay {
+    /// The full authorship note, via notes.displayRef=refs/notes/ai
+    Ai,
+    /// A one-line summary mirrored into refs/notes/commits, which git log shows by default
+    Commits,
+    /// Leave git log alone
+    None,
+}
+
+impl NotesDisplay {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "ai" => Some(Self::Ai),
+            "commits" => Some(Self::Commits),
+            "none" | "" => Some(Self::None),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ai => "ai",
+            Self::Commits => "commits",
+            Self::None => "none",
+        }
+    }
+}
+
+/// Answers given on the command line; `None` means ask (or take the default)
+#[derive(Debug, Default)]
+struct InitOptions {
+    yes: bool,
+    dry_run: bool,
+    agents: Option<bool>,
+    repo_hooks: Option<bool>,
+    notes: Option<NotesDisplay>,
+    /// An empty endpoint (`--no-otel`) skips OTel
+    otel_endpoint: Option<String>,
+    prometheus: Option<bool>,
+}
+
+pub fn handle_init(args: &[String]) {
+    let options = parse_args(args);
+    if let Err(e) = run_init(&options) {
+        eprintln!("git-ai init failed: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn print_init_help_and_exit() -> ! {
+    eprintln!("Usage: git-ai init [options]");
+    eprintln!();
+    eprintln!("Set up git-ai for this machine and the current repository.");
+    eprintln!();
+    eprintln!("  --yes, -y                 Don't prompt; use flags below or defaults");
+    eprintln!("  --dry-run                 Show what would change without writing anything");
+    eprintln!("  --agents | --no-agents    Install hooks for detected AI agents (default: yes)");
+    eprintln!(
+        "  --hooks | --no-hooks      Install this repository's post-rewrite hooks (default: yes)"
+    );
+    eprintln!("  --notes-ref <ai|commits|none>");
+    eprintln!("                            What `git log` displays: the full AI note, a summary");
+    eprintln!("                            line, or nothing (default: none). Notes are always");
+    eprintln!("                            stored in refs/notes/ai either way.");
+    eprintln!("  --otel-endpoint <url>     Export metrics to this OTLP endpoint");
+    eprintln!("  --no-otel                 Don't set up OTel export");
+    eprintln!("  --prometheus | --no-prometheus");
+    eprintln!("                            Record Prometheus metrics (default: no)");
+    std::process::exit(0);
+}
+
+fn parse_args(args: &[String]) -> InitOptions {
+    let mut options = InitOptions::default();
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--yes" | "-y" => options.yes = true,
+            "--dry-run" => options.dry_run = true,
+            "--agents" => options.agents = Some(true),
+            "--no-agents" => options.agents = Some(false),
+            "--hooks" => options.repo_hooks = Some(true),
+            "--no-hooks" => options.repo_hooks = Some(false),
+            "--notes-ref" => {
+                let Some(notes) = args.get(i + 1).and_then(|v| NotesDisplay::parse(v)) else {
+                    eprintln!("Error: --notes-ref requires one of ai, commits or none");
+                    std::process::exit(1);
+                };
+                options.notes = Some(notes);
+                i += 1;
+            }
+            "--otel-endpoint" => {
+                let Some(endpoint) = args.get(i + 1) else {
+                    eprintln!("Error: --otel-endpoint requires a URL");
+                    std::process::exit(1);
+                };
+                options.otel_endpoint = Some(endpoint.trim().to_string());
+                i += 1;
+            }
+            "--no-otel" => options.otel_endpoint = Some(String::new()),
+            "--prometheus" => options.prometheus = Some(true),
+            "--no-prometheus" => options.prometheus = Some(false),
+            "--help" | "-h" => print_init_help_and_exit(),
+            other => {
+                eprintln!("Unknown argument: {}", other);
+                std::process::exit(1);
+            }
+        }
+        i += 1;
+    }
+    options
+}
+
+/// Reads answers from stdin, or hands back the default when not interactive
+struct Prompter {
+    interactive: bool,
+}
+
+impl Prompter {
+    fn ask(&self, question: &str, default: &str) -> String {
+        if !self.interactive {
+            return default.to_string();
+        }
+        if default.is_empty() {
+            print!("{} ", question);
+        } else {
+            print!("{} [{}] ", question, default);
+        }
+        let _ = std::io::stdout().flush();
+
+        let mut answer = String::new();
+        match std::io::stdin().lock().read_line(&mut answer) {
+            Ok(0) | Err(_) => default.to_string(),
+            Ok(_) if answer.trim().is_empty() => default.to_string(),
+            Ok(_) => answer.trim().to_string(),
+        }
+    }
+
+    fn confirm(&self, question: &str, default: bool) -> bool {
+        let hint = if default { "Y/n" } else { "y/N" };
+        let answer = self.ask(&format!("{} ({})", question, hint), "");
+        parse_yes_no(&answer, default)
+    }
+}
+
+fn parse_yes_no(answer: &str, default: bool) -> bool {
+    match answer.trim().to_lowercase().as_str() {
+        "y" | "yes" | "true" | "1" => true,
+        "n" | "no" | "false" | "0" => false,
+        _ => default,
+    }
+}
+
+fn run_init(options: &InitOptions) -> Result<(), GitAiError> {
+    let prompter = Prompter {
+        interactive: !options.yes && is_interactive_terminal(),
+    };
+    let repo = find_repository(&Vec::<String>::new()).ok();
+    let mut file_config = load_file_config_public().map_err(GitAiError::Generic)?;
+    // `key: value` lines for the config file changes
+    let mut config_changes: Vec<String> = Vec::new();
+
+    // AI agents
+    if options.agents != Some(false) {
+        let detected = detect_agents()?;
+        if detected.is_empty() {
+            println!("No supported AI agents detected.");
+        } else {
+            println!("Detected AI agents: {}", detected.join(", "));
+            let install = options.agents.unwrap_or_else(|| {
+                prompter.confirm("Install git-ai hooks for these agents?", true)
+            });
+            if install {
+                let mut install_args = Vec::new();
+                if options.dry_run {
+                    install_args.push("--dry-run".to_string());
+                }
+                install_hooks::run(&install_args)?;
+            }
+        }
+    }
+
+    if let Some(repo) = &repo {
+        // Repository hooks
+        let install = options.repo_hooks.unwrap_or_else(|| {
+            prompter.confirm(
+                "Install post-rewrite hooks so plain `git commit --amend`/`rebase` keep AI notes?",
+                true,
+            )
+        });
+        if install && options.dry_run {
+            println!("Would install post-rewrite, post-commit, post-checkout and post-merge hooks");
+        } else if install {
+            // An existing hook of someone else's isn't worth failing the whole wizard over
+            match install_post_rewrite_hooks(&HookInstallOptions::default()) {
+                Ok(hooks) => {
+                    for hook in hooks {
+                        println!("Installed {}", hook);
+                    }
+                }
+                Err(e) => eprintln!("Skipped repository hooks: {}", e),
+            }
+        }
+
+        // Notes shown by git log
+        let notes = match options.notes {
+            Some(notes) => notes,
+            None => loop {
+                let answer = prompter.ask(
+                    "Display AI authorship in `git log`? ai = full notes, commits = one-line summary, none (notes are stored either way)",
+                    NotesDisplay::None.as_str(),
+                );
+                match NotesDisplay::parse(&answer) {
+                    Some(notes) => break notes,
+                    None => println!("Please answer ai, commits or none."),
+                }
+            },
+        };
+        match notes {
+            NotesDisplay::Ai => {
+                let ai_notes_ref = format!("refs/notes/{}", AI_AUTHORSHIP_REFNAME);
+                if !has_display_ref(repo, &ai_notes_ref)? {
+                    if options.dry_run {
+                        println!("Would set notes.displayRef={} in .git/config", ai_notes_ref);
+                    } else {
+                        git_config(repo, &["--add", "notes.displayRef", &ai_notes_ref])?;
+                        println!("Set notes.displayRef={} in .git/config", ai_notes_ref);
+                    }
+                }
+            }
+            NotesDisplay::Commits => {
+                if file_config.mirror_summary_notes != Some(true) {
+                    file_config.mirror_summary_notes = Some(true);
+                    config_changes.push("mirror_summary_notes: true".to_string());
+                }
+            }
+            NotesDisplay::None => {}
+        }
+    } else {
+        println!("Not inside a git repository; skipping repository setup.");
+    }
+
+    // Metrics export
+    let current_endpoint = if file_config.otel_enabled == Some(true) {
+        file_config.otel_endpoint.clone().unwrap_or_default()
+    } else {
+        String::new()
+    };
+    let endpoint = options.otel_endpoint.clone().unwrap_or_else(|| {
+        prompter.ask(
+            "OpenTelemetry collector endpoint for metrics (blank to skip):",
+            &current_endpoint,
+        )
+    });
+    if !endpoint.is_empty() {
+        if file_config.otel_enabled != Some(true)
+            || file_config.otel_endpoint.as_deref() != Some(endpoint.as_str())
+        {
+            config_changes.push("otel_enabled: true".to_string());
+            config_changes.push(format!("otel_endpoint: {}", endpoint));
+            file_config.otel_enabled = Some(true);
+            file_config.otel_endpoint = Some(endpoint);
+        }
+        if !cfg!(feature = "otel") {
+            println!("Note: this git-ai build doesn't include the `otel` feature.");
+        }
+    } else if options.otel_endpoint.is_some() && file_config.otel_enabled == Some(true) {
+        file_config.otel_enabled = Some(false);
+        config_changes.push("otel_enabled: false".to_string());
+    }
+
+    let current_prometheus = file_config.prometheus_enabled.unwrap_or(false);
+    let prometheus = options.prometheus.unwrap_or_else(|| {
+        prompter.confirm(
+            "Record Prometheus metrics (see `git-ai prometheus`)?",
+            current_prometheus,
+        )
+    });
+    if prometheus != current_prometheus {
+        file_config.prometheus_enabled = Some(prometheus);
+        config_changes.push(format!("prometheus_enabled: {}", prometheus));
+    }
+    if prometheus && !cfg!(feature = "prometheus") {
+        println!("Note: this git-ai build doesn't include the `prometheus` feature.");
+    }
+
+    if !config_changes.is_empty() {
+        if options.dry_run {
+            println!("Would update ~/.git-ai/config.json:");
+        } else {
+            save_file_config(&file_config).map_err(GitAiError::Generic)?;
+            println!("Updated ~/.git-ai/config.json:");
+        }
+        for change in &config_changes {
+            println!("  {}", change);
+        }
+    }
+
+    println!("git-ai is set up. Run `git-ai config` to review the settings.");
+    Ok(())
+}
+
+fn detect_agents() -> Result<Vec<String>, GitAiError> {
+    let params = HookInstallerParams {
+        binary_path: get_current_binary_path()?,
+    };
+    Ok(get_all_installers()
+        .iter()
+        .filter(|installer| {
+            installer
+                .check_hooks(&params)
+                .map(|result| result.tool_installed)
+                .unwrap_or(false)
+        })
+        .map(|installer| installer.name().to_string())
+        .collect())
+}
+
+fn has_display_ref(repo: &Repository, notes_ref: &str) -> Result<bool, GitAiError> {
+    let mut args = repo.global_args_for_exec();
+    args.extend(["config", "--get-all", "notes.displayRef"].map(String::from));
+    // Exits 1 when the key isn't set
+    let existing = match exec_git(&args) {
+        Ok(output) => String::from_utf8_lossy(&output.stdout).to_string(),
+        Err(_) => String::new(),
+    };
+    Ok(existing.lines().any(|line| line.trim() == notes_ref))
+}
+
+fn git_config(repo: &Repository, config_args: &[&str]) -> Result<(), GitAiError> {
+    let mut args = repo.global_args_for_exec();
+    args.push("config".to_string());
+    args.extend(config_args.iter().map(|s| s.to_string()));
+    exec_git(&args)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_yes_no() {
+        assert!(parse_yes_no("y", false));
+        assert!(parse_yes_no("YES", false));
+        assert!(!parse_yes_no("n", true));
+        assert!(parse_yes_no("", true));
+        assert!(!parse_yes_no("maybe", false));
+    }
+
+    #[test]
+    fn test_parse_notes_display() {
+        assert_eq!(NotesDisplay::parse("ai"), Some(NotesDisplay::Ai));
+        assert_eq!(
+            NotesDisplay::parse(" Commits "),
+            Some(NotesDisplay::Commits)
+        );
+        assert_eq!(NotesDisplay::parse(""), Some(NotesDisplay::None));
+        assert_eq!(NotesDisplay::parse("refs/notes/ai"), None);
+    }
+}
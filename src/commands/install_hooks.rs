@@ -8,6 +8,8 @@ use crate::mdm::skills_installer;
 use crate::mdm::spinner::{print_diff, Spinner};
 use crate::mdm::utils::{get_current_binary_path, git_shim_path};
 use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
 
 /// Installation status for a tool
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -101,11 +103,38 @@ pub fn to_hashmap(statuses: HashMap<String, InstallStatus>) -> HashMap<String, S
         .collect()
 }
 
+/// Bump whenever the hooks, extensions or skills `install-hooks` writes change, so that
+/// `self-update` reinstalls them after replacing the binary
+pub const HOOKS_TEMPLATE_VERSION: u32 = 1;
+
+fn hooks_template_version_path() -> Option<PathBuf> {
+    crate::config::internal_dir_path().map(|dir| dir.join("hooks_template_version"))
+}
+
+/// The `HOOKS_TEMPLATE_VERSION` of the last install-hooks run that succeeded
+pub fn installed_hooks_template_version() -> Option<u32> {
+    fs::read_to_string(hooks_template_version_path()?)
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+fn record_hooks_template_version() {
+    if let Some(path) = hooks_template_version_path() {
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        let _ = fs::write(path, HOOKS_TEMPLATE_VERSION.to_string());
+    }
+}
+
 /// Main entry point for install-hooks command
 pub fn run(args: &[String]) -> Result<HashMap<String, String>, GitAiError> {
     // Parse flags
     let mut dry_run = false;
     let mut verbose = false;
+    let mut if_template_changed = false;
     for arg in args {
         if arg == "--dry-run" || arg == "--dry-run=true" {
             dry_run = true;
@@ -113,6 +142,15 @@ pub fn run(args: &[String]) -> Result<HashMap<String, String>, GitAiError> {
         if arg == "--verbose" || arg == "-v" {
             verbose = true;
         }
+        if arg == "--if-template-changed" {
+            if_template_changed = true;
+        }
+    }
+
+    if if_template_changed && installed_hooks_template_version() == Some(HOOKS_TEMPLATE_VERSION)
+    {
+        eprintln!("Hooks are up to date");
+        return Ok(HashMap::new());
     }
 
     // Get absolute path to the current binary
@@ -121,6 +159,9 @@ pub fn run(args: &[String]) -> Result<HashMap<String, String>, GitAiError> {
 
     // Run async operations with smol and convert result
     let statuses = smol::block_on(async_run_install(&params, dry_run, verbose))?;
+    if !dry_run && !statuses.values().any(|s| *s == InstallStatus::Failed) {
+        record_hooks_template_version();
+    }
 
     // Spawn background processes to flush metrics
     crate::observability::spawn_background_flush();
@@ -0,0 +1,165 @@
+//! Machine-readable output shared by commands.
+//!
+//! `--format json` (or `--json`) prints one JSON object whose first key is `schema_version`.
+//! Fields are only ever added within a version; renaming or removing one bumps
+//! [`OUTPUT_SCHEMA_VERSION`]. `--porcelain` prints the same object flattened to one
+//! `key<TAB>value` line per scalar, with dotted paths for nested fields and array indices in
+//! sorted key order, so shell scripts can consume it with `grep` and `cut`. Neither format
+//! includes colors or emoji.
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// Version of every command's JSON and porcelain schema, documented in docs/machine-output.md
+pub const OUTPUT_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+    Porcelain,
+}
+
+impl OutputFormat {
+    /// Parse a `--format` value
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "text" => Some(OutputFormat::Text),
+            "json" => Some(OutputFormat::Json),
+            "porcelain" => Some(OutputFormat::Porcelain),
+            _ => None,
+        }
+    }
+
+    /// Parse the value following `--format` at `args[i]`, exiting with an error if it's missing
+    /// or unknown
+    pub fn from_flag_value(args: &[String], i: usize) -> Self {
+        let value = args.get(i + 1).map(String::as_str);
+        match value.and_then(OutputFormat::parse) {
+            Some(format) => format,
+            None => {
+                eprintln!(
+                    "Error: --format must be text, json or porcelain (got {})",
+                    value.unwrap_or("nothing")
+                );
+                std::process::exit(1);
+            }
+        }
+    }
+
+    pub fn is_text(&self) -> bool {
+        *self == OutputFormat::Text
+    }
+}
+
+/// `data` with `schema_version` as its first field. `data` must serialize as an object.
+#[derive(Serialize)]
+pub struct Versioned<'a, T: Serialize> {
+    schema_version: u32,
+    #[serde(flatten)]
+    data: &'a T,
+}
+
+pub fn versioned<T: Serialize>(data: &T) -> Versioned<'_, T> {
+    Versioned {
+        schema_version: OUTPUT_SCHEMA_VERSION,
+        data,
+    }
+}
+
+pub fn to_json<T: Serialize>(data: &T) -> Result<String, serde_json::Error> {
+    serde_json::to_string(&versioned(data))
+}
+
+/// `schema_version` first, then the data's keys in sorted order
+pub fn to_porcelain<T: Serialize>(data: &T) -> Result<String, serde_json::Error> {
+    let mut lines = vec![format!("schema_version\t{}", OUTPUT_SCHEMA_VERSION)];
+    flatten("", &serde_json::to_value(data)?, &mut lines);
+    Ok(lines.join("\n"))
+}
+
+/// Print `data` as JSON or porcelain. Text output is the caller's job.
+pub fn print<T: Serialize>(format: OutputFormat, data: &T) -> Result<(), serde_json::Error> {
+    match format {
+        OutputFormat::Text => {}
+        OutputFormat::Json => println!("{}", to_json(data)?),
+        OutputFormat::Porcelain => println!("{}", to_porcelain(data)?),
+    }
+    Ok(())
+}
+
+/// One line per scalar. Nulls, empty arrays and empty objects produce no lines.
+fn flatten(prefix: &str, value: &Value, lines: &mut Vec<String>) {
+    let key = |child: &str| {
+        if prefix.is_empty() {
+            child.to_string()
+        } else {
+            format!("{}.{}", prefix, child)
+        }
+    };
+    match value {
+        Value::Null => {}
+        Value::Object(map) => {
+            for (child, value) in map {
+                flatten(&key(child), value, lines);
+            }
+        }
+        Value::Array(items) => {
+            for (index, value) in items.iter().enumerate() {
+                flatten(&key(&index.to_string()), value, lines);
+            }
+        }
+        Value::String(s) => lines.push(format!("{}\t{}", prefix, escape(s))),
+        Value::Bool(_) | Value::Number(_) => lines.push(format!("{}\t{}", prefix, value)),
+    }
+}
+
+/// Keep every record on one line
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_json_leads_with_schema_version() {
+        let data = json!({"ai_additions": 3, "files": ["a.rs"]});
+        let rendered = to_json(&data).unwrap();
+        assert!(
+            rendered.starts_with("{\"schema_version\":1,"),
+            "{}",
+            rendered
+        );
+        let parsed: Value = serde_json::from_str(&rendered).unwrap();
+        assert_eq!(parsed["ai_additions"], 3);
+    }
+
+    #[test]
+    fn test_porcelain_flattens_and_escapes() {
+        let data = json!({
+            "remotes": [{"remote": "origin", "error": null}, {"remote": "up\tstream"}],
+            "ok": false,
+            "message": "line one\nline two",
+            "empty": [],
+        });
+        assert_eq!(
+            to_porcelain(&data).unwrap(),
+            [
+                "schema_version\t1",
+                "message\tline one\\nline two",
+                "ok\tfalse",
+                "remotes.0.remote\torigin",
+                "remotes.1.remote\tup\\tstream",
+            ]
+            .join("\n")
+        );
+    }
+}
@@ -0,0 +1,61 @@
+use crate::mcp::McpServer;
+use std::path::PathBuf;
+
+pub fn handle_mcp(args: &[String]) {
+    let mut repo: Option<PathBuf> = None;
+    let mut agent: Option<String> = None;
+    let mut model: Option<String> = None;
+
+    let mut i = 0;
+    while i < args.len() {
+        let value = args.get(i + 1).cloned();
+        match args[i].as_str() {
+            "--repo" | "--agent" | "--model" if value.is_none() => {
+                eprintln!("{} requires a value", args[i]);
+                std::process::exit(1);
+            }
+            "--repo" => {
+                repo = value.map(PathBuf::from);
+                i += 1;
+            }
+            "--agent" => {
+                agent = value;
+                i += 1;
+            }
+            "--model" => {
+                model = value;
+                i += 1;
+            }
+            "--help" | "-h" => print_mcp_help_and_exit(),
+            arg => {
+                eprintln!("Unknown mcp argument: {}", arg);
+                std::process::exit(1);
+            }
+        }
+        i += 1;
+    }
+
+    let repo = repo
+        .or_else(|| std::env::current_dir().ok())
+        .unwrap_or_else(|| PathBuf::from("."));
+    let mut server = McpServer::new(repo, agent, model);
+    let stdin = std::io::stdin();
+    if let Err(e) = server.serve(stdin.lock(), std::io::stdout().lock()) {
+        eprintln!("git-ai mcp: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn print_mcp_help_and_exit() -> ! {
+    eprintln!("Usage: git-ai mcp [--repo <dir>] [--agent <name>] [--model <model>]");
+    eprintln!();
+    eprintln!("Runs a Model Context Protocol server on stdin/stdout. Agents call its tools to");
+    eprintln!("register checkpoints, declare the edits they made and read current attribution.");
+    eprintln!();
+    eprintln!(
+        "  --repo <dir>      Repository tools act on by default (default: current directory)"
+    );
+    eprintln!("  --agent <name>    Agent name for declared edits (default: the client's name)");
+    eprintln!("  --model <model>   Model for declared edits when the call doesn't give one");
+    std::process::exit(0);
+}
@@ -0,0 +1,98 @@
+use crate::authorship::authorship_log_serialization::AUTHORSHIP_LOG_VERSION;
+use crate::authorship::note_schema::migrate_notes;
+use crate::commands::machine_output::{self, OutputFormat};
+use crate::git::find_repository;
+
+pub fn handle_migrate_notes(args: &[String]) {
+    let mut dry_run = false;
+    let mut format = OutputFormat::Text;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--dry-run" | "-n" => dry_run = true,
+            "--json" => format = OutputFormat::Json,
+            "--porcelain" => format = OutputFormat::Porcelain,
+            "--format" => {
+                format = OutputFormat::from_flag_value(args, i);
+                i += 1;
+            }
+            "--help" | "-h" => print_migrate_notes_help_and_exit(),
+            arg => {
+                eprintln!("Unknown migrate-notes argument: {}", arg);
+                std::process::exit(1);
+            }
+        }
+        i += 1;
+    }
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let report = match migrate_notes(&repo, dry_run) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Failed to migrate notes: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if !format.is_text() {
+        machine_output::print(format, &report).unwrap();
+        return;
+    }
+
+    let verb = if dry_run { "Would migrate" } else { "Migrated" };
+    for note in &report.migrated {
+        let from = if note.from.is_empty() {
+            "no schema_version"
+        } else {
+            note.from.as_str()
+        };
+        println!(
+            "{} {} ({} -> {})",
+            verb,
+            &note.commit[..note.commit.len().min(12)],
+            from,
+            AUTHORSHIP_LOG_VERSION
+        );
+    }
+    for note in &report.skipped {
+        eprintln!(
+            "Skipped {}: {}",
+            &note.commit[..note.commit.len().min(12)],
+            note.error.as_deref().unwrap_or("unknown error")
+        );
+    }
+    println!(
+        "{} {} of {} note{}, {} up to date, {} skipped",
+        verb,
+        report.migrated.len(),
+        report.checked,
+        if report.checked == 1 { "" } else { "s" },
+        report.up_to_date,
+        report.skipped.len()
+    );
+    if !dry_run && !report.migrated.is_empty() {
+        println!("Push refs/notes/ai (e.g. `git-ai sync`) to share the migrated notes");
+    }
+}
+
+fn print_migrate_notes_help_and_exit() -> ! {
+    eprintln!("Usage: git-ai migrate-notes [--dry-run] [--format <fmt>]");
+    eprintln!();
+    eprintln!(
+        "Rewrites authorship notes written with an older schema to {}.",
+        AUTHORSHIP_LOG_VERSION
+    );
+    eprintln!("Notes from a newer git-ai, or that don't parse, are left alone.");
+    eprintln!();
+    eprintln!("  -n, --dry-run    List the notes that would be rewritten without changing them");
+    eprintln!("  --format <fmt>   text (default), json or porcelain");
+    std::process::exit(0);
+}
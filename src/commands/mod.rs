@@ -1,27 +1,63 @@
+pub mod annotate_export;
+pub mod annotate_pr;
+pub mod backfill;
+pub mod bench;
 pub mod blame;
+pub mod branches;
+pub mod bundle;
 pub mod checkpoint;
 pub mod checkpoint_agent;
 pub mod ci_handlers;
+pub mod compare;
 pub mod config;
+pub mod config_bundle;
+pub mod daemon;
+pub mod db;
 pub mod diff;
 pub mod exchange_nonce;
+pub mod export;
 pub mod flush_cas;
 pub mod flush_logs;
 pub mod flush_metrics_db;
+pub mod gc;
 pub mod git_ai_handlers;
 pub mod git_handlers;
+pub mod github_app;
+pub mod hook_chain;
 pub mod hooks;
+pub mod init;
 pub mod install_hooks;
 pub mod login;
 pub mod logout;
+pub mod machine_output;
+pub mod mcp;
+pub mod migrate_notes;
+pub mod notes_merge_driver;
+pub mod ownership;
 pub mod personal_dashboard;
+pub mod policy;
+pub mod post_rewrite;
+pub mod prepare_commit_msg;
+pub mod prometheus;
 pub mod prompt_picker;
 pub mod prompts_db;
+pub mod reporting;
+pub mod self_update;
+pub mod server_hook;
 pub mod share;
 pub mod share_tui;
 pub mod show;
+pub mod simulate;
 pub mod show_prompt;
+pub mod stats_report;
+pub mod stats_reverts;
 pub mod status;
 pub mod squash_authorship;
+pub mod sync;
 pub mod sync_prompts;
+pub mod top;
+pub mod trace;
+pub mod uninstall;
 pub mod upgrade;
+pub mod verify;
+pub mod workspace;
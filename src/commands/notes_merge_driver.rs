@@ -0,0 +1,112 @@
+use crate::git::find_repository;
+use crate::git::notes_merge::{
+    NotesMergeOutcome, ai_notes_merge_in_progress, expand_notes_ref, merge_staged_notes,
+    union_conflicted_notes,
+};
+use crate::git::refs::AI_AUTHORSHIP_REFNAME;
+
+pub fn handle_notes_merge_driver(args: &[String]) {
+    let mut notes_ref: Option<String> = None;
+
+    for arg in args {
+        match arg.as_str() {
+            "--help" | "-h" => print_notes_merge_driver_help_and_exit(),
+            other if other.starts_with('-') => {
+                eprintln!("Unknown notes-merge-driver argument: {}", other);
+                std::process::exit(1);
+            }
+            other if notes_ref.is_none() => notes_ref = Some(other.to_string()),
+            other => {
+                eprintln!("Unexpected argument: {}", other);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let local_ref = format!("refs/notes/{}", AI_AUTHORSHIP_REFNAME);
+
+    let Some(notes_ref) = notes_ref else {
+        // Finish a `git notes --ref=ai merge` that stopped on conflicts
+        if !ai_notes_merge_in_progress(&repo) {
+            eprintln!(
+                "No conflicted merge of {} in progress; pass the notes ref to merge",
+                local_ref
+            );
+            std::process::exit(1);
+        }
+        let resolved = match union_conflicted_notes(&repo) {
+            Ok(resolved) => resolved,
+            Err(e) => {
+                eprintln!("Failed to resolve conflicted notes: {}", e);
+                std::process::exit(1);
+            }
+        };
+        let committed = repo.git(&[
+            "notes",
+            &format!("--ref={}", AI_AUTHORSHIP_REFNAME),
+            "merge",
+            "--commit",
+        ]);
+        if let Err(e) = committed {
+            eprintln!("Failed to commit the notes merge: {}", e);
+            std::process::exit(1);
+        }
+        println!(
+            "Resolved {} conflicting note{} and committed the merge into {}",
+            resolved,
+            if resolved == 1 { "" } else { "s" },
+            local_ref
+        );
+        return;
+    };
+
+    if ai_notes_merge_in_progress(&repo) {
+        eprintln!(
+            "A merge of {} is in progress; run `git-ai notes-merge-driver` without arguments to finish it",
+            local_ref
+        );
+        std::process::exit(1);
+    }
+    let source = expand_notes_ref(&notes_ref);
+    match merge_staged_notes(&repo, &source) {
+        Ok(NotesMergeOutcome::UpToDate) => println!("Already up to date"),
+        Ok(NotesMergeOutcome::Initialized) => {
+            println!("{} did not exist; it now points at {}", local_ref, source)
+        }
+        Ok(NotesMergeOutcome::FastForward) => {
+            println!("Fast-forwarded {} to {}", local_ref, source)
+        }
+        Ok(NotesMergeOutcome::Merged { unioned }) => println!(
+            "Merged {} into {} ({} note{} combined)",
+            source,
+            local_ref,
+            unioned,
+            if unioned == 1 { "" } else { "s" }
+        ),
+        Err(e) => {
+            eprintln!("Failed to merge {} into {}: {}", source, local_ref, e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn print_notes_merge_driver_help_and_exit() -> ! {
+    eprintln!("Usage: git-ai notes-merge-driver [<notes-ref>]");
+    eprintln!();
+    eprintln!("Merge authorship notes into refs/notes/ai, combining the AI-line attributions of");
+    eprintln!("notes both sides changed instead of stopping on textual conflicts.");
+    eprintln!();
+    eprintln!("  <notes-ref>           Notes ref to merge in (e.g. ai-backup or refs/notes/other)");
+    eprintln!("  (no arguments)        Resolve and commit a conflicted `git notes --ref=ai merge`");
+    eprintln!();
+    eprintln!("Through the git-ai wrapper, `git notes --ref=ai merge <ref>` uses this merge");
+    eprintln!("automatically unless a strategy is given with -s.");
+    std::process::exit(0);
+}
@@ -0,0 +1,484 @@
+use crate::authorship::codeowners::CodeOwners;
+use crate::commands::machine_output::{self, OutputFormat};
+use crate::commands::reporting::percent;
+use crate::commands::stats_report::{ReportGrouping, ReportOptions, lines_by_file};
+use crate::error::GitAiError;
+use crate::git::find_repository;
+use crate::git::repository::Repository;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+/// Owner key for files no CODEOWNERS rule assigns
+const UNOWNED: &str = "(unowned)";
+const DEFAULT_DEPTH: usize = 2;
+
+#[derive(Debug, Clone)]
+pub struct OwnershipOptions {
+    pub report: ReportOptions,
+    /// Directory levels kept when grouping files into areas; None lists every file
+    pub depth: Option<usize>,
+    /// Rows with fewer lines than this are left out of the listing (but not the totals)
+    pub min_lines: u64,
+    /// CODEOWNERS file to read from disk instead of looking it up in the revision
+    pub codeowners_file: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct AreaRow {
+    pub path: String,
+    /// Owners of any file in the area
+    pub owners: Vec<String>,
+    pub files: usize,
+    pub ai_lines: u64,
+    pub human_lines: u64,
+    pub ai_percent: f64,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct OwnerRow {
+    pub owner: String,
+    pub files: usize,
+    pub ai_lines: u64,
+    pub human_lines: u64,
+    pub ai_percent: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OwnershipReport {
+    pub rev: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<String>,
+    /// CODEOWNERS file the owners came from, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub codeowners: Option<String>,
+    pub files: usize,
+    pub ai_lines: u64,
+    pub human_lines: u64,
+    pub ai_percent: f64,
+    /// Highest AI share first
+    pub owners: Vec<OwnerRow>,
+    /// Highest AI share first
+    pub areas: Vec<AreaRow>,
+}
+
+pub fn handle_ownership(args: &[String]) {
+    let mut options = OwnershipOptions {
+        report: ReportOptions {
+            rev: "HEAD".to_string(),
+            since: None,
+            until: None,
+            by: ReportGrouping::Directory,
+            include_bots: false,
+        },
+        depth: Some(DEFAULT_DEPTH),
+        min_lines: 0,
+        codeowners_file: None,
+    };
+    let mut format = OutputFormat::Text;
+
+    let mut i = 0;
+    while i < args.len() {
+        let value = args.get(i + 1);
+        match args[i].as_str() {
+            "--since" | "--until" | "--depth" | "--min-lines" | "--codeowners"
+                if value.is_none() =>
+            {
+                eprintln!("{} requires a value", args[i]);
+                std::process::exit(1);
+            }
+            "--since" => {
+                options.report.since = value.cloned();
+                i += 2;
+            }
+            "--until" => {
+                options.report.until = value.cloned();
+                i += 2;
+            }
+            "--depth" => {
+                options.depth = match value.map(|v| v.parse::<usize>()) {
+                    Some(Ok(depth)) if depth > 0 => Some(depth),
+                    _ => {
+                        eprintln!("--depth requires a positive number of directory levels");
+                        std::process::exit(1);
+                    }
+                };
+                i += 2;
+            }
+            "--files" => {
+                options.depth = None;
+                i += 1;
+            }
+            "--min-lines" => {
+                options.min_lines = match value.map(|v| v.parse::<u64>()) {
+                    Some(Ok(min_lines)) => min_lines,
+                    _ => {
+                        eprintln!("--min-lines requires a number of lines");
+                        std::process::exit(1);
+                    }
+                };
+                i += 2;
+            }
+            "--codeowners" => {
+                options.codeowners_file = value.cloned();
+                i += 2;
+            }
+            "--include-bots" => {
+                options.report.include_bots = true;
+                i += 1;
+            }
+            "--format" => {
+                format = OutputFormat::from_flag_value(args, i);
+                i += 2;
+            }
+            "--json" => {
+                format = OutputFormat::Json;
+                i += 1;
+            }
+            "--porcelain" => {
+                format = OutputFormat::Porcelain;
+                i += 1;
+            }
+            "--help" | "-h" => print_ownership_help_and_exit(),
+            arg if !arg.starts_with('-') => {
+                options.report.rev = arg.to_string();
+                i += 1;
+            }
+            other => {
+                eprintln!("Unknown ownership argument: {}", other);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let report = match build_ownership_report(&repo, &options) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Ownership report failed: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if format.is_text() {
+        print_ownership_table(&report);
+    } else {
+        machine_output::print(format, &report).unwrap();
+    }
+}
+
+fn print_ownership_help_and_exit() -> ! {
+    eprintln!("Usage: git-ai ownership [<rev>] [options]");
+    eprintln!();
+    eprintln!("Break down AI vs human lines in the files present at <rev> (default: HEAD) by");
+    eprintln!("directory and by CODEOWNERS owner, highest AI share first.");
+    eprintln!();
+    eprintln!("  --since <date>        Only count commits after this date");
+    eprintln!("  --until <date>        Only count commits before this date");
+    eprintln!("  --depth <n>           Directory levels per area (default: 2)");
+    eprintln!("  --files               One area per file");
+    eprintln!("  --min-lines <n>       Hide areas and owners with fewer lines");
+    eprintln!("  --codeowners <file>   Read owners from this file instead of the repository's");
+    eprintln!("  --include-bots        Count commits by bot_authors identities");
+    eprintln!("  --format <fmt>        text (default), json or porcelain");
+    std::process::exit(0);
+}
+
+pub fn build_ownership_report(
+    repo: &Repository,
+    options: &OwnershipOptions,
+) -> Result<OwnershipReport, GitAiError> {
+    let (codeowners_path, codeowners) = match &options.codeowners_file {
+        Some(file) => {
+            let contents = std::fs::read_to_string(file)
+                .map_err(|e| GitAiError::Generic(format!("Cannot read {}: {}", file, e)))?;
+            (Some(file.clone()), CodeOwners::parse(&contents))
+        }
+        None => match CodeOwners::load(repo, &options.report.rev)? {
+            Some((path, codeowners)) => (Some(path), codeowners),
+            None => (None, CodeOwners::default()),
+        },
+    };
+
+    // Only files that still exist: deleted and renamed-away paths own nothing
+    let present = files_at(repo, &options.report.rev)?;
+    let mut lines = lines_by_file(repo, &options.report)?;
+    lines.retain(|path, _| present.contains(path));
+
+    let mut report = aggregate_ownership(&lines, &codeowners, options.depth, options.min_lines);
+    report.rev = options.report.rev.clone();
+    report.since = options.report.since.clone();
+    report.until = options.report.until.clone();
+    report.codeowners = codeowners_path;
+    Ok(report)
+}
+
+fn files_at(repo: &Repository, rev: &str) -> Result<HashSet<String>, GitAiError> {
+    let output = repo.git(&["ls-tree", "-r", "-z", "--name-only", rev])?;
+    Ok(output
+        .split('\0')
+        .filter(|path| !path.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+#[derive(Default)]
+struct Totals {
+    owners: BTreeSet<String>,
+    files: usize,
+    ai_lines: u64,
+    human_lines: u64,
+}
+
+impl Totals {
+    fn add(&mut self, ai: u64, human: u64) {
+        self.files += 1;
+        self.ai_lines += ai;
+        self.human_lines += human;
+    }
+}
+
+fn aggregate_ownership(
+    lines: &BTreeMap<String, (u64, u64)>,
+    codeowners: &CodeOwners,
+    depth: Option<usize>,
+    min_lines: u64,
+) -> OwnershipReport {
+    let mut areas: BTreeMap<String, Totals> = BTreeMap::new();
+    let mut owners: BTreeMap<String, Totals> = BTreeMap::new();
+    let (mut total_ai, mut total_human) = (0, 0);
+
+    for (path, &(ai, human)) in lines {
+        total_ai += ai;
+        total_human += human;
+
+        let file_owners = codeowners.owners_of(path);
+        let area = areas.entry(area_for(path, depth)).or_default();
+        area.add(ai, human);
+        area.owners.extend(file_owners.iter().cloned());
+
+        if file_owners.is_empty() {
+            owners
+                .entry(UNOWNED.to_string())
+                .or_default()
+                .add(ai, human);
+        }
+        // A file with several owners counts in full for each of them
+        for owner in file_owners {
+            owners.entry(owner.clone()).or_default().add(ai, human);
+        }
+    }
+
+    let shown = |totals: &Totals| totals.ai_lines + totals.human_lines >= min_lines;
+    let mut area_rows: Vec<AreaRow> = areas
+        .into_iter()
+        .filter(|(_, totals)| shown(totals))
+        .map(|(path, totals)| AreaRow {
+            path,
+            owners: totals.owners.into_iter().collect(),
+            files: totals.files,
+            ai_lines: totals.ai_lines,
+            human_lines: totals.human_lines,
+            ai_percent: percent(totals.ai_lines, totals.human_lines),
+        })
+        .collect();
+    area_rows.sort_by(|a, b| {
+        b.ai_percent
+            .total_cmp(&a.ai_percent)
+            .then(b.ai_lines.cmp(&a.ai_lines))
+            .then(a.path.cmp(&b.path))
+    });
+
+    let mut owner_rows: Vec<OwnerRow> = owners
+        .into_iter()
+        .filter(|(_, totals)| shown(totals))
+        .map(|(owner, totals)| OwnerRow {
+            owner,
+            files: totals.files,
+            ai_lines: totals.ai_lines,
+            human_lines: totals.human_lines,
+            ai_percent: percent(totals.ai_lines, totals.human_lines),
+        })
+        .collect();
+    owner_rows.sort_by(|a, b| {
+        b.ai_percent
+            .total_cmp(&a.ai_percent)
+            .then(b.ai_lines.cmp(&a.ai_lines))
+            .then(a.owner.cmp(&b.owner))
+    });
+
+    OwnershipReport {
+        rev: String::new(),
+        since: None,
+        until: None,
+        codeowners: None,
+        files: lines.len(),
+        ai_lines: total_ai,
+        human_lines: total_human,
+        ai_percent: percent(total_ai, total_human),
+        owners: owner_rows,
+        areas: area_rows,
+    }
+}
+
+/// The first `depth` directories of `path` (`src/commands/`), `.` for top-level files, or the
+/// path itself when listing files
+fn area_for(path: &str, depth: Option<usize>) -> String {
+    let Some(depth) = depth else {
+        return path.to_string();
+    };
+    let dirs: Vec<&str> = path.split('/').collect();
+    let dirs = &dirs[..dirs.len() - 1];
+    if dirs.is_empty() {
+        return ".".to_string();
+    }
+    format!("{}/", dirs[..dirs.len().min(depth)].join("/"))
+}
+
+fn print_ownership_table(report: &OwnershipReport) {
+    if report.files == 0 {
+        println!("No lines added by these commits remain in {}.", report.rev);
+        return;
+    }
+    match &report.codeowners {
+        Some(path) => println!("Owners from {}", path),
+        None => println!("No CODEOWNERS file found; every file is unowned"),
+    }
+    println!();
+
+    let owner_width = report
+        .owners
+        .iter()
+        .map(|row| row.owner.chars().count())
+        .chain(["OWNER".len()])
+        .max()
+        .unwrap_or(0);
+    println!(
+        "{:<width$}  {:>5}  {:>9}  {:>11}  {:>6}",
+        "OWNER",
+        "FILES",
+        "AI LINES",
+        "HUMAN LINES",
+        "AI %",
+        width = owner_width
+    );
+    for row in &report.owners {
+        println!(
+            "{:<width$}  {:>5}  {:>9}  {:>11}  {:>5.1}%",
+            row.owner,
+            row.files,
+            row.ai_lines,
+            row.human_lines,
+            row.ai_percent,
+            width = owner_width
+        );
+    }
+    println!();
+
+    let path_width = report
+        .areas
+        .iter()
+        .map(|row| row.path.chars().count())
+        .chain(["TOTAL".len()])
+        .max()
+        .unwrap_or(0);
+    println!(
+        "{:<width$}  {:>5}  {:>9}  {:>11}  {:>6}  OWNERS",
+        "AREA",
+        "FILES",
+        "AI LINES",
+        "HUMAN LINES",
+        "AI %",
+        width = path_width
+    );
+    for row in &report.areas {
+        println!(
+            "{:<width$}  {:>5}  {:>9}  {:>11}  {:>5.1}%  {}",
+            row.path,
+            row.files,
+            row.ai_lines,
+            row.human_lines,
+            row.ai_percent,
+            if row.owners.is_empty() {
+                UNOWNED.to_string()
+            } else {
+                row.owners.join(" ")
+            },
+            width = path_width
+        );
+    }
+    println!(
+        "{:<width$}  {:>5}  {:>9}  {:>11}  {:>5.1}%",
+        "TOTAL",
+        report.files,
+        report.ai_lines,
+        report.human_lines,
+        report.ai_percent,
+        width = path_width
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_area_for_depth() {
+        assert_eq!(area_for("README.md", Some(2)), ".");
+        assert_eq!(area_for("src/lib.rs", Some(2)), "src/");
+        assert_eq!(area_for("src/commands/top.rs", Some(2)), "src/commands/");
+        assert_eq!(area_for("src/commands/hooks/a.rs", Some(1)), "src/");
+        assert_eq!(area_for("src/commands/top.rs", None), "src/commands/top.rs");
+    }
+
+    #[test]
+    fn test_aggregate_ownership_ranks_by_ai_share() {
+        let codeowners = CodeOwners::parse("* @org/all\n/api/ @org/api @ana\n/vendor/\n");
+        let lines: BTreeMap<String, (u64, u64)> = [
+            ("api/routes.rs", (8, 2)),
+            ("api/models.rs", (2, 8)),
+            ("web/app.ts", (1, 9)),
+            ("vendor/lib.js", (0, 3)),
+        ]
+        .into_iter()
+        .map(|(path, counts)| (path.to_string(), counts))
+        .collect();
+
+        let report = aggregate_ownership(&lines, &codeowners, Some(1), 0);
+        assert_eq!(
+            (report.ai_lines, report.human_lines, report.files),
+            (11, 22, 4)
+        );
+
+        let areas: Vec<(&str, f64)> = report
+            .areas
+            .iter()
+            .map(|row| (row.path.as_str(), row.ai_percent))
+            .collect();
+        assert_eq!(areas, [("api/", 50.0), ("web/", 10.0), ("vendor/", 0.0)]);
+        assert_eq!(report.areas[0].owners, ["@ana", "@org/api"]);
+        assert!(report.areas[2].owners.is_empty());
+
+        let owners: Vec<(&str, usize)> = report
+            .owners
+            .iter()
+            .map(|row| (row.owner.as_str(), row.files))
+            .collect();
+        assert_eq!(
+            owners,
+            [("@ana", 2), ("@org/api", 2), ("@org/all", 1), (UNOWNED, 1)]
+        );
+
+        let report = aggregate_ownership(&lines, &codeowners, Some(1), 5);
+        assert_eq!(report.areas.len(), 2);
+        assert_eq!(report.ai_lines, 11);
+    }
+}
@@ -0,0 +1,367 @@
+//! `git-ai policy check`: evaluate `policy.toml` outside the hooks.
+//!
+//! The commit and push hooks only check what is being committed or pushed on the developer's
+//! machine. In CI, `policy check` evaluates the push rules against a range of commits, and
+//! `--format sarif` reports each violation against the file and line that broke the rule, so
+//! GitHub code scanning can annotate the pull request.
+
+use crate::authorship::policy::{
+    Enforcement, POLICY_FILE, Policy, PolicyHook, Violation, ViolationKind, evaluate_commits,
+    evaluate_staged,
+};
+use crate::ci::sarif::{SarifFinding, SarifLevel, SarifLog, SarifRule};
+use crate::commands::machine_output;
+use crate::commands::verify::rev_list_args;
+use crate::error::GitAiError;
+use crate::git::find_repository;
+use crate::git::repository::Repository;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyFormat {
+    Text,
+    Json,
+    Porcelain,
+    Sarif,
+}
+
+#[derive(Debug, Clone)]
+pub struct PolicyReport {
+    /// False when the repository has no policy file
+    pub has_policy: bool,
+    /// "staged changes" or the number of commits checked
+    pub checked: String,
+    pub violations: Vec<Violation>,
+}
+
+impl PolicyReport {
+    pub fn passed(&self) -> bool {
+        !self
+            .violations
+            .iter()
+            .any(|v| v.enforcement == Enforcement::Block)
+    }
+}
+
+pub fn policy_rules() -> Vec<SarifRule> {
+    vec![
+        SarifRule::new(
+            ViolationKind::MaxAiPercent.rule_id(),
+            "AiShareAboveLimit",
+            "More of the added lines are AI-generated than the policy allows",
+            SarifLevel::Error,
+        ),
+        SarifRule::new(
+            ViolationKind::DeniedTool.rule_id(),
+            "DeniedAiTool",
+            "Lines were written by an AI tool the policy denies for these paths",
+            SarifLevel::Error,
+        ),
+        SarifRule::new(
+            ViolationKind::DeniedModel.rule_id(),
+            "DeniedAiModel",
+            "Lines were written by a model the policy denies for these paths",
+            SarifLevel::Error,
+        ),
+        SarifRule::new(
+            ViolationKind::ReviewRequired.rule_id(),
+            "AiReviewRequired",
+            "Commit with AI-generated lines lacks the human review trailer",
+            SarifLevel::Error,
+        ),
+    ]
+}
+
+pub fn handle_policy(args: &[String]) {
+    match args.first().map(String::as_str) {
+        Some("check") => handle_policy_check(&args[1..]),
+        Some("--help") | Some("-h") | Some("help") | None => print_policy_help_and_exit(),
+        Some(other) => {
+            eprintln!("Unknown policy subcommand: {}", other);
+            print_policy_help_and_exit();
+        }
+    }
+}
+
+fn handle_policy_check(args: &[String]) {
+    let mut format = PolicyFormat::Text;
+    let mut output: Option<String> = None;
+    let mut range: Option<String> = None;
+    let mut staged = false;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--format" => {
+                format = match args.get(i + 1).map(String::as_str) {
+                    Some("text") => PolicyFormat::Text,
+                    Some("json") => PolicyFormat::Json,
+                    Some("porcelain") => PolicyFormat::Porcelain,
+                    Some("sarif") => PolicyFormat::Sarif,
+                    other => {
+                        eprintln!(
+                            "Error: --format must be text, json, porcelain or sarif (got {})",
+                            other.unwrap_or("nothing")
+                        );
+                        std::process::exit(1);
+                    }
+                };
+                i += 2;
+            }
+            "--json" => {
+                format = PolicyFormat::Json;
+                i += 1;
+            }
+            "--porcelain" => {
+                format = PolicyFormat::Porcelain;
+                i += 1;
+            }
+            "--staged" => {
+                staged = true;
+                i += 1;
+            }
+            "--output" | "-o" => {
+                let Some(path) = args.get(i + 1) else {
+                    eprintln!("Error: --output requires a file path");
+                    std::process::exit(1);
+                };
+                output = Some(path.clone());
+                i += 2;
+            }
+            "--help" | "-h" => print_policy_help_and_exit(),
+            arg if arg.starts_with('-') => {
+                eprintln!("Unknown policy check argument: {}", arg);
+                std::process::exit(1);
+            }
+            arg => {
+                if range.is_some() {
+                    eprintln!("Error: policy check accepts a single commit or range");
+                    std::process::exit(1);
+                }
+                range = Some(arg.to_string());
+                i += 1;
+            }
+        }
+    }
+    if staged && range.is_some() {
+        eprintln!("Error: --staged can't be combined with a commit or range");
+        std::process::exit(1);
+    }
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let report = match check_policy(&repo, range.as_deref(), staged) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Policy check failed to run: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let rendered = match format {
+        PolicyFormat::Text => render_text(&report),
+        PolicyFormat::Json => {
+            serde_json::to_string_pretty(&machine_output::versioned(&report_json(&report))).unwrap()
+        }
+        PolicyFormat::Porcelain => machine_output::to_porcelain(&report_json(&report)).unwrap(),
+        PolicyFormat::Sarif => SarifLog::new(&policy_rules(), &sarif_findings(&report))
+            .to_json_string()
+            .unwrap(),
+    };
+    match output {
+        Some(path) => {
+            if let Err(e) = std::fs::write(&path, rendered + "\n") {
+                eprintln!("Failed to write {}: {}", path, e);
+                std::process::exit(1);
+            }
+        }
+        None => println!("{}", rendered),
+    }
+
+    if !report.passed() {
+        std::process::exit(1);
+    }
+}
+
+fn print_policy_help_and_exit() -> ! {
+    eprintln!("Usage: git-ai policy check [<commit>|<range>] [--format <fmt>] [--output <file>]");
+    eprintln!("       git-ai policy check --staged [--format <fmt>]");
+    eprintln!();
+    eprintln!(
+        "Evaluates {} against commits the way the push hook does, or against the",
+        POLICY_FILE
+    );
+    eprintln!("staged changes the way the commit hook does. Exits 1 on blocking violations.");
+    eprintln!();
+    eprintln!(
+        "  <range>              e.g. origin/main..HEAD (default: @{{upstream}}..HEAD, or HEAD)"
+    );
+    eprintln!("  --staged             Check the staged changes against the commit rules");
+    eprintln!("  --format <fmt>       text (default), json, porcelain, or sarif");
+    eprintln!("  -o, --output <file>  Write the report to a file instead of stdout");
+    std::process::exit(0);
+}
+
+/// Evaluate the policy against the staged changes, or the non-merge commits `range` selects
+pub fn check_policy(
+    repo: &Repository,
+    range: Option<&str>,
+    staged: bool,
+) -> Result<PolicyReport, GitAiError> {
+    let Some(policy) = Policy::load(repo)? else {
+        return Ok(PolicyReport {
+            has_policy: false,
+            checked: "nothing".to_string(),
+            violations: Vec::new(),
+        });
+    };
+
+    if staged {
+        return Ok(PolicyReport {
+            has_policy: true,
+            checked: "staged changes".to_string(),
+            violations: evaluate_staged(repo, &policy, false)?,
+        });
+    }
+
+    let mut args = vec!["rev-list".to_string(), "--no-merges".to_string()];
+    args.extend(rev_list_args(repo, range));
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let commits: Vec<String> = repo.git(&args)?.lines().map(str::to_string).collect();
+    Ok(PolicyReport {
+        has_policy: true,
+        checked: format!("{} commit(s)", commits.len()),
+        violations: evaluate_commits(repo, &policy, PolicyHook::Push, &commits),
+    })
+}
+
+fn level(violation: &Violation) -> SarifLevel {
+    match violation.enforcement {
+        Enforcement::Block => SarifLevel::Error,
+        Enforcement::Warn => SarifLevel::Warning,
+    }
+}
+
+/// One finding per violation, named after the policy rule that raised it
+pub fn sarif_findings(report: &PolicyReport) -> Vec<SarifFinding> {
+    report
+        .violations
+        .iter()
+        .map(|violation| {
+            let mut finding = SarifFinding::new(
+                violation.kind.rule_id(),
+                level(violation),
+                format!("[{}] {}", violation.rule, violation.message),
+            );
+            if let Some(path) = &violation.path {
+                finding = finding.path(path);
+                if let Some(line) = violation.line {
+                    finding = finding.lines(line, line);
+                }
+            }
+            if let Some(commit) = &violation.commit {
+                finding = finding.commit_sha(commit);
+            }
+            finding
+        })
+        .collect()
+}
+
+fn render_text(report: &PolicyReport) -> String {
+    if !report.has_policy {
+        return format!("No {} in this repository; nothing to check", POLICY_FILE);
+    }
+    let mut out = String::new();
+    for violation in &report.violations {
+        let level = match violation.enforcement {
+            Enforcement::Block => "error",
+            Enforcement::Warn => "warning",
+        };
+        out.push_str(&format!(
+            "{} [{}] {}: {}\n",
+            level, violation.rule, violation.subject, violation.message
+        ));
+    }
+    let errors = report
+        .violations
+        .iter()
+        .filter(|v| v.enforcement == Enforcement::Block)
+        .count();
+    let warnings = report.violations.len() - errors;
+    if report.violations.is_empty() {
+        out.push_str(&format!("Checked {}: no policy violations", report.checked));
+    } else {
+        out.push_str(&format!(
+            "Checked {}: {} error(s), {} warning(s)",
+            report.checked, errors, warnings
+        ));
+    }
+    out
+}
+
+fn report_json(report: &PolicyReport) -> serde_json::Value {
+    serde_json::json!({
+        "has_policy": report.has_policy,
+        "passed": report.passed(),
+        "violations": report.violations.iter().map(|v| serde_json::json!({
+            "rule": v.rule,
+            "rule_id": v.kind.rule_id(),
+            "level": level(v),
+            "subject": v.subject,
+            "commit": v.commit,
+            "path": v.path,
+            "line": v.line,
+            "message": v.message,
+        })).collect::<Vec<_>>(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sarif_findings_carry_rule_id_location_and_level() {
+        let report = PolicyReport {
+            has_policy: true,
+            checked: "1 commit(s)".to_string(),
+            violations: vec![Violation {
+                rule: "approved-models".to_string(),
+                kind: ViolationKind::DeniedModel,
+                enforcement: Enforcement::Warn,
+                subject: "commit 0123456".to_string(),
+                commit: Some("0123456789".to_string()),
+                path: Some("src/lib.rs".to_string()),
+                line: Some(4),
+                message: "src/lib.rs has lines written by model gpt-3.5".to_string(),
+            }],
+        };
+        assert!(report.passed());
+
+        let findings = sarif_findings(&report);
+        assert_eq!(findings[0].rule_id, "policy/denied-model");
+        assert_eq!(findings[0].level, SarifLevel::Warning);
+        assert_eq!(findings[0].path.as_deref(), Some("src/lib.rs"));
+        assert_eq!(findings[0].lines, Some((4, 4)));
+        assert_eq!(findings[0].commit_sha.as_deref(), Some("0123456789"));
+        assert!(findings[0].message.starts_with("[approved-models] "));
+
+        let sarif = SarifLog::new(&policy_rules(), &findings)
+            .to_json_string()
+            .unwrap();
+        let sarif: serde_json::Value = serde_json::from_str(&sarif).unwrap();
+        assert_eq!(sarif["runs"][0]["results"][0]["ruleIndex"], 2);
+        assert_eq!(
+            sarif["runs"][0]["tool"]["driver"]["rules"]
+                .as_array()
+                .unwrap()
+                .len(),
+            4
+        );
+    }
+}
@@ -0,0 +1,334 @@
+//! Client-side git hooks that keep refs/notes/ai attached to rewritten commits and fresh.
+//!
+//! When git runs through the git-ai wrapper, amend/rebase/cherry-pick are already handled by the
+//! wrapper's own command hooks. These hooks cover git invoked directly (IDEs, GUI clients, plain
+//! `git` on PATH): `post-rewrite` receives the old→new mapping for `commit --amend` and `rebase`,
+//! and `post-commit` picks up `cherry-pick -x`, since git never runs post-rewrite for cherry-picks,
+//! and records the AI lines a `git revert` removed.
+//!
+//! `post-checkout`, `post-merge` and `post-rewrite` after a rebase also fetch the tracked
+//! remote's notes in the background, at most once per `notes_auto_fetch_minutes`, so a plain
+//! `git pull` or branch switch leaves attribution ready for blame and stats.
+
+use crate::authorship::revert::record_revert_note;
+use crate::commands::git_handlers::WRAPPED_GIT_ENV;
+use crate::commands::hook_chain::{HookInstallOptions, InstalledHook, install_hook_scripts};
+use crate::commands::hooks::commit_hooks::get_commit_default_author;
+use crate::error::GitAiError;
+use crate::git::find_repository;
+use crate::git::refs::show_authorship_note;
+use crate::git::repository::Repository;
+use crate::git::rewrite_log::{CherryPickCompleteEvent, RebaseCompleteEvent, RewriteLogEvent};
+use crate::git::sync_authorship::{fetch_authorship_notes, notes_auto_fetch_due};
+use crate::utils::{background_git_ai_command, debug_log};
+use std::io::Read;
+use std::process::Stdio;
+
+/// First line after the shebang in hook scripts we write; lets `install` overwrite its own hooks
+const POST_REWRITE_HOOK_MARKER: &str = "# Installed by git-ai post-rewrite";
+
+/// Hook name -> arguments passed to `git-ai post-rewrite`
+const INSTALLED_HOOKS: [(&str, &str); 4] = [
+    ("post-rewrite", "\"$@\""),
+    ("post-commit", "cherry-pick"),
+    ("post-checkout", "checkout \"$@\""),
+    ("post-merge", "merge \"$@\""),
+];
+
+/// One `<old-sha> <new-sha> [<extra>]` line from post-rewrite's stdin
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RewrittenCommit {
+    pub old_sha: String,
+    pub new_sha: String,
+}
+
+pub fn handle_post_rewrite(args: &[String]) {
+    let Some(subcommand) = args.first() else {
+        print_post_rewrite_help_and_exit();
+    };
+
+    if subcommand == "install" {
+        let options = match HookInstallOptions::parse(&args[1..]) {
+            Ok(options) => options,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        };
+        match install_post_rewrite_hooks(&options) {
+            Ok(hooks) => {
+                for hook in hooks {
+                    eprintln!("Installed {}", hook);
+                }
+            }
+            Err(e) => {
+                eprintln!("Failed to install post-rewrite hooks: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    // The wrapper rewrites notes itself once git exits; doing it here too would be wasted work
+    if std::env::var_os(WRAPPED_GIT_ENV).is_some() {
+        return;
+    }
+
+    let mut repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            debug_log(&format!("post-rewrite: failed to find repository: {}", e));
+            return;
+        }
+    };
+
+    // Hooks must never fail the user's git command, so errors are only logged
+    let result = match subcommand.as_str() {
+        "amend" | "rebase" => {
+            let mut input = String::new();
+            if let Err(e) = std::io::stdin().read_to_string(&mut input) {
+                debug_log(&format!("post-rewrite: failed to read stdin: {}", e));
+                return;
+            }
+            let result = rewrite_notes(&mut repo, subcommand, &parse_rewritten_commits(&input));
+            // `git pull --rebase` runs post-rewrite rather than post-merge
+            if subcommand == "rebase" {
+                auto_fetch_notes(&repo);
+            }
+            result
+        }
+        "cherry-pick" => {
+            rewrite_cherry_pick_note(&mut repo).and_then(|_| record_plain_revert_note(&repo))
+        }
+        "checkout" => {
+            // The third argument is 1 for a branch checkout and 0 for checking out files
+            if args.get(3).map(String::as_str) == Some("1") {
+                auto_fetch_notes(&repo);
+            }
+            Ok(())
+        }
+        "merge" => {
+            auto_fetch_notes(&repo);
+            Ok(())
+        }
+        "--help" | "-h" | "help" => print_post_rewrite_help_and_exit(),
+        other => {
+            eprintln!("Unknown post-rewrite subcommand: {}", other);
+            print_post_rewrite_help_and_exit();
+        }
+    };
+
+    if let Err(e) = result {
+        debug_log(&format!("post-rewrite {} failed: {}", subcommand, e));
+    }
+}
+
+fn print_post_rewrite_help_and_exit() -> ! {
+    eprintln!(
+        "Usage: git-ai post-rewrite <amend|rebase|cherry-pick|checkout|merge|install> [options]"
+    );
+    eprintln!();
+    eprintln!(
+        "  install            Install post-rewrite, post-commit, post-checkout and post-merge"
+    );
+    eprintln!("                     hooks in this repository");
+    eprintln!("    --force            Replace existing hooks instead of chaining them");
+    eprintln!("    --hooks-path <dir> Install into <dir> and point core.hooksPath at it");
+    eprintln!("    --run-existing <when>  Run chained hooks before (default) or after git-ai");
+    eprintln!("    --recurse-submodules   Also install into every checked-out submodule");
+    eprintln!("  amend | rebase     Run as git's post-rewrite hook (old/new SHAs on stdin)");
+    eprintln!(
+        "  cherry-pick        Run as git's post-commit hook to carry notes over `cherry-pick -x`"
+    );
+    eprintln!("                     and record the AI lines a revert removed");
+    eprintln!("  checkout | merge   Run as git's post-checkout/post-merge hook to fetch notes");
+    std::process::exit(0);
+}
+
+pub fn parse_rewritten_commits(input: &str) -> Vec<RewrittenCommit> {
+    input
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let old_sha = parts.next()?;
+            let new_sha = parts.next()?;
+            Some(RewrittenCommit {
+                old_sha: old_sha.to_string(),
+                new_sha: new_sha.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Replay a post-rewrite mapping through the same rewrite events the wrapper records
+fn rewrite_notes(
+    repo: &mut Repository,
+    kind: &str,
+    rewritten: &[RewrittenCommit],
+) -> Result<(), GitAiError> {
+    let Some(last) = rewritten.last() else {
+        return Ok(());
+    };
+    let commit_author = get_commit_default_author(repo, &[]);
+
+    let event = if kind == "amend" {
+        RewriteLogEvent::commit_amend(last.old_sha.clone(), last.new_sha.clone())
+    } else {
+        // squash/fixup map several old commits onto one new commit
+        let mut new_commits: Vec<String> = Vec::new();
+        for commit in rewritten {
+            if new_commits.last() != Some(&commit.new_sha) {
+                new_commits.push(commit.new_sha.clone());
+            }
+        }
+        RewriteLogEvent::rebase_complete(RebaseCompleteEvent::new(
+            last.old_sha.clone(),
+            last.new_sha.clone(),
+            false,
+            rewritten.iter().map(|c| c.old_sha.clone()).collect(),
+            new_commits,
+        ))
+    };
+
+    repo.handle_rewrite_log_event(event, commit_author, true, true);
+    Ok(())
+}
+
+/// Copy attribution onto HEAD when it was just made by `git cherry-pick -x`
+fn rewrite_cherry_pick_note(repo: &mut Repository) -> Result<(), GitAiError> {
+    let reflog = repo.git(&["reflog", "-1", "--format=%gs"])?;
+    if !reflog.starts_with("cherry-pick") {
+        return Ok(());
+    }
+
+    let head = repo.git(&["rev-parse", "HEAD"])?.trim().to_string();
+    if show_authorship_note(repo, &head).is_some() {
+        return Ok(());
+    }
+    let message = repo.git(&["log", "-1", "--format=%B", &head])?;
+    let Some(source) = cherry_picked_from(&message) else {
+        debug_log("post-commit: cherry-pick without -x trailer; source commit unknown");
+        return Ok(());
+    };
+    if show_authorship_note(repo, &source).is_none() {
+        return Ok(());
+    }
+
+    let parent = repo.git(&["rev-parse", "HEAD^"])?.trim().to_string();
+    let commit_author = get_commit_default_author(repo, &[]);
+    repo.handle_rewrite_log_event(
+        RewriteLogEvent::cherry_pick_complete(CherryPickCompleteEvent::new(
+            parent,
+            head.clone(),
+            vec![source],
+            vec![head],
+        )),
+        commit_author,
+        true,
+        true,
+    );
+    Ok(())
+}
+
+/// Record the AI lines HEAD removed when it was just made by `git revert`
+fn record_plain_revert_note(repo: &Repository) -> Result<(), GitAiError> {
+    let reflog = repo.git(&["reflog", "-1", "--format=%gs"])?;
+    if !reflog.starts_with("revert") {
+        return Ok(());
+    }
+    let head = repo.git(&["rev-parse", "HEAD"])?.trim().to_string();
+    let commit_author = get_commit_default_author(repo, &[]);
+    if record_revert_note(repo, &head, &commit_author)? {
+        debug_log(&format!(
+            "post-commit: recorded reverted AI lines on {}",
+            head
+        ));
+    }
+    Ok(())
+}
+
+/// Source SHA from the last `(cherry picked from commit <sha>)` line in a commit message
+pub fn cherry_picked_from(message: &str) -> Option<String> {
+    message
+        .lines()
+        .rev()
+        .find_map(|line| {
+            line.trim()
+                .strip_prefix("(cherry picked from commit ")?
+                .strip_suffix(')')
+        })
+        .map(str::to_string)
+}
+
+/// Fetch the tracked remote's notes if `notes_auto_fetch_minutes` have passed since the last
+/// time. The fetch runs in a background `git-ai sync --fetch-only` so the hook never waits on
+/// the network.
+fn auto_fetch_notes(repo: &Repository) {
+    let Some(remote) = notes_auto_fetch_due(repo) else {
+        return;
+    };
+    debug_log(&format!("auto-fetching authorship notes from '{}'", remote));
+
+    // Tests fetch in the foreground: a detached process could outlive the test's repository
+    #[cfg(debug_assertions)]
+    if std::env::var("GIT_AI_TEST_DB_PATH").is_ok() {
+        if let Err(e) = fetch_authorship_notes(repo, &remote) {
+            debug_log(&format!("auto-fetch of authorship notes failed: {}", e));
+        }
+        return;
+    }
+
+    let spawned = background_git_ai_command().and_then(|mut cmd| {
+        cmd.args(["sync", "--fetch-only", &remote])
+            .current_dir(repo.workdir()?)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(GitAiError::from)
+    });
+    if let Err(e) = spawned {
+        debug_log(&format!("failed to start notes auto-fetch: {}", e));
+    }
+}
+
+/// Write the hooks into the repository's hooks dir, or into `options.hooks_path` (which then
+/// becomes core.hooksPath), chaining any hooks already there
+pub fn install_post_rewrite_hooks(
+    options: &HookInstallOptions,
+) -> Result<Vec<InstalledHook>, GitAiError> {
+    install_hook_scripts(
+        "post-rewrite",
+        POST_REWRITE_HOOK_MARKER,
+        &INSTALLED_HOOKS,
+        options,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rewritten_commits() {
+        let input = format!(
+            "{} {}\n{} {} extra\nbogus\n\n",
+            "a".repeat(40),
+            "b".repeat(40),
+            "c".repeat(40),
+            "d".repeat(40)
+        );
+        let rewritten = parse_rewritten_commits(&input);
+        assert_eq!(rewritten.len(), 2);
+        assert_eq!(rewritten[1].old_sha, "c".repeat(40));
+        assert_eq!(rewritten[1].new_sha, "d".repeat(40));
+    }
+
+    #[test]
+    fn test_cherry_picked_from() {
+        let message =
+            "Fix bug\n\n(cherry picked from commit 1111)\n(cherry picked from commit 2222)\n";
+        assert_eq!(cherry_picked_from(message), Some("2222".to_string()));
+        assert_eq!(cherry_picked_from("Fix bug\n"), None);
+    }
+}
@@ -0,0 +1,277 @@
+//! Optional `prepare-commit-msg` hook that records AI authorship in the commit message.
+//!
+//! Some hosting platforms drop refs/notes/ai (squash merges through a web UI, mirrors that only
+//! copy branches). For teams in that position the hook appends a trailer such as
+//! `AI-Assisted: 42/100 lines (cursor, gpt-4o)` to every commit, computed from the pending
+//! checkpoints for the staged changes, so the attribution travels with the commit itself.
+
+use crate::authorship::authorship_log::LineRange;
+use crate::authorship::authorship_log_serialization::AuthorshipLog;
+use crate::authorship::pre_commit;
+use crate::authorship::staged::staged_authorship;
+use crate::commands::git_handlers::WRAPPED_GIT_ENV;
+use crate::commands::hook_chain::{HookInstallOptions, install_hook_scripts};
+use crate::commands::hooks::commit_hooks::get_commit_default_author;
+use crate::error::GitAiError;
+use crate::git::find_repository;
+use crate::git::repository::{Repository, exec_git};
+use crate::utils::debug_log;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Trailer key appended to commit messages
+pub const AI_ASSISTED_TRAILER: &str = "AI-Assisted";
+
+/// First line after the shebang in the hook script; lets `install` overwrite its own hook
+const PREPARE_COMMIT_MSG_HOOK_MARKER: &str = "# Installed by git-ai prepare-commit-msg";
+
+/// AI attribution of the changes about to be committed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingAiSummary {
+    pub ai_lines: u32,
+    pub total_lines: u32,
+    /// (tool, model, lines), most lines first
+    pub tools: Vec<(String, String, u32)>,
+}
+
+impl PendingAiSummary {
+    /// Summarize an authorship log for a commit that adds `total_lines` lines
+    pub fn from_authorship_log(log: &AuthorshipLog, total_lines: u32) -> Self {
+        let mut by_tool: BTreeMap<(String, String), u32> = BTreeMap::new();
+        for file in &log.attestations {
+            for entry in &file.entries {
+                let lines: u32 = entry
+                    .line_ranges
+                    .iter()
+                    .map(|range| match range {
+                        LineRange::Single(_) => 1,
+                        LineRange::Range(start, end) => end.saturating_sub(*start) + 1,
+                    })
+                    .sum();
+                let key = log
+                    .metadata
+                    .prompts
+                    .get(&entry.hash)
+                    .map(|p| (p.agent_id.tool.clone(), p.agent_id.model.clone()))
+                    .unwrap_or_else(|| ("unknown".to_string(), "unknown".to_string()));
+                *by_tool.entry(key).or_default() += lines;
+            }
+        }
+
+        let mut tools: Vec<(String, String, u32)> = by_tool
+            .into_iter()
+            .map(|((tool, model), lines)| (tool, model, lines))
+            .collect();
+        tools.sort_by_key(|(_, _, lines)| std::cmp::Reverse(*lines));
+        let ai_lines = tools.iter().map(|(_, _, lines)| lines).sum::<u32>();
+
+        PendingAiSummary {
+            ai_lines: ai_lines.min(total_lines),
+            total_lines,
+            tools,
+        }
+    }
+
+    /// Trailer value, e.g. `42/100 lines (cursor, gpt-4o; claude, claude-sonnet-4)`
+    pub fn trailer_value(&self) -> String {
+        let tools: Vec<String> = self
+            .tools
+            .iter()
+            .map(|(tool, model, _)| format!("{}, {}", tool, model))
+            .collect();
+        format!(
+            "{}/{} lines ({})",
+            self.ai_lines,
+            self.total_lines,
+            tools.join("; ")
+        )
+    }
+}
+
+pub fn handle_prepare_commit_msg(args: &[String]) {
+    let Some(first) = args.first() else {
+        print_prepare_commit_msg_help_and_exit();
+    };
+
+    match first.as_str() {
+        "install" => {
+            let options = match HookInstallOptions::parse(&args[1..]) {
+                Ok(options) => options,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            match install_hook_scripts(
+                "prepare-commit-msg",
+                PREPARE_COMMIT_MSG_HOOK_MARKER,
+                &[("prepare-commit-msg", "\"$@\"")],
+                &options,
+            ) {
+                Ok(hooks) => {
+                    for hook in hooks {
+                        eprintln!("Installed {}", hook);
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Failed to install prepare-commit-msg hook: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        "--help" | "-h" | "help" => print_prepare_commit_msg_help_and_exit(),
+        _ => {
+            // Hooks must never fail the user's commit, so errors are only logged
+            if let Err(e) = annotate_commit_message(Path::new(first), args.get(1)) {
+                debug_log(&format!("prepare-commit-msg failed: {}", e));
+            }
+        }
+    }
+}
+
+fn print_prepare_commit_msg_help_and_exit() -> ! {
+    eprintln!("Usage: git-ai prepare-commit-msg <install | <msg-file> [<source> [<sha>]]>");
+    eprintln!();
+    eprintln!(
+        "Append an `{}: <ai>/<total> lines (<tool>, <model>)` trailer to commit messages.",
+        AI_ASSISTED_TRAILER
+    );
+    eprintln!();
+    eprintln!("  install            Install the prepare-commit-msg hook in this repository");
+    eprintln!("    --force            Replace an existing hook instead of chaining it");
+    eprintln!("    --hooks-path <dir> Install into <dir> and point core.hooksPath at it");
+    eprintln!("    --run-existing <when>  Run a chained hook before (default) or after git-ai");
+    eprintln!("    --recurse-submodules   Also install into every checked-out submodule");
+    eprintln!("  <msg-file> ...     Run as git's prepare-commit-msg hook");
+    std::process::exit(0);
+}
+
+/// Add or replace the trailer in `message_file` for the staged changes
+fn annotate_commit_message(message_file: &Path, source: Option<&String>) -> Result<(), GitAiError> {
+    // Merges, squashes and -c/-C/--amend reuse a message that describes other changes
+    if matches!(
+        source.map(String::as_str),
+        Some("merge") | Some("squash") | Some("commit")
+    ) {
+        return Ok(());
+    }
+
+    let repo = find_repository(&Vec::<String>::new())?;
+
+    // The wrapper already ran its pre-commit checkpoint; plain git needs one here
+    if std::env::var_os(WRAPPED_GIT_ENV).is_none() {
+        let author = get_commit_default_author(&repo, &[]);
+        pre_commit::pre_commit(&repo, author)?;
+    }
+
+    let Some(summary) = pending_ai_summary(&repo)? else {
+        return Ok(());
+    };
+    if summary.ai_lines == 0 {
+        return Ok(());
+    }
+
+    let mut args = repo.global_args_for_exec();
+    args.push("interpret-trailers".to_string());
+    args.push("--in-place".to_string());
+    args.push("--if-exists".to_string());
+    args.push("replace".to_string());
+    args.push("--trailer".to_string());
+    args.push(format!(
+        "{}: {}",
+        AI_ASSISTED_TRAILER,
+        summary.trailer_value()
+    ));
+    args.push(message_file.to_string_lossy().to_string());
+    exec_git(&args)?;
+    Ok(())
+}
+
+/// AI attribution of the index relative to HEAD, from the pending checkpoints. `None` when no
+/// staged line is attributed to an agent.
+pub fn pending_ai_summary(repo: &Repository) -> Result<Option<PendingAiSummary>, GitAiError> {
+    let staged = staged_authorship(repo)?;
+    if staged.authorship_log.attestations.is_empty() {
+        return Ok(None);
+    }
+    let total_lines: usize = staged.added_lines.values().map(Vec::len).sum();
+
+    Ok(Some(PendingAiSummary::from_authorship_log(
+        &staged.authorship_log,
+        total_lines as u32,
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authorship::authorship_log::PromptRecord;
+    use crate::authorship::authorship_log_serialization::{AttestationEntry, FileAttestation};
+    use crate::authorship::working_log::AgentId;
+
+    fn prompt(tool: &str, model: &str) -> PromptRecord {
+        PromptRecord {
+            agent_id: AgentId {
+                tool: tool.to_string(),
+                id: "session".to_string(),
+                model: model.to_string(),
+            },
+            human_author: None,
+            messages: vec![],
+            total_additions: 0,
+            total_deletions: 0,
+            accepted_lines: 0,
+            overriden_lines: 0,
+            messages_url: None,
+            token_usage: None,
+            turns: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_summary_groups_lines_by_tool_and_model() {
+        let mut log = AuthorshipLog::new();
+        log.metadata
+            .prompts
+            .insert("aaaaaaa".to_string(), prompt("cursor", "gpt-4o"));
+        log.metadata
+            .prompts
+            .insert("bbbbbbb".to_string(), prompt("claude", "claude-sonnet-4"));
+        let mut file = FileAttestation::new("src/main.rs".to_string());
+        file.add_entry(AttestationEntry::new(
+            "aaaaaaa".to_string(),
+            vec![LineRange::Range(1, 40), LineRange::Single(50)],
+        ));
+        file.add_entry(AttestationEntry::new(
+            "bbbbbbb".to_string(),
+            vec![LineRange::Range(60, 61)],
+        ));
+        log.attestations.push(file);
+
+        let summary = PendingAiSummary::from_authorship_log(&log, 100);
+        assert_eq!(summary.ai_lines, 43);
+        assert_eq!(
+            summary.trailer_value(),
+            "43/100 lines (cursor, gpt-4o; claude, claude-sonnet-4)"
+        );
+    }
+
+    #[test]
+    fn test_summary_caps_ai_lines_at_total() {
+        let mut log = AuthorshipLog::new();
+        log.metadata
+            .prompts
+            .insert("aaaaaaa".to_string(), prompt("cursor", "gpt-4o"));
+        let mut file = FileAttestation::new("a.rs".to_string());
+        file.add_entry(AttestationEntry::new(
+            "aaaaaaa".to_string(),
+            vec![LineRange::Range(1, 5)],
+        ));
+        log.attestations.push(file);
+
+        assert_eq!(
+            PendingAiSummary::from_authorship_log(&log, 3).trailer_value(),
+            "3/3 lines (cursor, gpt-4o)"
+        );
+    }
+}
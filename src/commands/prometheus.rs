@@ -0,0 +1,56 @@
+use crate::observability::prometheus::{PrometheusConfig, serve};
+
+pub fn handle_prometheus(args: &[String]) {
+    let Some(subcommand) = args.first() else {
+        print_prometheus_help_and_exit();
+    };
+
+    match subcommand.as_str() {
+        "serve" => {
+            let mut config = PrometheusConfig::from_env();
+            let mut i = 1;
+            while i < args.len() {
+                match args[i].as_str() {
+                    "--listen" => {
+                        let Some(addr) = args.get(i + 1) else {
+                            eprintln!("--listen requires an address (e.g. 0.0.0.0:9464)");
+                            std::process::exit(1);
+                        };
+                        config.listen_addr = addr.clone();
+                        i += 2;
+                    }
+                    other => {
+                        eprintln!("Unknown prometheus serve argument: {}", other);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            if let Err(e) = serve(&config) {
+                eprintln!("Failed to serve metrics: {}", e);
+                std::process::exit(1);
+            }
+        }
+        "path" => println!("{}", PrometheusConfig::from_env().textfile_path.display()),
+        "--help" | "-h" | "help" => print_prometheus_help_and_exit(),
+        other => {
+            eprintln!("Unknown prometheus subcommand: {}", other);
+            print_prometheus_help_and_exit();
+        }
+    }
+}
+
+fn print_prometheus_help_and_exit() -> ! {
+    eprintln!("Usage: git-ai prometheus <serve|path>");
+    eprintln!();
+    eprintln!("Expose git-ai metrics to Prometheus (requires the `prometheus` build feature).");
+    eprintln!("Set GIT_AI_PROMETHEUS_ENABLED=1 to record metrics.");
+    eprintln!();
+    eprintln!("  serve                 Serve the metrics on GET /metrics");
+    eprintln!(
+        "    --listen <addr>       Listen address (default: GIT_AI_PROMETHEUS_LISTEN or 127.0.0.1:9464)"
+    );
+    eprintln!(
+        "  path                  Print the textfile collector path (GIT_AI_PROMETHEUS_TEXTFILE)"
+    );
+    std::process::exit(0);
+}
@@ -0,0 +1,28 @@
+//! Formatting helpers shared by the reporting commands (`stats --report`, `ownership`,
+//! `compare`, `export`).
+
+/// Share of AI lines among all attributed lines, as a percentage rounded to one decimal
+pub fn percent(ai: u64, human: u64) -> f64 {
+    let total = ai + human;
+    if total == 0 {
+        return 0.0;
+    }
+    round_tenth(ai as f64 * 100.0 / total as f64)
+}
+
+pub fn round_tenth(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percent_rounds_to_a_tenth() {
+        assert_eq!(percent(0, 0), 0.0);
+        assert_eq!(percent(1, 2), 33.3);
+        assert_eq!(percent(2, 1), 66.7);
+        assert_eq!(percent(5, 0), 100.0);
+    }
+}
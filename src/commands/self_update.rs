@@ -0,0 +1,632 @@
+//! `git ai self-update`: replace the running binary with the release channel's build.
+//!
+//! `upgrade` hands the install over to the channel's install script. Self-update downloads the
+//! binary itself, so it works on machines where scripts can't run and can be driven by fleet
+//! tooling through `--format json`.
+//!
+//! The releases API names the checksum of the channel's SHA256SUMS, and SHA256SUMS names the
+//! binary's. With `release_signing_key` set, SHA256SUMS must also carry an SSH signature from
+//! that key (`SHA256SUMS.sig`, namespace `git-ai-release`), so that a compromised API alone
+//! can't ship a binary.
+//!
+//! The new binary is written next to the old one, run once with `--version`, and renamed over
+//! it, so an interrupted update leaves either the old binary or the new one. The new binary then
+//! runs `install-hooks --if-template-changed`, which reinstalls agent hooks only when their
+//! template differs from the installed one.
+//!
+//! `required_version` pins the versions a team accepts: self-update won't install a release
+//! outside it, and git hooks warn while the running binary is outside it.
+
+use crate::api::client::ApiContext;
+use crate::commands::install_hooks::installed_hooks_template_version;
+use crate::commands::machine_output::{self, OutputFormat};
+use crate::commands::upgrade::{self, UpgradeAction};
+use crate::config::{Config, UpdateChannel};
+use crate::observability::{log_message, logging};
+use crate::utils::current_git_ai_exe;
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Namespace and principal of release signatures
+const SIGNATURE_NAMESPACE: &str = "git-ai-release";
+
+struct SelfUpdateOptions {
+    check: bool,
+    force: bool,
+    channel: Option<UpdateChannel>,
+    format: OutputFormat,
+}
+
+#[derive(Debug, Serialize)]
+struct SelfUpdateReport {
+    current_version: String,
+    channel: String,
+    available_version: String,
+    release_tag: String,
+    /// upgrade_available, already_latest, running_newer_version, force_reinstall, or
+    /// blocked_by_required_version
+    action: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    required_version: Option<String>,
+    installed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    signature_verified: bool,
+    hooks_reinstalled: bool,
+}
+
+pub fn handle_self_update(args: &[String]) {
+    let options = parse_args(args);
+    match run_self_update(&options) {
+        Ok(report) => print_report(&report, options.format),
+        Err(e) => {
+            eprintln!("self-update failed: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn print_self_update_help_and_exit() -> ! {
+    eprintln!("git-ai self-update - Replace this binary with the release channel's build");
+    eprintln!();
+    eprintln!("Usage: git-ai self-update [options]");
+    eprintln!();
+    eprintln!("Options:");
+    eprintln!("  --check               Report whether an update is available without installing");
+    eprintln!("  --force               Reinstall the channel's release even if already on it");
+    eprintln!("  --channel <name>      latest, next, enterprise-latest or enterprise-next");
+    eprintln!("                        (default: update_channel)");
+    eprintln!("  --format <fmt>        text (default), json or porcelain");
+    eprintln!();
+    eprintln!("Releases outside required_version are never installed. With release_signing_key");
+    eprintln!("set, SHA256SUMS must be signed with that SSH key.");
+    std::process::exit(0);
+}
+
+fn parse_args(args: &[String]) -> SelfUpdateOptions {
+    let mut options = SelfUpdateOptions {
+        check: false,
+        force: false,
+        channel: None,
+        format: OutputFormat::Text,
+    };
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--check" => {
+                options.check = true;
+                i += 1;
+            }
+            "--force" => {
+                options.force = true;
+                i += 1;
+            }
+            "--channel" => {
+                let Some(channel) = args.get(i + 1).and_then(|c| UpdateChannel::from_str(c)) else {
+                    eprintln!(
+                        "--channel requires one of: latest, next, enterprise-latest, enterprise-next"
+                    );
+                    std::process::exit(1);
+                };
+                options.channel = Some(channel);
+                i += 2;
+            }
+            "--format" => {
+                options.format = OutputFormat::from_flag_value(args, i);
+                i += 2;
+            }
+            "--json" => {
+                options.format = OutputFormat::Json;
+                i += 1;
+            }
+            "--porcelain" => {
+                options.format = OutputFormat::Porcelain;
+                i += 1;
+            }
+            "--help" | "-h" => print_self_update_help_and_exit(),
+            other => {
+                eprintln!("Unknown argument: {}", other);
+                eprintln!("Usage: git-ai self-update [--check] [--force] [--channel <name>]");
+                std::process::exit(1);
+            }
+        }
+    }
+    options
+}
+
+fn run_self_update(options: &SelfUpdateOptions) -> Result<SelfUpdateReport, String> {
+    let config = Config::get();
+    let api_base_url = config.api_base_url();
+    let channel = options.channel.unwrap_or_else(|| config.update_channel());
+    let current_version = env!("CARGO_PKG_VERSION");
+
+    let release = upgrade::fetch_release_for_channel(api_base_url, channel)?;
+    let action = upgrade::determine_action(options.force, &release, current_version);
+    let requirement = config
+        .required_version()
+        .map(|text| {
+            VersionRequirement::parse(text)
+                .map_err(|e| format!("required_version '{}': {}", text, e))
+        })
+        .transpose()?;
+    let blocked = requirement
+        .as_ref()
+        .is_some_and(|req| !req.matches(&release.semver));
+    let wants_install = matches!(
+        action,
+        UpgradeAction::UpgradeAvailable | UpgradeAction::ForceReinstall
+    );
+    upgrade::persist_update_state(
+        channel,
+        (action == UpgradeAction::UpgradeAvailable && !blocked).then_some(&release),
+    );
+
+    let mut report = SelfUpdateReport {
+        current_version: current_version.to_string(),
+        channel: channel.as_str().to_string(),
+        available_version: release.semver.clone(),
+        release_tag: release.tag.clone(),
+        action: action.to_string().to_string(),
+        required_version: config.required_version().map(str::to_string),
+        installed: false,
+        path: None,
+        signature_verified: false,
+        hooks_reinstalled: false,
+    };
+    if wants_install && blocked {
+        report.action = "blocked_by_required_version".to_string();
+    }
+    if options.check || !wants_install || blocked {
+        return Ok(report);
+    }
+
+    let sums = fetch_release_file(api_base_url, channel, "SHA256SUMS")?;
+    upgrade::verify_sha256(&sums, &release.checksum)
+        .map_err(|e| format!("SHA256SUMS verification failed: {}", e))?;
+    if let Some(key) = config.release_signing_key() {
+        let signature = fetch_release_file(api_base_url, channel, "SHA256SUMS.sig")?;
+        verify_release_signature(&sums, &signature, key)?;
+        report.signature_verified = true;
+    }
+
+    let sums =
+        std::str::from_utf8(&sums).map_err(|e| format!("SHA256SUMS is not valid UTF-8: {}", e))?;
+    let binary_name = release_binary_name()?;
+    let expected = upgrade::parse_checksums(sums)
+        .remove(&binary_name)
+        .ok_or_else(|| format!("Checksum for {} not found in SHA256SUMS", binary_name))?;
+    let binary = fetch_release_file(api_base_url, channel, &binary_name)?;
+    upgrade::verify_sha256(&binary, &expected)
+        .map_err(|e| format!("{} verification failed: {}", binary_name, e))?;
+
+    let exe = current_git_ai_exe().map_err(|e| e.to_string())?;
+    replace_binary(&exe, &binary, &release.semver)?;
+    report.installed = true;
+    report.path = Some(exe.display().to_string());
+    upgrade::persist_update_state(channel, None);
+
+    let template_before = installed_hooks_template_version();
+    let mut install_hooks = Command::new(&exe);
+    install_hooks.args(["install-hooks", "--if-template-changed"]);
+    if !options.format.is_text() {
+        install_hooks.stdout(Stdio::null());
+    }
+    match install_hooks.status() {
+        Ok(status) if status.success() => {}
+        _ => logging::warn(
+            "self_update",
+            "reinstalling hooks failed; run `git-ai install-hooks`",
+        ),
+    }
+    let template_after = installed_hooks_template_version();
+    report.hooks_reinstalled = template_after.is_some() && template_after != template_before;
+
+    log_message(
+        "self_updated",
+        "info",
+        Some(serde_json::json!({
+            "release_tag": release.tag,
+            "current_version": current_version,
+            "channel": channel.as_str(),
+            "result": report.action,
+            "signature_verified": report.signature_verified,
+            "hooks_reinstalled": report.hooks_reinstalled,
+        })),
+    );
+    Ok(report)
+}
+
+fn print_report(report: &SelfUpdateReport, format: OutputFormat) {
+    if !format.is_text() {
+        machine_output::print(format, report).unwrap();
+        return;
+    }
+
+    let current = &report.current_version;
+    let available = &report.available_version;
+    match report.action.as_str() {
+        "blocked_by_required_version" => println!(
+            "v{} is available on {}, but required_version ({}) doesn't allow it",
+            available,
+            report.channel,
+            report.required_version.as_deref().unwrap_or("")
+        ),
+        "already_latest" => println!("git-ai v{} is up to date ({})", current, report.channel),
+        "running_newer_version" => println!(
+            "git-ai v{} is newer than {}'s v{}; use --force to install it anyway",
+            current, report.channel, available
+        ),
+        _ if !report.installed => println!(
+            "v{} is available on {} (running v{}); run `git-ai self-update` to install it",
+            available, report.channel, current
+        ),
+        _ => {
+            if report.signature_verified {
+                println!("\x1b[1;32m✓\x1b[0m SHA256SUMS signed by the release key");
+            }
+            println!(
+                "\x1b[1;32m✓\x1b[0m Updated git-ai v{} → v{} at {}",
+                current,
+                available,
+                report.path.as_deref().unwrap_or("")
+            );
+            if report.hooks_reinstalled {
+                println!("\x1b[1;32m✓\x1b[0m Hooks reinstalled");
+            }
+        }
+    }
+}
+
+fn fetch_release_file(
+    api_base_url: &str,
+    channel: UpdateChannel,
+    name: &str,
+) -> Result<Vec<u8>, String> {
+    let url = format!(
+        "{}/worker/releases/{}/download/{}",
+        api_base_url,
+        channel.as_str(),
+        name
+    );
+    let response = ApiContext::http_get(&url)
+        .with_timeout(120)
+        .send()
+        .map_err(|e| format!("Failed to fetch {}: {}", name, e))?;
+    if response.status_code != 200 {
+        return Err(format!(
+            "Failed to fetch {}: HTTP {}",
+            name, response.status_code
+        ));
+    }
+    Ok(response.as_bytes().to_vec())
+}
+
+/// Release asset for this platform, named the way install.sh and install.ps1 download it
+fn release_binary_name() -> Result<String, String> {
+    let os = match std::env::consts::OS {
+        "linux" => "linux",
+        "macos" => "macos",
+        "windows" => "windows",
+        other => return Err(format!("No release builds for {}", other)),
+    };
+    let arch = match std::env::consts::ARCH {
+        "x86_64" => "x64",
+        "aarch64" => "arm64",
+        other => return Err(format!("No release builds for {}", other)),
+    };
+    Ok(format!(
+        "git-ai-{}-{}{}",
+        os,
+        arch,
+        std::env::consts::EXE_SUFFIX
+    ))
+}
+
+/// Check an SSH signature (`ssh-keygen -Y sign -n git-ai-release`) of `content` against
+/// `public_key`, a line such as `ssh-ed25519 AAAA...`
+fn verify_release_signature(
+    content: &[u8],
+    signature: &[u8],
+    public_key: &str,
+) -> Result<(), String> {
+    // Created exclusively and readable only by us, so nobody can swap the files before
+    // ssh-keygen reads them
+    let dir = tempfile::Builder::new()
+        .prefix("git-ai-release-")
+        .tempdir()
+        .map_err(|e| format!("Failed to create a temporary directory: {}", e))?;
+    let allowed_signers = dir.path().join("allowed_signers");
+    let signature_path = dir.path().join("SHA256SUMS.sig");
+    fs::write(
+        &allowed_signers,
+        format!("{} {}\n", SIGNATURE_NAMESPACE, public_key.trim()),
+    )
+    .map_err(|e| format!("Failed to write allowed signers: {}", e))?;
+    fs::write(&signature_path, signature)
+        .map_err(|e| format!("Failed to write signature: {}", e))?;
+
+    let mut child = Command::new("ssh-keygen")
+        .arg("-Y")
+        .arg("verify")
+        .arg("-f")
+        .arg(&allowed_signers)
+        .arg("-I")
+        .arg(SIGNATURE_NAMESPACE)
+        .arg("-n")
+        .arg(SIGNATURE_NAMESPACE)
+        .arg("-s")
+        .arg(&signature_path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run ssh-keygen: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(content)
+            .map_err(|e| format!("Failed to run ssh-keygen: {}", e))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to run ssh-keygen: {}", e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "SHA256SUMS signature verification failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Swap `contents` in for the binary at `exe`. The new binary is staged in the same directory,
+/// so the final rename never crosses filesystems, and must report `version` before it replaces
+/// anything.
+fn replace_binary(exe: &Path, contents: &[u8], version: &str) -> Result<(), String> {
+    let dir = exe
+        .parent()
+        .ok_or_else(|| format!("{} has no parent directory", exe.display()))?;
+    let staged = dir.join(format!(
+        ".git-ai-update-{}{}",
+        std::process::id(),
+        std::env::consts::EXE_SUFFIX
+    ));
+    let result = stage_and_swap(exe, &staged, contents, version);
+    if result.is_err() {
+        let _ = fs::remove_file(&staged);
+    }
+    result
+}
+
+fn stage_and_swap(exe: &Path, staged: &Path, contents: &[u8], version: &str) -> Result<(), String> {
+    let write_error = |e: std::io::Error| format!("Failed to write {}: {}", staged.display(), e);
+    {
+        let mut file = fs::File::create(staged).map_err(write_error)?;
+        file.write_all(contents).map_err(write_error)?;
+        file.sync_all().map_err(write_error)?;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(staged, fs::Permissions::from_mode(0o755)).map_err(write_error)?;
+    }
+
+    let output = Command::new(staged)
+        .arg("--version")
+        .output()
+        .map_err(|e| format!("The downloaded binary doesn't run: {}", e))?;
+    let reported = String::from_utf8_lossy(&output.stdout);
+    if reported.split_whitespace().next() != Some(version) {
+        return Err(format!(
+            "The downloaded binary reports version '{}', expected {}",
+            reported.trim(),
+            version
+        ));
+    }
+
+    // Windows can't replace a running executable, but it can rename one
+    #[cfg(windows)]
+    {
+        let old = exe.with_extension("exe.old");
+        let _ = fs::remove_file(&old);
+        fs::rename(exe, &old).map_err(|e| format!("Failed to move {}: {}", exe.display(), e))?;
+        if let Err(e) = fs::rename(staged, exe) {
+            let _ = fs::rename(&old, exe);
+            return Err(format!("Failed to replace {}: {}", exe.display(), e));
+        }
+        Ok(())
+    }
+    #[cfg(not(windows))]
+    fs::rename(staged, exe).map_err(|e| format!("Failed to replace {}: {}", exe.display(), e))
+}
+
+/// Warn when the running git-ai is outside `required_version`. Runs from git hooks, so it never
+/// fails the command.
+pub fn warn_if_required_version_unmet() {
+    let Some(text) = Config::get().required_version() else {
+        return;
+    };
+    let current = env!("CARGO_PKG_VERSION");
+    match VersionRequirement::parse(text) {
+        Ok(requirement) if requirement.matches(current) => {}
+        Ok(_) => logging::warn(
+            "self_update",
+            &format!(
+                "git-ai {} is required here but {} is running; run `git-ai self-update`",
+                text, current
+            ),
+        ),
+        Err(e) => logging::warn(
+            "self_update",
+            &format!("ignoring required_version '{}': {}", text, e),
+        ),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    Exact,
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+}
+
+/// A `required_version` value: comma-separated comparisons such as `>=1.4.0, <2`. A bare
+/// version means at least that version; `=1.4.2` pins one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionRequirement {
+    comparisons: Vec<(Comparison, Vec<u32>)>,
+}
+
+impl VersionRequirement {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut comparisons = Vec::new();
+        for part in text.split(',') {
+            let part = part.trim();
+            let (comparison, version) = [
+                (">=", Comparison::GreaterOrEqual),
+                ("<=", Comparison::LessOrEqual),
+                (">", Comparison::Greater),
+                ("<", Comparison::Less),
+                ("=", Comparison::Exact),
+            ]
+            .into_iter()
+            .find_map(|(op, comparison)| Some((comparison, part.strip_prefix(op)?)))
+            .unwrap_or((Comparison::GreaterOrEqual, part));
+            let version = parse_version(version.trim().trim_start_matches('v'))
+                .ok_or_else(|| format!("'{}' is not a version comparison", part))?;
+            comparisons.push((comparison, version));
+        }
+        Ok(VersionRequirement { comparisons })
+    }
+
+    pub fn matches(&self, version: &str) -> bool {
+        let version = version.trim().trim_start_matches('v');
+        let release = version.split(['-', '+']).next().unwrap_or("");
+        let Some(version) = parse_version(release) else {
+            return false;
+        };
+        self.comparisons.iter().all(|(comparison, required)| {
+            let ordering = compare_versions(&version, required);
+            match comparison {
+                Comparison::Exact => ordering == Ordering::Equal,
+                Comparison::Greater => ordering == Ordering::Greater,
+                Comparison::GreaterOrEqual => ordering != Ordering::Less,
+                Comparison::Less => ordering == Ordering::Less,
+                Comparison::LessOrEqual => ordering != Ordering::Greater,
+            }
+        })
+    }
+}
+
+fn parse_version(text: &str) -> Option<Vec<u32>> {
+    if text.is_empty() {
+        return None;
+    }
+    text.split('.').map(|part| part.parse().ok()).collect()
+}
+
+/// Missing components count as zero, so 1.4 equals 1.4.0
+fn compare_versions(a: &[u32], b: &[u32]) -> Ordering {
+    (0..a.len().max(b.len()))
+        .map(|i| {
+            let left = a.get(i).copied().unwrap_or(0);
+            let right = b.get(i).copied().unwrap_or(0);
+            left.cmp(&right)
+        })
+        .find(|ordering| *ordering != Ordering::Equal)
+        .unwrap_or(Ordering::Equal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_requirements() {
+        let minimum = VersionRequirement::parse("1.4").unwrap();
+        assert!(minimum.matches("1.4.0"));
+        assert!(minimum.matches("v1.10.2"));
+        assert!(!minimum.matches("1.3.9"));
+
+        let pinned = VersionRequirement::parse("=1.4.2").unwrap();
+        assert!(pinned.matches("1.4.2"));
+        assert!(pinned.matches("1.4.2-beta.1"));
+        assert!(!pinned.matches("1.4.3"));
+
+        let range = VersionRequirement::parse(">=1.4.0, <2").unwrap();
+        assert!(range.matches("1.9.9"));
+        assert!(!range.matches("2.0.0"));
+        assert!(!range.matches("1.3.0"));
+
+        assert!(VersionRequirement::parse("latest").is_err());
+        assert!(VersionRequirement::parse(">=1.4,").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_replace_binary_checks_the_new_version_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let exe = dir.path().join("git-ai");
+        fs::write(&exe, "old").unwrap();
+
+        let wrong = b"#!/bin/sh\necho 9.9.8\n";
+        let err = replace_binary(&exe, wrong, "9.9.9").unwrap_err();
+        assert!(err.contains("9.9.8"), "{}", err);
+        assert_eq!(fs::read_to_string(&exe).unwrap(), "old");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        let right = b"#!/bin/sh\necho 9.9.9\n";
+        replace_binary(&exe, right, "9.9.9").unwrap();
+        assert_eq!(fs::read(&exe).unwrap(), right);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_release_signature_must_match_key_and_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = dir.path().join("release_key");
+        let keygen = Command::new("ssh-keygen")
+            .args(["-q", "-t", "ed25519", "-N", "", "-f"])
+            .arg(&key)
+            .status();
+        if !keygen.is_ok_and(|s| s.success()) {
+            eprintln!("skipping: ssh-keygen unavailable");
+            return;
+        }
+        let sums = dir.path().join("SHA256SUMS");
+        fs::write(&sums, "abc123  git-ai-linux-x64\n").unwrap();
+        let signed = Command::new("ssh-keygen")
+            .args(["-q", "-Y", "sign", "-n", SIGNATURE_NAMESPACE, "-f"])
+            .arg(&key)
+            .arg(&sums)
+            .status()
+            .unwrap();
+        assert!(signed.success());
+
+        let content = fs::read(&sums).unwrap();
+        let signature = fs::read(dir.path().join("SHA256SUMS.sig")).unwrap();
+        let public_key = fs::read_to_string(dir.path().join("release_key.pub")).unwrap();
+        verify_release_signature(&content, &signature, &public_key).unwrap();
+        assert!(
+            verify_release_signature(b"def456  git-ai-linux-x64\n", &signature, &public_key)
+                .is_err()
+        );
+        // A signature from any other key is rejected
+        let other = dir.path().join("other_key");
+        let keygen = Command::new("ssh-keygen")
+            .args(["-q", "-t", "ed25519", "-N", "", "-f"])
+            .arg(&other)
+            .status()
+            .unwrap();
+        assert!(keygen.success());
+        let other_public_key = fs::read_to_string(dir.path().join("other_key.pub")).unwrap();
+        assert!(verify_release_signature(&content, &signature, &other_public_key).is_err());
+    }
+}
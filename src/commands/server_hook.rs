@@ -0,0 +1,499 @@
+//! Server-side enforcement of authorship notes.
+//!
+//! Installed on a bare repository, `git-ai server-hook` checks that every commit pushed to a
+//! branch has an entry in refs/notes/ai. In `reject` mode it runs as the pre-receive hook and
+//! refuses the push; in `tag` mode it runs as the post-receive hook and records the commits
+//! under refs/notes/ai-unverified instead.
+//!
+//! git-ai clients push notes alongside (not before) the branch, so both hooks wait a few
+//! seconds for the notes push to land before deciding a commit is unnoted.
+
+use crate::error::GitAiError;
+use crate::git::refs::AI_AUTHORSHIP_REFNAME;
+use crate::git::repository::{exec_git, exec_git_stdin};
+use std::collections::HashSet;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Where `tag` mode records pushed commits that arrived without an authorship note
+pub const UNVERIFIED_NOTES_REF: &str = "refs/notes/ai-unverified";
+
+/// First line after the shebang in hook scripts we write; lets `install` overwrite its own hooks
+const SERVER_HOOK_MARKER: &str = "# Installed by git-ai server-hook";
+
+const DEFAULT_WAIT_SECS: u64 = 10;
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// How many unnoted commits are listed back to the pusher
+const MAX_REPORTED_COMMITS: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServerHookMode {
+    /// Refuse pushes containing unnoted commits (pre-receive)
+    Reject,
+    /// Accept them and mark them unverified (post-receive)
+    Tag,
+}
+
+impl ServerHookMode {
+    fn from_str(input: &str) -> Option<Self> {
+        match input {
+            "reject" => Some(ServerHookMode::Reject),
+            "tag" => Some(ServerHookMode::Tag),
+            _ => None,
+        }
+    }
+
+    fn hook_name(&self) -> &'static str {
+        match self {
+            ServerHookMode::Reject => "pre-receive",
+            ServerHookMode::Tag => "post-receive",
+        }
+    }
+}
+
+/// One `<old> <new> <ref>` line from a receive hook's stdin
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RefUpdate {
+    pub old_sha: String,
+    pub new_sha: String,
+    pub ref_name: String,
+}
+
+impl RefUpdate {
+    fn is_deletion(&self) -> bool {
+        is_zero_oid(&self.new_sha)
+    }
+
+    fn is_creation(&self) -> bool {
+        is_zero_oid(&self.old_sha)
+    }
+}
+
+pub fn handle_server_hook(args: &[String]) {
+    let Some(subcommand) = args.first() else {
+        print_server_hook_help_and_exit();
+    };
+
+    let mut mode = ServerHookMode::Reject;
+    let mut wait = Duration::from_secs(DEFAULT_WAIT_SECS);
+    let mut force = false;
+    let mut repo_path: Option<String> = None;
+
+    let mut i = 1;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--mode" => {
+                mode = match args.get(i + 1).and_then(|m| ServerHookMode::from_str(m)) {
+                    Some(mode) => mode,
+                    None => {
+                        eprintln!("--mode must be 'reject' or 'tag'");
+                        std::process::exit(1);
+                    }
+                };
+                i += 2;
+            }
+            "--wait" => {
+                wait = match args.get(i + 1).and_then(|v| v.parse().ok()) {
+                    Some(secs) => Duration::from_secs(secs),
+                    None => {
+                        eprintln!("--wait requires a number of seconds");
+                        std::process::exit(1);
+                    }
+                };
+                i += 2;
+            }
+            "--force" => {
+                force = true;
+                i += 1;
+            }
+            "--help" | "-h" => print_server_hook_help_and_exit(),
+            arg if !arg.starts_with('-') && repo_path.is_none() => {
+                repo_path = Some(arg.to_string());
+                i += 1;
+            }
+            other => {
+                eprintln!("Unknown server-hook argument: {}", other);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    match subcommand.as_str() {
+        "pre-receive" => run_pre_receive(wait),
+        "post-receive" => run_post_receive(wait),
+        "install" => {
+            let repo_path = repo_path.unwrap_or_else(|| ".".to_string());
+            match install_server_hook(Path::new(&repo_path), mode, wait, force) {
+                Ok(path) => eprintln!("Installed {}", path.display()),
+                Err(e) => {
+                    eprintln!("Failed to install server hook: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        "--help" | "-h" | "help" => print_server_hook_help_and_exit(),
+        other => {
+            eprintln!("Unknown server-hook subcommand: {}", other);
+            print_server_hook_help_and_exit();
+        }
+    }
+}
+
+fn print_server_hook_help_and_exit() -> ! {
+    eprintln!("Usage: git-ai server-hook <pre-receive|post-receive|install> [options]");
+    eprintln!();
+    eprintln!("Enforce authorship notes on a server-side (usually bare) repository.");
+    eprintln!();
+    eprintln!("  pre-receive           Reject pushes with commits missing from refs/notes/ai");
+    eprintln!("  post-receive          Record such commits under refs/notes/ai-unverified");
+    eprintln!("  install [<repo>]      Install the hook for --mode into <repo> (default: .)");
+    eprintln!();
+    eprintln!("  --mode <reject|tag>   reject installs pre-receive, tag installs post-receive");
+    eprintln!(
+        "  --wait <secs>         How long to wait for notes pushed in parallel (default: 10)"
+    );
+    eprintln!("  --force               Overwrite an existing hook not written by git-ai");
+    std::process::exit(0);
+}
+
+fn run_pre_receive(wait: Duration) {
+    let updates = read_ref_updates_from_stdin();
+    let missing = match find_unnoted_commits(&updates, wait) {
+        Ok(missing) => missing,
+        Err(e) => {
+            // Fail closed: a broken check shouldn't silently let unverified history in
+            eprintln!("git-ai: failed to verify authorship notes: {}", e);
+            std::process::exit(1);
+        }
+    };
+    if missing.is_empty() {
+        return;
+    }
+
+    report_unnoted_commits(&missing);
+    eprintln!("git-ai: push rejected. Install git-ai so notes are pushed with your commits,");
+    eprintln!("git-ai: or push existing notes first: git push <remote> refs/notes/ai");
+    std::process::exit(1);
+}
+
+fn run_post_receive(wait: Duration) {
+    // post-receive can't undo the push, so errors are reported and otherwise ignored
+    let updates = read_ref_updates_from_stdin();
+    match find_unnoted_commits(&updates, wait) {
+        Ok(missing) if !missing.is_empty() => {
+            report_unnoted_commits(&missing);
+            match mark_unverified(&missing) {
+                Ok(()) => eprintln!("git-ai: marked as unverified in {}", UNVERIFIED_NOTES_REF),
+                Err(e) => eprintln!("git-ai: failed to mark unverified commits: {}", e),
+            }
+        }
+        Ok(_) => {}
+        Err(e) => eprintln!("git-ai: failed to verify authorship notes: {}", e),
+    }
+
+    // Notes that arrive later (e.g. a follow-up `git push origin refs/notes/ai`) verify them
+    if let Err(e) = clear_verified_markers() {
+        eprintln!("git-ai: failed to clear verified commits: {}", e);
+    }
+}
+
+fn report_unnoted_commits(missing: &[String]) {
+    eprintln!(
+        "git-ai: {} pushed commit(s) have no authorship note in refs/notes/{}:",
+        missing.len(),
+        AI_AUTHORSHIP_REFNAME
+    );
+    for sha in missing.iter().take(MAX_REPORTED_COMMITS) {
+        eprintln!("git-ai:   {}", sha);
+    }
+    if missing.len() > MAX_REPORTED_COMMITS {
+        eprintln!(
+            "git-ai:   ... and {} more",
+            missing.len() - MAX_REPORTED_COMMITS
+        );
+    }
+}
+
+fn read_ref_updates_from_stdin() -> Vec<RefUpdate> {
+    let mut input = String::new();
+    if let Err(e) = std::io::stdin().read_to_string(&mut input) {
+        eprintln!("git-ai: failed to read ref updates: {}", e);
+        std::process::exit(1);
+    }
+    parse_ref_updates(&input)
+}
+
+/// Parse receive-hook stdin. Malformed lines are skipped.
+pub fn parse_ref_updates(input: &str) -> Vec<RefUpdate> {
+    input
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let old_sha = parts.next()?;
+            let new_sha = parts.next()?;
+            let ref_name = parts.next()?;
+            if parts.next().is_some() {
+                return None;
+            }
+            Some(RefUpdate {
+                old_sha: old_sha.to_string(),
+                new_sha: new_sha.to_string(),
+                ref_name: ref_name.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Commits newly introduced on branches by `updates` that have no authorship note,
+/// polling for up to `wait` for a parallel notes push to arrive.
+pub fn find_unnoted_commits(
+    updates: &[RefUpdate],
+    wait: Duration,
+) -> Result<Vec<String>, GitAiError> {
+    let commits = pushed_commits(updates)?;
+    if commits.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let notes_ref = format!("refs/notes/{}", AI_AUTHORSHIP_REFNAME);
+    // In pre-receive, notes pushed together with the branch aren't on the ref yet
+    let pushed_notes_tip = updates
+        .iter()
+        .find(|u| u.ref_name == notes_ref && !u.is_deletion())
+        .map(|u| u.new_sha.clone());
+
+    let deadline = Instant::now() + wait;
+    loop {
+        let mut noted = HashSet::new();
+        if let Some(tip) = &pushed_notes_tip {
+            noted.extend(noted_commits_at(tip)?);
+        }
+        if let Some(tip) = resolve_ref(&notes_ref) {
+            noted.extend(noted_commits_at(&tip)?);
+        }
+
+        let missing: Vec<String> = commits
+            .iter()
+            .filter(|sha| !noted.contains(*sha))
+            .cloned()
+            .collect();
+        if missing.is_empty() || Instant::now() >= deadline {
+            return Ok(missing);
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Non-merge commits reachable from the pushed branch tips that weren't reachable before.
+/// Works in both pre-receive (refs not updated yet) and post-receive (refs updated).
+fn pushed_commits(updates: &[RefUpdate]) -> Result<Vec<String>, GitAiError> {
+    let branch_updates: Vec<&RefUpdate> = updates
+        .iter()
+        .filter(|u| u.ref_name.starts_with("refs/heads/") && !u.is_deletion())
+        .collect();
+    if branch_updates.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut args = vec!["rev-list".to_string(), "--no-merges".to_string()];
+    args.extend(branch_updates.iter().map(|u| u.new_sha.clone()));
+    args.push("--not".to_string());
+    args.extend(
+        branch_updates
+            .iter()
+            .filter(|u| !u.is_creation())
+            .map(|u| u.old_sha.clone()),
+    );
+    // Everything already on the server, ignoring the refs this push moves. Not --all: in
+    // post-receive HEAD already points at the pushed branch and --exclude doesn't cover it.
+    args.extend(
+        branch_updates
+            .iter()
+            .map(|u| format!("--exclude={}", u.ref_name)),
+    );
+    args.push("--glob=refs/*".to_string());
+
+    Ok(git_stdout(&args)?
+        .lines()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect())
+}
+
+/// Commits annotated by the notes commit `notes_tip`. Notes trees use fanout
+/// directories (ab/cdef...), so slashes are stripped from the paths.
+fn noted_commits_at(notes_tip: &str) -> Result<HashSet<String>, GitAiError> {
+    let args = vec![
+        "ls-tree".to_string(),
+        "-r".to_string(),
+        "--name-only".to_string(),
+        notes_tip.to_string(),
+    ];
+    Ok(git_stdout(&args)?
+        .lines()
+        .map(|path| path.replace('/', ""))
+        .collect())
+}
+
+fn mark_unverified(commits: &[String]) -> Result<(), GitAiError> {
+    let note = "git-ai: unverified (no authorship note at push time)";
+    for sha in commits {
+        // Server repos usually have no user identity configured, which `git notes` requires
+        let args = vec![
+            "-c".to_string(),
+            "user.name=git-ai".to_string(),
+            "-c".to_string(),
+            "user.email=git-ai@localhost".to_string(),
+            "notes".to_string(),
+            format!("--ref={}", UNVERIFIED_NOTES_REF),
+            "add".to_string(),
+            "-f".to_string(),
+            "-F".to_string(),
+            "-".to_string(),
+            sha.clone(),
+        ];
+        exec_git_stdin(&args, note.as_bytes())?;
+    }
+    Ok(())
+}
+
+fn clear_verified_markers() -> Result<(), GitAiError> {
+    let (Some(unverified_tip), Some(notes_tip)) = (
+        resolve_ref(UNVERIFIED_NOTES_REF),
+        resolve_ref(&format!("refs/notes/{}", AI_AUTHORSHIP_REFNAME)),
+    ) else {
+        return Ok(());
+    };
+    let noted = noted_commits_at(&notes_tip)?;
+    let verified: Vec<String> = noted_commits_at(&unverified_tip)?
+        .into_iter()
+        .filter(|sha| noted.contains(sha))
+        .collect();
+    if verified.is_empty() {
+        return Ok(());
+    }
+
+    let mut args = vec![
+        "-c".to_string(),
+        "user.name=git-ai".to_string(),
+        "-c".to_string(),
+        "user.email=git-ai@localhost".to_string(),
+        "notes".to_string(),
+        format!("--ref={}", UNVERIFIED_NOTES_REF),
+        "remove".to_string(),
+        "--ignore-missing".to_string(),
+    ];
+    args.extend(verified);
+    exec_git(&args)?;
+    Ok(())
+}
+
+/// Write the hook script for `mode` into the repository at `repo_path`
+pub fn install_server_hook(
+    repo_path: &Path,
+    mode: ServerHookMode,
+    wait: Duration,
+    force: bool,
+) -> Result<PathBuf, GitAiError> {
+    let repo_arg = repo_path.to_string_lossy().to_string();
+    let hooks_dir = git_stdout(&[
+        "-C".to_string(),
+        repo_arg,
+        "rev-parse".to_string(),
+        "--git-path".to_string(),
+        "hooks".to_string(),
+    ])?;
+    // --git-path is relative to the repository directory unless absolute
+    let hooks_dir = repo_path.join(hooks_dir.trim());
+    std::fs::create_dir_all(&hooks_dir)?;
+
+    let hook_path = hooks_dir.join(mode.hook_name());
+    if hook_path.exists() && !force {
+        let existing = std::fs::read_to_string(&hook_path).unwrap_or_default();
+        if !existing.contains(SERVER_HOOK_MARKER) {
+            return Err(GitAiError::Generic(format!(
+                "{} already exists; re-run with --force to replace it",
+                hook_path.display()
+            )));
+        }
+    }
+
+    let exe = crate::utils::current_git_ai_exe()?;
+    // GIT_AI=git (debug builds' proxy switch) can leak in from a local push made through git-ai
+    let script = format!(
+        "#!/bin/sh\n{}\nunset GIT_AI\nexec \"{}\" server-hook {} --wait {}\n",
+        SERVER_HOOK_MARKER,
+        exe.display(),
+        mode.hook_name(),
+        wait.as_secs()
+    );
+    std::fs::write(&hook_path, script)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&hook_path, std::fs::Permissions::from_mode(0o755))?;
+    }
+
+    Ok(hook_path)
+}
+
+fn resolve_ref(ref_name: &str) -> Option<String> {
+    let args = vec![
+        "rev-parse".to_string(),
+        "--verify".to_string(),
+        "--quiet".to_string(),
+        ref_name.to_string(),
+    ];
+    git_stdout(&args)
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// Run git against the repository the hook was invoked in (GIT_DIR / cwd)
+fn git_stdout(args: &[String]) -> Result<String, GitAiError> {
+    let output = exec_git(args)?;
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn is_zero_oid(sha: &str) -> bool {
+    !sha.is_empty() && sha.chars().all(|c| c == '0')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ref_updates() {
+        let zero = "0".repeat(40);
+        let input = format!(
+            "{} {} refs/heads/main\n{} {} refs/heads/gone\nnot a valid line\n\n",
+            "a".repeat(40),
+            "b".repeat(40),
+            "c".repeat(40),
+            zero
+        );
+        let updates = parse_ref_updates(&input);
+        assert_eq!(updates.len(), 2);
+        assert_eq!(updates[0].ref_name, "refs/heads/main");
+        assert!(!updates[0].is_creation());
+        assert!(!updates[0].is_deletion());
+        assert!(updates[1].is_deletion());
+    }
+
+    #[test]
+    fn test_mode_selects_hook() {
+        assert_eq!(
+            ServerHookMode::from_str("reject").map(|m| m.hook_name()),
+            Some("pre-receive")
+        );
+        assert_eq!(
+            ServerHookMode::from_str("tag").map(|m| m.hook_name()),
+            Some("post-receive")
+        );
+        assert_eq!(ServerHookMode::from_str("warn"), None);
+    }
+}
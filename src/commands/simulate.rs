@@ -0,0 +1,799 @@
+//! `git-ai simulate`: what the hooks would do for a `git commit` or `git push`, without doing it.
+//!
+//! Everything is computed from local state. The staged changes are attributed the way
+//! post-commit attributes a real commit, and notes pushes are judged against each remote's
+//! notes as of the last fetch. Nothing is written except unreferenced snapshot objects, the same
+//! ones `git-ai status --staged` writes.
+
+use crate::api::{ApiClient, ApiContext};
+use crate::authorship::authorship_log_serialization::AuthorshipLog;
+use crate::authorship::policy::{
+    Enforcement, Policy, PolicyHook, PolicySubject, Violation, commits_to_push, evaluate_commits,
+};
+use crate::authorship::post_commit::committed_event;
+use crate::authorship::secrets::{redact_secrets_from_prompts, strip_prompt_messages};
+use crate::authorship::staged::{
+    StagedAuthorship, StagedComposition, staged_authorship, working_tree_authorship,
+};
+use crate::authorship::stats::{CommitStats, get_git_diff_stats, stats_from_authorship_log};
+use crate::commands::hooks::commit_hooks::{commits_all_tracked, get_commit_default_author};
+use crate::commands::hooks::push_hooks::{pushed_revs, resolve_push_remote, should_sync_notes};
+use crate::commands::machine_output::{self, OutputFormat};
+use crate::config::Config;
+use crate::error::GitAiError;
+use crate::git::authorship_traversal::get_notes_list;
+use crate::git::cli_parser::{ParsedGitInvocation, is_dry_run, parse_git_cli_args};
+use crate::git::find_repository;
+use crate::git::note_signing::{self, SIGNATURES_PUSH_REFSPEC, SIGNATURES_REFNAME};
+use crate::git::pending_pushes::pending_pushes;
+use crate::git::refs::{
+    AI_AUTHORSHIP_PUSH_REFSPEC, AI_AUTHORSHIP_REFNAME, SUMMARY_NOTES_PUSH_REFSPEC,
+    SUMMARY_NOTES_REFNAME, authorship_summary_line, tracking_ref_for_remote,
+};
+use crate::git::repository::Repository;
+use crate::git::sync_authorship::{NotesSyncPolicy, find_rebased_notes};
+use crate::metrics::{MetricEvent, PosEncoded};
+use crate::observability::privacy::TelemetryPrivacy;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+
+pub fn handle_simulate(args: &[String]) {
+    let mut format = OutputFormat::Text;
+    let mut subcommand: Option<&str> = None;
+    let mut git_args: Vec<String> = Vec::new();
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--json" => format = OutputFormat::Json,
+            // After the subcommand it's `git push --porcelain`
+            "--porcelain" if subcommand.is_none() => format = OutputFormat::Porcelain,
+            "--format" => {
+                format = OutputFormat::from_flag_value(args, i);
+                i += 1;
+            }
+            "--help" | "-h" if subcommand.is_none() => print_simulate_help_and_exit(),
+            "commit" | "push" if subcommand.is_none() => subcommand = Some(args[i].as_str()),
+            arg if subcommand.is_none() => {
+                eprintln!("Unknown simulate argument: {}", arg);
+                std::process::exit(1);
+            }
+            arg => git_args.push(arg.to_string()),
+        }
+        i += 1;
+    }
+
+    let Some(subcommand) = subcommand else {
+        print_simulate_help_and_exit();
+    };
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let result = match subcommand {
+        "commit" => simulate_commit(&repo, &git_args).map(|simulation| {
+            if format.is_text() {
+                print_commit_simulation(&simulation);
+            } else {
+                machine_output::print(format, &simulation).unwrap();
+            }
+        }),
+        _ => simulate_push(&repo, &git_args).map(|simulation| {
+            if format.is_text() {
+                print_push_simulation(&simulation);
+            } else {
+                machine_output::print(format, &simulation).unwrap();
+            }
+        }),
+    };
+    if let Err(e) = result {
+        eprintln!("Failed to simulate git {}: {}", subcommand, e);
+        std::process::exit(1);
+    }
+}
+
+/// What post-commit would write and record if the staged changes were committed now
+#[derive(Debug, Serialize)]
+pub struct CommitSimulation {
+    /// The parent of the commit, or "initial"
+    pub head: String,
+    /// `git commit -a`: the working tree's changes rather than the index
+    pub all: bool,
+    pub staged: StagedComposition,
+    /// The authorship note for refs/notes/ai, as it would be written
+    pub note: String,
+    /// Where the prompts' messages would be kept: `note`, `local`, `prompt-store` or `none`
+    pub prompt_messages: &'static str,
+    /// The `git-ai:` line mirrored to refs/notes/commits, when mirroring is on
+    pub summary_note: Option<String>,
+    /// Whether the note would be signed into refs/notes/ai-signatures
+    pub signed: bool,
+    pub stats: CommitStats,
+    /// The `committed` metric event as sinks would receive it, after telemetry privacy rules
+    pub event: MetricEvent,
+    /// `event`, or `daily-aggregate` when only the day's totals leave the machine
+    pub exported: &'static str,
+    pub violations: Vec<SimulatedViolation>,
+}
+
+/// What the push hooks would do for `git push <args>`
+#[derive(Debug, Serialize)]
+pub struct PushSimulation {
+    pub remote: Option<String>,
+    /// The refs the push itself updates
+    pub refs: Vec<RefUpdate>,
+    /// Commits the remote doesn't have yet
+    pub commits: Vec<PushedCommit>,
+    /// Why authorship notes wouldn't be pushed, when they wouldn't
+    pub notes_skipped: Option<String>,
+    /// One entry per remote whose notes would be reconciled, the pushed remote first
+    pub notes: Vec<NotesPush>,
+    /// Remotes with a queued notes push from an earlier failure, which would be retried
+    pub retried: Vec<String>,
+    /// Notes that would move from pre-rebase commits onto their rewrites after the push
+    pub rebased_notes: Vec<SimulatedRebasedNote>,
+    pub violations: Vec<SimulatedViolation>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RefUpdate {
+    pub source: String,
+    pub destination: String,
+    /// `+` refspec or `--force`
+    pub forced: bool,
+    /// Against the remote-tracking branch: `new`, `up-to-date`, `fast-forward`, `forced`,
+    /// `rejected`, `delete` or `unknown`
+    pub mode: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PushedCommit {
+    pub commit: String,
+    pub noted: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NotesPush {
+    pub remote: String,
+    pub refspec: String,
+    /// Notes are never force-pushed; diverged notes are merged first
+    pub forced: bool,
+    /// Against the remote's notes as of the last fetch: `create`, `up-to-date`,
+    /// `fast-forward`, `merge`, `remote-ahead` or `none` when there are no local notes
+    pub mode: &'static str,
+    pub local: Option<String>,
+    pub remote_tip: Option<String>,
+    /// refs/notes/commits and refs/notes/ai-signatures refspecs pushed alongside
+    pub also: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SimulatedRebasedNote {
+    pub old_sha: String,
+    pub new_sha: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SimulatedViolation {
+    pub rule: String,
+    /// `error` blocks the command, `warning` doesn't
+    pub level: &'static str,
+    pub subject: String,
+    pub message: String,
+}
+
+impl From<&Violation> for SimulatedViolation {
+    fn from(violation: &Violation) -> Self {
+        SimulatedViolation {
+            rule: violation.rule.clone(),
+            level: match violation.enforcement {
+                Enforcement::Block => "error",
+                Enforcement::Warn => "warning",
+            },
+            subject: violation.subject.clone(),
+            message: violation.message.clone(),
+        }
+    }
+}
+
+pub fn simulate_commit(repo: &Repository, args: &[String]) -> Result<CommitSimulation, GitAiError> {
+    let all = commits_all_tracked(args);
+    let staged: StagedAuthorship = if all {
+        working_tree_authorship(repo)?
+    } else {
+        staged_authorship(repo)?
+    };
+    let composition = StagedComposition::from_staged(&staged);
+
+    let violations = match Policy::load(repo)? {
+        Some(policy) => {
+            let subject = PolicySubject {
+                label: "staged changes".to_string(),
+                commit: None,
+                authorship_log: &staged.authorship_log,
+                added_lines: &staged.added_lines,
+                message: None,
+            };
+            policy.evaluate(PolicyHook::Commit, &subject)
+        }
+        None => Vec::new(),
+    };
+
+    let mut log = staged.authorship_log.clone();
+    log.metadata.base_commit_sha = staged.snapshot_sha.clone();
+    let prompt_messages = prepare_prompts_for_note(repo, &mut log);
+    let note = log
+        .serialize_to_string()
+        .map_err(|_| GitAiError::Generic("Failed to serialize authorship log".to_string()))?;
+
+    let (added, deleted) = get_git_diff_stats(repo, &staged.snapshot_sha, &[])?;
+    let mut accepted_by_tool: BTreeMap<String, u32> = BTreeMap::new();
+    for agent in &composition.agents {
+        *accepted_by_tool
+            .entry(format!("{}::{}", agent.tool, agent.model))
+            .or_default() += agent.lines;
+    }
+    let stats = stats_from_authorship_log(
+        Some(&log),
+        added,
+        deleted,
+        composition.ai_lines,
+        &accepted_by_tool,
+    );
+
+    let checkpoints = repo
+        .storage
+        .working_log_for_base_commit(&staged.parent_sha)
+        .read_all_checkpoints()?;
+    let (values, attrs) = committed_event(
+        repo,
+        &staged.snapshot_sha,
+        &staged.parent_sha,
+        &get_commit_default_author(repo, args),
+        &log,
+        &stats,
+        &checkpoints,
+    );
+    // The snapshot's message is git-ai's own; use the one the commit would get
+    let values = match commit_message(args) {
+        Some(message) => {
+            let (subject, body) = split_message(&message);
+            let values = values.commit_subject(subject);
+            match body {
+                Some(body) => values.commit_body(body),
+                None => values.commit_body_null(),
+            }
+        }
+        None => values.commit_subject_null().commit_body_null(),
+    };
+    let privacy = TelemetryPrivacy::current();
+    let event = privacy.apply_to_event(&MetricEvent::new(&values, attrs.to_sparse()));
+
+    Ok(CommitSimulation {
+        head: staged.parent_sha.clone(),
+        all,
+        staged: composition,
+        note,
+        prompt_messages,
+        summary_note: if Config::get().mirror_summary_notes() {
+            authorship_summary_line(&log)
+        } else {
+            None
+        },
+        signed: note_signing::signing_enabled(repo),
+        stats,
+        event,
+        exported: if privacy.aggregates() {
+            "daily-aggregate"
+        } else {
+            "event"
+        },
+        violations: violations.iter().map(SimulatedViolation::from).collect(),
+    })
+}
+
+/// Strip or redact the prompts' messages the way post-commit does under `prompt_storage`, and
+/// say where the messages end up. Nothing is uploaded.
+fn prepare_prompts_for_note(repo: &Repository, log: &mut AuthorshipLog) -> &'static str {
+    let excluded = Config::get().should_exclude_prompts(&Some(repo.clone()));
+    match Config::get().prompt_storage() {
+        "local" => {
+            strip_prompt_messages(&mut log.metadata.prompts);
+            "local"
+        }
+        "notes" if !excluded => {
+            redact_secrets_from_prompts(&mut log.metadata.prompts);
+            "note"
+        }
+        "notes" => {
+            strip_prompt_messages(&mut log.metadata.prompts);
+            "none"
+        }
+        _ => {
+            strip_prompt_messages(&mut log.metadata.prompts);
+            let using_custom_api =
+                Config::get().api_base_url() != crate::config::DEFAULT_API_BASE_URL;
+            let logged_in = ApiClient::new(ApiContext::new(None)).is_logged_in();
+            if !excluded && (logged_in || using_custom_api) {
+                "prompt-store"
+            } else {
+                "none"
+            }
+        }
+    }
+}
+
+/// The message given with `-m`/`--message`, paragraphs joined as git joins them
+fn commit_message(args: &[String]) -> Option<String> {
+    let mut paragraphs = Vec::new();
+    let mut i = 0;
+    while i < args.len() {
+        let arg = args[i].as_str();
+        if arg == "--" {
+            break;
+        }
+        if arg == "--message" {
+            paragraphs.extend(args.get(i + 1).cloned());
+            i += 2;
+            continue;
+        }
+        if let Some(message) = arg.strip_prefix("--message=") {
+            paragraphs.push(message.to_string());
+        } else if arg.starts_with('-') && !arg.starts_with("--") {
+            // A cluster such as `-am`; the rest of it, or the next argument, is the flag's value
+            for (at, c) in arg.char_indices().skip(1) {
+                match c {
+                    'm' => {
+                        let rest = &arg[at + 1..];
+                        if rest.is_empty() {
+                            paragraphs.extend(args.get(i + 1).cloned());
+                            i += 1;
+                        } else {
+                            paragraphs.push(rest.to_string());
+                        }
+                        break;
+                    }
+                    'F' | 'C' | 'c' | 't' | 'u' | 'S' => break,
+                    _ => {}
+                }
+            }
+        }
+        i += 1;
+    }
+    if paragraphs.is_empty() {
+        None
+    } else {
+        Some(paragraphs.join("\n\n"))
+    }
+}
+
+/// Subject (the first line) and body (everything after the first blank line) of a message
+fn split_message(message: &str) -> (String, Option<String>) {
+    let message = message.trim();
+    let (subject, body) = match message.split_once("\n\n") {
+        Some((subject, body)) => (subject, Some(body.trim().to_string())),
+        None => (message, None),
+    };
+    let subject = subject.lines().next().unwrap_or_default().to_string();
+    (subject, body.filter(|body| !body.is_empty()))
+}
+
+pub fn simulate_push(repo: &Repository, args: &[String]) -> Result<PushSimulation, GitAiError> {
+    let mut push_args = vec!["push".to_string()];
+    push_args.extend(args.iter().cloned());
+    let parsed = parse_git_cli_args(&push_args);
+    let remote = resolve_push_remote(&parsed, repo);
+
+    let mut simulation = PushSimulation {
+        remote: remote.clone(),
+        refs: Vec::new(),
+        commits: Vec::new(),
+        notes_skipped: None,
+        notes: Vec::new(),
+        retried: Vec::new(),
+        rebased_notes: Vec::new(),
+        violations: Vec::new(),
+    };
+    let Some(remote) = remote else {
+        simulation.notes_skipped = Some("no remote to push to".to_string());
+        return Ok(simulation);
+    };
+    simulation.refs = ref_updates(repo, &parsed, &remote);
+
+    let revs = pushed_revs(&parsed);
+    let commits = if revs.is_empty() {
+        Vec::new()
+    } else {
+        commits_to_push(repo, &remote, &revs)?
+    };
+    let noted: HashSet<String> = get_notes_list(&repo.global_args_for_exec())?
+        .into_iter()
+        .map(|(_, commit)| commit)
+        .collect();
+    simulation.commits = commits
+        .iter()
+        .map(|commit| PushedCommit {
+            commit: commit.clone(),
+            noted: noted.contains(commit),
+        })
+        .collect();
+
+    if !should_sync_notes(&parsed) {
+        simulation.notes_skipped = Some(notes_skip_reason(&parsed).to_string());
+        return Ok(simulation);
+    }
+    if let Some(policy) = Policy::load(repo)? {
+        simulation.violations = evaluate_commits(repo, &policy, PolicyHook::Push, &commits)
+            .iter()
+            .map(SimulatedViolation::from)
+            .collect();
+    }
+
+    let targets = NotesSyncPolicy::for_repository(repo).push_targets(repo, &remote);
+    if targets.is_empty() {
+        simulation.notes_skipped = Some(format!("git-ai.syncRemotes excludes {}", remote));
+        return Ok(simulation);
+    }
+    simulation.notes = targets
+        .iter()
+        .map(|target| notes_push(repo, target))
+        .collect();
+    simulation.retried = pending_pushes(repo)
+        .into_iter()
+        .map(|pending| pending.remote)
+        .filter(|pending| !targets.contains(pending))
+        .collect();
+    simulation.rebased_notes = find_rebased_notes(repo, &remote)?
+        .into_iter()
+        .map(|rebased| SimulatedRebasedNote {
+            old_sha: rebased.old_sha,
+            new_sha: rebased.new_sha,
+        })
+        .collect();
+    Ok(simulation)
+}
+
+fn notes_skip_reason(parsed: &ParsedGitInvocation) -> &'static str {
+    if is_dry_run(&parsed.command_args) {
+        "--dry-run pushes nothing"
+    } else if parsed.has_command_flag("--mirror") {
+        "--mirror pushes refs/notes/ai itself"
+    } else {
+        "the push deletes refs"
+    }
+}
+
+/// How refs/notes/ai would reach `remote`, judged against the remote's notes as of the last fetch
+fn notes_push(repo: &Repository, remote: &str) -> NotesPush {
+    let local = rev_parse(repo, &format!("refs/notes/{}", AI_AUTHORSHIP_REFNAME));
+    let remote_tip = rev_parse(repo, &tracking_ref_for_remote(remote));
+    let mode = match (&local, &remote_tip) {
+        (None, _) => "none",
+        (Some(_), None) => "create",
+        (Some(local), Some(tip)) if local == tip => "up-to-date",
+        (Some(local), Some(tip)) if is_ancestor(repo, tip, local) => "fast-forward",
+        (Some(local), Some(tip)) if is_ancestor(repo, local, tip) => "remote-ahead",
+        _ => "merge",
+    };
+
+    let mut also = Vec::new();
+    if local.is_some() {
+        let summary_ref = format!("refs/notes/{}", SUMMARY_NOTES_REFNAME);
+        if Config::get().mirror_summary_notes() && rev_parse(repo, &summary_ref).is_some() {
+            also.push(SUMMARY_NOTES_PUSH_REFSPEC.to_string());
+        }
+        if rev_parse(repo, &format!("refs/notes/{}", SIGNATURES_REFNAME)).is_some() {
+            also.push(SIGNATURES_PUSH_REFSPEC.to_string());
+        }
+    }
+    NotesPush {
+        remote: remote.to_string(),
+        refspec: AI_AUTHORSHIP_PUSH_REFSPEC.to_string(),
+        forced: false,
+        mode,
+        local,
+        remote_tip,
+        also,
+    }
+}
+
+/// The branch updates the push itself makes, compared with `remote`'s remote-tracking branches
+fn ref_updates(repo: &Repository, parsed: &ParsedGitInvocation, remote: &str) -> Vec<RefUpdate> {
+    let force_all = parsed.command_args.iter().any(|arg| {
+        arg == "-f"
+            || arg == "--force"
+            || arg == "--force-with-lease"
+            || arg.starts_with("--force-with-lease=")
+    });
+    let current_branch = repo
+        .head()
+        .and_then(|head| head.shorthand())
+        .ok()
+        .filter(|branch| branch != "HEAD");
+
+    let mut refspecs: Vec<String> = Vec::new();
+    if parsed.has_command_flag("--all") || parsed.has_command_flag("--branches") {
+        refspecs = repo
+            .git(&["for-each-ref", "--format=%(refname:short)", "refs/heads"])
+            .unwrap_or_default()
+            .lines()
+            .map(str::to_string)
+            .collect();
+    } else {
+        let mut positional = (1..).map_while(|n| parsed.pos_command(n));
+        while let Some(refspec) = positional.next() {
+            if refspec == "tag" {
+                // `tag <name>` is `refs/tags/<name>:refs/tags/<name>`
+                positional.next();
+                continue;
+            }
+            refspecs.push(refspec);
+        }
+        if refspecs.is_empty() && parsed.pos_command(1).is_none() {
+            refspecs.extend(current_branch.clone());
+        }
+    }
+
+    refspecs
+        .iter()
+        .map(|refspec| {
+            let forced = force_all || refspec.starts_with('+');
+            let refspec = refspec.trim_start_matches('+');
+            let (source, destination) = match refspec.split_once(':') {
+                Some((source, destination)) => (source, destination.to_string()),
+                None if refspec == "HEAD" => (refspec, current_branch.clone().unwrap_or_default()),
+                None => (refspec, refspec.to_string()),
+            };
+            let branch = destination
+                .strip_prefix("refs/heads/")
+                .unwrap_or(&destination)
+                .to_string();
+            let mode = if source.is_empty() {
+                "delete"
+            } else if destination.starts_with("refs/") && !destination.starts_with("refs/heads/") {
+                "unknown"
+            } else {
+                let new = rev_parse(repo, &format!("{}^{{commit}}", source));
+                let old = rev_parse(repo, &format!("refs/remotes/{}/{}", remote, branch));
+                match (new, old) {
+                    (None, _) => "unknown",
+                    (Some(_), None) => "new",
+                    (Some(new), Some(old)) if new == old => "up-to-date",
+                    (Some(new), Some(old)) if is_ancestor(repo, &old, &new) => "fast-forward",
+                    _ if forced => "forced",
+                    _ => "rejected",
+                }
+            };
+            RefUpdate {
+                source: source.to_string(),
+                destination: if destination.starts_with("refs/") {
+                    destination.clone()
+                } else {
+                    format!("refs/heads/{}", destination)
+                },
+                forced,
+                mode,
+            }
+        })
+        .collect()
+}
+
+fn rev_parse(repo: &Repository, rev: &str) -> Option<String> {
+    repo.git(&["rev-parse", "--verify", "--quiet", rev])
+        .ok()
+        .map(|sha| sha.trim().to_string())
+        .filter(|sha| !sha.is_empty())
+}
+
+fn is_ancestor(repo: &Repository, ancestor: &str, descendant: &str) -> bool {
+    repo.git(&["merge-base", "--is-ancestor", ancestor, descendant])
+        .is_ok()
+}
+
+fn short(sha: &str) -> &str {
+    &sha[..sha.len().min(7)]
+}
+
+fn print_commit_simulation(simulation: &CommitSimulation) {
+    let staged = &simulation.staged;
+    println!(
+        "Simulated commit of the {} on {}",
+        if simulation.all {
+            "working tree"
+        } else {
+            "staged changes"
+        },
+        if simulation.head == "initial" {
+            "an empty repository"
+        } else {
+            short(&simulation.head)
+        }
+    );
+    println!(
+        "  {} added lines: {} AI, {} human",
+        staged.added_lines, staged.ai_lines, staged.human_lines
+    );
+    for agent in &staged.agents {
+        println!(
+            "  {} {} ({}): {} lines",
+            agent.tool, agent.model, agent.prompt, agent.lines
+        );
+    }
+
+    println!();
+    println!("Note for refs/notes/ai:");
+    println!("{}", simulation.note.trim_end());
+    println!(
+        "  prompt messages: {}",
+        match simulation.prompt_messages {
+            "note" => "stored in the note, secrets redacted",
+            "local" => "kept in the local prompt database only",
+            "prompt-store" => "uploaded to the prompt store, not stored in the note",
+            _ => "not stored",
+        }
+    );
+    if let Some(summary) = &simulation.summary_note {
+        println!("  refs/notes/commits: {}", summary);
+    }
+    if simulation.signed {
+        println!("  signed into refs/notes/ai-signatures");
+    }
+
+    let stats = &simulation.stats;
+    println!();
+    println!(
+        "Metric event committed{}:",
+        if simulation.exported == "daily-aggregate" {
+            " (held for the daily aggregate)"
+        } else {
+            ""
+        }
+    );
+    println!(
+        "  git diff +{} -{}, human {}, AI {} ({} accepted, {} mixed)",
+        stats.git_diff_added_lines,
+        stats.git_diff_deleted_lines,
+        stats.human_additions,
+        stats.ai_additions,
+        stats.ai_accepted,
+        stats.mixed_additions
+    );
+    for (tool_model, tool_stats) in &stats.tool_model_breakdown {
+        println!(
+            "  {}: AI {} ({} accepted)",
+            tool_model, tool_stats.ai_additions, tool_stats.ai_accepted
+        );
+    }
+
+    print_violations(&simulation.violations);
+}
+
+fn print_push_simulation(simulation: &PushSimulation) {
+    let Some(remote) = &simulation.remote else {
+        println!("Nothing to simulate: no remote to push to");
+        return;
+    };
+    println!("Simulated push to {}", remote);
+    for update in &simulation.refs {
+        println!(
+            "  {}:{} {}",
+            if update.forced {
+                format!("+{}", update.source)
+            } else {
+                update.source.clone()
+            },
+            update.destination,
+            update.mode
+        );
+    }
+    let unnoted = simulation.commits.iter().filter(|c| !c.noted).count();
+    println!(
+        "  {} commit{} to push, {} without a note",
+        simulation.commits.len(),
+        if simulation.commits.len() == 1 {
+            ""
+        } else {
+            "s"
+        },
+        unnoted
+    );
+    for commit in simulation.commits.iter().filter(|c| !c.noted) {
+        println!("    {} has no authorship note", short(&commit.commit));
+    }
+
+    println!();
+    if let Some(reason) = &simulation.notes_skipped {
+        println!("Authorship notes would not be pushed: {}", reason);
+        print_violations(&simulation.violations);
+        return;
+    }
+    println!("Authorship notes:");
+    for notes in &simulation.notes {
+        let mode = match notes.mode {
+            "none" => "nothing to push, no local notes",
+            "create" => "create (no remote notes fetched yet; any found are merged first)",
+            "up-to-date" => "up to date",
+            "fast-forward" => "fast-forward",
+            "remote-ahead" => "remote ahead; local notes catch up, nothing to push",
+            _ => "merge the remote's notes, then fast-forward",
+        };
+        println!("  {} {}: {}", notes.remote, notes.refspec, mode);
+        for refspec in &notes.also {
+            println!("  {} {}", notes.remote, refspec);
+        }
+    }
+    for remote in &simulation.retried {
+        println!(
+            "  {} {}: retry of a queued push",
+            remote, AI_AUTHORSHIP_PUSH_REFSPEC
+        );
+    }
+    for rebased in &simulation.rebased_notes {
+        println!(
+            "  note on {} moves to its rewrite {} after the push",
+            short(&rebased.old_sha),
+            short(&rebased.new_sha)
+        );
+    }
+
+    print_violations(&simulation.violations);
+}
+
+fn print_violations(violations: &[SimulatedViolation]) {
+    println!();
+    if violations.is_empty() {
+        println!("Policy: no violations");
+        return;
+    }
+    for violation in violations {
+        println!(
+            "Policy {}: [{}] {}: {}",
+            violation.level, violation.rule, violation.subject, violation.message
+        );
+    }
+}
+
+fn print_simulate_help_and_exit() -> ! {
+    eprintln!("Usage: git-ai simulate [--format <fmt>] commit [git commit args...]");
+    eprintln!("       git-ai simulate [--format <fmt>] push [git push args...]");
+    eprintln!();
+    eprintln!("Shows what git-ai's hooks would do for the command, without changing anything:");
+    eprintln!("  commit   The note that would be written, the committed metric event and");
+    eprintln!("           policy violations for the staged changes (-a: the working tree)");
+    eprintln!("  push     The refs the push updates, the notes refspecs git-ai would push to");
+    eprintln!("           each remote and whether they fast-forward or merge first, and the");
+    eprintln!("           rebased notes that would be retired");
+    eprintln!();
+    eprintln!("  --format <fmt>   text (default), json or porcelain");
+    std::process::exit(0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_commit_message_joins_paragraphs_and_splits_subject() {
+        let message = commit_message(&args(&["-am", "Fix parser", "-m", "Details\nhere"]));
+        assert_eq!(message.as_deref(), Some("Fix parser\n\nDetails\nhere"));
+        assert_eq!(
+            split_message(&message.unwrap()),
+            ("Fix parser".to_string(), Some("Details\nhere".to_string()))
+        );
+        assert_eq!(
+            commit_message(&args(&["--message=One line"])).as_deref(),
+            Some("One line")
+        );
+        assert_eq!(commit_message(&args(&["--", "-m"])), None);
+        assert_eq!(split_message("Subject\n"), ("Subject".to_string(), None));
+    }
+}
@@ -0,0 +1,861 @@
+use crate::authorship::stats_cache::{FileAgentLines, with_stats_cache};
+use crate::commands::reporting::percent;
+use crate::config::Config;
+use crate::error::GitAiError;
+use crate::export::csv::csv_field;
+use crate::git::find_repository;
+use crate::git::repository::Repository;
+use crate::git::workspace::{Workspace, load_workspace_or_exit};
+use chrono::{Datelike, TimeZone, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Key used for human-written lines when grouping by tool or model
+pub(crate) const HUMAN_ROW_KEY: &str = "human";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportGrouping {
+    Author,
+    Tool,
+    Model,
+    Directory,
+    Week,
+    Month,
+}
+
+impl ReportGrouping {
+    fn from_str(input: &str) -> Option<Self> {
+        match input {
+            "author" => Some(ReportGrouping::Author),
+            "tool" => Some(ReportGrouping::Tool),
+            "model" => Some(ReportGrouping::Model),
+            "directory" | "dir" => Some(ReportGrouping::Directory),
+            "week" => Some(ReportGrouping::Week),
+            "month" => Some(ReportGrouping::Month),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            ReportGrouping::Author => "author",
+            ReportGrouping::Tool => "tool",
+            ReportGrouping::Model => "model",
+            ReportGrouping::Directory => "directory",
+            ReportGrouping::Week => "week",
+            ReportGrouping::Month => "month",
+        }
+    }
+
+    fn is_time_window(&self) -> bool {
+        matches!(self, ReportGrouping::Week | ReportGrouping::Month)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReportFormat {
+    Table,
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReportOptions {
+    /// Revision to walk back from
+    pub rev: String,
+    /// Passed to `git log --since` / `--until`, so any date git understands works
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub by: ReportGrouping,
+    pub include_bots: bool,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ReportRow {
+    pub key: String,
+    pub commits: usize,
+    pub ai_lines: u64,
+    pub human_lines: u64,
+    pub ai_percent: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StatsReport {
+    pub by: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<String>,
+    pub total_commits: usize,
+    pub commits_with_authorship: usize,
+    /// Commits by configured bot identities left out of the numbers above
+    pub bot_commits_excluded: usize,
+    pub ai_lines: u64,
+    pub human_lines: u64,
+    pub ai_percent: f64,
+    pub rows: Vec<ReportRow>,
+}
+
+/// One repository's totals in a workspace report
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkspaceRepoTotals {
+    pub name: String,
+    pub path: String,
+    /// Set when the repository couldn't be read; it is left out of the combined numbers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub total_commits: usize,
+    pub commits_with_authorship: usize,
+    pub ai_lines: u64,
+    pub human_lines: u64,
+    pub ai_percent: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkspaceReport {
+    /// Totals and rows summed across the workspace's repositories
+    #[serde(flatten)]
+    pub combined: StatsReport,
+    pub repositories: Vec<WorkspaceRepoTotals>,
+}
+
+/// One non-merge commit from the walk, with lines added per file
+#[derive(Debug, Clone)]
+struct ReportCommit {
+    sha: String,
+    author: String,
+    timestamp: i64,
+    added_lines: Vec<(String, u64)>,
+}
+
+pub fn handle_stats_report(args: &[String]) {
+    let mut options = ReportOptions {
+        rev: "HEAD".to_string(),
+        since: None,
+        until: None,
+        by: ReportGrouping::Author,
+        include_bots: false,
+    };
+    let mut format = ReportFormat::Table;
+    let mut workspace = false;
+
+    let mut i = 0;
+    while i < args.len() {
+        let value = args.get(i + 1);
+        match args[i].as_str() {
+            "--since" | "--until" | "--by" | "--format" if value.is_none() => {
+                eprintln!("{} requires a value", args[i]);
+                std::process::exit(1);
+            }
+            "--since" => {
+                options.since = value.cloned();
+                i += 2;
+            }
+            "--until" => {
+                options.until = value.cloned();
+                i += 2;
+            }
+            "--by" => {
+                options.by = match value.and_then(|v| ReportGrouping::from_str(v)) {
+                    Some(by) => by,
+                    None => {
+                        eprintln!(
+                            "--by must be one of: author, tool, model, directory, week, month"
+                        );
+                        std::process::exit(1);
+                    }
+                };
+                i += 2;
+            }
+            "--format" => {
+                format = match value.map(String::as_str) {
+                    Some("table") => ReportFormat::Table,
+                    Some("json") => ReportFormat::Json,
+                    Some("csv") => ReportFormat::Csv,
+                    _ => {
+                        eprintln!("--format must be one of: table, json, csv");
+                        std::process::exit(1);
+                    }
+                };
+                i += 2;
+            }
+            "--json" => {
+                format = ReportFormat::Json;
+                i += 1;
+            }
+            "--include-bots" => {
+                options.include_bots = true;
+                i += 1;
+            }
+            "--workspace" => {
+                workspace = true;
+                i += 1;
+            }
+            "--help" | "-h" => print_stats_report_help_and_exit(),
+            arg if !arg.starts_with('-') => {
+                options.rev = arg.to_string();
+                i += 1;
+            }
+            other => {
+                eprintln!("Unknown stats report argument: {}", other);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if workspace {
+        let report = build_workspace_report(&load_workspace_or_exit(), &options);
+        match format {
+            ReportFormat::Table => print_workspace_report_table(&report),
+            ReportFormat::Json => println!(
+                "{}",
+                serde_json::to_string_pretty(&report).unwrap_or_else(|_| "{}".to_string())
+            ),
+            ReportFormat::Csv => print!("{}", workspace_report_to_csv(&report)),
+        }
+        return;
+    }
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let report = match build_stats_report(&repo, &options) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Stats report failed: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    match format {
+        ReportFormat::Table => print_report_table(&report),
+        ReportFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&report).unwrap_or_else(|_| "{}".to_string())
+        ),
+        ReportFormat::Csv => print!("{}", report_to_csv(&report)),
+    }
+}
+
+fn print_stats_report_help_and_exit() -> ! {
+    eprintln!("Usage: git-ai stats report [<rev>] [options]");
+    eprintln!();
+    eprintln!(
+        "Summarize AI vs human lines across the history reachable from <rev> (default: HEAD)."
+    );
+    eprintln!();
+    eprintln!("  --since <date>        Only commits after this date (any format git log accepts)");
+    eprintln!("  --until <date>        Only commits before this date");
+    eprintln!("  --by <key>            Group by author, tool, model, directory, week or month");
+    eprintln!("  --format <fmt>        table (default), json or csv");
+    eprintln!("  --include-bots        Count commits by bot_authors identities");
+    eprintln!("  --workspace           Report across every repository in the workspace");
+    std::process::exit(0);
+}
+
+pub fn build_stats_report(
+    repo: &Repository,
+    options: &ReportOptions,
+) -> Result<StatsReport, GitAiError> {
+    let (commits, bot_commits_excluded) = select_report_commits(repo, options)?;
+    let ai_lines = load_ai_lines(repo, &commits)?;
+    let mut report = aggregate_report(&commits, &ai_lines, options.by);
+    report.since = options.since.clone();
+    report.until = options.until.clone();
+    report.bot_commits_excluded = bot_commits_excluded;
+    Ok(report)
+}
+
+/// Run the report in every workspace repository and sum the results. A repository that can't be
+/// read is listed with its error rather than failing the whole report.
+pub fn build_workspace_report(workspace: &Workspace, options: &ReportOptions) -> WorkspaceReport {
+    let mut reports = Vec::new();
+    let mut repositories = Vec::new();
+    for entry in &workspace.repositories {
+        let mut totals = WorkspaceRepoTotals {
+            name: entry.name.clone(),
+            path: entry.path.display().to_string(),
+            error: None,
+            total_commits: 0,
+            commits_with_authorship: 0,
+            ai_lines: 0,
+            human_lines: 0,
+            ai_percent: 0.0,
+        };
+        match entry
+            .open()
+            .and_then(|repo| build_stats_report(&repo, options))
+        {
+            Ok(report) => {
+                totals.total_commits = report.total_commits;
+                totals.commits_with_authorship = report.commits_with_authorship;
+                totals.ai_lines = report.ai_lines;
+                totals.human_lines = report.human_lines;
+                totals.ai_percent = report.ai_percent;
+                reports.push((entry.name.as_str(), report));
+            }
+            Err(e) => totals.error = Some(e.to_string()),
+        }
+        repositories.push(totals);
+    }
+
+    WorkspaceReport {
+        combined: merge_reports(&reports, options),
+        repositories,
+    }
+}
+
+/// Sum per-repository reports into one. Rows with the same key are merged, except directories,
+/// which are prefixed with the repository name since `src/` in two repositories isn't one place.
+fn merge_reports(reports: &[(&str, StatsReport)], options: &ReportOptions) -> StatsReport {
+    let mut rows: BTreeMap<String, ReportRow> = BTreeMap::new();
+    for (name, report) in reports {
+        for row in &report.rows {
+            let key = match options.by {
+                ReportGrouping::Directory if row.key == "." => format!("{}/", name),
+                ReportGrouping::Directory => format!("{}/{}", name, row.key),
+                _ => row.key.clone(),
+            };
+            let merged = rows.entry(key.clone()).or_insert_with(|| ReportRow {
+                key,
+                commits: 0,
+                ai_lines: 0,
+                human_lines: 0,
+                ai_percent: 0.0,
+            });
+            merged.commits += row.commits;
+            merged.ai_lines += row.ai_lines;
+            merged.human_lines += row.human_lines;
+        }
+    }
+    let mut rows: Vec<ReportRow> = rows
+        .into_values()
+        .map(|mut row| {
+            row.ai_percent = percent(row.ai_lines, row.human_lines);
+            row
+        })
+        .collect();
+    sort_rows(&mut rows, options.by);
+
+    let ai_lines = reports.iter().map(|(_, r)| r.ai_lines).sum();
+    let human_lines = reports.iter().map(|(_, r)| r.human_lines).sum();
+    StatsReport {
+        by: options.by.as_str().to_string(),
+        since: options.since.clone(),
+        until: options.until.clone(),
+        total_commits: reports.iter().map(|(_, r)| r.total_commits).sum(),
+        commits_with_authorship: reports.iter().map(|(_, r)| r.commits_with_authorship).sum(),
+        bot_commits_excluded: reports.iter().map(|(_, r)| r.bot_commits_excluded).sum(),
+        ai_lines,
+        human_lines,
+        ai_percent: percent(ai_lines, human_lines),
+        rows,
+    }
+}
+
+/// AI and human lines added to each path by the commits `options` selects, as
+/// `path -> (ai_lines, human_lines)`. `options.by` is ignored.
+pub(crate) fn lines_by_file(
+    repo: &Repository,
+    options: &ReportOptions,
+) -> Result<BTreeMap<String, (u64, u64)>, GitAiError> {
+    let (commits, _) = select_report_commits(repo, options)?;
+    let ai_lines = load_ai_lines(repo, &commits)?;
+
+    let mut by_file: BTreeMap<String, (u64, u64)> = BTreeMap::new();
+    for commit in &commits {
+        let ai_by_file = ai_lines
+            .get(&commit.sha)
+            .map(|lines| ai_lines_by_file(lines))
+            .unwrap_or_default();
+        for (path, added) in &commit.added_lines {
+            let agents = ai_by_file.get(path.as_str()).map(Vec::as_slice);
+            let (ai_by_agent, human) = split_added_lines(*added, agents.unwrap_or_default());
+            let totals = by_file.entry(path.clone()).or_default();
+            totals.0 += ai_by_agent.iter().map(|(_, _, lines)| lines).sum::<u64>();
+            totals.1 += human;
+        }
+    }
+    by_file.retain(|_, (ai, human)| *ai + *human > 0);
+    Ok(by_file)
+}
+
+/// The walk's commits, without bot commits unless `include_bots`, and how many bot commits
+/// were dropped
+fn select_report_commits(
+    repo: &Repository,
+    options: &ReportOptions,
+) -> Result<(Vec<ReportCommit>, usize), GitAiError> {
+    let mut commits = list_report_commits(repo, options)?;
+    if options.include_bots {
+        return Ok((commits, 0));
+    }
+    let config = Config::get();
+    let before = commits.len();
+    commits.retain(|commit| !config.is_bot_author(&commit.author));
+    let excluded = before - commits.len();
+    Ok((commits, excluded))
+}
+
+fn list_report_commits(
+    repo: &Repository,
+    options: &ReportOptions,
+) -> Result<Vec<ReportCommit>, GitAiError> {
+    // %aN/%aE apply .mailmap so one person with several identities aggregates as one author
+    let mut args = vec![
+        "log".to_string(),
+        "--no-merges".to_string(),
+        "--no-renames".to_string(),
+        "--numstat".to_string(),
+        "--format=%x00%H%x09%aN%x09%aE%x09%at".to_string(),
+    ];
+    if let Some(since) = &options.since {
+        args.push(format!("--since={}", since));
+    }
+    if let Some(until) = &options.until {
+        args.push(format!("--until={}", until));
+    }
+    args.push(options.rev.clone());
+    args.push("--".to_string());
+
+    let output = repo.git(&args.iter().map(String::as_str).collect::<Vec<_>>())?;
+    Ok(parse_report_log(&output))
+}
+
+fn parse_report_log(output: &str) -> Vec<ReportCommit> {
+    let mut commits = Vec::new();
+    for record in output.split('\0').filter(|r| !r.trim().is_empty()) {
+        let mut lines = record.lines();
+        let Some(header) = lines.next() else {
+            continue;
+        };
+        let fields: Vec<&str> = header.split('\t').collect();
+        if fields.len() < 4 {
+            continue;
+        }
+
+        let added_lines = lines
+            .filter_map(|line| {
+                let mut parts = line.splitn(3, '\t');
+                // Binary files show "-" and have no lines to attribute
+                let added = parts.next()?.parse::<u64>().ok()?;
+                let _deleted = parts.next()?;
+                Some((parts.next()?.to_string(), added))
+            })
+            .collect();
+
+        commits.push(ReportCommit {
+            sha: fields[0].to_string(),
+            author: format!("{} <{}>", fields[1], fields[2]),
+            timestamp: fields[3].trim().parse().unwrap_or(0),
+            added_lines,
+        });
+    }
+    commits
+}
+
+/// Attested lines for the report's commits, from the stats cache. Notes the cache hasn't seen
+/// are read in one `cat-file --batch`.
+fn load_ai_lines(
+    repo: &Repository,
+    commits: &[ReportCommit],
+) -> Result<HashMap<String, Vec<FileAgentLines>>, GitAiError> {
+    let shas: Vec<String> = commits.iter().map(|c| c.sha.clone()).collect();
+    with_stats_cache(repo, |cache| cache.ai_lines(repo, &shas))
+}
+
+#[derive(Default)]
+struct RowTotals {
+    commits: HashSet<String>,
+    ai_lines: u64,
+    human_lines: u64,
+}
+
+fn aggregate_report(
+    commits: &[ReportCommit],
+    ai_lines: &HashMap<String, Vec<FileAgentLines>>,
+    by: ReportGrouping,
+) -> StatsReport {
+    let mut rows: BTreeMap<String, RowTotals> = BTreeMap::new();
+    let mut total_ai = 0;
+    let mut total_human = 0;
+
+    let mut add = |key: String, sha: &str, ai: u64, human: u64| {
+        if ai == 0 && human == 0 {
+            return;
+        }
+        let row = rows.entry(key).or_default();
+        row.commits.insert(sha.to_string());
+        row.ai_lines += ai;
+        row.human_lines += human;
+    };
+
+    for commit in commits {
+        let ai_by_file = ai_lines
+            .get(&commit.sha)
+            .map(|lines| ai_lines_by_file(lines))
+            .unwrap_or_default();
+
+        for (path, added) in &commit.added_lines {
+            let agents = ai_by_file.get(path.as_str()).map(Vec::as_slice);
+            let (ai_by_agent, human) = split_added_lines(*added, agents.unwrap_or_default());
+            let ai_in_file: u64 = ai_by_agent.iter().map(|(_, _, lines)| lines).sum();
+            total_ai += ai_in_file;
+            total_human += human;
+
+            match by {
+                ReportGrouping::Tool | ReportGrouping::Model => {
+                    for (tool, model, lines) in ai_by_agent {
+                        let key = if by == ReportGrouping::Tool {
+                            tool
+                        } else {
+                            model
+                        };
+                        add(key.to_string(), &commit.sha, lines, 0);
+                    }
+                    add(HUMAN_ROW_KEY.to_string(), &commit.sha, 0, human);
+                }
+                _ => {
+                    let key = match by {
+                        ReportGrouping::Author => commit.author.clone(),
+                        ReportGrouping::Directory => top_level_directory(path),
+                        _ => time_window_key(commit.timestamp, by),
+                    };
+                    add(key, &commit.sha, ai_in_file, human);
+                }
+            }
+        }
+    }
+
+    let mut rows: Vec<ReportRow> = rows
+        .into_iter()
+        .map(|(key, totals)| ReportRow {
+            key,
+            commits: totals.commits.len(),
+            ai_lines: totals.ai_lines,
+            human_lines: totals.human_lines,
+            ai_percent: percent(totals.ai_lines, totals.human_lines),
+        })
+        .collect();
+    sort_rows(&mut rows, by);
+
+    StatsReport {
+        by: by.as_str().to_string(),
+        since: None,
+        until: None,
+        total_commits: commits.len(),
+        commits_with_authorship: commits
+            .iter()
+            .filter(|c| ai_lines.contains_key(&c.sha))
+            .count(),
+        bot_commits_excluded: 0,
+        ai_lines: total_ai,
+        human_lines: total_human,
+        ai_percent: percent(total_ai, total_human),
+        rows,
+    }
+}
+
+/// Time windows read chronologically; everything else leads with the biggest AI users
+fn sort_rows(rows: &mut [ReportRow], by: ReportGrouping) {
+    if !by.is_time_window() {
+        rows.sort_by(|a, b| b.ai_lines.cmp(&a.ai_lines).then(a.key.cmp(&b.key)));
+    }
+}
+
+/// file path -> (tool, model, attested lines) for each prompt in the commit's note
+fn ai_lines_by_file(lines: &[FileAgentLines]) -> HashMap<&str, Vec<(&str, &str, u64)>> {
+    let mut by_file: HashMap<&str, Vec<(&str, &str, u64)>> = HashMap::new();
+    for entry in lines {
+        by_file.entry(entry.path.as_str()).or_default().push((
+            entry.tool.as_str(),
+            entry.model.as_str(),
+            entry.lines,
+        ));
+    }
+    by_file
+}
+
+/// Split the lines a commit added to a file between its attested agents and humans.
+/// Attestations can't claim more lines than the commit added to the file.
+fn split_added_lines<'a>(
+    added: u64,
+    agents: &[(&'a str, &'a str, u64)],
+) -> (Vec<(&'a str, &'a str, u64)>, u64) {
+    let mut remaining = added;
+    let ai_by_agent = agents
+        .iter()
+        .map(|&(tool, model, lines)| {
+            let lines = lines.min(remaining);
+            remaining -= lines;
+            (tool, model, lines)
+        })
+        .collect();
+    (ai_by_agent, remaining)
+}
+
+fn top_level_directory(path: &str) -> String {
+    match path.split_once('/') {
+        Some((dir, _)) => format!("{}/", dir),
+        None => ".".to_string(),
+    }
+}
+
+fn time_window_key(timestamp: i64, by: ReportGrouping) -> String {
+    let Some(date) = Utc.timestamp_opt(timestamp, 0).single() else {
+        return "unknown".to_string();
+    };
+    if by == ReportGrouping::Week {
+        let week = date.iso_week();
+        format!("{}-W{:02}", week.year(), week.week())
+    } else {
+        date.format("%Y-%m").to_string()
+    }
+}
+
+fn print_report_table(report: &StatsReport) {
+    if report.rows.is_empty() {
+        println!("No commits with added lines in this range.");
+        return;
+    }
+
+    let header = report.by.to_uppercase();
+    let key_width = report
+        .rows
+        .iter()
+        .map(|row| row.key.chars().count())
+        .chain([header.len(), "TOTAL".len()])
+        .max()
+        .unwrap_or(0);
+
+    println!(
+        "{:<width$}  {:>7}  {:>9}  {:>11}  {:>6}",
+        header,
+        "COMMITS",
+        "AI LINES",
+        "HUMAN LINES",
+        "AI %",
+        width = key_width
+    );
+    for row in &report.rows {
+        println!(
+            "{:<width$}  {:>7}  {:>9}  {:>11}  {:>5.1}%",
+            row.key,
+            row.commits,
+            row.ai_lines,
+            row.human_lines,
+            row.ai_percent,
+            width = key_width
+        );
+    }
+    println!(
+        "{:<width$}  {:>7}  {:>9}  {:>11}  {:>5.1}%",
+        "TOTAL",
+        report.total_commits,
+        report.ai_lines,
+        report.human_lines,
+        report.ai_percent,
+        width = key_width
+    );
+
+    println!();
+    println!(
+        "{} of {} commits carry authorship notes",
+        report.commits_with_authorship, report.total_commits
+    );
+    if report.bot_commits_excluded > 0 {
+        println!(
+            "{} bot commits excluded (use --include-bots to count them)",
+            report.bot_commits_excluded
+        );
+    }
+}
+
+fn print_workspace_report_table(report: &WorkspaceReport) {
+    let name_width = report
+        .repositories
+        .iter()
+        .map(|repo| repo.name.chars().count())
+        .chain(["REPOSITORY".len()])
+        .max()
+        .unwrap_or(0);
+    println!(
+        "{:<width$}  {:>7}  {:>9}  {:>11}  {:>6}",
+        "REPOSITORY",
+        "COMMITS",
+        "AI LINES",
+        "HUMAN LINES",
+        "AI %",
+        width = name_width
+    );
+    for repo in &report.repositories {
+        match &repo.error {
+            Some(error) => println!(
+                "{:<width$}  error: {}",
+                repo.name,
+                error,
+                width = name_width
+            ),
+            None => println!(
+                "{:<width$}  {:>7}  {:>9}  {:>11}  {:>5.1}%",
+                repo.name,
+                repo.total_commits,
+                repo.ai_lines,
+                repo.human_lines,
+                repo.ai_percent,
+                width = name_width
+            ),
+        }
+    }
+    println!();
+    print_report_table(&report.combined);
+}
+
+/// The combined rows; per-repository totals are in the JSON output
+fn workspace_report_to_csv(report: &WorkspaceReport) -> String {
+    report_to_csv(&report.combined)
+}
+
+fn report_to_csv(report: &StatsReport) -> String {
+    let mut out = format!("{},commits,ai_lines,human_lines,ai_percent\n", report.by);
+    for row in &report.rows {
+        out.push_str(&format!(
+            "{},{},{},{},{:.1}\n",
+            csv_field(&row.key),
+            row.commits,
+            row.ai_lines,
+            row.human_lines,
+            row.ai_percent
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_utils::synthetic_authorship_log;
+
+    #[test]
+    fn test_parse_report_log() {
+        let output = "\0abc\tAna\tana@example.com\t1700000000\n\n3\t1\tsrc/lib.rs\n-\t-\tlogo.png\n\0def\tBo\tbo@example.com\t1700000100\n";
+        let commits = parse_report_log(output);
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].author, "Ana <ana@example.com>");
+        assert_eq!(commits[0].added_lines, vec![("src/lib.rs".to_string(), 3)]);
+        assert!(commits[1].added_lines.is_empty());
+    }
+
+    #[test]
+    fn test_aggregate_report_caps_ai_lines_and_groups() {
+        // 1 file x 1 entry = 6 attested lines in src/module_0/file_0.rs
+        let log = synthetic_authorship_log(1, 1);
+        let commits = vec![
+            ReportCommit {
+                sha: "a".to_string(),
+                author: "Ana <ana@example.com>".to_string(),
+                timestamp: 1_700_000_000,
+                added_lines: vec![
+                    ("src/module_0/file_0.rs".to_string(), 10),
+                    ("README.md".to_string(), 2),
+                ],
+            },
+            ReportCommit {
+                sha: "b".to_string(),
+                author: "Bo <bo@example.com>".to_string(),
+                timestamp: 1_700_000_000,
+                added_lines: vec![("src/module_0/file_0.rs".to_string(), 4)],
+            },
+        ];
+        let lines = FileAgentLines::from_log(&log);
+        let logs = HashMap::from([("a".to_string(), lines.clone()), ("b".to_string(), lines)]);
+
+        let by_author = aggregate_report(&commits, &logs, ReportGrouping::Author);
+        assert_eq!(by_author.ai_lines, 10);
+        assert_eq!(by_author.human_lines, 6);
+        assert_eq!(by_author.rows[0].key, "Ana <ana@example.com>");
+        assert_eq!(by_author.rows[0].ai_lines, 6);
+        assert_eq!(by_author.rows[1].ai_lines, 4);
+        assert_eq!(by_author.rows[1].human_lines, 0);
+
+        let by_tool = aggregate_report(&commits, &logs, ReportGrouping::Tool);
+        let keys: Vec<&str> = by_tool.rows.iter().map(|r| r.key.as_str()).collect();
+        assert_eq!(keys, vec!["bench", HUMAN_ROW_KEY]);
+        assert_eq!(by_tool.rows[0].commits, 2);
+
+        let by_dir = aggregate_report(&commits, &logs, ReportGrouping::Directory);
+        let keys: Vec<&str> = by_dir.rows.iter().map(|r| r.key.as_str()).collect();
+        assert_eq!(keys, vec!["src/", "."]);
+
+        let by_month = aggregate_report(&commits, &logs, ReportGrouping::Month);
+        assert_eq!(by_month.rows[0].key, "2023-11");
+    }
+
+    #[test]
+    fn test_merge_reports_sums_rows_and_prefixes_directories() {
+        let row = |key: &str, ai_lines: u64, human_lines: u64| ReportRow {
+            key: key.to_string(),
+            commits: 1,
+            ai_lines,
+            human_lines,
+            ai_percent: percent(ai_lines, human_lines),
+        };
+        let report = |rows: Vec<ReportRow>| StatsReport {
+            by: "tool".to_string(),
+            since: None,
+            until: None,
+            total_commits: rows.len(),
+            commits_with_authorship: 1,
+            bot_commits_excluded: 0,
+            ai_lines: rows.iter().map(|r| r.ai_lines).sum(),
+            human_lines: rows.iter().map(|r| r.human_lines).sum(),
+            ai_percent: 0.0,
+            rows,
+        };
+        let mut options = ReportOptions {
+            rev: "HEAD".to_string(),
+            since: None,
+            until: None,
+            by: ReportGrouping::Tool,
+            include_bots: false,
+        };
+
+        let reports = vec![
+            (
+                "api",
+                report(vec![row("cursor", 6, 0), row(HUMAN_ROW_KEY, 0, 4)]),
+            ),
+            (
+                "web",
+                report(vec![row("cursor", 2, 0), row("claude", 8, 0)]),
+            ),
+        ];
+        let merged = merge_reports(&reports, &options);
+        assert_eq!(merged.total_commits, 4);
+        assert_eq!(merged.ai_lines, 16);
+        assert_eq!(merged.ai_percent, 80.0);
+        let rows: Vec<(&str, usize, u64)> = merged
+            .rows
+            .iter()
+            .map(|r| (r.key.as_str(), r.commits, r.ai_lines))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![("claude", 1, 8), ("cursor", 2, 8), (HUMAN_ROW_KEY, 1, 0)]
+        );
+
+        options.by = ReportGrouping::Directory;
+        let reports = vec![
+            ("api", report(vec![row("src/", 1, 1)])),
+            ("web", report(vec![row("src/", 3, 1), row(".", 0, 2)])),
+        ];
+        let merged = merge_reports(&reports, &options);
+        let keys: Vec<&str> = merged.rows.iter().map(|r| r.key.as_str()).collect();
+        assert_eq!(keys, vec!["web/src/", "api/src/", "web/"]);
+    }
+}
@@ -0,0 +1,323 @@
+use crate::authorship::authorship_log::RevertedLines;
+use crate::authorship::revert::reverted_lines;
+use crate::commands::machine_output::{self, OutputFormat};
+use crate::commands::stats_report::{
+    HUMAN_ROW_KEY, ReportGrouping, ReportOptions, build_stats_report,
+};
+use crate::config::Config;
+use crate::error::GitAiError;
+use crate::git::find_repository;
+use crate::git::refs::get_authorship;
+use crate::git::repository::Repository;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// One tool's committed AI lines against the AI lines reverts removed
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct RevertRow {
+    pub tool: String,
+    pub ai_lines: u64,
+    pub ai_lines_reverted: u64,
+    pub reverted_percent: f64,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct RevertCommit {
+    pub commit: String,
+    pub subject: String,
+    pub reverted_commit: String,
+    pub ai_lines: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RevertsReport {
+    pub rev: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<String>,
+    /// AI lines committed in the walk
+    pub ai_lines: u64,
+    /// AI lines removed by the walk's reverts
+    pub ai_lines_reverted: u64,
+    pub reverted_percent: f64,
+    /// Most reverted first
+    pub rows: Vec<RevertRow>,
+    /// Newest first
+    pub reverts: Vec<RevertCommit>,
+}
+
+pub fn handle_stats_reverts(args: &[String]) {
+    let mut options = ReportOptions {
+        rev: "HEAD".to_string(),
+        since: None,
+        until: None,
+        by: ReportGrouping::Tool,
+        include_bots: false,
+    };
+    let mut format = OutputFormat::Text;
+
+    let mut i = 0;
+    while i < args.len() {
+        let value = args.get(i + 1);
+        match args[i].as_str() {
+            "--since" | "--until" if value.is_none() => {
+                eprintln!("{} requires a value", args[i]);
+                std::process::exit(1);
+            }
+            "--since" => {
+                options.since = value.cloned();
+                i += 2;
+            }
+            "--until" => {
+                options.until = value.cloned();
+                i += 2;
+            }
+            "--include-bots" => {
+                options.include_bots = true;
+                i += 1;
+            }
+            "--format" => {
+                format = OutputFormat::from_flag_value(args, i);
+                i += 2;
+            }
+            "--json" => {
+                format = OutputFormat::Json;
+                i += 1;
+            }
+            "--porcelain" => {
+                format = OutputFormat::Porcelain;
+                i += 1;
+            }
+            "--help" | "-h" => print_stats_reverts_help_and_exit(),
+            arg if !arg.starts_with('-') => {
+                options.rev = arg.to_string();
+                i += 1;
+            }
+            other => {
+                eprintln!("Unknown stats --reverts argument: {}", other);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let report = match build_reverts_report(&repo, &options) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Stats failed: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if format.is_text() {
+        print_reverts_table(&report);
+    } else {
+        machine_output::print(format, &report).unwrap();
+    }
+}
+
+fn print_stats_reverts_help_and_exit() -> ! {
+    eprintln!("Usage: git-ai stats --reverts [<rev>] [options]");
+    eprintln!();
+    eprintln!("Show how much AI-written code was rolled back by `git revert` in the history");
+    eprintln!("reachable from <rev> (default: HEAD), per tool.");
+    eprintln!();
+    eprintln!("  --since <date>        Only commits after this date (any format git log accepts)");
+    eprintln!("  --until <date>        Only commits before this date");
+    eprintln!("  --include-bots        Count commits by bot_authors identities");
+    eprintln!("  --format <fmt>        text (default), json or porcelain");
+    std::process::exit(0);
+}
+
+pub fn build_reverts_report(
+    repo: &Repository,
+    options: &ReportOptions,
+) -> Result<RevertsReport, GitAiError> {
+    let committed = build_stats_report(repo, options)?;
+    let mut ai_by_tool: BTreeMap<String, u64> = committed
+        .rows
+        .into_iter()
+        .filter(|row| row.key != HUMAN_ROW_KEY)
+        .map(|row| (row.key, row.ai_lines))
+        .collect();
+
+    let mut reverted_by_tool: BTreeMap<String, u64> = BTreeMap::new();
+    let mut reverts = Vec::new();
+    for (commit, subject) in list_reverts(repo, options)? {
+        let reverted = match get_authorship(repo, &commit).and_then(|log| log.metadata.reverted) {
+            Some(reverted) => Some(reverted),
+            // Reverts made before git-ai recorded them, or outside any hook
+            None => reverted_lines(repo, &commit)?,
+        };
+        let Some(RevertedLines {
+            commit: reverted_commit,
+            ai_lines,
+            agents,
+        }) = reverted
+        else {
+            continue;
+        };
+        for agent in agents {
+            *reverted_by_tool.entry(agent.tool).or_default() += u64::from(agent.lines);
+        }
+        reverts.push(RevertCommit {
+            commit,
+            subject,
+            reverted_commit,
+            ai_lines: u64::from(ai_lines),
+        });
+    }
+
+    for tool in reverted_by_tool.keys() {
+        ai_by_tool.entry(tool.clone()).or_default();
+    }
+    let mut rows: Vec<RevertRow> = ai_by_tool
+        .into_iter()
+        .map(|(tool, ai_lines)| {
+            let ai_lines_reverted = reverted_by_tool.get(&tool).copied().unwrap_or(0);
+            RevertRow {
+                tool,
+                ai_lines,
+                ai_lines_reverted,
+                reverted_percent: reverted_percent(ai_lines_reverted, ai_lines),
+            }
+        })
+        .collect();
+    rows.sort_by(|a, b| {
+        b.ai_lines_reverted
+            .cmp(&a.ai_lines_reverted)
+            .then(b.ai_lines.cmp(&a.ai_lines))
+            .then(a.tool.cmp(&b.tool))
+    });
+
+    let ai_lines_reverted = reverts.iter().map(|r| r.ai_lines).sum();
+    Ok(RevertsReport {
+        rev: options.rev.clone(),
+        since: options.since.clone(),
+        until: options.until.clone(),
+        ai_lines: committed.ai_lines,
+        ai_lines_reverted,
+        reverted_percent: reverted_percent(ai_lines_reverted, committed.ai_lines),
+        rows,
+        reverts,
+    })
+}
+
+/// `(sha, subject)` for each revert commit the walk selects, newest first
+fn list_reverts(
+    repo: &Repository,
+    options: &ReportOptions,
+) -> Result<Vec<(String, String)>, GitAiError> {
+    let mut args = vec![
+        "log".to_string(),
+        "--no-merges".to_string(),
+        "--grep=^This reverts commit ".to_string(),
+        "--format=%H%x09%aN <%aE>%x09%s".to_string(),
+    ];
+    if let Some(since) = &options.since {
+        args.push(format!("--since={}", since));
+    }
+    if let Some(until) = &options.until {
+        args.push(format!("--until={}", until));
+    }
+    args.push(options.rev.clone());
+    args.push("--".to_string());
+
+    let output = repo.git(&args.iter().map(String::as_str).collect::<Vec<_>>())?;
+    let config = Config::get();
+    Ok(output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\t');
+            let sha = fields.next()?;
+            let author = fields.next()?;
+            let subject = fields.next().unwrap_or_default();
+            (options.include_bots || !config.is_bot_author(author))
+                .then(|| (sha.to_string(), subject.to_string()))
+        })
+        .collect())
+}
+
+/// Reverted lines as a share of committed AI lines. Reverts of commits older than the walk can
+/// push this past 100%.
+fn reverted_percent(reverted: u64, committed: u64) -> f64 {
+    if committed == 0 {
+        return 0.0;
+    }
+    ((reverted as f64 / committed as f64) * 1000.0).round() / 10.0
+}
+
+fn print_reverts_table(report: &RevertsReport) {
+    if report.reverts.is_empty() {
+        println!(
+            "No reverts of AI-attributed commits in {} ({} AI lines committed).",
+            report.rev, report.ai_lines
+        );
+        return;
+    }
+
+    let tool_width = report
+        .rows
+        .iter()
+        .map(|row| row.tool.chars().count())
+        .chain(["TOOL".len(), "TOTAL".len()])
+        .max()
+        .unwrap_or(0);
+    println!(
+        "{:<width$}  {:>9}  {:>8}  {:>10}",
+        "TOOL",
+        "AI LINES",
+        "REVERTED",
+        "REVERTED %",
+        width = tool_width
+    );
+    for row in &report.rows {
+        println!(
+            "{:<width$}  {:>9}  {:>8}  {:>9.1}%",
+            row.tool,
+            row.ai_lines,
+            row.ai_lines_reverted,
+            row.reverted_percent,
+            width = tool_width
+        );
+    }
+    println!(
+        "{:<width$}  {:>9}  {:>8}  {:>9.1}%",
+        "TOTAL",
+        report.ai_lines,
+        report.ai_lines_reverted,
+        report.reverted_percent,
+        width = tool_width
+    );
+
+    println!();
+    println!("Reverts:");
+    for revert in &report.reverts {
+        println!(
+            "  {}  {:>5} AI lines  {}",
+            &revert.commit[..revert.commit.len().min(8)],
+            revert.ai_lines,
+            revert.subject
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reverted_percent() {
+        assert_eq!(reverted_percent(0, 0), 0.0);
+        assert_eq!(reverted_percent(1, 3), 33.3);
+        assert_eq!(reverted_percent(30, 20), 150.0);
+    }
+}
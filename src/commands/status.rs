@@ -1,18 +1,23 @@
+use crate::authorship::staged::{StagedComposition, staged_composition};
 use crate::authorship::stats::{CommitStats, write_stats_to_terminal};
 use crate::authorship::virtual_attribution::VirtualAttributions;
-use crate::authorship::working_log::CheckpointKind;
+use crate::authorship::working_log::{Checkpoint, CheckpointKind};
 use crate::commands::checkpoint;
+use crate::commands::machine_output::{self, OutputFormat};
 use crate::error::GitAiError;
 use crate::git::find_repository;
 use crate::git::repo_storage::InitialAttributions;
 use crate::git::repository::Repository;
 use serde::Serialize;
 use std::collections::HashSet;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::io::Write;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 #[derive(Serialize)]
 struct CheckpointInfo {
     time_ago: String,
+    /// Unix seconds
+    timestamp: u64,
     additions: u32,
     deletions: u32,
     tool_model: String,
@@ -25,36 +30,74 @@ struct StatusOutput {
     checkpoints: Vec<CheckpointInfo>,
 }
 
+/// How often `--watch` checks the index and working log for changes
+const DEFAULT_WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
 pub fn handle_status(args: &[String]) {
-    let mut json_output = false;
+    let mut format = OutputFormat::Text;
+    let mut staged = false;
+    let mut watch = false;
+    let mut interval = DEFAULT_WATCH_INTERVAL;
 
     let mut i = 0;
     while i < args.len() {
         match args[i].as_str() {
             "--json" => {
-                json_output = true;
+                format = OutputFormat::Json;
+            }
+            "--porcelain" => {
+                format = OutputFormat::Porcelain;
+            }
+            "--format" => {
+                format = OutputFormat::from_flag_value(args, i);
+                i += 1;
+            }
+            "--staged" => {
+                staged = true;
+            }
+            "--watch" => {
+                watch = true;
+            }
+            "--interval" => {
+                let Some(ms) = args.get(i + 1).and_then(|v| v.parse::<u64>().ok()) else {
+                    eprintln!("--interval requires a number of milliseconds");
+                    std::process::exit(1);
+                };
+                interval = Duration::from_millis(ms);
+                i += 1;
             }
             _ => {}
         }
         i += 1;
     }
 
-    if let Err(e) = run_status(json_output) {
+    if watch && format != OutputFormat::Json {
+        eprintln!("--watch streams JSON lines and requires --json");
+        std::process::exit(1);
+    }
+
+    let result = if watch {
+        watch_staged(interval)
+    } else if staged {
+        run_staged_status(format)
+    } else {
+        run_status(format)
+    };
+    if let Err(e) = result {
         eprintln!("Error: {}", e);
         std::process::exit(1);
     }
 }
 
-fn run_status(json: bool) -> Result<(), GitAiError> {
-    let repo = find_repository(&vec![])?;
-
+/// Record a human checkpoint for edits made since the last one, so they aren't credited to AI
+pub(crate) fn checkpoint_human_edits(repo: &Repository) -> String {
     let default_user_name = match repo.config_get_str("user.name") {
         Ok(Some(name)) if !name.trim().is_empty() => name,
         _ => "unknown".to_string(),
     };
 
     let _ = checkpoint::run(
-        &repo,
+        repo,
         &default_user_name,
         CheckpointKind::Human,
         false,
@@ -63,6 +106,103 @@ fn run_status(json: bool) -> Result<(), GitAiError> {
         None,
         false,
     );
+    default_user_name
+}
+
+/// Composition of the staged changes, after checkpointing any human edits
+pub fn current_staged_composition(repo: &Repository) -> Result<StagedComposition, GitAiError> {
+    checkpoint_human_edits(repo);
+    staged_composition(repo)
+}
+
+fn run_staged_status(format: OutputFormat) -> Result<(), GitAiError> {
+    let repo = find_repository(&vec![])?;
+    let composition = current_staged_composition(&repo)?;
+
+    if !format.is_text() {
+        machine_output::print(format, &composition)?;
+        return Ok(());
+    }
+
+    if composition.added_lines == 0 {
+        eprintln!("No staged additions");
+        return Ok(());
+    }
+    println!(
+        "{}/{} staged lines by AI",
+        composition.ai_lines, composition.added_lines
+    );
+    for agent in &composition.agents {
+        println!(
+            "  {:>5}  {} {}",
+            agent.lines,
+            capitalize(&agent.tool),
+            agent.model
+        );
+    }
+    println!();
+    for file in &composition.files {
+        println!(
+            "{:>5} ai  {:>5} human  {}",
+            file.ai_lines, file.human_lines, file.path
+        );
+    }
+    Ok(())
+}
+
+/// Print the staged composition as a JSON line whenever HEAD, the index or the working log
+/// changes. Runs until stdout is closed.
+fn watch_staged(interval: Duration) -> Result<(), GitAiError> {
+    let repo = find_repository(&vec![])?;
+    let mut last_fingerprint = None;
+    let mut last_output = String::new();
+
+    loop {
+        let fingerprint = staged_fingerprint(&repo);
+        if last_fingerprint.as_ref() != Some(&fingerprint) {
+            let output = machine_output::to_json(&current_staged_composition(&repo)?)?;
+            if output != last_output {
+                let mut stdout = std::io::stdout().lock();
+                if writeln!(stdout, "{}", output)
+                    .and_then(|_| stdout.flush())
+                    .is_err()
+                {
+                    return Ok(());
+                }
+                last_output = output;
+            }
+            // Taken after the checkpoint above so its own write doesn't trigger another pass
+            last_fingerprint = Some(staged_fingerprint(&repo));
+        }
+        std::thread::sleep(interval);
+    }
+}
+
+/// Cheap summary of the state the staged composition depends on: HEAD, and the modification
+/// time and size of the index and the working log
+fn staged_fingerprint(repo: &Repository) -> (String, Vec<Option<(SystemTime, u64)>>) {
+    let head = repo
+        .head()
+        .and_then(|head| head.target())
+        .unwrap_or_else(|_| "initial".to_string());
+    let checkpoints = repo
+        .storage
+        .working_log_for_base_commit(&head)
+        .dir
+        .join("checkpoints.jsonl");
+    let files = [repo.path().join("index"), checkpoints]
+        .iter()
+        .map(|path| {
+            let metadata = std::fs::metadata(path).ok()?;
+            Some((metadata.modified().ok()?, metadata.len()))
+        })
+        .collect();
+    (head, files)
+}
+
+fn run_status(format: OutputFormat) -> Result<(), GitAiError> {
+    let repo = find_repository(&vec![])?;
+    let default_user_name = checkpoint_human_edits(&repo);
 
     let head = repo.head()?;
     let head_sha = head.target()?;
@@ -71,13 +211,12 @@ fn run_status(json: bool) -> Result<(), GitAiError> {
     let checkpoints = working_log.read_all_checkpoints()?;
 
     if checkpoints.is_empty() {
-        if json {
+        if !format.is_text() {
             let output = StatusOutput {
                 stats: CommitStats::default(),
                 checkpoints: vec![],
             };
-            let json_str = serde_json::to_string(&output)?;
-            println!("{}", json_str);
+            machine_output::print(format, &output)?;
         } else {
             eprintln!(
                 "No checkpoints recorded since last commit ({})",
@@ -112,6 +251,7 @@ fn run_status(json: bool) -> Result<(), GitAiError> {
         let is_human = checkpoint.kind == CheckpointKind::Human;
         checkpoint_infos.push(CheckpointInfo {
             time_ago: format_time_ago(checkpoint.timestamp),
+            timestamp: checkpoint.timestamp,
             additions,
             deletions,
             tool_model,
@@ -119,46 +259,14 @@ fn run_status(json: bool) -> Result<(), GitAiError> {
         });
     }
 
-    let working_va = VirtualAttributions::from_just_working_log(
-        repo.clone(),
-        head_sha.clone(),
-        Some(default_user_name.clone()),
-    )?;
+    let stats = uncommitted_stats(&repo, &head_sha, &checkpoints, &default_user_name)?;
 
-    let pathspecs: HashSet<String> = checkpoints
-        .iter()
-        .flat_map(|cp| cp.entries.iter().map(|e| e.file.clone()))
-        .collect();
-
-    let (authorship_log, initial) = working_va.to_authorship_log_and_initial_working_log(
-        &repo,
-        &head_sha,
-        &head_sha,
-        Some(&pathspecs),
-    )?;
-
-    // Get actual git diff stats between HEAD and working directory (like post_commit does)
-    let (total_additions, total_deletions) = get_working_dir_diff_stats(&repo, Some(&pathspecs))?;
-
-    // For status (uncommitted changes), the AI attributions are in `initial` (uncommitted),
-    // not in authorship_log.attestations (which is for committed changes).
-    // Count AI lines from the uncommitted attributions.
-    let ai_accepted = count_ai_lines_from_initial(&initial);
-
-    let stats = stats_from_authorship_log_with_override(
-        Some(&authorship_log),
-        total_additions,
-        total_deletions,
-        ai_accepted,
-    );
-
-    if json {
+    if !format.is_text() {
         let output = StatusOutput {
             stats,
             checkpoints: checkpoint_infos,
         };
-        let json_str = serde_json::to_string(&output)?;
-        println!("{}", json_str);
+        machine_output::print(format, &output)?;
         return Ok(());
     }
 
@@ -192,7 +300,48 @@ fn run_status(json: bool) -> Result<(), GitAiError> {
     Ok(())
 }
 
-fn format_time_ago(timestamp: u64) -> String {
+/// Line stats for the uncommitted changes recorded by `checkpoints` since `head_sha`
+pub(crate) fn uncommitted_stats(
+    repo: &Repository,
+    head_sha: &str,
+    checkpoints: &[Checkpoint],
+    default_user_name: &str,
+) -> Result<CommitStats, GitAiError> {
+    let working_va = VirtualAttributions::from_just_working_log(
+        repo.clone(),
+        head_sha.to_string(),
+        Some(default_user_name.to_string()),
+    )?;
+
+    let pathspecs: HashSet<String> = checkpoints
+        .iter()
+        .flat_map(|cp| cp.entries.iter().map(|e| e.file.clone()))
+        .collect();
+
+    let (authorship_log, initial) = working_va.to_authorship_log_and_initial_working_log(
+        repo,
+        head_sha,
+        head_sha,
+        Some(&pathspecs),
+    )?;
+
+    // Get actual git diff stats between HEAD and working directory (like post_commit does)
+    let (total_additions, total_deletions) = get_working_dir_diff_stats(repo, Some(&pathspecs))?;
+
+    // For status (uncommitted changes), the AI attributions are in `initial` (uncommitted),
+    // not in authorship_log.attestations (which is for committed changes).
+    // Count AI lines from the uncommitted attributions.
+    let ai_accepted = count_ai_lines_from_initial(&initial);
+
+    Ok(stats_from_authorship_log_with_override(
+        Some(&authorship_log),
+        total_additions,
+        total_deletions,
+        ai_accepted,
+    ))
+}
+
+pub(crate) fn format_time_ago(timestamp: u64) -> String {
     let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap()
@@ -210,7 +359,7 @@ fn format_time_ago(timestamp: u64) -> String {
     }
 }
 
-fn capitalize(s: &str) -> String {
+pub(crate) fn capitalize(s: &str) -> String {
     let mut c = s.chars();
     match c.next() {
         None => String::new(),
@@ -317,6 +466,10 @@ fn stats_from_authorship_log_with_override(
                 messages: prompt_record.messages.clone(),
             };
             stats.time_waiting_for_ai += calculate_waiting_time(&transcript);
+
+            if let Some(usage) = &prompt_record.token_usage {
+                stats.token_usage.get_or_insert_with(Default::default).add(usage);
+            }
         }
     }
 
@@ -0,0 +1,335 @@
+use crate::commands::machine_output::{self, OutputFormat};
+use crate::git::fetch_progress;
+use crate::git::find_repository;
+use crate::git::pending_pushes::{pending_pushes, retry_pending_pushes};
+use crate::git::repository::Repository;
+use crate::git::sync_authorship::{
+    NotesExistence, NotesSyncPolicy, RemoteSyncOutcome, configured_remotes, fetch_authorship_notes,
+    sync_notes_with_remotes,
+};
+use crate::git::workspace::load_workspace_or_exit;
+use serde::Serialize;
+
+/// One remote's result in `--format json`/`--porcelain` output
+#[derive(Serialize)]
+struct RemoteOutcome {
+    remote: String,
+    /// "fetched", "no-notes" or "failed"; absent for --retry
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fetch: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fetch_error: Option<String>,
+    /// "pushed", "nothing-to-push", "skipped" (--fetch-only) or "failed"
+    push: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    push_error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct SyncOutput {
+    ok: bool,
+    remotes: Vec<RemoteOutcome>,
+}
+
+/// One workspace repository's result for `sync --workspace`
+#[derive(Serialize)]
+struct RepositorySync {
+    name: String,
+    path: String,
+    ok: bool,
+    /// Set when the repository can't be opened or has no remotes to sync with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    /// Remotes whose notes push has been failing, after this sync
+    pending_pushes: usize,
+    remotes: Vec<RemoteOutcome>,
+}
+
+#[derive(Serialize)]
+struct WorkspaceSyncOutput {
+    ok: bool,
+    repositories: Vec<RepositorySync>,
+}
+
+pub fn handle_sync(args: &[String]) {
+    let mut all_remotes = false;
+    let mut retry = false;
+    let mut fetch_only = false;
+    let mut workspace = false;
+    let mut format = OutputFormat::Text;
+    let mut remotes: Vec<String> = Vec::new();
+    fetch_progress::show_progress();
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--all-remotes" => all_remotes = true,
+            "--retry" => retry = true,
+            "--fetch-only" => fetch_only = true,
+            "--workspace" => workspace = true,
+            "--json" => format = OutputFormat::Json,
+            "--porcelain" => format = OutputFormat::Porcelain,
+            "--format" => {
+                format = OutputFormat::from_flag_value(args, i);
+                i += 1;
+            }
+            "--help" | "-h" => print_sync_help_and_exit(),
+            other if other.starts_with('-') => {
+                eprintln!("Unknown sync argument: {}", other);
+                std::process::exit(1);
+            }
+            remote => remotes.push(remote.to_string()),
+        }
+        i += 1;
+    }
+
+    if all_remotes && !remotes.is_empty() {
+        eprintln!("--all-remotes cannot be combined with remote names");
+        std::process::exit(1);
+    }
+    if retry && (all_remotes || !remotes.is_empty()) {
+        eprintln!("--retry cannot be combined with remote names or --all-remotes");
+        std::process::exit(1);
+    }
+    if retry && fetch_only {
+        eprintln!("--retry cannot be combined with --fetch-only");
+        std::process::exit(1);
+    }
+    if workspace && (retry || !remotes.is_empty()) {
+        eprintln!("--workspace cannot be combined with remote names or --retry");
+        std::process::exit(1);
+    }
+    if workspace {
+        sync_workspace(all_remotes, fetch_only, format);
+        return;
+    }
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if retry {
+        retry_queued_pushes(&repo, format);
+        return;
+    }
+
+    if all_remotes {
+        remotes = configured_remotes(&repo);
+    } else if remotes.is_empty() {
+        remotes = NotesSyncPolicy::for_repository(&repo).sync_targets(&repo);
+    }
+    if remotes.is_empty() {
+        eprintln!("No remotes to sync authorship notes with");
+        std::process::exit(1);
+    }
+
+    let width = remotes.iter().map(String::len).max().unwrap_or(0);
+    let mut output = SyncOutput {
+        ok: true,
+        remotes: Vec::new(),
+    };
+    for (outcome, status) in sync_remotes(&repo, &remotes, fetch_only) {
+        output.ok &= outcome.fetch_error.is_none() && outcome.push_error.is_none();
+        if format.is_text() {
+            println!("{:<width$}  {}", outcome.remote, status, width = width);
+        }
+        output.remotes.push(outcome);
+    }
+    machine_output::print(format, &output).unwrap();
+
+    if !output.ok {
+        std::process::exit(1);
+    }
+}
+
+/// Fetch, and unless `fetch_only` push, notes with each remote. Returns each remote's outcome
+/// with its status line for text output.
+fn sync_remotes(
+    repo: &Repository,
+    remotes: &[String],
+    fetch_only: bool,
+) -> Vec<(RemoteOutcome, String)> {
+    let outcomes = if fetch_only {
+        fetch_notes_from_remotes(repo, remotes)
+    } else {
+        sync_notes_with_remotes(repo, remotes)
+    };
+    outcomes
+        .into_iter()
+        .map(|outcome| {
+            let (fetched, fetch, fetch_error) = match &outcome.fetch {
+                Ok(NotesExistence::Found) => ("fetched".to_string(), "fetched", None),
+                Ok(NotesExistence::NotFound) => ("no notes".to_string(), "no-notes", None),
+                Err(e) => (
+                    format!("fetch failed: {}", e),
+                    "failed",
+                    Some(e.to_string()),
+                ),
+            };
+            let (pushed, push, push_error) = match &outcome.push {
+                _ if fetch_only => (String::new(), "skipped", None),
+                Some(Ok(())) => ("pushed".to_string(), "pushed", None),
+                Some(Err(e)) => (format!("push failed: {}", e), "failed", Some(e.to_string())),
+                None => ("nothing to push".to_string(), "nothing-to-push", None),
+            };
+            let status = if fetch_only {
+                fetched
+            } else {
+                format!("{}, {}", fetched, pushed)
+            };
+            let outcome = RemoteOutcome {
+                remote: outcome.remote,
+                fetch: Some(fetch),
+                fetch_error,
+                push,
+                push_error,
+            };
+            (outcome, status)
+        })
+        .collect()
+}
+
+/// Sync every workspace repository with its usual remotes, or all of them with `--all-remotes`,
+/// and report each repository's outcome and queued pushes
+fn sync_workspace(all_remotes: bool, fetch_only: bool, format: OutputFormat) {
+    let workspace = load_workspace_or_exit();
+    let mut output = WorkspaceSyncOutput {
+        ok: true,
+        repositories: Vec::new(),
+    };
+    for entry in &workspace.repositories {
+        let mut result = RepositorySync {
+            name: entry.name.clone(),
+            path: entry.path.display().to_string(),
+            ok: true,
+            error: None,
+            pending_pushes: 0,
+            remotes: Vec::new(),
+        };
+        let mut statuses = Vec::new();
+        match entry.open() {
+            Ok(repo) => {
+                let remotes = if all_remotes {
+                    configured_remotes(&repo)
+                } else {
+                    NotesSyncPolicy::for_repository(&repo).sync_targets(&repo)
+                };
+                if remotes.is_empty() {
+                    result.error = Some("no remotes to sync authorship notes with".to_string());
+                }
+                for (outcome, status) in sync_remotes(&repo, &remotes, fetch_only) {
+                    result.ok &= outcome.fetch_error.is_none() && outcome.push_error.is_none();
+                    statuses.push(format!("{}  {}", outcome.remote, status));
+                    result.remotes.push(outcome);
+                }
+                result.pending_pushes = pending_pushes(&repo).len();
+            }
+            Err(e) => result.error = Some(e.to_string()),
+        }
+        result.ok &= result.error.is_none();
+        output.ok &= result.ok;
+
+        if format.is_text() {
+            println!("{} ({})", result.name, result.path);
+            if let Some(error) = &result.error {
+                println!("  error: {}", error);
+            }
+            for status in statuses {
+                println!("  {}", status);
+            }
+            if result.pending_pushes > 0 {
+                println!(
+                    "  {} remote(s) with queued notes pushes; run `git-ai sync --retry` there",
+                    result.pending_pushes
+                );
+            }
+        }
+        output.repositories.push(result);
+    }
+    machine_output::print(format, &output).unwrap();
+
+    if !output.ok {
+        std::process::exit(1);
+    }
+}
+
+fn fetch_notes_from_remotes(repo: &Repository, remotes: &[String]) -> Vec<RemoteSyncOutcome> {
+    remotes
+        .iter()
+        .map(|remote| RemoteSyncOutcome {
+            remote: remote.clone(),
+            fetch: fetch_authorship_notes(repo, remote),
+            push: None,
+        })
+        .collect()
+}
+
+/// Push notes to every remote a failed push left behind, and report which still fail
+fn retry_queued_pushes(repo: &Repository, format: OutputFormat) {
+    let results = retry_pending_pushes(repo, &[]);
+    if results.is_empty() && format.is_text() {
+        println!("No queued authorship note pushes");
+        return;
+    }
+
+    let width = results
+        .iter()
+        .map(|(pending, _)| pending.remote.len())
+        .max()
+        .unwrap_or(0);
+    let mut output = SyncOutput {
+        ok: true,
+        remotes: Vec::new(),
+    };
+    for (pending, result) in &results {
+        let (status, push, push_error) = match result {
+            Ok(()) => ("pushed".to_string(), "pushed", None),
+            Err(e) => (format!("push failed: {}", e), "failed", Some(e.to_string())),
+        };
+        output.ok &= push_error.is_none();
+        if format.is_text() {
+            println!("{:<width$}  {}", pending.remote, status, width = width);
+        }
+        output.remotes.push(RemoteOutcome {
+            remote: pending.remote.clone(),
+            fetch: None,
+            fetch_error: None,
+            push,
+            push_error,
+        });
+    }
+    machine_output::print(format, &output).unwrap();
+
+    if !output.ok {
+        std::process::exit(1);
+    }
+}
+
+fn print_sync_help_and_exit() -> ! {
+    eprintln!(
+        "Usage: git-ai sync [<remote>...] [--all-remotes] [--fetch-only] [--retry] [--workspace] [--format <fmt>]"
+    );
+    eprintln!();
+    eprintln!("Reconcile refs/notes/ai across remotes: fetch and merge each remote's notes,");
+    eprintln!("then push the merged notes back to every one of them.");
+    eprintln!();
+    eprintln!("  <remote>...           Remotes to sync (default: per git-ai.syncRemotes, else the");
+    eprintln!("                        branch's upstream remote or origin)");
+    eprintln!(
+        "  --all-remotes         Sync every configured remote, regardless of git-ai.syncRemotes"
+    );
+    eprintln!("  --fetch-only          Fetch and merge each remote's notes without pushing");
+    eprintln!(
+        "  --retry               Only retry notes pushes that failed earlier and were queued"
+    );
+    eprintln!(
+        "  --workspace           Sync every repository in the workspace and report each one's health"
+    );
+    eprintln!("  --format <fmt>        text (default), json or porcelain");
+    eprintln!("  --json, --porcelain   Same as --format json / --format porcelain");
+    std::process::exit(0);
+}
@@ -0,0 +1,626 @@
+//! `git-ai top`: a live terminal dashboard of the working tree's AI authorship.
+//!
+//! Shows the AI vs human split of the uncommitted changes (the same numbers as `git-ai status`),
+//! activity per agent and the most recent checkpoints since HEAD, and how refs/notes/ai stands
+//! with each sync remote. Everything is re-read from the working log and refs on each refresh,
+//! so the dashboard picks up checkpoints written by agents while it runs.
+
+use crate::authorship::stats::CommitStats;
+use crate::authorship::working_log::{Checkpoint, CheckpointKind};
+use crate::commands::status::{
+    capitalize, checkpoint_human_edits, format_time_ago, uncommitted_stats,
+};
+use crate::error::GitAiError;
+use crate::git::find_repository;
+use crate::git::pending_pushes::{PendingPush, pending_pushes};
+use crate::git::refs::{AI_AUTHORSHIP_REFNAME, ref_exists, tracking_ref_for_remote};
+use crate::git::repository::Repository;
+use crate::git::sync_authorship::NotesSyncPolicy;
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    execute,
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+};
+use ratatui::{
+    Frame, Terminal,
+    backend::{Backend, CrosstermBackend},
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Gauge, Paragraph, Row, Table},
+};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io;
+use std::time::{Duration, Instant};
+
+/// How often the dashboard re-reads the working log and notes refs
+const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_millis(2000);
+
+/// Checkpoints listed in the "Recent checkpoints" panel
+const RECENT_CHECKPOINTS: usize = 50;
+
+/// Everything the dashboard shows, read in one pass
+#[derive(Debug, Serialize)]
+struct TopSnapshot {
+    branch: Option<String>,
+    head: String,
+    stats: CommitStats,
+    agents: Vec<AgentActivity>,
+    checkpoints: Vec<RecentCheckpoint>,
+    notes: Vec<RemoteNotesStatus>,
+}
+
+/// Checkpoints one agent (tool and model) recorded since HEAD
+#[derive(Debug, PartialEq, Serialize)]
+struct AgentActivity {
+    agent: String,
+    checkpoints: u32,
+    additions: u32,
+    deletions: u32,
+    last_active: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct RecentCheckpoint {
+    timestamp: u64,
+    author: String,
+    is_human: bool,
+    files: usize,
+    additions: u32,
+    deletions: u32,
+}
+
+/// refs/notes/ai compared with a remote's copy as of the last fetch or push
+#[derive(Debug, Serialize)]
+struct RemoteNotesStatus {
+    remote: String,
+    /// False until notes have been fetched from or pushed to the remote
+    synced: bool,
+    /// Notes commits only in the local ref
+    ahead: u32,
+    /// Notes commits only in the remote's copy
+    behind: u32,
+    pending_push: Option<PendingPush>,
+}
+
+pub fn handle_top(args: &[String]) {
+    let mut json = false;
+    let mut interval = DEFAULT_REFRESH_INTERVAL;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--json" => json = true,
+            "--interval" => {
+                let Some(ms) = args.get(i + 1).and_then(|v| v.parse::<u64>().ok()) else {
+                    eprintln!("--interval requires a number of milliseconds");
+                    std::process::exit(1);
+                };
+                interval = Duration::from_millis(ms.max(100));
+                i += 1;
+            }
+            "--help" | "-h" => print_top_help_and_exit(),
+            other => {
+                eprintln!("Unknown top argument: {}", other);
+                print_top_help_and_exit();
+            }
+        }
+        i += 1;
+    }
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let result = if json {
+        collect_snapshot(&repo).and_then(|snapshot| {
+            println!("{}", serde_json::to_string(&snapshot)?);
+            Ok(())
+        })
+    } else {
+        run_dashboard(&repo, interval)
+    };
+    if let Err(e) = result {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn print_top_help_and_exit() -> ! {
+    eprintln!("Usage: git-ai top [--interval <ms>] [--json]");
+    eprintln!();
+    eprintln!("Live dashboard of uncommitted AI vs human lines, agent activity, recent");
+    eprintln!("checkpoints and authorship notes sync status. Press q to quit, r to refresh.");
+    eprintln!();
+    eprintln!("  --interval <ms>  Refresh interval (default: 2000)");
+    eprintln!("  --json           Print one snapshot as JSON and exit");
+    std::process::exit(0);
+}
+
+fn collect_snapshot(repo: &Repository) -> Result<TopSnapshot, GitAiError> {
+    let default_user_name = checkpoint_human_edits(repo);
+    let head = repo.head()?;
+    let head_sha = head.target()?;
+    let branch = head.shorthand().ok().filter(|name| name != "HEAD");
+
+    let checkpoints = repo
+        .storage
+        .working_log_for_base_commit(&head_sha)
+        .read_all_checkpoints()?;
+    let stats = if checkpoints.is_empty() {
+        CommitStats::default()
+    } else {
+        uncommitted_stats(repo, &head_sha, &checkpoints, &default_user_name)?
+    };
+
+    Ok(TopSnapshot {
+        branch,
+        head: head_sha,
+        stats,
+        agents: agent_activity(&checkpoints),
+        checkpoints: recent_checkpoints(&checkpoints, &default_user_name),
+        notes: notes_sync_status(repo),
+    })
+}
+
+fn agent_name(checkpoint: &Checkpoint) -> Option<String> {
+    let agent = checkpoint.agent_id.as_ref()?;
+    Some(
+        format!("{} {}", capitalize(&agent.tool), agent.model)
+            .trim()
+            .to_string(),
+    )
+}
+
+/// AI checkpoints grouped by agent, most recently active first
+fn agent_activity(checkpoints: &[Checkpoint]) -> Vec<AgentActivity> {
+    let mut by_agent: BTreeMap<String, AgentActivity> = BTreeMap::new();
+    for checkpoint in checkpoints {
+        if checkpoint.kind == CheckpointKind::Human {
+            continue;
+        }
+        let Some(agent) = agent_name(checkpoint) else {
+            continue;
+        };
+        let activity = by_agent
+            .entry(agent.clone())
+            .or_insert_with(|| AgentActivity {
+                agent,
+                checkpoints: 0,
+                additions: 0,
+                deletions: 0,
+                last_active: 0,
+            });
+        activity.checkpoints += 1;
+        activity.additions += checkpoint.line_stats.additions;
+        activity.deletions += checkpoint.line_stats.deletions;
+        activity.last_active = activity.last_active.max(checkpoint.timestamp);
+    }
+    let mut agents: Vec<AgentActivity> = by_agent.into_values().collect();
+    agents.sort_by_key(|agent| std::cmp::Reverse(agent.last_active));
+    agents
+}
+
+fn recent_checkpoints(
+    checkpoints: &[Checkpoint],
+    default_user_name: &str,
+) -> Vec<RecentCheckpoint> {
+    checkpoints
+        .iter()
+        .rev()
+        .take(RECENT_CHECKPOINTS)
+        .map(|checkpoint| RecentCheckpoint {
+            timestamp: checkpoint.timestamp,
+            author: agent_name(checkpoint).unwrap_or_else(|| default_user_name.to_string()),
+            is_human: checkpoint.kind == CheckpointKind::Human,
+            files: checkpoint.entries.len(),
+            additions: checkpoint.line_stats.additions,
+            deletions: checkpoint.line_stats.deletions,
+        })
+        .collect()
+}
+
+fn notes_sync_status(repo: &Repository) -> Vec<RemoteNotesStatus> {
+    let local_ref = format!("refs/notes/{}", AI_AUTHORSHIP_REFNAME);
+    let has_local = ref_exists(repo, &local_ref);
+    let mut pending = pending_pushes(repo);
+
+    NotesSyncPolicy::for_repository(repo)
+        .sync_targets(repo)
+        .into_iter()
+        .filter(|remote| !remote.is_empty())
+        .map(|remote| {
+            let tracking_ref = tracking_ref_for_remote(&remote);
+            let synced = ref_exists(repo, &tracking_ref);
+            let (ahead, behind) = if synced && has_local {
+                ahead_behind(repo, &local_ref, &tracking_ref).unwrap_or((0, 0))
+            } else {
+                (0, 0)
+            };
+            let pending_push = pending
+                .iter()
+                .position(|p| p.remote == remote)
+                .map(|i| pending.remove(i));
+            RemoteNotesStatus {
+                remote,
+                synced,
+                ahead,
+                behind,
+                pending_push,
+            }
+        })
+        .collect()
+}
+
+fn ahead_behind(repo: &Repository, local: &str, remote: &str) -> Result<(u32, u32), GitAiError> {
+    let output = repo.git(&[
+        "rev-list",
+        "--left-right",
+        "--count",
+        &format!("{}...{}", local, remote),
+    ])?;
+    let mut counts = output
+        .split_whitespace()
+        .map(|n| n.parse::<u32>().unwrap_or(0));
+    Ok((counts.next().unwrap_or(0), counts.next().unwrap_or(0)))
+}
+
+fn run_dashboard(repo: &Repository, interval: Duration) -> Result<(), GitAiError> {
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+
+    let result = dashboard_loop(&mut terminal, repo, interval);
+
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+    result
+}
+
+fn dashboard_loop<B: Backend>(
+    terminal: &mut Terminal<B>,
+    repo: &Repository,
+    interval: Duration,
+) -> Result<(), GitAiError> {
+    // A failed read (e.g. mid-rebase) is shown in place of the data and retried next refresh
+    let mut snapshot = collect_snapshot(repo).map_err(|e| e.to_string());
+    let mut refreshed_at = Instant::now();
+    loop {
+        terminal.draw(|f| render(f, &snapshot, refreshed_at.elapsed()))?;
+
+        let timeout = interval.saturating_sub(refreshed_at.elapsed());
+        let refresh = if event::poll(timeout)? {
+            match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        return Ok(());
+                    }
+                    KeyCode::Char('r') => true,
+                    _ => false,
+                },
+                _ => false,
+            }
+        } else {
+            true
+        };
+        if refresh {
+            snapshot = collect_snapshot(repo).map_err(|e| e.to_string());
+            refreshed_at = Instant::now();
+        }
+    }
+}
+
+fn render(f: &mut Frame, snapshot: &Result<TopSnapshot, String>, age: Duration) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1), // Title
+            Constraint::Length(3), // AI vs human gauge
+            Constraint::Min(6),    // Agents and notes sync
+            Constraint::Min(6),    // Recent checkpoints
+            Constraint::Length(1), // Footer
+        ])
+        .split(f.area());
+
+    let footer = Paragraph::new(format!(
+        "q quit · r refresh · updated {}s ago",
+        age.as_secs()
+    ))
+    .style(Style::default().fg(Color::DarkGray));
+    f.render_widget(footer, chunks[4]);
+
+    let snapshot = match snapshot {
+        Ok(snapshot) => snapshot,
+        Err(e) => {
+            let error =
+                Paragraph::new(format!("git-ai top: {}", e)).style(Style::default().fg(Color::Red));
+            f.render_widget(error, chunks[0]);
+            return;
+        }
+    };
+
+    let head = snapshot.head.get(..7).unwrap_or(&snapshot.head);
+    let title = match &snapshot.branch {
+        Some(branch) => format!("git-ai top · {} @ {}", branch, head),
+        None => format!("git-ai top · detached @ {}", head),
+    };
+    f.render_widget(
+        Paragraph::new(title).style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        ),
+        chunks[0],
+    );
+
+    render_gauge(f, chunks[1], &snapshot.stats);
+
+    let middle = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+        .split(chunks[2]);
+    render_agents(f, middle[0], &snapshot.agents);
+    render_notes(f, middle[1], &snapshot.notes);
+    render_checkpoints(f, chunks[3], &snapshot.checkpoints);
+}
+
+fn render_gauge(f: &mut Frame, area: Rect, stats: &CommitStats) {
+    let ai = stats.ai_additions;
+    let human = stats.human_additions;
+    let total = ai + human;
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Uncommitted lines");
+    if total == 0 {
+        f.render_widget(
+            Paragraph::new("No uncommitted changes recorded since HEAD")
+                .style(Style::default().fg(Color::DarkGray))
+                .block(block),
+            area,
+        );
+        return;
+    }
+    let label = format!(
+        "AI {} · Human {} · +{} -{}",
+        ai, human, stats.git_diff_added_lines, stats.git_diff_deleted_lines
+    );
+    let gauge = Gauge::default()
+        .block(block)
+        .gauge_style(Style::default().fg(Color::Magenta).bg(Color::DarkGray))
+        .ratio(ai as f64 / total as f64)
+        .label(label);
+    f.render_widget(gauge, area);
+}
+
+fn render_agents(f: &mut Frame, area: Rect, agents: &[AgentActivity]) {
+    let rows = agents.iter().map(|agent| {
+        Row::new(vec![
+            Cell::from(agent.agent.clone()),
+            Cell::from(agent.checkpoints.to_string()),
+            Cell::from(format!("+{}", agent.additions)).style(Style::default().fg(Color::Green)),
+            Cell::from(format!("-{}", agent.deletions)).style(Style::default().fg(Color::Red)),
+            Cell::from(format_time_ago(agent.last_active)),
+        ])
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Min(16),
+            Constraint::Length(5),
+            Constraint::Length(7),
+            Constraint::Length(7),
+            Constraint::Length(14),
+        ],
+    )
+    .header(
+        Row::new(vec!["Agent", "Cps", "Added", "Deleted", "Last active"])
+            .style(Style::default().add_modifier(Modifier::BOLD)),
+    )
+    .block(Block::default().borders(Borders::ALL).title("Agents"));
+    f.render_widget(table, area);
+}
+
+fn render_notes(f: &mut Frame, area: Rect, notes: &[RemoteNotesStatus]) {
+    let lines: Vec<Line> = if notes.is_empty() {
+        vec![Line::from(Span::styled(
+            "No remotes",
+            Style::default().fg(Color::DarkGray),
+        ))]
+    } else {
+        notes
+            .iter()
+            .map(|status| {
+                let (text, color) = notes_state(status);
+                Line::from(vec![
+                    Span::styled(
+                        format!("{:<10} ", status.remote),
+                        Style::default().add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled(text, Style::default().fg(color)),
+                ])
+            })
+            .collect()
+    };
+    f.render_widget(
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("Notes sync")),
+        area,
+    );
+}
+
+fn notes_state(status: &RemoteNotesStatus) -> (String, Color) {
+    if let Some(pending) = &status.pending_push {
+        return (
+            format!(
+                "push failing ({}x), run `git-ai sync --retry`",
+                pending.attempts
+            ),
+            Color::Red,
+        );
+    }
+    if !status.synced {
+        return ("not synced yet".to_string(), Color::Yellow);
+    }
+    match (status.ahead, status.behind) {
+        (0, 0) => ("in sync".to_string(), Color::Green),
+        (ahead, 0) => (format!("{} to push", ahead), Color::Yellow),
+        (0, behind) => (format!("{} to merge", behind), Color::Yellow),
+        (ahead, behind) => (
+            format!("{} to push, {} to merge", ahead, behind),
+            Color::Yellow,
+        ),
+    }
+}
+
+fn render_checkpoints(f: &mut Frame, area: Rect, checkpoints: &[RecentCheckpoint]) {
+    let rows = checkpoints.iter().map(|checkpoint| {
+        let style = if checkpoint.is_human {
+            Style::default().fg(Color::DarkGray)
+        } else {
+            Style::default()
+        };
+        Row::new(vec![
+            Cell::from(format_time_ago(checkpoint.timestamp)),
+            Cell::from(format!("+{}", checkpoint.additions)),
+            Cell::from(format!("-{}", checkpoint.deletions)),
+            Cell::from(format!(
+                "{} file{}",
+                checkpoint.files,
+                if checkpoint.files == 1 { "" } else { "s" }
+            )),
+            Cell::from(checkpoint.author.clone()),
+        ])
+        .style(style)
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(14),
+            Constraint::Length(7),
+            Constraint::Length(7),
+            Constraint::Length(9),
+            Constraint::Min(16),
+        ],
+    )
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Recent checkpoints"),
+    );
+    f.render_widget(table, area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authorship::working_log::{AgentId, CheckpointLineStats};
+    use ratatui::backend::TestBackend;
+
+    fn checkpoint(tool: Option<&str>, timestamp: u64, additions: u32) -> Checkpoint {
+        let kind = if tool.is_some() {
+            CheckpointKind::AiAgent
+        } else {
+            CheckpointKind::Human
+        };
+        let mut checkpoint = Checkpoint::new(kind, String::new(), "dev".to_string(), vec![]);
+        checkpoint.timestamp = timestamp;
+        checkpoint.agent_id = tool.map(|tool| AgentId {
+            tool: tool.to_string(),
+            id: "session".to_string(),
+            model: "model-1".to_string(),
+        });
+        checkpoint.line_stats = CheckpointLineStats {
+            additions,
+            ..Default::default()
+        };
+        checkpoint
+    }
+
+    #[test]
+    fn test_agent_activity_groups_ai_checkpoints() {
+        let checkpoints = vec![
+            checkpoint(Some("cursor"), 10, 5),
+            checkpoint(None, 20, 100),
+            checkpoint(Some("claude"), 30, 1),
+            checkpoint(Some("cursor"), 40, 2),
+        ];
+        let agents = agent_activity(&checkpoints);
+        assert_eq!(
+            agents,
+            vec![
+                AgentActivity {
+                    agent: "Cursor model-1".to_string(),
+                    checkpoints: 2,
+                    additions: 7,
+                    deletions: 0,
+                    last_active: 40,
+                },
+                AgentActivity {
+                    agent: "Claude model-1".to_string(),
+                    checkpoints: 1,
+                    additions: 1,
+                    deletions: 0,
+                    last_active: 30,
+                },
+            ]
+        );
+
+        let recent = recent_checkpoints(&checkpoints, "Dev");
+        assert_eq!(recent[0].author, "Cursor model-1");
+        assert_eq!(recent[2].author, "Dev");
+        assert!(recent[2].is_human);
+    }
+
+    #[test]
+    fn test_render_shows_every_panel() {
+        let checkpoints = vec![checkpoint(Some("cursor"), 10, 5)];
+        let snapshot = TopSnapshot {
+            branch: Some("main".to_string()),
+            head: "0123456789abcdef".to_string(),
+            stats: CommitStats {
+                ai_additions: 5,
+                human_additions: 5,
+                git_diff_added_lines: 10,
+                ..Default::default()
+            },
+            agents: agent_activity(&checkpoints),
+            checkpoints: recent_checkpoints(&checkpoints, "Dev"),
+            notes: vec![RemoteNotesStatus {
+                remote: "origin".to_string(),
+                synced: true,
+                ahead: 2,
+                behind: 0,
+                pending_push: None,
+            }],
+        };
+
+        let mut terminal = Terminal::new(TestBackend::new(100, 24)).unwrap();
+        terminal
+            .draw(|f| render(f, &Ok(snapshot), Duration::from_secs(1)))
+            .unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        for expected in [
+            "main @ 0123456",
+            "AI 5 · Human 5",
+            "Cursor model-1",
+            "origin",
+            "2 to push",
+            "Recent checkpoints",
+        ] {
+            assert!(screen.contains(expected), "missing {:?}", expected);
+        }
+    }
+}
@@ -0,0 +1,342 @@
+use crate::authorship::authorship_log::PromptRecord;
+use crate::authorship::authorship_log_serialization::{
+    AuthorshipLog, generate_prompt_hash, parse_line_ranges,
+};
+use crate::authorship::internal_db::InternalDatabase;
+use crate::authorship::transcript::Message;
+use crate::commands::machine_output::{self, OutputFormat};
+use crate::error::GitAiError;
+use crate::git::find_repository;
+use crate::git::refs::get_authorship;
+use crate::git::repository::{Repository, exec_git};
+use serde::Serialize;
+
+/// Where a line came from: the commit that introduced it and, for AI lines, the agent session
+/// and prompt that produced it
+#[derive(Debug, Clone, Serialize)]
+pub struct LineTrace {
+    pub rev: String,
+    pub file: String,
+    pub line: u32,
+    /// Commit that introduced the line, with the line's path and number there
+    pub commit: String,
+    pub commit_file: String,
+    pub commit_line: u32,
+    /// Whether the commit's authorship note attributes the line to an agent
+    pub ai: bool,
+    /// False when the introducing commit has no authorship note
+    pub noted: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session: Option<TraceSession>,
+    /// Short hash of the user prompt whose edits produced the line, when recorded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_hash: Option<String>,
+    /// Text of that prompt, from the note or the local prompt database
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TraceSession {
+    /// Key of the session's prompt record in the note, as `show-prompt` takes it
+    pub prompt_id: String,
+    pub tool: String,
+    pub model: String,
+    /// The agent's own opaque session or conversation ID
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub human_author: Option<String>,
+}
+
+pub fn handle_trace(args: &[String]) {
+    let mut format = OutputFormat::Text;
+    let mut positional = Vec::new();
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--format" => {
+                format = OutputFormat::from_flag_value(args, i);
+                i += 2;
+            }
+            "--json" => {
+                format = OutputFormat::Json;
+                i += 1;
+            }
+            "--porcelain" => {
+                format = OutputFormat::Porcelain;
+                i += 1;
+            }
+            "--help" | "-h" => print_trace_help_and_exit(),
+            arg if !arg.starts_with('-') => {
+                positional.push(arg.to_string());
+                i += 1;
+            }
+            other => {
+                eprintln!("Unknown trace argument: {}", other);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let (rev, location) = match positional.as_slice() {
+        [location] => ("HEAD".to_string(), location.clone()),
+        [rev, location] => (rev.clone(), location.clone()),
+        _ => print_trace_help_and_exit(),
+    };
+    let Some((file, line)) = location
+        .rsplit_once(':')
+        .and_then(|(file, line)| Some((file.to_string(), line.parse::<u32>().ok()?)))
+        .filter(|(file, line)| !file.is_empty() && *line > 0)
+    else {
+        eprintln!("Expected <file>:<line>, got {}", location);
+        std::process::exit(1);
+    };
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let file = repo_relative_path(&repo, &file);
+    let trace = match trace_line(&repo, &rev, &file, line) {
+        Ok(trace) => trace,
+        Err(e) => {
+            eprintln!("Trace failed: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if format.is_text() {
+        print_trace(&trace);
+    } else {
+        machine_output::print(format, &trace).unwrap();
+    }
+}
+
+fn print_trace_help_and_exit() -> ! {
+    eprintln!("Usage: git-ai trace [<commit>] <file>:<line>");
+    eprintln!();
+    eprintln!("Show which commit introduced a line of <file> as of <commit> (default: HEAD)");
+    eprintln!("and, for AI lines, the agent session and prompt that produced it.");
+    eprintln!();
+    eprintln!("  --format <fmt>        text (default), json or porcelain");
+    std::process::exit(0);
+}
+
+/// Paths that exist relative to the current directory are made relative to the repository root;
+/// anything else is taken as repository-relative already
+pub(crate) fn repo_relative_path(repo: &Repository, file: &str) -> String {
+    let (Ok(path), Ok(workdir)) = (std::fs::canonicalize(file), repo.workdir()) else {
+        return file.to_string();
+    };
+    let workdir = workdir.canonicalize().unwrap_or(workdir);
+    match path.strip_prefix(&workdir) {
+        Ok(relative) => relative.to_string_lossy().replace('\\', "/"),
+        Err(_) => file.to_string(),
+    }
+}
+
+pub fn trace_line(
+    repo: &Repository,
+    rev: &str,
+    file: &str,
+    line: u32,
+) -> Result<LineTrace, GitAiError> {
+    let Some(blamed) = blame_lines(repo, rev, file, Some((line, line)))?
+        .into_iter()
+        .next()
+    else {
+        return Err(GitAiError::Generic(format!(
+            "Unexpected blame output for {}:{}",
+            file, line
+        )));
+    };
+    let BlamedLine {
+        commit,
+        commit_file,
+        commit_line,
+        ..
+    } = blamed;
+    let mut trace = LineTrace {
+        rev: rev.to_string(),
+        file: file.to_string(),
+        line,
+        commit: commit.clone(),
+        commit_file: commit_file.clone(),
+        commit_line,
+        ai: false,
+        noted: false,
+        session: None,
+        prompt_hash: None,
+        prompt: None,
+    };
+
+    let Some(log) = get_authorship(repo, &commit) else {
+        return Ok(trace);
+    };
+    trace.noted = true;
+
+    let Some(prompt_id) = attested_prompt_id(&log, &commit_file, commit_line) else {
+        return Ok(trace);
+    };
+    trace.ai = true;
+
+    let Some(record) = log.metadata.prompts.get(&prompt_id) else {
+        return Ok(trace);
+    };
+    trace.session = Some(TraceSession {
+        prompt_id: prompt_id.clone(),
+        tool: record.agent_id.tool.clone(),
+        model: record.agent_id.model.clone(),
+        id: record.agent_id.id.clone(),
+        human_author: record.human_author.clone(),
+    });
+
+    trace.prompt_hash = record
+        .turns
+        .iter()
+        .find(|turn| {
+            turn.files
+                .get(&commit_file)
+                .and_then(|ranges| parse_line_ranges(ranges).ok())
+                .is_some_and(|ranges| ranges.iter().any(|r| r.contains(commit_line)))
+        })
+        .map(|turn| turn.prompt_hash.clone());
+    if let Some(prompt_hash) = &trace.prompt_hash {
+        trace.prompt = prompt_text(record, &prompt_id, prompt_hash);
+    }
+
+    Ok(trace)
+}
+
+/// A line of `file` as blamed at some revision
+pub(crate) struct BlamedLine {
+    /// Commit that introduced the line, with the line's path and number there
+    pub commit: String,
+    pub commit_file: String,
+    pub commit_line: u32,
+}
+
+/// Blame `file` at `rev`, every line or just the inclusive `range`, in line order
+pub(crate) fn blame_lines(
+    repo: &Repository,
+    rev: &str,
+    file: &str,
+    range: Option<(u32, u32)>,
+) -> Result<Vec<BlamedLine>, GitAiError> {
+    let mut args = repo.global_args_for_exec();
+    args.extend(["blame".to_string(), "--line-porcelain".to_string()]);
+    if let Some((start, end)) = range {
+        args.extend(["-L".to_string(), format!("{},{}", start, end)]);
+    }
+    args.extend([rev.to_string(), "--".to_string(), file.to_string()]);
+    let output = exec_git(&args)?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    // Each line is a `<sha> <orig line> <final line> [<count>]` header, `key value` lines, and
+    // the content prefixed with a tab
+    let mut blamed = Vec::new();
+    let mut current: Option<BlamedLine> = None;
+    for line in stdout.lines() {
+        if line.starts_with('\t') {
+            blamed.extend(current.take());
+            continue;
+        }
+        match current.as_mut() {
+            None => {
+                let mut fields = line.split_whitespace();
+                let (Some(commit), Some(commit_line)) = (
+                    fields.next(),
+                    fields.next().and_then(|n| n.parse::<u32>().ok()),
+                ) else {
+                    return Err(GitAiError::Generic(format!(
+                        "Unexpected blame output for {}: {}",
+                        file, line
+                    )));
+                };
+                current = Some(BlamedLine {
+                    commit: commit.to_string(),
+                    commit_file: file.to_string(),
+                    commit_line,
+                });
+            }
+            Some(current) => {
+                if let Some(filename) = line.strip_prefix("filename ") {
+                    current.commit_file = filename.to_string();
+                }
+            }
+        }
+    }
+
+    Ok(blamed)
+}
+
+/// Key of the prompt record the note attributes `line` of `file` to, if it's an AI line
+pub(crate) fn attested_prompt_id(log: &AuthorshipLog, file: &str, line: u32) -> Option<String> {
+    log.attestations
+        .iter()
+        .filter(|f| f.file_path == file)
+        .flat_map(|f| f.entries.iter())
+        .find(|e| e.line_ranges.iter().any(|r| r.contains(line)))
+        .map(|e| e.hash.clone())
+}
+
+/// The user message hashing to `prompt_hash`, from the note's messages or, when the note doesn't
+/// keep them, the transcript in the local prompt database
+fn prompt_text(record: &PromptRecord, prompt_id: &str, prompt_hash: &str) -> Option<String> {
+    let find = |messages: &[Message]| {
+        messages.iter().find_map(|message| match message {
+            Message::User { text, .. } if generate_prompt_hash(text) == prompt_hash => {
+                Some(text.clone())
+            }
+            _ => None,
+        })
+    };
+    if let Some(text) = find(&record.messages) {
+        return Some(text);
+    }
+
+    let db = InternalDatabase::global().ok()?;
+    let db_guard = db.lock().ok()?;
+    let stored = db_guard.get_prompt(prompt_id).ok()??;
+    find(stored.messages.messages())
+}
+
+fn print_trace(trace: &LineTrace) {
+    println!("{}:{} at {}", trace.file, trace.line, trace.rev);
+    let location = if trace.commit_file != trace.file || trace.commit_line != trace.line {
+        format!(" (as {}:{})", trace.commit_file, trace.commit_line)
+    } else {
+        String::new()
+    };
+    println!("  commit     {}{}", trace.commit, location);
+    if !trace.noted {
+        println!("  author     unknown (the commit has no authorship note)");
+        return;
+    }
+    let Some(session) = &trace.session else {
+        let author = if trace.ai { "AI" } else { "human" };
+        println!("  author     {}", author);
+        return;
+    };
+    println!("  agent      {} ({})", session.tool, session.model);
+    println!("  session    {}", session.id);
+    println!("  prompt id  {}", session.prompt_id);
+    if let Some(human) = &session.human_author {
+        println!("  human      {}", human);
+    }
+    match &trace.prompt_hash {
+        Some(prompt_hash) => println!("  prompt     {}", prompt_hash),
+        None => println!("  prompt     not recorded for this line"),
+    }
+    if let Some(prompt) = &trace.prompt {
+        println!();
+        for line in prompt.lines() {
+            println!("    {}", line);
+        }
+    }
+}
@@ -0,0 +1,296 @@
+use crate::commands::hook_chain::uninstall_hook_scripts;
+use crate::commands::install_hooks;
+use crate::error::GitAiError;
+use crate::git::find_repository_in_path;
+use crate::git::repository::Repository;
+use crate::metrics::db::MetricsDatabase;
+use crate::storage::LocalStore;
+
+const AI_NOTES_REF: &str = "refs/notes/ai";
+
+/// Where `--notes archive` keeps the notes, locally and on remotes
+pub const NOTES_ARCHIVE_REF: &str = "refs/ai-archive/notes/ai";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotesAction {
+    Keep,
+    /// Move refs/notes/ai under refs/ai-archive/ so git stops showing and syncing it
+    Archive,
+    Delete,
+}
+
+impl NotesAction {
+    pub fn parse(value: &str) -> Option<NotesAction> {
+        match value {
+            "keep" => Some(NotesAction::Keep),
+            "archive" => Some(NotesAction::Archive),
+            "delete" => Some(NotesAction::Delete),
+            _ => None,
+        }
+    }
+}
+
+pub fn handle_uninstall(args: &[String]) {
+    let mut notes = NotesAction::Keep;
+    let mut remotes: Vec<String> = Vec::new();
+    let mut keep_metrics = false;
+    let mut keep_agent_hooks = false;
+    let mut dry_run = false;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--notes" if i + 1 < args.len() => {
+                notes = NotesAction::parse(&args[i + 1]).unwrap_or_else(|| {
+                    eprintln!(
+                        "Invalid --notes value: {} (expected keep, archive or delete)",
+                        args[i + 1]
+                    );
+                    std::process::exit(1);
+                });
+                i += 1;
+            }
+            "--remote" if i + 1 < args.len() => {
+                remotes.push(args[i + 1].clone());
+                i += 1;
+            }
+            "--keep-metrics" => keep_metrics = true,
+            "--keep-agent-hooks" => keep_agent_hooks = true,
+            "--dry-run" => dry_run = true,
+            "--help" | "-h" => print_uninstall_help_and_exit(),
+            arg => {
+                eprintln!("Unknown uninstall argument: {}", arg);
+                print_uninstall_help_and_exit();
+            }
+        }
+        i += 1;
+    }
+
+    if !remotes.is_empty() && notes == NotesAction::Keep {
+        eprintln!("--remote needs --notes archive or --notes delete");
+        std::process::exit(1);
+    }
+
+    let prefix = if dry_run { "[dry run] " } else { "" };
+    let mut failed = false;
+    let mut report = |step: &str, result: Result<Vec<String>, GitAiError>| match result {
+        Ok(lines) => {
+            for line in lines {
+                println!("{}{}", prefix, line);
+            }
+        }
+        Err(e) => {
+            eprintln!("Failed to {}: {}", step, e);
+            failed = true;
+        }
+    };
+
+    // Agent hooks and metrics are per-user, so they are cleaned up even outside a repository
+    match find_repository_in_path(".") {
+        Ok(repo) => {
+            report(
+                "remove repository hooks",
+                uninstall_hook_scripts(dry_run)
+                    .map(|removals| removals.iter().map(|r| r.to_string()).collect()),
+            );
+            if notes != NotesAction::Keep {
+                for remote in &remotes {
+                    report(
+                        &format!("update notes on {}", remote),
+                        remove_remote_notes(&repo, remote, notes, dry_run),
+                    );
+                }
+                report(
+                    "remove local notes",
+                    remove_local_notes(&repo, notes, dry_run),
+                );
+            }
+            report(
+                "remove repository state",
+                remove_repo_storage(&repo, dry_run),
+            );
+        }
+        Err(e) => {
+            if notes != NotesAction::Keep {
+                eprintln!("Failed to find repository: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if !keep_agent_hooks {
+        let mut uninstall_args = Vec::new();
+        if dry_run {
+            uninstall_args.push("--dry-run".to_string());
+        }
+        // run_uninstall prints its own per-tool status
+        report(
+            "remove agent hooks",
+            install_hooks::run_uninstall(&uninstall_args).map(|_| Vec::new()),
+        );
+    }
+
+    if !keep_metrics {
+        report("clear cached metrics", clear_cached_metrics(dry_run));
+    }
+
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+/// Archive or delete refs/notes/ai on `remote`. Archiving fetches the remote's notes first so
+/// the archive holds what the remote had, not the local copy.
+fn remove_remote_notes(
+    repo: &Repository,
+    remote: &str,
+    action: NotesAction,
+    dry_run: bool,
+) -> Result<Vec<String>, GitAiError> {
+    let advertised = repo.git(&["ls-remote", remote, AI_NOTES_REF])?;
+    let Some(remote_tip) = advertised.split_whitespace().next().map(str::to_string) else {
+        return Ok(vec![format!("No {} on {}", AI_NOTES_REF, remote)]);
+    };
+
+    let mut refspecs = Vec::new();
+    if action == NotesAction::Archive {
+        if !dry_run {
+            repo.git(&["fetch", "--no-tags", remote, AI_NOTES_REF])?;
+        }
+        refspecs.push(format!("{}:{}", remote_tip, NOTES_ARCHIVE_REF));
+    }
+    refspecs.push(format!(":{}", AI_NOTES_REF));
+    if !dry_run {
+        let mut push_args = vec!["push", "--no-verify", "--atomic", remote];
+        push_args.extend(refspecs.iter().map(String::as_str));
+        repo.git(&push_args)?;
+    }
+
+    Ok(vec![match action {
+        NotesAction::Archive => format!(
+            "Archived {} on {} to {}",
+            AI_NOTES_REF, remote, NOTES_ARCHIVE_REF
+        ),
+        _ => format!("Deleted {} on {}", AI_NOTES_REF, remote),
+    }])
+}
+
+/// Archive or delete the local notes ref, and drop the remote-tracking copies and the
+/// notes.displayRef entry git-ai added
+fn remove_local_notes(
+    repo: &Repository,
+    action: NotesAction,
+    dry_run: bool,
+) -> Result<Vec<String>, GitAiError> {
+    let mut lines = Vec::new();
+    let notes_tip = repo
+        .git(&["rev-parse", "--verify", "--quiet", AI_NOTES_REF])
+        .ok()
+        .map(|sha| sha.trim().to_string())
+        .filter(|sha| !sha.is_empty());
+
+    if let Some(tip) = &notes_tip {
+        if !dry_run {
+            if action == NotesAction::Archive {
+                repo.git(&["update-ref", NOTES_ARCHIVE_REF, tip])?;
+            }
+            repo.git(&["update-ref", "-d", AI_NOTES_REF, tip])?;
+        }
+        lines.push(match action {
+            NotesAction::Archive => format!("Archived {} to {}", AI_NOTES_REF, NOTES_ARCHIVE_REF),
+            _ => format!("Deleted {}", AI_NOTES_REF),
+        });
+    }
+
+    let tracking_refs = repo.git(&[
+        "for-each-ref",
+        "--format=%(refname)",
+        "refs/notes/ai-remote/",
+        "refs/notes/ai-summary-remote/",
+    ])?;
+    for tracking_ref in tracking_refs.lines().filter(|line| !line.is_empty()) {
+        if !dry_run {
+            repo.git(&["update-ref", "-d", tracking_ref])?;
+        }
+        lines.push(format!("Deleted {}", tracking_ref));
+    }
+
+    let display_refs = repo
+        .git(&["config", "--get-all", "notes.displayRef"])
+        .unwrap_or_default();
+    if display_refs
+        .lines()
+        .any(|value| value.trim() == AI_NOTES_REF)
+    {
+        if !dry_run {
+            repo.git(&[
+                "config",
+                "--unset-all",
+                "notes.displayRef",
+                &format!("^{}$", AI_NOTES_REF),
+            ])?;
+        }
+        lines.push(format!("Unset notes.displayRef={}", AI_NOTES_REF));
+    }
+
+    Ok(lines)
+}
+
+/// Remove .git/ai: working logs, rewrite log and caches, for the main checkout and every
+/// linked worktree
+fn remove_repo_storage(repo: &Repository, dry_run: bool) -> Result<Vec<String>, GitAiError> {
+    let mut ai_dirs = vec![repo.common_dir().join("ai")];
+    let worktrees = std::fs::read_dir(repo.common_dir().join("worktrees"))
+        .into_iter()
+        .flatten()
+        .flatten();
+    ai_dirs.extend(worktrees.map(|entry| entry.path().join("ai")));
+
+    let mut lines = Vec::new();
+    for ai_dir in ai_dirs.into_iter().filter(|dir| dir.exists()) {
+        if !dry_run {
+            std::fs::remove_dir_all(&ai_dir)?;
+        }
+        lines.push(format!("Removed {}", ai_dir.display()));
+    }
+    Ok(lines)
+}
+
+fn clear_cached_metrics(dry_run: bool) -> Result<Vec<String>, GitAiError> {
+    if dry_run {
+        return Ok(vec!["Clear cached metric events".to_string()]);
+    }
+    let events = {
+        let mut store = LocalStore::global()?
+            .lock()
+            .map_err(|e| GitAiError::Generic(format!("Local store lock poisoned: {}", e)))?;
+        store.clear_metric_events()?
+    };
+    let metrics = {
+        let mut db = MetricsDatabase::global()?
+            .lock()
+            .map_err(|e| GitAiError::Generic(format!("Metrics db lock poisoned: {}", e)))?;
+        db.clear()?
+    };
+    Ok(vec![format!(
+        "Cleared {} cached metric event{}",
+        events + metrics,
+        if events + metrics == 1 { "" } else { "s" }
+    )])
+}
+
+fn print_uninstall_help_and_exit() -> ! {
+    eprintln!("Usage: git-ai uninstall [options]");
+    eprintln!();
+    eprintln!("Remove git-ai from this repository and this machine. Hooks that git-ai chained");
+    eprintln!("to are put back where they were.");
+    eprintln!();
+    eprintln!("  --notes <keep|archive|delete>  What to do with refs/notes/ai (default: keep)");
+    eprintln!("                                 archive moves it to refs/ai-archive/notes/ai");
+    eprintln!("  --remote <name>                Also archive/delete the notes on <name>");
+    eprintln!("                                 (repeatable)");
+    eprintln!("  --keep-metrics                 Don't clear cached metric events");
+    eprintln!("  --keep-agent-hooks             Don't remove agent/editor hooks");
+    eprintln!("  --dry-run                      Only print what would be removed");
+    std::process::exit(0);
+}
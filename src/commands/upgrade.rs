@@ -25,7 +25,7 @@ static UPDATE_NOTICE_EMITTED: AtomicBool = AtomicBool::new(false);
 static LAST_BACKGROUND_SPAWN: AtomicU64 = AtomicU64::new(0);
 
 #[derive(Debug, PartialEq)]
-enum UpgradeAction {
+pub(crate) enum UpgradeAction {
     UpgradeAvailable,
     AlreadyLatest,
     RunningNewerVersion,
@@ -33,7 +33,7 @@ enum UpgradeAction {
 }
 
 impl UpgradeAction {
-    fn to_string(&self) -> &str {
+    pub(crate) fn to_string(&self) -> &str {
         match self {
             UpgradeAction::UpgradeAvailable => "upgrade_available",
             UpgradeAction::AlreadyLatest => "already_latest",
@@ -44,10 +44,10 @@ impl UpgradeAction {
 }
 
 #[derive(Debug, Clone)]
-struct ChannelRelease {
-    tag: String,
-    semver: String,
-    checksum: String,
+pub(crate) struct ChannelRelease {
+    pub(crate) tag: String,
+    pub(crate) semver: String,
+    pub(crate) checksum: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -150,7 +150,7 @@ fn semver_from_tag(tag: &str) -> String {
         .to_string()
 }
 
-fn determine_action(force: bool, release: &ChannelRelease, current_version: &str) -> UpgradeAction {
+pub(crate) fn determine_action(force: bool, release: &ChannelRelease, current_version: &str) -> UpgradeAction {
     if force {
         return UpgradeAction::ForceReinstall;
     }
@@ -164,7 +164,7 @@ fn determine_action(force: bool, release: &ChannelRelease, current_version: &str
     }
 }
 
-fn persist_update_state(channel: UpdateChannel, release: Option<&ChannelRelease>) {
+pub(crate) fn persist_update_state(channel: UpdateChannel, release: Option<&ChannelRelease>) {
     let mut cache = UpdateCache::new(channel);
     cache.last_checked_at = current_timestamp();
     if let Some(release) = release {
@@ -178,7 +178,7 @@ fn releases_endpoint() -> &'static str {
     "/worker/releases"
 }
 
-fn verify_sha256(content: &[u8], expected_hash: &str) -> Result<(), String> {
+pub(crate) fn verify_sha256(content: &[u8], expected_hash: &str) -> Result<(), String> {
     let mut hasher = Sha256::new();
     hasher.update(content);
     let actual_hash = format!("{:x}", hasher.finalize());
@@ -195,7 +195,7 @@ fn verify_sha256(content: &[u8], expected_hash: &str) -> Result<(), String> {
 
 /// Parse SHA256SUMS file content into a map of filename → hash.
 /// Format: `<hash>  <filename>` (two spaces between hash and filename)
-fn parse_checksums(content: &str) -> HashMap<String, String> {
+pub(crate) fn parse_checksums(content: &str) -> HashMap<String, String> {
     let mut checksums = HashMap::new();
 
     for line in content.lines() {
@@ -285,7 +285,7 @@ fn fetch_and_verify_install_script(
     Ok(script.to_string())
 }
 
-fn fetch_release_for_channel(
+pub(crate) fn fetch_release_for_channel(
     api_base_url: &str,
     channel: UpdateChannel,
 ) -> Result<ChannelRelease, String> {
@@ -712,9 +712,8 @@ pub fn maybe_schedule_background_update_check() {
 }
 
 fn spawn_background_upgrade_process() -> bool {
-    match crate::utils::current_git_ai_exe() {
-        Ok(exe) => {
-            let mut cmd = Command::new(exe);
+    match crate::utils::background_git_ai_command() {
+        Ok(mut cmd) => {
             cmd.arg("upgrade")
                 .arg("--background")
                 .stdout(Stdio::null())
@@ -725,7 +724,7 @@ fn spawn_background_upgrade_process() -> bool {
     }
 }
 
-fn is_newer_version(latest: &str, current: &str) -> bool {
+pub(crate) fn is_newer_version(latest: &str, current: &str) -> bool {
     let parse_version =
         |v: &str| -> Vec<u32> { v.split('.').filter_map(|s| s.parse::<u32>().ok()).collect() };
 
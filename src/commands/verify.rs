@@ -0,0 +1,575 @@
+//! `git-ai verify`: integrity checks for authorship notes.
+//!
+//! Every non-merge commit in the range must have a note in refs/notes/ai, the note must parse
+//! and reference only prompts it defines, and every AI-attributed line must be a line the commit
+//! actually added. Where notes are signed (see `git-ai.signNotes`), each note's signature must
+//! also verify. Failures exit non-zero so the command can gate CI, and `--format sarif` lets
+//! code scanning annotate the offending commits and files.
+
+use crate::authorship::authorship_log::LineRange;
+use crate::authorship::authorship_log_serialization::{AUTHORSHIP_LOG_VERSION, AuthorshipLog};
+use crate::authorship::note_schema::{SchemaStatus, schema_status};
+use crate::ci::sarif::{SarifFinding, SarifLevel, SarifLog, SarifRule};
+use crate::commands::machine_output;
+use crate::error::GitAiError;
+use crate::git::authorship_traversal::{batch_read_blobs, get_notes_list};
+use crate::git::find_repository;
+use crate::git::note_signing::{self, SignatureStatus};
+use crate::git::repository::Repository;
+use std::collections::{HashMap, HashSet};
+
+pub const RULE_MISSING_NOTE: &str = "verify/missing-note";
+pub const RULE_INVALID_NOTE: &str = "verify/invalid-note";
+pub const RULE_LINE_MISMATCH: &str = "verify/line-mismatch";
+pub const RULE_BAD_SIGNATURE: &str = "verify/bad-signature";
+pub const RULE_UNSIGNED_NOTE: &str = "verify/unsigned-note";
+
+/// Git's well-known empty tree, the diff base for root commits
+const EMPTY_TREE: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyFormat {
+    Text,
+    Json,
+    Porcelain,
+    Sarif,
+}
+
+#[derive(Debug, Clone)]
+pub struct VerifyReport {
+    pub commits_checked: usize,
+    pub findings: Vec<SarifFinding>,
+}
+
+impl VerifyReport {
+    pub fn passed(&self) -> bool {
+        !self.findings.iter().any(|f| f.level == SarifLevel::Error)
+    }
+}
+
+pub fn verify_rules() -> Vec<SarifRule> {
+    vec![
+        SarifRule::new(
+            RULE_MISSING_NOTE,
+            "MissingAuthorshipNote",
+            "Commit has no authorship note in refs/notes/ai",
+            SarifLevel::Error,
+        ),
+        SarifRule::new(
+            RULE_INVALID_NOTE,
+            "InvalidAuthorshipNote",
+            "Authorship note is malformed or does not match the authorship schema",
+            SarifLevel::Error,
+        ),
+        SarifRule::new(
+            RULE_LINE_MISMATCH,
+            "AuthorshipLineMismatch",
+            "Authorship note attributes lines the commit did not add",
+            SarifLevel::Error,
+        ),
+        SarifRule::new(
+            RULE_BAD_SIGNATURE,
+            "BadAuthorshipNoteSignature",
+            "Authorship note signature is invalid or covers different note content",
+            SarifLevel::Error,
+        ),
+        SarifRule::new(
+            RULE_UNSIGNED_NOTE,
+            "UnsignedAuthorshipNote",
+            "Authorship note has no signature in refs/notes/ai-signatures",
+            SarifLevel::Warning,
+        ),
+    ]
+}
+
+pub fn handle_verify(args: &[String]) {
+    let mut format = VerifyFormat::Text;
+    let mut output: Option<String> = None;
+    let mut range: Option<String> = None;
+    let mut require_signed = false;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--format" => {
+                format = match args.get(i + 1).map(String::as_str) {
+                    Some("text") => VerifyFormat::Text,
+                    Some("json") => VerifyFormat::Json,
+                    Some("porcelain") => VerifyFormat::Porcelain,
+                    Some("sarif") => VerifyFormat::Sarif,
+                    other => {
+                        eprintln!(
+                            "Error: --format must be text, json, porcelain or sarif (got {})",
+                            other.unwrap_or("nothing")
+                        );
+                        std::process::exit(1);
+                    }
+                };
+                i += 2;
+            }
+            "--json" => {
+                format = VerifyFormat::Json;
+                i += 1;
+            }
+            "--porcelain" => {
+                format = VerifyFormat::Porcelain;
+                i += 1;
+            }
+            "--require-signed" => {
+                require_signed = true;
+                i += 1;
+            }
+            "--output" | "-o" => {
+                let Some(path) = args.get(i + 1) else {
+                    eprintln!("Error: --output requires a file path");
+                    std::process::exit(1);
+                };
+                output = Some(path.clone());
+                i += 2;
+            }
+            "--help" | "-h" => print_verify_help_and_exit(),
+            arg if arg.starts_with('-') => {
+                eprintln!("Unknown verify argument: {}", arg);
+                std::process::exit(1);
+            }
+            arg => {
+                if range.is_some() {
+                    eprintln!("Error: verify accepts a single commit or range");
+                    std::process::exit(1);
+                }
+                range = Some(arg.to_string());
+                i += 1;
+            }
+        }
+    }
+
+    let repo = match find_repository(&Vec::<String>::new()) {
+        Ok(repo) => repo,
+        Err(e) => {
+            eprintln!("Failed to find repository: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let report = match verify_range(&repo, range.as_deref(), require_signed) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Verification failed to run: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let rendered = match format {
+        VerifyFormat::Text => render_text(&report),
+        VerifyFormat::Json => {
+            serde_json::to_string_pretty(&machine_output::versioned(&report_json(&report))).unwrap()
+        }
+        VerifyFormat::Porcelain => machine_output::to_porcelain(&report_json(&report)).unwrap(),
+        VerifyFormat::Sarif => SarifLog::new(&verify_rules(), &report.findings)
+            .to_json_string()
+            .unwrap(),
+    };
+    match output {
+        Some(path) => {
+            if let Err(e) = std::fs::write(&path, rendered + "\n") {
+                eprintln!("Failed to write {}: {}", path, e);
+                std::process::exit(1);
+            }
+        }
+        None => println!("{}", rendered),
+    }
+
+    if !report.passed() {
+        std::process::exit(1);
+    }
+}
+
+fn print_verify_help_and_exit() -> ! {
+    eprintln!("Usage: git-ai verify [<commit>|<range>] [--format <fmt>] [--output <file>]");
+    eprintln!("                     [--require-signed]");
+    eprintln!();
+    eprintln!("Checks that every non-merge commit has a well-formed authorship note whose");
+    eprintln!("AI-attributed lines were all added by that commit, and that signed notes carry");
+    eprintln!("a good signature. Exits 1 on any failure.");
+    eprintln!();
+    eprintln!(
+        "  <range>              e.g. origin/main..HEAD (default: @{{upstream}}..HEAD, or HEAD)"
+    );
+    eprintln!("  --format <fmt>       text (default), json, porcelain, or sarif");
+    eprintln!("  --json               Same as --format json");
+    eprintln!("  --porcelain          Same as --format porcelain");
+    eprintln!("  -o, --output <file>  Write the report to a file instead of stdout");
+    eprintln!("  --require-signed     Fail on unsigned notes and signatures that can't be checked");
+    std::process::exit(0);
+}
+
+/// Verify a single commit (`<rev>`), a range (`a..b`), or by default the commits not yet on
+/// the upstream branch (just HEAD when there is no upstream). Signatures are checked when the
+/// repository has any, or always with `require_signed`, which also makes unsigned notes errors.
+pub fn verify_range(
+    repo: &Repository,
+    range: Option<&str>,
+    require_signed: bool,
+) -> Result<VerifyReport, GitAiError> {
+    let rev_args = rev_list_args(repo, range);
+    let mut args = vec!["rev-list", "--no-merges", "--parents"];
+    args.extend(rev_args.iter().map(String::as_str));
+    let commits: Vec<(String, Option<String>)> = repo
+        .git(&args)?
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let sha = parts.next()?.to_string();
+            Some((sha, parts.next().map(str::to_string)))
+        })
+        .collect();
+
+    let note_blobs = list_note_blobs(repo, &commits)?;
+    let notes = load_notes(repo, &note_blobs)?;
+    let signatures = if require_signed || note_signing::signatures_in_use(repo) {
+        let signatures_ref = format!("refs/notes/{}", note_signing::SIGNATURES_REFNAME);
+        note_signing::verify_notes(repo, &signatures_ref, &note_blobs)?
+    } else {
+        HashMap::new()
+    };
+    let mut findings = Vec::new();
+    for (sha, parent) in &commits {
+        let parent = parent.as_deref().unwrap_or(EMPTY_TREE);
+        let added_lines = repo.diff_added_lines(parent, sha, None)?;
+        let Some(content) = notes.get(sha) else {
+            // A commit that only deletes or renames has nothing to attribute
+            let level = if added_lines.values().all(|lines| lines.is_empty()) {
+                SarifLevel::Warning
+            } else {
+                SarifLevel::Error
+            };
+            findings.push(
+                SarifFinding::new(
+                    RULE_MISSING_NOTE,
+                    level,
+                    format!("Commit {} has no authorship note", short(sha)),
+                )
+                .commit_sha(sha),
+            );
+            continue;
+        };
+        findings.extend(
+            check_note(content, &added_lines)
+                .into_iter()
+                .map(|finding| finding.commit_sha(sha)),
+        );
+        if let Some(finding) = signatures
+            .get(sha)
+            .and_then(|status| signature_finding(sha, status, require_signed))
+        {
+            findings.push(finding.commit_sha(sha));
+        }
+    }
+
+    Ok(VerifyReport {
+        commits_checked: commits.len(),
+        findings,
+    })
+}
+
+/// `git rev-list` arguments for a single commit (`<rev>`), a range (`a..b`), or by default the
+/// commits not yet on the upstream branch (just HEAD when there is no upstream)
+pub fn rev_list_args(repo: &Repository, range: Option<&str>) -> Vec<String> {
+    match range {
+        Some(range) if range.contains("..") => vec![range.to_string()],
+        Some(rev) => vec!["-1".to_string(), rev.to_string()],
+        None => match repo.git(&["rev-parse", "--verify", "-q", "@{upstream}"]) {
+            Ok(upstream) if !upstream.trim().is_empty() => {
+                vec![format!("{}..HEAD", upstream.trim())]
+            }
+            _ => vec!["-1".to_string(), "HEAD".to_string()],
+        },
+    }
+}
+
+/// Validate one note against the lines its commit added (repo-relative path -> added line
+/// numbers). Findings are returned without a commit SHA.
+pub fn check_note(content: &str, added_lines: &HashMap<String, Vec<u32>>) -> Vec<SarifFinding> {
+    let log = match AuthorshipLog::deserialize_from_string(content) {
+        Ok(log) => log,
+        Err(e) => {
+            return vec![SarifFinding::new(
+                RULE_INVALID_NOTE,
+                SarifLevel::Error,
+                format!("Authorship note does not parse: {}", e),
+            )];
+        }
+    };
+
+    let mut findings = Vec::new();
+    // The note parsed, so an older schema is readable; worth surfacing but not failing on
+    match schema_status(&log.metadata.schema_version) {
+        SchemaStatus::Current => {}
+        SchemaStatus::Legacy => findings.push(SarifFinding::new(
+            RULE_INVALID_NOTE,
+            SarifLevel::Note,
+            format!(
+                "Authorship note uses schema '{}' (expected {}); `git-ai migrate-notes` rewrites it",
+                log.metadata.schema_version, AUTHORSHIP_LOG_VERSION
+            ),
+        )),
+        SchemaStatus::Newer | SchemaStatus::Unsupported => findings.push(SarifFinding::new(
+            RULE_INVALID_NOTE,
+            SarifLevel::Note,
+            format!(
+                "Authorship note uses schema {} (expected {})",
+                log.metadata.schema_version, AUTHORSHIP_LOG_VERSION
+            ),
+        )),
+    }
+
+    for file in &log.attestations {
+        let mut added: Vec<u32> = added_lines
+            .get(&file.file_path)
+            .cloned()
+            .unwrap_or_default();
+        added.sort_unstable();
+        added.dedup();
+
+        let mut attributed = 0u64;
+        let mut unmatched = 0u64;
+        let mut first_unmatched: Option<(u32, u32)> = None;
+        for entry in &file.entries {
+            if !log.metadata.prompts.contains_key(&entry.hash) {
+                findings.push(
+                    SarifFinding::new(
+                        RULE_INVALID_NOTE,
+                        SarifLevel::Error,
+                        format!(
+                            "{} references prompt {} which the note does not define",
+                            file.file_path, entry.hash
+                        ),
+                    )
+                    .path(&file.file_path),
+                );
+            }
+            for range in &entry.line_ranges {
+                let (start, end) = match *range {
+                    LineRange::Single(line) => (line, line),
+                    LineRange::Range(start, end) => (start, end),
+                };
+                if start == 0 || start > end {
+                    findings.push(
+                        SarifFinding::new(
+                            RULE_INVALID_NOTE,
+                            SarifLevel::Error,
+                            format!(
+                                "{} has an invalid line range {}-{}",
+                                file.file_path, start, end
+                            ),
+                        )
+                        .path(&file.file_path),
+                    );
+                    continue;
+                }
+                let len = (end - start) as u64 + 1;
+                let matched = (added.partition_point(|l| *l <= end)
+                    - added.partition_point(|l| *l < start)) as u64;
+                attributed += len;
+                if matched < len {
+                    unmatched += len - matched;
+                    first_unmatched.get_or_insert((start, end));
+                }
+            }
+        }
+
+        if unmatched > 0 {
+            let (start, end) = first_unmatched.unwrap();
+            findings.push(
+                SarifFinding::new(
+                    RULE_LINE_MISMATCH,
+                    SarifLevel::Error,
+                    format!(
+                        "{} attributes {} AI lines but {} of them were not added by this commit ({} lines added)",
+                        file.file_path,
+                        attributed,
+                        unmatched,
+                        added.len()
+                    ),
+                )
+                .path(&file.file_path)
+                .lines(start, end),
+            );
+        }
+    }
+
+    findings
+}
+
+fn signature_finding(
+    sha: &str,
+    status: &SignatureStatus,
+    require_signed: bool,
+) -> Option<SarifFinding> {
+    let strict_level = if require_signed {
+        SarifLevel::Error
+    } else {
+        SarifLevel::Warning
+    };
+    let (rule, level, message) = match status {
+        SignatureStatus::Valid { .. } => return None,
+        SignatureStatus::Unsigned => (
+            RULE_UNSIGNED_NOTE,
+            strict_level,
+            format!("Authorship note for {} is not signed", short(sha)),
+        ),
+        SignatureStatus::Mismatch => (
+            RULE_BAD_SIGNATURE,
+            SarifLevel::Error,
+            format!(
+                "Authorship note for {} changed after it was signed",
+                short(sha)
+            ),
+        ),
+        SignatureStatus::Invalid(reason) => (
+            RULE_BAD_SIGNATURE,
+            SarifLevel::Error,
+            format!(
+                "Authorship note signature for {} is invalid: {}",
+                short(sha),
+                reason
+            ),
+        ),
+        SignatureStatus::Unverifiable(reason) => (
+            RULE_BAD_SIGNATURE,
+            strict_level,
+            format!(
+                "Authorship note signature for {} could not be checked: {}",
+                short(sha),
+                reason
+            ),
+        ),
+    };
+    Some(SarifFinding::new(rule, level, message))
+}
+
+/// Notes for `commits` as (note_blob_sha, commit_sha) pairs
+fn list_note_blobs(
+    repo: &Repository,
+    commits: &[(String, Option<String>)],
+) -> Result<Vec<(String, String)>, GitAiError> {
+    let wanted: HashSet<&str> = commits.iter().map(|(sha, _)| sha.as_str()).collect();
+    Ok(get_notes_list(&repo.global_args_for_exec())?
+        .into_iter()
+        .filter(|(_, commit_sha)| wanted.contains(commit_sha.as_str()))
+        .collect())
+}
+
+fn load_notes(
+    repo: &Repository,
+    note_blobs: &[(String, String)],
+) -> Result<HashMap<String, String>, GitAiError> {
+    let (blobs, shas): (Vec<String>, Vec<String>) = note_blobs.iter().cloned().unzip();
+    let contents = batch_read_blobs(&repo.global_args_for_exec(), &blobs)?;
+    Ok(shas.into_iter().zip(contents).collect())
+}
+
+fn render_text(report: &VerifyReport) -> String {
+    let mut out = String::new();
+    for finding in &report.findings {
+        let level = match finding.level {
+            SarifLevel::Error => "error",
+            SarifLevel::Warning => "warning",
+            SarifLevel::Note => "note",
+        };
+        out.push_str(&format!(
+            "{} [{}] {}: {}\n",
+            level,
+            finding.rule_id,
+            finding.commit_sha.as_deref().map(short).unwrap_or("-"),
+            finding.message
+        ));
+    }
+    let errors = report
+        .findings
+        .iter()
+        .filter(|f| f.level == SarifLevel::Error)
+        .count();
+    if report.passed() {
+        out.push_str(&format!(
+            "Verified {} commit(s): all authorship notes are intact",
+            report.commits_checked
+        ));
+    } else {
+        out.push_str(&format!(
+            "Verified {} commit(s): {} error(s)",
+            report.commits_checked, errors
+        ));
+    }
+    out
+}
+
+fn report_json(report: &VerifyReport) -> serde_json::Value {
+    serde_json::json!({
+        "commits_checked": report.commits_checked,
+        "passed": report.passed(),
+        "findings": report.findings.iter().map(|f| serde_json::json!({
+            "rule": f.rule_id,
+            "level": f.level,
+            "commit": f.commit_sha,
+            "path": f.path,
+            "lines": f.lines.map(|(start, end)| vec![start, end]),
+            "message": f.message,
+        })).collect::<Vec<_>>(),
+    })
+}
+
+fn short(sha: &str) -> &str {
+    &sha[..sha.len().min(8)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_utils::synthetic_authorship_log;
+
+    fn note_and_added(lines: Vec<u32>) -> (String, HashMap<String, Vec<u32>>) {
+        let log = synthetic_authorship_log(1, 2);
+        let path = log.attestations[0].file_path.clone();
+        (
+            log.serialize_to_string().unwrap(),
+            HashMap::from([(path, lines)]),
+        )
+    }
+
+    #[test]
+    fn test_check_note_accepts_lines_added_by_commit() {
+        let log = synthetic_authorship_log(1, 2);
+        let added: Vec<u32> = log.attestations[0]
+            .entries
+            .iter()
+            .flat_map(|e| e.line_ranges.iter().flat_map(|r| r.expand()))
+            .collect();
+        let (note, added) = note_and_added(added);
+        assert!(check_note(&note, &added).is_empty());
+    }
+
+    #[test]
+    fn test_check_note_flags_lines_not_in_diff() {
+        let (note, added) = note_and_added(vec![1]);
+        let findings = check_note(&note, &added);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].rule_id, RULE_LINE_MISMATCH);
+        assert!(findings[0].path.is_some());
+    }
+
+    #[test]
+    fn test_check_note_flags_malformed_and_dangling_prompts() {
+        let findings = check_note("not a note", &HashMap::new());
+        assert_eq!(findings[0].rule_id, RULE_INVALID_NOTE);
+
+        let mut log = synthetic_authorship_log(1, 1);
+        log.metadata.prompts.clear();
+        let findings = check_note(&log.serialize_to_string().unwrap(), &HashMap::new());
+        assert!(
+            findings
+                .iter()
+                .any(|f| f.rule_id == RULE_INVALID_NOTE && f.message.contains("does not define"))
+        );
+    }
+}
@@ -0,0 +1,207 @@
+use crate::commands::machine_output::{self, OutputFormat};
+use crate::git::workspace::Workspace;
+use serde::Serialize;
+
+#[derive(Serialize)]
+struct ListedRepo {
+    name: String,
+    path: String,
+    /// False when the path is gone or no longer a git repository
+    available: bool,
+}
+
+#[derive(Serialize)]
+struct WorkspaceList {
+    file: String,
+    repositories: Vec<ListedRepo>,
+}
+
+pub fn handle_workspace(args: &[String]) {
+    match args.first().map(String::as_str) {
+        Some("add") => handle_add(&args[1..]),
+        Some("remove") | Some("rm") => handle_remove(&args[1..]),
+        Some("list") | Some("ls") => handle_list(&args[1..]),
+        Some("--help") | Some("-h") | Some("help") | None => print_workspace_help_and_exit(),
+        Some(other) => {
+            eprintln!("Unknown workspace subcommand: {}", other);
+            print_workspace_help_and_exit();
+        }
+    }
+}
+
+fn print_workspace_help_and_exit() -> ! {
+    eprintln!("Usage: git-ai workspace <add|remove|list> [options]");
+    eprintln!();
+    eprintln!("Manage the set of repositories that `stats report`, `sync` and `daemon` operate on");
+    eprintln!(
+        "with --workspace. The list is kept in $GIT_AI_WORKSPACE, or ~/.git-ai/workspace.json."
+    );
+    eprintln!();
+    eprintln!("  add <path>... [--name <name>]  Add the repositories containing these paths");
+    eprintln!("  remove <name|path>...          Remove repositories from the workspace");
+    eprintln!(
+        "  list [--format <fmt>]          List the repositories: text (default), json or porcelain"
+    );
+    std::process::exit(0);
+}
+
+fn load_or_exit() -> Workspace {
+    match Workspace::load() {
+        Ok(workspace) => workspace,
+        Err(e) => {
+            eprintln!("Failed to load workspace: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn save_or_exit(workspace: &Workspace) {
+    if let Err(e) = workspace.save() {
+        eprintln!("Failed to save workspace: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn handle_add(args: &[String]) {
+    let mut name: Option<String> = None;
+    let mut paths: Vec<String> = Vec::new();
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--name" => {
+                let Some(value) = args.get(i + 1) else {
+                    eprintln!("--name requires a value");
+                    std::process::exit(1);
+                };
+                name = Some(value.clone());
+                i += 2;
+            }
+            "--help" | "-h" => print_workspace_help_and_exit(),
+            other if other.starts_with('-') => {
+                eprintln!("Unknown workspace add argument: {}", other);
+                std::process::exit(1);
+            }
+            path => {
+                paths.push(path.to_string());
+                i += 1;
+            }
+        }
+    }
+    if paths.is_empty() {
+        paths.push(".".to_string());
+    }
+    if name.is_some() && paths.len() > 1 {
+        eprintln!("--name can only be used when adding a single repository");
+        std::process::exit(1);
+    }
+
+    let mut workspace = load_or_exit();
+    let mut failed = false;
+    for path in &paths {
+        match workspace.add(path, name.as_deref()) {
+            Ok((repo, true)) => println!("Added {} ({})", repo.name, repo.path.display()),
+            Ok((repo, false)) => println!(
+                "{} is already in the workspace as {}",
+                repo.path.display(),
+                repo.name
+            ),
+            Err(e) => {
+                eprintln!("Failed to add {}: {}", path, e);
+                failed = true;
+            }
+        }
+    }
+    save_or_exit(&workspace);
+
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+fn handle_remove(args: &[String]) {
+    if args.is_empty() {
+        eprintln!("Usage: git-ai workspace remove <name|path>...");
+        std::process::exit(1);
+    }
+
+    let mut workspace = load_or_exit();
+    let mut failed = false;
+    for arg in args {
+        match workspace.remove(arg) {
+            Some(repo) => println!("Removed {} ({})", repo.name, repo.path.display()),
+            None => {
+                eprintln!("{} is not in the workspace", arg);
+                failed = true;
+            }
+        }
+    }
+    save_or_exit(&workspace);
+
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+fn handle_list(args: &[String]) {
+    let mut format = OutputFormat::Text;
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--json" => format = OutputFormat::Json,
+            "--porcelain" => format = OutputFormat::Porcelain,
+            "--format" => {
+                format = OutputFormat::from_flag_value(args, i);
+                i += 1;
+            }
+            other => {
+                eprintln!("Unknown workspace list argument: {}", other);
+                std::process::exit(1);
+            }
+        }
+        i += 1;
+    }
+
+    let workspace = load_or_exit();
+    let list = WorkspaceList {
+        file: Workspace::file_path().display().to_string(),
+        repositories: workspace
+            .repositories
+            .iter()
+            .map(|repo| ListedRepo {
+                name: repo.name.clone(),
+                path: repo.path.display().to_string(),
+                available: repo.open().is_ok(),
+            })
+            .collect(),
+    };
+
+    if !format.is_text() {
+        machine_output::print(format, &list).unwrap();
+        return;
+    }
+    if list.repositories.is_empty() {
+        println!("No repositories in {}", list.file);
+        return;
+    }
+    let width = list
+        .repositories
+        .iter()
+        .map(|repo| repo.name.chars().count())
+        .max()
+        .unwrap_or(0);
+    for repo in &list.repositories {
+        let note = if repo.available {
+            ""
+        } else {
+            "  (unavailable)"
+        };
+        println!(
+            "{:<width$}  {}{}",
+            repo.name,
+            repo.path,
+            note,
+            width = width
+        );
+    }
+}
@@ -8,8 +8,10 @@ use uuid::Uuid;
 use glob::Pattern;
 use serde::{Deserialize, Serialize};
 
+use crate::config_layers::LayeredConfig;
 use crate::feature_flags::FeatureFlags;
 use crate::git::repository::Repository;
+use crate::observability::logging;
 
 #[cfg(any(test, feature = "test-support"))]
 use std::sync::RwLock;
@@ -17,6 +19,36 @@ use std::sync::RwLock;
 /// Default API base URL for comparison
 pub const DEFAULT_API_BASE_URL: &str = "https://usegitai.com";
 
+/// Identities excluded from aggregate stats unless `--include-bots` is passed.
+/// Matched as glob patterns against the mailmapped author name, email, and `Name <email>`.
+pub const DEFAULT_BOT_AUTHORS: &[&str] =
+    &["*[[]bot]*", "dependabot*", "renovate*", "github-actions*"];
+
+/// Upper bound on concurrent git subprocesses and blocking threads
+pub const DEFAULT_MAX_CONCURRENCY: usize = 30;
+
+/// Nice level for background processes (flushes, upgrades); 19 is the lowest priority
+pub const DEFAULT_BACKGROUND_NICE: i32 = 10;
+
+/// Working logs untouched for this many days are pruned by `git-ai gc`
+pub const DEFAULT_CHECKPOINT_MAX_AGE_DAYS: u64 = 30;
+
+/// Days a commit reached only by a reflog entry keeps its note under `git-ai gc --notes`.
+/// Matches git's own `gc.reflogExpireUnreachable`.
+pub const DEFAULT_NOTES_GC_GRACE_DAYS: u64 = 30;
+
+/// Working logs kept per repository by `git-ai gc`
+pub const DEFAULT_CHECKPOINT_MAX_COUNT: usize = 20;
+
+/// Minimum minutes between the notes fetches run by the post-checkout/post-merge hooks
+pub const DEFAULT_NOTES_AUTO_FETCH_MINUTES: u64 = 15;
+
+/// Note blobs requested per fetch when a partial clone prefetches them
+pub const DEFAULT_NOTES_FETCH_CHUNK_SIZE: usize = 5000;
+
+/// Files larger than this are attributed line by line rather than character by character
+pub const DEFAULT_CHECKPOINT_SUMMARY_ONLY_BYTES: usize = 256 * 1024;
+
 pub struct Config {
     git_path: String,
     exclude_prompts_in_repositories: Vec<Pattern>,
@@ -37,6 +69,41 @@ pub struct Config {
     otel_export_interval_secs: u64,
     otel_auth_header: Option<String>,
     otel_protocol: String,
+    otel_traces_enabled: bool,
+    otel_queue_capacity: Option<usize>,
+    otel_shutdown_grace_ms: Option<u64>,
+    prometheus_enabled: bool,
+    metrics_sinks: Vec<String>,
+    webhook_url: Option<String>,
+    webhook_secret: Option<String>,
+    telemetry_privacy: Option<String>,
+    telemetry_redact: Option<String>,
+    telemetry_allow: Option<String>,
+    telemetry_hash_salt: Option<String>,
+    bot_authors: Vec<Pattern>,
+    mirror_summary_notes: bool,
+    max_concurrency: usize,
+    background_nice: i32,
+    background_io_idle: bool,
+    agent_detectors: Vec<AgentDetectorConfig>,
+    metrics_routes: Vec<MetricsRouteConfig>,
+    notes_sync_remotes: Option<String>,
+    notes_transport: Option<String>,
+    notes_auto_fetch_minutes: Option<u64>,
+    notes_fetch_chunk_size: usize,
+    checkpoint_summary_only_bytes: usize,
+    sign_notes: Option<bool>,
+    verify_notes: Option<String>,
+    policy_file: Option<String>,
+    required_version: Option<String>,
+    release_signing_key: Option<String>,
+    checkpoint_max_age_days: Option<u64>,
+    checkpoint_max_count: Option<usize>,
+    checkpoint_keep_until_committed: bool,
+    gc_auto: bool,
+    notes_gc_auto: bool,
+    detect_generated_files: bool,
+    notes_gc_grace_days: Option<u64>,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -57,7 +124,7 @@ impl UpdateChannel {
         }
     }
 
-    fn from_str(input: &str) -> Option<Self> {
+    pub(crate) fn from_str(input: &str) -> Option<Self> {
         match input.trim().to_lowercase().as_str() {
             "latest" => Some(UpdateChannel::Latest),
             "next" => Some(UpdateChannel::Next),
@@ -113,8 +180,137 @@ pub struct FileConfig {
     pub otel_auth_header: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub otel_protocol: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub otel_traces_enabled: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub otel_queue_capacity: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub otel_shutdown_grace_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prometheus_enabled: Option<bool>,
+    /// Comma-separated extra metrics sinks, e.g. "statsd"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metrics_sink: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook_url: Option<String>,
+    /// Key for the HMAC-SHA256 signature on webhook deliveries
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook_secret: Option<String>,
+    /// Redaction preset for exported metric attributes: "strict", "default", "off" or "aggregate"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub telemetry_privacy: Option<String>,
+    /// Comma-separated per-attribute rules, e.g. "author=hash,branch=drop"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub telemetry_redact: Option<String>,
+    /// Comma-separated attributes to export; all others are dropped
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub telemetry_allow: Option<String>,
+    /// Salt mixed into hashed attribute values
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub telemetry_hash_salt: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bot_authors: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mirror_summary_notes: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrency: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub background_nice: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub background_io_idle: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_detectors: Option<Vec<AgentDetectorConfig>>,
+    /// Which event types each metrics sink receives, and at what sample rate
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metrics_routes: Option<Vec<MetricsRouteConfig>>,
+    /// Fallback for `git-ai.syncRemotes` when git config doesn't set it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes_sync_remotes: Option<String>,
+    /// How notes are fetched and pushed: "auto", "cli" or "libgit2"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes_transport: Option<String>,
+    /// Minutes between notes fetches run from git hooks (0 = never)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes_auto_fetch_minutes: Option<u64>,
+    /// Note blobs requested per fetch in partial clones (0 = all at once)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes_fetch_chunk_size: Option<usize>,
+    /// Files above this size get line-level attribution only (0 = never)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checkpoint_summary_only_bytes: Option<usize>,
+    /// Fallback for `git-ai.signNotes`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sign_notes: Option<bool>,
+    /// Fallback for `git-ai.verifyNotes`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verify_notes: Option<String>,
+    /// Policy file relative to the repository root (default: policy.toml)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy_file: Option<String>,
+    /// Versions of git-ai hooks accept, e.g. ">=1.4.0" or "=1.4.2"; others get a warning
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub required_version: Option<String>,
+    /// SSH public key that must have signed a release's SHA256SUMS for self-update
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub release_signing_key: Option<String>,
+    /// Working logs older than this many days are pruned (0 = no limit)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checkpoint_max_age_days: Option<u64>,
+    /// Working logs kept per repository (0 = no limit)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checkpoint_max_count: Option<usize>,
+    /// Never prune the working log of a local branch tip
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checkpoint_keep_until_committed: Option<bool>,
+    /// Prune working logs after commits
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gc_auto: Option<bool>,
+    /// Archive notes of unreachable commits after commits, at most once a day
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes_gc_auto: Option<bool>,
+    /// Days a commit only a reflog reaches keeps its note (0 = ignore reflogs)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes_gc_grace_days: Option<u64>,
+    /// Leave lockfiles, vendored code and build output out of attribution
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detect_generated_files: Option<bool>,
+}
+
+/// A user-defined agent detector. The agent is detected when the named process is an
+/// ancestor of git-ai, or when the env var is set (and equals `env_value`, if given).
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, Default)]
+pub struct AgentDetectorConfig {
+    /// Tool name recorded in notes and metrics
+    pub tool: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub process_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env_var: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env_value: Option<String>,
+    /// Fixed model name; `model_env` takes precedence when that variable is set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_env: Option<String>,
+}
+
+/// Narrows what one metrics sink exports. A sink with routes exports an event when the
+/// first route naming its type (or naming no types) keeps it after sampling.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, Default)]
+pub struct MetricsRouteConfig {
+    /// otel, prometheus, statsd, webhook or api
+    pub sink: String,
+    /// Event types this route covers; all of them when empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<String>,
+    /// Fraction of matching events exported, 0.0 to 1.0 (default: 1.0)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample_rate: Option<f64>,
 }
 
+pub const METRICS_ROUTE_SINKS: &[&str] = &["otel", "prometheus", "statsd", "webhook", "api"];
+
 static CONFIG: OnceLock<Config> = OnceLock::new();
 
 #[cfg(any(test, feature = "test-support"))]
@@ -135,6 +331,8 @@ pub struct ConfigPatch {
     pub disable_auto_updates: Option<bool>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub prompt_storage: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mirror_summary_notes: Option<bool>,
 }
 
 impl Config {
@@ -286,6 +484,36 @@ impl Config {
         self.quiet
     }
 
+    /// Returns true if a one-line summary should be mirrored into refs/notes/commits
+    pub fn mirror_summary_notes(&self) -> bool {
+        self.mirror_summary_notes
+    }
+
+    /// Returns the maximum number of concurrent tasks (git subprocesses, blocking threads)
+    pub fn max_concurrency(&self) -> usize {
+        self.max_concurrency
+    }
+
+    /// Returns the nice level (0-19) applied to background git-ai processes
+    pub fn background_nice(&self) -> i32 {
+        self.background_nice
+    }
+
+    /// Returns true if background git-ai processes should use the idle IO class (Linux)
+    pub fn background_io_idle(&self) -> bool {
+        self.background_io_idle
+    }
+
+    /// Returns the custom agent detectors from the config file, in priority order
+    pub fn agent_detectors(&self) -> &[AgentDetectorConfig] {
+        &self.agent_detectors
+    }
+
+    /// Returns the metrics routes from the config file, in priority order
+    pub fn metrics_routes(&self) -> &[MetricsRouteConfig] {
+        &self.metrics_routes
+    }
+
     /// Returns true if OpenTelemetry export is enabled
     pub fn is_otel_enabled(&self) -> bool {
         self.otel_enabled
@@ -311,6 +539,162 @@ impl Config {
         &self.otel_protocol
     }
 
+    pub fn otel_traces_enabled(&self) -> bool {
+        self.otel_traces_enabled
+    }
+
+    pub fn otel_queue_capacity(&self) -> Option<usize> {
+        self.otel_queue_capacity
+    }
+
+    pub fn otel_shutdown_grace_ms(&self) -> Option<u64> {
+        self.otel_shutdown_grace_ms
+    }
+
+    /// Returns true if Prometheus metrics should be recorded
+    pub fn is_prometheus_enabled(&self) -> bool {
+        self.prometheus_enabled
+    }
+
+    /// Extra metrics sinks from `GIT_AI_METRICS_SINK`/`metrics_sink`, lowercased
+    pub fn metrics_sinks(&self) -> &[String] {
+        &self.metrics_sinks
+    }
+
+    /// Returns true if `sink` (e.g. "statsd") is listed in `GIT_AI_METRICS_SINK`/`metrics_sink`
+    pub fn is_metrics_sink_enabled(&self, sink: &str) -> bool {
+        self.metrics_sinks.iter().any(|s| s == sink)
+    }
+
+    /// Returns the endpoint the webhook metrics sink posts to, if configured
+    pub fn webhook_url(&self) -> Option<&str> {
+        self.webhook_url.as_deref()
+    }
+
+    /// Returns the webhook signing secret, if configured
+    pub fn webhook_secret(&self) -> Option<&str> {
+        self.webhook_secret.as_deref()
+    }
+
+    /// Redaction preset from `GIT_AI_TELEMETRY_PRIVACY`/`telemetry_privacy`, if set
+    pub fn telemetry_privacy(&self) -> Option<&str> {
+        self.telemetry_privacy.as_deref()
+    }
+
+    /// Per-attribute redaction rules from `GIT_AI_TELEMETRY_REDACT`/`telemetry_redact`, if set
+    pub fn telemetry_redact(&self) -> Option<&str> {
+        self.telemetry_redact.as_deref()
+    }
+
+    /// Attribute allowlist from `GIT_AI_TELEMETRY_ALLOW`/`telemetry_allow`, if set
+    pub fn telemetry_allow(&self) -> Option<&str> {
+        self.telemetry_allow.as_deref()
+    }
+
+    /// Salt for hashed attributes from `GIT_AI_TELEMETRY_HASH_SALT`/`telemetry_hash_salt`
+    pub fn telemetry_hash_salt(&self) -> Option<&str> {
+        self.telemetry_hash_salt.as_deref()
+    }
+
+    /// `notes_sync_remotes` from git-ai.toml; git config's `git-ai.syncRemotes` wins over it
+    pub fn notes_sync_remotes(&self) -> Option<&str> {
+        self.notes_sync_remotes.as_deref()
+    }
+
+    /// `notes_transport` from git-ai.toml or `GIT_AI_NOTES_TRANSPORT`, lowercased
+    pub fn notes_transport(&self) -> Option<&str> {
+        self.notes_transport.as_deref()
+    }
+
+    /// Minutes between hook-triggered notes fetches, None when they're turned off
+    pub fn notes_auto_fetch_minutes(&self) -> Option<u64> {
+        self.notes_auto_fetch_minutes
+    }
+
+    /// Note blobs requested per fetch when a partial clone prefetches them
+    pub fn notes_fetch_chunk_size(&self) -> usize {
+        self.notes_fetch_chunk_size
+    }
+
+    /// Files above this many bytes are attributed line by line in checkpoints
+    pub fn checkpoint_summary_only_bytes(&self) -> usize {
+        self.checkpoint_summary_only_bytes
+    }
+
+    pub fn sign_notes(&self) -> Option<bool> {
+        self.sign_notes
+    }
+
+    pub fn verify_notes(&self) -> Option<&str> {
+        self.verify_notes.as_deref()
+    }
+
+    pub fn policy_file(&self) -> Option<&str> {
+        self.policy_file.as_deref()
+    }
+
+    /// Version requirement git hooks warn about when the running binary doesn't meet it
+    pub fn required_version(&self) -> Option<&str> {
+        self.required_version.as_deref()
+    }
+
+    /// SSH public key `self-update` requires release checksums to be signed with
+    pub fn release_signing_key(&self) -> Option<&str> {
+        self.release_signing_key.as_deref()
+    }
+
+    /// Age in days after which working logs are pruned, None for no limit
+    pub fn checkpoint_max_age_days(&self) -> Option<u64> {
+        self.checkpoint_max_age_days
+    }
+
+    /// Number of working logs kept per repository, None for no limit
+    pub fn checkpoint_max_count(&self) -> Option<usize> {
+        self.checkpoint_max_count
+    }
+
+    pub fn checkpoint_keep_until_committed(&self) -> bool {
+        self.checkpoint_keep_until_committed
+    }
+
+    /// Returns true if working logs should be pruned automatically after commits
+    pub fn is_gc_auto_enabled(&self) -> bool {
+        self.gc_auto
+    }
+
+    /// Returns true if notes of unreachable commits should be archived automatically
+    pub fn is_notes_gc_auto_enabled(&self) -> bool {
+        self.notes_gc_auto
+    }
+
+    /// Returns true if generated files (lockfiles, vendored code, build output) are left out
+    /// of attribution without being listed in `.gitaiignore`
+    pub fn detect_generated_files(&self) -> bool {
+        self.detect_generated_files
+    }
+
+    /// Days a commit reached only by a reflog entry keeps its note, None to ignore reflogs
+    pub fn notes_gc_grace_days(&self) -> Option<u64> {
+        self.notes_gc_grace_days
+    }
+
+    /// Returns true if the author identity matches a configured bot pattern.
+    /// Accepts either a bare name/email or a `Name <email>` identity.
+    pub fn is_bot_author(&self, author: &str) -> bool {
+        let author = author.trim();
+        let (name, email) = match (author.find('<'), author.rfind('>')) {
+            (Some(start), Some(end)) if start < end => {
+                (author[..start].trim(), &author[start + 1..end])
+            }
+            _ => (author, ""),
+        };
+        self.bot_authors.iter().any(|pattern| {
+            pattern.matches(author)
+                || pattern.matches(name)
+                || (!email.is_empty() && pattern.matches(email))
+        })
+    }
+
     /// Override feature flags for testing purposes.
     /// Only available when the `test-support` feature is enabled or in test mode.
     /// Must be `pub` to work with integration tests in the `tests/` directory.
@@ -436,11 +820,10 @@ fn build_config() -> Config {
     // Build feature flags from file config
     let feature_flags = build_feature_flags(&file_cfg);
 
-    // Get API base URL from config, env var, or default
+    // Get API base URL from config (GIT_AI_API_BASE_URL overrides it) or default
     let api_base_url = file_cfg
         .as_ref()
         .and_then(|c| c.api_base_url.clone())
-        .or_else(|| env::var("GIT_AI_API_BASE_URL").ok())
         .unwrap_or_else(|| DEFAULT_API_BASE_URL.to_string());
 
     // Get prompt_storage setting (defaults to "default")
@@ -460,16 +843,11 @@ fn build_config() -> Config {
         }
     };
 
-    // Get API key from env var or config file (env var takes precedence)
-    let api_key = env::var("GIT_AI_API_KEY")
-        .ok()
-        .filter(|s| !s.is_empty())
-        .or_else(|| {
-            file_cfg
-                .as_ref()
-                .and_then(|c| c.api_key.clone())
-                .filter(|s| !s.is_empty())
-        });
+    // Get API key from config (GIT_AI_API_KEY overrides it)
+    let api_key = file_cfg
+        .as_ref()
+        .and_then(|c| c.api_key.clone())
+        .filter(|s| !s.is_empty());
 
     // Get quiet setting (defaults to false)
     let quiet = file_cfg
@@ -477,59 +855,204 @@ fn build_config() -> Config {
         .and_then(|c| c.quiet)
         .unwrap_or(false);
 
-    // Get OTel settings from config file or env vars (env vars take precedence)
-    let otel_enabled = env::var("GIT_AI_OTEL_ENABLED")
-        .map(|v| v == "1" || v.to_lowercase() == "true")
-        .ok()
-        .or_else(|| file_cfg.as_ref().and_then(|c| c.otel_enabled))
+    // Mirroring summaries into refs/notes/commits is opt-in
+    let mirror_summary_notes = file_cfg
+        .as_ref()
+        .and_then(|c| c.mirror_summary_notes)
         .unwrap_or(false);
 
-    let otel_endpoint = env::var("GIT_AI_OTEL_ENDPOINT")
-        .ok()
-        .filter(|s| !s.is_empty())
-        .or_else(|| {
-            file_cfg
-                .as_ref()
-                .and_then(|c| c.otel_endpoint.clone())
-                .filter(|s| !s.is_empty())
-        });
-
-    let otel_export_interval_secs = env::var("GIT_AI_OTEL_EXPORT_INTERVAL")
-        .ok()
-        .and_then(|v| v.parse().ok())
-        .or_else(|| file_cfg.as_ref().and_then(|c| c.otel_export_interval_secs))
-        .unwrap_or(60);
+    // Resource limits for background work, so git-ai doesn't compete with builds
+    let max_concurrency = file_cfg
+        .as_ref()
+        .and_then(|c| c.max_concurrency)
+        .filter(|n| *n > 0)
+        .unwrap_or(DEFAULT_MAX_CONCURRENCY);
+    let background_nice = file_cfg
+        .as_ref()
+        .and_then(|c| c.background_nice)
+        .unwrap_or(DEFAULT_BACKGROUND_NICE)
+        .clamp(0, 19);
+    let background_io_idle = file_cfg
+        .as_ref()
+        .and_then(|c| c.background_io_idle)
+        .unwrap_or(true);
 
-    let otel_auth_header = env::var("GIT_AI_OTEL_AUTH_HEADER")
-        .ok()
-        .filter(|s| !s.is_empty())
-        .or_else(|| {
-            file_cfg
-                .as_ref()
-                .and_then(|c| c.otel_auth_header.clone())
-                .filter(|s| !s.is_empty())
-        });
-
-    let otel_protocol = env::var("GIT_AI_OTEL_PROTOCOL")
-        .ok()
-        .filter(|s| !s.is_empty())
-        .or_else(|| {
-            file_cfg
-                .as_ref()
-                .and_then(|c| c.otel_protocol.clone())
-                .filter(|s| !s.is_empty())
+    // Detectors without a tool or anything to match on can never fire
+    let agent_detectors = file_cfg
+        .as_ref()
+        .and_then(|c| c.agent_detectors.clone())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|d| {
+            let usable = !d.tool.trim().is_empty()
+                && (d.process_name.is_some() || d.env_var.is_some());
+            if !usable {
+                eprintln!(
+                    "Warning: Ignoring agent detector '{}': needs a tool and a process_name or env_var",
+                    d.tool
+                );
+            }
+            usable
+        })
+        .collect();
+
+    // A route that can't be applied is dropped, leaving its sink with the other routes
+    let metrics_routes = file_cfg
+        .as_ref()
+        .and_then(|c| c.metrics_routes.clone())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|r| {
+            let problem = if !METRICS_ROUTE_SINKS.contains(&r.sink.as_str()) {
+                Some(format!("unknown sink '{}'", r.sink))
+            } else if let Some(event) = r
+                .events
+                .iter()
+                .find(|e| crate::metrics::MetricEventId::from_name(e).is_none())
+            {
+                Some(format!("unknown event type '{}'", event))
+            } else if r.sample_rate.is_some_and(|rate| !(0.0..=1.0).contains(&rate)) {
+                Some("sample_rate must be between 0 and 1".to_string())
+            } else {
+                None
+            };
+            if let Some(problem) = &problem {
+                eprintln!("Warning: Ignoring metrics route: {}", problem);
+            }
+            problem.is_none()
         })
+        .collect();
+
+    // Every layer, env vars included, is already merged into file_cfg
+    let setting = |get: fn(&FileConfig) -> Option<String>| {
+        file_cfg
+            .as_ref()
+            .and_then(get)
+            .filter(|s| !s.trim().is_empty())
+    };
+
+    let otel_enabled = file_cfg
+        .as_ref()
+        .and_then(|c| c.otel_enabled)
+        .unwrap_or(false);
+    let otel_endpoint = setting(|c| c.otel_endpoint.clone());
+    let otel_export_interval_secs = file_cfg
+        .as_ref()
+        .and_then(|c| c.otel_export_interval_secs)
+        .unwrap_or(60);
+    let otel_auth_header = setting(|c| c.otel_auth_header.clone());
+    // Validated against the schema when loaded
+    let otel_protocol = setting(|c| c.otel_protocol.clone())
+        .map(|p| p.to_lowercase())
         .unwrap_or_else(|| "grpc".to_string());
-    let otel_protocol = match otel_protocol.to_lowercase().as_str() {
-        "http" | "grpc" => otel_protocol.to_lowercase(),
-        other => {
-            eprintln!(
-                "Warning: Invalid otel_protocol value '{}', using 'grpc'",
-                other
-            );
-            "grpc".to_string()
-        }
+    let otel_traces_enabled = file_cfg
+        .as_ref()
+        .and_then(|c| c.otel_traces_enabled)
+        .unwrap_or(false);
+    let otel_queue_capacity = file_cfg.as_ref().and_then(|c| c.otel_queue_capacity);
+    let otel_shutdown_grace_ms = file_cfg.as_ref().and_then(|c| c.otel_shutdown_grace_ms);
+
+    let prometheus_enabled = file_cfg
+        .as_ref()
+        .and_then(|c| c.prometheus_enabled)
+        .unwrap_or(false);
+
+    let metrics_sinks = setting(|c| c.metrics_sink.clone())
+        .unwrap_or_default()
+        .split(',')
+        .map(|s| s.trim().to_lowercase())
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    let webhook_url = setting(|c| c.webhook_url.clone());
+    let webhook_secret = setting(|c| c.webhook_secret.clone());
+
+    let telemetry_privacy = setting(|c| c.telemetry_privacy.clone());
+    let telemetry_redact = setting(|c| c.telemetry_redact.clone());
+    let telemetry_allow = setting(|c| c.telemetry_allow.clone());
+    let telemetry_hash_salt = setting(|c| c.telemetry_hash_salt.clone());
+
+    let notes_sync_remotes = setting(|c| c.notes_sync_remotes.clone());
+    let notes_transport = setting(|c| c.notes_transport.clone()).map(|t| t.to_lowercase());
+    let notes_auto_fetch_minutes = Some(
+        file_cfg
+            .as_ref()
+            .and_then(|c| c.notes_auto_fetch_minutes)
+            .unwrap_or(DEFAULT_NOTES_AUTO_FETCH_MINUTES),
+    )
+    .filter(|minutes| *minutes > 0);
+    let notes_fetch_chunk_size = match file_cfg.as_ref().and_then(|c| c.notes_fetch_chunk_size) {
+        Some(0) => usize::MAX,
+        Some(size) => size,
+        None => DEFAULT_NOTES_FETCH_CHUNK_SIZE,
     };
+    let checkpoint_summary_only_bytes = match file_cfg
+        .as_ref()
+        .and_then(|c| c.checkpoint_summary_only_bytes)
+    {
+        Some(0) => usize::MAX,
+        Some(bytes) => bytes,
+        None => DEFAULT_CHECKPOINT_SUMMARY_ONLY_BYTES,
+    };
+    let sign_notes = file_cfg.as_ref().and_then(|c| c.sign_notes);
+    let verify_notes = setting(|c| c.verify_notes.clone()).map(|v| v.to_lowercase());
+    let policy_file = setting(|c| c.policy_file.clone());
+    let required_version = setting(|c| c.required_version.clone());
+    let release_signing_key = setting(|c| c.release_signing_key.clone());
+
+    // 0 turns a limit off
+    let checkpoint_max_age_days = Some(
+        file_cfg
+            .as_ref()
+            .and_then(|c| c.checkpoint_max_age_days)
+            .unwrap_or(DEFAULT_CHECKPOINT_MAX_AGE_DAYS),
+    )
+    .filter(|days| *days > 0);
+    let checkpoint_max_count = Some(
+        file_cfg
+            .as_ref()
+            .and_then(|c| c.checkpoint_max_count)
+            .unwrap_or(DEFAULT_CHECKPOINT_MAX_COUNT),
+    )
+    .filter(|count| *count > 0);
+    let checkpoint_keep_until_committed = file_cfg
+        .as_ref()
+        .and_then(|c| c.checkpoint_keep_until_committed)
+        .unwrap_or(true);
+    let gc_auto = file_cfg.as_ref().and_then(|c| c.gc_auto).unwrap_or(true);
+    let notes_gc_auto = file_cfg
+        .as_ref()
+        .and_then(|c| c.notes_gc_auto)
+        .unwrap_or(false);
+    let detect_generated_files = file_cfg
+        .as_ref()
+        .and_then(|c| c.detect_generated_files)
+        .unwrap_or(true);
+    let notes_gc_grace_days = Some(
+        file_cfg
+            .as_ref()
+            .and_then(|c| c.notes_gc_grace_days)
+            .unwrap_or(DEFAULT_NOTES_GC_GRACE_DAYS),
+    )
+    .filter(|days| *days > 0);
+
+    // An explicit (even empty) list replaces the defaults
+    let bot_authors = file_cfg
+        .as_ref()
+        .and_then(|c| c.bot_authors.clone())
+        .unwrap_or_else(|| DEFAULT_BOT_AUTHORS.iter().map(|s| s.to_string()).collect())
+        .into_iter()
+        .filter_map(|pattern_str| {
+            Pattern::new(&pattern_str)
+                .map_err(|e| {
+                    eprintln!(
+                        "Warning: Invalid glob pattern in bot_authors '{}': {}",
+                        pattern_str, e
+                    );
+                })
+                .ok()
+        })
+        .collect();
 
     #[cfg(any(test, feature = "test-support"))]
     {
@@ -553,6 +1076,41 @@ fn build_config() -> Config {
             otel_export_interval_secs,
             otel_auth_header,
             otel_protocol,
+            otel_traces_enabled,
+            otel_queue_capacity,
+            otel_shutdown_grace_ms,
+            prometheus_enabled,
+            metrics_sinks,
+            webhook_url,
+            webhook_secret,
+            telemetry_privacy,
+            telemetry_redact,
+            telemetry_allow,
+            telemetry_hash_salt,
+            bot_authors,
+            mirror_summary_notes,
+            max_concurrency,
+            background_nice,
+            background_io_idle,
+            agent_detectors,
+            metrics_routes,
+            notes_sync_remotes,
+            notes_transport,
+            notes_auto_fetch_minutes,
+            notes_fetch_chunk_size,
+            checkpoint_summary_only_bytes,
+            sign_notes,
+            verify_notes,
+            policy_file,
+            required_version,
+            release_signing_key,
+            checkpoint_max_age_days,
+            checkpoint_max_count,
+            checkpoint_keep_until_committed,
+            gc_auto,
+            notes_gc_auto,
+            notes_gc_grace_days,
+            detect_generated_files,
         };
         apply_test_config_patch(&mut config);
         config
@@ -579,6 +1137,41 @@ fn build_config() -> Config {
         otel_export_interval_secs,
         otel_auth_header,
         otel_protocol,
+        otel_traces_enabled,
+        otel_queue_capacity,
+        otel_shutdown_grace_ms,
+        prometheus_enabled,
+        metrics_sinks,
+        webhook_url,
+        webhook_secret,
+        telemetry_privacy,
+        telemetry_redact,
+        telemetry_allow,
+        telemetry_hash_salt,
+        bot_authors,
+        mirror_summary_notes,
+        max_concurrency,
+        background_nice,
+        background_io_idle,
+        agent_detectors,
+        metrics_routes,
+        notes_sync_remotes,
+        notes_transport,
+        notes_auto_fetch_minutes,
+        notes_fetch_chunk_size,
+        checkpoint_summary_only_bytes,
+        sign_notes,
+        verify_notes,
+        policy_file,
+        required_version,
+        release_signing_key,
+        checkpoint_max_age_days,
+        checkpoint_max_count,
+        checkpoint_keep_until_committed,
+        gc_auto,
+        notes_gc_auto,
+        notes_gc_grace_days,
+        detect_generated_files,
     }
 }
 
@@ -639,10 +1232,21 @@ fn resolve_git_path(file_cfg: &Option<FileConfig>) -> String {
     std::process::exit(1);
 }
 
+/// Merge every configuration layer into one FileConfig, warning about values that don't fit
+/// the schema (those are skipped)
 fn load_file_config() -> Option<FileConfig> {
-    let path = config_file_path()?;
-    let data = fs::read(&path).ok()?;
-    serde_json::from_slice::<FileConfig>(&data).ok()
+    let layered = LayeredConfig::load();
+    for problem in &layered.problems {
+        logging::warn("config", &problem.to_string());
+    }
+    serde_json::from_value(serde_json::Value::Object(layered.merged())).ok()
+}
+
+/// The configuration every layer adds up to, without the defaults `Config` fills in.
+/// Problems were already reported when `Config` was built.
+pub fn effective_file_config() -> FileConfig {
+    serde_json::from_value(serde_json::Value::Object(LayeredConfig::load().merged()))
+        .unwrap_or_default()
 }
 
 fn config_file_path() -> Option<PathBuf> {
@@ -814,6 +1418,9 @@ fn apply_test_config_patch(config: &mut Config) {
                     );
                 }
             }
+            if let Some(mirror_summary_notes) = patch.mirror_summary_notes {
+                config.mirror_summary_notes = mirror_summary_notes;
+            }
         }
     }
 }
@@ -852,9 +1459,60 @@ mod tests {
             otel_export_interval_secs: 60,
             otel_auth_header: None,
             otel_protocol: "grpc".to_string(),
+            otel_traces_enabled: false,
+            otel_queue_capacity: None,
+            otel_shutdown_grace_ms: None,
+            prometheus_enabled: false,
+            metrics_sinks: Vec::new(),
+            webhook_url: None,
+            webhook_secret: None,
+            telemetry_privacy: None,
+            telemetry_redact: None,
+            telemetry_allow: None,
+            telemetry_hash_salt: None,
+            bot_authors: DEFAULT_BOT_AUTHORS
+                .iter()
+                .filter_map(|s| Pattern::new(s).ok())
+                .collect(),
+            mirror_summary_notes: false,
+            max_concurrency: DEFAULT_MAX_CONCURRENCY,
+            background_nice: DEFAULT_BACKGROUND_NICE,
+            background_io_idle: true,
+            agent_detectors: vec![],
+            metrics_routes: vec![],
+            notes_sync_remotes: None,
+            notes_transport: None,
+            notes_auto_fetch_minutes: Some(DEFAULT_NOTES_AUTO_FETCH_MINUTES),
+            notes_fetch_chunk_size: DEFAULT_NOTES_FETCH_CHUNK_SIZE,
+            checkpoint_summary_only_bytes: DEFAULT_CHECKPOINT_SUMMARY_ONLY_BYTES,
+            sign_notes: None,
+            verify_notes: None,
+            policy_file: None,
+            required_version: None,
+            release_signing_key: None,
+            checkpoint_max_age_days: Some(DEFAULT_CHECKPOINT_MAX_AGE_DAYS),
+            checkpoint_max_count: Some(DEFAULT_CHECKPOINT_MAX_COUNT),
+            checkpoint_keep_until_committed: true,
+            gc_auto: true,
+            notes_gc_auto: false,
+            notes_gc_grace_days: Some(DEFAULT_NOTES_GC_GRACE_DAYS),
+            detect_generated_files: true,
         }
     }
 
+    #[test]
+    fn test_is_bot_author_default_patterns() {
+        let config = create_test_config(vec![], vec![]);
+
+        assert!(config.is_bot_author(
+            "dependabot[bot] <49699333+dependabot[bot]@users.noreply.github.com>"
+        ));
+        assert!(config.is_bot_author("renovate-bot <bot@renovateapp.com>"));
+        assert!(config.is_bot_author("github-actions"));
+        assert!(!config.is_bot_author("Jane Doe <jane@example.com>"));
+        assert!(!config.is_bot_author("Robot Fan <1234+robotfan@users.noreply.github.com>"));
+    }
+
     #[test]
     fn test_exclusion_takes_precedence_over_allow() {
         let config = create_test_config(
@@ -962,6 +1620,44 @@ mod tests {
             otel_export_interval_secs: 60,
             otel_auth_header: None,
             otel_protocol: "grpc".to_string(),
+            otel_traces_enabled: false,
+            otel_queue_capacity: None,
+            otel_shutdown_grace_ms: None,
+            prometheus_enabled: false,
+            metrics_sinks: Vec::new(),
+            webhook_url: None,
+            webhook_secret: None,
+            telemetry_privacy: None,
+            telemetry_redact: None,
+            telemetry_allow: None,
+            telemetry_hash_salt: None,
+            bot_authors: DEFAULT_BOT_AUTHORS
+                .iter()
+                .filter_map(|s| Pattern::new(s).ok())
+                .collect(),
+            mirror_summary_notes: false,
+            max_concurrency: DEFAULT_MAX_CONCURRENCY,
+            background_nice: DEFAULT_BACKGROUND_NICE,
+            background_io_idle: true,
+            agent_detectors: vec![],
+            metrics_routes: vec![],
+            notes_sync_remotes: None,
+            notes_transport: None,
+            notes_auto_fetch_minutes: Some(DEFAULT_NOTES_AUTO_FETCH_MINUTES),
+            notes_fetch_chunk_size: DEFAULT_NOTES_FETCH_CHUNK_SIZE,
+            checkpoint_summary_only_bytes: DEFAULT_CHECKPOINT_SUMMARY_ONLY_BYTES,
+            sign_notes: None,
+            verify_notes: None,
+            policy_file: None,
+            required_version: None,
+            release_signing_key: None,
+            checkpoint_max_age_days: Some(DEFAULT_CHECKPOINT_MAX_AGE_DAYS),
+            checkpoint_max_count: Some(DEFAULT_CHECKPOINT_MAX_COUNT),
+            checkpoint_keep_until_committed: true,
+            gc_auto: true,
+            notes_gc_auto: false,
+            notes_gc_grace_days: Some(DEFAULT_NOTES_GC_GRACE_DAYS),
+            detect_generated_files: true,
         }
     }
 
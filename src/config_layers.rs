@@ -0,0 +1,961 @@
+//! Layered configuration: system → user → repository → environment.
+//!
+//! Each layer is a flat `git-ai.toml` of the same keys as `~/.git-ai/config.json`:
+//! - system: `/etc/git-ai/git-ai.toml` (`%ProgramData%\git-ai\git-ai.toml` on Windows), or the
+//!   file named by `GIT_AI_SYSTEM_CONFIG`
+//! - user: `~/.git-ai/config.json`, then `~/.git-ai/git-ai.toml`
+//! - repository: `.git/git-ai.toml`, shared by all worktrees and never committed, so a clone
+//!   can't point git-ai at another git binary or webhook
+//! - environment: the `GIT_AI_*` variable listed for the key in [`SETTINGS`]
+//!
+//! Later layers win. Every value is checked against [`SETTINGS`]; entries that don't fit are
+//! reported as [`ConfigProblem`]s and skipped, so one typo doesn't discard a whole file.
+
+use serde_json::Value;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml_edit::{Document, Item};
+
+pub const CONFIG_FILE_NAME: &str = "git-ai.toml";
+
+/// Overrides the system layer's path
+pub const SYSTEM_CONFIG_ENV: &str = "GIT_AI_SYSTEM_CONFIG";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueKind {
+    String,
+    Bool,
+    Integer { min: i64, max: i64 },
+    StringList,
+    Table,
+    TableList,
+}
+
+/// One configuration key and the values it accepts
+#[derive(Debug)]
+pub struct Setting {
+    pub key: &'static str,
+    pub kind: ValueKind,
+    /// Environment variable that overrides every file layer
+    pub env: Option<&'static str>,
+    /// Accepted values for string keys, compared case-insensitively; empty accepts anything
+    pub allowed: &'static [&'static str],
+    /// Masked by `git-ai config list`
+    pub secret: bool,
+}
+
+const fn setting(key: &'static str, kind: ValueKind) -> Setting {
+    Setting {
+        key,
+        kind,
+        env: None,
+        allowed: &[],
+        secret: false,
+    }
+}
+
+const fn env_setting(key: &'static str, kind: ValueKind, env: &'static str) -> Setting {
+    Setting {
+        key,
+        kind,
+        env: Some(env),
+        allowed: &[],
+        secret: false,
+    }
+}
+
+const fn one_of(key: &'static str, allowed: &'static [&'static str]) -> Setting {
+    Setting {
+        key,
+        kind: ValueKind::String,
+        env: None,
+        allowed,
+        secret: false,
+    }
+}
+
+const fn secret(key: &'static str, env: Option<&'static str>) -> Setting {
+    Setting {
+        key,
+        kind: ValueKind::String,
+        env,
+        allowed: &[],
+        secret: true,
+    }
+}
+
+const POSITIVE: ValueKind = ValueKind::Integer {
+    min: 1,
+    max: i64::MAX,
+};
+
+const NON_NEGATIVE: ValueKind = ValueKind::Integer {
+    min: 0,
+    max: i64::MAX,
+};
+
+pub static SETTINGS: &[Setting] = &[
+    setting("git_path", ValueKind::String),
+    setting("exclude_prompts_in_repositories", ValueKind::StringList),
+    setting("allow_repositories", ValueKind::StringList),
+    setting("exclude_repositories", ValueKind::StringList),
+    one_of("telemetry_oss", &["on", "off"]),
+    setting("telemetry_enterprise_dsn", ValueKind::String),
+    setting("disable_version_checks", ValueKind::Bool),
+    setting("disable_auto_updates", ValueKind::Bool),
+    one_of(
+        "update_channel",
+        &["latest", "next", "enterprise-latest", "enterprise-next"],
+    ),
+    setting("feature_flags", ValueKind::Table),
+    env_setting("api_base_url", ValueKind::String, "GIT_AI_API_BASE_URL"),
+    one_of("prompt_storage", &["default", "notes", "local"]),
+    secret("api_key", Some("GIT_AI_API_KEY")),
+    setting("quiet", ValueKind::Bool),
+    env_setting("otel_enabled", ValueKind::Bool, "GIT_AI_OTEL_ENABLED"),
+    env_setting("otel_endpoint", ValueKind::String, "GIT_AI_OTEL_ENDPOINT"),
+    env_setting(
+        "otel_export_interval_secs",
+        POSITIVE,
+        "GIT_AI_OTEL_EXPORT_INTERVAL",
+    ),
+    secret("otel_auth_header", Some("GIT_AI_OTEL_AUTH_HEADER")),
+    Setting {
+        env: Some("GIT_AI_OTEL_PROTOCOL"),
+        ..one_of("otel_protocol", &["grpc", "http"])
+    },
+    env_setting(
+        "otel_traces_enabled",
+        ValueKind::Bool,
+        "GIT_AI_OTEL_TRACES_ENABLED",
+    ),
+    env_setting(
+        "otel_queue_capacity",
+        POSITIVE,
+        "GIT_AI_OTEL_QUEUE_CAPACITY",
+    ),
+    env_setting(
+        "otel_shutdown_grace_ms",
+        NON_NEGATIVE,
+        "GIT_AI_OTEL_SHUTDOWN_GRACE_MS",
+    ),
+    env_setting(
+        "prometheus_enabled",
+        ValueKind::Bool,
+        "GIT_AI_PROMETHEUS_ENABLED",
+    ),
+    env_setting("metrics_sink", ValueKind::String, "GIT_AI_METRICS_SINK"),
+    env_setting("webhook_url", ValueKind::String, "GIT_AI_WEBHOOK_URL"),
+    secret("webhook_secret", Some("GIT_AI_WEBHOOK_SECRET")),
+    Setting {
+        env: Some("GIT_AI_TELEMETRY_PRIVACY"),
+        ..one_of(
+            "telemetry_privacy",
+            &["strict", "default", "off", "aggregate"],
+        )
+    },
+    env_setting(
+        "telemetry_redact",
+        ValueKind::String,
+        "GIT_AI_TELEMETRY_REDACT",
+    ),
+    env_setting(
+        "telemetry_allow",
+        ValueKind::String,
+        "GIT_AI_TELEMETRY_ALLOW",
+    ),
+    secret("telemetry_hash_salt", Some("GIT_AI_TELEMETRY_HASH_SALT")),
+    setting("bot_authors", ValueKind::StringList),
+    setting("mirror_summary_notes", ValueKind::Bool),
+    setting("max_concurrency", POSITIVE),
+    setting("background_nice", ValueKind::Integer { min: 0, max: 19 }),
+    setting("background_io_idle", ValueKind::Bool),
+    setting("agent_detectors", ValueKind::TableList),
+    setting("metrics_routes", ValueKind::TableList),
+    setting("notes_sync_remotes", ValueKind::String),
+    Setting {
+        env: Some("GIT_AI_NOTES_TRANSPORT"),
+        ..one_of("notes_transport", &["auto", "cli", "libgit2"])
+    },
+    env_setting(
+        "notes_auto_fetch_minutes",
+        NON_NEGATIVE,
+        "GIT_AI_NOTES_AUTO_FETCH_MINUTES",
+    ),
+    env_setting(
+        "notes_fetch_chunk_size",
+        NON_NEGATIVE,
+        "GIT_AI_NOTES_FETCH_CHUNK_SIZE",
+    ),
+    setting("sign_notes", ValueKind::Bool),
+    one_of("verify_notes", &["off", "warn", "strict", "true", "false"]),
+    env_setting("policy_file", ValueKind::String, "GIT_AI_POLICY_FILE"),
+    env_setting(
+        "required_version",
+        ValueKind::String,
+        "GIT_AI_REQUIRED_VERSION",
+    ),
+    env_setting(
+        "release_signing_key",
+        ValueKind::String,
+        "GIT_AI_RELEASE_SIGNING_KEY",
+    ),
+    setting("checkpoint_max_age_days", NON_NEGATIVE),
+    env_setting(
+        "checkpoint_summary_only_bytes",
+        NON_NEGATIVE,
+        "GIT_AI_CHECKPOINT_SUMMARY_ONLY_BYTES",
+    ),
+    setting("checkpoint_max_count", NON_NEGATIVE),
+    setting("checkpoint_keep_until_committed", ValueKind::Bool),
+    setting("gc_auto", ValueKind::Bool),
+    setting("notes_gc_auto", ValueKind::Bool),
+    setting("detect_generated_files", ValueKind::Bool),
+    setting("notes_gc_grace_days", NON_NEGATIVE),
+];
+
+pub fn find_setting(key: &str) -> Option<&'static Setting> {
+    SETTINGS.iter().find(|setting| setting.key == key)
+}
+
+/// The setting for `key`, or an error that suggests the closest known key
+pub fn lookup_setting(key: &str) -> Result<&'static Setting, String> {
+    find_setting(key).ok_or_else(|| match suggest_key(key) {
+        Some(suggestion) => format!("unknown key '{}' (did you mean '{}'?)", key, suggestion),
+        None => format!(
+            "unknown key '{}'; run `git-ai config --help` for the list of keys",
+            key
+        ),
+    })
+}
+
+fn suggest_key(key: &str) -> Option<&'static str> {
+    SETTINGS
+        .iter()
+        .map(|setting| (edit_distance(key, setting.key), setting.key))
+        .filter(|(distance, _)| *distance <= 3)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, key)| key)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+impl Setting {
+    /// Check a value read from a file
+    pub fn validate(&self, value: &Value) -> Result<(), String> {
+        let got = describe(value);
+        match self.kind {
+            ValueKind::String => {
+                let Some(s) = value.as_str() else {
+                    return Err(format!("expected a string, got {}", got));
+                };
+                if !self.allowed.is_empty()
+                    && !self.allowed.iter().any(|a| a.eq_ignore_ascii_case(s))
+                {
+                    return Err(format!(
+                        "must be one of {}, got \"{}\"",
+                        self.allowed.join(", "),
+                        s
+                    ));
+                }
+            }
+            ValueKind::Bool if !value.is_boolean() => {
+                return Err(format!("expected true or false, got {}", got));
+            }
+            ValueKind::Integer { min, max } => {
+                let Some(n) = value.as_i64() else {
+                    return Err(format!("expected a whole number, got {}", got));
+                };
+                if n < min || n > max {
+                    return Err(if max == i64::MAX {
+                        format!("must be at least {}, got {}", min, n)
+                    } else {
+                        format!("must be between {} and {}, got {}", min, max, n)
+                    });
+                }
+            }
+            ValueKind::StringList => {
+                let all_strings = value
+                    .as_array()
+                    .is_some_and(|items| items.iter().all(Value::is_string));
+                if !all_strings {
+                    return Err(format!("expected a list of strings, got {}", got));
+                }
+            }
+            ValueKind::Table if !value.is_object() => {
+                return Err(format!("expected a table, got {}", got));
+            }
+            ValueKind::TableList => {
+                let all_tables = value
+                    .as_array()
+                    .is_some_and(|items| items.iter().all(Value::is_object));
+                if !all_tables {
+                    return Err(format!(
+                        "expected a list of tables ([[{}]]), got {}",
+                        self.key, got
+                    ));
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Parse a value given as text, on the command line or in an environment variable.
+    /// Lists are comma-separated or JSON; tables are JSON.
+    pub fn parse_text(&self, text: &str) -> Result<Value, String> {
+        let value = match self.kind {
+            ValueKind::String => Value::String(text.to_string()),
+            ValueKind::Bool => match text.trim().to_lowercase().as_str() {
+                "true" | "1" | "yes" | "on" => Value::Bool(true),
+                "false" | "0" | "no" | "off" => Value::Bool(false),
+                _ => return Err(format!("expected true or false, got \"{}\"", text)),
+            },
+            ValueKind::Integer { .. } => text
+                .trim()
+                .parse::<i64>()
+                .map(Value::from)
+                .map_err(|_| format!("expected a whole number, got \"{}\"", text))?,
+            ValueKind::StringList => match serde_json::from_str::<Value>(text) {
+                Ok(value @ Value::Array(_)) => value,
+                _ => Value::Array(
+                    text.split(',')
+                        .map(str::trim)
+                        .filter(|item| !item.is_empty())
+                        .map(|item| Value::String(item.to_string()))
+                        .collect(),
+                ),
+            },
+            ValueKind::Table | ValueKind::TableList => serde_json::from_str(text)
+                .map_err(|e| format!("expected JSON, got \"{}\" ({})", text, e))?,
+        };
+        self.validate(&value)?;
+        Ok(value)
+    }
+}
+
+fn describe(value: &Value) -> String {
+    match value {
+        Value::Null => "nothing".to_string(),
+        Value::Bool(b) => format!("boolean {}", b),
+        Value::Number(n) => format!("number {}", n),
+        Value::String(s) => format!("string \"{}\"", s),
+        Value::Array(_) => "a list".to_string(),
+        Value::Object(_) => "a table".to_string(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigScope {
+    System,
+    User,
+    Repo,
+}
+
+impl ConfigScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConfigScope::System => "system",
+            ConfigScope::User => "user",
+            ConfigScope::Repo => "repo",
+        }
+    }
+}
+
+/// Where a value came from, formatted like `git config --show-origin`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigOrigin {
+    File { scope: ConfigScope, path: PathBuf },
+    Env(&'static str),
+}
+
+impl fmt::Display for ConfigOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigOrigin::File { path, .. } => write!(f, "file:{}", path.display()),
+            ConfigOrigin::Env(name) => write!(f, "env:{}", name),
+        }
+    }
+}
+
+/// A value that didn't pass validation, or a file that couldn't be read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigProblem {
+    pub origin: ConfigOrigin,
+    pub key: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let location = match &self.origin {
+            ConfigOrigin::File { path, .. } => path.display().to_string(),
+            ConfigOrigin::Env(name) => name.to_string(),
+        };
+        match &self.key {
+            Some(key) => write!(f, "{}: {}: {}", location, key, self.message),
+            None => write!(f, "{}: {}", location, self.message),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigEntry {
+    pub key: String,
+    pub value: Value,
+    pub origin: ConfigOrigin,
+}
+
+/// Where each file layer lives. Missing files are skipped.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigPaths {
+    pub system: Option<PathBuf>,
+    pub user_json: Option<PathBuf>,
+    pub user_toml: Option<PathBuf>,
+    pub repo: Option<PathBuf>,
+}
+
+impl ConfigPaths {
+    /// The layers for a process running in the current directory
+    pub fn discover() -> Self {
+        let git_ai_dir = crate::config::git_ai_dir_path();
+        ConfigPaths {
+            system: layer_path(ConfigScope::System),
+            user_json: git_ai_dir.as_ref().map(|dir| dir.join("config.json")),
+            user_toml: git_ai_dir.map(|dir| dir.join(CONFIG_FILE_NAME)),
+            repo: layer_path(ConfigScope::Repo),
+        }
+    }
+}
+
+/// The `git-ai.toml` for `scope`; None for the repository layer outside a repository
+pub fn layer_path(scope: ConfigScope) -> Option<PathBuf> {
+    match scope {
+        ConfigScope::System => Some(system_config_path()),
+        ConfigScope::User => crate::config::git_ai_dir_path().map(|dir| dir.join(CONFIG_FILE_NAME)),
+        ConfigScope::Repo => {
+            let cwd = std::env::current_dir().ok()?;
+            discover_common_git_dir(&cwd).map(|dir| dir.join(CONFIG_FILE_NAME))
+        }
+    }
+}
+
+fn system_config_path() -> PathBuf {
+    if let Some(path) = std::env::var_os(SYSTEM_CONFIG_ENV).filter(|p| !p.is_empty()) {
+        return PathBuf::from(path);
+    }
+    #[cfg(windows)]
+    {
+        let base = std::env::var_os("ProgramData").unwrap_or_else(|| "C:\\ProgramData".into());
+        PathBuf::from(base).join("git-ai").join(CONFIG_FILE_NAME)
+    }
+    #[cfg(not(windows))]
+    PathBuf::from("/etc/git-ai").join(CONFIG_FILE_NAME)
+}
+
+/// The repository's common git directory, found without running git: configuration is
+/// loaded before git-ai knows which git binary to run.
+fn discover_common_git_dir(start: &Path) -> Option<PathBuf> {
+    let git_dir = match std::env::var_os("GIT_DIR").filter(|d| !d.is_empty()) {
+        Some(dir) => start.join(dir),
+        None => start.ancestors().find_map(|dir| {
+            let dot_git = dir.join(".git");
+            if dot_git.is_dir() {
+                Some(dot_git)
+            } else if dot_git.is_file() {
+                let content = fs::read_to_string(&dot_git).ok()?;
+                let target = content.trim().strip_prefix("gitdir:")?.trim();
+                Some(dir.join(target))
+            } else if dir.join("HEAD").is_file() && dir.join("objects").is_dir() {
+                Some(dir.to_path_buf())
+            } else {
+                None
+            }
+        })?,
+    };
+    // Linked worktrees share the main repository's configuration
+    match fs::read_to_string(git_dir.join("commondir")) {
+        Ok(common) => Some(git_dir.join(common.trim())),
+        Err(_) => Some(git_dir),
+    }
+}
+
+/// Every configured value in load order, so later entries for a key win
+#[derive(Debug, Clone, Default)]
+pub struct LayeredConfig {
+    pub entries: Vec<ConfigEntry>,
+    pub problems: Vec<ConfigProblem>,
+}
+
+impl LayeredConfig {
+    pub fn load() -> Self {
+        Self::load_from(&ConfigPaths::discover(), |name| std::env::var(name).ok())
+    }
+
+    pub fn load_from(paths: &ConfigPaths, env: impl Fn(&str) -> Option<String>) -> Self {
+        let mut config = LayeredConfig::default();
+        if let Some(path) = &paths.system {
+            config.load_toml(ConfigScope::System, path);
+        }
+        if let Some(path) = &paths.user_json {
+            config.load_json(path);
+        }
+        if let Some(path) = &paths.user_toml {
+            config.load_toml(ConfigScope::User, path);
+        }
+        if let Some(path) = &paths.repo {
+            config.load_toml(ConfigScope::Repo, path);
+        }
+        for setting in SETTINGS {
+            let Some(name) = setting.env else { continue };
+            let Some(text) = env(name).filter(|v| !v.trim().is_empty()) else {
+                continue;
+            };
+            let origin = ConfigOrigin::Env(name);
+            match setting.parse_text(&text) {
+                Ok(value) => config.entries.push(ConfigEntry {
+                    key: setting.key.to_string(),
+                    value,
+                    origin,
+                }),
+                Err(message) => config.problems.push(ConfigProblem {
+                    origin,
+                    key: None,
+                    message,
+                }),
+            }
+        }
+        config
+    }
+
+    /// The winning entry for `key`
+    pub fn get(&self, key: &str) -> Option<&ConfigEntry> {
+        self.entries.iter().rev().find(|entry| entry.key == key)
+    }
+
+    /// The winning value of every key, as the JSON object `FileConfig` deserializes from
+    pub fn merged(&self) -> serde_json::Map<String, Value> {
+        let mut merged = serde_json::Map::new();
+        for entry in &self.entries {
+            merged.insert(entry.key.clone(), entry.value.clone());
+        }
+        merged
+    }
+
+    fn load_toml(&mut self, scope: ConfigScope, path: &Path) {
+        let origin = ConfigOrigin::File {
+            scope,
+            path: path.to_path_buf(),
+        };
+        let Some(content) = self.read(&origin, path) else {
+            return;
+        };
+        let doc = match Document::parse(content.as_str()) {
+            Ok(doc) => doc,
+            Err(e) => {
+                let line = e
+                    .span()
+                    .map(|span| content[..span.start].lines().count().max(1));
+                self.problems.push(ConfigProblem {
+                    origin,
+                    key: None,
+                    message: match line {
+                        Some(line) => {
+                            format!("not valid TOML at line {}: {}", line, e.message().trim())
+                        }
+                        None => format!("not valid TOML: {}", e.message().trim()),
+                    },
+                });
+                return;
+            }
+        };
+        for (key, item) in doc.as_table().iter() {
+            self.add(&origin, key, toml_to_json(item), true);
+        }
+    }
+
+    /// The legacy JSON file. Unknown keys are ignored, since older versions wrote keys that
+    /// no longer exist.
+    fn load_json(&mut self, path: &Path) {
+        let origin = ConfigOrigin::File {
+            scope: ConfigScope::User,
+            path: path.to_path_buf(),
+        };
+        let Some(content) = self.read(&origin, path) else {
+            return;
+        };
+        match serde_json::from_str::<Value>(&content) {
+            Ok(Value::Object(map)) => {
+                for (key, value) in map {
+                    // `null` is how older versions wrote an unset key
+                    if !value.is_null() {
+                        self.add(&origin, &key, value, false);
+                    }
+                }
+            }
+            Ok(_) => self.problems.push(ConfigProblem {
+                origin,
+                key: None,
+                message: "expected a JSON object".to_string(),
+            }),
+            Err(e) => self.problems.push(ConfigProblem {
+                origin,
+                key: None,
+                message: format!("not valid JSON: {}", e),
+            }),
+        }
+    }
+
+    fn read(&mut self, origin: &ConfigOrigin, path: &Path) -> Option<String> {
+        match fs::read_to_string(path) {
+            Ok(content) => Some(content),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => {
+                self.problems.push(ConfigProblem {
+                    origin: origin.clone(),
+                    key: None,
+                    message: format!("could not read file: {}", e),
+                });
+                None
+            }
+        }
+    }
+
+    fn add(&mut self, origin: &ConfigOrigin, key: &str, value: Value, report_unknown: bool) {
+        let checked = lookup_setting(key).and_then(|setting| setting.validate(&value));
+        match checked {
+            Ok(()) => self.entries.push(ConfigEntry {
+                key: key.to_string(),
+                value,
+                origin: origin.clone(),
+            }),
+            Err(message) if report_unknown || find_setting(key).is_some() => {
+                self.problems.push(ConfigProblem {
+                    origin: origin.clone(),
+                    key: find_setting(key).map(|_| key.to_string()),
+                    message,
+                })
+            }
+            Err(_) => {}
+        }
+    }
+}
+
+fn toml_to_json(item: &Item) -> Value {
+    match item {
+        Item::None => Value::Null,
+        Item::Value(value) => toml_value_to_json(value),
+        Item::Table(table) => Value::Object(
+            table
+                .iter()
+                .map(|(key, item)| (key.to_string(), toml_to_json(item)))
+                .collect(),
+        ),
+        Item::ArrayOfTables(tables) => Value::Array(
+            tables
+                .iter()
+                .map(|table| toml_to_json(&Item::Table(table.clone())))
+                .collect(),
+        ),
+    }
+}
+
+fn toml_value_to_json(value: &toml_edit::Value) -> Value {
+    use toml_edit::Value as Toml;
+    match value {
+        Toml::String(s) => Value::String(s.value().clone()),
+        Toml::Integer(n) => Value::from(*n.value()),
+        Toml::Float(f) => serde_json::Number::from_f64(*f.value())
+            .map(Value::Number)
+            .unwrap_or(Value::Null),
+        Toml::Boolean(b) => Value::Bool(*b.value()),
+        Toml::Datetime(d) => Value::String(d.value().to_string()),
+        Toml::Array(items) => Value::Array(items.iter().map(toml_value_to_json).collect()),
+        Toml::InlineTable(table) => Value::Object(
+            table
+                .iter()
+                .map(|(key, value)| (key.to_string(), toml_value_to_json(value)))
+                .collect(),
+        ),
+    }
+}
+
+/// Render a value as a TOML inline value
+fn json_to_toml(value: &Value) -> String {
+    match value {
+        // JSON string escapes are all valid in TOML basic strings
+        Value::String(_) | Value::Bool(_) | Value::Number(_) => value.to_string(),
+        Value::Null => "\"\"".to_string(),
+        Value::Array(items) => format!(
+            "[{}]",
+            items
+                .iter()
+                .map(json_to_toml)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        Value::Object(map) if map.is_empty() => "{}".to_string(),
+        Value::Object(map) => format!(
+            "{{ {} }}",
+            map.iter()
+                .map(|(key, value)| format!("{} = {}", toml_key(key), json_to_toml(value)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+fn toml_key(key: &str) -> String {
+    let bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if bare {
+        key.to_string()
+    } else {
+        Value::String(key.to_string()).to_string()
+    }
+}
+
+/// Set `key` in the TOML file at `path`, keeping the rest of the file (comments included)
+/// as written. The value is parsed and validated as [`Setting::parse_text`] does.
+pub fn set_in_file(path: &Path, key: &str, text: &str) -> Result<Value, String> {
+    let setting = lookup_setting(key)?;
+    let value = setting
+        .parse_text(text)
+        .map_err(|e| format!("{}: {}", key, e))?;
+    let line = format!("{} = {}", key, json_to_toml(&value));
+
+    let content = read_optional(path)?;
+    let mut updated = match top_level_span(path, &content, key)? {
+        Some(span) => format!("{}{}{}", &content[..span.start], line, &content[span.end..]),
+        None => {
+            // Top-level keys must come before the first [table] header
+            let insert_at = first_table_header(&content).unwrap_or(content.len());
+            let (before, after) = content.split_at(insert_at);
+            let separator = if before.is_empty() || before.ends_with('\n') {
+                ""
+            } else {
+                "\n"
+            };
+            format!("{}{}{}\n{}", before, separator, line, after)
+        }
+    };
+    if !updated.ends_with('\n') {
+        updated.push('\n');
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    fs::write(path, updated).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(value)
+}
+
+/// Remove `key` from the TOML file at `path`. Returns whether the key was there.
+pub fn unset_in_file(path: &Path, key: &str) -> Result<bool, String> {
+    lookup_setting(key)?;
+    let content = read_optional(path)?;
+    let Some(span) = top_level_span(path, &content, key)? else {
+        return Ok(false);
+    };
+    let end = if content[span.end..].starts_with('\n') {
+        span.end + 1
+    } else {
+        span.end
+    };
+    let updated = format!("{}{}", &content[..span.start], &content[end..]);
+    fs::write(path, updated).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(true)
+}
+
+fn read_optional(path: &Path) -> Result<String, String> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(content),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => Err(format!("Failed to read {}: {}", path.display(), e)),
+    }
+}
+
+/// Byte range of the `key = value` line(s) for a top-level key, up to the end of the value's
+/// last line (including any trailing comment)
+fn top_level_span(
+    path: &Path,
+    content: &str,
+    key: &str,
+) -> Result<Option<std::ops::Range<usize>>, String> {
+    let doc = Document::parse(content).map_err(|e| {
+        format!(
+            "{} is not valid TOML: {}",
+            path.display(),
+            e.message().trim()
+        )
+    })?;
+    let Some((key_repr, item)) = doc.as_table().get_key_value(key) else {
+        return Ok(None);
+    };
+    let (Some(key_span), Some(value_span)) = (key_repr.span(), item.span()) else {
+        return Err(format!("Could not locate {} in {}", key, path.display()));
+    };
+    if !item.is_value() {
+        return Err(format!(
+            "{} can't be changed in place because it is written as a [{}] table; edit {} by hand",
+            key,
+            key,
+            path.display()
+        ));
+    }
+    let start = content[..key_span.start].rfind('\n').map_or(0, |i| i + 1);
+    let end = content[value_span.end..]
+        .find('\n')
+        .map_or(content.len(), |i| value_span.end + i);
+    Ok(Some(start..end))
+}
+
+fn first_table_header(content: &str) -> Option<usize> {
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        if line.trim_start().starts_with('[') {
+            return Some(offset);
+        }
+        offset += line.len();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(files: &[(&str, &str)], env: &[(&str, &str)]) -> (tempfile::TempDir, LayeredConfig) {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, content) in files {
+            fs::write(dir.path().join(name), content).unwrap();
+        }
+        let paths = ConfigPaths {
+            system: Some(dir.path().join("system.toml")),
+            user_json: Some(dir.path().join("config.json")),
+            user_toml: Some(dir.path().join("user.toml")),
+            repo: Some(dir.path().join("repo.toml")),
+        };
+        let env: Vec<(String, String)> = env
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let config = LayeredConfig::load_from(&paths, |name| {
+            env.iter().find(|(k, _)| k == name).map(|(_, v)| v.clone())
+        });
+        (dir, config)
+    }
+
+    #[test]
+    fn test_later_layers_win() {
+        let (_dir, config) = load(
+            &[
+                (
+                    "system.toml",
+                    "otel_endpoint = \"https://system\"\nquiet = true\nmax_concurrency = 4\n",
+                ),
+                ("config.json", r#"{"quiet": false, "old_key": 1}"#),
+                ("user.toml", "otel_protocol = \"http\"\n"),
+                (
+                    "repo.toml",
+                    "max_concurrency = 8\n\n[[agent_detectors]]\ntool = \"cline\"\nprocess_name = \"cline\"\n",
+                ),
+            ],
+            &[("GIT_AI_OTEL_ENDPOINT", "https://env")],
+        );
+        assert!(config.problems.is_empty(), "{:?}", config.problems);
+
+        let merged = config.merged();
+        assert_eq!(merged["quiet"], Value::Bool(false));
+        assert_eq!(merged["max_concurrency"], Value::from(8));
+        assert_eq!(merged["otel_protocol"], "http");
+        assert_eq!(merged["agent_detectors"][0]["tool"], "cline");
+        assert!(!merged.contains_key("old_key"));
+
+        let endpoint = config.get("otel_endpoint").unwrap();
+        assert_eq!(endpoint.value, "https://env");
+        assert_eq!(endpoint.origin.to_string(), "env:GIT_AI_OTEL_ENDPOINT");
+        assert!(matches!(
+            config.get("max_concurrency").unwrap().origin,
+            ConfigOrigin::File {
+                scope: ConfigScope::Repo,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_problems_are_actionable() {
+        let (_dir, config) = load(
+            &[
+                (
+                    "repo.toml",
+                    "otel_endpiont = \"x\"\nbackground_nice = 25\nprompt_storage = \"cloud\"\nquiet = \"yes\"\nsign_notes = true\n",
+                ),
+                ("user.toml", "quiet = \n"),
+            ],
+            &[("GIT_AI_OTEL_ENABLED", "maybe")],
+        );
+        let messages: Vec<String> = config.problems.iter().map(|p| p.to_string()).collect();
+        let expect = |needle: &str| {
+            assert!(
+                messages.iter().any(|m| m.contains(needle)),
+                "no problem mentions {:?}: {:#?}",
+                needle,
+                messages
+            )
+        };
+        expect("unknown key 'otel_endpiont' (did you mean 'otel_endpoint'?)");
+        expect("background_nice: must be between 0 and 19, got 25");
+        expect("prompt_storage: must be one of default, notes, local, got \"cloud\"");
+        expect("quiet: expected true or false, got string \"yes\"");
+        expect("user.toml: not valid TOML at line 1");
+        expect("GIT_AI_OTEL_ENABLED: expected true or false, got \"maybe\"");
+        assert_eq!(messages.len(), 6);
+        // Valid keys in the same file still load
+        assert_eq!(config.get("sign_notes").unwrap().value, Value::Bool(true));
+    }
+
+    #[test]
+    fn test_set_and_unset_in_file_keep_the_rest() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CONFIG_FILE_NAME);
+        fs::write(
+            &path,
+            "# team settings\nquiet = true # keep charts out of CI logs\n\n[feature_flags]\nfoo = true\n",
+        )
+        .unwrap();
+
+        set_in_file(&path, "quiet", "false").unwrap();
+        set_in_file(&path, "bot_authors", "ci-bot*, release-bot").unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "# team settings\nquiet = false\n\nbot_authors = [\"ci-bot*\", \"release-bot\"]\n[feature_flags]\nfoo = true\n"
+        );
+        assert!(set_in_file(&path, "background_nice", "42").is_err());
+        assert!(set_in_file(&path, "feature_flags", "{}").is_err());
+
+        assert!(unset_in_file(&path, "quiet").unwrap());
+        assert!(!unset_in_file(&path, "quiet").unwrap());
+        let (_d, config) = load(&[("repo.toml", &fs::read_to_string(&path).unwrap())], &[]);
+        assert!(config.problems.is_empty(), "{:?}", config.problems);
+        assert!(config.get("quiet").is_none());
+        assert_eq!(config.get("bot_authors").unwrap().value[1], "release-bot");
+    }
+}
@@ -0,0 +1,669 @@
+//! `git ai daemon`: a long-running process that editor integrations talk to over a local socket.
+//!
+//! Spawning `git-ai checkpoint` for every save costs a process start, a repository discovery
+//! and a metrics flush each time. The daemon accepts the same agent-v1 checkpoint payloads as
+//! JSON-RPC calls, queues them, and every batch interval runs one checkpoint per run of
+//! consecutive events from the same session and repository. Metric export is flushed at most
+//! once per [`METRICS_FLUSH_INTERVAL`] instead of once per event.
+//!
+//! The socket is a Unix domain socket (mode 0600) at `~/.git-ai/internal/daemon.sock`. Windows
+//! has no Unix sockets in std, so there the daemon binds loopback TCP and writes the address
+//! and a per-daemon token into that file instead; any local user can reach the port, so a
+//! connection must send the token before its first request. Clients go through
+//! [`rpc::connect`] either way.
+
+pub mod rpc;
+
+use crate::authorship::staged::StagedComposition;
+use crate::commands::checkpoint;
+use crate::commands::checkpoint_agent::agent_presets::{
+    AgentCheckpointFlags, AgentCheckpointPreset, AgentRunResult, MAX_HOOK_INPUT_BYTES,
+};
+use crate::commands::checkpoint_agent::agent_v1_preset::AgentV1Preset;
+use crate::commands::status::current_staged_composition;
+use crate::error::GitAiError;
+use crate::git::find_repository_in_path;
+use crate::utils::debug_log;
+use rpc::{
+    INVALID_PARAMS, INVALID_REQUEST, METHOD_NOT_FOUND, Request, Response, SERVER_ERROR, Stream,
+};
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// Overrides the socket path for both the daemon and its clients
+pub const DAEMON_SOCKET_ENV: &str = "GIT_AI_DAEMON_SOCKET";
+
+/// How long the daemon waits after the first queued event for more to batch with it
+pub const DEFAULT_BATCH_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Minimum time between metric flushes the daemon starts
+pub const METRICS_FLUSH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Longest request line the daemon reads. Checkpoint params are hook input, so they share
+/// its limit.
+pub const MAX_REQUEST_BYTES: usize = MAX_HOOK_INPUT_BYTES;
+
+/// Socket the daemon listens on: `GIT_AI_DAEMON_SOCKET`, else `~/.git-ai/internal/daemon.sock`
+pub fn default_endpoint() -> PathBuf {
+    if let Ok(path) = std::env::var(DAEMON_SOCKET_ENV)
+        && !path.is_empty()
+    {
+        return PathBuf::from(path);
+    }
+    crate::config::internal_dir_path()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("daemon.sock")
+}
+
+#[derive(Debug, Clone)]
+pub struct DaemonConfig {
+    pub endpoint: PathBuf,
+    /// Repositories checkpoints are accepted for. Empty accepts any repository.
+    pub repositories: Vec<PathBuf>,
+    pub batch_interval: Duration,
+}
+
+#[derive(Debug, Default)]
+struct DaemonStats {
+    received: u64,
+    processed: u64,
+    checkpoints_run: u64,
+    failed: u64,
+    last_error: Option<String>,
+    /// Repository working dir -> events processed
+    repositories: BTreeMap<String, u64>,
+}
+
+struct DaemonState {
+    endpoint: PathBuf,
+    /// Secret a connection must send first. Only set where the endpoint is a TCP port.
+    token: Option<String>,
+    batch_interval: Duration,
+    started: Instant,
+    queue: Mutex<Vec<AgentRunResult>>,
+    wake: Condvar,
+    /// Held while checkpoints run, so a `flush` call and the batch worker never overlap
+    processing: Mutex<()>,
+    stats: Mutex<DaemonStats>,
+    watched: Mutex<Vec<PathBuf>>,
+    last_metrics_flush: Mutex<Option<Instant>>,
+    shutdown: AtomicBool,
+}
+
+impl DaemonState {
+    fn new(config: &DaemonConfig) -> Self {
+        Self {
+            endpoint: config.endpoint.clone(),
+            token: (!cfg!(unix)).then(|| uuid::Uuid::new_v4().simple().to_string()),
+            batch_interval: config.batch_interval,
+            started: Instant::now(),
+            queue: Mutex::new(Vec::new()),
+            wake: Condvar::new(),
+            processing: Mutex::new(()),
+            stats: Mutex::new(DaemonStats::default()),
+            watched: Mutex::new(config.repositories.clone()),
+            last_metrics_flush: Mutex::new(None),
+            shutdown: AtomicBool::new(false),
+        }
+    }
+
+    fn enqueue(&self, event: AgentRunResult) -> usize {
+        let mut queue = self.queue.lock().unwrap();
+        queue.push(event);
+        self.stats.lock().unwrap().received += 1;
+        self.wake.notify_all();
+        queue.len()
+    }
+
+    /// Run every queued checkpoint now. Returns the number of events processed.
+    fn process_pending(&self) -> usize {
+        let _processing = self.processing.lock().unwrap();
+        let events: Vec<AgentRunResult> = std::mem::take(&mut *self.queue.lock().unwrap());
+        if events.is_empty() {
+            return 0;
+        }
+        let received = events.len();
+
+        for (event, count) in coalesce(events) {
+            let result = run_checkpoint(event);
+            let mut stats = self.stats.lock().unwrap();
+            stats.processed += count as u64;
+            match result {
+                Ok(workdir) => {
+                    stats.checkpoints_run += 1;
+                    *stats.repositories.entry(workdir).or_default() += count as u64;
+                }
+                Err(e) => {
+                    debug_log(&format!("daemon checkpoint failed: {}", e));
+                    stats.failed += count as u64;
+                    stats.last_error = Some(e.to_string());
+                }
+            }
+        }
+
+        self.maybe_flush_metrics(false);
+        received
+    }
+
+    /// Composition of the staged changes in `dir`, after running any queued checkpoints so
+    /// edits already reported by editors are attributed to their agents
+    fn staged_composition(&self, dir: &str) -> Result<StagedComposition, GitAiError> {
+        self.process_pending();
+        let _processing = self.processing.lock().unwrap();
+        let repo = find_repository_in_path(dir)?;
+        current_staged_composition(&repo)
+    }
+
+    fn maybe_flush_metrics(&self, force: bool) {
+        let mut last = self.last_metrics_flush.lock().unwrap();
+        if force || last.is_none_or(|at| at.elapsed() >= METRICS_FLUSH_INTERVAL) {
+            crate::observability::spawn_background_flush();
+            *last = Some(Instant::now());
+        }
+    }
+
+    fn is_watched(&self, repo_dir: &Path) -> bool {
+        let watched = self.watched.lock().unwrap();
+        if watched.is_empty() {
+            return true;
+        }
+        let repo_dir = repo_dir
+            .canonicalize()
+            .unwrap_or_else(|_| repo_dir.to_path_buf());
+        watched.iter().any(|dir| repo_dir.starts_with(dir))
+    }
+
+    fn status(&self) -> Value {
+        let stats = self.stats.lock().unwrap();
+        let watched: Vec<String> = self
+            .watched
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|dir| dir.display().to_string())
+            .collect();
+        json!({
+            "version": env!("CARGO_PKG_VERSION"),
+            "pid": std::process::id(),
+            "endpoint": self.endpoint.display().to_string(),
+            "uptime_secs": self.started.elapsed().as_secs(),
+            "pending": self.queue.lock().unwrap().len(),
+            "received": stats.received,
+            "processed": stats.processed,
+            "checkpoints_run": stats.checkpoints_run,
+            "failed": stats.failed,
+            "last_error": stats.last_error,
+            "watched": watched,
+            "repositories": stats.repositories,
+        })
+    }
+}
+
+/// Merge consecutive checkpoints from the same session in the same repository, returning each
+/// merged checkpoint with the number of events folded into it. Editors report every save;
+/// one checkpoint over the union of their files records the same attribution for less work.
+fn coalesce(events: Vec<AgentRunResult>) -> Vec<(AgentRunResult, usize)> {
+    let mut merged: Vec<(AgentRunResult, usize)> = Vec::new();
+    for event in events {
+        if let Some((last, count)) = merged.last_mut()
+            && can_merge(last, &event)
+        {
+            merge_paths(&mut last.edited_filepaths, event.edited_filepaths);
+            merge_paths(&mut last.will_edit_filepaths, event.will_edit_filepaths);
+            if let (Some(dirty), Some(newer)) = (&mut last.dirty_files, event.dirty_files) {
+                dirty.extend(newer);
+            }
+            if event.transcript.is_some() {
+                last.transcript = event.transcript;
+            }
+            if event.agent_metadata.is_some() {
+                last.agent_metadata = event.agent_metadata;
+            }
+            *count += 1;
+            continue;
+        }
+        merged.push((event, 1));
+    }
+    merged
+}
+
+fn can_merge(a: &AgentRunResult, b: &AgentRunResult) -> bool {
+    a.checkpoint_kind == b.checkpoint_kind
+        && a.agent_id == b.agent_id
+        && a.repo_working_dir == b.repo_working_dir
+        && a.edited_filepaths.is_some() == b.edited_filepaths.is_some()
+        && a.will_edit_filepaths.is_some() == b.will_edit_filepaths.is_some()
+        && a.dirty_files.is_some() == b.dirty_files.is_some()
+}
+
+fn merge_paths(paths: &mut Option<Vec<String>>, more: Option<Vec<String>>) {
+    if let (Some(paths), Some(more)) = (paths, more) {
+        for path in more {
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+    }
+}
+
+/// Run one checkpoint the way `git-ai checkpoint agent-v1` would. Returns the repository's
+/// working directory.
+pub(crate) fn run_checkpoint(event: AgentRunResult) -> Result<String, GitAiError> {
+    let dir = event
+        .repo_working_dir
+        .clone()
+        .ok_or_else(|| GitAiError::Generic("checkpoint has no repo_working_dir".to_string()))?;
+    let repo = find_repository_in_path(&dir)?;
+    let author = match repo.config_get_str("user.name") {
+        Ok(Some(name)) if !name.trim().is_empty() => name,
+        _ => "unknown".to_string(),
+    };
+    let kind = event.checkpoint_kind;
+    checkpoint::run(&repo, &author, kind, false, false, true, Some(event), false)?;
+    Ok(repo.workdir()?.display().to_string())
+}
+
+/// Handle one request. `Err` carries a JSON-RPC error code and message.
+fn dispatch(state: &DaemonState, request: &Request) -> Result<Value, (i64, String)> {
+    match request.method.as_str() {
+        "ping" => Ok(json!({
+            "version": env!("CARGO_PKG_VERSION"),
+            "pid": std::process::id(),
+        })),
+        "checkpoint" => {
+            if !request.params.is_object() {
+                return Err((
+                    INVALID_PARAMS,
+                    "params must be an agent-v1 checkpoint object".to_string(),
+                ));
+            }
+            let event = AgentV1Preset
+                .run(AgentCheckpointFlags {
+                    hook_input: Some(request.params.to_string()),
+                })
+                .map_err(|e| (INVALID_PARAMS, e.to_string()))?;
+            let dir = PathBuf::from(event.repo_working_dir.clone().unwrap_or_default());
+            if !state.is_watched(&dir) {
+                return Err((
+                    INVALID_PARAMS,
+                    format!("{} is not a repository this daemon watches", dir.display()),
+                ));
+            }
+            Ok(json!({ "pending": state.enqueue(event) }))
+        }
+        "flush" => Ok(json!({ "processed": state.process_pending() })),
+        "status" => Ok(state.status()),
+        "staged" => {
+            let Some(dir) = request.params.get("repo").and_then(Value::as_str) else {
+                return Err((INVALID_PARAMS, "params.repo is required".to_string()));
+            };
+            if !state.is_watched(Path::new(dir)) {
+                return Err((
+                    INVALID_PARAMS,
+                    format!("{} is not a repository this daemon watches", dir),
+                ));
+            }
+            let composition = state
+                .staged_composition(dir)
+                .map_err(|e| (SERVER_ERROR, e.to_string()))?;
+            serde_json::to_value(composition).map_err(|e| (SERVER_ERROR, e.to_string()))
+        }
+        "watch" => {
+            let Some(dir) = request.params.get("repo").and_then(Value::as_str) else {
+                return Err((INVALID_PARAMS, "params.repo is required".to_string()));
+            };
+            let repo = find_repository_in_path(dir).map_err(|e| (INVALID_PARAMS, e.to_string()))?;
+            let workdir = repo.workdir().map_err(|e| (SERVER_ERROR, e.to_string()))?;
+            let workdir = workdir.canonicalize().unwrap_or(workdir);
+            let mut watched = state.watched.lock().unwrap();
+            if !watched.contains(&workdir) {
+                watched.push(workdir);
+            }
+            let watched: Vec<String> = watched.iter().map(|d| d.display().to_string()).collect();
+            Ok(json!({ "watched": watched }))
+        }
+        "shutdown" => {
+            state.shutdown.store(true, Ordering::SeqCst);
+            state.wake.notify_all();
+            Ok(json!({ "stopping": true }))
+        }
+        other => Err((METHOD_NOT_FOUND, format!("Method not found: {}", other))),
+    }
+}
+
+/// Read one line of at most [`MAX_REQUEST_BYTES`] into `line`. False at end of stream, on a
+/// read error, or when the line is longer than the limit.
+fn read_request_line(reader: &mut impl BufRead, line: &mut String) -> bool {
+    line.clear();
+    matches!(
+        reader.take(MAX_REQUEST_BYTES as u64 + 1).read_line(line),
+        Ok(n) if n > 0 && n <= MAX_REQUEST_BYTES
+    )
+}
+
+fn handle_connection(state: Arc<DaemonState>, stream: Box<dyn Stream>) {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+
+    if let Some(token) = &state.token
+        && (!read_request_line(&mut reader, &mut line) || line.trim() != token)
+    {
+        return;
+    }
+
+    loop {
+        if !read_request_line(&mut reader, &mut line) {
+            if line.len() > MAX_REQUEST_BYTES {
+                let response = Response::error(
+                    Value::Null,
+                    INVALID_REQUEST,
+                    format!("request exceeds {} bytes", MAX_REQUEST_BYTES),
+                );
+                if let Ok(mut out) = serde_json::to_string(&response) {
+                    out.push('\n');
+                    let _ = reader.get_mut().write_all(out.as_bytes());
+                }
+            }
+            return;
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match rpc::parse_request(&line) {
+            Ok(request) => {
+                let result = dispatch(&state, &request);
+                // Notifications get no response, even on error
+                request.id.map(|id| match result {
+                    Ok(value) => Response::result(id, value),
+                    Err((code, message)) => Response::error(id, code, message),
+                })
+            }
+            Err(response) => Some(response),
+        };
+
+        if let Some(response) = response {
+            let Ok(mut out) = serde_json::to_string(&response) else {
+                return;
+            };
+            out.push('\n');
+            if reader.get_mut().write_all(out.as_bytes()).is_err() {
+                return;
+            }
+            let _ = reader.get_mut().flush();
+        }
+
+        if state.shutdown.load(Ordering::SeqCst) {
+            // The accept loop is blocked in accept(); a throwaway connection lets it see the flag
+            let _ = rpc::connect(&state.endpoint);
+            return;
+        }
+    }
+}
+
+/// Batch worker: once an event arrives, wait out the batch interval so events that follow
+/// closely share a checkpoint, then process everything queued.
+fn run_batches(state: Arc<DaemonState>) {
+    loop {
+        {
+            let mut queue = state.queue.lock().unwrap();
+            while queue.is_empty() && !state.shutdown.load(Ordering::SeqCst) {
+                queue = state.wake.wait(queue).unwrap();
+            }
+            if queue.is_empty() {
+                return;
+            }
+        }
+        if !state.shutdown.load(Ordering::SeqCst) {
+            std::thread::sleep(state.batch_interval);
+        }
+        state.process_pending();
+    }
+}
+
+#[cfg(unix)]
+type PlatformListener = std::os::unix::net::UnixListener;
+#[cfg(not(unix))]
+type PlatformListener = std::net::TcpListener;
+
+fn bind(
+    endpoint: &Path,
+    #[cfg_attr(unix, allow(unused_variables))] token: Option<&str>,
+) -> Result<PlatformListener, GitAiError> {
+    if rpc::connect(endpoint).is_ok() {
+        return Err(GitAiError::Generic(format!(
+            "a git-ai daemon is already listening on {}",
+            endpoint.display()
+        )));
+    }
+    if let Some(parent) = endpoint.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+        // Left over from a daemon that didn't shut down cleanly
+        if endpoint.exists() {
+            std::fs::remove_file(endpoint)?;
+        }
+        // Bind inside a directory only we can enter, so nobody can connect in the window
+        // before the socket's mode is tightened, then move the socket into place
+        let staging = endpoint.with_file_name(format!(
+            ".daemon-{}-{}",
+            std::process::id(),
+            uuid::Uuid::new_v4().simple()
+        ));
+        std::fs::DirBuilder::new().mode(0o700).create(&staging)?;
+        let staged = staging.join("daemon.sock");
+        let bound = PlatformListener::bind(&staged).and_then(|listener| {
+            std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o600))?;
+            std::fs::rename(&staged, endpoint)?;
+            Ok(listener)
+        });
+        let _ = std::fs::remove_dir_all(&staging);
+        Ok(bound?)
+    }
+    #[cfg(not(unix))]
+    {
+        let listener = PlatformListener::bind("127.0.0.1:0")?;
+        let token = token.ok_or_else(|| {
+            GitAiError::Generic("a TCP daemon endpoint needs a token".to_string())
+        })?;
+        std::fs::write(endpoint, format!("{}\n{}\n", listener.local_addr()?, token))?;
+        Ok(listener)
+    }
+}
+
+/// Serve requests on `config.endpoint` until a `shutdown` call. Queued checkpoints are
+/// processed before returning.
+pub fn serve(config: &DaemonConfig) -> Result<(), GitAiError> {
+    let state = Arc::new(DaemonState::new(config));
+    let listener = bind(&config.endpoint, state.token.as_deref())?;
+
+    let worker = {
+        let state = state.clone();
+        std::thread::spawn(move || run_batches(state))
+    };
+
+    loop {
+        let accepted = listener.accept();
+        if state.shutdown.load(Ordering::SeqCst) {
+            break;
+        }
+        match accepted {
+            Ok((stream, _)) => {
+                let state = state.clone();
+                std::thread::spawn(move || handle_connection(state, Box::new(stream)));
+            }
+            Err(e) => debug_log(&format!("daemon accept failed: {}", e)),
+        }
+    }
+
+    state.wake.notify_all();
+    let _ = worker.join();
+    state.process_pending();
+    state.maybe_flush_metrics(true);
+    let _ = std::fs::remove_file(&config.endpoint);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authorship::working_log::{AgentId, CheckpointKind};
+
+    fn ai_event(session: &str, files: &[&str]) -> AgentRunResult {
+        AgentRunResult {
+            agent_id: AgentId {
+                tool: "cursor".to_string(),
+                id: session.to_string(),
+                model: "gpt-4o".to_string(),
+            },
+            agent_metadata: None,
+            checkpoint_kind: CheckpointKind::AiAgent,
+            transcript: None,
+            repo_working_dir: Some("/repo".to_string()),
+            edited_filepaths: Some(files.iter().map(|f| f.to_string()).collect()),
+            will_edit_filepaths: None,
+            dirty_files: None,
+        }
+    }
+
+    fn request(method: &str, params: Value) -> Request {
+        Request {
+            jsonrpc: "2.0".to_string(),
+            id: Some(Value::from(1)),
+            method: method.to_string(),
+            params,
+        }
+    }
+
+    fn test_config() -> DaemonConfig {
+        DaemonConfig {
+            endpoint: std::env::temp_dir().join(format!(
+                "git-ai-daemon-test-{}-{}.sock",
+                std::process::id(),
+                uuid::Uuid::new_v4().simple()
+            )),
+            repositories: Vec::new(),
+            batch_interval: Duration::from_millis(10),
+        }
+    }
+
+    #[test]
+    fn test_coalesce_merges_consecutive_events_from_one_session() {
+        let merged = coalesce(vec![
+            ai_event("a", &["x.rs"]),
+            ai_event("a", &["y.rs", "x.rs"]),
+            ai_event("b", &["z.rs"]),
+            ai_event("a", &["w.rs"]),
+        ]);
+        let summary: Vec<(String, Vec<String>, usize)> = merged
+            .into_iter()
+            .map(|(event, count)| (event.agent_id.id, event.edited_filepaths.unwrap(), count))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "a".to_string(),
+                    vec!["x.rs".to_string(), "y.rs".to_string()],
+                    2
+                ),
+                ("b".to_string(), vec!["z.rs".to_string()], 1),
+                ("a".to_string(), vec!["w.rs".to_string()], 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dispatch_validates_requests() {
+        let state = DaemonState::new(&test_config());
+
+        assert!(dispatch(&state, &request("ping", Value::Null)).is_ok());
+        assert_eq!(
+            dispatch(&state, &request("bogus", Value::Null))
+                .unwrap_err()
+                .0,
+            METHOD_NOT_FOUND
+        );
+        assert_eq!(
+            dispatch(&state, &request("checkpoint", json!({"type": "nope"})))
+                .unwrap_err()
+                .0,
+            INVALID_PARAMS
+        );
+
+        let checkpoint = json!({
+            "type": "ai_agent",
+            "repo_working_dir": "/repo",
+            "edited_filepaths": ["a.rs"],
+            "transcript": {"messages": []},
+            "agent_name": "cursor",
+            "model": "gpt-4o",
+            "conversation_id": "c1",
+        });
+        assert_eq!(
+            dispatch(&state, &request("checkpoint", checkpoint)).unwrap(),
+            json!({"pending": 1})
+        );
+        assert_eq!(state.status()["pending"], json!(1));
+    }
+
+    #[test]
+    fn test_dispatch_rejects_unwatched_repositories() {
+        let mut config = test_config();
+        config.repositories = vec![PathBuf::from("/watched")];
+        let state = DaemonState::new(&config);
+        let checkpoint = json!({
+            "type": "human",
+            "repo_working_dir": "/elsewhere",
+            "will_edit_filepaths": ["a.rs"],
+        });
+        let (code, message) = dispatch(&state, &request("checkpoint", checkpoint)).unwrap_err();
+        assert_eq!(code, INVALID_PARAMS);
+        assert!(message.contains("/elsewhere"), "{}", message);
+    }
+
+    #[test]
+    fn test_read_request_line_stops_at_the_size_limit() {
+        let mut input = b"{\"method\":\"ping\"}\n".to_vec();
+        input.extend(std::iter::repeat_n(b' ', MAX_REQUEST_BYTES + 1));
+        input.push(b'\n');
+        let mut reader = std::io::Cursor::new(input);
+        let mut line = String::new();
+
+        assert!(read_request_line(&mut reader, &mut line));
+        assert_eq!(line, "{\"method\":\"ping\"}\n");
+        assert!(!read_request_line(&mut reader, &mut line));
+        assert_eq!(line.len(), MAX_REQUEST_BYTES + 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_serve_answers_over_socket_until_shutdown() {
+        let config = test_config();
+        let server = {
+            let config = config.clone();
+            std::thread::spawn(move || serve(&config))
+        };
+        assert!(rpc::wait_until_ready(
+            &config.endpoint,
+            Duration::from_secs(5)
+        ));
+
+        let status = rpc::call(&config.endpoint, "status", Value::Null).unwrap();
+        assert_eq!(status.result.unwrap()["pending"], json!(0));
+
+        let response = rpc::call(&config.endpoint, "shutdown", Value::Null).unwrap();
+        assert_eq!(response.result, Some(json!({"stopping": true})));
+        server.join().unwrap().unwrap();
+        assert!(!config.endpoint.exists());
+    }
+}
@@ -0,0 +1,176 @@
+//! JSON-RPC 2.0 framing for the daemon's local socket.
+//!
+//! Each request and response is one JSON object on its own line. Requests without an `id` are
+//! notifications and get no response, which lets editor plugins fire checkpoints without
+//! waiting on the socket.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::Path;
+use std::time::Duration;
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const SERVER_ERROR: i64 = -32000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Request {
+    #[serde(default)]
+    pub jsonrpc: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<Value>,
+    pub method: String,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub params: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Response {
+    pub jsonrpc: String,
+    pub id: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<RpcError>,
+}
+
+impl Response {
+    pub fn result(id: Value, result: Value) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: Some(result),
+            error: None,
+        }
+    }
+
+    pub fn error(id: Value, code: i64, message: impl Into<String>) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: None,
+            error: Some(RpcError {
+                code,
+                message: message.into(),
+            }),
+        }
+    }
+}
+
+/// Parse one request line. Malformed input becomes the error response to send back.
+pub fn parse_request(line: &str) -> Result<Request, Response> {
+    let value: Value = serde_json::from_str(line)
+        .map_err(|e| Response::error(Value::Null, PARSE_ERROR, format!("Parse error: {}", e)))?;
+    let id = value.get("id").cloned().unwrap_or(Value::Null);
+    let request: Request = serde_json::from_value(value)
+        .map_err(|e| Response::error(id.clone(), INVALID_REQUEST, e.to_string()))?;
+    if request.jsonrpc != "2.0" {
+        return Err(Response::error(
+            id,
+            INVALID_REQUEST,
+            "jsonrpc must be \"2.0\"",
+        ));
+    }
+    Ok(request)
+}
+
+/// A connected daemon socket: a Unix domain socket, or loopback TCP where those aren't available
+pub trait Stream: Read + Write + Send {}
+impl<T: Read + Write + Send> Stream for T {}
+
+/// Connect to the daemon listening at `endpoint`. On Windows the endpoint file holds the
+/// loopback address the daemon bound and the token that authenticates the connection.
+pub fn connect(endpoint: &Path) -> std::io::Result<Box<dyn Stream>> {
+    #[cfg(unix)]
+    {
+        Ok(Box::new(std::os::unix::net::UnixStream::connect(endpoint)?))
+    }
+    #[cfg(not(unix))]
+    {
+        let contents = std::fs::read_to_string(endpoint)?;
+        let mut lines = contents.lines();
+        let addr = lines.next().unwrap_or_default();
+        let token = lines.next().unwrap_or_default();
+        let mut stream = std::net::TcpStream::connect(addr.trim())?;
+        stream.write_all(format!("{}\n", token.trim()).as_bytes())?;
+        Ok(Box::new(stream))
+    }
+}
+
+/// Send one request and wait for its response
+pub fn call(endpoint: &Path, method: &str, params: Value) -> std::io::Result<Response> {
+    let mut stream = connect(endpoint)?;
+    let request = Request {
+        jsonrpc: "2.0".to_string(),
+        id: Some(Value::from(1)),
+        method: method.to_string(),
+        params,
+    };
+    let mut line = serde_json::to_string(&request)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())?;
+    stream.flush()?;
+
+    let mut reader = BufReader::new(stream);
+    let mut response = String::new();
+    reader.read_line(&mut response)?;
+    if response.is_empty() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::UnexpectedEof,
+            "daemon closed the connection without responding",
+        ));
+    }
+    Ok(serde_json::from_str(&response)?)
+}
+
+/// Poll until the daemon at `endpoint` answers `ping`, for up to `timeout`
+pub fn wait_until_ready(endpoint: &Path, timeout: Duration) -> bool {
+    let deadline = std::time::Instant::now() + timeout;
+    while std::time::Instant::now() < deadline {
+        if call(endpoint, "ping", Value::Null).is_ok() {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_request() {
+        let request =
+            parse_request(r#"{"jsonrpc":"2.0","id":7,"method":"ping"}"#).expect("valid request");
+        assert_eq!(request.method, "ping");
+        assert_eq!(request.id, Some(Value::from(7)));
+        assert!(request.params.is_null());
+
+        let notification =
+            parse_request(r#"{"jsonrpc":"2.0","method":"checkpoint","params":{}}"#).unwrap();
+        assert_eq!(notification.id, None);
+    }
+
+    #[test]
+    fn test_parse_request_errors() {
+        let err = parse_request("{not json").unwrap_err();
+        assert_eq!(err.error.unwrap().code, PARSE_ERROR);
+
+        let err = parse_request(r#"{"jsonrpc":"1.0","id":"a","method":"ping"}"#).unwrap_err();
+        assert_eq!(err.id, Value::from("a"));
+        assert_eq!(err.error.unwrap().code, INVALID_REQUEST);
+
+        let err = parse_request(r#"{"jsonrpc":"2.0","id":1}"#).unwrap_err();
+        assert_eq!(err.error.unwrap().code, INVALID_REQUEST);
+    }
+}
@@ -0,0 +1,92 @@
+//! CSV encoding for export tables (RFC 4180, header row first).
+
+use super::{ColumnType, Table, Value};
+use chrono::{SecondsFormat, TimeZone, Utc};
+
+pub fn write_table(table: &Table) -> String {
+    let mut out = table
+        .columns
+        .iter()
+        .map(|c| c.name)
+        .collect::<Vec<_>>()
+        .join(",");
+    out.push('\n');
+
+    for row in &table.rows {
+        let fields: Vec<String> = table
+            .columns
+            .iter()
+            .zip(row)
+            .map(|(column, value)| format_value(column.kind, value))
+            .collect();
+        out.push_str(&fields.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// Nulls are empty fields; timestamps are RFC 3339 in UTC so warehouses parse them as-is
+fn format_value(kind: ColumnType, value: &Value) -> String {
+    match (kind, value) {
+        (_, Value::Null) => String::new(),
+        (ColumnType::Timestamp, Value::Int(ms)) => Utc
+            .timestamp_millis_opt(*ms)
+            .single()
+            .map(|t| t.to_rfc3339_opts(SecondsFormat::Secs, true))
+            .unwrap_or_default(),
+        (_, Value::Int(n)) => n.to_string(),
+        (_, Value::Bool(b)) => b.to_string(),
+        (_, Value::String(s)) => csv_field(s),
+    }
+}
+
+/// Quotes a field when it contains a delimiter, quote or line break
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::Column;
+
+    #[test]
+    fn test_write_table() {
+        const COLUMNS: &[Column] = &[
+            Column::new("sha", ColumnType::String, ""),
+            Column::new("at", ColumnType::Timestamp, ""),
+            Column::new("lines", ColumnType::Int, ""),
+            Column::new("noted", ColumnType::Bool, ""),
+        ];
+        let table = Table {
+            name: "t",
+            columns: COLUMNS,
+            rows: vec![
+                vec![
+                    "Doe, \"Jane\"".into(),
+                    Value::Int(1_700_000_000_000),
+                    Value::Int(3),
+                    Value::Bool(true),
+                ],
+                vec![Value::Null, Value::Null, Value::Int(0), Value::Bool(false)],
+            ],
+        };
+
+        assert_eq!(
+            write_table(&table),
+            "sha,at,lines,noted\n\"Doe, \"\"Jane\"\"\",2023-11-14T22:13:20Z,3,true\n,,0,false\n"
+        );
+    }
+
+    #[test]
+    fn test_csv_field_escaping() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("Doe, Jane"), "\"Doe, Jane\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("line\r\nbreak"), "\"line\r\nbreak\"");
+    }
+}
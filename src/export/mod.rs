@@ -0,0 +1,711 @@
+//! Flat tables of authorship notes and metric events for `git-ai export`.
+//!
+//! Each table has a fixed, documented column list (see docs/export.md) so data teams can
+//! declare the warehouse schema once and load every export into it.
+
+pub mod csv;
+pub mod parquet;
+
+use crate::authorship::authorship_log::LineRange;
+use crate::authorship::authorship_log_serialization::AuthorshipLog;
+use crate::error::GitAiError;
+use crate::git::authorship_traversal::{batch_read_blobs, get_notes_list};
+use crate::git::repository::Repository;
+use crate::metrics::attrs::attr_pos;
+use crate::metrics::{MetricEvent, MetricEventId};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    String,
+    Int,
+    Bool,
+    /// Milliseconds since the Unix epoch, UTC
+    Timestamp,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Column {
+    pub name: &'static str,
+    pub kind: ColumnType,
+    pub description: &'static str,
+}
+
+impl Column {
+    pub const fn new(name: &'static str, kind: ColumnType, description: &'static str) -> Self {
+        Self {
+            name,
+            kind,
+            description,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    String(String),
+    Int(i64),
+    Bool(bool),
+    Null,
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
+impl From<Option<&str>> for Value {
+    fn from(value: Option<&str>) -> Self {
+        value.map_or(Value::Null, Value::from)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Value::Int(value)
+    }
+}
+
+impl From<u32> for Value {
+    fn from(value: u32) -> Self {
+        Value::Int(value.into())
+    }
+}
+
+impl From<u64> for Value {
+    fn from(value: u64) -> Self {
+        Value::Int(value as i64)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+pub struct Table {
+    pub name: &'static str,
+    pub columns: &'static [Column],
+    pub rows: Vec<Vec<Value>>,
+}
+
+impl Table {
+    fn new(name: &'static str, columns: &'static [Column]) -> Self {
+        Self {
+            name,
+            columns,
+            rows: Vec::new(),
+        }
+    }
+}
+
+pub const COMMITS_COLUMNS: &[Column] = &[
+    Column::new("commit_sha", ColumnType::String, "Full commit SHA"),
+    Column::new(
+        "author_name",
+        ColumnType::String,
+        "Commit author name (.mailmap applied)",
+    ),
+    Column::new(
+        "author_email",
+        ColumnType::String,
+        "Commit author email (.mailmap applied)",
+    ),
+    Column::new("committed_at", ColumnType::Timestamp, "Committer date"),
+    Column::new(
+        "lines_added",
+        ColumnType::Int,
+        "Lines added by the commit (text files)",
+    ),
+    Column::new(
+        "lines_deleted",
+        ColumnType::Int,
+        "Lines deleted by the commit (text files)",
+    ),
+    Column::new(
+        "ai_lines",
+        ColumnType::Int,
+        "Added lines attested to an AI session",
+    ),
+    Column::new(
+        "human_lines",
+        ColumnType::Int,
+        "Added lines not attested to any AI session",
+    ),
+    Column::new(
+        "has_authorship_note",
+        ColumnType::Bool,
+        "Whether refs/notes/ai has a note",
+    ),
+    Column::new("schema_version", ColumnType::String, "Note schema version"),
+    Column::new(
+        "git_ai_version",
+        ColumnType::String,
+        "git-ai version that wrote the note",
+    ),
+];
+
+pub const ATTRIBUTIONS_COLUMNS: &[Column] = &[
+    Column::new(
+        "commit_sha",
+        ColumnType::String,
+        "Commit the note is attached to",
+    ),
+    Column::new(
+        "file_path",
+        ColumnType::String,
+        "Repository-relative file path",
+    ),
+    Column::new(
+        "prompt_id",
+        ColumnType::String,
+        "AI session hash (joins prompts.prompt_id)",
+    ),
+    Column::new(
+        "tool",
+        ColumnType::String,
+        "Agent tool, e.g. cursor or claude",
+    ),
+    Column::new("model", ColumnType::String, "Model reported by the agent"),
+    Column::new(
+        "start_line",
+        ColumnType::Int,
+        "First attested line, 1-based",
+    ),
+    Column::new("end_line", ColumnType::Int, "Last attested line, inclusive"),
+    Column::new("line_count", ColumnType::Int, "end_line - start_line + 1"),
+];
+
+pub const PARTIAL_LINES_COLUMNS: &[Column] = &[
+    Column::new(
+        "commit_sha",
+        ColumnType::String,
+        "Commit the note is attached to",
+    ),
+    Column::new(
+        "file_path",
+        ColumnType::String,
+        "Repository-relative file path",
+    ),
+    Column::new(
+        "prompt_id",
+        ColumnType::String,
+        "AI session hash (joins prompts.prompt_id)",
+    ),
+    Column::new(
+        "line",
+        ColumnType::Int,
+        "Line only partly written by the session, 1-based",
+    ),
+    Column::new(
+        "start_column",
+        ColumnType::Int,
+        "First AI-written character, 0-based",
+    ),
+    Column::new(
+        "end_column",
+        ColumnType::Int,
+        "Character after the AI-written span",
+    ),
+];
+
+pub const PROMPTS_COLUMNS: &[Column] = &[
+    Column::new(
+        "commit_sha",
+        ColumnType::String,
+        "Commit the note is attached to",
+    ),
+    Column::new("prompt_id", ColumnType::String, "AI session hash"),
+    Column::new("tool", ColumnType::String, "Agent tool"),
+    Column::new("model", ColumnType::String, "Model reported by the agent"),
+    Column::new(
+        "agent_session_id",
+        ColumnType::String,
+        "Session id in the tool's own domain",
+    ),
+    Column::new(
+        "human_author",
+        ColumnType::String,
+        "Person who prompted the session",
+    ),
+    Column::new(
+        "total_additions",
+        ColumnType::Int,
+        "Lines the session added while working",
+    ),
+    Column::new(
+        "total_deletions",
+        ColumnType::Int,
+        "Lines the session deleted while working",
+    ),
+    Column::new(
+        "accepted_lines",
+        ColumnType::Int,
+        "Session lines committed unchanged",
+    ),
+    Column::new(
+        "overridden_lines",
+        ColumnType::Int,
+        "Session lines a human later changed",
+    ),
+    Column::new(
+        "message_count",
+        ColumnType::Int,
+        "Transcript messages stored in the note",
+    ),
+    Column::new(
+        "messages_url",
+        ColumnType::String,
+        "Where the full transcript is stored",
+    ),
+];
+
+pub const METRIC_EVENTS_COLUMNS: &[Column] = &[
+    Column::new(
+        "recorded_at",
+        ColumnType::Timestamp,
+        "When git-ai recorded the event",
+    ),
+    Column::new(
+        "event",
+        ColumnType::String,
+        "committed, agent_usage, install_hooks or checkpoint",
+    ),
+    Column::new("event_id", ColumnType::Int, "Numeric event id"),
+    Column::new("repo_url", ColumnType::String, "Normalized remote URL"),
+    Column::new(
+        "commit_sha",
+        ColumnType::String,
+        "Commit the event belongs to",
+    ),
+    Column::new("author", ColumnType::String, "Human author"),
+    Column::new(
+        "branch",
+        ColumnType::String,
+        "Branch at the time of the event",
+    ),
+    Column::new("tool", ColumnType::String, "Agent tool"),
+    Column::new("model", ColumnType::String, "Model reported by the agent"),
+    Column::new("prompt_id", ColumnType::String, "AI session hash"),
+    Column::new(
+        "values_json",
+        ColumnType::String,
+        "Event values as position-keyed JSON",
+    ),
+];
+
+/// Every table `build_export` can produce, in output order
+pub const TABLES: &[(&str, &[Column])] = &[
+    ("commits", COMMITS_COLUMNS),
+    ("attributions", ATTRIBUTIONS_COLUMNS),
+    ("partial_lines", PARTIAL_LINES_COLUMNS),
+    ("prompts", PROMPTS_COLUMNS),
+    ("metric_events", METRIC_EVENTS_COLUMNS),
+];
+
+impl ColumnType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ColumnType::String => "string",
+            ColumnType::Int => "integer",
+            ColumnType::Bool => "boolean",
+            ColumnType::Timestamp => "timestamp",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    /// Revision or range passed to `git log`, e.g. `HEAD` or `main..feature`
+    pub range: String,
+    /// Passed to `git log --since` / `--until`, so any date git understands works
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub include_metrics: bool,
+}
+
+/// One commit from the walk, with (path, added, deleted) per text file
+#[derive(Debug, Clone)]
+pub struct ExportCommit {
+    pub sha: String,
+    pub author_name: String,
+    pub author_email: String,
+    pub timestamp: i64,
+    pub files: Vec<(String, u64, u64)>,
+}
+
+/// Build every export table for the commits selected by `options`
+pub fn build_export(repo: &Repository, options: &ExportOptions) -> Result<Vec<Table>, GitAiError> {
+    let commits = list_export_commits(repo, options)?;
+    let logs = load_authorship_logs(repo, &commits)?;
+    let mut tables = commit_tables(&commits, &logs);
+
+    if options.include_metrics {
+        let events = crate::storage::LocalStore::global().and_then(|db| {
+            db.lock()
+                .map_err(|e| GitAiError::Generic(format!("Local store lock poisoned: {}", e)))?
+                .metric_events_since(0, None)
+        })?;
+        let shas: HashSet<&str> = commits.iter().map(|c| c.sha.as_str()).collect();
+        let earliest = commits.iter().map(|c| c.timestamp).min().unwrap_or(0);
+        let repo_url = default_repo_url(repo);
+        tables.push(metric_events_table(
+            &events,
+            &shas,
+            repo_url.as_deref(),
+            earliest,
+        ));
+    }
+
+    Ok(tables)
+}
+
+fn list_export_commits(
+    repo: &Repository,
+    options: &ExportOptions,
+) -> Result<Vec<ExportCommit>, GitAiError> {
+    let mut args = vec![
+        "log".to_string(),
+        "--no-renames".to_string(),
+        "--numstat".to_string(),
+        "--format=%x00%H%x09%aN%x09%aE%x09%ct".to_string(),
+    ];
+    if let Some(since) = &options.since {
+        args.push(format!("--since={}", since));
+    }
+    if let Some(until) = &options.until {
+        args.push(format!("--until={}", until));
+    }
+    args.push(options.range.clone());
+    args.push("--".to_string());
+
+    let output = repo.git(&args.iter().map(String::as_str).collect::<Vec<_>>())?;
+    Ok(parse_export_log(&output))
+}
+
+fn parse_export_log(output: &str) -> Vec<ExportCommit> {
+    let mut commits = Vec::new();
+    for record in output.split('\0').filter(|r| !r.trim().is_empty()) {
+        let mut lines = record.lines();
+        let Some(header) = lines.next() else {
+            continue;
+        };
+        let fields: Vec<&str> = header.split('\t').collect();
+        if fields.len() < 4 {
+            continue;
+        }
+
+        let files = lines
+            .filter_map(|line| {
+                let mut parts = line.splitn(3, '\t');
+                // Binary files show "-" and have no lines to attribute
+                let added = parts.next()?.parse::<u64>().ok()?;
+                let deleted = parts.next()?.parse::<u64>().ok()?;
+                Some((parts.next()?.to_string(), added, deleted))
+            })
+            .collect();
+
+        commits.push(ExportCommit {
+            sha: fields[0].to_string(),
+            author_name: fields[1].to_string(),
+            author_email: fields[2].to_string(),
+            timestamp: fields[3].trim().parse().unwrap_or(0),
+            files,
+        });
+    }
+    commits
+}
+
+/// Authorship logs for the exported commits, read in one `cat-file --batch`
+fn load_authorship_logs(
+    repo: &Repository,
+    commits: &[ExportCommit],
+) -> Result<HashMap<String, AuthorshipLog>, GitAiError> {
+    let global_args = repo.global_args_for_exec();
+    let wanted: HashSet<&str> = commits.iter().map(|c| c.sha.as_str()).collect();
+    let (blobs, shas): (Vec<String>, Vec<String>) = get_notes_list(&global_args)?
+        .into_iter()
+        .filter(|(_, commit_sha)| wanted.contains(commit_sha.as_str()))
+        .unzip();
+
+    let contents = batch_read_blobs(&global_args, &blobs)?;
+    Ok(shas
+        .into_iter()
+        .zip(contents)
+        .filter_map(|(sha, content)| {
+            AuthorshipLog::deserialize_from_string(&content)
+                .ok()
+                .map(|log| (sha, log))
+        })
+        .collect())
+}
+
+/// The commits, attributions, partial_lines and prompts tables
+fn commit_tables(commits: &[ExportCommit], logs: &HashMap<String, AuthorshipLog>) -> Vec<Table> {
+    let mut commit_rows = Table::new("commits", COMMITS_COLUMNS);
+    let mut attributions = Table::new("attributions", ATTRIBUTIONS_COLUMNS);
+    let mut partial_lines = Table::new("partial_lines", PARTIAL_LINES_COLUMNS);
+    let mut prompts = Table::new("prompts", PROMPTS_COLUMNS);
+
+    for commit in commits {
+        let log = logs.get(&commit.sha);
+        let mut attested_by_file: HashMap<&str, u64> = HashMap::new();
+
+        if let Some(log) = log {
+            for file in &log.attestations {
+                for entry in &file.entries {
+                    let prompt = log.metadata.prompts.get(&entry.hash);
+                    for range in &entry.line_ranges {
+                        let (start, end) = match range {
+                            LineRange::Single(line) => (*line, *line),
+                            LineRange::Range(start, end) => (*start, *end),
+                        };
+                        let count = u64::from(end.saturating_sub(start)) + 1;
+                        *attested_by_file.entry(file.file_path.as_str()).or_default() += count;
+                        attributions.rows.push(vec![
+                            commit.sha.as_str().into(),
+                            file.file_path.as_str().into(),
+                            entry.hash.as_str().into(),
+                            prompt.map(|p| p.agent_id.tool.as_str()).into(),
+                            prompt.map(|p| p.agent_id.model.as_str()).into(),
+                            start.into(),
+                            end.into(),
+                            count.into(),
+                        ]);
+                    }
+                }
+            }
+
+            for (file_path, by_hash) in &log.metadata.partial_lines {
+                for (hash, ranges) in by_hash {
+                    for range in ranges {
+                        partial_lines.rows.push(vec![
+                            commit.sha.as_str().into(),
+                            file_path.as_str().into(),
+                            hash.as_str().into(),
+                            range.line.into(),
+                            range.start.into(),
+                            range.end.into(),
+                        ]);
+                    }
+                }
+            }
+
+            for (hash, prompt) in &log.metadata.prompts {
+                prompts.rows.push(vec![
+                    commit.sha.as_str().into(),
+                    hash.as_str().into(),
+                    prompt.agent_id.tool.as_str().into(),
+                    prompt.agent_id.model.as_str().into(),
+                    prompt.agent_id.id.as_str().into(),
+                    prompt.human_author.as_deref().into(),
+                    prompt.total_additions.into(),
+                    prompt.total_deletions.into(),
+                    prompt.accepted_lines.into(),
+                    prompt.overriden_lines.into(),
+                    (prompt.messages.len() as u64).into(),
+                    prompt.messages_url.as_deref().into(),
+                ]);
+            }
+        }
+
+        // Attestations can't claim more lines than the commit added to the file
+        let lines_added: u64 = commit.files.iter().map(|(_, added, _)| added).sum();
+        let lines_deleted: u64 = commit.files.iter().map(|(_, _, deleted)| deleted).sum();
+        let ai_lines: u64 = commit
+            .files
+            .iter()
+            .map(|(path, added, _)| {
+                attested_by_file
+                    .get(path.as_str())
+                    .copied()
+                    .unwrap_or(0)
+                    .min(*added)
+            })
+            .sum();
+
+        commit_rows.rows.push(vec![
+            commit.sha.as_str().into(),
+            commit.author_name.as_str().into(),
+            commit.author_email.as_str().into(),
+            Value::Int(commit.timestamp * 1000),
+            lines_added.into(),
+            lines_deleted.into(),
+            ai_lines.into(),
+            (lines_added - ai_lines).into(),
+            log.is_some().into(),
+            log.map(|l| l.metadata.schema_version.as_str()).into(),
+            log.and_then(|l| l.metadata.git_ai_version.as_deref())
+                .into(),
+        ]);
+    }
+
+    vec![commit_rows, attributions, partial_lines, prompts]
+}
+
+/// Events tied to an exported commit, plus events from this repository that aren't tied to a
+/// commit (agent usage, checkpoints) recorded since the earliest exported commit
+fn metric_events_table<'a>(
+    events: &'a [MetricEvent],
+    commit_shas: &HashSet<&str>,
+    repo_url: Option<&str>,
+    earliest: i64,
+) -> Table {
+    let mut table = Table::new("metric_events", METRIC_EVENTS_COLUMNS);
+    let attr = |event: &'a MetricEvent, pos: usize| -> Option<&'a str> {
+        event.attrs.get(&pos.to_string()).and_then(|v| v.as_str())
+    };
+
+    for event in events {
+        let commit_sha = attr(event, attr_pos::COMMIT_SHA);
+        let event_repo = attr(event, attr_pos::REPO_URL);
+        let included = match commit_sha {
+            Some(sha) => commit_shas.contains(sha),
+            None => {
+                repo_url.is_some()
+                    && event_repo == repo_url
+                    && i64::from(event.timestamp) >= earliest
+            }
+        };
+        if !included {
+            continue;
+        }
+
+        let name = MetricEventId::try_from(event.event_id).map_or("unknown", MetricEventId::name);
+        // Ordered by position rather than as strings, so "10" follows "9"
+        let values: BTreeMap<usize, &serde_json::Value> = event
+            .values
+            .iter()
+            .filter_map(|(key, value)| Some((key.parse().ok()?, value)))
+            .collect();
+        let values_json = serde_json::to_string(&values).unwrap_or_default();
+
+        table.rows.push(vec![
+            Value::Int(i64::from(event.timestamp) * 1000),
+            name.into(),
+            i64::from(event.event_id).into(),
+            event_repo.into(),
+            commit_sha.into(),
+            attr(event, attr_pos::AUTHOR).into(),
+            attr(event, attr_pos::BRANCH).into(),
+            attr(event, attr_pos::TOOL).into(),
+            attr(event, attr_pos::MODEL).into(),
+            attr(event, attr_pos::PROMPT_ID).into(),
+            values_json.into(),
+        ]);
+    }
+    table
+}
+
+/// Normalized URL of the default remote, matching the `repo_url` metric attribute
+fn default_repo_url(repo: &Repository) -> Option<String> {
+    let remote = repo.get_default_remote().ok()??;
+    let (_, url) = repo
+        .remotes_with_urls()
+        .ok()?
+        .into_iter()
+        .find(|(name, _)| name == &remote)?;
+    crate::repo_url::normalize_repo_url(&url).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_utils::synthetic_authorship_log;
+    use crate::metrics::types::SparseArray;
+
+    #[test]
+    fn test_parse_export_log() {
+        let output = "\0abc\tAna\tana@example.com\t1700000000\n\n3\t1\tsrc/lib.rs\n-\t-\tlogo.png\n\0def\tBo\tbo@example.com\t1700000100\n";
+        let commits = parse_export_log(output);
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].author_email, "ana@example.com");
+        assert_eq!(commits[0].files, vec![("src/lib.rs".to_string(), 3, 1)]);
+        assert!(commits[1].files.is_empty());
+    }
+
+    #[test]
+    fn test_commit_tables_cap_ai_lines() {
+        // 1 file x 1 entry = 6 attested lines in src/module_0/file_0.rs
+        let log = synthetic_authorship_log(1, 1);
+        let commits = vec![ExportCommit {
+            sha: "a".to_string(),
+            author_name: "Ana".to_string(),
+            author_email: "ana@example.com".to_string(),
+            timestamp: 1_700_000_000,
+            files: vec![
+                ("src/module_0/file_0.rs".to_string(), 4, 0),
+                ("README.md".to_string(), 2, 1),
+            ],
+        }];
+        let logs = HashMap::from([("a".to_string(), log.clone())]);
+
+        let tables = commit_tables(&commits, &logs);
+        let commit = &tables[0].rows[0];
+        assert_eq!(commit[3], Value::Int(1_700_000_000_000));
+        assert_eq!(commit[4], Value::Int(6));
+        assert_eq!(commit[6], Value::Int(4));
+        assert_eq!(commit[7], Value::Int(2));
+        assert_eq!(commit[8], Value::Bool(true));
+
+        let line_count: i64 = tables[1]
+            .rows
+            .iter()
+            .map(|row| match row[7] {
+                Value::Int(n) => n,
+                _ => 0,
+            })
+            .sum();
+        assert_eq!(line_count, 6);
+        assert_eq!(tables[3].rows.len(), log.metadata.prompts.len());
+        for (table, (name, columns)) in tables.iter().zip(TABLES) {
+            assert_eq!(table.name, *name);
+            assert_eq!(table.columns.len(), columns.len());
+            assert!(
+                table
+                    .rows
+                    .iter()
+                    .all(|row| row.len() == table.columns.len())
+            );
+        }
+    }
+
+    #[test]
+    fn test_metric_events_table_filters_to_repo() {
+        let event = |commit: Option<&str>, repo: &str, timestamp: u32| {
+            let mut attrs = SparseArray::new();
+            attrs.insert(attr_pos::REPO_URL.to_string(), repo.into());
+            if let Some(commit) = commit {
+                attrs.insert(attr_pos::COMMIT_SHA.to_string(), commit.into());
+            }
+            MetricEvent {
+                timestamp,
+                event_id: MetricEventId::Committed as u16,
+                values: SparseArray::from([("0".to_string(), 5.into())]),
+                attrs,
+            }
+        };
+        let events = vec![
+            event(Some("a"), "https://github.com/o/r", 10),
+            event(Some("other"), "https://github.com/o/r", 10),
+            event(None, "https://github.com/o/r", 10),
+            event(None, "https://github.com/o/r", 1),
+            event(None, "https://github.com/x/y", 10),
+        ];
+        let shas = HashSet::from(["a"]);
+
+        let table = metric_events_table(&events, &shas, Some("https://github.com/o/r"), 5);
+        assert_eq!(table.rows.len(), 2);
+        assert_eq!(table.rows[0][1], Value::from("committed"));
+        assert_eq!(table.rows[0][10], Value::from("{\"0\":5}"));
+        assert_eq!(table.rows[1][4], Value::Null);
+    }
+}
@@ -0,0 +1,345 @@
+//! Minimal Parquet writer for export tables.
+//!
+//! Writes a single row group with one uncompressed, PLAIN-encoded v1 data page per column.
+//! Every column is OPTIONAL so nulls need no special casing; definition levels are RLE encoded.
+//! That subset is enough for Snowflake, BigQuery, DuckDB, Spark and pyarrow to load the files,
+//! without pulling an Arrow stack into the binary.
+
+use super::{ColumnType, Table, Value};
+
+const MAGIC: &[u8] = b"PAR1";
+
+// parquet.thrift enum values
+const TYPE_BOOLEAN: i32 = 0;
+const TYPE_INT64: i32 = 2;
+const TYPE_BYTE_ARRAY: i32 = 6;
+const CONVERTED_UTF8: i32 = 0;
+const CONVERTED_TIMESTAMP_MILLIS: i32 = 9;
+const REPETITION_OPTIONAL: i32 = 1;
+const ENCODING_PLAIN: i32 = 0;
+const ENCODING_RLE: i32 = 3;
+const CODEC_UNCOMPRESSED: i32 = 0;
+const PAGE_DATA: i32 = 0;
+
+// Thrift compact protocol field types
+const CT_I32: u8 = 5;
+const CT_I64: u8 = 6;
+const CT_BINARY: u8 = 8;
+const CT_LIST: u8 = 9;
+const CT_STRUCT: u8 = 12;
+
+/// Encode `table` as a complete Parquet file
+pub fn write_table(table: &Table) -> Vec<u8> {
+    let mut out = MAGIC.to_vec();
+    let mut chunks = Vec::with_capacity(table.columns.len());
+
+    // An empty table is just the schema, with no row group
+    let columns_with_data = if table.rows.is_empty() {
+        &[][..]
+    } else {
+        table.columns
+    };
+    for (index, column) in columns_with_data.iter().enumerate() {
+        let values: Vec<&Value> = table.rows.iter().map(|row| &row[index]).collect();
+        let page = encode_page_body(column.kind, &values);
+
+        let mut header = CompactWriter::new();
+        header.field_i32(1, PAGE_DATA);
+        header.field_i32(2, page.len() as i32);
+        header.field_i32(3, page.len() as i32);
+        header.field_struct_begin(5);
+        header.field_i32(1, values.len() as i32);
+        header.field_i32(2, ENCODING_PLAIN);
+        header.field_i32(3, ENCODING_RLE);
+        header.field_i32(4, ENCODING_RLE);
+        header.struct_end();
+        header.struct_end();
+
+        let offset = out.len() as i64;
+        let size = (header.buf.len() + page.len()) as i64;
+        out.extend_from_slice(&header.buf);
+        out.extend_from_slice(&page);
+        chunks.push((offset, size, values.len() as i64));
+    }
+
+    let mut meta = CompactWriter::new();
+    meta.field_i32(1, 1);
+
+    // Schema: a root group followed by one leaf per column
+    meta.field_list_begin(2, CT_STRUCT, table.columns.len() + 1);
+    meta.struct_begin();
+    meta.field_binary(4, b"schema");
+    meta.field_i32(5, table.columns.len() as i32);
+    meta.struct_end();
+    for column in table.columns {
+        meta.struct_begin();
+        meta.field_i32(1, physical_type(column.kind));
+        meta.field_i32(3, REPETITION_OPTIONAL);
+        meta.field_binary(4, column.name.as_bytes());
+        if let Some(converted) = converted_type(column.kind) {
+            meta.field_i32(6, converted);
+        }
+        meta.struct_end();
+    }
+
+    meta.field_i64(3, table.rows.len() as i64);
+
+    meta.field_list_begin(4, CT_STRUCT, usize::from(!chunks.is_empty()));
+    if !chunks.is_empty() {
+        write_row_group(&mut meta, table, &chunks);
+    }
+
+    meta.field_binary(
+        6,
+        format!("git-ai version {}", env!("CARGO_PKG_VERSION")).as_bytes(),
+    );
+    meta.struct_end();
+
+    out.extend_from_slice(&meta.buf);
+    out.extend_from_slice(&(meta.buf.len() as u32).to_le_bytes());
+    out.extend_from_slice(MAGIC);
+    out
+}
+
+/// RowGroup metadata for the column chunks at (offset, size, num_values)
+fn write_row_group(meta: &mut CompactWriter, table: &Table, chunks: &[(i64, i64, i64)]) {
+    meta.struct_begin();
+    meta.field_list_begin(1, CT_STRUCT, chunks.len());
+    for (column, (offset, size, num_values)) in table.columns.iter().zip(chunks) {
+        meta.struct_begin();
+        meta.field_i64(2, *offset);
+        meta.field_struct_begin(3);
+        meta.field_i32(1, physical_type(column.kind));
+        meta.field_list_begin(2, CT_I32, 2);
+        meta.write_varint(zigzag(ENCODING_PLAIN as i64));
+        meta.write_varint(zigzag(ENCODING_RLE as i64));
+        meta.field_list_begin(3, CT_BINARY, 1);
+        meta.write_binary(column.name.as_bytes());
+        meta.field_i32(4, CODEC_UNCOMPRESSED);
+        meta.field_i64(5, *num_values);
+        meta.field_i64(6, *size);
+        meta.field_i64(7, *size);
+        meta.field_i64(9, *offset);
+        meta.struct_end();
+        meta.struct_end();
+    }
+    meta.field_i64(2, chunks.iter().map(|(_, size, _)| size).sum());
+    meta.field_i64(3, table.rows.len() as i64);
+    meta.struct_end();
+}
+
+fn physical_type(kind: ColumnType) -> i32 {
+    match kind {
+        ColumnType::String => TYPE_BYTE_ARRAY,
+        ColumnType::Int | ColumnType::Timestamp => TYPE_INT64,
+        ColumnType::Bool => TYPE_BOOLEAN,
+    }
+}
+
+fn converted_type(kind: ColumnType) -> Option<i32> {
+    match kind {
+        ColumnType::String => Some(CONVERTED_UTF8),
+        ColumnType::Timestamp => Some(CONVERTED_TIMESTAMP_MILLIS),
+        ColumnType::Int | ColumnType::Bool => None,
+    }
+}
+
+/// Definition levels (length-prefixed RLE) followed by the PLAIN-encoded non-null values
+fn encode_page_body(kind: ColumnType, values: &[&Value]) -> Vec<u8> {
+    let levels = encode_definition_levels(values);
+    let mut body = (levels.len() as u32).to_le_bytes().to_vec();
+    body.extend_from_slice(&levels);
+
+    let mut bits = 0usize;
+    for value in values {
+        match (kind, value) {
+            (_, Value::Null) => {}
+            (ColumnType::String, Value::String(s)) => {
+                body.extend_from_slice(&(s.len() as u32).to_le_bytes());
+                body.extend_from_slice(s.as_bytes());
+            }
+            (ColumnType::Int | ColumnType::Timestamp, Value::Int(n)) => {
+                body.extend_from_slice(&n.to_le_bytes());
+            }
+            (ColumnType::Bool, Value::Bool(b)) => {
+                // Booleans are bit-packed, least significant bit first
+                if bits.is_multiple_of(8) {
+                    body.push(0);
+                }
+                if *b {
+                    *body.last_mut().unwrap() |= 1 << (bits % 8);
+                }
+                bits += 1;
+            }
+            (kind, value) => panic!("{:?} value in {:?} column", value, kind),
+        }
+    }
+    body
+}
+
+/// RLE runs of 1-bit definition levels: 1 for a value, 0 for null
+fn encode_definition_levels(values: &[&Value]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < values.len() {
+        let defined = !matches!(values[i], Value::Null);
+        let run = values[i..]
+            .iter()
+            .take_while(|v| !matches!(v, Value::Null) == defined)
+            .count();
+        write_varint(&mut out, (run as u64) << 1);
+        out.push(defined as u8);
+        i += run;
+    }
+    out
+}
+
+fn zigzag(n: i64) -> u64 {
+    ((n << 1) ^ (n >> 63)) as u64
+}
+
+fn write_varint(out: &mut Vec<u8>, mut n: u64) {
+    while n >= 0x80 {
+        out.push((n as u8) | 0x80);
+        n >>= 7;
+    }
+    out.push(n as u8);
+}
+
+/// Just enough of the Thrift compact protocol for Parquet page headers and footers
+struct CompactWriter {
+    buf: Vec<u8>,
+    last_field: Vec<i16>,
+}
+
+impl CompactWriter {
+    fn new() -> Self {
+        Self {
+            buf: Vec::new(),
+            last_field: vec![0],
+        }
+    }
+
+    fn field_header(&mut self, id: i16, field_type: u8) {
+        let last = self.last_field.last_mut().unwrap();
+        let delta = id - *last;
+        if (1..=15).contains(&delta) {
+            self.buf.push(((delta as u8) << 4) | field_type);
+        } else {
+            self.buf.push(field_type);
+            write_varint(&mut self.buf, zigzag(id as i64));
+        }
+        *last = id;
+    }
+
+    fn field_i32(&mut self, id: i16, value: i32) {
+        self.field_header(id, CT_I32);
+        self.write_varint(zigzag(value as i64));
+    }
+
+    fn field_i64(&mut self, id: i16, value: i64) {
+        self.field_header(id, CT_I64);
+        self.write_varint(zigzag(value));
+    }
+
+    fn field_binary(&mut self, id: i16, value: &[u8]) {
+        self.field_header(id, CT_BINARY);
+        self.write_binary(value);
+    }
+
+    fn field_list_begin(&mut self, id: i16, element_type: u8, len: usize) {
+        self.field_header(id, CT_LIST);
+        if len < 15 {
+            self.buf.push(((len as u8) << 4) | element_type);
+        } else {
+            self.buf.push(0xf0 | element_type);
+            write_varint(&mut self.buf, len as u64);
+        }
+    }
+
+    fn field_struct_begin(&mut self, id: i16) {
+        self.field_header(id, CT_STRUCT);
+        self.struct_begin();
+    }
+
+    /// Start a struct that is a list element (no field header)
+    fn struct_begin(&mut self) {
+        self.last_field.push(0);
+    }
+
+    fn struct_end(&mut self) {
+        self.buf.push(0);
+        self.last_field.pop();
+    }
+
+    fn write_varint(&mut self, n: u64) {
+        write_varint(&mut self.buf, n);
+    }
+
+    fn write_binary(&mut self, value: &[u8]) {
+        write_varint(&mut self.buf, value.len() as u64);
+        self.buf.extend_from_slice(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::Column;
+
+    const COLUMNS: &[Column] = &[
+        Column::new("name", ColumnType::String, ""),
+        Column::new("count", ColumnType::Int, ""),
+        Column::new("flag", ColumnType::Bool, ""),
+    ];
+
+    #[test]
+    fn test_file_framing() {
+        let table = Table {
+            name: "t",
+            columns: COLUMNS,
+            rows: vec![
+                vec![Value::String("a".into()), Value::Int(7), Value::Bool(true)],
+                vec![Value::Null, Value::Int(-1), Value::Null],
+            ],
+        };
+        let bytes = write_table(&table);
+
+        assert_eq!(&bytes[..4], MAGIC);
+        assert_eq!(&bytes[bytes.len() - 4..], MAGIC);
+        let footer_len =
+            u32::from_le_bytes(bytes[bytes.len() - 8..bytes.len() - 4].try_into().unwrap());
+        let footer_start = bytes.len() - 8 - footer_len as usize;
+        // FileMetaData starts with version = 1: field 1, type i32, zigzag(1) = 2
+        assert_eq!(&bytes[footer_start..footer_start + 2], &[0x15, 0x02]);
+
+        // First column's page holds the one non-null string after its levels
+        let page = encode_page_body(ColumnType::String, &[&table.rows[0][0], &table.rows[1][0]]);
+        assert_eq!(page, vec![4, 0, 0, 0, 2, 1, 2, 0, 1, 0, 0, 0, b'a']);
+    }
+
+    #[test]
+    fn test_definition_levels_and_booleans() {
+        let values = [
+            Value::Bool(true),
+            Value::Bool(false),
+            Value::Bool(true),
+            Value::Null,
+        ];
+        let refs: Vec<&Value> = values.iter().collect();
+        // Runs: three defined (3 << 1 = 6, value 1), one null (1 << 1 = 2, value 0)
+        assert_eq!(encode_definition_levels(&refs), vec![6, 1, 2, 0]);
+        let body = encode_page_body(ColumnType::Bool, &refs);
+        assert_eq!(body[body.len() - 1], 0b101);
+    }
+
+    #[test]
+    fn test_compact_field_headers() {
+        let mut writer = CompactWriter::new();
+        writer.field_i32(1, -1);
+        writer.field_i64(20, 300);
+        writer.struct_end();
+        // delta 1 / i32 / zigzag(-1) = 1; long-form header for id 20, zigzag(300) = 600
+        assert_eq!(writer.buf, vec![0x15, 0x01, 0x06, 0x28, 0xd8, 0x04, 0x00]);
+    }
+}
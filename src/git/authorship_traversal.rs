@@ -70,7 +70,7 @@ pub fn commits_have_authorship_notes(
 }
 
 /// Get all notes as (note_blob_sha, commit_sha) pairs
-fn get_notes_list(global_args: &[String]) -> Result<Vec<(String, String)>, GitAiError> {
+pub(crate) fn get_notes_list(global_args: &[String]) -> Result<Vec<(String, String)>, GitAiError> {
     let mut args = global_args.to_vec();
     args.push("notes".to_string());
     args.push("--ref=ai".to_string());
@@ -103,7 +103,7 @@ fn get_notes_list(global_args: &[String]) -> Result<Vec<(String, String)>, GitAi
 }
 
 /// Read multiple blobs efficiently using cat-file --batch
-fn batch_read_blobs(
+pub(crate) fn batch_read_blobs(
     global_args: &[String],
     blob_shas: &[String],
 ) -> Result<Vec<String>, GitAiError> {
@@ -191,6 +191,12 @@ fn parse_cat_file_batch_output(data: &[u8]) -> Result<Vec<String>, GitAiError> {
 
 /// Extract file paths from a note blob content
 fn extract_file_paths_from_note(content: &str, files: &mut HashSet<String>) {
+    // Notes written or edited on Windows may have CRLF line endings
+    let content = if content.contains('\r') {
+        std::borrow::Cow::Owned(content.replace("\r\n", "\n"))
+    } else {
+        std::borrow::Cow::Borrowed(content)
+    };
     // Find the divider and slice before it, then add minimal metadata to make it parseable
     if let Some(divider_pos) = content.find("\n---\n") {
         let attestation_section = &content[..divider_pos];
@@ -291,4 +297,15 @@ mod tests {
             );
         });
     }
+
+    #[test]
+    fn test_extract_file_paths_from_crlf_note() {
+        let note = "src/main.rs\r\n  abcd1234abcd1234 1-3\r\nsrc/lib.rs\r\n  abcd1234abcd1234 7\r\n---\r\n{}\r\n";
+        let mut files = HashSet::new();
+        extract_file_paths_from_note(note, &mut files);
+        assert_eq!(
+            files,
+            HashSet::from(["src/main.rs".to_string(), "src/lib.rs".to_string()])
+        );
+    }
 }
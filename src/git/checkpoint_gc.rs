@@ -0,0 +1,373 @@
+//! Retention for checkpoint working logs.
+//!
+//! Every base commit that saw a checkpoint gets a directory under `.git/ai/working_logs/`.
+//! Committing consumes the log for HEAD, but logs left behind by abandoned branches, resets and
+//! stashes were never removed, so long-lived clones grew without bound. `git-ai gc` prunes them
+//! under a [`RetentionPolicy`], and commits run it opportunistically at most once a day.
+//!
+//! The log for HEAD is always kept. With `keep_until_committed`, so are logs based on a local
+//! branch tip, since switching back to that branch can still commit their checkpoints.
+
+use crate::config::Config;
+use crate::error::GitAiError;
+use crate::git::notes_gc::{NotesGcPolicy, prune_stale_notes};
+use crate::git::repository::Repository;
+use crate::utils::debug_log;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Minimum time between automatic collections in one repository
+const AUTO_GC_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+/// Stamp file next to `working_logs/` whose mtime records the last automatic collection
+const AUTO_GC_STAMP: &str = "last-gc";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    /// Logs not written to for this long are pruned
+    pub max_age: Option<Duration>,
+    /// Unprotected logs kept beyond this many are pruned, oldest first
+    pub max_count: Option<usize>,
+    /// Protect logs based on a local branch tip
+    pub keep_until_committed: bool,
+}
+
+impl RetentionPolicy {
+    pub fn from_config() -> Self {
+        let config = Config::get();
+        RetentionPolicy {
+            max_age: config
+                .checkpoint_max_age_days()
+                .map(|days| Duration::from_secs(days * 24 * 60 * 60)),
+            max_count: config.checkpoint_max_count(),
+            keep_until_committed: config.checkpoint_keep_until_committed(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PruneReason {
+    /// No checkpoints and no carried-over attributions
+    Empty,
+    /// Older than `max_age`
+    Expired,
+    /// Beyond `max_count`
+    OverLimit,
+}
+
+impl PruneReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PruneReason::Empty => "empty",
+            PruneReason::Expired => "expired",
+            PruneReason::OverLimit => "over limit",
+        }
+    }
+}
+
+/// One directory under `working_logs/`
+#[derive(Debug, Clone)]
+pub struct WorkingLogInfo {
+    /// Directory name: the base commit, `initial`, or `old-<sha>` in debug builds
+    pub base: String,
+    pub dir: PathBuf,
+    pub checkpoints: usize,
+    pub has_initial: bool,
+    pub bytes: u64,
+    /// Newest mtime of anything in the directory
+    pub modified: SystemTime,
+}
+
+impl WorkingLogInfo {
+    fn is_empty(&self) -> bool {
+        self.checkpoints == 0 && !self.has_initial
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct GcReport {
+    pub removed: Vec<(WorkingLogInfo, PruneReason)>,
+    pub kept: usize,
+}
+
+impl GcReport {
+    pub fn bytes_freed(&self) -> u64 {
+        self.removed.iter().map(|(log, _)| log.bytes).sum()
+    }
+}
+
+/// Prune working logs under `policy`. With `dry_run` nothing is deleted, but the report lists
+/// what would have been.
+pub fn collect_garbage(
+    repository: &Repository,
+    policy: &RetentionPolicy,
+    dry_run: bool,
+) -> Result<GcReport, GitAiError> {
+    let logs = list_working_logs(&repository.storage.working_logs)?;
+    let tips = if policy.keep_until_committed {
+        branch_tips(repository)?
+    } else {
+        HashSet::new()
+    };
+    let pruned = plan(
+        &logs,
+        &head_base(repository),
+        &tips,
+        policy,
+        SystemTime::now(),
+    );
+
+    let mut report = GcReport {
+        kept: logs.len() - pruned.len(),
+        ..Default::default()
+    };
+    for (index, reason) in pruned {
+        let log = logs[index].clone();
+        if !dry_run {
+            fs::remove_dir_all(&log.dir)?;
+        }
+        report.removed.push((log, reason));
+    }
+    Ok(report)
+}
+
+/// Run [`collect_garbage`] with the configured policy if `gc_auto` is on and the last automatic
+/// run was more than a day ago, then archive stale notes if `notes_gc_auto` is on too. Failures
+/// are logged and otherwise ignored.
+pub fn maybe_auto_gc(repository: &Repository) {
+    if !Config::get().is_gc_auto_enabled() {
+        return;
+    }
+    let Some(ai_dir) = repository.storage.working_logs.parent() else {
+        return;
+    };
+    let stamp = ai_dir.join(AUTO_GC_STAMP);
+    let recently_run = fs::metadata(&stamp)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|elapsed| elapsed < AUTO_GC_INTERVAL);
+    if recently_run {
+        return;
+    }
+    // Stamp first so a failing collection isn't retried on every commit
+    if let Err(e) = fs::write(&stamp, "") {
+        debug_log(&format!("failed to write {}: {}", stamp.display(), e));
+        return;
+    }
+    match collect_garbage(repository, &RetentionPolicy::from_config(), false) {
+        Ok(report) if !report.removed.is_empty() => debug_log(&format!(
+            "gc: removed {} working logs ({} bytes)",
+            report.removed.len(),
+            report.bytes_freed()
+        )),
+        Ok(_) => {}
+        Err(e) => debug_log(&format!("gc failed: {}", e)),
+    }
+    if Config::get().is_notes_gc_auto_enabled() {
+        // Unattended runs archive rather than delete, so nothing is lost for good
+        let policy = NotesGcPolicy {
+            archive: true,
+            ..NotesGcPolicy::from_config()
+        };
+        match prune_stale_notes(repository, &policy, false) {
+            Ok(report) if !report.pruned.is_empty() => debug_log(&format!(
+                "gc: archived {} notes of unreachable commits",
+                report.pruned.len()
+            )),
+            Ok(_) => {}
+            Err(e) => debug_log(&format!("notes gc failed: {}", e)),
+        }
+    }
+}
+
+fn list_working_logs(working_logs: &Path) -> Result<Vec<WorkingLogInfo>, GitAiError> {
+    let entries = match fs::read_dir(working_logs) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut logs = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        let dir = entry.path();
+        let checkpoints = fs::read_to_string(dir.join("checkpoints.jsonl"))
+            .map(|content| content.lines().filter(|l| !l.trim().is_empty()).count())
+            .unwrap_or(0);
+        let has_initial = fs::metadata(dir.join("INITIAL")).is_ok_and(|meta| meta.len() > 0);
+        let (bytes, modified) = dir_usage(&dir)?;
+        logs.push(WorkingLogInfo {
+            base: entry.file_name().to_string_lossy().to_string(),
+            dir,
+            checkpoints,
+            has_initial,
+            bytes,
+            modified,
+        });
+    }
+    Ok(logs)
+}
+
+/// Total size and newest mtime of everything under `dir`, including `dir` itself
+fn dir_usage(dir: &Path) -> Result<(u64, SystemTime), GitAiError> {
+    let mut bytes = 0;
+    let mut modified = fs::metadata(dir)?.modified()?;
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let meta = entry.metadata()?;
+        if meta.is_dir() {
+            let (sub_bytes, sub_modified) = dir_usage(&entry.path())?;
+            bytes += sub_bytes;
+            modified = modified.max(sub_modified);
+        } else {
+            bytes += meta.len();
+            modified = modified.max(meta.modified()?);
+        }
+    }
+    Ok((bytes, modified))
+}
+
+/// HEAD's base, or `initial` in a repository with no commits yet
+fn head_base(repository: &Repository) -> String {
+    repository
+        .head()
+        .ok()
+        .and_then(|head| head.target().ok())
+        .unwrap_or_else(|| "initial".to_string())
+}
+
+fn branch_tips(repository: &Repository) -> Result<HashSet<String>, GitAiError> {
+    let tips = repository.git(&["for-each-ref", "--format=%(objectname)", "refs/heads"])?;
+    Ok(tips.lines().map(|line| line.trim().to_string()).collect())
+}
+
+/// Indices into `logs` to prune, with the first rule that applies to each. `branch_tips` is
+/// only consulted with `keep_until_committed`.
+fn plan(
+    logs: &[WorkingLogInfo],
+    head: &str,
+    branch_tips: &HashSet<String>,
+    policy: &RetentionPolicy,
+    now: SystemTime,
+) -> Vec<(usize, PruneReason)> {
+    let mut pruned = Vec::new();
+    let mut candidates = Vec::new();
+    for (index, log) in logs.iter().enumerate() {
+        if log.base == head {
+            continue;
+        }
+        let age = now.duration_since(log.modified).unwrap_or_default();
+        if log.is_empty() {
+            pruned.push((index, PruneReason::Empty));
+        } else if policy.keep_until_committed && branch_tips.contains(&log.base) {
+            continue;
+        } else if policy.max_age.is_some_and(|max_age| age > max_age) {
+            pruned.push((index, PruneReason::Expired));
+        } else {
+            candidates.push(index);
+        }
+    }
+
+    if let Some(max_count) = policy.max_count {
+        // Newest first, so the oldest fall past the limit
+        candidates.sort_by_key(|&index| std::cmp::Reverse(logs[index].modified));
+        pruned.extend(
+            candidates
+                .into_iter()
+                .skip(max_count)
+                .map(|index| (index, PruneReason::OverLimit)),
+        );
+    }
+    pruned
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(base: &str, checkpoints: usize, days_old: u64, now: SystemTime) -> WorkingLogInfo {
+        WorkingLogInfo {
+            base: base.to_string(),
+            dir: PathBuf::from(base),
+            checkpoints,
+            has_initial: false,
+            bytes: 0,
+            modified: now - Duration::from_secs(days_old * 24 * 60 * 60),
+        }
+    }
+
+    fn pruned_bases(
+        logs: &[WorkingLogInfo],
+        tips: &[&str],
+        policy: &RetentionPolicy,
+        now: SystemTime,
+    ) -> Vec<(String, PruneReason)> {
+        let tips = tips.iter().map(|tip| tip.to_string()).collect();
+        let mut pruned: Vec<_> = plan(logs, "head", &tips, policy, now)
+            .into_iter()
+            .map(|(index, reason)| (logs[index].base.clone(), reason))
+            .collect();
+        pruned.sort_by(|a, b| a.0.cmp(&b.0));
+        pruned
+    }
+
+    #[test]
+    fn test_plan_applies_age_count_and_protection() {
+        let now = SystemTime::now();
+        let logs = vec![
+            log("head", 0, 90, now),
+            log("tip", 3, 90, now),
+            log("empty", 0, 0, now),
+            log("stale", 2, 45, now),
+            log("a", 1, 1, now),
+            log("b", 1, 2, now),
+            log("c", 1, 3, now),
+        ];
+        let policy = RetentionPolicy {
+            max_age: Some(Duration::from_secs(30 * 24 * 60 * 60)),
+            max_count: Some(2),
+            keep_until_committed: true,
+        };
+
+        assert_eq!(
+            pruned_bases(&logs, &["tip"], &policy, now),
+            vec![
+                ("c".to_string(), PruneReason::OverLimit),
+                ("empty".to_string(), PruneReason::Empty),
+                ("stale".to_string(), PruneReason::Expired),
+            ]
+        );
+
+        // Without keep_until_committed a branch tip's log is just another log
+        let policy = RetentionPolicy {
+            keep_until_committed: false,
+            ..policy
+        };
+        assert!(
+            pruned_bases(&logs, &["tip"], &policy, now)
+                .contains(&("tip".to_string(), PruneReason::Expired))
+        );
+    }
+
+    #[test]
+    fn test_plan_without_limits_only_prunes_empty_logs() {
+        let now = SystemTime::now();
+        let mut carried = log("carried", 0, 400, now);
+        carried.has_initial = true;
+        let logs = vec![log("old", 5, 400, now), carried, log("empty", 0, 0, now)];
+        let policy = RetentionPolicy {
+            max_age: None,
+            max_count: None,
+            keep_until_committed: true,
+        };
+
+        assert_eq!(
+            pruned_bases(&logs, &[], &policy, now),
+            vec![("empty".to_string(), PruneReason::Empty)]
+        );
+    }
+}
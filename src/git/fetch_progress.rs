@@ -0,0 +1,145 @@
+//! Progress output for notes fetches.
+//!
+//! Fetching refs/notes/ai from a remote with hundreds of thousands of notes can take minutes.
+//! Commands a person waits on, such as `git-ai sync`, call [`show_progress`]. Their notes fetches then pass git's own progress through under a
+//! `git-ai:` heading when stderr is a terminal, and chunked blob fetches report how far along
+//! they are. Fetches that run alongside another git command, such as the wrapper's
+//! `git fetch`, stay silent so their output doesn't interleave with git's.
+//!
+//! `GIT_AI_PROGRESS=1` shows progress regardless, for CI logs, and `GIT_AI_PROGRESS=0` hides it.
+
+use crate::config::Config;
+use crate::error::GitAiError;
+use std::io::{IsTerminal, Read, Write};
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Environment variable forcing progress output on (`1`) or off (`0`)
+pub const PROGRESS_ENV: &str = "GIT_AI_PROGRESS";
+
+static SHOW_PROGRESS: AtomicBool = AtomicBool::new(false);
+
+/// Show progress for notes fetches made by this process, when stderr is a terminal
+pub fn show_progress() {
+    SHOW_PROGRESS.store(true, Ordering::Relaxed);
+}
+
+pub fn progress_enabled() -> bool {
+    match std::env::var(PROGRESS_ENV).as_deref() {
+        Ok("1") | Ok("true") => true,
+        Ok("0") | Ok("false") => false,
+        _ => SHOW_PROGRESS.load(Ordering::Relaxed) && std::io::stderr().is_terminal(),
+    }
+}
+
+/// Run git, copying its stderr to ours as it arrives. `heading` is printed before git's first
+/// output, so a fetch with nothing new prints nothing at all. The returned output still holds
+/// stderr, for callers that inspect git's error messages.
+pub fn exec_git_with_progress(args: &[String], heading: &str) -> Result<Output, GitAiError> {
+    let mut child = Command::new(Config::get().git_cmd())
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(GitAiError::IoError)?;
+
+    // Drain stdout on its own thread so a full pipe can't stall git
+    let mut stdout_pipe = child.stdout.take().expect("stdout is piped");
+    let stdout_reader = std::thread::spawn(move || {
+        let mut stdout = Vec::new();
+        let _ = stdout_pipe.read_to_end(&mut stdout);
+        stdout
+    });
+
+    let mut stderr_pipe = child.stderr.take().expect("stderr is piped");
+    let mut stderr = Vec::new();
+    let mut buffer = [0u8; 4096];
+    let mut terminal = std::io::stderr();
+    loop {
+        let read = stderr_pipe.read(&mut buffer).map_err(GitAiError::IoError)?;
+        if read == 0 {
+            break;
+        }
+        if stderr.is_empty() {
+            let _ = writeln!(terminal, "git-ai: {}", heading);
+        }
+        stderr.extend_from_slice(&buffer[..read]);
+        let _ = terminal.write_all(&buffer[..read]);
+        let _ = terminal.flush();
+    }
+
+    let status = child.wait().map_err(GitAiError::IoError)?;
+    let stdout = stdout_reader.join().unwrap_or_default();
+    if !status.success() {
+        return Err(GitAiError::GitCliError {
+            code: status.code(),
+            stderr: String::from_utf8_lossy(&stderr).to_string(),
+            args: args.to_vec(),
+        });
+    }
+    Ok(Output {
+        status,
+        stdout,
+        stderr,
+    })
+}
+
+/// A count of items fetched in chunks, redrawn on one stderr line
+pub struct ChunkProgress {
+    label: String,
+    total: usize,
+    done: usize,
+    enabled: bool,
+}
+
+impl ChunkProgress {
+    /// Progress over `total` items split into `chunks` requests. A single chunk finishes as
+    /// quickly as git's own progress would appear, so it isn't reported.
+    pub fn new(label: &str, total: usize, chunks: usize) -> Self {
+        ChunkProgress {
+            label: label.to_string(),
+            total,
+            done: 0,
+            enabled: chunks > 1 && progress_enabled(),
+        }
+    }
+
+    pub fn advance(&mut self, count: usize) {
+        self.done = (self.done + count).min(self.total);
+        if self.enabled {
+            eprint!("\r{}", self.line());
+            if self.done == self.total {
+                eprintln!();
+            }
+        }
+    }
+
+    fn line(&self) -> String {
+        let percent = (self.done * 100).checked_div(self.total).unwrap_or(100);
+        format!(
+            "git-ai: {}: {}% ({}/{})",
+            self.label, percent, self.done, self.total
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_progress_line() {
+        let mut progress = ChunkProgress::new("fetching note blobs", 12_000, 3);
+        progress.enabled = false;
+        progress.advance(5_000);
+        assert_eq!(
+            progress.line(),
+            "git-ai: fetching note blobs: 41% (5000/12000)"
+        );
+        progress.advance(10_000);
+        assert_eq!(
+            progress.line(),
+            "git-ai: fetching note blobs: 100% (12000/12000)"
+        );
+    }
+}
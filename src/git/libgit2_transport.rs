@@ -0,0 +1,166 @@
+//! In-process fetch and push of notes refs through libgit2 (the `libgit2` feature).
+//!
+//! Notes sync normally shells out to `git fetch` and `git push`, which fails in minimal CI
+//! containers without a `git` binary and misbehaves with very old ones. With the feature
+//! enabled and `notes_transport` set to `auto` or `libgit2`, `sync_authorship` moves
+//! refs/notes/ai through these functions instead and falls back to the CLI if they fail.
+//!
+//! Credentials are tried in order: the SSH agent for SSH remotes, then for HTTPS an explicit
+//! token (`GIT_AI_GIT_TOKEN`, or `GITHUB_TOKEN`/`GH_TOKEN` for github.com), then git's
+//! configured credential helper. Each is offered once, so a rejected credential ends the
+//! attempt instead of looping.
+
+use crate::error::GitAiError;
+use crate::git::repository::Repository;
+use crate::git::sync_authorship::NotesExistence;
+use crate::utils::debug_log;
+use git2::{Cred, CredentialType, ErrorCode, FetchOptions, PushOptions, RemoteCallbacks};
+use std::cell::RefCell;
+
+/// Env var holding an HTTPS token for any host
+pub const GIT_TOKEN_ENV: &str = "GIT_AI_GIT_TOKEN";
+/// Username sent with a token; GitHub and most hosts accept any non-empty name
+const TOKEN_USERNAME: &str = "x-access-token";
+
+fn open(repository: &Repository) -> Result<git2::Repository, GitAiError> {
+    git2::Repository::open(repository.path()).map_err(libgit2_error)
+}
+
+fn libgit2_error(e: git2::Error) -> GitAiError {
+    GitAiError::Generic(format!("libgit2: {}", e.message()))
+}
+
+/// Fetch `remote_name`'s `source_ref` into `tracking_ref`, force-updating it. Returns NotFound
+/// when the remote doesn't have `source_ref`.
+pub fn fetch_ref(
+    repository: &Repository,
+    remote_name: &str,
+    source_ref: &str,
+    tracking_ref: &str,
+) -> Result<NotesExistence, GitAiError> {
+    let repo = open(repository)?;
+    let mut remote = repo.find_remote(remote_name).map_err(libgit2_error)?;
+    let config = repo.config().map_err(libgit2_error)?;
+
+    // libgit2 skips refspecs with no matching remote ref instead of failing like `git fetch`,
+    // so the tracking ref is cleared first and only exists afterwards if the remote had it.
+    // (Listing the remote's refs would panic on an empty remote in git2 0.20.)
+    if let Ok(mut stale) = repo.find_reference(tracking_ref) {
+        stale.delete().map_err(libgit2_error)?;
+    }
+
+    let mut options = FetchOptions::new();
+    options.remote_callbacks(callbacks(&config));
+    options.download_tags(git2::AutotagOption::None);
+    options.update_fetchhead(false);
+    let refspec = format!("+{}:{}", source_ref, tracking_ref);
+    remote
+        .fetch(&[refspec.as_str()], Some(&mut options), None)
+        .map_err(libgit2_error)?;
+
+    if repo.find_reference(tracking_ref).is_err() {
+        return Ok(NotesExistence::NotFound);
+    }
+    debug_log(&format!(
+        "libgit2: fetched {} from '{}' into {}",
+        source_ref, remote_name, tracking_ref
+    ));
+    Ok(NotesExistence::Found)
+}
+
+/// Push `refspec` (without force) to `remote_name`. A rejection because the remote ref moved
+/// reports "non-fast-forward", like git, so callers can re-merge and retry.
+pub fn push_ref(
+    repository: &Repository,
+    remote_name: &str,
+    refspec: &str,
+) -> Result<(), GitAiError> {
+    let repo = open(repository)?;
+    let mut remote = repo.find_remote(remote_name).map_err(libgit2_error)?;
+    let config = repo.config().map_err(libgit2_error)?;
+
+    let rejection: RefCell<Option<String>> = RefCell::new(None);
+    let mut callbacks = callbacks(&config);
+    callbacks.push_update_reference(|refname, status| {
+        if let Some(status) = status {
+            *rejection.borrow_mut() = Some(format!("{} rejected: {}", refname, status));
+        }
+        Ok(())
+    });
+    let mut options = PushOptions::new();
+    options.remote_callbacks(callbacks);
+
+    let pushed = remote.push(&[refspec], Some(&mut options));
+    drop(options);
+    match pushed {
+        Ok(()) => {}
+        Err(e) if e.code() == ErrorCode::NotFastForward => {
+            return Err(GitAiError::Generic(format!(
+                "libgit2: {} rejected (non-fast-forward)",
+                refspec
+            )));
+        }
+        Err(e) => return Err(libgit2_error(e)),
+    }
+    match rejection.into_inner() {
+        Some(message) => Err(GitAiError::Generic(format!("libgit2: {}", message))),
+        None => {
+            debug_log(&format!("libgit2: pushed {} to '{}'", refspec, remote_name));
+            Ok(())
+        }
+    }
+}
+
+/// Credential callbacks that offer each source once
+fn callbacks(config: &git2::Config) -> RemoteCallbacks<'_> {
+    let mut tried_agent = false;
+    let mut tried_token = false;
+    let mut tried_helper = false;
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.credentials(move |url, url_username, allowed| {
+        let username = url_username.unwrap_or("git");
+        if allowed.contains(CredentialType::USERNAME) {
+            return Cred::username(username);
+        }
+        if allowed.contains(CredentialType::SSH_KEY) && !tried_agent {
+            tried_agent = true;
+            return Cred::ssh_key_from_agent(username);
+        }
+        if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
+            if !tried_token {
+                tried_token = true;
+                if let Some(token) = https_token(url) {
+                    return Cred::userpass_plaintext(TOKEN_USERNAME, &token);
+                }
+            }
+            if !tried_helper {
+                tried_helper = true;
+                if let Ok(cred) = Cred::credential_helper(config, url, url_username) {
+                    return Ok(cred);
+                }
+            }
+        }
+        if allowed.contains(CredentialType::DEFAULT) {
+            return Cred::default();
+        }
+        Err(git2::Error::from_str(&format!(
+            "no credentials accepted for {}",
+            url
+        )))
+    });
+    callbacks
+}
+
+/// A token for `url`: `GIT_AI_GIT_TOKEN` for any host, the GitHub Actions token only for
+/// github.com so it's never sent elsewhere
+fn https_token(url: &str) -> Option<String> {
+    let non_empty = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+    non_empty(GIT_TOKEN_ENV).or_else(|| {
+        let host = url::Url::parse(url).ok()?.host_str()?.to_lowercase();
+        if host == "github.com" {
+            non_empty("GITHUB_TOKEN").or_else(|| non_empty("GH_TOKEN"))
+        } else {
+            None
+        }
+    })
+}
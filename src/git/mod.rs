@@ -1,5 +1,13 @@
+pub mod checkpoint_gc;
 pub mod cli_parser;
 pub mod diff_tree_to_tree;
+pub mod fetch_progress;
+#[cfg(feature = "libgit2")]
+pub mod libgit2_transport;
+pub mod note_signing;
+pub mod notes_gc;
+pub mod notes_merge;
+pub mod pending_pushes;
 pub mod refs;
 pub mod repository;
 
@@ -10,10 +18,12 @@ pub use repository::{
     find_repository, find_repository_for_file, find_repository_in_path, from_bare_repository,
     group_files_by_repository,
 };
+pub mod repo_lock;
 pub mod repo_storage;
 pub mod rewrite_log;
 pub mod status;
 pub mod sync_authorship;
+pub mod workspace;
 
-#[cfg(feature = "test-support")]
+#[cfg(any(test, feature = "test-support"))]
 pub mod test_utils;
@@ -0,0 +1,485 @@
+//! Optional signatures for authorship notes.
+//!
+//! With `git config git-ai.signNotes true`, every note written to refs/notes/ai is signed with
+//! the same key and settings git uses for signed commits (`user.signingkey`, `gpg.format`,
+//! `gpg.program`). The signature is an ordinary signed commit object, created with
+//! `git commit-tree -S`, whose message names the annotated commit and the note's blob ID. Its
+//! raw text is stored as a note in refs/notes/ai-signatures, which is pushed and fetched next to
+//! refs/notes/ai. Verification writes the object back and asks git to check it, so GPG, SSH and
+//! X.509 keys all work the way `git verify-commit` does.
+
+use crate::authorship::staged::EMPTY_TREE_HASH;
+use crate::config::Config;
+use crate::error::GitAiError;
+use crate::git::authorship_traversal::batch_read_blobs;
+use crate::git::refs::{AI_AUTHORSHIP_REFNAME, ref_exists};
+use crate::git::repository::{Repository, exec_git, exec_git_stdin};
+use crate::utils::debug_log;
+use std::collections::{HashMap, HashSet};
+
+pub const SIGNATURES_REFNAME: &str = "ai-signatures";
+pub const SIGNATURES_PUSH_REFSPEC: &str = "refs/notes/ai-signatures:refs/notes/ai-signatures";
+/// Git config key that turns on note signing (`git config git-ai.signNotes true`). Without it,
+/// `sign_notes` in git-ai.toml applies; likewise `verify_notes` for the key below.
+pub const SIGN_NOTES_CONFIG_KEY: &str = "git-ai.signNotes";
+/// Git config key that checks fetched notes: `true` warns about bad signatures, `strict`
+/// also refuses to merge the remote's notes
+pub const VERIFY_NOTES_CONFIG_KEY: &str = "git-ai.verifyNotes";
+
+const PAYLOAD_HEADER: &str = "git-ai authorship note signature";
+
+/// Outcome of checking one note against refs/notes/ai-signatures
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureStatus {
+    /// Signed by a key git trusts; `signer` is the key's identity as git reports it
+    Valid { signer: String },
+    /// No signature was recorded for the note
+    Unsigned,
+    /// The signature covers different note content, so the note changed after it was signed
+    Mismatch,
+    /// The signature is bad, revoked, or not a signature at all
+    Invalid(String),
+    /// The signature couldn't be checked here, e.g. the public key isn't available
+    Unverifiable(String),
+}
+
+/// How fetched notes are checked, from `git-ai.verifyNotes`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FetchVerification {
+    Off,
+    Warn,
+    Strict,
+}
+
+impl FetchVerification {
+    pub fn for_repository(repo: &Repository) -> Self {
+        let value = config_value(repo, VERIFY_NOTES_CONFIG_KEY)
+            .or_else(|| Config::get().verify_notes().map(str::to_string));
+        match value.as_deref() {
+            Some("strict") => FetchVerification::Strict,
+            Some(value) if is_true(value) || value == "warn" => FetchVerification::Warn,
+            _ => FetchVerification::Off,
+        }
+    }
+}
+
+pub fn signing_enabled(repo: &Repository) -> bool {
+    config_value(repo, SIGN_NOTES_CONFIG_KEY)
+        .map(|value| is_true(&value))
+        .or_else(|| Config::get().sign_notes())
+        .unwrap_or(false)
+}
+
+/// Whether signatures are worth syncing: this clone signs or verifies notes, or has some
+pub fn signatures_in_use(repo: &Repository) -> bool {
+    signing_enabled(repo)
+        || FetchVerification::for_repository(repo) != FetchVerification::Off
+        || ref_exists(repo, &format!("refs/notes/{}", SIGNATURES_REFNAME))
+}
+
+fn config_value(repo: &Repository, key: &str) -> Option<String> {
+    repo.config_get_str(key)
+        .ok()
+        .flatten()
+        .map(|value| value.trim().to_ascii_lowercase())
+}
+
+fn is_true(value: &str) -> bool {
+    matches!(value, "true" | "yes" | "on" | "1")
+}
+
+/// The message a signature commit carries for the note blob `note_blob` on `commit_sha`
+fn signature_payload(commit_sha: &str, note_blob: &str) -> String {
+    format!(
+        "{}\n\ncommit {}\nnote {}\n",
+        PAYLOAD_HEADER, commit_sha, note_blob
+    )
+}
+
+/// Sign the note refs/notes/ai currently holds for `commit_sha`
+pub fn sign_note(repo: &Repository, commit_sha: &str) -> Result<(), GitAiError> {
+    let Some(note_blob) = note_blob_for(repo, AI_AUTHORSHIP_REFNAME, commit_sha)? else {
+        return Err(GitAiError::Generic(format!(
+            "commit {} has no authorship note to sign",
+            commit_sha
+        )));
+    };
+
+    let mut args = repo.global_args_for_exec();
+    args.push("commit-tree".to_string());
+    args.push("-S".to_string());
+    args.push(EMPTY_TREE_HASH.to_string());
+    args.push("-m".to_string());
+    args.push(signature_payload(commit_sha, &note_blob));
+    let output = exec_git(&args)?;
+    let signature_oid = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    // The signature commit itself isn't reachable from anything, so copy its raw text into a
+    // blob that the notes tree keeps. `-C` stores the blob as is; `-F` would strip the blank
+    // continuation lines GPG armor relies on.
+    let mut args = repo.global_args_for_exec();
+    args.push("cat-file".to_string());
+    args.push("commit".to_string());
+    args.push(signature_oid);
+    let raw = exec_git(&args)?.stdout;
+
+    let mut args = repo.global_args_for_exec();
+    args.push("hash-object".to_string());
+    args.push("-w".to_string());
+    args.push("--stdin".to_string());
+    let output = exec_git_stdin(&args, &raw)?;
+    let blob = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    let mut args = repo.global_args_for_exec();
+    args.push("notes".to_string());
+    args.push(format!("--ref={}", SIGNATURES_REFNAME));
+    args.push("add".to_string());
+    args.push("-f".to_string());
+    args.push("-C".to_string());
+    args.push(blob);
+    args.push(commit_sha.to_string());
+    exec_git(&args)?;
+    Ok(())
+}
+
+/// Blob ID of the note `notes_ref` holds for `commit_sha`
+fn note_blob_for(
+    repo: &Repository,
+    notes_ref: &str,
+    commit_sha: &str,
+) -> Result<Option<String>, GitAiError> {
+    let mut args = repo.global_args_for_exec();
+    args.push("notes".to_string());
+    args.push(format!("--ref={}", notes_ref));
+    args.push("list".to_string());
+    args.push(commit_sha.to_string());
+    match exec_git(&args) {
+        Ok(output) => Ok(Some(
+            String::from_utf8_lossy(&output.stdout).trim().to_string(),
+        )),
+        Err(GitAiError::GitCliError { code: Some(1), .. }) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Every note in `notes_rev` (a notes ref or commit) as (note_blob_sha, commit_sha) pairs;
+/// empty if it doesn't exist. Reads the notes tree directly, so it works on tracking refs and
+/// on old tips that no ref points at anymore.
+pub fn list_notes(repo: &Repository, notes_rev: &str) -> Result<Vec<(String, String)>, GitAiError> {
+    let mut args = repo.global_args_for_exec();
+    args.push("ls-tree".to_string());
+    args.push("-r".to_string());
+    args.push(notes_rev.to_string());
+    let output = match exec_git(&args) {
+        Ok(output) => output,
+        Err(GitAiError::GitCliError { .. }) => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            // "<mode> blob <oid>\t<fanout path>", e.g. "100644 blob 1a2b...\tab/cdef..."
+            let (info, path) = line.split_once('\t')?;
+            let blob = info.split_whitespace().nth(2)?;
+            let commit: String = path.chars().filter(|c| *c != '/').collect();
+            (!commit.is_empty() && commit.chars().all(|c| c.is_ascii_hexdigit()))
+                .then(|| (blob.to_string(), commit))
+        })
+        .collect())
+}
+
+/// Merge a remote's signatures into refs/notes/ai-signatures. Where both sides signed the same
+/// commit, the local signature is kept: it was made for the local note.
+pub fn merge_signatures_from_ref(repo: &Repository, source_ref: &str) -> Result<(), GitAiError> {
+    let mut args = repo.global_args_for_exec();
+    args.push("notes".to_string());
+    args.push(format!("--ref={}", SIGNATURES_REFNAME));
+    args.push("merge".to_string());
+    args.push("-s".to_string());
+    args.push("ours".to_string());
+    args.push("--quiet".to_string());
+    args.push(source_ref.to_string());
+    exec_git(&args)?;
+    Ok(())
+}
+
+/// Check `notes`, (note_blob_sha, commit_sha) pairs as [`list_notes`] returns them, against the
+/// signatures in `signatures_ref`. Returns a status for every commit.
+pub fn verify_notes(
+    repo: &Repository,
+    signatures_ref: &str,
+    notes: &[(String, String)],
+) -> Result<HashMap<String, SignatureStatus>, GitAiError> {
+    let signature_blobs: HashMap<String, String> = list_notes(repo, signatures_ref)?
+        .into_iter()
+        .map(|(blob, commit)| (commit, blob))
+        .collect();
+
+    let mut statuses = HashMap::new();
+    let signed: Vec<&(String, String)> = notes
+        .iter()
+        .filter(|(_, commit)| {
+            let found = signature_blobs.contains_key(commit);
+            if !found {
+                statuses.insert(commit.clone(), SignatureStatus::Unsigned);
+            }
+            found
+        })
+        .collect();
+    let blobs: Vec<String> = signed
+        .iter()
+        .map(|(_, commit)| signature_blobs[commit].clone())
+        .collect();
+    let contents = batch_read_blobs(&repo.global_args_for_exec(), &blobs)?;
+    if contents.len() != blobs.len() {
+        return Err(GitAiError::Generic(format!(
+            "could not read all signatures from {}",
+            signatures_ref
+        )));
+    }
+
+    // Signatures whose payload matches; git checks these in one `git log` call
+    let mut to_check: HashMap<String, String> = HashMap::new();
+    for ((note_blob, commit), raw) in signed.into_iter().zip(contents) {
+        let status = match check_payload(&raw, commit, note_blob) {
+            Err(status) => status,
+            Ok(()) => {
+                let mut args = repo.global_args_for_exec();
+                args.push("hash-object".to_string());
+                args.push("-t".to_string());
+                args.push("commit".to_string());
+                args.push("-w".to_string());
+                args.push("--stdin".to_string());
+                match exec_git_stdin(&args, raw.as_bytes()) {
+                    Ok(output) => {
+                        let oid = String::from_utf8_lossy(&output.stdout).trim().to_string();
+                        to_check.insert(oid, commit.clone());
+                        continue;
+                    }
+                    Err(e) => SignatureStatus::Invalid(format!("not a signature object: {}", e)),
+                }
+            }
+        };
+        statuses.insert(commit.clone(), status);
+    }
+
+    if !to_check.is_empty() {
+        let mut args = repo.global_args_for_exec();
+        args.push("log".to_string());
+        args.push("--no-walk=unsorted".to_string());
+        args.push("--format=%H %G? %GS".to_string());
+        args.extend(to_check.keys().cloned());
+        let output = exec_git(&args)?;
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            let mut parts = line.splitn(3, ' ');
+            let (Some(oid), Some(code)) = (parts.next(), parts.next()) else {
+                continue;
+            };
+            if let Some(commit) = to_check.get(oid) {
+                let signer = parts.next().unwrap_or("").trim();
+                statuses.insert(commit.clone(), status_from_git(code, signer));
+            }
+        }
+    }
+    Ok(statuses)
+}
+
+/// Most failures printed for one fetch; the rest are only counted
+const MAX_FETCH_WARNINGS: usize = 10;
+
+/// Check the notes in `tracking_ref` that refs/notes/ai doesn't already have against the
+/// remote's signatures in `signatures_ref`, printing a warning for each note that doesn't
+/// verify. Returns false if any failed and `mode` is [`FetchVerification::Strict`].
+pub fn verify_fetched_notes(
+    repo: &Repository,
+    remote_name: &str,
+    tracking_ref: &str,
+    signatures_ref: &str,
+    mode: FetchVerification,
+) -> bool {
+    if mode == FetchVerification::Off {
+        return true;
+    }
+    let result = (|| {
+        let local_ref = format!("refs/notes/{}", AI_AUTHORSHIP_REFNAME);
+        let known: HashSet<(String, String)> = list_notes(repo, &local_ref)?.into_iter().collect();
+        let fetched: Vec<(String, String)> = list_notes(repo, tracking_ref)?
+            .into_iter()
+            .filter(|note| !known.contains(note))
+            .collect();
+        verify_notes(repo, signatures_ref, &fetched)
+    })();
+    let statuses = match result {
+        Ok(statuses) => statuses,
+        Err(e) => {
+            eprintln!(
+                "git-ai: warning: could not verify authorship notes from {}: {}",
+                remote_name, e
+            );
+            return mode != FetchVerification::Strict;
+        }
+    };
+
+    let mut failures: Vec<(&String, &SignatureStatus)> = statuses
+        .iter()
+        .filter(|(_, status)| !matches!(status, SignatureStatus::Valid { .. }))
+        .collect();
+    failures.sort_by_key(|(commit, _)| *commit);
+    for (commit, status) in failures.iter().take(MAX_FETCH_WARNINGS) {
+        eprintln!(
+            "git-ai: warning: authorship note for {} from {}: {}",
+            &commit[..commit.len().min(8)],
+            remote_name,
+            status.describe()
+        );
+    }
+    if failures.len() > MAX_FETCH_WARNINGS {
+        eprintln!(
+            "git-ai: warning: {} more authorship notes from {} failed verification",
+            failures.len() - MAX_FETCH_WARNINGS,
+            remote_name
+        );
+    }
+    debug_log(&format!(
+        "verified {} fetched notes from {}: {} failed",
+        statuses.len(),
+        remote_name,
+        failures.len()
+    ));
+
+    if failures.is_empty() || mode != FetchVerification::Strict {
+        return true;
+    }
+    eprintln!(
+        "git-ai: not merging authorship notes from {} (git-ai.verifyNotes is strict)",
+        remote_name
+    );
+    false
+}
+
+/// Check that a raw signature object signs `note_blob` on `commit_sha`
+fn check_payload(raw: &str, commit_sha: &str, note_blob: &str) -> Result<(), SignatureStatus> {
+    let Some((headers, message)) = raw.split_once("\n\n") else {
+        return Err(SignatureStatus::Invalid(
+            "not a signature object".to_string(),
+        ));
+    };
+    if !message.starts_with(PAYLOAD_HEADER) {
+        return Err(SignatureStatus::Invalid(
+            "not a signature object".to_string(),
+        ));
+    }
+    if !headers.lines().any(|line| line.starts_with("gpgsig")) {
+        return Err(SignatureStatus::Invalid(
+            "the signature object carries no signature".to_string(),
+        ));
+    }
+    if message != signature_payload(commit_sha, note_blob) {
+        return Err(SignatureStatus::Mismatch);
+    }
+    Ok(())
+}
+
+/// Map git's `%G?` code for a signed object to a status
+fn status_from_git(code: &str, signer: &str) -> SignatureStatus {
+    match code {
+        "G" => SignatureStatus::Valid {
+            signer: signer.to_string(),
+        },
+        // For SSH this is any key missing from gpg.ssh.allowedSignersFile, so anyone could
+        // produce one with a key of their own
+        "U" => SignatureStatus::Invalid(format!(
+            "signed by an untrusted key{}",
+            if signer.is_empty() {
+                String::new()
+            } else {
+                format!(" ({})", signer)
+            }
+        )),
+        // Notes carry no trusted timestamp, so there's no telling whether the signature was
+        // made while the key was still valid
+        "X" => SignatureStatus::Unverifiable("the signature has expired".to_string()),
+        "Y" => SignatureStatus::Unverifiable("signed with an expired key".to_string()),
+        "B" => SignatureStatus::Invalid("bad signature".to_string()),
+        "R" => SignatureStatus::Invalid("signed with a revoked key".to_string()),
+        "E" => SignatureStatus::Unverifiable("the signing key isn't available".to_string()),
+        // The object is signed (checked above), so git couldn't check it at all, most often
+        // because gpg.ssh.allowedSignersFile isn't set for SSH signatures
+        _ => SignatureStatus::Unverifiable(
+            "git couldn't check the signature (for SSH keys, set gpg.ssh.allowedSignersFile)"
+                .to_string(),
+        ),
+    }
+}
+
+impl SignatureStatus {
+    pub fn describe(&self) -> String {
+        match self {
+            SignatureStatus::Valid { signer } if signer.is_empty() => "good signature".to_string(),
+            SignatureStatus::Valid { signer } => format!("good signature from {}", signer),
+            SignatureStatus::Unsigned => "not signed".to_string(),
+            SignatureStatus::Mismatch => "changed after it was signed".to_string(),
+            SignatureStatus::Invalid(reason) | SignatureStatus::Unverifiable(reason) => {
+                reason.clone()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIGNED: &str = "tree 4b825dc642cb6eb9a060e54bf8d69288fbee4904\n\
+        author T <t@example.com> 1700000000 +0000\n\
+        committer T <t@example.com> 1700000000 +0000\n\
+        gpgsig -----BEGIN SSH SIGNATURE-----\n \
+        U1NIU0lHAAAAAQ==\n \
+        -----END SSH SIGNATURE-----\n\n";
+
+    #[test]
+    fn test_check_payload() {
+        let raw = format!("{}{}", SIGNED, signature_payload("abc", "def"));
+        assert_eq!(check_payload(&raw, "abc", "def"), Ok(()));
+        assert_eq!(
+            check_payload(&raw, "abc", "other"),
+            Err(SignatureStatus::Mismatch)
+        );
+        assert!(matches!(
+            check_payload("just some text", "abc", "def"),
+            Err(SignatureStatus::Invalid(_))
+        ));
+
+        let unsigned = raw.replace("gpgsig", "x-sig");
+        assert!(matches!(
+            check_payload(&unsigned, "abc", "def"),
+            Err(SignatureStatus::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_status_from_git() {
+        assert_eq!(
+            status_from_git("G", "dev@example.com"),
+            SignatureStatus::Valid {
+                signer: "dev@example.com".to_string()
+            }
+        );
+        assert!(matches!(
+            status_from_git("B", ""),
+            SignatureStatus::Invalid(_)
+        ));
+        assert!(matches!(
+            status_from_git("U", "someone@example.com"),
+            SignatureStatus::Invalid(_)
+        ));
+        assert!(matches!(
+            status_from_git("Y", ""),
+            SignatureStatus::Unverifiable(_)
+        ));
+        assert!(matches!(
+            status_from_git("N", ""),
+            SignatureStatus::Unverifiable(_)
+        ));
+    }
+}
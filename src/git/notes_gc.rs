@@ -0,0 +1,251 @@
+//! Pruning authorship notes whose commits are no longer reachable.
+//!
+//! Rebases, amends and deleted branches leave the old commits' notes behind in `refs/notes/ai`,
+//! which every clone that syncs notes then downloads. `git-ai gc --notes` removes the notes of
+//! commits that no ref reaches, or moves them to `refs/notes/ai-archive`.
+//!
+//! A commit that only a reflog entry reaches is kept until the entry is older than the grace
+//! period, so work that was just rebased away can still be recovered with its attribution.
+//!
+//! Notes of commits this clone doesn't have are always kept. A single-branch or partially
+//! fetched clone can't tell whether they are reachable on the remote, and the notes merge
+//! would push their removal to everyone else.
+
+use crate::config::Config;
+use crate::error::GitAiError;
+use crate::git::authorship_traversal::get_notes_list;
+use crate::git::note_signing::SIGNATURES_REFNAME;
+use crate::git::refs::{AI_AUTHORSHIP_REFNAME, ref_exists};
+use crate::git::repo_lock;
+use crate::git::repository::{Repository, exec_git, exec_git_stdin};
+use std::collections::HashSet;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Notes ref that `--archive` moves pruned notes to. It is never pushed.
+pub const ARCHIVE_REFNAME: &str = "ai-archive";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotesGcPolicy {
+    /// Commits reached by a reflog entry younger than this are kept. None ignores reflogs.
+    pub grace: Option<Duration>,
+    /// Copy pruned notes to `refs/notes/ai-archive` before removing them
+    pub archive: bool,
+}
+
+impl NotesGcPolicy {
+    pub fn from_config() -> Self {
+        NotesGcPolicy {
+            grace: Config::get()
+                .notes_gc_grace_days()
+                .map(|days| Duration::from_secs(days * 24 * 60 * 60)),
+            archive: false,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct NotesGcReport {
+    /// Commits whose notes were pruned, or would be with `dry_run`
+    pub pruned: Vec<String>,
+    pub kept: usize,
+}
+
+/// Prune the notes of unreachable commits under `policy`. With `dry_run` nothing is changed,
+/// but the report lists what would have been.
+pub fn prune_stale_notes(
+    repository: &Repository,
+    policy: &NotesGcPolicy,
+    dry_run: bool,
+) -> Result<NotesGcReport, GitAiError> {
+    // Everything past the shallow boundary would look unreachable
+    if repository.is_shallow() {
+        return Err(GitAiError::Generic(
+            "Refusing to prune notes in a shallow clone; fetch full history first".to_string(),
+        ));
+    }
+    let notes = get_notes_list(&repository.global_args_for_exec())?;
+    if notes.is_empty() {
+        return Ok(NotesGcReport::default());
+    }
+
+    let roots = match policy.grace {
+        Some(grace) => fresh_reflog_commits(&reflog_entries(repository)?, grace, SystemTime::now()),
+        None => Vec::new(),
+    };
+    let live = reachable_commits(repository, &roots)?;
+    let unreachable: Vec<&(String, String)> = notes
+        .iter()
+        .filter(|(_, commit)| !live.contains(commit))
+        .collect();
+    let present = local_commits(
+        repository,
+        unreachable.iter().map(|(_, commit)| commit.as_str()),
+    )?;
+    let stale: Vec<(String, String)> = unreachable
+        .into_iter()
+        .filter(|(_, commit)| present.contains(commit))
+        .cloned()
+        .collect();
+
+    let report = NotesGcReport {
+        pruned: stale.iter().map(|(_, commit)| commit.clone()).collect(),
+        kept: notes.len() - stale.len(),
+    };
+    if dry_run || stale.is_empty() {
+        return Ok(report);
+    }
+
+    let _lock = repo_lock::lock(&repository.storage.lock)?;
+    if policy.archive {
+        for (blob, commit) in &stale {
+            let mut args = repository.global_args_for_exec();
+            args.extend(
+                [
+                    "notes",
+                    &format!("--ref={}", ARCHIVE_REFNAME),
+                    "add",
+                    "-f",
+                    "-C",
+                    blob,
+                    commit,
+                ]
+                .iter()
+                .map(|arg| arg.to_string()),
+            );
+            exec_git(&args)?;
+        }
+    }
+    let commits = report.pruned.join("\n") + "\n";
+    remove_notes(repository, AI_AUTHORSHIP_REFNAME, &commits)?;
+    // A signature is worthless without its note
+    if ref_exists(repository, &format!("refs/notes/{}", SIGNATURES_REFNAME)) {
+        remove_notes(repository, SIGNATURES_REFNAME, &commits)?;
+    }
+    Ok(report)
+}
+
+/// Remove the notes of newline-separated `commits` from `refs/notes/<notes_ref>` in one commit
+fn remove_notes(repository: &Repository, notes_ref: &str, commits: &str) -> Result<(), GitAiError> {
+    let mut args = repository.global_args_for_exec();
+    args.push("notes".to_string());
+    args.push(format!("--ref={}", notes_ref));
+    args.push("remove".to_string());
+    args.push("--ignore-missing".to_string());
+    args.push("--stdin".to_string());
+    exec_git_stdin(&args, commits.as_bytes())?;
+    Ok(())
+}
+
+/// `(commit, ref, unix time)` for every reflog entry, newest first
+fn reflog_entries(repository: &Repository) -> Result<Vec<(String, String, u64)>, GitAiError> {
+    let output = repository.git(&["log", "-g", "--all", "--format=%H %gD", "--date=unix"])?;
+    Ok(output.lines().filter_map(parse_reflog_line).collect())
+}
+
+/// `<sha> <ref>@{<unix time>}`, as printed by `git log -g --format='%H %gD' --date=unix`
+fn parse_reflog_line(line: &str) -> Option<(String, String, u64)> {
+    let (commit, selector) = line.trim().split_once(' ')?;
+    let (refname, time) = selector.rsplit_once("@{")?;
+    let time = time.strip_suffix('}')?.parse().ok()?;
+    Some((commit.to_string(), refname.to_string(), time))
+}
+
+/// Commits of reflog entries younger than `grace`. Notes refs' own reflogs point at notes
+/// commits, not annotated ones, so they are skipped.
+fn fresh_reflog_commits(
+    entries: &[(String, String, u64)],
+    grace: Duration,
+    now: SystemTime,
+) -> Vec<String> {
+    let cutoff = now
+        .checked_sub(grace)
+        .and_then(|cutoff| cutoff.duration_since(UNIX_EPOCH).ok())
+        .map(|cutoff| cutoff.as_secs())
+        .unwrap_or(0);
+    let mut seen = HashSet::new();
+    entries
+        .iter()
+        .filter(|(_, refname, time)| !refname.starts_with("refs/notes/") && *time >= cutoff)
+        .filter(|(commit, _, _)| seen.insert(commit.clone()))
+        .map(|(commit, _, _)| commit.clone())
+        .collect()
+}
+
+/// The subset of `commits` whose commit objects exist in this repository
+fn local_commits<'a>(
+    repository: &Repository,
+    commits: impl Iterator<Item = &'a str>,
+) -> Result<HashSet<String>, GitAiError> {
+    let input: String = commits
+        .map(|commit| format!("{}^{{commit}}\n", commit))
+        .collect();
+    if input.is_empty() {
+        return Ok(HashSet::new());
+    }
+    let mut args = repository.global_args_for_exec();
+    args.push("cat-file".to_string());
+    args.push("--batch-check=%(objecttype) %(objectname)".to_string());
+    let output = exec_git_stdin(&args, input.as_bytes())?;
+    // Missing objects print `<input> missing`
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.strip_prefix("commit "))
+        .map(|commit| commit.trim().to_string())
+        .collect())
+}
+
+/// Every commit reachable from a ref other than a notes ref, or from `roots`. This includes
+/// remote-tracking refs, so commits other clones pushed are never pruned.
+fn reachable_commits(
+    repository: &Repository,
+    roots: &[String],
+) -> Result<HashSet<String>, GitAiError> {
+    let mut args = repository.global_args_for_exec();
+    args.push("rev-list".to_string());
+    args.push("--exclude=refs/notes/*".to_string());
+    args.push("--all".to_string());
+    // Reflogs can outlive their objects
+    args.push("--ignore-missing".to_string());
+    args.push("--stdin".to_string());
+    let input = roots.join("\n") + "\n";
+    let output = exec_git_stdin(&args, input.as_bytes())?;
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| line.trim().to_string())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fresh_reflog_commits_respects_grace_and_skips_notes_refs() {
+        let now = UNIX_EPOCH + Duration::from_secs(100 * 24 * 60 * 60);
+        let day = |d: u64| d * 24 * 60 * 60;
+        let lines = [
+            format!("aaa HEAD@{{{}}}", day(99)),
+            format!("bbb refs/heads/topic@{{{}}}", day(80)),
+            format!("aaa refs/heads/main@{{{}}}", day(95)),
+            format!("ccc refs/notes/ai@{{{}}}", day(99)),
+            "not a reflog line".to_string(),
+        ];
+        let entries: Vec<_> = lines.iter().filter_map(|l| parse_reflog_line(l)).collect();
+        assert_eq!(entries.len(), 4);
+        assert_eq!(
+            entries[1],
+            ("bbb".to_string(), "refs/heads/topic".to_string(), day(80))
+        );
+
+        let grace = Duration::from_secs(day(30));
+        assert_eq!(
+            fresh_reflog_commits(&entries, grace, now),
+            vec!["aaa".to_string(), "bbb".to_string()]
+        );
+        let grace = Duration::from_secs(day(10));
+        assert_eq!(
+            fresh_reflog_commits(&entries, grace, now),
+            vec!["aaa".to_string()]
+        );
+    }
+}
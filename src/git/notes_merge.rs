@@ -0,0 +1,537 @@
+//! Three-way merge of authorship notes refs.
+//!
+//! `git notes merge -s ours` keeps only the local note when both sides annotated the same
+//! commit, so two machines writing notes for one commit range lose each other's attributions.
+//! Remote notes are fetched into a staging ref first; this module then merges it into
+//! refs/notes/ai note by note against the merge base, taking the union of the AI-line
+//! attestations where both sides changed a note.
+//!
+//! The same union backs `git notes --ref=ai merge`: git has no pluggable notes merge
+//! strategy, so the wrapper merges the named ref here before git runs (leaving git nothing to
+//! do), and a conflicted manual merge is finished by [`union_conflicted_notes`].
+
+use crate::authorship::authorship_log::LineRange;
+use crate::authorship::authorship_log_serialization::{AttestationEntry, AuthorshipLog};
+use crate::error::GitAiError;
+use crate::git::refs::AI_AUTHORSHIP_REFNAME;
+use crate::git::repo_lock;
+use crate::git::repository::{Repository, exec_git, exec_git_stdin};
+use crate::utils::debug_log;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+
+/// What merging a staging ref into refs/notes/ai did
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NotesMergeOutcome {
+    /// Local notes already contained everything in the staging ref
+    UpToDate,
+    /// There were no local notes; refs/notes/ai now points at the staging ref
+    Initialized,
+    /// Local notes were an ancestor of the staging ref and were moved forward
+    FastForward,
+    /// Both sides had new notes; a merge commit was written. `unioned` counts the notes
+    /// changed on both sides whose attestations were combined.
+    Merged { unioned: usize },
+}
+
+/// Merge the notes commit at `staging_ref` into refs/notes/ai
+pub fn merge_staged_notes(
+    repo: &Repository,
+    staging_ref: &str,
+) -> Result<NotesMergeOutcome, GitAiError> {
+    let _lock = repo_lock::lock(&repo.storage.lock)?;
+    let local_ref = format!("refs/notes/{}", AI_AUTHORSHIP_REFNAME);
+    let Some(theirs) = resolve(repo, staging_ref) else {
+        return Ok(NotesMergeOutcome::UpToDate);
+    };
+    let Some(ours) = resolve(repo, &local_ref) else {
+        update_ref(repo, &local_ref, &theirs, None)?;
+        return Ok(NotesMergeOutcome::Initialized);
+    };
+
+    if ours == theirs || is_ancestor(repo, &theirs, &ours)? {
+        return Ok(NotesMergeOutcome::UpToDate);
+    }
+    if is_ancestor(repo, &ours, &theirs)? {
+        update_ref(repo, &local_ref, &theirs, Some(&ours))?;
+        return Ok(NotesMergeOutcome::FastForward);
+    }
+
+    // Unrelated histories (two clones that each started notes) merge against an empty base
+    let base = merge_base(repo, &ours, &theirs)?;
+    let base_notes = match &base {
+        Some(base) => note_blobs(repo, base)?,
+        None => HashMap::new(),
+    };
+    let our_notes = note_blobs(repo, &ours)?;
+    let their_notes = note_blobs(repo, &theirs)?;
+
+    let mut annotated: BTreeSet<&String> = our_notes.keys().collect();
+    annotated.extend(their_notes.keys());
+
+    let mut unioned = 0;
+    let mut tree_entries = String::new();
+    for commit in annotated {
+        let merged = match merge_note_blob(
+            base_notes.get(commit),
+            our_notes.get(commit),
+            their_notes.get(commit),
+        ) {
+            BlobMerge::Take(blob) => blob.map(str::to_string),
+            BlobMerge::Union(ours_blob, theirs_blob) => {
+                unioned += 1;
+                Some(union_note_blobs(repo, ours_blob, theirs_blob)?)
+            }
+        };
+        if let Some(blob) = merged {
+            tree_entries.push_str(&format!("100644 blob {}\t{}\n", blob, commit));
+        }
+    }
+
+    let mut mktree_args = repo.global_args_for_exec();
+    mktree_args.push("mktree".to_string());
+    let tree = String::from_utf8(exec_git_stdin(&mktree_args, tree_entries.as_bytes())?.stdout)?
+        .trim()
+        .to_string();
+
+    let message = format!(
+        "Notes merged by git-ai ({} note{} combined)",
+        unioned,
+        if unioned == 1 { "" } else { "s" }
+    );
+    let merge_commit = repo
+        .git(&[
+            "commit-tree",
+            &tree,
+            "-p",
+            &ours,
+            "-p",
+            &theirs,
+            "-m",
+            &message,
+        ])?
+        .trim()
+        .to_string();
+    // Compare-and-swap so a note written locally while we merged isn't dropped
+    update_ref(repo, &local_ref, &merge_commit, Some(&ours))?;
+
+    debug_log(&format!(
+        "merged {} into {} ({} notes unioned)",
+        staging_ref, local_ref, unioned
+    ));
+    Ok(NotesMergeOutcome::Merged { unioned })
+}
+
+/// Full name of the notes ref `name` as `git notes` resolves it: `ai` and `notes/ai` both
+/// mean refs/notes/ai.
+pub fn expand_notes_ref(name: &str) -> String {
+    if name.starts_with("refs/notes/") {
+        name.to_string()
+    } else if let Some(rest) = name.strip_prefix("notes/") {
+        format!("refs/notes/{}", rest)
+    } else {
+        format!("refs/notes/{}", name)
+    }
+}
+
+/// Whether a conflicted `git notes --ref=ai merge` is waiting for `--commit` or `--abort`
+pub fn ai_notes_merge_in_progress(repo: &Repository) -> bool {
+    let local_ref = format!("refs/notes/{}", AI_AUTHORSHIP_REFNAME);
+    fs::read_to_string(repo.path().join("NOTES_MERGE_REF"))
+        .is_ok_and(|target| target.trim().strip_prefix("ref: ") == Some(local_ref.as_str()))
+        && resolve(repo, "NOTES_MERGE_PARTIAL").is_some()
+}
+
+/// Resolve the conflicts of an in-progress `git notes --ref=ai merge` by writing the union of
+/// both sides' authorship logs into .git/NOTES_MERGE_WORKTREE, ready for
+/// `git notes merge --commit`. Notes the user already resolved (no conflict markers left) are
+/// kept as they are. Returns how many notes were resolved.
+pub fn union_conflicted_notes(repo: &Repository) -> Result<usize, GitAiError> {
+    if !ai_notes_merge_in_progress(repo) {
+        return Err(GitAiError::Generic(format!(
+            "No conflicted merge of refs/notes/{} in progress",
+            AI_AUTHORSHIP_REFNAME
+        )));
+    }
+    // The partial merge commit's parents are the local notes and the notes being merged
+    let parents = repo.git(&["rev-list", "--parents", "-n", "1", "NOTES_MERGE_PARTIAL"])?;
+    let parents: Vec<&str> = parents.split_whitespace().skip(1).collect();
+    let [ours, theirs] = parents[..] else {
+        return Err(GitAiError::Generic(
+            "NOTES_MERGE_PARTIAL is not a two-parent merge".to_string(),
+        ));
+    };
+    let our_notes = note_blobs(repo, ours)?;
+    let their_notes = note_blobs(repo, theirs)?;
+
+    let mut resolved = 0;
+    for entry in fs::read_dir(repo.path().join("NOTES_MERGE_WORKTREE"))? {
+        let path = entry?.path();
+        let Some(commit) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        let content = fs::read_to_string(&path)?;
+        if !has_conflict_markers(&content) {
+            continue;
+        }
+        let blob = match (our_notes.get(commit), their_notes.get(commit)) {
+            (Some(ours_blob), Some(theirs_blob)) => union_note_blobs(repo, ours_blob, theirs_blob)?,
+            (Some(blob), None) | (None, Some(blob)) => blob.clone(),
+            (None, None) => continue,
+        };
+        fs::write(&path, repo.git(&["cat-file", "blob", &blob])?)?;
+        resolved += 1;
+    }
+    debug_log(&format!("resolved {} conflicted notes", resolved));
+    Ok(resolved)
+}
+
+fn has_conflict_markers(content: &str) -> bool {
+    content.lines().any(|line| line.starts_with("<<<<<<< "))
+        && content.lines().any(|line| line.starts_with(">>>>>>> "))
+}
+
+/// Resolution of one commit's note, given its blob id at the base and on each side
+#[derive(Debug, PartialEq, Eq)]
+enum BlobMerge<'a> {
+    /// Use this blob (None: the note is deleted)
+    Take(Option<&'a str>),
+    /// Both sides changed the note; combine them
+    Union(&'a str, &'a str),
+}
+
+fn merge_note_blob<'a>(
+    base: Option<&'a String>,
+    ours: Option<&'a String>,
+    theirs: Option<&'a String>,
+) -> BlobMerge<'a> {
+    let (base, ours, theirs) = (
+        base.map(String::as_str),
+        ours.map(String::as_str),
+        theirs.map(String::as_str),
+    );
+    if ours == theirs || theirs == base {
+        return BlobMerge::Take(ours);
+    }
+    if ours == base {
+        return BlobMerge::Take(theirs);
+    }
+    match (ours, theirs) {
+        (Some(ours), Some(theirs)) => BlobMerge::Union(ours, theirs),
+        // Deleted on one side and rewritten on the other: keep the rewrite
+        (ours, theirs) => BlobMerge::Take(ours.or(theirs)),
+    }
+}
+
+/// Write the union of two note blobs and return the new blob id. Notes that aren't authorship
+/// logs can't be combined meaningfully, so ours wins.
+fn union_note_blobs(
+    repo: &Repository,
+    ours_blob: &str,
+    theirs_blob: &str,
+) -> Result<String, GitAiError> {
+    let ours_content = repo.git(&["cat-file", "blob", ours_blob])?;
+    let theirs_content = repo.git(&["cat-file", "blob", theirs_blob])?;
+    let (Ok(ours), Ok(theirs)) = (
+        AuthorshipLog::deserialize_from_string(&ours_content),
+        AuthorshipLog::deserialize_from_string(&theirs_content),
+    ) else {
+        return Ok(ours_blob.to_string());
+    };
+
+    let merged = union_authorship_logs(&ours, &theirs)
+        .serialize_to_string()
+        .map_err(|_| GitAiError::Generic("Failed to serialize authorship log".into()))?;
+    let mut args = repo.global_args_for_exec();
+    args.extend(["hash-object", "-w", "--stdin"].map(String::from));
+    Ok(
+        String::from_utf8(exec_git_stdin(&args, merged.as_bytes())?.stdout)?
+            .trim()
+            .to_string(),
+    )
+}
+
+/// Combine two authorship logs for the same commit. Every prompt and every attested line from
+/// either side is kept; a line both sides attribute stays with our prompt.
+pub fn union_authorship_logs(ours: &AuthorshipLog, theirs: &AuthorshipLog) -> AuthorshipLog {
+    let mut merged = ours.clone();
+    for (hash, prompt) in &theirs.metadata.prompts {
+        merged
+            .metadata
+            .prompts
+            .entry(hash.clone())
+            .or_insert_with(|| prompt.clone());
+    }
+
+    for their_file in &theirs.attestations {
+        let file = merged.get_or_create_file(&their_file.file_path);
+        let claimed: Vec<LineRange> = file
+            .entries
+            .iter()
+            .flat_map(|entry| entry.line_ranges.iter().cloned())
+            .collect();
+
+        for their_entry in &their_file.entries {
+            let mut unclaimed =
+                AttestationEntry::new(their_entry.hash.clone(), their_entry.line_ranges.clone());
+            unclaimed.remove_line_ranges(&claimed);
+            if unclaimed.line_ranges.is_empty() {
+                continue;
+            }
+
+            match file
+                .entries
+                .iter_mut()
+                .find(|entry| entry.hash == unclaimed.hash)
+            {
+                Some(entry) => {
+                    let mut lines: Vec<u32> = entry
+                        .line_ranges
+                        .iter()
+                        .chain(unclaimed.line_ranges.iter())
+                        .flat_map(|range| range.expand())
+                        .collect();
+                    lines.sort_unstable();
+                    lines.dedup();
+                    entry.line_ranges = LineRange::compress_lines(&lines);
+                }
+                None => file.add_entry(unclaimed),
+            }
+        }
+    }
+    merged
+}
+
+/// Commit sha -> note blob id for the notes commit `notes_commit`. Fanout directories
+/// (ab/cdef...) are flattened.
+fn note_blobs(
+    repo: &Repository,
+    notes_commit: &str,
+) -> Result<HashMap<String, String>, GitAiError> {
+    let listing = repo.git(&["ls-tree", "-r", notes_commit])?;
+    Ok(listing
+        .lines()
+        .filter_map(|line| {
+            let (meta, path) = line.split_once('\t')?;
+            let blob = meta.split_whitespace().nth(2)?;
+            let commit: String = path.chars().filter(|c| *c != '/').collect();
+            commit
+                .chars()
+                .all(|c| c.is_ascii_hexdigit())
+                .then(|| (commit, blob.to_string()))
+        })
+        .collect())
+}
+
+fn resolve(repo: &Repository, rev: &str) -> Option<String> {
+    repo.git(&["rev-parse", "--verify", "--quiet", rev])
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+fn is_ancestor(repo: &Repository, ancestor: &str, descendant: &str) -> Result<bool, GitAiError> {
+    let mut args = repo.global_args_for_exec();
+    args.extend(["merge-base", "--is-ancestor", ancestor, descendant].map(String::from));
+    match exec_git(&args) {
+        Ok(_) => Ok(true),
+        Err(GitAiError::GitCliError { code: Some(1), .. }) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+fn merge_base(repo: &Repository, a: &str, b: &str) -> Result<Option<String>, GitAiError> {
+    match repo.git(&["merge-base", a, b]) {
+        Ok(base) => Ok(Some(base.trim().to_string())),
+        Err(GitAiError::GitCliError { code: Some(1), .. }) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+fn update_ref(
+    repo: &Repository,
+    ref_name: &str,
+    new: &str,
+    expected_old: Option<&str>,
+) -> Result<(), GitAiError> {
+    let mut args = vec!["update-ref", ref_name, new];
+    if let Some(old) = expected_old {
+        args.push(old);
+    }
+    repo.git(&args)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::refs::{notes_add, show_authorship_note};
+    use crate::git::test_utils::{TmpRepo, synthetic_authorship_log};
+
+    #[test]
+    fn test_merge_note_blob_three_way() {
+        let (a, b, c) = ("a".to_string(), "b".to_string(), "c".to_string());
+        // One side changed
+        assert_eq!(
+            merge_note_blob(Some(&a), Some(&a), Some(&b)),
+            BlobMerge::Take(Some("b"))
+        );
+        assert_eq!(
+            merge_note_blob(Some(&a), Some(&b), Some(&a)),
+            BlobMerge::Take(Some("b"))
+        );
+        // Deleted on one side only
+        assert_eq!(
+            merge_note_blob(Some(&a), None, Some(&a)),
+            BlobMerge::Take(None)
+        );
+        // Added on both sides differently, or changed on both
+        assert_eq!(
+            merge_note_blob(None, Some(&b), Some(&c)),
+            BlobMerge::Union("b", "c")
+        );
+        assert_eq!(
+            merge_note_blob(Some(&a), Some(&b), Some(&c)),
+            BlobMerge::Union("b", "c")
+        );
+        // Deleted on one side, rewritten on the other
+        assert_eq!(
+            merge_note_blob(Some(&a), None, Some(&c)),
+            BlobMerge::Take(Some("c"))
+        );
+    }
+
+    #[test]
+    fn test_union_authorship_logs_keeps_both_sides() {
+        let ours = synthetic_authorship_log(1, 1);
+        let mut theirs = synthetic_authorship_log(2, 2);
+        // Their first entry overlaps ours exactly and should not be duplicated
+        let hash = theirs.attestations[1].entries[0].hash.clone();
+        theirs.attestations[1].entries.push(AttestationEntry::new(
+            hash,
+            vec![LineRange::Range(100, 102)],
+        ));
+
+        let merged = union_authorship_logs(&ours, &theirs);
+        assert_eq!(merged.attestations.len(), 2);
+        assert_eq!(merged.attestations[0], theirs.attestations[0]);
+        let second = &merged.attestations[1];
+        assert_eq!(second.entries.len(), 2);
+        assert!(
+            second.entries[0]
+                .line_ranges
+                .contains(&LineRange::Range(100, 102))
+        );
+    }
+
+    #[test]
+    fn test_merge_staged_notes_unions_concurrent_notes() {
+        let tmp_repo = TmpRepo::new().expect("Failed to create tmp repo");
+        tmp_repo.commit_with_message("Initial commit").unwrap();
+        let commit_sha = tmp_repo.get_head_commit_sha().unwrap();
+        let repo = tmp_repo.gitai_repo();
+
+        let base = synthetic_authorship_log(1, 1);
+        notes_add(repo, &commit_sha, &base.serialize_to_string().unwrap()).unwrap();
+        let base_tip = resolve(repo, "refs/notes/ai").unwrap();
+
+        // The other machine attributes a second file to the same commit...
+        let mut theirs = base.clone();
+        let their_file = synthetic_authorship_log(2, 1).attestations[1].clone();
+        theirs.attestations.push(their_file.clone());
+        notes_add(repo, &commit_sha, &theirs.serialize_to_string().unwrap()).unwrap();
+        repo.git(&["update-ref", "refs/notes/ai-remote/origin", "refs/notes/ai"])
+            .unwrap();
+
+        // ...while this one extends the first file's attestation
+        repo.git(&["update-ref", "refs/notes/ai", &base_tip])
+            .unwrap();
+        let mut ours = base.clone();
+        ours.attestations[0].entries[0]
+            .line_ranges
+            .push(LineRange::Single(50));
+        notes_add(repo, &commit_sha, &ours.serialize_to_string().unwrap()).unwrap();
+
+        let outcome = merge_staged_notes(repo, "refs/notes/ai-remote/origin").unwrap();
+        assert_eq!(outcome, NotesMergeOutcome::Merged { unioned: 1 });
+
+        let merged = AuthorshipLog::deserialize_from_string(
+            &show_authorship_note(repo, &commit_sha).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(merged.attestations[0], ours.attestations[0]);
+        assert_eq!(merged.attestations[1], their_file);
+
+        // Merging the same staging ref again is a no-op
+        assert_eq!(
+            merge_staged_notes(repo, "refs/notes/ai-remote/origin").unwrap(),
+            NotesMergeOutcome::UpToDate
+        );
+    }
+
+    #[test]
+    fn test_expand_notes_ref() {
+        assert_eq!(expand_notes_ref("ai"), "refs/notes/ai");
+        assert_eq!(expand_notes_ref("notes/ai-remote"), "refs/notes/ai-remote");
+        assert_eq!(expand_notes_ref("refs/notes/ai"), "refs/notes/ai");
+    }
+
+    #[test]
+    fn test_union_conflicted_notes_resolves_manual_merge() {
+        let tmp_repo = TmpRepo::new().expect("Failed to create tmp repo");
+        tmp_repo.commit_with_message("Initial commit").unwrap();
+        let commit_sha = tmp_repo.get_head_commit_sha().unwrap();
+        let repo = tmp_repo.gitai_repo();
+
+        let base = synthetic_authorship_log(1, 1);
+        notes_add(repo, &commit_sha, &base.serialize_to_string().unwrap()).unwrap();
+        repo.git(&["update-ref", "refs/notes/other", "refs/notes/ai"])
+            .unwrap();
+
+        let mut ours = base.clone();
+        ours.attestations[0].entries[0]
+            .line_ranges
+            .push(LineRange::Single(50));
+        notes_add(repo, &commit_sha, &ours.serialize_to_string().unwrap()).unwrap();
+        let mut theirs = base.clone();
+        let their_file = synthetic_authorship_log(2, 1).attestations[1].clone();
+        theirs.attestations.push(their_file.clone());
+        repo.git(&[
+            "notes",
+            "--ref=other",
+            "add",
+            "-f",
+            "-m",
+            &theirs.serialize_to_string().unwrap(),
+            &commit_sha,
+        ])
+        .unwrap();
+
+        assert!(!ai_notes_merge_in_progress(repo));
+        assert!(union_conflicted_notes(repo).is_err());
+        assert!(
+            repo.git(&[
+                "notes",
+                "--ref=ai",
+                "merge",
+                "-s",
+                "manual",
+                "refs/notes/other"
+            ])
+            .is_err()
+        );
+        assert!(ai_notes_merge_in_progress(repo));
+
+        assert_eq!(union_conflicted_notes(repo).unwrap(), 1);
+        repo.git(&["notes", "--ref=ai", "merge", "--commit"])
+            .unwrap();
+        assert!(!ai_notes_merge_in_progress(repo));
+
+        let merged = AuthorshipLog::deserialize_from_string(
+            &show_authorship_note(repo, &commit_sha).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(merged.attestations[0], ours.attestations[0]);
+        assert_eq!(merged.attestations[1], their_file);
+    }
+}
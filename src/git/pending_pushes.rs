@@ -0,0 +1,231 @@
+//! Queue of authorship note pushes that failed.
+//!
+//! Notes are pushed alongside the branch on a best-effort basis, so an auth error, a protected
+//! ref or a network outage used to leave the remote's notes silently behind. A failed push is
+//! now recorded under `.git/ai/pending-pushes/<remote>.json`. The next push or fetch retries it,
+//! as does `git-ai sync --retry`, and the push command warns while it stays queued. Each record
+//! stands for "refs/notes/ai on this remote is behind", so repeated failures update one record
+//! rather than piling up.
+
+use crate::error::GitAiError;
+use crate::git::refs::{AI_AUTHORSHIP_REFNAME, ref_exists, sanitize_remote_name};
+use crate::git::repo_lock;
+use crate::git::repository::Repository;
+use crate::git::sync_authorship::push_authorship_notes;
+use crate::utils::debug_log;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A remote whose refs/notes/ai could not be pushed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingPush {
+    pub remote: String,
+    /// Unix seconds of the first failure since the last successful push
+    pub first_failed_at: u64,
+    pub last_failed_at: u64,
+    pub attempts: u32,
+    pub last_error: String,
+}
+
+fn pending_push_path(repository: &Repository, remote: &str) -> PathBuf {
+    repository
+        .storage
+        .pending_pushes
+        .join(format!("{}.json", sanitize_remote_name(remote)))
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Update the queue after pushing notes to `remote`: a success clears its record, a failure
+/// adds or updates it. Failures with no local notes to push are not queued.
+pub fn record_push_outcome(repository: &Repository, remote: &str, result: &Result<(), GitAiError>) {
+    // Concurrent pushes to one remote would otherwise both read the old attempt count
+    let _lock = match repo_lock::lock(&repository.storage.lock) {
+        Ok(lock) => lock,
+        Err(e) => {
+            debug_log(&format!(
+                "failed to update pending notes push for {}: {}",
+                remote, e
+            ));
+            return;
+        }
+    };
+    let recorded = match result {
+        Ok(()) => clear_pending_push(repository, remote),
+        Err(_) if !ref_exists(repository, &format!("refs/notes/{}", AI_AUTHORSHIP_REFNAME)) => {
+            Ok(())
+        }
+        Err(e) => record_failed_push(repository, remote, &e.to_string()),
+    };
+    if let Err(e) = recorded {
+        debug_log(&format!(
+            "failed to update pending notes push for {}: {}",
+            remote, e
+        ));
+    }
+}
+
+fn record_failed_push(
+    repository: &Repository,
+    remote: &str,
+    error: &str,
+) -> Result<(), GitAiError> {
+    let now = now_secs();
+    let pending = match load_pending_push(repository, remote) {
+        Some(previous) => PendingPush {
+            last_failed_at: now,
+            attempts: previous.attempts + 1,
+            last_error: error.to_string(),
+            ..previous
+        },
+        None => PendingPush {
+            remote: remote.to_string(),
+            first_failed_at: now,
+            last_failed_at: now,
+            attempts: 1,
+            last_error: error.to_string(),
+        },
+    };
+    fs::create_dir_all(&repository.storage.pending_pushes)?;
+    // Write-then-rename so a concurrent reader never sees half a record
+    let path = pending_push_path(repository, remote);
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_string_pretty(&pending)?)?;
+    fs::rename(&tmp, &path)?;
+    Ok(())
+}
+
+fn clear_pending_push(repository: &Repository, remote: &str) -> Result<(), GitAiError> {
+    match fs::remove_file(pending_push_path(repository, remote)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+fn load_pending_push(repository: &Repository, remote: &str) -> Option<PendingPush> {
+    let contents = fs::read_to_string(pending_push_path(repository, remote)).ok()?;
+    serde_json::from_str::<PendingPush>(&contents)
+        .ok()
+        .filter(|pending| pending.remote == remote)
+}
+
+/// Queued pushes, oldest first. Records for remotes that no longer exist are discarded.
+pub fn pending_pushes(repository: &Repository) -> Vec<PendingPush> {
+    let Ok(entries) = fs::read_dir(&repository.storage.pending_pushes) else {
+        return Vec::new();
+    };
+    let remotes: Vec<String> = repository
+        .remotes_with_urls()
+        .map(|remotes| remotes.into_iter().map(|(name, _)| name).collect())
+        .unwrap_or_default();
+
+    let mut pending = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        let Some(record) = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str::<PendingPush>(&contents).ok())
+        else {
+            continue;
+        };
+        if remotes.contains(&record.remote) {
+            pending.push(record);
+        } else {
+            debug_log(&format!(
+                "discarding pending notes push for removed remote {}",
+                record.remote
+            ));
+            let _ = fs::remove_file(&path);
+        }
+    }
+    pending.sort_by(|a, b| {
+        a.first_failed_at
+            .cmp(&b.first_failed_at)
+            .then_with(|| a.remote.cmp(&b.remote))
+    });
+    pending
+}
+
+/// Retry every queued push except those to `skip` (remotes the caller is pushing to anyway).
+/// Successful ones leave the queue.
+pub fn retry_pending_pushes(
+    repository: &Repository,
+    skip: &[String],
+) -> Vec<(PendingPush, Result<(), GitAiError>)> {
+    pending_pushes(repository)
+        .into_iter()
+        .filter(|pending| !skip.contains(&pending.remote))
+        .map(|pending| {
+            debug_log(&format!(
+                "retrying queued authorship notes push to {}",
+                pending.remote
+            ));
+            let result = push_authorship_notes(repository, &pending.remote);
+            (pending, result)
+        })
+        .collect()
+}
+
+/// Tell the user about every remote whose notes are still queued
+pub fn warn_pending_pushes(repository: &Repository) {
+    let pending = pending_pushes(repository);
+    for push in &pending {
+        eprintln!(
+            "git-ai: warning: authorship notes could not be pushed to '{}' ({} failed attempt{}): {}",
+            push.remote,
+            push.attempts,
+            if push.attempts == 1 { "" } else { "s" },
+            push.last_error.trim()
+        );
+    }
+    if !pending.is_empty() {
+        eprintln!(
+            "git-ai: they will be retried on the next push or fetch, or run `git-ai sync --retry`."
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_utils::TmpRepo;
+
+    #[test]
+    fn test_failures_accumulate_and_success_clears() {
+        let tmp_repo = TmpRepo::new().unwrap();
+        let repo = tmp_repo.gitai_repo();
+        repo.git(&["remote", "add", "origin", "/nonexistent/remote.git"])
+            .unwrap();
+
+        record_failed_push(repo, "origin", "auth failed").unwrap();
+        record_failed_push(repo, "origin", "network down").unwrap();
+        let pending = pending_pushes(repo);
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].remote, "origin");
+        assert_eq!(pending[0].attempts, 2);
+        assert_eq!(pending[0].last_error, "network down");
+
+        record_push_outcome(repo, "origin", &Ok(()));
+        assert!(pending_pushes(repo).is_empty());
+    }
+
+    #[test]
+    fn test_removed_remotes_are_discarded() {
+        let tmp_repo = TmpRepo::new().unwrap();
+        let repo = tmp_repo.gitai_repo();
+
+        record_failed_push(repo, "gone", "auth failed").unwrap();
+        assert!(pending_pushes(repo).is_empty());
+        assert!(!pending_push_path(repo, "gone").exists());
+    }
+}
@@ -1,6 +1,11 @@
+use crate::authorship::authorship_log::LineRange;
 use crate::authorship::authorship_log_serialization::{AUTHORSHIP_LOG_VERSION, AuthorshipLog};
+use crate::authorship::note_schema;
 use crate::authorship::working_log::Checkpoint;
+use crate::config::Config;
 use crate::error::GitAiError;
+use crate::git::note_signing;
+use crate::git::repo_lock;
 use crate::git::repository::{Repository, exec_git, exec_git_stdin};
 use crate::utils::debug_log;
 use serde_json;
@@ -9,6 +14,11 @@ use std::collections::{HashMap, HashSet};
 // Modern refspecs without force to enable proper merging
 pub const AI_AUTHORSHIP_REFNAME: &str = "ai";
 pub const AI_AUTHORSHIP_PUSH_REFSPEC: &str = "refs/notes/ai:refs/notes/ai";
+/// git's default notes ref, shown by `git log` without any extra configuration
+pub const SUMMARY_NOTES_REFNAME: &str = "commits";
+pub const SUMMARY_NOTES_PUSH_REFSPEC: &str = "refs/notes/commits:refs/notes/commits";
+/// Marks the line git-ai owns in a refs/notes/commits note; other lines are left alone
+pub const SUMMARY_NOTE_PREFIX: &str = "git-ai:";
 
 pub fn notes_add(
     repo: &Repository,
@@ -24,8 +34,139 @@ pub fn notes_add(
     args.push("-".to_string()); // Read note content from stdin
     args.push(commit_sha.to_string());
 
+    let _lock = repo_lock::lock(&repo.storage.lock)?;
     // Use stdin to provide the note content to avoid command line length limits
     exec_git_stdin(&args, note_content.as_bytes())?;
+
+    if Config::get().mirror_summary_notes() {
+        // Best-effort; refs/notes/ai stays the source of truth
+        if let Err(e) = mirror_summary_note(repo, commit_sha, note_content) {
+            debug_log(&format!(
+                "failed to mirror summary note for {}: {}",
+                commit_sha, e
+            ));
+        }
+    }
+
+    if note_signing::signing_enabled(repo)
+        && let Err(e) = note_signing::sign_note(repo, commit_sha)
+    {
+        // The note itself is written; `git ai verify` reports it as unsigned
+        eprintln!(
+            "git-ai: failed to sign the authorship note for {}: {}",
+            commit_sha, e
+        );
+    }
+    Ok(())
+}
+
+/// One-line, human-readable description of an authorship log, or None if it attributes no lines
+pub fn authorship_summary_line(log: &AuthorshipLog) -> Option<String> {
+    let mut ai_lines: u64 = 0;
+    let mut files = 0;
+    for file in &log.attestations {
+        let file_lines: u64 = file
+            .entries
+            .iter()
+            .flat_map(|entry| entry.line_ranges.iter())
+            .map(|range| match range {
+                LineRange::Single(_) => 1,
+                LineRange::Range(start, end) => (end.saturating_sub(*start) as u64) + 1,
+            })
+            .sum();
+        if file_lines > 0 {
+            ai_lines += file_lines;
+            files += 1;
+        }
+    }
+    if ai_lines == 0 {
+        return None;
+    }
+
+    let mut agents: Vec<String> = log
+        .metadata
+        .prompts
+        .values()
+        .map(|prompt| {
+            if prompt.agent_id.model.is_empty() {
+                prompt.agent_id.tool.clone()
+            } else {
+                format!("{}/{}", prompt.agent_id.tool, prompt.agent_id.model)
+            }
+        })
+        .collect();
+    agents.sort();
+    agents.dedup();
+
+    let mut summary = format!(
+        "{} {} AI-generated line{} in {} file{}",
+        SUMMARY_NOTE_PREFIX,
+        ai_lines,
+        if ai_lines == 1 { "" } else { "s" },
+        files,
+        if files == 1 { "" } else { "s" }
+    );
+    if !agents.is_empty() {
+        summary.push_str(&format!(" ({})", agents.join(", ")));
+    }
+    Some(summary)
+}
+
+/// Write the summary of `note_content` into refs/notes/commits for `commit_sha`, replacing any
+/// previous git-ai line and keeping whatever else the note says.
+pub fn mirror_summary_note(
+    repo: &Repository,
+    commit_sha: &str,
+    note_content: &str,
+) -> Result<(), GitAiError> {
+    let summary = AuthorshipLog::deserialize_from_string(note_content)
+        .ok()
+        .and_then(|log| authorship_summary_line(&log));
+
+    let mut show_args = repo.global_args_for_exec();
+    show_args.push("notes".to_string());
+    show_args.push(format!("--ref={}", SUMMARY_NOTES_REFNAME));
+    show_args.push("show".to_string());
+    show_args.push(commit_sha.to_string());
+    let existing = exec_git(&show_args)
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .unwrap_or_default();
+
+    let mut lines: Vec<&str> = existing
+        .lines()
+        .filter(|line| !line.starts_with(SUMMARY_NOTE_PREFIX))
+        .collect();
+    while lines.last().is_some_and(|line| line.trim().is_empty()) {
+        lines.pop();
+    }
+    let mut content = lines.join("\n");
+    if let Some(summary) = summary {
+        if !content.is_empty() {
+            content.push('\n');
+        }
+        content.push_str(&summary);
+    }
+    if content.trim() == existing.trim() {
+        return Ok(());
+    }
+
+    let mut args = repo.global_args_for_exec();
+    args.push("notes".to_string());
+    args.push(format!("--ref={}", SUMMARY_NOTES_REFNAME));
+    if content.is_empty() {
+        args.push("remove".to_string());
+        args.push("--ignore-missing".to_string());
+        args.push(commit_sha.to_string());
+        exec_git(&args)?;
+    } else {
+        args.push("add".to_string());
+        args.push("-f".to_string());
+        args.push("-F".to_string());
+        args.push("-".to_string());
+        args.push(commit_sha.to_string());
+        exec_git_stdin(&args, content.as_bytes())?;
+    }
     Ok(())
 }
 
@@ -55,10 +196,11 @@ pub fn get_commits_with_notes_from_list(
 
     // Get the git authors for all commits using git rev-list
     // This approach works in both bare and normal repositories
+    // %aN/%aE apply .mailmap so one person with several identities aggregates as one author
     let mut args = repo.global_args_for_exec();
     args.push("rev-list".to_string());
     args.push("--no-walk".to_string());
-    args.push("--pretty=format:%H%n%an%n%ae".to_string());
+    args.push("--pretty=format:%H%n%aN%n%aE".to_string());
     for sha in commit_shas {
         args.push(sha.clone());
     }
@@ -170,7 +312,7 @@ pub fn get_reference_as_authorship_log_v3(
     };
 
     // Check version compatibility
-    if authorship_log.metadata.schema_version != AUTHORSHIP_LOG_VERSION {
+    if !note_schema::can_read(&authorship_log.metadata.schema_version) {
         return Err(GitAiError::Generic(format!(
             "Unsupported authorship log version: {} (expected: {})",
             authorship_log.metadata.schema_version, AUTHORSHIP_LOG_VERSION
@@ -183,7 +325,7 @@ pub fn get_reference_as_authorship_log_v3(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::git::test_utils::TmpRepo;
+    use crate::git::test_utils::{TmpRepo, synthetic_authorship_log};
 
     #[test]
     fn test_notes_add_and_show_authorship_note() {
@@ -221,11 +363,53 @@ mod tests {
         );
         assert!(non_existent_content.is_none());
     }
+
+    #[test]
+    fn test_authorship_summary_line() {
+        // 2 files x 3 entries x (5-line range + single line)
+        let log = synthetic_authorship_log(2, 3);
+        assert_eq!(
+            authorship_summary_line(&log).as_deref(),
+            Some("git-ai: 36 AI-generated lines in 2 files (bench/bench-model)")
+        );
+
+        assert_eq!(authorship_summary_line(&AuthorshipLog::new()), None);
+    }
+
+    #[test]
+    fn test_mirror_summary_note_keeps_other_lines() {
+        let tmp_repo = TmpRepo::new().expect("Failed to create tmp repo");
+        tmp_repo
+            .commit_with_message("Initial commit")
+            .expect("Failed to create initial commit");
+        let commit_sha = tmp_repo
+            .get_head_commit_sha()
+            .expect("Failed to get head commit SHA");
+        let repo = tmp_repo.gitai_repo();
+        repo.git(&["notes", "add", "-m", "Reviewed-by: Ana", &commit_sha])
+            .unwrap();
+
+        let note = synthetic_authorship_log(1, 1)
+            .serialize_to_string()
+            .unwrap();
+        mirror_summary_note(repo, &commit_sha, &note).unwrap();
+        mirror_summary_note(repo, &commit_sha, &note).unwrap();
+        let mirrored = repo.git(&["notes", "show", &commit_sha]).unwrap();
+        assert_eq!(
+            mirrored.trim(),
+            "Reviewed-by: Ana\ngit-ai: 6 AI-generated lines in 1 file (bench/bench-model)"
+        );
+
+        // A note with nothing attributed drops the git-ai line only
+        mirror_summary_note(repo, &commit_sha, "not an authorship log").unwrap();
+        let mirrored = repo.git(&["notes", "show", &commit_sha]).unwrap();
+        assert_eq!(mirrored.trim(), "Reviewed-by: Ana");
+    }
 }
 
 /// Sanitize a remote name to create a safe ref name
 /// Replaces special characters with underscores to ensure valid ref names
-fn sanitize_remote_name(remote: &str) -> String {
+pub(crate) fn sanitize_remote_name(remote: &str) -> String {
     remote
         .chars()
         .map(|c| {
@@ -251,6 +435,23 @@ pub fn tracking_ref_for_remote(remote_name: &str) -> String {
     format!("refs/notes/ai-remote/{}", sanitize_remote_name(remote_name))
 }
 
+/// Tracking ref for a remote's refs/notes/commits, e.g. "refs/notes/ai-summary-remote/origin"
+pub fn summary_tracking_ref_for_remote(remote_name: &str) -> String {
+    format!(
+        "refs/notes/ai-summary-remote/{}",
+        sanitize_remote_name(remote_name)
+    )
+}
+
+/// Tracking ref for a remote's refs/notes/ai-signatures, e.g.
+/// "refs/notes/ai-signatures-remote/origin"
+pub fn signatures_tracking_ref_for_remote(remote_name: &str) -> String {
+    format!(
+        "refs/notes/ai-signatures-remote/{}",
+        sanitize_remote_name(remote_name)
+    )
+}
+
 /// Check if a ref exists in the repository
 pub fn ref_exists(repo: &Repository, ref_name: &str) -> bool {
     let mut args = repo.global_args_for_exec();
@@ -262,38 +463,42 @@ pub fn ref_exists(repo: &Repository, ref_name: &str) -> bool {
     exec_git(&args).is_ok()
 }
 
-/// Merge notes from a source ref into refs/notes/ai
-/// Uses the 'ours' strategy to combine notes without data loss
-pub fn merge_notes_from_ref(repo: &Repository, source_ref: &str) -> Result<(), GitAiError> {
+/// Every commit that carries an authorship note
+pub fn noted_commits(repo: &Repository) -> Result<HashSet<String>, GitAiError> {
+    let notes_ref = format!("--ref={}", AI_AUTHORSHIP_REFNAME);
+    // `git notes list` fails when the notes ref doesn't exist yet
+    let output = match repo.git(&["notes", &notes_ref, "list"]) {
+        Ok(output) => output,
+        Err(GitAiError::GitCliError { .. }) => return Ok(HashSet::new()),
+        Err(e) => return Err(e),
+    };
+    Ok(output
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(1))
+        .map(|sha| sha.to_string())
+        .collect())
+}
+
+/// Merge a remote's summary notes into refs/notes/commits.
+/// These notes may be hand-written, so both sides' lines are kept (`cat_sort_uniq`).
+pub fn merge_summary_notes_from_ref(repo: &Repository, source_ref: &str) -> Result<(), GitAiError> {
     let mut args = repo.global_args_for_exec();
     args.push("notes".to_string());
-    args.push(format!("--ref={}", AI_AUTHORSHIP_REFNAME));
+    args.push(format!("--ref={}", SUMMARY_NOTES_REFNAME));
     args.push("merge".to_string());
     args.push("-s".to_string());
-    args.push("ours".to_string());
+    args.push("cat_sort_uniq".to_string());
     args.push("--quiet".to_string());
     args.push(source_ref.to_string());
 
     debug_log(&format!(
-        "Merging notes from {} into refs/notes/ai",
+        "Merging summary notes from {} into refs/notes/commits",
         source_ref
     ));
     exec_git(&args)?;
     Ok(())
 }
 
-/// Copy a ref to another location (used for initial setup of local notes from tracking ref)
-pub fn copy_ref(repo: &Repository, source_ref: &str, dest_ref: &str) -> Result<(), GitAiError> {
-    let mut args = repo.global_args_for_exec();
-    args.push("update-ref".to_string());
-    args.push(dest_ref.to_string());
-    args.push(source_ref.to_string());
-
-    debug_log(&format!("Copying ref {} to {}", source_ref, dest_ref));
-    exec_git(&args)?;
-    Ok(())
-}
-
 /// Search AI notes for a pattern and return matching commit SHAs ordered by commit date (newest first)
 /// Uses git grep to search through refs/notes/ai
 pub fn grep_ai_notes(repo: &Repository, pattern: &str) -> Result<Vec<String>, GitAiError> {
@@ -0,0 +1,178 @@
+//! Advisory lock serializing git-ai's writes to a repository.
+//!
+//! Hooks, the wrapper and background fetches can run at the same time (an IDE auto-fetching
+//! while the user pushes), and several of git-ai's writes read state and write it back: adding
+//! a note, merging fetched notes, appending a checkpoint and updating the push queue. Those
+//! paths hold `.git/ai/lock` while they write. The lock file sits in the common git dir, so
+//! every worktree of a repository shares it.
+//!
+//! The lock is an OS file lock, which the OS releases when its holder exits. A process that
+//! crashes or is killed mid-write therefore never leaves a stale lock behind; the lock file
+//! itself stays and is reused. Its contents name the last holder, for the timeout message.
+//!
+//! The lock is reentrant per thread, so a locked path can call another one. Filesystems that
+//! don't support file locks fall back to writing unlocked, as git-ai did before.
+
+use crate::error::GitAiError;
+use crate::utils::debug_log;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// How long a writer waits for another process's lock before giving up
+pub const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+const POLL_INTERVAL: Duration = Duration::from_millis(25);
+
+thread_local! {
+    /// Locks this thread holds: lock path -> (open lock file, nesting depth)
+    static HELD: RefCell<HashMap<PathBuf, (File, usize)>> = RefCell::new(HashMap::new());
+}
+
+/// Holds the lock until dropped
+#[must_use = "the lock is released as soon as the guard is dropped"]
+pub struct RepoLockGuard {
+    /// None when the filesystem doesn't support locking
+    path: Option<PathBuf>,
+}
+
+impl Drop for RepoLockGuard {
+    fn drop(&mut self) {
+        let Some(path) = self.path.take() else {
+            return;
+        };
+        HELD.with(|held| {
+            let mut held = held.borrow_mut();
+            if let Some((_, depth)) = held.get_mut(&path) {
+                *depth -= 1;
+                if *depth == 0 {
+                    // Closing the file releases the lock
+                    held.remove(&path);
+                }
+            }
+        });
+    }
+}
+
+/// Take the lock at `path`, waiting up to `LOCK_TIMEOUT` for another process to release it
+pub fn lock(path: &Path) -> Result<RepoLockGuard, GitAiError> {
+    lock_with_timeout(path, LOCK_TIMEOUT)
+}
+
+pub fn lock_with_timeout(path: &Path, timeout: Duration) -> Result<RepoLockGuard, GitAiError> {
+    let reentered = HELD.with(|held| match held.borrow_mut().get_mut(path) {
+        Some((_, depth)) => {
+            *depth += 1;
+            true
+        }
+        None => false,
+    });
+    if reentered {
+        return Ok(RepoLockGuard {
+            path: Some(path.to_path_buf()),
+        });
+    }
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)?;
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        match file.try_lock() {
+            Ok(()) => break,
+            Err(TryLockError::WouldBlock) if Instant::now() < deadline => {
+                std::thread::sleep(POLL_INTERVAL);
+            }
+            Err(TryLockError::WouldBlock) => {
+                return Err(GitAiError::Generic(format!(
+                    "timed out after {}s waiting for {} (held by {})",
+                    timeout.as_secs_f64(),
+                    path.display(),
+                    holder(&mut file).unwrap_or_else(|| "another git-ai process".to_string())
+                )));
+            }
+            Err(TryLockError::Error(e)) => {
+                debug_log(&format!(
+                    "file locks unsupported for {}, writing unlocked: {}",
+                    path.display(),
+                    e
+                ));
+                return Ok(RepoLockGuard { path: None });
+            }
+        }
+    }
+
+    // Record the holder for anyone who times out waiting; failing to is harmless
+    let _ = file.set_len(0).and_then(|_| {
+        file.rewind()?;
+        writeln!(
+            file,
+            "pid {} since {}",
+            std::process::id(),
+            chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+        )
+    });
+
+    HELD.with(|held| {
+        held.borrow_mut().insert(path.to_path_buf(), (file, 1));
+    });
+    Ok(RepoLockGuard {
+        path: Some(path.to_path_buf()),
+    })
+}
+
+fn holder(file: &mut File) -> Option<String> {
+    let mut contents = String::new();
+    file.rewind().ok()?;
+    file.read_to_string(&mut contents).ok()?;
+    let contents = contents.trim();
+    (!contents.is_empty()).then(|| contents.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_is_reentrant_and_excludes_other_threads() {
+        let dir = std::env::temp_dir().join(format!("git-ai-lock-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("lock");
+
+        let outer = lock(&path).unwrap();
+        let inner = lock(&path).unwrap();
+        drop(inner);
+
+        // Still held by the outer guard, so another thread times out
+        let contended = {
+            let path = path.clone();
+            std::thread::spawn(move || lock_with_timeout(&path, Duration::from_millis(100)))
+                .join()
+                .unwrap()
+        };
+        let err = contended.err().expect("lock should be held").to_string();
+        assert!(err.contains("timed out"), "{}", err);
+        assert!(
+            err.contains(&format!("pid {}", std::process::id())),
+            "{}",
+            err
+        );
+
+        drop(outer);
+        let path_for_thread = path.clone();
+        std::thread::spawn(move || lock_with_timeout(&path_for_thread, Duration::ZERO).map(drop))
+            .join()
+            .unwrap()
+            .expect("lock should be free once released");
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -3,7 +3,9 @@ use crate::authorship::authorship_log::PromptRecord;
 use crate::authorship::authorship_log_serialization::generate_short_hash;
 use crate::authorship::working_log::{CHECKPOINT_API_VERSION, Checkpoint, CheckpointKind};
 use crate::error::GitAiError;
+use crate::git::repo_lock;
 use crate::git::rewrite_log::{RewriteLogEvent, append_event_to_file};
+use crate::observability::logging;
 use crate::utils::{debug_log, normalize_to_posix};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
@@ -27,14 +29,32 @@ pub struct RepoStorage {
     pub working_logs: PathBuf,
     pub rewrite_log: PathBuf,
     pub logs: PathBuf,
+    /// Derived data that can be rebuilt from notes at any time (see `authorship::stats_cache`)
+    pub cache: PathBuf,
+    /// Notes pushes waiting to be retried (see `git::pending_pushes`)
+    pub pending_pushes: PathBuf,
+    /// Lock held while writing notes, checkpoints or the push queue (see `git::repo_lock`)
+    pub lock: PathBuf,
 }
 
 impl RepoStorage {
     pub fn for_repo_path(repo_path: &Path, repo_workdir: &Path) -> RepoStorage {
+        Self::for_worktree(repo_path, repo_path, repo_workdir)
+    }
+
+    /// Storage for one worktree of a repository. Checkpoints and the rewrite log follow the
+    /// worktree's HEAD and index, so they live in its own git dir (`.git/worktrees/<name>` for
+    /// linked worktrees); the cache and the notes push queue concern notes, which all worktrees
+    /// share.
+    pub fn for_worktree(git_dir: &Path, common_dir: &Path, repo_workdir: &Path) -> RepoStorage {
+        let repo_path = git_dir;
         let ai_dir = repo_path.join("ai");
         let working_logs_dir = ai_dir.join("working_logs");
         let rewrite_log_file = ai_dir.join("rewrite_log");
         let logs_dir = ai_dir.join("logs");
+        let cache_dir = common_dir.join("ai").join("cache");
+        let pending_pushes_dir = common_dir.join("ai").join("pending-pushes");
+        let lock_file = common_dir.join("ai").join("lock");
 
         let config = RepoStorage {
             repo_path: repo_path.to_path_buf(),
@@ -42,9 +62,13 @@ impl RepoStorage {
             working_logs: working_logs_dir,
             rewrite_log: rewrite_log_file,
             logs: logs_dir,
+            cache: cache_dir,
+            pending_pushes: pending_pushes_dir,
+            lock: lock_file,
         };
 
         config.ensure_config_directory().unwrap();
+        logging::set_log_dir(&config.logs);
         return config;
     }
 
@@ -56,7 +80,7 @@ impl RepoStorage {
         // Create working_logs directory
         fs::create_dir_all(&self.working_logs)?;
 
-        // Create logs directory for Sentry events
+        // Create logs directory for the diagnostic log (see `observability::logging`)
         fs::create_dir_all(&self.logs)?;
 
         if !&self.rewrite_log.exists() && !&self.rewrite_log.is_file() {
@@ -81,6 +105,7 @@ impl RepoStorage {
             self.repo_workdir.clone(),
             canonical_workdir,
             None,
+            self.lock.clone(),
         )
     }
 
@@ -159,6 +184,8 @@ pub struct PersistedWorkingLog {
     pub canonical_workdir: PathBuf,
     pub dirty_files: Option<HashMap<String, String>>,
     pub initial_file: PathBuf,
+    /// The repository's write lock (`RepoStorage::lock`)
+    pub lock: PathBuf,
 }
 
 impl PersistedWorkingLog {
@@ -168,6 +195,7 @@ impl PersistedWorkingLog {
         repo_root: PathBuf,
         canonical_workdir: PathBuf,
         dirty_files: Option<HashMap<String, String>>,
+        lock: PathBuf,
     ) -> Self {
         let initial_file = dir.join("INITIAL");
         Self {
@@ -177,6 +205,7 @@ impl PersistedWorkingLog {
             canonical_workdir,
             dirty_files,
             initial_file,
+            lock,
         }
     }
 
@@ -195,6 +224,7 @@ impl PersistedWorkingLog {
     }
 
     pub fn reset_working_log(&self) -> Result<(), GitAiError> {
+        let _lock = repo_lock::lock(&self.lock)?;
         // Clear all blobs by removing the blobs directory
         let blobs_dir = self.dir.join("blobs");
         if blobs_dir.exists() {
@@ -312,6 +342,9 @@ impl PersistedWorkingLog {
 
     /* append checkpoint */
     pub fn append_checkpoint(&self, checkpoint: &Checkpoint) -> Result<(), GitAiError> {
+        // Held from the read to the write so concurrent appends don't drop each other's checkpoint
+        let _lock = repo_lock::lock(&self.lock)?;
+
         // Read existing checkpoints
         let mut checkpoints = self.read_all_checkpoints().unwrap_or_default();
 
@@ -477,6 +510,7 @@ impl PersistedWorkingLog {
     /// by post-commit after transcripts have been refetched and need to be preserved
     /// for from_just_working_log() to read them.
     pub fn write_all_checkpoints(&self, checkpoints: &[Checkpoint]) -> Result<(), GitAiError> {
+        let _lock = repo_lock::lock(&self.lock)?;
         let checkpoints_file = self.dir.join("checkpoints.jsonl");
 
         // Serialize all checkpoints to JSONL
@@ -555,6 +589,7 @@ impl PersistedWorkingLog {
         };
 
         let json = serde_json::to_string_pretty(&initial_data)?;
+        let _lock = repo_lock::lock(&self.lock)?;
         fs::write(&self.initial_file, json)?;
 
         Ok(())
@@ -843,6 +843,8 @@ impl<'a> Iterator for References<'a> {
 pub struct Repository {
     global_args: Vec<String>,
     git_dir: PathBuf,
+    /// Where objects, refs and config live; differs from git_dir in linked worktrees
+    common_dir: PathBuf,
     pub storage: RepoStorage,
     pub pre_command_base_commit: Option<String>,
     pub pre_command_refname: Option<String>,
@@ -957,6 +959,25 @@ impl Repository {
         self.git_dir.as_path()
     }
 
+    /// The directory shared by all worktrees of this repository (objects, refs, config, hooks).
+    /// Same as `path()` except in linked worktrees, whose `path()` is `.git/worktrees/<name>`.
+    pub fn common_dir(&self) -> &Path {
+        self.common_dir.as_path()
+    }
+
+    /// Working directories of this repository's checked-out submodules, not recursing into them
+    pub fn submodule_workdirs(&self) -> Result<Vec<PathBuf>, GitAiError> {
+        // Gitlinks are the index entries with mode 160000
+        let staged = self.git(&["ls-files", "--stage", "-z"])?;
+        Ok(staged
+            .split('\0')
+            .filter_map(|entry| entry.strip_prefix("160000 "))
+            .filter_map(|entry| entry.split_once('\t').map(|(_, path)| path))
+            .map(|path| self.workdir.join(path))
+            .filter(|dir| dir.join(".git").exists())
+            .collect())
+    }
+
     // Get the path of the working directory for this repository.
     // If this repository is bare, then None is returned.
     pub fn workdir(&self) -> Result<PathBuf, GitAiError> {
@@ -1032,9 +1053,31 @@ impl Repository {
         Ok(remotes)
     }
 
+    /// Whether this is a shallow clone (`git clone --depth`), so history past the shallow
+    /// boundary is missing locally.
+    pub fn is_shallow(&self) -> bool {
+        let mut args = self.global_args_for_exec();
+        args.push("rev-parse".to_string());
+        args.push("--is-shallow-repository".to_string());
+        exec_git(&args)
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim() == "true")
+            .unwrap_or(false)
+    }
+
+    /// Whether `remote_name` is the promisor remote of a partial clone (`git clone --filter`).
+    /// Objects fetched from it may be left out locally and fetched lazily, one request per
+    /// object, the first time they are read.
+    pub fn is_partial_clone_remote(&self, remote_name: &str) -> bool {
+        let config = |key: &str| self.config_get_str(key).ok().flatten().unwrap_or_default();
+        config(&format!("remote.{}.promisor", remote_name)) == "true"
+            || !config(&format!("remote.{}.partialclonefilter", remote_name)).is_empty()
+            || config("extensions.partialclone") == remote_name
+    }
+
     /// Get the git config file for this repository and fallback to global config if not found.
     fn get_git_config_file(&self) -> Result<gix_config::File<'static>, GitAiError> {
-        match gix_config::File::from_git_dir(self.path().to_path_buf()) {
+        // Linked worktrees have no config of their own
+        match gix_config::File::from_git_dir(self.common_dir.clone()) {
             Ok(git_config_file) => Ok(git_config_file),
             Err(e) => match gix_config::File::from_globals() {
                 Ok(system_config) => Ok(system_config),
@@ -1617,10 +1660,10 @@ impl Repository {
         use futures::future::join_all;
         use std::sync::Arc;
 
-        const MAX_CONCURRENT: usize = 30;
-
         let repo_global_args = self.global_args_for_exec();
-        let semaphore = Arc::new(smol::lock::Semaphore::new(MAX_CONCURRENT));
+        let semaphore = Arc::new(smol::lock::Semaphore::new(
+            config::Config::get().max_concurrency(),
+        ));
 
         let futures: Vec<_> = file_paths
             .iter()
@@ -1858,16 +1901,18 @@ pub fn find_repository(global_args: &Vec<String>) -> Result<Repository, GitAiErr
     // string "absolute-git-dir" instead of the resolved path).
     args.push("--git-dir".to_string());
     args.push("--show-toplevel".to_string());
+    args.push("--git-common-dir".to_string());
 
     let output = exec_git(&args)?;
     let both_dirs = String::from_utf8(output.stdout)?;
 
     let both_dirs = both_dirs.trim();
-    let lines: Vec<&str> = both_dirs.split("\n").collect();
+    // lines() also drops the \r some Windows git builds emit
+    let lines: Vec<&str> = both_dirs.lines().collect();
 
-    if lines.len() < 2 {
+    if lines.len() < 3 {
         return Err(GitAiError::Generic(format!(
-            "Expected git rev-parse to return 2 lines (git dir and work dir), got {}:\n{}",
+            "Expected git rev-parse to return 3 lines (git dir, work dir and common dir), got {}:\n{}",
             lines.len(),
             both_dirs
         )));
@@ -1882,6 +1927,22 @@ pub fn find_repository(global_args: &Vec<String>) -> Result<Repository, GitAiErr
     } else {
         PathBuf::from(git_dir_str)
     };
+    // --git-common-dir is relative to where git ran (the last -C), which may be a subdirectory
+    let common_dir = if Path::new(lines[2]).is_relative() {
+        let run_dir = global_args
+            .iter()
+            .rposition(|arg| arg == "-C")
+            .and_then(|i| global_args.get(i + 1))
+            .map(PathBuf::from)
+            .unwrap_or_default();
+        let common_dir = run_dir.join(lines[2]);
+        common_dir
+            .canonicalize()
+            .or_else(|_| std::path::absolute(&common_dir))
+            .unwrap_or(common_dir)
+    } else {
+        PathBuf::from(lines[2])
+    };
     if !git_dir.is_dir() {
         return Err(GitAiError::Generic(format!(
             "Git directory does not exist: {}",
@@ -1921,8 +1982,9 @@ pub fn find_repository(global_args: &Vec<String>) -> Result<Repository, GitAiErr
 
     Ok(Repository {
         global_args: global_args.clone(),
-        storage: RepoStorage::for_repo_path(&git_dir, &workdir),
+        storage: RepoStorage::for_worktree(&git_dir, &common_dir, &workdir),
         git_dir,
+        common_dir,
         pre_command_base_commit: None,
         pre_command_refname: None,
         pre_reset_target_commit: None,
@@ -1945,6 +2007,7 @@ pub fn from_bare_repository(git_dir: &Path) -> Result<Repository, GitAiError> {
         global_args,
         storage: RepoStorage::for_repo_path(git_dir, &workdir),
         git_dir: git_dir.to_path_buf(),
+        common_dir: git_dir.to_path_buf(),
         pre_command_base_commit: None,
         pre_command_refname: None,
         pre_reset_target_commit: None,
@@ -1954,7 +2017,9 @@ pub fn from_bare_repository(git_dir: &Path) -> Result<Repository, GitAiError> {
 }
 
 pub fn find_repository_in_path(path: &str) -> Result<Repository, GitAiError> {
-    let global_args = vec!["-C".to_string(), path.to_string()];
+    // Callers often pass canonicalized paths, and git rejects the \\?\ prefix those carry on
+    // Windows. Plain drive-letter (C:\repo) and UNC (\\server\share\repo) paths work as-is.
+    let global_args = vec!["-C".to_string(), crate::utils::strip_verbatim_prefix(path)];
     return find_repository(&global_args);
 }
 
@@ -2010,23 +2075,11 @@ pub fn find_repository_for_file(
             }
         }
 
-        // Check for .git directory or file (file for submodules/worktrees)
-        let git_path = dir.join(".git");
-        if git_path.exists() {
-            // Found a .git - but we need to check if this is a submodule
-            // Submodules have a .git file (not directory) that points to the parent's .git/modules
-            if git_path.is_file() {
-                // This is a submodule - read the file to check if it points to modules/
-                if let Ok(content) = std::fs::read_to_string(&git_path) {
-                    if content.contains("gitdir:") && content.contains("/modules/") {
-                        // This is a submodule, skip it and continue searching up
-                        current_dir = dir.parent();
-                        continue;
-                    }
-                }
-            }
-
-            // Found a real git repository, use find_repository_in_path
+        // A .git directory, or a .git file pointing at the git dir of a linked worktree
+        // (.git/worktrees/<name>) or a submodule (.git/modules/<name>). A submodule is a
+        // repository of its own: its commits, and so their notes, live in its git dir rather
+        // than the superproject's.
+        if dir.join(".git").exists() {
             return find_repository_in_path(&dir.to_string_lossy());
         }
 
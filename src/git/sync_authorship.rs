@@ -1,13 +1,66 @@
+use crate::authorship::authorship_log_serialization::AuthorshipLog;
+use crate::git::fetch_progress::{self, ChunkProgress};
+use crate::git::note_signing::{self, FetchVerification};
+use crate::git::notes_merge::merge_staged_notes;
+use crate::git::pending_pushes;
 use crate::git::refs::{
-    AI_AUTHORSHIP_PUSH_REFSPEC, copy_ref, merge_notes_from_ref, ref_exists, tracking_ref_for_remote,
+    AI_AUTHORSHIP_PUSH_REFSPEC, AI_AUTHORSHIP_REFNAME, SUMMARY_NOTES_PUSH_REFSPEC,
+    merge_summary_notes_from_ref, noted_commits, notes_add, ref_exists, show_authorship_note,
+    signatures_tracking_ref_for_remote, summary_tracking_ref_for_remote, tracking_ref_for_remote,
 };
+use crate::observability::spans::HookSpan;
 use crate::{
+    config::Config,
     error::GitAiError,
-    git::{cli_parser::ParsedGitInvocation, repository::exec_git},
+    git::{
+        cli_parser::ParsedGitInvocation,
+        repository::{exec_git, exec_git_stdin},
+    },
     utils::debug_log,
 };
 
 use super::repository::Repository;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::time::Duration;
+
+/// How far back the HEAD reflog is searched for commits that were rewritten
+const REBASED_NOTES_REFLOG_DEPTH: &str = "500";
+/// Upper bound on rewritten commits compared by patch-id in one reconciliation
+const REBASED_NOTES_MAX_NEW_COMMITS: &str = "1000";
+/// Fetch-merge-push rounds before giving up on a notes ref that keeps moving
+const NOTES_PUSH_ATTEMPTS: usize = 3;
+/// File under .git/ai whose mtime records the last notes fetch started from a git hook
+const NOTES_AUTO_FETCH_STAMP: &str = "last-notes-fetch";
+
+/// How refs/notes/ai is fetched from and pushed to remotes (`notes_transport`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotesTransport {
+    /// libgit2 when built with the `libgit2` feature, falling back to the git CLI if it fails
+    Auto,
+    /// Always the git CLI
+    Cli,
+    /// Only libgit2, with no fallback; the CLI when built without the feature
+    Libgit2,
+}
+
+impl NotesTransport {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "auto" => Some(NotesTransport::Auto),
+            "cli" => Some(NotesTransport::Cli),
+            "libgit2" => Some(NotesTransport::Libgit2),
+            _ => None,
+        }
+    }
+
+    pub fn configured() -> Self {
+        Config::get()
+            .notes_transport()
+            .and_then(NotesTransport::parse)
+            .unwrap_or(NotesTransport::Auto)
+    }
+}
 
 /// Result of checking for authorship notes on a remote
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -18,6 +71,282 @@ pub enum NotesExistence {
     NotFound,
 }
 
+/// Per-remote git config key that overrides URL-based provider detection, for self-hosted
+/// instances on custom domains (`git config remote.origin.gitaiProvider gitlab`)
+pub const REMOTE_PROVIDER_CONFIG_KEY: &str = "gitaiProvider";
+
+/// Hosting service behind a remote. Notes sync is plain git everywhere, but the servers
+/// differ in what they advertise and what they let a push update.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemoteProvider {
+    GitHub,
+    GitLab,
+    Bitbucket,
+    /// Any other SSH/HTTPS server, or a local path
+    Generic,
+}
+
+impl RemoteProvider {
+    /// Detect the provider from a remote URL (https://, ssh://, or scp-like user@host:path)
+    pub fn from_url(url: &str) -> Self {
+        let Some(host) = remote_url_host(url) else {
+            return RemoteProvider::Generic;
+        };
+        if host == "github.com" || host.ends_with(".ghe.com") {
+            RemoteProvider::GitHub
+        } else if host.contains("gitlab") {
+            RemoteProvider::GitLab
+        } else if host.contains("bitbucket") || remote_url_path(url).starts_with("scm/") {
+            // Bitbucket Data Center serves HTTPS clones under /scm/<project>/<repo>
+            RemoteProvider::Bitbucket
+        } else {
+            RemoteProvider::Generic
+        }
+    }
+
+    /// Parse a provider name as used in `remote.<name>.gitaiProvider`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "github" => Some(RemoteProvider::GitHub),
+            "gitlab" => Some(RemoteProvider::GitLab),
+            "bitbucket" => Some(RemoteProvider::Bitbucket),
+            "generic" => Some(RemoteProvider::Generic),
+            _ => None,
+        }
+    }
+
+    /// Provider for `remote_name`, which may be a configured remote or a URL/path
+    pub fn for_remote(repository: &Repository, remote_name: &str) -> Self {
+        let override_key = format!("remote.{}.{}", remote_name, REMOTE_PROVIDER_CONFIG_KEY);
+        if let Some(provider) = repository
+            .config_get_str(&override_key)
+            .ok()
+            .flatten()
+            .and_then(|name| Self::from_name(&name))
+        {
+            return provider;
+        }
+
+        let url = repository
+            .remotes_with_urls()
+            .ok()
+            .and_then(|remotes| {
+                remotes
+                    .into_iter()
+                    .find(|(name, _)| name == remote_name)
+                    .map(|(_, url)| url)
+            })
+            .unwrap_or_else(|| remote_name.to_string());
+        Self::from_url(&url)
+    }
+
+    /// Whether `ls-remote` can be trusted to list refs/notes/ai when it exists. Bitbucket
+    /// doesn't always advertise refs outside heads/tags but still serves them by name.
+    pub fn advertises_notes(&self) -> bool {
+        !matches!(self, RemoteProvider::Bitbucket)
+    }
+
+    /// Explain a rejected notes push, if `stderr` looks like the provider's ref protection
+    pub fn push_rejection_hint(&self, stderr: &str) -> Option<&'static str> {
+        let stderr = stderr.to_ascii_lowercase();
+        let declined = stderr.contains("pre-receive hook declined")
+            || stderr.contains("protected")
+            || stderr.contains("permission");
+        if !declined {
+            return None;
+        }
+        Some(self.notes_protection_hint())
+    }
+
+    /// How to let the provider accept pushes to refs/notes/*
+    pub fn notes_protection_hint(&self) -> &'static str {
+        match self {
+            RemoteProvider::GitHub => {
+                "a repository ruleset or push protection rejected refs/notes/ai; exclude refs/notes/* from it"
+            }
+            RemoteProvider::GitLab => {
+                "GitLab rejected refs/notes/ai; check the project's push rules and protected refs, or grant Developer access"
+            }
+            RemoteProvider::Bitbucket => {
+                "Bitbucket rejected refs/notes/ai; allow it in the repository's branch permissions (pattern refs/notes/**)"
+            }
+            RemoteProvider::Generic => {
+                "the server's hooks rejected refs/notes/ai; ask its administrator to allow pushes to refs/notes/*"
+            }
+        }
+    }
+}
+
+/// Lowercased host of a remote URL, without user info or port
+fn remote_url_host(url: &str) -> Option<String> {
+    let url = url.trim();
+    let authority = if let Some((_, rest)) = url.split_once("://") {
+        rest.split('/').next()?
+    } else if !url.starts_with('/') && url.contains(':') {
+        // scp-like: [user@]host:path
+        url.split(':').next()?
+    } else {
+        return None;
+    };
+    let host = authority.rsplit('@').next()?;
+    let host = host.split(':').next()?;
+    if host.is_empty() {
+        return None;
+    }
+    Some(host.to_ascii_lowercase())
+}
+
+/// Repository path of a remote URL, without leading slashes
+fn remote_url_path(url: &str) -> &str {
+    let url = url.trim();
+    let path = if let Some((_, rest)) = url.split_once("://") {
+        rest.split_once('/').map(|(_, path)| path).unwrap_or("")
+    } else {
+        url.split_once(':').map(|(_, path)| path).unwrap_or(url)
+    };
+    path.trim_start_matches('/')
+}
+
+/// Git config key choosing which remotes a push keeps refs/notes/ai in sync with
+/// (`git config git-ai.syncRemotes all`)
+pub const SYNC_REMOTES_CONFIG_KEY: &str = "git-ai.syncRemotes";
+
+/// Which remotes hold authorship notes, from `git-ai.syncRemotes` or else `notes_sync_remotes`
+/// in git-ai.toml
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NotesSyncPolicy {
+    /// Notes follow each push to whichever remote was pushed (the default)
+    PushedRemote,
+    /// Every configured remote; a push to any of them updates all of them
+    All,
+    /// Only the canonical remote: `upstream` if configured, otherwise the default remote.
+    /// Pushes to forks and mirrors leave their notes alone.
+    UpstreamOnly,
+    /// The named remotes; a push to one of them updates all of them
+    Remotes(Vec<String>),
+}
+
+impl NotesSyncPolicy {
+    /// Parse a `git-ai.syncRemotes` value: `all`, `upstream-only`, `pushed`, or a comma or
+    /// space separated list of remote names
+    pub fn parse(value: &str) -> Self {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "" | "pushed" => NotesSyncPolicy::PushedRemote,
+            "all" => NotesSyncPolicy::All,
+            "upstream-only" => NotesSyncPolicy::UpstreamOnly,
+            _ => NotesSyncPolicy::Remotes(
+                value
+                    .split(|c: char| c == ',' || c.is_whitespace())
+                    .filter(|name| !name.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+            ),
+        }
+    }
+
+    pub fn for_repository(repository: &Repository) -> Self {
+        repository
+            .config_get_str(SYNC_REMOTES_CONFIG_KEY)
+            .ok()
+            .flatten()
+            .or_else(|| Config::get().notes_sync_remotes().map(str::to_string))
+            .map(|value| Self::parse(&value))
+            .unwrap_or(NotesSyncPolicy::PushedRemote)
+    }
+
+    /// Remotes whose notes should be reconciled after a push to `pushed_remote`, starting
+    /// with `pushed_remote` itself. Empty when the policy keeps notes off that remote.
+    pub fn push_targets(&self, repository: &Repository, pushed_remote: &str) -> Vec<String> {
+        let group = match self {
+            NotesSyncPolicy::PushedRemote => return vec![pushed_remote.to_string()],
+            NotesSyncPolicy::All => configured_remotes(repository),
+            NotesSyncPolicy::UpstreamOnly => canonical_remote(repository).into_iter().collect(),
+            NotesSyncPolicy::Remotes(names) => names.clone(),
+        };
+        if !group.iter().any(|name| name == pushed_remote) {
+            return Vec::new();
+        }
+        let mut targets = vec![pushed_remote.to_string()];
+        targets.extend(group.into_iter().filter(|name| name != pushed_remote));
+        targets
+    }
+
+    /// Remotes `git ai sync` reconciles when none are named on the command line
+    pub fn sync_targets(&self, repository: &Repository) -> Vec<String> {
+        match self {
+            NotesSyncPolicy::PushedRemote => repository
+                .upstream_remote()
+                .ok()
+                .flatten()
+                .or_else(|| repository.get_default_remote().ok().flatten())
+                .into_iter()
+                .collect(),
+            NotesSyncPolicy::All => configured_remotes(repository),
+            NotesSyncPolicy::UpstreamOnly => canonical_remote(repository).into_iter().collect(),
+            NotesSyncPolicy::Remotes(names) => names.clone(),
+        }
+    }
+}
+
+/// Names of all configured remotes
+pub fn configured_remotes(repository: &Repository) -> Vec<String> {
+    repository
+        .remotes()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+/// The remote the current branch tracks, else origin or the first remote
+pub fn tracked_remote(repository: &Repository) -> Option<String> {
+    // Both lookups report a missing remote as an empty name rather than None
+    repository
+        .upstream_remote()
+        .ok()
+        .flatten()
+        .filter(|remote| !remote.is_empty())
+        .or_else(|| repository.get_default_remote().ok().flatten())
+        .filter(|remote| !remote.is_empty())
+}
+
+/// The remote to fetch notes from after a checkout, merge or rebase, if one is due:
+/// `notes_auto_fetch_minutes` must be on and have passed since the last automatic fetch.
+/// The time is recorded before returning, so a failing fetch isn't retried on every checkout.
+pub fn notes_auto_fetch_due(repository: &Repository) -> Option<String> {
+    let interval = Duration::from_secs(Config::get().notes_auto_fetch_minutes()? * 60);
+    let stamp = repository
+        .storage
+        .working_logs
+        .parent()?
+        .join(NOTES_AUTO_FETCH_STAMP);
+    let recently_fetched = fs::metadata(&stamp)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|elapsed| elapsed < interval);
+    if recently_fetched {
+        return None;
+    }
+    let remote = tracked_remote(repository)?;
+    if let Err(e) = fs::write(&stamp, "") {
+        debug_log(&format!("failed to write {}: {}", stamp.display(), e));
+        return None;
+    }
+    Some(remote)
+}
+
+/// The remote a fork workflow treats as canonical: `upstream` if configured, else the default
+fn canonical_remote(repository: &Repository) -> Option<String> {
+    if configured_remotes(repository)
+        .iter()
+        .any(|name| name == "upstream")
+    {
+        return Some("upstream".to_string());
+    }
+    repository.get_default_remote().ok().flatten()
+}
+
 pub fn fetch_remote_from_args(
     repository: &Repository,
     parsed_args: &ParsedGitInvocation,
@@ -57,6 +386,15 @@ pub fn fetch_remote_from_args(
 pub fn fetch_authorship_notes(
     repository: &Repository,
     remote_name: &str,
+) -> Result<NotesExistence, GitAiError> {
+    let mut span = HookSpan::start("notes.fetch");
+    span.set_attribute("git.remote", remote_name);
+    span.record_result(fetch_authorship_notes_inner(repository, remote_name))
+}
+
+fn fetch_authorship_notes_inner(
+    repository: &Repository,
+    remote_name: &str,
 ) -> Result<NotesExistence, GitAiError> {
     // Generate tracking ref for this remote
     let tracking_ref = tracking_ref_for_remote(&remote_name);
@@ -66,8 +404,366 @@ pub fn fetch_authorship_notes(
         remote_name, tracking_ref
     ));
 
+    let provider = RemoteProvider::for_remote(repository, remote_name);
+    debug_log(&format!(
+        "remote '{}' detected as {:?}",
+        remote_name, provider
+    ));
+
+    match try_libgit2_fetch(repository, remote_name, &tracking_ref) {
+        Some(Ok(NotesExistence::NotFound)) => return Ok(NotesExistence::NotFound),
+        Some(Ok(NotesExistence::Found)) => {}
+        Some(Err(e)) => return Err(e),
+        None => {
+            if fetch_notes_with_cli(repository, remote_name, &tracking_ref, provider)?
+                == NotesExistence::NotFound
+            {
+                return Ok(NotesExistence::NotFound);
+            }
+        }
+    }
+
+    prefetch_note_blobs_if_partial(repository, remote_name, &tracking_ref);
+
+    let signatures_ref = signatures_tracking_ref_for_remote(remote_name);
+    let signatures_fetched = note_signing::signatures_in_use(repository)
+        && fetch_signature_notes(repository, remote_name, &signatures_ref);
+    if !note_signing::verify_fetched_notes(
+        repository,
+        remote_name,
+        &tracking_ref,
+        &signatures_ref,
+        FetchVerification::for_repository(repository),
+    ) {
+        return Ok(NotesExistence::Found);
+    }
+
+    // After successful fetch, merge the tracking ref into refs/notes/ai
+    if let Err(e) = merge_tracking_notes(repository, &tracking_ref) {
+        // Don't fail on merge errors, just log and continue
+        debug_log(&format!("notes merge failed: {}", e));
+    }
+    if signatures_fetched
+        && let Err(e) = note_signing::merge_signatures_from_ref(repository, &signatures_ref)
+    {
+        debug_log(&format!("signature notes merge failed: {}", e));
+    }
+
+    Ok(NotesExistence::Found)
+}
+
+/// Fetch the remote's refs/notes/ai into `tracking_ref` with `git fetch`, checking with
+/// ls-remote first on providers that advertise notes refs
+fn fetch_notes_with_cli(
+    repository: &Repository,
+    remote_name: &str,
+    tracking_ref: &str,
+    provider: RemoteProvider,
+) -> Result<NotesExistence, GitAiError> {
     // First, check if the remote has refs/notes/ai using ls-remote
     // This is important for bare repos where the refmap might not be configured
+    if provider.advertises_notes() {
+        match remote_has_notes(repository, remote_name)? {
+            NotesExistence::Found => {}
+            NotesExistence::NotFound => return Ok(NotesExistence::NotFound),
+        }
+    }
+
+    // Now fetch the notes to the tracking ref with explicit refspec
+    let mut fetch_authorship = notes_fetch_args(repository, remote_name, tracking_ref);
+
+    debug_log(&format!("fetch command: {:?}", fetch_authorship));
+
+    let fetched = if fetch_progress::progress_enabled() {
+        fetch_authorship.insert(fetch_authorship.len() - 2, "--progress".to_string());
+        let heading = format!("fetching authorship notes from {}", remote_name);
+        fetch_progress::exec_git_with_progress(&fetch_authorship, &heading)
+    } else {
+        exec_git(&fetch_authorship)
+    };
+    match fetched {
+        Ok(output) => {
+            debug_log(&format!(
+                "fetch stdout: '{}'",
+                String::from_utf8_lossy(&output.stdout)
+            ));
+            debug_log(&format!(
+                "fetch stderr: '{}'",
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+        // Without an ls-remote check first, a missing notes ref only shows up here
+        Err(GitAiError::GitCliError { ref stderr, .. })
+            if !provider.advertises_notes() && stderr.contains("couldn't find remote ref") =>
+        {
+            debug_log(&format!(
+                "no authorship notes found on remote '{}', nothing to sync",
+                remote_name
+            ));
+            return Ok(NotesExistence::NotFound);
+        }
+        Err(e) => {
+            debug_log(&format!("authorship fetch failed: {}", e));
+            return Err(e);
+        }
+    }
+    Ok(NotesExistence::Found)
+}
+
+/// The transport to try libgit2 with, or None to use the CLI. `auto` leaves three cases to
+/// the CLI: libgit2 can't fetch with a blob filter, its shallow fetches leave history git
+/// can't traverse, and it writes to local-path remotes without running their hooks.
+#[cfg(feature = "libgit2")]
+fn libgit2_transport(repository: &Repository, remote_name: &str) -> Option<NotesTransport> {
+    match NotesTransport::configured() {
+        NotesTransport::Cli => None,
+        NotesTransport::Auto
+            if repository.is_shallow()
+                || repository.is_partial_clone_remote(remote_name)
+                || remote_is_local_path(repository, remote_name) =>
+        {
+            debug_log(&format!(
+                "syncing notes with '{}' through git: shallow, partial or local remote",
+                remote_name
+            ));
+            None
+        }
+        transport => Some(transport),
+    }
+}
+
+#[cfg(feature = "libgit2")]
+fn remote_is_local_path(repository: &Repository, remote_name: &str) -> bool {
+    let url = repository
+        .remotes_with_urls()
+        .ok()
+        .and_then(|remotes| {
+            remotes
+                .into_iter()
+                .find(|(name, _)| name == remote_name)
+                .map(|(_, url)| url)
+        })
+        .unwrap_or_else(|| remote_name.to_string());
+    remote_url_host(&url).is_none()
+}
+
+/// Fetch with libgit2 if the configured transport says so. None means use the CLI, either by
+/// configuration or because libgit2 failed in `auto` mode.
+#[cfg(feature = "libgit2")]
+fn try_libgit2_fetch(
+    repository: &Repository,
+    remote_name: &str,
+    tracking_ref: &str,
+) -> Option<Result<NotesExistence, GitAiError>> {
+    let transport = libgit2_transport(repository, remote_name)?;
+    let source_ref = format!("refs/notes/{}", AI_AUTHORSHIP_REFNAME);
+    match super::libgit2_transport::fetch_ref(repository, remote_name, &source_ref, tracking_ref) {
+        Err(e) if transport == NotesTransport::Auto => {
+            debug_log(&format!("libgit2 notes fetch failed, using git: {}", e));
+            None
+        }
+        result => Some(result),
+    }
+}
+
+#[cfg(not(feature = "libgit2"))]
+fn try_libgit2_fetch(
+    _repository: &Repository,
+    _remote_name: &str,
+    _tracking_ref: &str,
+) -> Option<Result<NotesExistence, GitAiError>> {
+    None
+}
+
+/// Push refs/notes/ai with libgit2 if the configured transport says so. None means use the
+/// CLI. A non-fast-forward rejection is returned rather than retried with the CLI, since the
+/// caller merges and tries again.
+#[cfg(feature = "libgit2")]
+fn try_libgit2_push(repository: &Repository, remote_name: &str) -> Option<Result<(), GitAiError>> {
+    let transport = libgit2_transport(repository, remote_name)?;
+    match super::libgit2_transport::push_ref(repository, remote_name, AI_AUTHORSHIP_PUSH_REFSPEC) {
+        Err(e) if transport == NotesTransport::Auto && !is_push_race(&e) => {
+            debug_log(&format!("libgit2 notes push failed, using git: {}", e));
+            None
+        }
+        result => Some(result),
+    }
+}
+
+#[cfg(not(feature = "libgit2"))]
+fn try_libgit2_push(
+    _repository: &Repository,
+    _remote_name: &str,
+) -> Option<Result<(), GitAiError>> {
+    None
+}
+
+/// Fetch a remote's refs/notes/ai-signatures into `tracking_ref`. Returns false if the remote
+/// has no signatures; they are best-effort, so errors are only logged.
+fn fetch_signature_notes(repository: &Repository, remote_name: &str, tracking_ref: &str) -> bool {
+    let mut args: Vec<String> = repository.global_args_for_exec();
+    args.push("-c".to_string());
+    args.push("core.hooksPath=/dev/null".to_string());
+    args.push("fetch".to_string());
+    args.push("--no-tags".to_string());
+    args.push("--recurse-submodules=no".to_string());
+    args.push("--no-write-fetch-head".to_string());
+    args.push("--no-write-commit-graph".to_string());
+    args.push("--no-auto-maintenance".to_string());
+    args.push(remote_name.to_string());
+    args.push(format!(
+        "+refs/notes/{}:{}",
+        note_signing::SIGNATURES_REFNAME,
+        tracking_ref
+    ));
+    match exec_git(&args) {
+        Ok(_) => ref_exists(repository, tracking_ref),
+        Err(e) => {
+            debug_log(&format!("signature notes fetch failed: {}", e));
+            false
+        }
+    }
+}
+
+/// The internal fetch of a remote's refs/notes/ai into `tracking_ref`, with hooks disabled.
+///
+/// A shallow clone only has the last few commits, and each notes commit's tree already holds
+/// every note, so the first fetch takes just the remote's notes tip (`--depth=1`) instead of
+/// the whole notes history. Later fetches negotiate from that tip and only transfer what is
+/// new, which keeps the old tip an ancestor so merges stay fast-forwards.
+///
+/// Notes history shares no commits with branches, so only notes refs are offered as haves.
+/// Otherwise git walks every local branch during negotiation, which in a large repository
+/// takes longer than the transfer.
+fn notes_fetch_args(repository: &Repository, remote_name: &str, tracking_ref: &str) -> Vec<String> {
+    // IMPORTANT: use repository.global_args_for_exec() to ensure -C flag is present for bare repos
+    let mut args: Vec<String> = repository.global_args_for_exec();
+    args.push("-c".to_string());
+    args.push("core.hooksPath=/dev/null".to_string());
+    args.push("fetch".to_string());
+    args.push("--no-tags".to_string());
+    args.push("--recurse-submodules=no".to_string());
+    args.push("--no-write-fetch-head".to_string());
+    args.push("--no-write-commit-graph".to_string());
+    args.push("--no-auto-maintenance".to_string());
+    if repository.is_shallow() && !ref_exists(repository, tracking_ref) {
+        debug_log("shallow clone: fetching only the remote's notes tip");
+        args.push("--depth=1".to_string());
+    }
+    for tip in [tracking_ref, "refs/notes/ai"] {
+        if ref_exists(repository, tip) {
+            args.push(format!("--negotiation-tip={}", tip));
+        }
+    }
+    args.push(remote_name.to_string());
+    args.push(format!("+refs/notes/ai:{}", tracking_ref));
+    args
+}
+
+/// In a partial clone the notes fetch brings the notes commits and trees but leaves out the
+/// note blobs, which git would then fetch one request at a time as notes are read. Fetch the
+/// blobs for commits in the local history in batches of `notes_fetch_chunk_size` instead, and
+/// skip notes for commits this clone doesn't have. Each batch is kept once fetched, so an
+/// interrupted prefetch resumes where it stopped the next time notes are fetched.
+/// Best-effort: anything missed is still fetched lazily.
+fn prefetch_note_blobs_if_partial(repository: &Repository, remote_name: &str, notes_ref: &str) {
+    if !repository.is_partial_clone_remote(remote_name) {
+        return;
+    }
+    match prefetch_note_blobs(repository, remote_name, notes_ref) {
+        Ok(count) => debug_log(&format!(
+            "partial clone: prefetched {} note blobs from '{}'",
+            count, remote_name
+        )),
+        Err(e) => debug_log(&format!("partial clone: note blob prefetch failed: {}", e)),
+    }
+}
+
+fn prefetch_note_blobs(
+    repository: &Repository,
+    remote_name: &str,
+    notes_ref: &str,
+) -> Result<usize, GitAiError> {
+    // --missing=print lists the blobs we don't have without fetching them
+    let mut args = repository.global_args_for_exec();
+    args.push("rev-list".to_string());
+    args.push("--objects".to_string());
+    args.push("--no-walk".to_string());
+    args.push("--missing=print".to_string());
+    args.push(notes_ref.to_string());
+    let listing = String::from_utf8(exec_git(&args)?.stdout)?;
+    let missing: HashSet<&str> = listing
+        .lines()
+        .filter_map(|line| line.strip_prefix('?'))
+        .collect();
+    if missing.is_empty() {
+        return Ok(0);
+    }
+
+    let mut args = repository.global_args_for_exec();
+    args.push("rev-list".to_string());
+    args.push("HEAD".to_string());
+    let local_commits: HashSet<String> = match exec_git(&args) {
+        Ok(output) => String::from_utf8(output.stdout)?
+            .lines()
+            .map(str::to_string)
+            .collect(),
+        // No commits yet
+        Err(_) => return Ok(0),
+    };
+
+    let mut args = repository.global_args_for_exec();
+    args.push("ls-tree".to_string());
+    args.push("-r".to_string());
+    args.push(notes_ref.to_string());
+    let tree = String::from_utf8(exec_git(&args)?.stdout)?;
+    let wanted: Vec<&str> = tree
+        .lines()
+        .filter_map(|line| {
+            let (meta, path) = line.split_once('\t')?;
+            let blob = meta.split_whitespace().nth(2)?;
+            // Notes trees fan out large note sets into `ab/cdef...` directories
+            let commit = path.replace('/', "");
+            (missing.contains(blob) && local_commits.contains(&commit)).then_some(blob)
+        })
+        .collect();
+    if wanted.is_empty() {
+        return Ok(0);
+    }
+
+    let mut args = repository.global_args_for_exec();
+    args.push("-c".to_string());
+    args.push("core.hooksPath=/dev/null".to_string());
+    args.push("-c".to_string());
+    args.push("fetch.negotiationAlgorithm=noop".to_string());
+    args.push("fetch".to_string());
+    args.push("--no-tags".to_string());
+    args.push("--recurse-submodules=no".to_string());
+    args.push("--no-write-fetch-head".to_string());
+    args.push("--no-write-commit-graph".to_string());
+    args.push("--no-auto-maintenance".to_string());
+    args.push("--stdin".to_string());
+    args.push(remote_name.to_string());
+    let chunk_size = Config::get().notes_fetch_chunk_size().max(1);
+    let chunks = wanted.len().div_ceil(chunk_size);
+    let mut progress = ChunkProgress::new(
+        &format!("fetching note blobs from {}", remote_name),
+        wanted.len(),
+        chunks,
+    );
+    for chunk in wanted.chunks(chunk_size) {
+        let mut stdin = chunk.join("\n");
+        stdin.push('\n');
+        exec_git_stdin(&args, stdin.as_bytes())?;
+        progress.advance(chunk.len());
+    }
+    Ok(wanted.len())
+}
+/// Ask the remote whether it has refs/notes/ai via ls-remote
+fn remote_has_notes(
+    repository: &Repository,
+    remote_name: &str,
+) -> Result<NotesExistence, GitAiError> {
     let mut ls_remote_args = repository.global_args_for_exec();
     ls_remote_args.push("ls-remote".to_string());
     ls_remote_args.push(remote_name.to_string());
@@ -95,6 +791,7 @@ pub fn fetch_authorship_notes(
                 "found authorship notes on remote '{}'",
                 remote_name
             ));
+            Ok(NotesExistence::Found)
         }
         Err(e) => {
             debug_log(&format!(
@@ -102,155 +799,447 @@ pub fn fetch_authorship_notes(
                 remote_name, e
             ));
             // Return error instead of assuming no notes - we don't know the state
-            return Err(e);
+            Err(e)
         }
     }
+}
 
-    // Now fetch the notes to the tracking ref with explicit refspec
-    let fetch_refspec = format!("+refs/notes/ai:{}", tracking_ref);
+// for use with post-push hook
+// Failures are queued for retry, and a success clears the remote's queued push
+pub fn push_authorship_notes(repository: &Repository, remote_name: &str) -> Result<(), GitAiError> {
+    let mut span = HookSpan::start("notes.push");
+    span.set_attribute("git.remote", remote_name);
+    let result = push_authorship_notes_inner(repository, remote_name);
+    pending_pushes::record_push_outcome(repository, remote_name, &result);
+    span.record_result(result)
+}
 
-    // Build the internal authorship fetch with explicit flags and disabled hooks
-    // IMPORTANT: use repository.global_args_for_exec() to ensure -C flag is present for bare repos
-    let mut fetch_authorship: Vec<String> = repository.global_args_for_exec();
-    fetch_authorship.push("-c".to_string());
-    fetch_authorship.push("core.hooksPath=/dev/null".to_string());
-    fetch_authorship.push("fetch".to_string());
-    fetch_authorship.push("--no-tags".to_string());
-    fetch_authorship.push("--recurse-submodules=no".to_string());
-    fetch_authorship.push("--no-write-fetch-head".to_string());
-    fetch_authorship.push("--no-write-commit-graph".to_string());
-    fetch_authorship.push("--no-auto-maintenance".to_string());
-    fetch_authorship.push(remote_name.to_string());
-    fetch_authorship.push(fetch_refspec.clone());
+fn push_authorship_notes_inner(
+    repository: &Repository,
+    remote_name: &str,
+) -> Result<(), GitAiError> {
+    let tracking_ref = tracking_ref_for_remote(&remote_name);
 
-    debug_log(&format!("fetch command: {:?}", fetch_authorship));
+    let mut push_authorship: Vec<String> = repository.global_args_for_exec();
+    push_authorship.push("-c".to_string());
+    push_authorship.push("core.hooksPath=/dev/null".to_string());
+    push_authorship.push("push".to_string());
+    push_authorship.push("--quiet".to_string());
+    push_authorship.push("--no-recurse-submodules".to_string());
+    push_authorship.push("--no-verify".to_string());
+    push_authorship.push("--no-signed".to_string());
+    push_authorship.push(remote_name.to_string());
+    push_authorship.push(AI_AUTHORSHIP_PUSH_REFSPEC.to_string());
 
-    match exec_git(&fetch_authorship) {
-        Ok(output) => {
-            debug_log(&format!(
-                "fetch stdout: '{}'",
-                String::from_utf8_lossy(&output.stdout)
-            ));
+    let mut attempt = 1;
+    loop {
+        // STEP 1: Fetch remote notes into tracking ref and merge before pushing
+        // This ensures we don't lose notes from other branches/clones
+        // Fetch is best-effort; if it fails (e.g., no remote notes yet), continue
+        let fetched = match try_libgit2_fetch(repository, remote_name, &tracking_ref) {
+            Some(result) => matches!(result, Ok(NotesExistence::Found)),
+            None => {
+                // Rebuilt every round: a shallow clone only limits the depth of its first fetch
+                let fetch_before_push = notes_fetch_args(repository, remote_name, &tracking_ref);
+                debug_log(&format!(
+                    "pre-push authorship fetch: {:?}",
+                    &fetch_before_push
+                ));
+                exec_git(&fetch_before_push).is_ok()
+            }
+        };
+        if fetched {
+            prefetch_note_blobs_if_partial(repository, remote_name, &tracking_ref);
+            if let Err(e) = merge_tracking_notes(repository, &tracking_ref) {
+                debug_log(&format!("pre-push notes merge failed: {}", e));
+            }
+        }
+
+        // STEP 2: Push notes without force (requires fast-forward)
+        let pushed = match try_libgit2_push(repository, remote_name) {
+            Some(result) => result,
+            None => {
+                debug_log(&format!(
+                    "pushing authorship refs (no force): {:?}",
+                    &push_authorship
+                ));
+                exec_git(&push_authorship).map(|_| ())
+            }
+        };
+        let e = match pushed {
+            Ok(()) => break,
+            Err(e) => e,
+        };
+
+        // Another clone pushed notes between our fetch and push: merge theirs and try again
+        if attempt < NOTES_PUSH_ATTEMPTS && is_push_race(&e) {
             debug_log(&format!(
-                "fetch stderr: '{}'",
-                String::from_utf8_lossy(&output.stderr)
+                "authorship push raced another writer (attempt {}), re-merging",
+                attempt
             ));
+            attempt += 1;
+            continue;
         }
-        Err(e) => {
-            debug_log(&format!("authorship fetch failed: {}", e));
-            return Err(e);
+
+        // Best-effort; don't fail user operation due to authorship sync issues
+        debug_log(&format!("authorship push skipped due to error: {}", e));
+        if let GitAiError::GitCliError { stderr, .. } = &e
+            && let Some(hint) =
+                RemoteProvider::for_remote(repository, remote_name).push_rejection_hint(stderr)
+        {
+            return Err(GitAiError::Generic(format!("{} (hint: {})", e, hint)));
         }
+        return Err(e);
     }
 
-    // After successful fetch, merge the tracking ref into refs/notes/ai
-    let local_notes_ref = "refs/notes/ai";
+    if Config::get().mirror_summary_notes()
+        && let Err(e) = push_summary_notes(repository, remote_name)
+    {
+        debug_log(&format!("summary notes push skipped due to error: {}", e));
+    }
+    if let Err(e) = push_signature_notes(repository, remote_name) {
+        debug_log(&format!("signature notes push skipped due to error: {}", e));
+    }
 
-    if crate::git::refs::ref_exists(&repository, &tracking_ref) {
-        if crate::git::refs::ref_exists(&repository, local_notes_ref) {
-            // Both exist - merge them
-            debug_log(&format!(
-                "merging authorship notes from {} into {}",
-                tracking_ref, local_notes_ref
-            ));
-            if let Err(e) = merge_notes_from_ref(&repository, &tracking_ref) {
-                debug_log(&format!("notes merge failed: {}", e));
-                // Don't fail on merge errors, just log and continue
-            }
-        } else {
-            // Only tracking ref exists - copy it to local
-            debug_log(&format!(
-                "initializing {} from tracking ref {}",
-                local_notes_ref, tracking_ref
-            ));
-            if let Err(e) = copy_ref(&repository, &tracking_ref, local_notes_ref) {
-                debug_log(&format!("notes copy failed: {}", e));
-                // Don't fail on copy errors, just log and continue
-            }
-        }
-    } else {
+    Ok(())
+}
+
+/// Result of reconciling refs/notes/ai with one remote
+#[derive(Debug)]
+pub struct RemoteSyncOutcome {
+    pub remote: String,
+    pub fetch: Result<NotesExistence, GitAiError>,
+    /// `None` when there was nothing to push because no remote or local notes exist
+    pub push: Option<Result<(), GitAiError>>,
+}
+
+/// Bring refs/notes/ai on every remote in `remotes` up to date with the union of all of them:
+/// fetch and merge each remote's notes first, then push the merged ref back to each.
+pub fn sync_notes_with_remotes(
+    repository: &Repository,
+    remotes: &[String],
+) -> Vec<RemoteSyncOutcome> {
+    let mut outcomes: Vec<RemoteSyncOutcome> = remotes
+        .iter()
+        .map(|remote| RemoteSyncOutcome {
+            remote: remote.clone(),
+            fetch: fetch_authorship_notes(repository, remote),
+            push: None,
+        })
+        .collect();
+
+    let local_ref = format!("refs/notes/{}", AI_AUTHORSHIP_REFNAME);
+    if !ref_exists(repository, &local_ref) {
+        return outcomes;
+    }
+    for outcome in &mut outcomes {
+        outcome.push = Some(push_authorship_notes(repository, &outcome.remote));
+    }
+    outcomes
+}
+
+/// Merge a remote's notes, fetched into `tracking_ref`, into refs/notes/ai
+fn merge_tracking_notes(repository: &Repository, tracking_ref: &str) -> Result<(), GitAiError> {
+    let mut span = HookSpan::start("notes.merge");
+    span.set_attribute("git.notes_ref", tracking_ref);
+    span.record_result(merge_tracking_notes_inner(repository, tracking_ref))
+}
+
+fn merge_tracking_notes_inner(
+    repository: &Repository,
+    tracking_ref: &str,
+) -> Result<(), GitAiError> {
+    if !ref_exists(repository, tracking_ref) {
         debug_log(&format!(
             "tracking ref {} was not created after fetch",
             tracking_ref
         ));
+        return Ok(());
     }
+    let outcome = merge_staged_notes(repository, tracking_ref)?;
+    debug_log(&format!(
+        "merged authorship notes from {}: {:?}",
+        tracking_ref, outcome
+    ));
+    Ok(())
+}
 
-    Ok(NotesExistence::Found)
+/// Whether a push failed because the remote ref moved since we last fetched it
+fn is_non_fast_forward(stderr: &str) -> bool {
+    stderr.contains("non-fast-forward")
+        || stderr.contains("fetch first")
+        || stderr.contains("stale info")
 }
-// for use with post-push hook
-pub fn push_authorship_notes(repository: &Repository, remote_name: &str) -> Result<(), GitAiError> {
-    // STEP 1: Fetch remote notes into tracking ref and merge before pushing
-    // This ensures we don't lose notes from other branches/clones
-    let tracking_ref = tracking_ref_for_remote(&remote_name);
-    let fetch_refspec = format!("+refs/notes/ai:{}", tracking_ref);
-
-    let mut fetch_before_push: Vec<String> = repository.global_args_for_exec();
-    fetch_before_push.push("-c".to_string());
-    fetch_before_push.push("core.hooksPath=/dev/null".to_string());
-    fetch_before_push.push("fetch".to_string());
-    fetch_before_push.push("--no-tags".to_string());
-    fetch_before_push.push("--recurse-submodules=no".to_string());
-    fetch_before_push.push("--no-write-fetch-head".to_string());
-    fetch_before_push.push("--no-write-commit-graph".to_string());
-    fetch_before_push.push("--no-auto-maintenance".to_string());
-    fetch_before_push.push(remote_name.to_string());
-    fetch_before_push.push(fetch_refspec);
+
+/// `is_non_fast_forward` for a push error from either transport
+fn is_push_race(e: &GitAiError) -> bool {
+    match e {
+        GitAiError::GitCliError { stderr, .. } => is_non_fast_forward(stderr),
+        GitAiError::Generic(message) => is_non_fast_forward(message),
+        _ => false,
+    }
+}
+
+/// Push the human-readable mirror in refs/notes/commits, merging in the remote's copy first.
+/// Teammates without git-ai see it after `git fetch origin refs/notes/commits:refs/notes/commits`.
+fn push_summary_notes(repository: &Repository, remote_name: &str) -> Result<(), GitAiError> {
+    let local_ref = "refs/notes/commits";
+    if !ref_exists(repository, local_ref) {
+        return Ok(());
+    }
+
+    let tracking_ref = summary_tracking_ref_for_remote(remote_name);
+    let mut fetch_args: Vec<String> = repository.global_args_for_exec();
+    fetch_args.push("-c".to_string());
+    fetch_args.push("core.hooksPath=/dev/null".to_string());
+    fetch_args.push("fetch".to_string());
+    fetch_args.push("--no-tags".to_string());
+    fetch_args.push("--recurse-submodules=no".to_string());
+    fetch_args.push("--no-write-fetch-head".to_string());
+    fetch_args.push("--no-write-commit-graph".to_string());
+    fetch_args.push("--no-auto-maintenance".to_string());
+    fetch_args.push(remote_name.to_string());
+    fetch_args.push(format!("+{}:{}", local_ref, tracking_ref));
+
+    // The remote may not have summary notes yet
+    if exec_git(&fetch_args).is_ok() && ref_exists(repository, &tracking_ref) {
+        merge_summary_notes_from_ref(repository, &tracking_ref)?;
+    }
+
+    let mut push_args: Vec<String> = repository.global_args_for_exec();
+    push_args.push("-c".to_string());
+    push_args.push("core.hooksPath=/dev/null".to_string());
+    push_args.push("push".to_string());
+    push_args.push("--quiet".to_string());
+    push_args.push("--no-recurse-submodules".to_string());
+    push_args.push("--no-verify".to_string());
+    push_args.push("--no-signed".to_string());
+    push_args.push(remote_name.to_string());
+    push_args.push(SUMMARY_NOTES_PUSH_REFSPEC.to_string());
 
     debug_log(&format!(
-        "pre-push authorship fetch: {:?}",
-        &fetch_before_push
+        "pushing summary notes (no force): {:?}",
+        &push_args
     ));
+    exec_git(&push_args)?;
+    Ok(())
+}
+
+/// Push refs/notes/ai-signatures next to refs/notes/ai, merging in the remote's copy first
+fn push_signature_notes(repository: &Repository, remote_name: &str) -> Result<(), GitAiError> {
+    let local_ref = format!("refs/notes/{}", note_signing::SIGNATURES_REFNAME);
+    if !ref_exists(repository, &local_ref) {
+        return Ok(());
+    }
 
-    // Fetch is best-effort; if it fails (e.g., no remote notes yet), continue
-    if exec_git(&fetch_before_push).is_ok() {
-        // Merge fetched notes into local refs/notes/ai
-        let local_notes_ref = "refs/notes/ai";
+    let tracking_ref = signatures_tracking_ref_for_remote(remote_name);
+    if fetch_signature_notes(repository, remote_name, &tracking_ref) {
+        note_signing::merge_signatures_from_ref(repository, &tracking_ref)?;
+    }
 
-        if ref_exists(repository, &tracking_ref) {
-            if ref_exists(repository, local_notes_ref) {
-                // Both exist - merge them
-                debug_log(&format!(
-                    "pre-push: merging {} into {}",
-                    tracking_ref, local_notes_ref
-                ));
-                if let Err(e) = merge_notes_from_ref(repository, &tracking_ref) {
-                    debug_log(&format!("pre-push notes merge failed: {}", e));
-                }
-            } else {
-                // Only tracking ref exists - copy it to local
+    let mut push_args: Vec<String> = repository.global_args_for_exec();
+    push_args.push("-c".to_string());
+    push_args.push("core.hooksPath=/dev/null".to_string());
+    push_args.push("push".to_string());
+    push_args.push("--quiet".to_string());
+    push_args.push("--no-recurse-submodules".to_string());
+    push_args.push("--no-verify".to_string());
+    push_args.push("--no-signed".to_string());
+    push_args.push(remote_name.to_string());
+    push_args.push(note_signing::SIGNATURES_PUSH_REFSPEC.to_string());
+
+    debug_log(&format!(
+        "pushing signature notes (no force): {:?}",
+        &push_args
+    ));
+    exec_git(&push_args)?;
+    Ok(())
+}
+
+/// A noted commit that was rewritten (e.g. by `git pull --rebase`) into `new_sha`,
+/// matched by patch-id.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RebasedNote {
+    pub old_sha: String,
+    pub new_sha: String,
+}
+
+/// Find noted commits that HEAD used to contain (per the reflog) but that were rewritten into
+/// commits with the same patch-id and are no longer reachable from any local branch or tag.
+/// Their notes are orphaned once the rewritten branch is pushed to `remote_name`.
+pub fn find_rebased_notes(
+    repository: &Repository,
+    remote_name: &str,
+) -> Result<Vec<RebasedNote>, GitAiError> {
+    let noted = noted_commits(repository)?;
+    if noted.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // A fresh clone has no reflog yet
+    let reflog = repository
+        .git(&[
+            "reflog",
+            "show",
+            "--format=%H",
+            "-n",
+            REBASED_NOTES_REFLOG_DEPTH,
+            "HEAD",
+        ])
+        .unwrap_or_default();
+    let mut seen = HashSet::new();
+    let candidates: Vec<String> = reflog
+        .lines()
+        .map(|line| line.trim().to_string())
+        .filter(|sha| noted.contains(sha) && seen.insert(sha.clone()))
+        .collect();
+    if candidates.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // The remote-tracking ref for the branch being pushed still points at the pre-rebase
+    // commits; every other ref keeps a commit (and its note) alive.
+    let mut args: Vec<String> = candidates.clone();
+    args.extend(["--not", "--branches", "--tags"].map(String::from));
+    if let Ok(branch) = repository.head().and_then(|head| head.shorthand()) {
+        args.push(format!("--exclude={}/{}", remote_name, branch));
+    }
+    args.push("--remotes".to_string());
+    let mut rev_list = vec!["rev-list".to_string()];
+    rev_list.extend(args);
+    let unreachable: HashSet<String> = repository
+        .git(&rev_list.iter().map(String::as_str).collect::<Vec<_>>())?
+        .lines()
+        .map(|line| line.trim().to_string())
+        .collect();
+    let stale: Vec<String> = candidates
+        .into_iter()
+        .filter(|sha| unreachable.contains(sha))
+        .collect();
+    if stale.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut new_commits_args = vec![
+        "rev-list",
+        "--no-merges",
+        "--max-count",
+        REBASED_NOTES_MAX_NEW_COMMITS,
+        "HEAD",
+        "--not",
+    ];
+    new_commits_args.extend(stale.iter().map(String::as_str));
+    let new_commits: Vec<String> = repository
+        .git(&new_commits_args)?
+        .lines()
+        .map(|line| line.trim().to_string())
+        .collect();
+    if new_commits.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // rev-list is newest first; keep the newest rewrite of each patch
+    let mut new_by_patch_id: HashMap<String, String> = HashMap::new();
+    for (patch_id, sha) in patch_ids(repository, &new_commits)? {
+        new_by_patch_id.entry(patch_id).or_insert(sha);
+    }
+
+    Ok(patch_ids(repository, &stale)?
+        .into_iter()
+        .filter_map(|(patch_id, old_sha)| {
+            new_by_patch_id.get(&patch_id).map(|new_sha| RebasedNote {
+                old_sha,
+                new_sha: new_sha.clone(),
+            })
+        })
+        .collect())
+}
+
+/// Move each rebased note onto its rewritten commit and drop it from the pre-rebase commit.
+/// The rewritten commit's own note wins when it has one (the rebase hooks normally write it);
+/// otherwise the old note is carried over only if the two patches touch identical line
+/// numbers, so attestations stay accurate. Returns how many old notes were retired.
+pub fn retire_rebased_notes(
+    repository: &Repository,
+    rebased: &[RebasedNote],
+) -> Result<usize, GitAiError> {
+    let notes_ref = format!("--ref={}", AI_AUTHORSHIP_REFNAME);
+    let mut retired = 0;
+
+    for pair in rebased {
+        if show_authorship_note(repository, &pair.new_sha).is_none() {
+            let Some(content) = show_authorship_note(repository, &pair.old_sha) else {
+                continue;
+            };
+            if patch_body(repository, &pair.old_sha)? != patch_body(repository, &pair.new_sha)? {
                 debug_log(&format!(
-                    "pre-push: initializing {} from {}",
-                    local_notes_ref, tracking_ref
+                    "keeping note on {}: rewritten commit {} has shifted hunks",
+                    pair.old_sha, pair.new_sha
                 ));
-                if let Err(e) = copy_ref(repository, &tracking_ref, local_notes_ref) {
-                    debug_log(&format!("pre-push notes copy failed: {}", e));
-                }
+                continue;
             }
+            let Ok(mut log) = AuthorshipLog::deserialize_from_string(&content) else {
+                continue;
+            };
+            log.metadata.base_commit_sha = pair.new_sha.clone();
+            let serialized = log
+                .serialize_to_string()
+                .map_err(|_| GitAiError::Generic("Failed to serialize authorship log".into()))?;
+            notes_add(repository, &pair.new_sha, &serialized)?;
         }
+
+        repository.git(&[
+            "notes",
+            &notes_ref,
+            "remove",
+            "--ignore-missing",
+            &pair.old_sha,
+        ])?;
+        debug_log(&format!(
+            "retired note on {} (rewritten as {})",
+            pair.old_sha, pair.new_sha
+        ));
+        retired += 1;
     }
 
-    // STEP 2: Push notes without force (requires fast-forward)
-    let mut push_authorship: Vec<String> = repository.global_args_for_exec();
-    push_authorship.push("-c".to_string());
-    push_authorship.push("core.hooksPath=/dev/null".to_string());
-    push_authorship.push("push".to_string());
-    push_authorship.push("--quiet".to_string());
-    push_authorship.push("--no-recurse-submodules".to_string());
-    push_authorship.push("--no-verify".to_string());
-    push_authorship.push("--no-signed".to_string());
-    push_authorship.push(remote_name.to_string());
-    push_authorship.push(AI_AUTHORSHIP_PUSH_REFSPEC.to_string());
+    Ok(retired)
+}
 
-    debug_log(&format!(
-        "pushing authorship refs (no force): {:?}",
-        &push_authorship
-    ));
-    if let Err(e) = exec_git(&push_authorship) {
-        // Best-effort; don't fail user operation due to authorship sync issues
-        debug_log(&format!("authorship push skipped due to error: {}", e));
-        return Err(e);
-    }
+/// Stable patch-ids for `commits`, as (patch_id, commit_sha) pairs. Empty commits have none.
+fn patch_ids(
+    repository: &Repository,
+    commits: &[String],
+) -> Result<Vec<(String, String)>, GitAiError> {
+    let mut args = vec![
+        "log",
+        "--no-walk=unsorted",
+        "-p",
+        "--no-color",
+        "--format=commit %H",
+    ];
+    args.extend(commits.iter().map(String::as_str));
+    let patches = repository.git(&args)?;
 
-    Ok(())
+    let mut patch_id_args = repository.global_args_for_exec();
+    patch_id_args.push("patch-id".to_string());
+    patch_id_args.push("--stable".to_string());
+    let output = crate::git::repository::exec_git_stdin(&patch_id_args, patches.as_bytes())?;
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.split_once(' '))
+        .map(|(patch_id, sha)| (patch_id.to_string(), sha.trim().to_string()))
+        .collect())
+}
+
+/// A commit's diff without blob ids, for comparing hunks position-for-position
+fn patch_body(repository: &Repository, commit_sha: &str) -> Result<String, GitAiError> {
+    let diff = repository.git(&["show", "--format=", "-p", "--no-color", commit_sha])?;
+    Ok(diff
+        .lines()
+        .filter(|line| !line.starts_with("index "))
+        .collect::<Vec<_>>()
+        .join("\n"))
 }
 
 fn extract_remote_from_fetch_args(args: &[String]) -> Option<String> {
@@ -303,3 +1292,119 @@ fn extract_remote_from_fetch_args(args: &[String]) -> Option<String> {
 
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notes_transport_parse_and_push_race() {
+        assert_eq!(NotesTransport::parse("auto"), Some(NotesTransport::Auto));
+        assert_eq!(NotesTransport::parse(" CLI "), Some(NotesTransport::Cli));
+        assert_eq!(
+            NotesTransport::parse("libgit2"),
+            Some(NotesTransport::Libgit2)
+        );
+        assert_eq!(NotesTransport::parse("ssh"), None);
+
+        assert!(is_push_race(&GitAiError::Generic(
+            "libgit2: refs/notes/ai rejected (non-fast-forward)".to_string()
+        )));
+        assert!(!is_push_race(&GitAiError::Generic(
+            "libgit2: authentication required".to_string()
+        )));
+    }
+
+    #[test]
+    fn test_remote_provider_from_url() {
+        let cases = [
+            ("https://github.com/org/repo.git", RemoteProvider::GitHub),
+            ("git@github.com:org/repo.git", RemoteProvider::GitHub),
+            ("https://acme.ghe.com/org/repo", RemoteProvider::GitHub),
+            (
+                "https://gitlab.com/group/sub/repo.git",
+                RemoteProvider::GitLab,
+            ),
+            (
+                "ssh://git@gitlab.example.com:2222/group/repo.git",
+                RemoteProvider::GitLab,
+            ),
+            ("git@bitbucket.org:team/repo.git", RemoteProvider::Bitbucket),
+            (
+                "https://user@bitbucket.org/team/repo.git",
+                RemoteProvider::Bitbucket,
+            ),
+            (
+                "https://git.example.com/scm/proj/repo.git",
+                RemoteProvider::Bitbucket,
+            ),
+            (
+                "https://git.example.com/org/repo.git",
+                RemoteProvider::Generic,
+            ),
+            ("/srv/git/repo.git", RemoteProvider::Generic),
+            ("../upstream", RemoteProvider::Generic),
+        ];
+        for (url, expected) in cases {
+            assert_eq!(RemoteProvider::from_url(url), expected, "{}", url);
+        }
+    }
+
+    #[test]
+    fn test_notes_sync_policy_parse() {
+        assert_eq!(NotesSyncPolicy::parse(""), NotesSyncPolicy::PushedRemote);
+        assert_eq!(NotesSyncPolicy::parse("ALL"), NotesSyncPolicy::All);
+        assert_eq!(
+            NotesSyncPolicy::parse("upstream-only"),
+            NotesSyncPolicy::UpstreamOnly
+        );
+        assert_eq!(
+            NotesSyncPolicy::parse("origin, mirror backup"),
+            NotesSyncPolicy::Remotes(vec![
+                "origin".to_string(),
+                "mirror".to_string(),
+                "backup".to_string()
+            ])
+        );
+    }
+
+    #[test]
+    fn test_remote_provider_from_name() {
+        assert_eq!(
+            RemoteProvider::from_name("GitLab"),
+            Some(RemoteProvider::GitLab)
+        );
+        assert_eq!(RemoteProvider::from_name("gitea"), None);
+    }
+
+    #[test]
+    fn test_bitbucket_does_not_trust_ls_remote() {
+        assert!(!RemoteProvider::Bitbucket.advertises_notes());
+        assert!(RemoteProvider::GitLab.advertises_notes());
+        assert!(RemoteProvider::GitHub.advertises_notes());
+        assert!(RemoteProvider::Generic.advertises_notes());
+    }
+
+    #[test]
+    fn test_push_rejection_hint_is_provider_specific() {
+        let gitlab = "remote: GitLab: You are not allowed to push code to protected refs.\n\
+                      ! [remote rejected] refs/notes/ai -> refs/notes/ai (pre-receive hook declined)";
+        let hint = RemoteProvider::GitLab.push_rejection_hint(gitlab).unwrap();
+        assert!(hint.contains("GitLab"));
+
+        let bitbucket =
+            "! [remote rejected] refs/notes/ai -> refs/notes/ai (pre-receive hook declined)";
+        let hint = RemoteProvider::Bitbucket
+            .push_rejection_hint(bitbucket)
+            .unwrap();
+        assert!(hint.contains("branch permissions"));
+
+        // Ordinary failures (network, non-fast-forward) get no hint
+        let rejected = "! [rejected] refs/notes/ai -> refs/notes/ai (fetch first)";
+        assert_eq!(RemoteProvider::GitLab.push_rejection_hint(rejected), None);
+        assert_eq!(
+            RemoteProvider::Generic.push_rejection_hint("Could not resolve host"),
+            None
+        );
+    }
+}
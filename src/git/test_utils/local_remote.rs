@@ -0,0 +1,234 @@
+//! Network-free harness for exercising authorship note sync.
+//!
+//! Remotes are bare repositories on disk and clones talk to them over plain file paths, so
+//! the push/fetch/merge matrix (ahead, behind, diverged, forks) runs in an ordinary
+//! `cargo test` without credentials. This is the local counterpart to the GitHub CLI harness
+//! used by the integration tests, and is public so plugin authors can reuse it.
+//!
+//! ```ignore
+//! let remote = LocalRemote::new()?;
+//! let alice = remote.clone_repo()?;
+//! let sha = alice.commit("a.txt", "hello\n", "first")?;
+//! alice.add_note(&sha, "note")?;
+//! alice.push("main")?;
+//! alice.push_notes("origin")?;
+//! assert_eq!(remote.note(&sha).as_deref(), Some("note"));
+//! ```
+
+use super::create_unique_tmp_dir;
+use crate::error::GitAiError;
+use crate::git::refs::{notes_add, show_authorship_note};
+use crate::git::repository::{Repository as GitAiRepository, find_repository_in_path};
+use crate::git::sync_authorship::{
+    NotesExistence, NotesSyncPolicy, fetch_authorship_notes, push_authorship_notes,
+};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+#[allow(dead_code)]
+/// Branch every harness remote starts on, independent of the user's `init.defaultBranch`
+pub const LOCAL_REMOTE_DEFAULT_BRANCH: &str = "main";
+
+#[allow(dead_code)]
+/// A bare repository on disk standing in for a hosted remote
+pub struct LocalRemote {
+    path: PathBuf,
+}
+
+#[allow(dead_code)]
+/// A working clone of a [`LocalRemote`], with `origin` pointing at it
+pub struct LocalClone {
+    path: PathBuf,
+    repo: GitAiRepository,
+}
+
+#[allow(dead_code)]
+impl LocalRemote {
+    pub fn new() -> Result<Self, GitAiError> {
+        let path = create_unique_tmp_dir("git-ai-remote")?;
+        run_git(
+            &path,
+            &["init", "--bare", "-b", LOCAL_REMOTE_DEFAULT_BRANCH],
+        )?;
+        // Let clones use `--filter` and fetch single objects by id, as hosted remotes do
+        run_git(&path, &["config", "uploadpack.allowFilter", "true"])?;
+        run_git(&path, &["config", "uploadpack.allowAnySHA1InWant", "true"])?;
+        Ok(Self { path })
+    }
+
+    /// Create a second bare remote holding a copy of this one's branches and notes,
+    /// the way a fork on a hosting service starts out.
+    pub fn fork(&self) -> Result<Self, GitAiError> {
+        let path = create_unique_tmp_dir("git-ai-fork")?;
+        run_git(
+            &path,
+            &["clone", "--bare", "--quiet", self.url().as_str(), "."],
+        )?;
+        // `clone --bare` copies branches but not notes
+        if self.notes_tip().is_some() {
+            run_git(
+                &path,
+                &[
+                    "fetch",
+                    "--quiet",
+                    self.url().as_str(),
+                    "refs/notes/ai:refs/notes/ai",
+                ],
+            )?;
+        }
+        Ok(Self { path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// URL usable with `git remote add` and `git clone`
+    pub fn url(&self) -> String {
+        self.path.to_string_lossy().to_string()
+    }
+
+    /// `file://` URL, which git clones through the transport like a network remote. Plain
+    /// paths take a shortcut that ignores `--depth` and `--filter`.
+    pub fn file_url(&self) -> String {
+        format!("file://{}", self.url().replace('\\', "/"))
+    }
+
+    pub fn clone_repo(&self) -> Result<LocalClone, GitAiError> {
+        self.clone_repo_with(&[])
+    }
+
+    /// Clone with extra `git clone` flags, e.g. `["--depth=1"]` or `["--filter=blob:none"]`
+    /// for the shallow and partial clones CI systems make
+    pub fn clone_repo_with(&self, clone_args: &[&str]) -> Result<LocalClone, GitAiError> {
+        let path = create_unique_tmp_dir("git-ai-clone")?;
+        let url = if clone_args.is_empty() {
+            self.url()
+        } else {
+            self.file_url()
+        };
+        let mut args = vec!["clone", "--quiet"];
+        args.extend_from_slice(clone_args);
+        args.extend_from_slice(&[url.as_str(), "."]);
+        run_git(&path, &args)?;
+        run_git(&path, &["config", "user.name", "Test User"])?;
+        run_git(&path, &["config", "user.email", "test@example.com"])?;
+        // Cloning an empty remote leaves HEAD on the user's default branch name
+        run_git(
+            &path,
+            &[
+                "symbolic-ref",
+                "HEAD",
+                &format!("refs/heads/{}", LOCAL_REMOTE_DEFAULT_BRANCH),
+            ],
+        )?;
+        let repo = find_repository_in_path(&path.to_string_lossy())?;
+        Ok(LocalClone { path, repo })
+    }
+
+    /// Commit refs/notes/ai points at on the remote, if it has any notes
+    pub fn notes_tip(&self) -> Option<String> {
+        self.ref_target("refs/notes/ai")
+    }
+
+    /// Where `reference` points on the remote, if it exists
+    pub fn ref_target(&self, reference: &str) -> Option<String> {
+        run_git(&self.path, &["rev-parse", "--verify", "--quiet", reference])
+            .ok()
+            .map(|s| s.trim().to_string())
+    }
+
+    /// The remote's note for `commit_sha`
+    pub fn note(&self, commit_sha: &str) -> Option<String> {
+        run_git(&self.path, &["notes", "--ref=ai", "show", commit_sha])
+            .ok()
+            .map(|s| s.trim().to_string())
+    }
+}
+
+#[allow(dead_code)]
+impl LocalClone {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn gitai_repo(&self) -> &GitAiRepository {
+        &self.repo
+    }
+
+    /// Run plain git in the clone. Wrapper hooks are not involved.
+    pub fn git(&self, args: &[&str]) -> Result<String, GitAiError> {
+        run_git(&self.path, args)
+    }
+
+    /// Write `contents` to `filename`, commit it, and return the new commit sha
+    pub fn commit(
+        &self,
+        filename: &str,
+        contents: &str,
+        message: &str,
+    ) -> Result<String, GitAiError> {
+        let file_path = self.path.join(filename);
+        if let Some(parent) = file_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&file_path, contents)?;
+        self.git(&["add", filename])?;
+        self.git(&["commit", "--quiet", "-m", message])?;
+        Ok(self.git(&["rev-parse", "HEAD"])?.trim().to_string())
+    }
+
+    pub fn add_note(&self, commit_sha: &str, content: &str) -> Result<(), GitAiError> {
+        notes_add(&self.repo, commit_sha, content)
+    }
+
+    pub fn note(&self, commit_sha: &str) -> Option<String> {
+        show_authorship_note(&self.repo, commit_sha).map(|s| s.trim().to_string())
+    }
+
+    pub fn add_remote(&self, name: &str, remote: &LocalRemote) -> Result<(), GitAiError> {
+        self.git(&["remote", "add", name, remote.url().as_str()])?;
+        Ok(())
+    }
+
+    /// Push `branch` to origin
+    pub fn push(&self, branch: &str) -> Result<(), GitAiError> {
+        self.git(&["push", "--quiet", "origin", branch])?;
+        Ok(())
+    }
+
+    /// Sync notes to `remote_name` exactly as the post-push hook does
+    pub fn push_notes(&self, remote_name: &str) -> Result<(), GitAiError> {
+        push_authorship_notes(&self.repo, remote_name)
+    }
+
+    /// Sync notes after a push to `remote_name` as the pre-push hook does when
+    /// `git-ai.syncRemotes` is honored. Returns the remotes that were updated.
+    pub fn push_notes_per_policy(&self, remote_name: &str) -> Result<Vec<String>, GitAiError> {
+        let targets =
+            NotesSyncPolicy::for_repository(&self.repo).push_targets(&self.repo, remote_name);
+        for target in &targets {
+            push_authorship_notes(&self.repo, target)?;
+        }
+        Ok(targets)
+    }
+
+    /// Sync notes from `remote_name` exactly as the post-fetch hook does
+    pub fn fetch_notes(&self, remote_name: &str) -> Result<NotesExistence, GitAiError> {
+        fetch_authorship_notes(&self.repo, remote_name)
+    }
+}
+
+#[allow(dead_code)]
+fn run_git(dir: &Path, args: &[&str]) -> Result<String, GitAiError> {
+    let output = Command::new("git").arg("-C").arg(dir).args(args).output()?;
+    if !output.status.success() {
+        return Err(GitAiError::GitCliError {
+            code: output.status.code(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            args: args.iter().map(|s| s.to_string()).collect(),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
@@ -13,6 +13,8 @@ use std::path::PathBuf;
 use std::process::Command;
 use std::sync::atomic::{AtomicU64, Ordering};
 
+pub mod local_remote;
+
 // Create a guaranteed-unique temporary directory under the OS temp dir.
 // Combines high-resolution time, process id, and an atomic counter, retrying on collisions.
 fn create_unique_tmp_dir(prefix: &str) -> Result<PathBuf, GitAiError> {
@@ -1278,6 +1280,12 @@ impl TmpRepo {
     }
 }
 
+/// A realistic note for tests: `files` files with `ranges_per_file` AI ranges each, the same
+/// shape `git-ai bench` measures
+pub fn synthetic_authorship_log(files: usize, ranges_per_file: usize) -> AuthorshipLog {
+    crate::commands::bench::note_workload(files, ranges_per_file)
+}
+
 // @todo move this acunniffe
 /// Sanitized checkpoint representation for deterministic snapshots
 #[derive(Debug)]
@@ -0,0 +1,197 @@
+//! A workspace is a named set of repositories that `stats report`, `sync` and the daemon can
+//! operate on at once with `--workspace`.
+//!
+//! The list lives in `~/.git-ai/workspace.json`, or in the file `GIT_AI_WORKSPACE` names. Paths
+//! in the file may be relative to the file's directory, so a team can check in a shared
+//! workspace file next to its repositories.
+
+use crate::error::GitAiError;
+use crate::git::find_repository_in_path;
+use crate::git::repository::Repository;
+use crate::mdm::utils::write_atomic;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Overrides the workspace file location
+pub const WORKSPACE_ENV: &str = "GIT_AI_WORKSPACE";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkspaceRepo {
+    /// Shown in reports; defaults to the repository's directory name
+    pub name: String,
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Workspace {
+    #[serde(default)]
+    pub repositories: Vec<WorkspaceRepo>,
+    #[serde(skip)]
+    file: PathBuf,
+}
+
+impl Workspace {
+    /// `$GIT_AI_WORKSPACE`, else ~/.git-ai/workspace.json
+    pub fn file_path() -> PathBuf {
+        if let Some(path) = std::env::var_os(WORKSPACE_ENV).filter(|p| !p.is_empty()) {
+            return PathBuf::from(path);
+        }
+        dirs::home_dir()
+            .unwrap_or_default()
+            .join(".git-ai")
+            .join("workspace.json")
+    }
+
+    /// The workspace file's repositories, or an empty workspace when there is no file yet
+    pub fn load() -> Result<Workspace, GitAiError> {
+        Self::load_from(&Self::file_path())
+    }
+
+    pub fn load_from(file: &Path) -> Result<Workspace, GitAiError> {
+        let mut workspace = match fs::read_to_string(file) {
+            Ok(contents) => serde_json::from_str::<Workspace>(&contents).map_err(|e| {
+                GitAiError::Generic(format!("Invalid workspace file {}: {}", file.display(), e))
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Workspace::default(),
+            Err(e) => return Err(e.into()),
+        };
+        let base = file.parent().unwrap_or(Path::new("."));
+        for repo in &mut workspace.repositories {
+            if repo.path.is_relative() {
+                repo.path = base.join(&repo.path);
+            }
+        }
+        workspace.file = file.to_path_buf();
+        Ok(workspace)
+    }
+
+    pub fn save(&self) -> Result<(), GitAiError> {
+        if let Some(parent) = self.file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        write_atomic(&self.file, format!("{}\n", json).as_bytes())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.repositories.is_empty()
+    }
+
+    /// Add the repository containing `path`, named `name` or after its directory. Returns the
+    /// entry, and false when the repository was already in the workspace.
+    pub fn add(
+        &mut self,
+        path: &str,
+        name: Option<&str>,
+    ) -> Result<(WorkspaceRepo, bool), GitAiError> {
+        let repo = find_repository_in_path(path)?;
+        let root = repo.canonical_workdir().to_path_buf();
+        if let Some(existing) = self.repositories.iter().find(|r| same_path(&r.path, &root)) {
+            return Ok((existing.clone(), false));
+        }
+
+        let name = match name {
+            Some(name) => name.to_string(),
+            None => root
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| root.display().to_string()),
+        };
+        if self.repositories.iter().any(|r| r.name == name) {
+            return Err(GitAiError::Generic(format!(
+                "A repository named '{}' is already in the workspace; pick another with --name",
+                name
+            )));
+        }
+
+        let entry = WorkspaceRepo { name, path: root };
+        self.repositories.push(entry.clone());
+        Ok((entry, true))
+    }
+
+    /// Remove the repository with this name or path, returning it
+    pub fn remove(&mut self, name_or_path: &str) -> Option<WorkspaceRepo> {
+        let path = Path::new(name_or_path);
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let index = self
+            .repositories
+            .iter()
+            .position(|r| r.name == name_or_path || same_path(&r.path, &path))?;
+        Some(self.repositories.remove(index))
+    }
+}
+
+impl WorkspaceRepo {
+    pub fn open(&self) -> Result<Repository, GitAiError> {
+        if !self.path.is_dir() {
+            return Err(GitAiError::Generic(format!(
+                "{} does not exist",
+                self.path.display()
+            )));
+        }
+        find_repository_in_path(&self.path.to_string_lossy())
+    }
+}
+
+fn same_path(a: &Path, b: &Path) -> bool {
+    a == b || a.canonicalize().ok().as_deref() == Some(b)
+}
+
+/// Load the workspace for a `--workspace` flag, exiting when it can't be read or is empty
+pub fn load_workspace_or_exit() -> Workspace {
+    match Workspace::load() {
+        Ok(workspace) if workspace.is_empty() => {
+            eprintln!(
+                "The workspace has no repositories. Add some with `git-ai workspace add <path>`."
+            );
+            std::process::exit(1);
+        }
+        Ok(workspace) => workspace,
+        Err(e) => {
+            eprintln!("Failed to load workspace: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_resolves_relative_paths_against_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("team").join("workspace.json");
+        fs::create_dir_all(file.parent().unwrap()).unwrap();
+        fs::write(
+            &file,
+            r#"{"repositories":[{"name":"api","path":"../api"},{"name":"web","path":"/src/web"}]}"#,
+        )
+        .unwrap();
+
+        let workspace = Workspace::load_from(&file).unwrap();
+        assert_eq!(
+            workspace.repositories[0].path,
+            dir.path().join("team").join("../api")
+        );
+        assert_eq!(workspace.repositories[1].path, PathBuf::from("/src/web"));
+
+        let missing = Workspace::load_from(&dir.path().join("none.json")).unwrap();
+        assert!(missing.is_empty());
+    }
+
+    #[test]
+    fn test_remove_by_name() {
+        let mut workspace = Workspace {
+            repositories: vec![WorkspaceRepo {
+                name: "api".to_string(),
+                path: PathBuf::from("/nonexistent/api"),
+            }],
+            file: PathBuf::new(),
+        };
+        assert!(workspace.remove("web").is_none());
+        assert_eq!(workspace.remove("api").unwrap().name, "api");
+        assert!(workspace.is_empty());
+    }
+}
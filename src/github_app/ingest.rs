@@ -0,0 +1,302 @@
+//! Turning a push into `committed` metric events from the repository's authorship notes.
+
+use super::auth::basic_auth_header;
+use super::{GithubAppConfig, PushEvent};
+use crate::authorship::post_commit::committed_event;
+use crate::authorship::stats::stats_for_commit_stats;
+use crate::error::GitAiError;
+use crate::git::refs::{AI_AUTHORSHIP_REFNAME, get_authorship};
+use crate::git::repository::{Repository, exec_git_stdin_with_env, find_repository_in_path};
+use crate::metrics::{MetricEvent, PosEncoded};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Ingestion state kept next to each mirror's git directory
+const STATE_FILE: &str = "git-ai-github-app.json";
+
+/// Commits remembered as ingested per repository, newest kept
+const MAX_INGESTED: usize = 10_000;
+
+/// Commits found between `before` and `after` of a single push, newest first
+const MAX_COMMITS_PER_PUSH: usize = 1_000;
+
+/// How long a commit waits for its note before it's given up on
+const PENDING_TTL_SECS: u64 = 24 * 60 * 60;
+
+const ZERO_SHA: &str = "0000000000000000000000000000000000000000";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct MirrorState {
+    /// Oldest first
+    #[serde(default)]
+    ingested: Vec<String>,
+    #[serde(default)]
+    pending: Vec<PendingCommit>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PendingCommit {
+    commit: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    branch: Option<String>,
+    received_at: u64,
+}
+
+/// What one push contributed
+#[derive(Debug, Serialize)]
+pub struct IngestReport {
+    pub repository: String,
+    /// Whether the repository has any notes yet
+    pub notes_found: bool,
+    /// Commits whose events were produced, oldest first
+    pub recorded: Vec<String>,
+    /// Commits still waiting for their note
+    pub pending: Vec<String>,
+    /// Commits that waited longer than a day and were dropped
+    pub expired: Vec<String>,
+    #[serde(skip)]
+    pub events: Vec<MetricEvent>,
+}
+
+/// Fetch the pushed branch and the notes into the repository's mirror and produce a
+/// `committed` event for every new commit, from this push or earlier ones, that now has a note.
+/// The events are returned rather than recorded.
+pub fn ingest_push(
+    config: &GithubAppConfig,
+    event: &PushEvent,
+    token: Option<&str>,
+) -> Result<IngestReport, GitAiError> {
+    let mirror = mirror_path(&config.data_dir, &event.repository.full_name)?;
+    // Never the payload's clone_url: the token is sent to whatever origin points at
+    let remote_url = format!("{}/{}.git", config.git_url, event.repository.full_name);
+    ensure_mirror(&mirror, &remote_url)?;
+
+    let branch = event.branch().map(str::to_string);
+    // Under refs/remotes: git refuses to fetch into the (unborn) checked-out branch
+    if let Some(branch) = &branch
+        && !event.deleted
+        && event.after != ZERO_SHA
+    {
+        fetch(
+            &mirror,
+            token,
+            &format!("+{}:refs/remotes/origin/{}", event.git_ref, branch),
+        )?;
+    }
+    let notes_ref = format!("refs/notes/{}", AI_AUTHORSHIP_REFNAME);
+    let notes_found = match fetch(&mirror, token, &format!("+{}:{}", notes_ref, notes_ref)) {
+        Ok(()) => true,
+        Err(GitAiError::GitCliError { stderr, .. })
+            if stderr.contains("couldn't find remote ref") =>
+        {
+            false
+        }
+        Err(e) => return Err(e),
+    };
+
+    let state_path = mirror.join(STATE_FILE);
+    let mut state: MirrorState = std::fs::read_to_string(&state_path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default();
+    let ingested: HashSet<String> = state.ingested.iter().cloned().collect();
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    let repo = find_repository_in_path(&mirror.to_string_lossy())?;
+    let mut candidates = std::mem::take(&mut state.pending);
+    if branch.is_some() && !event.deleted {
+        for commit in pushed_commits(&repo, event) {
+            if !ingested.contains(&commit) && !candidates.iter().any(|p| p.commit == commit) {
+                candidates.push(PendingCommit {
+                    commit,
+                    branch: branch.clone(),
+                    received_at: now,
+                });
+            }
+        }
+    }
+
+    let mut report = IngestReport {
+        repository: event.repository.full_name.clone(),
+        notes_found,
+        recorded: Vec::new(),
+        pending: Vec::new(),
+        expired: Vec::new(),
+        events: Vec::new(),
+    };
+    for candidate in candidates {
+        match committed_metric_event(&repo, &candidate)? {
+            Some(event) => {
+                report.events.push(event);
+                state.ingested.push(candidate.commit.clone());
+                report.recorded.push(candidate.commit);
+            }
+            None if now.saturating_sub(candidate.received_at) > PENDING_TTL_SECS => {
+                report.expired.push(candidate.commit);
+            }
+            None => {
+                report.pending.push(candidate.commit.clone());
+                state.pending.push(candidate);
+            }
+        }
+    }
+
+    let excess = state.ingested.len().saturating_sub(MAX_INGESTED);
+    state.ingested.drain(..excess);
+    std::fs::write(&state_path, serde_json::to_string(&state)?)?;
+    Ok(report)
+}
+
+/// `<data_dir>/repos/<owner>/<repo>`, refusing names that would leave it
+fn mirror_path(data_dir: &Path, full_name: &str) -> Result<PathBuf, GitAiError> {
+    let parts: Vec<&str> = full_name.split('/').collect();
+    let valid = parts.len() == 2
+        && parts.iter().all(|part| {
+            !part.is_empty()
+                && *part != "."
+                && *part != ".."
+                && part
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        });
+    if !valid {
+        return Err(GitAiError::Generic(format!(
+            "Unexpected repository name: {}",
+            full_name
+        )));
+    }
+    Ok(data_dir.join("repos").join(parts[0]).join(parts[1]))
+}
+
+/// A repository with no checkout whose `origin` is the GitHub repository. It has a work tree
+/// (never populated) because attribution stats blame committed content through one.
+fn ensure_mirror(mirror: &Path, remote_url: &str) -> Result<(), GitAiError> {
+    if !mirror.join(".git").exists() {
+        std::fs::create_dir_all(mirror)?;
+        git(mirror, None, &["init", "--quiet"])?;
+    }
+    if git(mirror, None, &["remote", "set-url", "origin", remote_url]).is_err() {
+        git(mirror, None, &["remote", "add", "origin", remote_url])?;
+    }
+    Ok(())
+}
+
+fn fetch(mirror: &Path, token: Option<&str>, refspec: &str) -> Result<(), GitAiError> {
+    git(
+        mirror,
+        token,
+        &["fetch", "--quiet", "--no-tags", "origin", refspec],
+    )
+    .map(|_| ())
+}
+
+/// Run git in the mirror. The token goes in through the environment so it never shows up in
+/// process listings.
+fn git(mirror: &Path, token: Option<&str>, args: &[&str]) -> Result<String, GitAiError> {
+    let mut full_args = vec!["-C".to_string(), mirror.to_string_lossy().to_string()];
+    full_args.extend(args.iter().map(|a| a.to_string()));
+    let env = match token {
+        Some(token) => vec![
+            ("GIT_CONFIG_COUNT".to_string(), "1".to_string()),
+            (
+                "GIT_CONFIG_KEY_0".to_string(),
+                "http.extraHeader".to_string(),
+            ),
+            ("GIT_CONFIG_VALUE_0".to_string(), basic_auth_header(token)),
+        ],
+        None => Vec::new(),
+    };
+    let output = exec_git_stdin_with_env(&full_args, &env, &[])?;
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Commits the push added, oldest first: `before..after` when the mirror has `before`, otherwise
+/// the commits listed in the payload
+fn pushed_commits(repo: &Repository, event: &PushEvent) -> Vec<String> {
+    let mirror = repo.workdir().unwrap_or_default();
+    let has_before = event.before != ZERO_SHA
+        && git(
+            &mirror,
+            None,
+            &["cat-file", "-e", &format!("{}^{{commit}}", event.before)],
+        )
+        .is_ok();
+    if has_before {
+        let range = format!("{}..{}", event.before, event.after);
+        let max_count = format!("--max-count={}", MAX_COMMITS_PER_PUSH);
+        if let Ok(output) = git(
+            &mirror,
+            None,
+            &["rev-list", "--reverse", &max_count, &range],
+        ) {
+            return output.lines().map(str::to_string).collect();
+        }
+    }
+    event.commits.iter().map(|c| c.id.clone()).collect()
+}
+
+/// The `committed` event for a commit with a note, as post-commit would have recorded it
+fn committed_metric_event(
+    repo: &Repository,
+    pending: &PendingCommit,
+) -> Result<Option<MetricEvent>, GitAiError> {
+    let Some(authorship_log) = get_authorship(repo, &pending.commit) else {
+        return Ok(None);
+    };
+    let commit = repo.find_commit(pending.commit.clone())?;
+    let author = commit.author()?;
+    let human_author = format!(
+        "{} <{}>",
+        author.name().unwrap_or_default(),
+        author.email().unwrap_or_default()
+    );
+    let parent = if commit.parent_count()? == 0 {
+        "initial".to_string()
+    } else {
+        commit.parent(0)?.id()
+    };
+
+    let stats = stats_for_commit_stats(repo, &pending.commit, &[])?;
+    let (values, mut attrs) = committed_event(
+        repo,
+        &pending.commit,
+        &parent,
+        &human_author,
+        &authorship_log,
+        &stats,
+        &[],
+    );
+    if let Some(branch) = &pending.branch {
+        attrs = attrs.branch(branch);
+    }
+    Ok(Some(MetricEvent::new(&values, attrs.to_sparse())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mirror_path_rejects_names_outside_the_data_dir() {
+        let data_dir = Path::new("/data");
+        assert_eq!(
+            mirror_path(data_dir, "org/repo.js").unwrap(),
+            PathBuf::from("/data/repos/org/repo.js")
+        );
+        for name in [
+            "org",
+            "org/../x",
+            "../repo",
+            "org/repo/extra",
+            "org/",
+            "org/a b",
+        ] {
+            assert!(mirror_path(data_dir, name).is_err(), "{}", name);
+        }
+    }
+}
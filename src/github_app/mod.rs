@@ -0,0 +1,471 @@
+//! GitHub App webhook receiver for org-wide notes collection.
+//!
+//! `git-ai github-app serve` listens for the App's `push` webhooks. For each push it fetches the
+//! pushed branch and `refs/notes/ai` into a mirror under the data directory, computes the same
+//! `committed` metric event post-commit records locally for every pushed commit that has an
+//! authorship note, and hands the events to the metrics pipeline (`flush-logs` and the configured
+//! sinks). Org dashboards then cover repositories whose developers don't export metrics
+//! themselves.
+//!
+//! Clients push notes right after the branch, so commits that arrive without a note wait in the
+//! mirror's pending list until a later push to the same repository brings it.
+
+pub mod auth;
+pub mod ingest;
+
+use crate::utils::{decode_hex, encode_hex};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Address `serve` listens on unless GIT_AI_GITHUB_APP_LISTEN says otherwise
+pub const DEFAULT_GITHUB_APP_LISTEN: &str = "127.0.0.1:8787";
+
+/// Path GitHub delivers webhooks to; point the App's webhook URL at it
+pub const WEBHOOK_PATH: &str = "/webhook";
+
+/// GitHub caps webhook payloads at 25 MB
+const MAX_BODY_BYTES: usize = 25 * 1024 * 1024;
+
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Configuration for the webhook receiver, from environment variables
+#[derive(Debug, Clone)]
+pub struct GithubAppConfig {
+    pub listen_addr: String,
+    /// Webhook secret set on the App. `serve` refuses to start without it.
+    pub webhook_secret: Option<String>,
+    /// App ID and private key (PEM file), used to mint installation tokens for fetching
+    pub app_id: Option<String>,
+    pub private_key_path: Option<PathBuf>,
+    /// Token used for every fetch instead of installation tokens (e.g. a fine-grained PAT)
+    pub token: Option<String>,
+    pub api_url: String,
+    /// Repositories are fetched from `<git_url>/<owner>/<repo>.git`. It's derived from the API
+    /// URL rather than taken from the payload, so a forged delivery can't redirect the token.
+    pub git_url: String,
+    /// Repository mirrors and their ingestion state live under `<data_dir>/repos/<owner>/<repo>`
+    pub data_dir: PathBuf,
+}
+
+impl GithubAppConfig {
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|s| !s.is_empty());
+
+        let api_url = var("GIT_AI_GITHUB_API_URL")
+            .unwrap_or_else(|| "https://api.github.com".to_string())
+            .trim_end_matches('/')
+            .to_string();
+        Self {
+            listen_addr: var("GIT_AI_GITHUB_APP_LISTEN")
+                .unwrap_or_else(|| DEFAULT_GITHUB_APP_LISTEN.to_string()),
+            webhook_secret: var("GIT_AI_GITHUB_APP_WEBHOOK_SECRET"),
+            app_id: var("GIT_AI_GITHUB_APP_ID"),
+            private_key_path: var("GIT_AI_GITHUB_APP_PRIVATE_KEY").map(PathBuf::from),
+            token: var("GIT_AI_GITHUB_APP_TOKEN"),
+            git_url: git_url_for_api(&api_url),
+            api_url,
+            data_dir: var("GIT_AI_GITHUB_APP_DATA_DIR")
+                .map(PathBuf::from)
+                .unwrap_or_else(default_data_dir),
+        }
+    }
+}
+
+/// The web host behind an API URL: github.com for api.github.com, and the Enterprise Server
+/// host for `https://<host>/api/v3`
+fn git_url_for_api(api_url: &str) -> String {
+    if api_url == "https://api.github.com" {
+        return "https://github.com".to_string();
+    }
+    api_url
+        .strip_suffix("/api/v3")
+        .unwrap_or(api_url)
+        .to_string()
+}
+
+/// ~/.git-ai/internal/github-app
+fn default_data_dir() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_default()
+        .join(".git-ai")
+        .join("internal")
+        .join("github-app")
+}
+
+/// The parts of a `push` webhook payload ingestion uses
+#[derive(Debug, Clone, Deserialize)]
+pub struct PushEvent {
+    #[serde(rename = "ref")]
+    pub git_ref: String,
+    pub before: String,
+    pub after: String,
+    #[serde(default)]
+    pub deleted: bool,
+    pub repository: PushRepository,
+    #[serde(default)]
+    pub installation: Option<PushInstallation>,
+    /// At most the 20 newest commits; the rest are found in the mirror from `before`
+    #[serde(default)]
+    pub commits: Vec<PushCommit>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PushRepository {
+    pub full_name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PushInstallation {
+    pub id: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PushCommit {
+    pub id: String,
+}
+
+impl PushEvent {
+    pub fn parse(body: &[u8]) -> Result<Self, serde_json::Error> {
+        serde_json::from_slice(body)
+    }
+
+    /// The branch name for pushes to `refs/heads/*`
+    pub fn branch(&self) -> Option<&str> {
+        self.git_ref.strip_prefix("refs/heads/")
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    /// Header names lowercased
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).map(String::as_str)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+impl Response {
+    fn new(status: u16, body: &str) -> Self {
+        Self {
+            status,
+            body: body.to_string(),
+        }
+    }
+
+    fn to_http(&self) -> String {
+        let reason = match self.status {
+            200 => "OK",
+            202 => "Accepted",
+            204 => "No Content",
+            400 => "Bad Request",
+            401 => "Unauthorized",
+            404 => "Not Found",
+            413 => "Payload Too Large",
+            _ => "Error",
+        };
+        format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.status,
+            reason,
+            self.body.len(),
+            self.body
+        )
+    }
+}
+
+/// Whether `signature` (the `X-Hub-Signature-256` header) is the HMAC-SHA256 of `body`
+pub fn verify_signature(secret: &str, body: &[u8], signature: Option<&str>) -> bool {
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+
+    let Some(hex) = signature.and_then(|s| s.strip_prefix("sha256=")) else {
+        return false;
+    };
+    let Some(expected) = decode_hex(hex) else {
+        return false;
+    };
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    mac.verify_slice(&expected).is_ok()
+}
+
+/// Answer one webhook delivery. Returns the push to ingest, if any, once the response is sent.
+pub fn handle_request(
+    config: &GithubAppConfig,
+    request: &Request,
+) -> (Response, Option<PushEvent>) {
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/healthz") => return (Response::new(200, "ok"), None),
+        ("POST", WEBHOOK_PATH) => {}
+        _ => return (Response::new(404, "not found"), None),
+    }
+
+    if let Some(secret) = &config.webhook_secret
+        && !verify_signature(secret, &request.body, request.header("x-hub-signature-256"))
+    {
+        return (Response::new(401, "bad signature"), None);
+    }
+
+    match request.header("x-github-event") {
+        Some("ping") => (Response::new(200, "pong"), None),
+        Some("push") => match PushEvent::parse(&request.body) {
+            // Deleted refs have no commits to ingest
+            Ok(event) if event.deleted => (Response::new(204, ""), None),
+            Ok(event) => (Response::new(202, "queued"), Some(event)),
+            Err(e) => (
+                Response::new(400, &format!("invalid push payload: {}", e)),
+                None,
+            ),
+        },
+        _ => (Response::new(204, ""), None),
+    }
+}
+
+fn read_request(stream: &mut std::net::TcpStream) -> Result<Request, Response> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader
+        .read_line(&mut line)
+        .map_err(|_| Response::new(400, "unreadable request"))?;
+    let mut parts = line.split_whitespace();
+    let mut request = Request {
+        method: parts.next().unwrap_or_default().to_string(),
+        path: parts.next().unwrap_or_default().to_string(),
+        ..Default::default()
+    };
+
+    loop {
+        line.clear();
+        reader
+            .read_line(&mut line)
+            .map_err(|_| Response::new(400, "unreadable headers"))?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            request
+                .headers
+                .insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let length = request
+        .header("content-length")
+        .and_then(|l| l.parse::<usize>().ok())
+        .unwrap_or(0);
+    if length > MAX_BODY_BYTES {
+        return Err(Response::new(413, "payload too large"));
+    }
+    request.body = vec![0; length];
+    reader
+        .read_exact(&mut request.body)
+        .map_err(|_| Response::new(400, "truncated body"))?;
+    Ok(request)
+}
+
+/// Receive webhooks until the process is killed. The accept loop only reads, verifies and
+/// answers each delivery; pushes go over a channel to a worker thread that ingests them one at
+/// a time, so a slow fetch never holds up the next delivery past GitHub's 10 second timeout.
+pub fn serve(config: &GithubAppConfig) -> std::io::Result<()> {
+    // Without a secret anyone who can reach the port could trigger fetches with App credentials
+    if config.webhook_secret.is_none() {
+        return Err(std::io::Error::other(
+            "GIT_AI_GITHUB_APP_WEBHOOK_SECRET must be set to receive webhooks",
+        ));
+    }
+    let listener = std::net::TcpListener::bind(&config.listen_addr)?;
+    eprintln!(
+        "Receiving GitHub App webhooks on http://{}{}",
+        listener.local_addr()?,
+        WEBHOOK_PATH
+    );
+
+    let (pushes, queue) = mpsc::channel();
+    let worker_config = config.clone();
+    std::thread::spawn(move || ingest_pushes(&worker_config, queue));
+
+    for stream in listener.incoming() {
+        let Ok(mut stream) = stream else {
+            continue;
+        };
+        let _ = stream.set_read_timeout(Some(READ_TIMEOUT));
+        let (response, push) = match read_request(&mut stream) {
+            Ok(request) => handle_request(config, &request),
+            Err(response) => (response, None),
+        };
+        let _ = stream.write_all(response.to_http().as_bytes());
+        drop(stream);
+
+        if let Some(event) = push
+            && pushes.send(event).is_err()
+        {
+            return Err(std::io::Error::other("push ingestion worker stopped"));
+        }
+    }
+    Ok(())
+}
+
+/// Ingest the pushes `serve` queues, in the order they were delivered, until it stops
+fn ingest_pushes(config: &GithubAppConfig, queue: mpsc::Receiver<PushEvent>) {
+    let mut credentials = auth::Credentials::new(config.clone());
+    for event in queue {
+        let token = match credentials.token_for(event.installation.as_ref().map(|i| i.id)) {
+            Ok(token) => token,
+            Err(e) => {
+                eprintln!("{}: no credentials: {}", event.repository.full_name, e);
+                continue;
+            }
+        };
+        match ingest::ingest_push(config, &event, token.as_deref()) {
+            Ok(report) => {
+                eprintln!(
+                    "{} {}: {} commits recorded, {} waiting for notes",
+                    report.repository,
+                    event.git_ref,
+                    report.recorded.len(),
+                    report.pending.len()
+                );
+                if !report.events.is_empty() {
+                    crate::observability::log_metrics(report.events);
+                    crate::observability::spawn_background_flush();
+                }
+            }
+            Err(e) => eprintln!("{} {}: {}", event.repository.full_name, event.git_ref, e),
+        }
+    }
+}
+
+/// `X-Hub-Signature-256` value for `body`, as GitHub computes it
+pub fn signature_header(secret: &str, body: &[u8]) -> String {
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    format!("sha256={}", encode_hex(&mac.finalize().into_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(secret: Option<&str>) -> GithubAppConfig {
+        GithubAppConfig {
+            listen_addr: DEFAULT_GITHUB_APP_LISTEN.to_string(),
+            webhook_secret: secret.map(str::to_string),
+            app_id: None,
+            private_key_path: None,
+            token: None,
+            api_url: "https://api.github.com".to_string(),
+            git_url: "https://github.com".to_string(),
+            data_dir: PathBuf::from("/nonexistent"),
+        }
+    }
+
+    fn push_request(body: &str, signature: Option<String>) -> Request {
+        let mut headers = HashMap::from([("x-github-event".to_string(), "push".to_string())]);
+        if let Some(signature) = signature {
+            headers.insert("x-hub-signature-256".to_string(), signature);
+        }
+        Request {
+            method: "POST".to_string(),
+            path: WEBHOOK_PATH.to_string(),
+            headers,
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    const PUSH: &str = r#"{
+        "ref": "refs/heads/main",
+        "before": "0000000000000000000000000000000000000000",
+        "after": "1111111111111111111111111111111111111111",
+        "repository": {"full_name": "org/repo", "clone_url": "https://github.com/org/repo.git"},
+        "installation": {"id": 42},
+        "commits": [{"id": "1111111111111111111111111111111111111111"}]
+    }"#;
+
+    #[test]
+    fn test_signed_push_is_queued() {
+        let signature = signature_header("s3cret", PUSH.as_bytes());
+        let (response, event) = handle_request(
+            &config(Some("s3cret")),
+            &push_request(PUSH, Some(signature)),
+        );
+        assert_eq!(response.status, 202);
+        let event = event.unwrap();
+        assert_eq!(event.branch(), Some("main"));
+        assert_eq!(event.repository.full_name, "org/repo");
+        assert_eq!(event.installation.unwrap().id, 42);
+        assert_eq!(event.commits.len(), 1);
+    }
+
+    #[test]
+    fn test_bad_or_missing_signature_is_rejected() {
+        let config = config(Some("s3cret"));
+        let wrong = signature_header("other", PUSH.as_bytes());
+        let (response, event) = handle_request(&config, &push_request(PUSH, Some(wrong)));
+        assert_eq!(response.status, 401);
+        assert!(event.is_none());
+
+        let (response, _) = handle_request(&config, &push_request(PUSH, None));
+        assert_eq!(response.status, 401);
+    }
+
+    #[test]
+    fn test_git_url_comes_from_the_api_host() {
+        assert_eq!(
+            git_url_for_api("https://api.github.com"),
+            "https://github.com"
+        );
+        assert_eq!(
+            git_url_for_api("https://ghe.example.com/api/v3"),
+            "https://ghe.example.com"
+        );
+    }
+
+    #[test]
+    fn test_serve_requires_a_webhook_secret() {
+        let err = serve(&config(None)).unwrap_err();
+        assert!(err.to_string().contains("WEBHOOK_SECRET"), "{}", err);
+    }
+
+    #[test]
+    fn test_other_events_and_paths() {
+        let mut request = push_request("{}", None);
+        request
+            .headers
+            .insert("x-github-event".to_string(), "ping".to_string());
+        assert_eq!(handle_request(&config(None), &request).0.status, 200);
+
+        request
+            .headers
+            .insert("x-github-event".to_string(), "issues".to_string());
+        assert_eq!(handle_request(&config(None), &request).0.status, 204);
+
+        request.path = "/other".to_string();
+        assert_eq!(handle_request(&config(None), &request).0.status, 404);
+
+        let (response, event) = handle_request(&config(None), &push_request("not json", None));
+        assert_eq!(response.status, 400);
+        assert!(event.is_none());
+    }
+}
@@ -1,14 +1,21 @@
+pub mod agents;
 pub mod api;
 pub mod auth;
 pub mod authorship;
 pub mod ci;
 pub mod commands;
 pub mod config;
+pub mod config_layers;
+pub mod daemon;
 pub mod error;
+pub mod export;
 pub mod feature_flags;
 pub mod git;
+pub mod github_app;
+pub mod mcp;
 pub mod mdm;
 pub mod metrics;
 pub mod observability;
 pub mod repo_url;
+pub mod storage;
 pub mod utils;
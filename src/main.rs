@@ -1,16 +1,23 @@
+mod agents;
 mod api;
 mod auth;
 mod authorship;
 mod ci;
 mod commands;
 mod config;
+mod config_layers;
+mod daemon;
 mod error;
+mod export;
 mod feature_flags;
 mod git;
+mod github_app;
+mod mcp;
 mod mdm;
 mod metrics;
 mod observability;
 mod repo_url;
+mod storage;
 mod utils;
 
 use clap::Parser;
@@ -40,6 +47,17 @@ fn main() {
 
     let cli = Cli::parse();
 
+    // smol's blocking pool reads this once; cap it so snapshot work can't fan out past the limit
+    if std::env::var_os("BLOCKING_MAX_THREADS").is_none() {
+        // SAFETY: no other threads have been started yet
+        unsafe {
+            std::env::set_var(
+                "BLOCKING_MAX_THREADS",
+                config::Config::get().max_concurrency().to_string(),
+            );
+        }
+    }
+
     #[cfg(debug_assertions)]
     {
         if std::env::var("GIT_AI").as_deref() == Ok("git") {
@@ -50,6 +68,7 @@ fn main() {
 
     if binary_name == "git-ai" || binary_name == "git-ai.exe" {
         commands::git_ai_handlers::handle_git_ai(&cli.args);
+        observability::otel::shutdown_otel();
         std::process::exit(0);
     }
 
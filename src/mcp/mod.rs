@@ -0,0 +1,429 @@
+//! `git ai mcp`: a Model Context Protocol server on stdin/stdout.
+//!
+//! Agents that speak MCP can report their work directly instead of git-ai inferring it from hook
+//! payloads and file changes. The server exposes three tools:
+//!
+//! - `register_checkpoint`: call before editing. Snapshots the files so that changes made
+//!   before the agent's edit stay attributed to the human.
+//! - `declare_edits`: call after editing. Attributes the changes to the listed files since the
+//!   last checkpoint to the agent.
+//! - `get_attribution`: the AI/human makeup of the uncommitted (or staged) changes.
+//!
+//! Messages are JSON-RPC 2.0, one per line, framed as in [`crate::daemon::rpc`]. Stdout carries
+//! only protocol messages; diagnostics go to stderr.
+
+use crate::authorship::staged::{StagedComposition, staged_authorship, working_tree_authorship};
+use crate::authorship::transcript::{AiTranscript, Message};
+use crate::authorship::working_log::{AgentId, CheckpointKind};
+use crate::commands::checkpoint_agent::agent_presets::AgentRunResult;
+use crate::config::Config;
+use crate::daemon::rpc::{INVALID_PARAMS, METHOD_NOT_FOUND, Request, Response, parse_request};
+use crate::error::GitAiError;
+use crate::git::find_repository_in_path;
+use crate::git::repository::Repository;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+
+/// Protocol revision this server implements; clients asking for another get this one back
+pub const PROTOCOL_VERSION: &str = "2025-06-18";
+
+/// Agent name used when neither the call, `--agent` nor the client's name provides one
+const FALLBACK_AGENT: &str = "mcp";
+
+pub struct McpServer {
+    /// Repository tools act on when a call doesn't name one
+    default_repo: PathBuf,
+    /// `--agent`, else the `clientInfo.name` the client sent with `initialize`
+    agent: Option<String>,
+    model: Option<String>,
+    /// Edits declared without a `session_id` are grouped under this one
+    session_id: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct RegisterCheckpointArgs {
+    repo: Option<String>,
+    #[serde(default)]
+    paths: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct DeclareEditsArgs {
+    repo: Option<String>,
+    paths: Vec<String>,
+    agent: Option<String>,
+    model: Option<String>,
+    session_id: Option<String>,
+    #[serde(default)]
+    transcript: Vec<Message>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct GetAttributionArgs {
+    repo: Option<String>,
+    path: Option<String>,
+    #[serde(default)]
+    staged: bool,
+}
+
+impl McpServer {
+    pub fn new(default_repo: PathBuf, agent: Option<String>, model: Option<String>) -> Self {
+        Self {
+            default_repo,
+            agent,
+            model,
+            session_id: format!("mcp-{}", uuid::Uuid::new_v4()),
+        }
+    }
+
+    /// Answer requests from `input` on `output` until `input` closes
+    pub fn serve<R: BufRead, W: Write>(&mut self, input: R, mut output: W) -> std::io::Result<()> {
+        for line in input.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(response) = self.handle_line(&line) {
+                let mut text = serde_json::to_string(&response)?;
+                text.push('\n');
+                output.write_all(text.as_bytes())?;
+                output.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The response to one message, or None for notifications
+    pub fn handle_line(&mut self, line: &str) -> Option<Response> {
+        let request = match parse_request(line) {
+            Ok(request) => request,
+            Err(response) => return Some(response),
+        };
+        let result = self.dispatch(&request);
+        let id = request.id?;
+        Some(match result {
+            Ok(value) => Response::result(id, value),
+            Err((code, message)) => Response::error(id, code, message),
+        })
+    }
+
+    fn dispatch(&mut self, request: &Request) -> Result<Value, (i64, String)> {
+        match request.method.as_str() {
+            "initialize" => {
+                if self.agent.is_none() {
+                    self.agent = request
+                        .params
+                        .pointer("/clientInfo/name")
+                        .and_then(Value::as_str)
+                        .filter(|name| !name.is_empty())
+                        .map(str::to_string);
+                }
+                Ok(json!({
+                    "protocolVersion": PROTOCOL_VERSION,
+                    "capabilities": { "tools": { "listChanged": false } },
+                    "serverInfo": { "name": "git-ai", "version": env!("CARGO_PKG_VERSION") },
+                    "instructions": "Call register_checkpoint before editing files and \
+                        declare_edits after, listing the files you changed, so git-ai can \
+                        attribute your edits. get_attribution shows what is attributed so far.",
+                }))
+            }
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": tool_definitions() })),
+            "tools/call" => {
+                let Some(name) = request.params.get("name").and_then(Value::as_str) else {
+                    return Err((INVALID_PARAMS, "params.name is required".to_string()));
+                };
+                let arguments = request
+                    .params
+                    .get("arguments")
+                    .cloned()
+                    .unwrap_or_else(|| json!({}));
+                let result = match name {
+                    "register_checkpoint" => {
+                        parse_args(arguments).and_then(|args| self.register_checkpoint(args))
+                    }
+                    "declare_edits" => {
+                        parse_args(arguments).and_then(|args| self.declare_edits(args))
+                    }
+                    "get_attribution" => {
+                        parse_args(arguments).and_then(|args| self.get_attribution(args))
+                    }
+                    other => return Err((INVALID_PARAMS, format!("Unknown tool: {}", other))),
+                };
+                // Failures are reported to the model as tool results it can act on
+                Ok(match result {
+                    Ok(value) => tool_result(value, false),
+                    Err(e) => tool_result(Value::String(e.to_string()), true),
+                })
+            }
+            // Notifications such as notifications/initialized need no handling
+            method if method.starts_with("notifications/") => Ok(Value::Null),
+            other => Err((METHOD_NOT_FOUND, format!("Method not found: {}", other))),
+        }
+    }
+
+    fn open_repo(&self, repo: Option<&str>) -> Result<Repository, GitAiError> {
+        let dir = repo
+            .map(PathBuf::from)
+            .unwrap_or_else(|| self.default_repo.clone());
+        let repo = Some(find_repository_in_path(&dir.to_string_lossy())?);
+        if !Config::get().is_allowed_repository(&repo) {
+            return Err(GitAiError::Generic(
+                "Repository is excluded or not in allow_repositories".to_string(),
+            ));
+        }
+        repo.ok_or_else(|| GitAiError::Generic("No repository".to_string()))
+    }
+
+    fn register_checkpoint(&self, args: RegisterCheckpointArgs) -> Result<Value, GitAiError> {
+        let repo = self.open_repo(args.repo.as_deref())?;
+        let workdir = repo.workdir()?.display().to_string();
+        let event = AgentRunResult {
+            agent_id: AgentId {
+                tool: "human".to_string(),
+                id: "human".to_string(),
+                model: "human".to_string(),
+            },
+            agent_metadata: None,
+            checkpoint_kind: CheckpointKind::Human,
+            transcript: None,
+            repo_working_dir: Some(workdir.clone()),
+            edited_filepaths: None,
+            will_edit_filepaths: (!args.paths.is_empty()).then(|| args.paths.clone()),
+            dirty_files: None,
+        };
+        crate::daemon::run_checkpoint(event)?;
+        Ok(json!({ "repo": workdir, "kind": "human", "paths": args.paths }))
+    }
+
+    fn declare_edits(&self, args: DeclareEditsArgs) -> Result<Value, GitAiError> {
+        if args.paths.is_empty() {
+            return Err(GitAiError::Generic(
+                "paths must list the files you edited".to_string(),
+            ));
+        }
+        let repo = self.open_repo(args.repo.as_deref())?;
+        let workdir = repo.workdir()?.display().to_string();
+        let agent = args
+            .agent
+            .or_else(|| self.agent.clone())
+            .unwrap_or_else(|| FALLBACK_AGENT.to_string());
+        let model = args
+            .model
+            .or_else(|| self.model.clone())
+            .unwrap_or_else(|| "unknown".to_string());
+        let session_id = args.session_id.unwrap_or_else(|| self.session_id.clone());
+
+        let event = AgentRunResult {
+            agent_id: AgentId {
+                tool: agent.clone(),
+                id: session_id.clone(),
+                model: model.clone(),
+            },
+            agent_metadata: None,
+            checkpoint_kind: CheckpointKind::AiAgent,
+            transcript: Some(AiTranscript {
+                messages: args.transcript,
+            }),
+            repo_working_dir: Some(workdir.clone()),
+            edited_filepaths: Some(args.paths.clone()),
+            will_edit_filepaths: None,
+            dirty_files: None,
+        };
+        crate::daemon::run_checkpoint(event)?;
+        crate::observability::spawn_background_flush();
+        Ok(json!({
+            "repo": workdir,
+            "kind": "ai_agent",
+            "paths": args.paths,
+            "agent": agent,
+            "model": model,
+            "session_id": session_id,
+        }))
+    }
+
+    fn get_attribution(&self, args: GetAttributionArgs) -> Result<Value, GitAiError> {
+        let repo = self.open_repo(args.repo.as_deref())?;
+        let snapshot = if args.staged {
+            staged_authorship(&repo)?
+        } else {
+            working_tree_authorship(&repo)?
+        };
+        let mut composition = StagedComposition::from_staged(&snapshot);
+        if let Some(path) = args.path {
+            let path = path.trim_start_matches("./").to_string();
+            composition.files.retain(|file| file.path == path);
+        }
+        Ok(serde_json::to_value(composition)?)
+    }
+}
+
+fn parse_args<T: for<'de> Deserialize<'de>>(arguments: Value) -> Result<T, GitAiError> {
+    serde_json::from_value(arguments)
+        .map_err(|e| GitAiError::Generic(format!("Invalid arguments: {}", e)))
+}
+
+/// A `tools/call` result: the value as text for the model, and as structured content
+fn tool_result(value: Value, is_error: bool) -> Value {
+    let text = match &value {
+        Value::String(text) => text.clone(),
+        other => serde_json::to_string_pretty(other).unwrap_or_default(),
+    };
+    let mut result = json!({
+        "content": [{ "type": "text", "text": text }],
+        "isError": is_error,
+    });
+    if value.is_object() {
+        result["structuredContent"] = value;
+    }
+    result
+}
+
+fn tool_definitions() -> Value {
+    let repo = json!({
+        "type": "string",
+        "description": "Path inside the repository (default: the server's working directory)",
+    });
+    json!([
+        {
+            "name": "register_checkpoint",
+            "description": "Record the current state of files before you edit them, so that \
+                changes made before your edit stay attributed to the human.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "repo": repo,
+                    "paths": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Files you are about to edit (default: all changed files)",
+                    },
+                },
+            },
+        },
+        {
+            "name": "declare_edits",
+            "description": "Declare that you wrote the changes to these files since the last \
+                checkpoint. Call after each edit.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "repo": repo,
+                    "paths": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Files you edited",
+                    },
+                    "agent": { "type": "string", "description": "Agent name, e.g. claude-code" },
+                    "model": { "type": "string", "description": "Model that wrote the edits" },
+                    "session_id": {
+                        "type": "string",
+                        "description": "Conversation id; edits in one session share a prompt record",
+                    },
+                    "transcript": {
+                        "type": "array",
+                        "description": "Conversation so far, as {\"type\": \"user\"|\"assistant\", \"text\": ...} messages",
+                        "items": { "type": "object" },
+                    },
+                },
+                "required": ["paths"],
+            },
+        },
+        {
+            "name": "get_attribution",
+            "description": "Show which uncommitted lines are attributed to AI agents and which \
+                to humans, per file and agent session.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "repo": repo,
+                    "path": { "type": "string", "description": "Only report this file" },
+                    "staged": {
+                        "type": "boolean",
+                        "description": "Report the staged changes instead of the working tree",
+                    },
+                },
+            },
+        },
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server() -> McpServer {
+        McpServer::new(PathBuf::from("."), None, None)
+    }
+
+    #[test]
+    fn test_initialize_takes_agent_name_from_client() {
+        let mut server = server();
+        let response = server
+            .handle_line(
+                r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2025-03-26","clientInfo":{"name":"claude-code","version":"2.0"}}}"#,
+            )
+            .unwrap();
+        let result = response.result.unwrap();
+        assert_eq!(result["protocolVersion"], PROTOCOL_VERSION);
+        assert_eq!(result["serverInfo"]["name"], "git-ai");
+        assert_eq!(server.agent.as_deref(), Some("claude-code"));
+
+        // --agent wins over the client's name
+        let mut server = McpServer::new(PathBuf::from("."), Some("custom".to_string()), None);
+        server.handle_line(
+            r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"clientInfo":{"name":"claude-code"}}}"#,
+        );
+        assert_eq!(server.agent.as_deref(), Some("custom"));
+    }
+
+    #[test]
+    fn test_notifications_get_no_response() {
+        assert!(
+            server()
+                .handle_line(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_tools_list_and_argument_errors() {
+        let mut server = server();
+        let response = server
+            .handle_line(r#"{"jsonrpc":"2.0","id":2,"method":"tools/list"}"#)
+            .unwrap();
+        let tools = response.result.unwrap()["tools"].clone();
+        let names: Vec<&str> = tools
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            vec!["register_checkpoint", "declare_edits", "get_attribution"]
+        );
+
+        // Bad arguments are a tool error the model can see, not a protocol error
+        let response = server
+            .handle_line(r#"{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"declare_edits","arguments":{"paths":[]}}}"#)
+            .unwrap();
+        let result = response.result.unwrap();
+        assert_eq!(result["isError"], true);
+        assert!(
+            result["content"][0]["text"]
+                .as_str()
+                .unwrap()
+                .contains("paths must list")
+        );
+
+        let response = server
+            .handle_line(
+                r#"{"jsonrpc":"2.0","id":4,"method":"tools/call","params":{"name":"nope"}}"#,
+            )
+            .unwrap();
+        assert_eq!(response.error.unwrap().code, INVALID_PARAMS);
+    }
+}
@@ -250,6 +250,11 @@ impl MetricsDatabase {
         Ok(())
     }
 
+    /// Drop every pending metric without uploading it. Returns the number dropped.
+    pub fn clear(&mut self) -> Result<usize, GitAiError> {
+        Ok(self.conn.execute("DELETE FROM metrics", [])?)
+    }
+
     /// Get count of pending metrics
     pub fn count(&self) -> Result<usize, GitAiError> {
         let count: i64 = self.conn.query_row(
@@ -259,6 +264,12 @@ impl MetricsDatabase {
         )?;
         Ok(count as usize)
     }
+
+    /// Rebuild the database file to reclaim space freed by uploads
+    pub fn vacuum(&mut self) -> Result<(), GitAiError> {
+        self.conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE); VACUUM;")?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
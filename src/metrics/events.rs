@@ -1,11 +1,13 @@
 //! Event-specific value structs for metrics.
 
 use super::pos_encoded::{
-    sparse_get_string, sparse_get_u32, sparse_get_u64, sparse_get_vec_string, sparse_get_vec_u32,
-    sparse_get_vec_u64, sparse_set, string_to_json, u32_to_json, u64_to_json, vec_string_to_json,
-    vec_u32_to_json, vec_u64_to_json, PosEncoded, PosField,
+    f64_to_json, sparse_get_f64, sparse_get_string, sparse_get_u32, sparse_get_u64,
+    sparse_get_vec_string, sparse_get_vec_u32, sparse_get_vec_u64, sparse_set, string_to_json,
+    u32_to_json, u64_to_json, vec_string_to_json, vec_u32_to_json, vec_u64_to_json, PosEncoded,
+    PosField,
 };
 use super::types::{EventValues, MetricEventId, SparseArray};
+use crate::authorship::token_usage::TokenUsage;
 
 /// Value positions for "committed" event.
 pub mod committed_pos {
@@ -27,6 +29,7 @@ pub mod committed_pos {
     pub const FIRST_CHECKPOINT_TS: usize = 10; // u64 (null if no checkpoints)
     pub const COMMIT_SUBJECT: usize = 11; // String
     pub const COMMIT_BODY: usize = 12; // String (null if empty)
+    pub const AI_REVERTED: usize = 13; // u32 (AI lines removed by reverting an earlier commit)
 }
 
 /// Values for Event ID 1: committed
@@ -53,6 +56,7 @@ pub mod committed_pos {
 /// | 10 | first_checkpoint_ts | u64 |
 /// | 11 | commit_subject | String |
 /// | 12 | commit_body | String |
+/// | 13 | ai_reverted | u32 |
 #[derive(Debug, Clone, Default)]
 pub struct CommittedValues {
     // Scalar fields
@@ -73,6 +77,7 @@ pub struct CommittedValues {
     pub first_checkpoint_ts: PosField<u64>,
     pub commit_subject: PosField<String>,
     pub commit_body: PosField<String>,
+    pub ai_reverted: PosField<u32>,
 }
 
 impl CommittedValues {
@@ -215,6 +220,16 @@ impl CommittedValues {
         self.commit_body = Some(None);
         self
     }
+
+    pub fn ai_reverted(mut self, value: u32) -> Self {
+        self.ai_reverted = Some(Some(value));
+        self
+    }
+
+    pub fn ai_reverted_null(mut self) -> Self {
+        self.ai_reverted = Some(None);
+        self
+    }
 }
 
 impl PosEncoded for CommittedValues {
@@ -291,6 +306,11 @@ impl PosEncoded for CommittedValues {
             committed_pos::COMMIT_BODY,
             string_to_json(&self.commit_body),
         );
+        sparse_set(
+            &mut map,
+            committed_pos::AI_REVERTED,
+            u32_to_json(&self.ai_reverted),
+        );
 
         map
     }
@@ -315,6 +335,7 @@ impl PosEncoded for CommittedValues {
             first_checkpoint_ts: sparse_get_u64(arr, committed_pos::FIRST_CHECKPOINT_TS),
             commit_subject: sparse_get_string(arr, committed_pos::COMMIT_SUBJECT),
             commit_body: sparse_get_string(arr, committed_pos::COMMIT_BODY),
+            ai_reverted: sparse_get_u32(arr, committed_pos::AI_REVERTED),
         }
     }
 }
@@ -333,26 +354,94 @@ impl EventValues for CommittedValues {
     }
 }
 
+/// Value positions for "agent_usage" event.
+pub mod agent_usage_pos {
+    pub const INPUT_TOKENS: usize = 0; // u64 - prompt tokens spent since the previous checkpoint
+    pub const OUTPUT_TOKENS: usize = 1; // u64 - completion tokens spent since the previous checkpoint
+    pub const COST_USD: usize = 2; // f64 - estimated cost of those tokens
+}
+
 /// Values for Event ID 2: agent_usage
 ///
 /// Recorded on every AI checkpoint to track agent usage.
-/// Uses attributes (prompt_id, tool, model) rather than event-specific values.
+/// Uses attributes (prompt_id, tool, model) to identify the session. Token counts and cost are
+/// only set when the agent exposes them, and cover the interaction since the session's
+/// previous checkpoint.
+///
+/// **Fields:**
+/// | Position | Name | Type |
+/// |----------|------|------|
+/// | 0 | input_tokens | u64 |
+/// | 1 | output_tokens | u64 |
+/// | 2 | cost_usd | f64 |
 #[derive(Debug, Clone, Default)]
-pub struct AgentUsageValues {}
+pub struct AgentUsageValues {
+    pub input_tokens: PosField<u64>,
+    pub output_tokens: PosField<u64>,
+    pub cost_usd: PosField<f64>,
+}
 
 impl AgentUsageValues {
     pub fn new() -> Self {
         Self::default()
     }
+
+    pub fn input_tokens(mut self, value: u64) -> Self {
+        self.input_tokens = Some(Some(value));
+        self
+    }
+
+    pub fn output_tokens(mut self, value: u64) -> Self {
+        self.output_tokens = Some(Some(value));
+        self
+    }
+
+    pub fn cost_usd(mut self, value: f64) -> Self {
+        self.cost_usd = Some(Some(value));
+        self
+    }
+
+    /// Set the token counts and cost from an interaction's usage
+    pub fn token_usage(self, usage: &TokenUsage) -> Self {
+        let values = self
+            .input_tokens(usage.input_tokens)
+            .output_tokens(usage.output_tokens);
+        match usage.cost_usd {
+            Some(cost) => values.cost_usd(cost),
+            None => values,
+        }
+    }
 }
 
 impl PosEncoded for AgentUsageValues {
     fn to_sparse(&self) -> SparseArray {
-        SparseArray::new()
+        let mut map = SparseArray::new();
+
+        sparse_set(
+            &mut map,
+            agent_usage_pos::INPUT_TOKENS,
+            u64_to_json(&self.input_tokens),
+        );
+        sparse_set(
+            &mut map,
+            agent_usage_pos::OUTPUT_TOKENS,
+            u64_to_json(&self.output_tokens),
+        );
+        sparse_set(
+            &mut map,
+            agent_usage_pos::COST_USD,
+            f64_to_json(&self.cost_usd),
+        );
+
+        map
     }
 
-    fn from_sparse(_arr: &SparseArray) -> Self {
-        Self::default()
+    fn from_sparse(arr: &SparseArray) -> Self {
+        Self {
+            input_tokens: sparse_get_u64(arr, agent_usage_pos::INPUT_TOKENS),
+            output_tokens: sparse_get_u64(arr, agent_usage_pos::OUTPUT_TOKENS),
+            cost_usd: sparse_get_f64(arr, agent_usage_pos::COST_USD),
+        }
     }
 }
 
@@ -652,6 +741,306 @@ impl EventValues for CheckpointValues {
     }
 }
 
+/// Value positions for "team_aggregate" event.
+pub mod team_aggregate_pos {
+    pub const DAY: usize = 0; // String - UTC day, YYYY-MM-DD
+    pub const COMMITS: usize = 1; // u32 - commits made that day
+    pub const AI_ADDITIONS: usize = 2; // u32 - AI lines those commits added
+    pub const HUMAN_ADDITIONS: usize = 3; // u32 - human lines those commits added
+    pub const RATIO_BUCKETS: usize = 4; // Vec<u32> - commits per AI-ratio bucket
+}
+
+/// AI-ratio bucket labels, in the order of `team_aggregate_pos::RATIO_BUCKETS`. A commit's
+/// ratio is its AI lines over AI plus human lines; commits that add no AI lines are in "0".
+pub const TEAM_RATIO_BUCKETS: [&str; 5] = ["0", "1-25", "26-50", "51-75", "76-100"];
+
+/// Values for Event ID 5: team_aggregate
+///
+/// One repository's commits over one UTC day, without author or commit identities. Emitted
+/// to external sinks instead of per-commit events under the `aggregate` telemetry privacy
+/// preset, once the day is over. Attributes carry only the git-ai version and repository URL.
+///
+/// **Fields:**
+/// | Position | Name | Type |
+/// |----------|------|------|
+/// | 0 | day | String |
+/// | 1 | commits | u32 |
+/// | 2 | ai_additions | u32 |
+/// | 3 | human_additions | u32 |
+/// | 4 | ratio_buckets | Vec<u32> |
+#[derive(Debug, Clone, Default)]
+pub struct TeamAggregateValues {
+    pub day: PosField<String>,
+    pub commits: PosField<u32>,
+    pub ai_additions: PosField<u32>,
+    pub human_additions: PosField<u32>,
+    pub ratio_buckets: PosField<Vec<u32>>,
+}
+
+impl TeamAggregateValues {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn day(mut self, value: String) -> Self {
+        self.day = Some(Some(value));
+        self
+    }
+
+    pub fn commits(mut self, value: u32) -> Self {
+        self.commits = Some(Some(value));
+        self
+    }
+
+    pub fn ai_additions(mut self, value: u32) -> Self {
+        self.ai_additions = Some(Some(value));
+        self
+    }
+
+    pub fn human_additions(mut self, value: u32) -> Self {
+        self.human_additions = Some(Some(value));
+        self
+    }
+
+    pub fn ratio_buckets(mut self, value: Vec<u32>) -> Self {
+        self.ratio_buckets = Some(Some(value));
+        self
+    }
+}
+
+impl PosEncoded for TeamAggregateValues {
+    fn to_sparse(&self) -> SparseArray {
+        let mut map = SparseArray::new();
+
+        sparse_set(&mut map, team_aggregate_pos::DAY, string_to_json(&self.day));
+        sparse_set(&mut map, team_aggregate_pos::COMMITS, u32_to_json(&self.commits));
+        sparse_set(
+            &mut map,
+            team_aggregate_pos::AI_ADDITIONS,
+            u32_to_json(&self.ai_additions),
+        );
+        sparse_set(
+            &mut map,
+            team_aggregate_pos::HUMAN_ADDITIONS,
+            u32_to_json(&self.human_additions),
+        );
+        sparse_set(
+            &mut map,
+            team_aggregate_pos::RATIO_BUCKETS,
+            vec_u32_to_json(&self.ratio_buckets),
+        );
+
+        map
+    }
+
+    fn from_sparse(arr: &SparseArray) -> Self {
+        Self {
+            day: sparse_get_string(arr, team_aggregate_pos::DAY),
+            commits: sparse_get_u32(arr, team_aggregate_pos::COMMITS),
+            ai_additions: sparse_get_u32(arr, team_aggregate_pos::AI_ADDITIONS),
+            human_additions: sparse_get_u32(arr, team_aggregate_pos::HUMAN_ADDITIONS),
+            ratio_buckets: sparse_get_vec_u32(arr, team_aggregate_pos::RATIO_BUCKETS),
+        }
+    }
+}
+
+impl EventValues for TeamAggregateValues {
+    fn event_id() -> MetricEventId {
+        MetricEventId::TeamAggregate
+    }
+
+    fn to_sparse(&self) -> SparseArray {
+        PosEncoded::to_sparse(self)
+    }
+
+    fn from_sparse(arr: &SparseArray) -> Self {
+        PosEncoded::from_sparse(arr)
+    }
+}
+
+/// Value positions for "pr_merged" event.
+pub mod pr_merged_pos {
+    pub const PR_NUMBER: usize = 0; // u32 (null for merges made outside a pull request)
+    pub const COMMITS: usize = 1; // u32 - commits the pull request brought in
+    pub const GIT_DIFF_ADDED_LINES: usize = 2; // u32
+    pub const GIT_DIFF_DELETED_LINES: usize = 3; // u32
+    pub const AI_ADDITIONS: usize = 4; // u32
+    pub const HUMAN_ADDITIONS: usize = 5; // u32
+    pub const AI_RATIO: usize = 6; // f64 - AI over AI plus human additions (null if neither)
+    pub const REVIEW_DURATION: usize = 7; // u64 - seconds from opening (or first commit) to merge
+    pub const REVERTED_COMMITS: usize = 8; // Vec<String> - commits the pull request reverts
+}
+
+/// Values for Event ID 6: pr_merged
+///
+/// Recorded when a pull request lands: by `git-ai ci github run` for merged pull requests, and
+/// by the merge hook when `git merge` creates a merge commit. Attributes carry the merge commit
+/// as `commit_sha`, the base it landed on as `base_commit_sha`, and the merged branch.
+///
+/// `reverted_commits` links reverts back to what they undo: a pull request whose merge commit
+/// (or one of its commits) shows up there was later reverted.
+///
+/// **Fields:**
+/// | Position | Name | Type |
+/// |----------|------|------|
+/// | 0 | pr_number | u32 |
+/// | 1 | commits | u32 |
+/// | 2 | git_diff_added_lines | u32 |
+/// | 3 | git_diff_deleted_lines | u32 |
+/// | 4 | ai_additions | u32 |
+/// | 5 | human_additions | u32 |
+/// | 6 | ai_ratio | f64 |
+/// | 7 | review_duration | u64 |
+/// | 8 | reverted_commits | Vec<String> |
+#[derive(Debug, Clone, Default)]
+pub struct PrMergedValues {
+    pub pr_number: PosField<u32>,
+    pub commits: PosField<u32>,
+    pub git_diff_added_lines: PosField<u32>,
+    pub git_diff_deleted_lines: PosField<u32>,
+    pub ai_additions: PosField<u32>,
+    pub human_additions: PosField<u32>,
+    pub ai_ratio: PosField<f64>,
+    pub review_duration: PosField<u64>,
+    pub reverted_commits: PosField<Vec<String>>,
+}
+
+impl PrMergedValues {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn pr_number(mut self, value: u32) -> Self {
+        self.pr_number = Some(Some(value));
+        self
+    }
+
+    pub fn pr_number_null(mut self) -> Self {
+        self.pr_number = Some(None);
+        self
+    }
+
+    pub fn commits(mut self, value: u32) -> Self {
+        self.commits = Some(Some(value));
+        self
+    }
+
+    pub fn git_diff_added_lines(mut self, value: u32) -> Self {
+        self.git_diff_added_lines = Some(Some(value));
+        self
+    }
+
+    pub fn git_diff_deleted_lines(mut self, value: u32) -> Self {
+        self.git_diff_deleted_lines = Some(Some(value));
+        self
+    }
+
+    pub fn ai_additions(mut self, value: u32) -> Self {
+        self.ai_additions = Some(Some(value));
+        self
+    }
+
+    pub fn human_additions(mut self, value: u32) -> Self {
+        self.human_additions = Some(Some(value));
+        self
+    }
+
+    pub fn ai_ratio(mut self, value: f64) -> Self {
+        self.ai_ratio = Some(Some(value));
+        self
+    }
+
+    pub fn ai_ratio_null(mut self) -> Self {
+        self.ai_ratio = Some(None);
+        self
+    }
+
+    pub fn review_duration(mut self, value: u64) -> Self {
+        self.review_duration = Some(Some(value));
+        self
+    }
+
+    pub fn review_duration_null(mut self) -> Self {
+        self.review_duration = Some(None);
+        self
+    }
+
+    pub fn reverted_commits(mut self, value: Vec<String>) -> Self {
+        self.reverted_commits = Some(Some(value));
+        self
+    }
+}
+
+impl PosEncoded for PrMergedValues {
+    fn to_sparse(&self) -> SparseArray {
+        let mut map = SparseArray::new();
+
+        sparse_set(&mut map, pr_merged_pos::PR_NUMBER, u32_to_json(&self.pr_number));
+        sparse_set(&mut map, pr_merged_pos::COMMITS, u32_to_json(&self.commits));
+        sparse_set(
+            &mut map,
+            pr_merged_pos::GIT_DIFF_ADDED_LINES,
+            u32_to_json(&self.git_diff_added_lines),
+        );
+        sparse_set(
+            &mut map,
+            pr_merged_pos::GIT_DIFF_DELETED_LINES,
+            u32_to_json(&self.git_diff_deleted_lines),
+        );
+        sparse_set(
+            &mut map,
+            pr_merged_pos::AI_ADDITIONS,
+            u32_to_json(&self.ai_additions),
+        );
+        sparse_set(
+            &mut map,
+            pr_merged_pos::HUMAN_ADDITIONS,
+            u32_to_json(&self.human_additions),
+        );
+        sparse_set(&mut map, pr_merged_pos::AI_RATIO, f64_to_json(&self.ai_ratio));
+        sparse_set(
+            &mut map,
+            pr_merged_pos::REVIEW_DURATION,
+            u64_to_json(&self.review_duration),
+        );
+        sparse_set(
+            &mut map,
+            pr_merged_pos::REVERTED_COMMITS,
+            vec_string_to_json(&self.reverted_commits),
+        );
+
+        map
+    }
+
+    fn from_sparse(arr: &SparseArray) -> Self {
+        Self {
+            pr_number: sparse_get_u32(arr, pr_merged_pos::PR_NUMBER),
+            commits: sparse_get_u32(arr, pr_merged_pos::COMMITS),
+            git_diff_added_lines: sparse_get_u32(arr, pr_merged_pos::GIT_DIFF_ADDED_LINES),
+            git_diff_deleted_lines: sparse_get_u32(arr, pr_merged_pos::GIT_DIFF_DELETED_LINES),
+            ai_additions: sparse_get_u32(arr, pr_merged_pos::AI_ADDITIONS),
+            human_additions: sparse_get_u32(arr, pr_merged_pos::HUMAN_ADDITIONS),
+            ai_ratio: sparse_get_f64(arr, pr_merged_pos::AI_RATIO),
+            review_duration: sparse_get_u64(arr, pr_merged_pos::REVIEW_DURATION),
+            reverted_commits: sparse_get_vec_string(arr, pr_merged_pos::REVERTED_COMMITS),
+        }
+    }
+}
+
+impl EventValues for PrMergedValues {
+    fn event_id() -> MetricEventId {
+        MetricEventId::PrMerged
+    }
+
+    fn to_sparse(&self) -> SparseArray {
+        PosEncoded::to_sparse(self)
+    }
+
+    fn from_sparse(arr: &SparseArray) -> Self {
+        PosEncoded::from_sparse(arr)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -745,4 +1134,53 @@ mod tests {
         assert_eq!(CommittedValues::event_id(), MetricEventId::Committed);
         assert_eq!(CommittedValues::event_id() as u16, 1);
     }
+
+    #[test]
+    fn test_agent_usage_values_roundtrip() {
+        use super::PosEncoded;
+
+        let empty = PosEncoded::to_sparse(&AgentUsageValues::new());
+        assert!(empty.is_empty());
+
+        let usage = TokenUsage {
+            input_tokens: 1200,
+            output_tokens: 340,
+            cost_usd: Some(0.0123),
+        };
+        let sparse = PosEncoded::to_sparse(&AgentUsageValues::new().token_usage(&usage));
+        assert_eq!(sparse.get("0"), Some(&Value::Number(1200.into())));
+        assert_eq!(sparse.get("1"), Some(&Value::Number(340.into())));
+
+        let values = <AgentUsageValues as PosEncoded>::from_sparse(&sparse);
+        assert_eq!(values.input_tokens, Some(Some(1200)));
+        assert_eq!(values.output_tokens, Some(Some(340)));
+        assert_eq!(values.cost_usd, Some(Some(0.0123)));
+    }
+
+    #[test]
+    fn test_pr_merged_values_roundtrip() {
+        use super::PosEncoded;
+
+        let values = PrMergedValues::new()
+            .pr_number(42)
+            .commits(3)
+            .ai_additions(30)
+            .human_additions(10)
+            .ai_ratio(0.75)
+            .review_duration(3600)
+            .reverted_commits(vec!["abc1234".to_string()]);
+        let sparse = PosEncoded::to_sparse(&values);
+        assert_eq!(sparse.get("0"), Some(&Value::Number(42.into())));
+        assert!(!sparse.contains_key("2"));
+
+        let decoded = <PrMergedValues as PosEncoded>::from_sparse(&sparse);
+        assert_eq!(decoded.pr_number, Some(Some(42)));
+        assert_eq!(decoded.ai_ratio, Some(Some(0.75)));
+        assert_eq!(decoded.review_duration, Some(Some(3600)));
+        assert_eq!(
+            decoded.reverted_commits,
+            Some(Some(vec!["abc1234".to_string()]))
+        );
+        assert_eq!(PrMergedValues::event_id() as u16, 6);
+    }
 }
@@ -49,6 +49,20 @@ pub fn u64_to_json(field: &PosField<u64>) -> Option<Value> {
     }
 }
 
+/// Convert a PosField<f64> to JSON Value for sparse array.
+/// Non-finite values have no JSON representation and are written as null.
+pub fn f64_to_json(field: &PosField<f64>) -> Option<Value> {
+    match field {
+        None => None,
+        Some(None) => Some(Value::Null),
+        Some(Some(n)) => Some(
+            serde_json::Number::from_f64(*n)
+                .map(Value::Number)
+                .unwrap_or(Value::Null),
+        ),
+    }
+}
+
 /// Get a string field from a sparse array at a position.
 pub fn sparse_get_string(arr: &SparseArray, pos: usize) -> PosField<String> {
     match arr.get(&pos.to_string()) {
@@ -85,6 +99,16 @@ pub fn sparse_get_u64(arr: &SparseArray, pos: usize) -> PosField<u64> {
     }
 }
 
+/// Get an f64 field from a sparse array at a position.
+pub fn sparse_get_f64(arr: &SparseArray, pos: usize) -> PosField<f64> {
+    match arr.get(&pos.to_string()) {
+        None => None,
+        Some(Value::Null) => Some(None),
+        Some(Value::Number(n)) => n.as_f64().map(Some),
+        Some(_) => None,
+    }
+}
+
 /// Convert a PosField<Vec<String>> to JSON array.
 pub fn vec_string_to_json(field: &PosField<Vec<String>>) -> Option<Value> {
     match field {
@@ -20,6 +20,8 @@ pub enum MetricEventId {
     AgentUsage = 2,
     InstallHooks = 3,
     Checkpoint = 4,
+    TeamAggregate = 5,
+    PrMerged = 6,
 }
 
 impl TryFrom<u16> for MetricEventId {
@@ -31,11 +33,40 @@ impl TryFrom<u16> for MetricEventId {
             2 => Ok(MetricEventId::AgentUsage),
             3 => Ok(MetricEventId::InstallHooks),
             4 => Ok(MetricEventId::Checkpoint),
+            5 => Ok(MetricEventId::TeamAggregate),
+            6 => Ok(MetricEventId::PrMerged),
             _ => Err(()),
         }
     }
 }
 
+impl MetricEventId {
+    pub const ALL: [MetricEventId; 6] = [
+        MetricEventId::Committed,
+        MetricEventId::AgentUsage,
+        MetricEventId::InstallHooks,
+        MetricEventId::Checkpoint,
+        MetricEventId::TeamAggregate,
+        MetricEventId::PrMerged,
+    ];
+
+    /// Name used in exports and config (`committed`, `agent_usage`, ...)
+    pub fn name(self) -> &'static str {
+        match self {
+            MetricEventId::Committed => "committed",
+            MetricEventId::AgentUsage => "agent_usage",
+            MetricEventId::InstallHooks => "install_hooks",
+            MetricEventId::Checkpoint => "checkpoint",
+            MetricEventId::TeamAggregate => "team_aggregate",
+            MetricEventId::PrMerged => "pr_merged",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|id| id.name() == name)
+    }
+}
+
 /// Trait for event-specific values.
 pub trait EventValues: Sized {
     fn event_id() -> MetricEventId;
@@ -0,0 +1,335 @@
+//! Team-level aggregation for the `aggregate` telemetry privacy preset.
+//!
+//! Works councils in several countries don't allow per-developer productivity tracking, which
+//! per-commit events make possible even with identities hashed. Under `aggregate`, sinks never
+//! see individual events: committed events are folded into per-repository UTC-day totals kept
+//! in `~/.git-ai/internal/team-aggregates.json`, and once a day is over its totals go out as a
+//! single `team_aggregate` event carrying the commit count, AI and human lines, and how many
+//! commits fell into each AI-ratio bucket. Every other event type is withheld.
+//!
+//! The local store keeps the original events; only what leaves the machine is aggregated.
+
+use super::otel::CommitBreakdown;
+use crate::error::GitAiError;
+use crate::git::repo_lock;
+use crate::mdm::utils::write_atomic;
+use crate::metrics::attrs::attr_pos;
+use crate::metrics::events::TEAM_RATIO_BUCKETS;
+use crate::metrics::{
+    EventAttributes, MetricEvent, MetricEventId, PosEncoded, TeamAggregateValues,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::path::PathBuf;
+
+/// Totals for one repository over one UTC day
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DayBucket {
+    /// YYYY-MM-DD
+    pub day: String,
+    pub repo_url: Option<String>,
+    pub commits: u32,
+    pub ai_additions: u32,
+    pub human_additions: u32,
+    /// Commits per entry of `TEAM_RATIO_BUCKETS`
+    pub ratio_buckets: Vec<u32>,
+}
+
+/// Days that haven't been exported yet
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AggregateState {
+    #[serde(default)]
+    pub buckets: Vec<DayBucket>,
+}
+
+/// ~/.git-ai/internal/team-aggregates.json
+fn state_path() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_default()
+        .join(".git-ai")
+        .join("internal")
+        .join("team-aggregates.json")
+}
+
+/// The events sinks may receive. Outside aggregate mode that is `events` unchanged; in it,
+/// `events` are folded into the daily totals and only the totals of finished days come back.
+pub fn sink_events(events: &[MetricEvent]) -> Cow<'_, [MetricEvent]> {
+    if !super::privacy::TelemetryPrivacy::current().aggregates() {
+        return Cow::Borrowed(events);
+    }
+    match fold_into_state(events) {
+        Ok(aggregates) => Cow::Owned(aggregates),
+        Err(e) => {
+            super::logging::error("aggregation", &e.to_string());
+            Cow::Owned(Vec::new())
+        }
+    }
+}
+
+fn fold_into_state(events: &[MetricEvent]) -> Result<Vec<MetricEvent>, GitAiError> {
+    let path = state_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let _lock = repo_lock::lock(&path.with_extension("lock"))?;
+
+    let mut state = match std::fs::read_to_string(&path) {
+        Ok(contents) => serde_json::from_str(&contents).unwrap_or_default(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => AggregateState::default(),
+        Err(e) => return Err(e.into()),
+    };
+    let before = state.clone();
+    let aggregates = fold(&mut state, events, Utc::now());
+    if state != before {
+        let json = serde_json::to_string(&state)?;
+        write_atomic(&path, json.as_bytes())?;
+    }
+    Ok(aggregates)
+}
+
+/// Add the committed events to `state`, then remove the days before `now`'s and return them as
+/// `team_aggregate` events
+pub fn fold(
+    state: &mut AggregateState,
+    events: &[MetricEvent],
+    now: DateTime<Utc>,
+) -> Vec<MetricEvent> {
+    for event in events {
+        if MetricEventId::try_from(event.event_id) != Ok(MetricEventId::Committed) {
+            continue;
+        }
+        let Some(day) = DateTime::from_timestamp(i64::from(event.timestamp), 0) else {
+            continue;
+        };
+        let day = day.date_naive().format("%Y-%m-%d").to_string();
+        let repo_url = event
+            .attrs
+            .get(&attr_pos::REPO_URL.to_string())
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
+
+        let index = match state
+            .buckets
+            .iter()
+            .position(|b| b.day == day && b.repo_url == repo_url)
+        {
+            Some(index) => index,
+            None => {
+                state.buckets.push(DayBucket {
+                    day,
+                    repo_url,
+                    ratio_buckets: vec![0; TEAM_RATIO_BUCKETS.len()],
+                    ..DayBucket::default()
+                });
+                state.buckets.len() - 1
+            }
+        };
+        let bucket = &mut state.buckets[index];
+        let commit = CommitBreakdown::from_values(&event.values);
+        bucket.commits = bucket.commits.saturating_add(1);
+        bucket.ai_additions = bucket
+            .ai_additions
+            .saturating_add(clamp(commit.ai_additions));
+        bucket.human_additions = bucket
+            .human_additions
+            .saturating_add(clamp(commit.human_additions));
+        bucket.ratio_buckets.resize(TEAM_RATIO_BUCKETS.len(), 0);
+        bucket.ratio_buckets[ratio_bucket(&commit)] += 1;
+    }
+
+    let today = now.date_naive();
+    let (closed, open): (Vec<DayBucket>, Vec<DayBucket>) = std::mem::take(&mut state.buckets)
+        .into_iter()
+        .partition(|b| {
+            NaiveDate::parse_from_str(&b.day, "%Y-%m-%d").map_or(true, |day| day < today)
+        });
+    state.buckets = open;
+    closed.into_iter().filter_map(aggregate_event).collect()
+}
+
+/// The figures a `team_aggregate` event carries, unpacked from its positional values
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TeamTotals {
+    pub commits: u64,
+    pub ai_additions: u64,
+    pub human_additions: u64,
+    /// (bucket label, commits) for each of `TEAM_RATIO_BUCKETS`
+    pub ratio_buckets: Vec<(&'static str, u64)>,
+}
+
+impl TeamTotals {
+    pub fn from_values(values: &crate::metrics::types::SparseArray) -> Self {
+        use crate::metrics::events::team_aggregate_pos;
+
+        let scalar = |pos: usize| {
+            values
+                .get(&pos.to_string())
+                .and_then(|v| v.as_u64())
+                .unwrap_or(0)
+        };
+        let counts: Vec<u64> = values
+            .get(&team_aggregate_pos::RATIO_BUCKETS.to_string())
+            .and_then(|v| v.as_array())
+            .map(|arr| arr.iter().map(|v| v.as_u64().unwrap_or(0)).collect())
+            .unwrap_or_default();
+
+        Self {
+            commits: scalar(team_aggregate_pos::COMMITS),
+            ai_additions: scalar(team_aggregate_pos::AI_ADDITIONS),
+            human_additions: scalar(team_aggregate_pos::HUMAN_ADDITIONS),
+            ratio_buckets: TEAM_RATIO_BUCKETS
+                .iter()
+                .enumerate()
+                .map(|(i, label)| (*label, counts.get(i).copied().unwrap_or(0)))
+                .collect(),
+        }
+    }
+}
+
+/// Index into `TEAM_RATIO_BUCKETS` for a commit's share of AI lines
+pub fn ratio_bucket(commit: &CommitBreakdown) -> usize {
+    match commit.ai_ratio() {
+        None => 0,
+        Some(ratio) if commit.ai_additions == 0 || ratio <= 0.0 => 0,
+        Some(ratio) if ratio <= 0.25 => 1,
+        Some(ratio) if ratio <= 0.5 => 2,
+        Some(ratio) if ratio <= 0.75 => 3,
+        Some(_) => 4,
+    }
+}
+
+/// The bucket as an event stamped at the start of its day
+fn aggregate_event(bucket: DayBucket) -> Option<MetricEvent> {
+    let midnight = NaiveDate::parse_from_str(&bucket.day, "%Y-%m-%d")
+        .ok()?
+        .and_hms_opt(0, 0, 0)?
+        .and_utc()
+        .timestamp();
+    let values = TeamAggregateValues::new()
+        .commits(bucket.commits)
+        .ai_additions(bucket.ai_additions)
+        .human_additions(bucket.human_additions)
+        .ratio_buckets(bucket.ratio_buckets)
+        .day(bucket.day);
+    let mut attrs = EventAttributes::with_version(env!("CARGO_PKG_VERSION"));
+    if let Some(url) = bucket.repo_url {
+        attrs = attrs.repo_url(url);
+    }
+    Some(MetricEvent::with_timestamp(
+        u32::try_from(midnight).unwrap_or(0),
+        &values,
+        attrs.to_sparse(),
+    ))
+}
+
+fn clamp(n: u64) -> u32 {
+    u32::try_from(n).unwrap_or(u32::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::CommittedValues;
+    use crate::metrics::events::team_aggregate_pos;
+    use chrono::TimeZone;
+
+    fn committed(timestamp: DateTime<Utc>, repo: &str, ai: u32, human: u32) -> MetricEvent {
+        let values = CommittedValues::new()
+            .human_additions(human)
+            .git_diff_added_lines(ai + human)
+            .git_diff_deleted_lines(0)
+            .tool_model_pairs(vec!["all".to_string()])
+            .ai_additions(vec![ai]);
+        let attrs = EventAttributes::with_version("1.0.0")
+            .repo_url(repo)
+            .author("dev@acme.com")
+            .commit_sha("0123456789abcdef");
+        MetricEvent::with_timestamp(timestamp.timestamp() as u32, &values, attrs.to_sparse())
+    }
+
+    fn value<'a>(event: &'a MetricEvent, pos: usize) -> &'a serde_json::Value {
+        &event.values[&pos.to_string()]
+    }
+
+    #[test]
+    fn test_fold_exports_finished_days_without_identities() {
+        let day1 = Utc.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap();
+        let day2 = Utc.with_ymd_and_hms(2026, 3, 3, 9, 0, 0).unwrap();
+        let mut state = AggregateState::default();
+
+        let events = vec![
+            committed(day1, "https://github.com/acme/app", 0, 10),
+            committed(day1, "https://github.com/acme/app", 9, 1),
+            committed(day1, "https://github.com/acme/web", 3, 3),
+        ];
+        assert!(fold(&mut state, &events, day1).is_empty());
+        assert_eq!(state.buckets.len(), 2);
+
+        // A commit the next day closes the first day's buckets
+        let out = fold(
+            &mut state,
+            &[committed(day2, "https://github.com/acme/app", 1, 0)],
+            day2,
+        );
+        assert_eq!(out.len(), 2);
+        assert_eq!(state.buckets.len(), 1);
+        assert_eq!(state.buckets[0].day, "2026-03-03");
+
+        let app = &out[0];
+        assert_eq!(app.event_id, MetricEventId::TeamAggregate as u16);
+        assert_eq!(
+            app.timestamp as i64,
+            Utc.with_ymd_and_hms(2026, 3, 2, 0, 0, 0)
+                .unwrap()
+                .timestamp()
+        );
+        assert_eq!(value(app, team_aggregate_pos::DAY), "2026-03-02");
+        assert_eq!(value(app, team_aggregate_pos::COMMITS), 2);
+        assert_eq!(value(app, team_aggregate_pos::AI_ADDITIONS), 9);
+        assert_eq!(value(app, team_aggregate_pos::HUMAN_ADDITIONS), 11);
+        assert_eq!(
+            value(app, team_aggregate_pos::RATIO_BUCKETS),
+            &serde_json::json!([1, 0, 0, 0, 1])
+        );
+
+        assert_eq!(
+            app.attrs.get(&attr_pos::REPO_URL.to_string()),
+            Some(&serde_json::json!("https://github.com/acme/app"))
+        );
+        assert!(!app.attrs.contains_key(&attr_pos::AUTHOR.to_string()));
+        assert!(!app.attrs.contains_key(&attr_pos::COMMIT_SHA.to_string()));
+    }
+
+    #[test]
+    fn test_fold_withholds_other_events() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap();
+        let usage = MetricEvent::new(
+            &crate::metrics::AgentUsageValues::new(),
+            EventAttributes::with_version("1.0.0")
+                .author("dev@acme.com")
+                .to_sparse(),
+        );
+        let mut state = AggregateState::default();
+        assert!(fold(&mut state, &[usage], now).is_empty());
+        assert!(state.buckets.is_empty());
+    }
+
+    #[test]
+    fn test_ratio_bucket_edges() {
+        let bucket = |ai: u64, human: u64| {
+            ratio_bucket(&CommitBreakdown {
+                ai_additions: ai,
+                human_additions: human,
+                ..CommitBreakdown::default()
+            })
+        };
+        assert_eq!(bucket(0, 0), 0);
+        assert_eq!(bucket(0, 5), 0);
+        assert_eq!(bucket(1, 3), 1);
+        assert_eq!(bucket(1, 1), 2);
+        assert_eq!(bucket(3, 1), 3);
+        assert_eq!(bucket(4, 1), 4);
+        assert_eq!(bucket(5, 0), 4);
+    }
+}
@@ -0,0 +1,246 @@
+//! Hand-off between hook code and the background OTel exporter.
+//!
+//! `export_metric_event` pushes onto a bounded in-process queue and returns immediately; a worker
+//! thread records and flushes batches. Whatever the worker hasn't delivered when the process
+//! exits (or that doesn't fit in the queue) is spilled to the local store, and the next git-ai
+//! process with OTel enabled delivers it. Delivery is at-least-once: an event can be exported
+//! twice if the process exits between a successful flush and its acknowledgement.
+
+// Without the `otel` feature this module is only built for its own unit tests
+#![cfg_attr(not(feature = "otel"), allow(dead_code))]
+
+use crate::metrics::MetricEvent;
+use crate::storage::LocalStore;
+use crate::utils::debug_log;
+use std::collections::VecDeque;
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How long a claimed spill row stays reserved for the process that claimed it
+pub const SPILL_LEASE_SECS: u64 = 300;
+
+/// A metric event on its way to the exporter. `spill_id` is set for events read back from the
+/// spill, so they can be deleted once exported.
+#[derive(Debug, Clone)]
+pub struct QueuedEvent {
+    pub event: MetricEvent,
+    pub spill_id: Option<i64>,
+}
+
+impl QueuedEvent {
+    pub fn new(event: MetricEvent) -> Self {
+        Self {
+            event,
+            spill_id: None,
+        }
+    }
+}
+
+#[derive(Default)]
+struct QueueState {
+    pending: VecDeque<QueuedEvent>,
+    /// The batch the worker is exporting right now
+    in_flight: Vec<QueuedEvent>,
+    closed: bool,
+}
+
+pub struct ExportQueue {
+    state: Mutex<QueueState>,
+    ready: Condvar,
+    capacity: usize,
+}
+
+impl ExportQueue {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            state: Mutex::new(QueueState::default()),
+            ready: Condvar::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Enqueue without blocking. Hands the event back when the queue is full or closed so the
+    /// caller can spill it instead.
+    pub fn push(&self, item: QueuedEvent) -> Result<(), QueuedEvent> {
+        let Ok(mut state) = self.state.lock() else {
+            return Err(item);
+        };
+        if state.closed || state.pending.len() >= self.capacity {
+            return Err(item);
+        }
+        state.pending.push_back(item);
+        self.ready.notify_one();
+        Ok(())
+    }
+
+    /// Wait up to `timeout` for events and take up to `max` of them as the in-flight batch.
+    /// Returns an empty batch on timeout and `None` once the queue is closed and drained.
+    pub fn next_batch(&self, max: usize, timeout: Duration) -> Option<Vec<QueuedEvent>> {
+        let mut state = self.state.lock().ok()?;
+        if state.pending.is_empty() && !state.closed {
+            state = self.ready.wait_timeout(state, timeout).ok()?.0;
+        }
+        if state.pending.is_empty() {
+            return if state.closed { None } else { Some(Vec::new()) };
+        }
+
+        let take = state.pending.len().min(max.max(1));
+        let batch: Vec<QueuedEvent> = state.pending.drain(..take).collect();
+        state.in_flight = batch.clone();
+        Some(batch)
+    }
+
+    /// Mark the in-flight batch as handled (exported or spilled)
+    pub fn complete_batch(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.in_flight.clear();
+        }
+    }
+
+    /// Stop accepting events and wake the worker so it can drain and exit
+    pub fn close(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.closed = true;
+        }
+        self.ready.notify_all();
+    }
+
+    /// Everything not yet known to be exported: the in-flight batch plus the pending events
+    pub fn take_unexported(&self) -> Vec<QueuedEvent> {
+        let Ok(mut state) = self.state.lock() else {
+            return Vec::new();
+        };
+        let mut items = std::mem::take(&mut state.in_flight);
+        items.extend(state.pending.drain(..));
+        items
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn with_store<T>(
+    f: impl FnOnce(&mut LocalStore) -> Result<T, crate::error::GitAiError>,
+) -> Option<T> {
+    let result = LocalStore::global().and_then(|store| {
+        let mut store = store.lock().map_err(|e| {
+            crate::error::GitAiError::Generic(format!("Local store lock poisoned: {}", e))
+        })?;
+        f(&mut store)
+    });
+    match result {
+        Ok(value) => Some(value),
+        Err(e) => {
+            debug_log(&format!("OTel spill store unavailable: {}", e));
+            None
+        }
+    }
+}
+
+/// Persist events for a later process: new events are written to the spill, events that came
+/// from the spill have their claim released
+pub fn spill(items: Vec<QueuedEvent>) {
+    if items.is_empty() {
+        return;
+    }
+    let (from_spill, fresh): (Vec<_>, Vec<_>) =
+        items.into_iter().partition(|item| item.spill_id.is_some());
+    let release_ids: Vec<i64> = from_spill.iter().filter_map(|item| item.spill_id).collect();
+    let events: Vec<MetricEvent> = fresh.into_iter().map(|item| item.event).collect();
+
+    debug_log(&format!(
+        "Spilling {} metric events for a later OTel export",
+        events.len() + release_ids.len()
+    ));
+    with_store(|store| {
+        store.spill_otel_events(&events, now_secs())?;
+        store.release_spilled_otel_events(&release_ids)
+    });
+}
+
+/// Claim up to `limit` events spilled by earlier processes
+pub fn claim_spilled(limit: usize) -> Vec<QueuedEvent> {
+    with_store(|store| store.claim_spilled_otel_events(limit, now_secs(), SPILL_LEASE_SECS))
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(id, event)| QueuedEvent {
+            event,
+            spill_id: Some(id),
+        })
+        .collect()
+}
+
+/// Drop exported events from the spill
+pub fn ack(items: &[QueuedEvent]) {
+    let ids: Vec<i64> = items.iter().filter_map(|item| item.spill_id).collect();
+    if ids.is_empty() {
+        return;
+    }
+    with_store(|store| store.ack_spilled_otel_events(&ids));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::types::SparseArray;
+
+    fn item(timestamp: u32) -> QueuedEvent {
+        QueuedEvent::new(MetricEvent {
+            timestamp,
+            event_id: 1,
+            values: SparseArray::new(),
+            attrs: SparseArray::new(),
+        })
+    }
+
+    fn timestamps(items: &[QueuedEvent]) -> Vec<u32> {
+        items.iter().map(|i| i.event.timestamp).collect()
+    }
+
+    #[test]
+    fn test_push_rejects_when_full_or_closed() {
+        let queue = ExportQueue::new(2);
+        assert!(queue.push(item(1)).is_ok());
+        assert!(queue.push(item(2)).is_ok());
+        assert_eq!(queue.push(item(3)).unwrap_err().event.timestamp, 3);
+
+        queue.close();
+        let batch = queue.next_batch(1, Duration::ZERO).unwrap();
+        assert_eq!(timestamps(&batch), vec![1]);
+        assert!(queue.push(item(4)).is_err());
+    }
+
+    #[test]
+    fn test_unexported_includes_in_flight_batch() {
+        let queue = ExportQueue::new(10);
+        for t in 1..=3 {
+            queue.push(item(t)).unwrap();
+        }
+
+        let batch = queue.next_batch(2, Duration::ZERO).unwrap();
+        assert_eq!(timestamps(&batch), vec![1, 2]);
+        assert_eq!(timestamps(&queue.take_unexported()), vec![1, 2, 3]);
+
+        queue.push(item(4)).unwrap();
+        queue.next_batch(10, Duration::ZERO).unwrap();
+        queue.complete_batch();
+        assert!(queue.take_unexported().is_empty());
+    }
+
+    #[test]
+    fn test_next_batch_times_out_then_ends_after_close() {
+        let queue = ExportQueue::new(10);
+        assert_eq!(
+            queue
+                .next_batch(10, Duration::from_millis(1))
+                .map(|b| b.len()),
+            Some(0)
+        );
+        queue.close();
+        assert!(queue.next_batch(10, Duration::from_millis(1)).is_none());
+    }
+}
@@ -3,6 +3,8 @@ use crate::config::{get_or_create_distinct_id, Config};
 use crate::git::find_repository_in_path;
 use crate::metrics::db::MetricsDatabase;
 use crate::metrics::{MetricEvent, MetricsBatch};
+use crate::observability::routing;
+use crate::observability::webhook::{self, WebhookConfig};
 use futures::stream::{self, StreamExt};
 use serde_json::{json, Value};
 use std::collections::BTreeMap;
@@ -43,6 +45,15 @@ pub fn handle_flush_logs(args: &[String]) {
         .filter(|s| !s.is_empty())
         .unwrap_or_else(|| "https://us.i.posthog.com".to_string());
 
+    // Batches an earlier flush couldn't deliver go first, even when there are no new logs
+    let webhook_config = WebhookConfig::from_env();
+    if webhook_config.enabled {
+        let delivered = webhook::retry_pending(&webhook_config);
+        if delivered > 0 {
+            eprintln!("Delivered {} pending webhook batches", delivered);
+        }
+    }
+
     // Get the global logs directory
     let Some(logs_dir) = get_logs_directory() else {
         // No logs directory - nothing to do, exit successfully
@@ -688,6 +699,14 @@ fn send_metrics_envelope(envelope: &Value, uploader: &MetricsUploader) -> bool {
         return true; // Nothing to upload, but not a failure
     }
 
+    webhook::export_metric_events(&routing::filter("webhook", &events));
+
+    // Events routed away from the API are neither uploaded nor kept for a later upload
+    let events = routing::filter("api", &events);
+    if events.is_empty() {
+        return true;
+    }
+
     // Build batch for upload
     let batch = MetricsBatch::new(events.clone());
 
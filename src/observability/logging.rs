@@ -0,0 +1,267 @@
+//! Leveled diagnostic logging to stderr and to a per-repository log file.
+//!
+//! Configured with:
+//! - `GIT_AI_LOG`: stderr level (`off`, `error`, `warn`, `info`, `debug`, `trace`). Defaults to
+//!   `warn`, or `debug` in debug builds and with `GIT_AI_DEBUG=1`.
+//! - `GIT_AI_LOG_FORMAT`: `text` (default) or `json`, one object per line, for both outputs.
+//! - `GIT_AI_LOG_FILE`: level written to `.git/ai/logs/git-ai.log` (default `info`). The file
+//!   rotates at 1 MiB and keeps three old copies, so a hook that misbehaved inside an editor can
+//!   be diagnosed after the fact with `GIT_AI_LOG_FILE=trace` set in the shell profile.
+//!
+//! With the `tracing` feature every record is also emitted as a `tracing` event under the
+//! `git_ai` target, for applications that install their own subscriber.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Size at which the log file is rotated
+const MAX_LOG_FILE_BYTES: u64 = 1024 * 1024;
+/// Rotated copies kept next to the live file (git-ai.log.1 is the newest)
+const ROTATED_LOG_FILES: usize = 3;
+const LOG_FILE_NAME: &str = "git-ai.log";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    /// `Some(None)` for `off`, None if `input` isn't a level
+    fn parse(input: &str) -> Option<Option<Self>> {
+        match input.trim().to_lowercase().as_str() {
+            "off" | "none" | "0" => Some(None),
+            "error" => Some(Some(LogLevel::Error)),
+            "warn" | "warning" => Some(Some(LogLevel::Warn)),
+            "info" => Some(Some(LogLevel::Info)),
+            "debug" | "1" => Some(Some(LogLevel::Debug)),
+            "trace" => Some(Some(LogLevel::Trace)),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Error => "error",
+            LogLevel::Warn => "warn",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+            LogLevel::Trace => "trace",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogSettings {
+    /// Most verbose level printed to stderr; None prints nothing
+    pub stderr_level: Option<LogLevel>,
+    /// Most verbose level written to the log file; None writes nothing
+    pub file_level: Option<LogLevel>,
+    pub json: bool,
+}
+
+impl LogSettings {
+    fn current() -> &'static LogSettings {
+        static SETTINGS: OnceLock<LogSettings> = OnceLock::new();
+        SETTINGS.get_or_init(|| {
+            LogSettings::from_values(
+                std::env::var("GIT_AI_LOG").ok().as_deref(),
+                std::env::var("GIT_AI_LOG_FILE").ok().as_deref(),
+                std::env::var("GIT_AI_LOG_FORMAT").ok().as_deref(),
+                crate::utils::is_debug_enabled(),
+            )
+        })
+    }
+
+    /// Resolve the raw setting values. `debug` is the legacy `GIT_AI_DEBUG` switch, which only
+    /// applies when `GIT_AI_LOG` is unset.
+    pub fn from_values(
+        level: Option<&str>,
+        file_level: Option<&str>,
+        format: Option<&str>,
+        debug: bool,
+    ) -> Self {
+        let default_level = if debug {
+            LogLevel::Debug
+        } else {
+            LogLevel::Warn
+        };
+        let parse = |value: Option<&str>, name: &str, default: Option<LogLevel>| match value {
+            None => default,
+            Some(value) => LogLevel::parse(value).unwrap_or_else(|| {
+                eprintln!("Warning: Unknown {} level '{}', ignoring", name, value);
+                default
+            }),
+        };
+        LogSettings {
+            stderr_level: parse(level, "GIT_AI_LOG", Some(default_level)),
+            file_level: parse(file_level, "GIT_AI_LOG_FILE", Some(LogLevel::Info)),
+            json: format.is_some_and(|f| f.trim().eq_ignore_ascii_case("json")),
+        }
+    }
+}
+
+static LOG_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Send file records to `dir` from now on. The first repository opened by the process wins.
+pub fn set_log_dir(dir: &Path) {
+    let _ = LOG_DIR.set(dir.to_path_buf());
+}
+
+pub fn log(level: LogLevel, target: &str, message: &str) {
+    let settings = LogSettings::current();
+    if settings.stderr_level.is_some_and(|max| level <= max) {
+        eprintln!("{}", format_stderr(settings, level, target, message));
+    }
+    if let Some(dir) = LOG_DIR.get()
+        && settings.file_level.is_some_and(|max| level <= max)
+    {
+        // Logging must never fail the command that logs
+        let _ = append_to_file(dir, &format_file(settings, level, target, message));
+    }
+    #[cfg(feature = "tracing")]
+    emit_tracing_event(level, target, message);
+}
+
+pub fn error(target: &str, message: &str) {
+    log(LogLevel::Error, target, message);
+}
+
+pub fn warn(target: &str, message: &str) {
+    log(LogLevel::Warn, target, message);
+}
+
+pub fn info(target: &str, message: &str) {
+    log(LogLevel::Info, target, message);
+}
+
+pub fn debug(target: &str, message: &str) {
+    log(LogLevel::Debug, target, message);
+}
+
+pub fn trace(target: &str, message: &str) {
+    log(LogLevel::Trace, target, message);
+}
+
+fn format_stderr(settings: &LogSettings, level: LogLevel, target: &str, message: &str) -> String {
+    if settings.json {
+        return json_record(level, target, message);
+    }
+    match level {
+        LogLevel::Error => format!("git-ai: error: {}", message),
+        LogLevel::Warn => format!("git-ai: warning: {}", message),
+        LogLevel::Info => format!("git-ai: {}", message),
+        LogLevel::Debug | LogLevel::Trace => format!("\x1b[1;33m[git-ai]\x1b[0m {}", message),
+    }
+}
+
+fn format_file(settings: &LogSettings, level: LogLevel, target: &str, message: &str) -> String {
+    if settings.json {
+        return json_record(level, target, message);
+    }
+    format!(
+        "{} {:<5} [{}] {}: {}",
+        chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+        level.as_str().to_uppercase(),
+        std::process::id(),
+        target,
+        message
+    )
+}
+
+fn json_record(level: LogLevel, target: &str, message: &str) -> String {
+    serde_json::json!({
+        "timestamp": chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+        "level": level.as_str(),
+        "target": target,
+        "pid": std::process::id(),
+        "message": message,
+    })
+    .to_string()
+}
+
+fn append_to_file(dir: &Path, line: &str) -> std::io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(LOG_FILE_NAME);
+    if fs::metadata(&path).is_ok_and(|meta| meta.len() >= MAX_LOG_FILE_BYTES) {
+        rotate(&path)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+    writeln!(file, "{}", line)
+}
+
+/// git-ai.log -> git-ai.log.1 -> ... -> git-ai.log.N, dropping the oldest
+fn rotate(path: &Path) -> std::io::Result<()> {
+    let rotated = |n: usize| PathBuf::from(format!("{}.{}", path.display(), n));
+    let _ = fs::remove_file(rotated(ROTATED_LOG_FILES));
+    for n in (1..ROTATED_LOG_FILES).rev() {
+        let _ = fs::rename(rotated(n), rotated(n + 1));
+    }
+    fs::rename(path, rotated(1))
+}
+
+#[cfg(feature = "tracing")]
+fn emit_tracing_event(level: LogLevel, target: &str, message: &str) {
+    match level {
+        LogLevel::Error => tracing::error!(target: "git_ai", module = target, "{}", message),
+        LogLevel::Warn => tracing::warn!(target: "git_ai", module = target, "{}", message),
+        LogLevel::Info => tracing::info!(target: "git_ai", module = target, "{}", message),
+        LogLevel::Debug => tracing::debug!(target: "git_ai", module = target, "{}", message),
+        LogLevel::Trace => tracing::trace!(target: "git_ai", module = target, "{}", message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings_from_values() {
+        let defaults = LogSettings::from_values(None, None, None, false);
+        assert_eq!(defaults.stderr_level, Some(LogLevel::Warn));
+        assert_eq!(defaults.file_level, Some(LogLevel::Info));
+        assert!(!defaults.json);
+
+        // GIT_AI_DEBUG raises the default, GIT_AI_LOG overrides it
+        let debug = LogSettings::from_values(None, None, None, true);
+        assert_eq!(debug.stderr_level, Some(LogLevel::Debug));
+        let quiet = LogSettings::from_values(Some("off"), Some("trace"), Some("JSON"), true);
+        assert_eq!(quiet.stderr_level, None);
+        assert_eq!(quiet.file_level, Some(LogLevel::Trace));
+        assert!(quiet.json);
+
+        let bogus = LogSettings::from_values(Some("loud"), None, None, false);
+        assert_eq!(bogus.stderr_level, Some(LogLevel::Warn));
+    }
+
+    #[test]
+    fn test_json_record_fields() {
+        let record: serde_json::Value =
+            serde_json::from_str(&json_record(LogLevel::Warn, "hooks::push", "oops")).unwrap();
+        assert_eq!(record["level"], "warn");
+        assert_eq!(record["target"], "hooks::push");
+        assert_eq!(record["message"], "oops");
+    }
+
+    #[test]
+    fn test_log_file_rotates() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(LOG_FILE_NAME);
+        for generation in 0..=ROTATED_LOG_FILES + 1 {
+            fs::write(&path, vec![b'x'; MAX_LOG_FILE_BYTES as usize]).unwrap();
+            append_to_file(dir.path(), &format!("generation {}", generation)).unwrap();
+        }
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            format!("generation {}\n", ROTATED_LOG_FILES + 1)
+        );
+        let rotated: Vec<_> = fs::read_dir(dir.path()).unwrap().flatten().collect();
+        assert_eq!(rotated.len(), ROTATED_LOG_FILES + 1);
+    }
+}
@@ -8,8 +8,18 @@ use std::time::Duration;
 
 use crate::metrics::{MetricEvent, METRICS_API_VERSION};
 
+pub mod aggregation;
+#[cfg(any(feature = "otel", test))]
+pub mod export_queue;
 pub mod flush;
+pub mod logging;
 pub mod otel;
+pub mod privacy;
+pub mod prometheus;
+pub mod routing;
+pub mod spans;
+pub mod statsd;
+pub mod webhook;
 pub mod wrapper_performance_targets;
 
 /// Maximum events per metrics envelope
@@ -187,10 +197,8 @@ pub fn spawn_background_flush() {
         return;
     }
 
-    use std::process::Command;
-
-    if let Ok(exe) = crate::utils::current_git_ai_exe() {
-        let _ = Command::new(exe)
+    if let Ok(mut cmd) = crate::utils::background_git_ai_command() {
+        let _ = cmd
             .arg("flush-logs")
             .stdout(std::process::Stdio::null())
             .stderr(std::process::Stdio::null())
@@ -205,19 +213,34 @@ pub fn spawn_background_flush() {
 /// store them in SQLite for later upload.
 ///
 /// If OpenTelemetry export is enabled (via `otel` feature and configuration),
-/// events are also exported to the configured OTLP endpoint.
+/// events are also exported to the configured OTLP endpoint. Likewise for the
+/// `prometheus` feature and its exposition file, and for StatsD when `GIT_AI_METRICS_SINK`
+/// includes `statsd`. `metrics_routes` can narrow which events each sink receives.
 pub fn log_metrics(events: Vec<MetricEvent>) {
     if events.is_empty() {
         return;
     }
 
-    // Export to OpenTelemetry if enabled (non-blocking, won't impact existing pipeline)
-    for event in &events {
-        otel::export_metric_event(event);
+    // Under the aggregate privacy preset only finished days' team totals leave the machine
+    let exported = aggregation::sink_events(&events);
+
+    // Export to OpenTelemetry if enabled (queued for a background exporter, won't impact existing pipeline)
+    for event in exported.iter() {
+        if routing::allows("otel", event) {
+            otel::export_metric_event(event);
+        }
+        if routing::allows("prometheus", event) {
+            prometheus::export_metric_event(event);
+        }
+        if routing::allows("statsd", event) {
+            statsd::export_metric_event(event);
+        }
     }
+    crate::storage::record_metric_events(&events);
 
-    // Split into chunks of MAX_METRICS_PER_ENVELOPE
-    for chunk in events.chunks(MAX_METRICS_PER_ENVELOPE) {
+    // Split into chunks of MAX_METRICS_PER_ENVELOPE; flush-logs uploads these and sends them to
+    // the webhook
+    for chunk in exported.chunks(MAX_METRICS_PER_ENVELOPE) {
         let envelope = MetricsEnvelope {
             event_type: "metrics".to_string(),
             timestamp: chrono::Utc::now().to_rfc3339(),
@@ -1,12 +1,13 @@
-//! OpenTelemetry metrics export module.
+//! OpenTelemetry metrics and trace export module.
 //!
 //! This module provides OpenTelemetry export capability for git-ai metrics,
-//! enabling visualization in Grafana dashboards via OTLP protocol.
+//! enabling visualization in Grafana dashboards via OTLP protocol, and for
+//! hook execution spans (see `observability::spans`) to Tempo/Jaeger.
 //!
 //! The module is conditionally compiled only when the `otel` feature is enabled.
 
 #[cfg(feature = "otel")]
-use opentelemetry::metrics::{Counter, Histogram, Meter, MeterProvider};
+use opentelemetry::metrics::{Counter, Gauge, Histogram, Meter, MeterProvider};
 #[cfg(feature = "otel")]
 use opentelemetry::KeyValue;
 #[cfg(feature = "otel")]
@@ -16,14 +17,22 @@ use std::collections::HashMap;
 #[cfg(feature = "otel")]
 use opentelemetry_sdk::metrics::{PeriodicReader, SdkMeterProvider};
 #[cfg(feature = "otel")]
+use opentelemetry_sdk::trace::SdkTracerProvider;
+#[cfg(feature = "otel")]
 use opentelemetry_sdk::Resource;
 #[cfg(feature = "otel")]
-use std::sync::OnceLock;
+use std::sync::{Arc, Mutex, OnceLock};
 #[cfg(feature = "otel")]
 use std::time::Duration;
 
 #[cfg(feature = "otel")]
-use crate::metrics::events::{checkpoint_pos, committed_pos};
+use super::export_queue::{self, ExportQueue, QueuedEvent};
+#[cfg(feature = "otel")]
+use super::logging;
+#[cfg(feature = "otel")]
+use super::spans::FinishedSpan;
+#[cfg(feature = "otel")]
+use crate::metrics::events::{agent_usage_pos, checkpoint_pos};
 #[cfg(feature = "otel")]
 use crate::metrics::types::{MetricEvent, MetricEventId};
 
@@ -33,6 +42,12 @@ pub const DEFAULT_OTEL_ENDPOINT: &str = "http://localhost:4317";
 /// Default export interval in seconds
 pub const DEFAULT_EXPORT_INTERVAL_SECS: u64 = 60;
 
+/// Default number of metric events held in memory for the background exporter
+pub const DEFAULT_QUEUE_CAPACITY: usize = 1024;
+
+/// Default time the exiting process gives the exporter before spilling what's left to disk
+pub const DEFAULT_SHUTDOWN_GRACE_MS: u64 = 200;
+
 /// Service name for OpenTelemetry resource
 pub const SERVICE_NAME: &str = "git-ai";
 
@@ -63,6 +78,12 @@ pub struct OtelConfig {
     pub auth_header: Option<String>,
     /// OTLP transport protocol (gRPC or HTTP/protobuf)
     pub protocol: OtelProtocol,
+    /// Whether hook execution spans are exported as traces (independent of metrics)
+    pub traces_enabled: bool,
+    /// In-memory queue size; events beyond it are spilled to disk instead of blocking
+    pub queue_capacity: usize,
+    /// How long process exit waits on the exporter before spilling queued events
+    pub shutdown_grace_ms: u64,
 }
 
 impl Default for OtelConfig {
@@ -73,36 +94,43 @@ impl Default for OtelConfig {
             export_interval_secs: DEFAULT_EXPORT_INTERVAL_SECS,
             auth_header: None,
             protocol: OtelProtocol::default(),
+            traces_enabled: false,
+            queue_capacity: DEFAULT_QUEUE_CAPACITY,
+            shutdown_grace_ms: DEFAULT_SHUTDOWN_GRACE_MS,
         }
     }
 }
 
 impl OtelConfig {
-    /// Create OtelConfig from environment variables
+    /// Create OtelConfig from the `otel_*` configuration keys, which the `GIT_AI_OTEL_*`
+    /// environment variables override
     pub fn from_env() -> Self {
-        let enabled = std::env::var("GIT_AI_OTEL_ENABLED")
-            .map(|v| v == "1" || v.to_lowercase() == "true")
-            .unwrap_or(false);
-
-        let endpoint = std::env::var("GIT_AI_OTEL_ENDPOINT")
-            .unwrap_or_else(|_| DEFAULT_OTEL_ENDPOINT.to_string());
-
-        let export_interval_secs = std::env::var("GIT_AI_OTEL_EXPORT_INTERVAL")
-            .ok()
-            .and_then(|v| v.parse().ok())
-            .unwrap_or(DEFAULT_EXPORT_INTERVAL_SECS);
-
-        let auth_header = std::env::var("GIT_AI_OTEL_AUTH_HEADER")
-            .ok()
-            .filter(|s| !s.is_empty());
-
-        let protocol = std::env::var("GIT_AI_OTEL_PROTOCOL")
-            .ok()
-            .map(|v| match v.to_lowercase().as_str() {
-                "http" => OtelProtocol::Http,
-                _ => OtelProtocol::Grpc,
-            })
-            .unwrap_or_default();
+        let config = crate::config::Config::get();
+        let enabled = config.is_otel_enabled();
+
+        let endpoint = config
+            .otel_endpoint()
+            .unwrap_or(DEFAULT_OTEL_ENDPOINT)
+            .to_string();
+
+        let export_interval_secs = config.otel_export_interval_secs();
+
+        let auth_header = config.otel_auth_header().map(str::to_string);
+
+        let protocol = match config.otel_protocol() {
+            "http" => OtelProtocol::Http,
+            _ => OtelProtocol::Grpc,
+        };
+
+        let traces_enabled = config.otel_traces_enabled();
+
+        let queue_capacity = config
+            .otel_queue_capacity()
+            .unwrap_or(DEFAULT_QUEUE_CAPACITY);
+
+        let shutdown_grace_ms = config
+            .otel_shutdown_grace_ms()
+            .unwrap_or(DEFAULT_SHUTDOWN_GRACE_MS);
 
         Self {
             endpoint,
@@ -110,8 +138,179 @@ impl OtelConfig {
             export_interval_secs,
             auth_header,
             protocol,
+            traces_enabled,
+            queue_capacity,
+            shutdown_grace_ms,
+        }
+    }
+}
+
+/// Bucket boundaries for `git_ai.commit.ai_ratio`
+#[cfg(feature = "otel")]
+const AI_RATIO_BOUNDARIES: [f64; 11] = [0.0, 0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9, 1.0];
+
+/// Bucket boundaries for `git_ai.commit.size`, in lines
+#[cfg(feature = "otel")]
+const COMMIT_SIZE_BOUNDARIES: [f64; 11] = [
+    1.0, 5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0, 5000.0,
+];
+
+/// Bucket boundaries for `git_ai.pr.review_duration`, in seconds (10 minutes to 4 weeks)
+#[cfg(feature = "otel")]
+const REVIEW_DURATION_BOUNDARIES: [f64; 10] = [
+    600.0, 1800.0, 3600.0, 14400.0, 28800.0, 86400.0, 172800.0, 345600.0, 604800.0, 2419200.0,
+];
+
+/// AI line counts for one tool/model in a committed event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolCommitStats {
+    pub tool: String,
+    pub model: String,
+    pub ai_additions: u64,
+    pub ai_accepted: u64,
+    pub mixed_additions: u64,
+}
+
+/// The figures a committed event carries, unpacked from its positional values.
+/// The parallel arrays hold the "all" aggregate at index 0 and one entry per tool/model after
+/// it, so aggregates come from index 0 rather than summing the arrays.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommitBreakdown {
+    pub human_additions: u64,
+    pub diff_added: u64,
+    pub diff_deleted: u64,
+    pub ai_additions: u64,
+    pub ai_accepted: u64,
+    /// AI lines the commit removed by reverting an earlier commit
+    pub ai_reverted: u64,
+    pub per_tool: Vec<ToolCommitStats>,
+}
+
+impl CommitBreakdown {
+    pub fn from_values(values: &crate::metrics::types::SparseArray) -> Self {
+        use crate::metrics::events::committed_pos;
+
+        let scalar = |pos: usize| {
+            values
+                .get(&pos.to_string())
+                .and_then(|v| v.as_u64())
+                .unwrap_or(0)
+        };
+        let array = |pos: usize| -> Vec<u64> {
+            values
+                .get(&pos.to_string())
+                .and_then(|v| v.as_array())
+                .map(|arr| arr.iter().map(|v| v.as_u64().unwrap_or(0)).collect())
+                .unwrap_or_default()
+        };
+        let pairs: Vec<String> = values
+            .get(&committed_pos::TOOL_MODEL_PAIRS.to_string())
+            .and_then(|v| v.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .map(|v| v.as_str().unwrap_or_default().to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let ai_additions = array(committed_pos::AI_ADDITIONS);
+        let ai_accepted = array(committed_pos::AI_ACCEPTED);
+        let mixed_additions = array(committed_pos::MIXED_ADDITIONS);
+
+        let has_aggregate = pairs.first().is_some_and(|p| p == "all");
+        let (aggregate_additions, aggregate_accepted) = if has_aggregate {
+            (
+                ai_additions.first().copied().unwrap_or(0),
+                ai_accepted.first().copied().unwrap_or(0),
+            )
+        } else {
+            (ai_additions.iter().sum(), ai_accepted.iter().sum())
+        };
+
+        let per_tool = pairs
+            .iter()
+            .enumerate()
+            .skip(usize::from(has_aggregate))
+            .map(|(i, pair)| {
+                let (tool, model) = pair
+                    .split_once("::")
+                    .or_else(|| pair.split_once(':'))
+                    .unwrap_or((pair.as_str(), "unknown"));
+                ToolCommitStats {
+                    tool: tool.to_string(),
+                    model: model.to_string(),
+                    ai_additions: ai_additions.get(i).copied().unwrap_or(0),
+                    ai_accepted: ai_accepted.get(i).copied().unwrap_or(0),
+                    mixed_additions: mixed_additions.get(i).copied().unwrap_or(0),
+                }
+            })
+            .collect();
+
+        Self {
+            human_additions: scalar(committed_pos::HUMAN_ADDITIONS),
+            diff_added: scalar(committed_pos::GIT_DIFF_ADDED_LINES),
+            diff_deleted: scalar(committed_pos::GIT_DIFF_DELETED_LINES),
+            ai_additions: aggregate_additions,
+            ai_accepted: aggregate_accepted,
+            ai_reverted: scalar(committed_pos::AI_REVERTED),
+            per_tool,
         }
     }
+
+    /// AI lines over AI plus human lines; `None` for commits that add nothing
+    pub fn ai_ratio(&self) -> Option<f64> {
+        let total = self.ai_additions + self.human_additions;
+        (total > 0).then(|| (self.ai_additions as f64 / total as f64).min(1.0))
+    }
+
+    pub fn size(&self) -> u64 {
+        self.diff_added + self.diff_deleted
+    }
+}
+
+/// The figures a pr_merged event carries, unpacked from its positional values
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PrBreakdown {
+    pub ai_additions: u64,
+    pub human_additions: u64,
+    pub diff_added: u64,
+    pub diff_deleted: u64,
+    /// Seconds from opening to merge, when known
+    pub review_duration: Option<u64>,
+    pub reverted_commits: u64,
+}
+
+impl PrBreakdown {
+    pub fn from_values(values: &crate::metrics::types::SparseArray) -> Self {
+        use crate::metrics::events::pr_merged_pos;
+
+        let scalar = |pos: usize| values.get(&pos.to_string()).and_then(|v| v.as_u64());
+        Self {
+            ai_additions: scalar(pr_merged_pos::AI_ADDITIONS).unwrap_or(0),
+            human_additions: scalar(pr_merged_pos::HUMAN_ADDITIONS).unwrap_or(0),
+            diff_added: scalar(pr_merged_pos::GIT_DIFF_ADDED_LINES).unwrap_or(0),
+            diff_deleted: scalar(pr_merged_pos::GIT_DIFF_DELETED_LINES).unwrap_or(0),
+            review_duration: scalar(pr_merged_pos::REVIEW_DURATION),
+            reverted_commits: values
+                .get(&pr_merged_pos::REVERTED_COMMITS.to_string())
+                .and_then(|v| v.as_array())
+                .map_or(0, |arr| arr.len() as u64),
+        }
+    }
+
+    /// Label from `TEAM_RATIO_BUCKETS` for the pull request's share of AI lines, so pull
+    /// requests and daily team aggregates bucket the same way
+    pub fn ratio_bucket(&self) -> &'static str {
+        let commit = CommitBreakdown {
+            ai_additions: self.ai_additions,
+            human_additions: self.human_additions,
+            ..CommitBreakdown::default()
+        };
+        crate::metrics::events::TEAM_RATIO_BUCKETS[super::aggregation::ratio_bucket(&commit)]
+    }
+
+    pub fn size(&self) -> u64 {
+        self.diff_added + self.diff_deleted
+    }
 }
 
 /// OpenTelemetry metrics instruments for git-ai
@@ -127,14 +326,46 @@ pub struct OtelMetrics {
     pub committed_diff_deleted: Counter<u64>,
     /// Counter for AI accepted lines
     pub committed_ai_accepted: Counter<u64>,
+    /// Counter for AI lines removed by reverting the commits that added them
+    pub committed_ai_reverted: Counter<u64>,
     /// Counter for agent usage events
     pub agent_usage_count: Counter<u64>,
+    /// Counter for tokens agents report spending, split by `token_type` (input or output)
+    pub agent_usage_tokens: Counter<u64>,
+    /// Counter for the estimated cost agents report, in US dollars
+    pub agent_usage_cost_usd: Counter<f64>,
     /// Counter for checkpoint events
     pub checkpoint_count: Counter<u64>,
     /// Histogram for checkpoint lines added
     pub checkpoint_lines_added: Histogram<u64>,
     /// Histogram for checkpoint lines deleted
     pub checkpoint_lines_deleted: Histogram<u64>,
+    /// Histogram of AI lines / (AI + human lines) per commit
+    pub commit_ai_ratio: Histogram<f64>,
+    /// AI ratio of the most recent commit
+    pub last_commit_ai_ratio: Gauge<f64>,
+    /// Histogram of lines added plus deleted per commit
+    pub commit_size: Histogram<u64>,
+    /// Counter for AI additions, attributed to the tool and model that wrote them
+    pub tool_ai_additions: Counter<u64>,
+    /// Counter for AI lines accepted unedited, per tool and model
+    pub tool_ai_accepted: Counter<u64>,
+    /// Counter for AI lines a human edited before committing, per tool and model
+    pub tool_mixed_additions: Counter<u64>,
+    /// Counter for commits in daily team aggregates, by `ai_ratio_bucket`
+    pub team_commits: Counter<u64>,
+    /// Counter for AI additions in daily team aggregates
+    pub team_ai_additions: Counter<u64>,
+    /// Counter for human additions in daily team aggregates
+    pub team_human_additions: Counter<u64>,
+    /// Counter for merged pull requests, by `ai_ratio_bucket`
+    pub pr_merged_count: Counter<u64>,
+    /// Histogram of seconds from opening a pull request to merging it, by `ai_ratio_bucket`
+    pub pr_review_duration: Histogram<u64>,
+    /// Histogram of lines added plus deleted per merged pull request, by `ai_ratio_bucket`
+    pub pr_size: Histogram<u64>,
+    /// Counter for commits that merged pull requests revert, by `ai_ratio_bucket`
+    pub pr_reverted_commits: Counter<u64>,
 }
 
 #[cfg(feature = "otel")]
@@ -162,10 +393,22 @@ impl OtelMetrics {
                 .u64_counter("git_ai.committed.ai_accepted")
                 .with_description("Number of AI-generated lines accepted into commit")
                 .build(),
+            committed_ai_reverted: meter
+                .u64_counter("git_ai.committed.ai_reverted")
+                .with_description("Number of AI-generated lines removed by reverts")
+                .build(),
             agent_usage_count: meter
                 .u64_counter("git_ai.agent_usage.count")
                 .with_description("Number of AI agent usage events")
                 .build(),
+            agent_usage_tokens: meter
+                .u64_counter("git_ai.agent_usage.tokens")
+                .with_description("Tokens spent by AI agents, by token_type (input or output)")
+                .build(),
+            agent_usage_cost_usd: meter
+                .f64_counter("git_ai.agent_usage.cost_usd")
+                .with_description("Estimated cost of AI agent interactions in US dollars")
+                .build(),
             checkpoint_count: meter
                 .u64_counter("git_ai.checkpoint.count")
                 .with_description("Number of checkpoint events")
@@ -178,6 +421,69 @@ impl OtelMetrics {
                 .u64_histogram("git_ai.checkpoint.lines_deleted")
                 .with_description("Lines deleted per checkpoint")
                 .build(),
+            commit_ai_ratio: meter
+                .f64_histogram("git_ai.commit.ai_ratio")
+                .with_description("Share of committed lines written by AI, per commit")
+                .with_unit("1")
+                .with_boundaries(AI_RATIO_BOUNDARIES.to_vec())
+                .build(),
+            last_commit_ai_ratio: meter
+                .f64_gauge("git_ai.commit.last_ai_ratio")
+                .with_description("Share of committed lines written by AI in the latest commit")
+                .with_unit("1")
+                .build(),
+            commit_size: meter
+                .u64_histogram("git_ai.commit.size")
+                .with_description("Lines added plus deleted per commit")
+                .with_unit("{line}")
+                .with_boundaries(COMMIT_SIZE_BOUNDARIES.to_vec())
+                .build(),
+            tool_ai_additions: meter
+                .u64_counter("git_ai.committed.tool.ai_additions")
+                .with_description("AI-generated lines committed, by tool and model")
+                .build(),
+            tool_ai_accepted: meter
+                .u64_counter("git_ai.committed.tool.ai_accepted")
+                .with_description("AI-generated lines accepted without edits, by tool and model")
+                .build(),
+            tool_mixed_additions: meter
+                .u64_counter("git_ai.committed.tool.mixed_additions")
+                .with_description(
+                    "AI-generated lines edited by a human before commit, by tool and model",
+                )
+                .build(),
+            team_commits: meter
+                .u64_counter("git_ai.team.commits")
+                .with_description("Commits in daily team aggregates, by AI ratio bucket")
+                .build(),
+            team_ai_additions: meter
+                .u64_counter("git_ai.team.ai_additions")
+                .with_description("AI-generated lines in daily team aggregates")
+                .build(),
+            team_human_additions: meter
+                .u64_counter("git_ai.team.human_additions")
+                .with_description("Human-written lines in daily team aggregates")
+                .build(),
+            pr_merged_count: meter
+                .u64_counter("git_ai.pr.merged")
+                .with_description("Merged pull requests, by AI ratio bucket")
+                .build(),
+            pr_review_duration: meter
+                .u64_histogram("git_ai.pr.review_duration")
+                .with_description("Seconds from opening a pull request to merging it")
+                .with_unit("s")
+                .with_boundaries(REVIEW_DURATION_BOUNDARIES.to_vec())
+                .build(),
+            pr_size: meter
+                .u64_histogram("git_ai.pr.size")
+                .with_description("Lines added plus deleted per merged pull request")
+                .with_unit("{line}")
+                .with_boundaries(COMMIT_SIZE_BOUNDARIES.to_vec())
+                .build(),
+            pr_reverted_commits: meter
+                .u64_counter("git_ai.pr.reverted_commits")
+                .with_description("Commits reverted by merged pull requests")
+                .build(),
         }
     }
 }
@@ -206,7 +512,7 @@ pub fn init_otel(config: &OtelConfig) -> bool {
         match init_otel_internal(config) {
             Ok(state) => Some(state),
             Err(e) => {
-                eprintln!("[OTel] Failed to initialize OpenTelemetry: {}", e);
+                logging::error("otel", &format!("Failed to initialize OpenTelemetry: {}", e));
                 None
             }
         }
@@ -291,32 +597,124 @@ fn ensure_otel_initialized() -> bool {
     init_otel(&config)
 }
 
-/// Export a metric event to OpenTelemetry.
-/// This is a non-blocking operation that won't impact the existing metrics pipeline.
+/// Events recorded and flushed per export by the background worker
 #[cfg(feature = "otel")]
-pub fn export_metric_event(event: &MetricEvent) {
-    if !ensure_otel_initialized() {
+const EXPORT_BATCH_SIZE: usize = 256;
+
+/// Spilled events a process takes on at startup, so one backlog doesn't stall a single hook
+#[cfg(feature = "otel")]
+const SPILL_DRAIN_LIMIT: usize = 2048;
+
+/// Background exporter: owns the queue `export_metric_event` feeds and the thread draining it
+#[cfg(feature = "otel")]
+struct ExportWorker {
+    queue: Arc<ExportQueue>,
+    handle: Mutex<Option<std::thread::JoinHandle<()>>>,
+    shutdown_grace: Duration,
+}
+
+#[cfg(feature = "otel")]
+static EXPORT_WORKER: OnceLock<Option<ExportWorker>> = OnceLock::new();
+
+/// Start the export worker on first use (after OTel itself initialized successfully)
+#[cfg(feature = "otel")]
+fn export_worker() -> Option<&'static ExportWorker> {
+    EXPORT_WORKER
+        .get_or_init(|| {
+            if !ensure_otel_initialized() {
+                return None;
+            }
+            let config = OtelConfig::from_env();
+            let queue = Arc::new(ExportQueue::new(config.queue_capacity));
+            let worker_queue = Arc::clone(&queue);
+            let handle = std::thread::Builder::new()
+                .name("git-ai-otel-export".to_string())
+                .spawn(move || run_export_worker(&worker_queue));
+            match handle {
+                Ok(handle) => Some(ExportWorker {
+                    queue,
+                    handle: Mutex::new(Some(handle)),
+                    shutdown_grace: Duration::from_millis(config.shutdown_grace_ms),
+                }),
+                Err(e) => {
+                    logging::error("otel", &format!("Failed to start export worker: {}", e));
+                    None
+                }
+            }
+        })
+        .as_ref()
+}
+
+#[cfg(feature = "otel")]
+fn run_export_worker(queue: &ExportQueue) {
+    let Some(Some(state)) = OTEL_STATE.get() else {
         return;
+    };
+
+    // Deliver what earlier processes left behind before this process's own events
+    let backlog = export_queue::claim_spilled(SPILL_DRAIN_LIMIT);
+    for batch in backlog.chunks(EXPORT_BATCH_SIZE) {
+        export_batch(state, batch.to_vec());
+    }
+
+    while let Some(batch) = queue.next_batch(EXPORT_BATCH_SIZE, Duration::from_millis(100)) {
+        if !batch.is_empty() {
+            export_batch(state, batch);
+        }
+        queue.complete_batch();
     }
+}
 
-    let state = match OTEL_STATE.get() {
-        Some(Some(state)) => state,
-        _ => return,
+/// Record a batch into the instruments and push it out. Only a successful flush removes spilled
+/// events from disk; a failed one sends the whole batch back to the spill.
+#[cfg(feature = "otel")]
+fn export_batch(state: &OtelState, batch: Vec<QueuedEvent>) {
+    for item in &batch {
+        record_metric_event(&state.metrics, &item.event);
+    }
+    match state._provider.force_flush() {
+        Ok(()) => export_queue::ack(&batch),
+        Err(e) => {
+            crate::utils::debug_log(&format!("[OTel] Metric export failed, spilling: {:?}", e));
+            export_queue::spill(batch);
+        }
+    }
+}
+
+/// Export a metric event to OpenTelemetry.
+/// The event is queued for the background exporter, so this never waits on the network.
+#[cfg(feature = "otel")]
+pub fn export_metric_event(event: &MetricEvent) {
+    let Some(worker) = export_worker() else {
+        return;
     };
+    if let Err(rejected) = worker.queue.push(QueuedEvent::new(event.clone())) {
+        export_queue::spill(vec![rejected]);
+    }
+}
 
+/// Record a metric event into the OTel instruments
+#[cfg(feature = "otel")]
+fn record_metric_event(metrics: &OtelMetrics, event: &MetricEvent) {
     // Extract common attributes from the event
     let attrs = extract_attributes(&event.attrs);
 
     // Route to appropriate handler based on event type
     match MetricEventId::try_from(event.event_id) {
         Ok(MetricEventId::Committed) => {
-            export_committed_event(&state.metrics, &event.values, &attrs);
+            export_committed_event(metrics, &event.values, &attrs);
         }
         Ok(MetricEventId::AgentUsage) => {
-            export_agent_usage_event(&state.metrics, &attrs);
+            export_agent_usage_event(metrics, &event.values, &attrs);
         }
         Ok(MetricEventId::Checkpoint) => {
-            export_checkpoint_event(&state.metrics, &event.values, &attrs);
+            export_checkpoint_event(metrics, &event.values, &attrs);
+        }
+        Ok(MetricEventId::TeamAggregate) => {
+            export_team_aggregate_event(metrics, &event.values, &attrs);
+        }
+        Ok(MetricEventId::PrMerged) => {
+            export_pr_merged_event(metrics, &event.values, &attrs);
         }
         Ok(MetricEventId::InstallHooks) => {
             // InstallHooks events are not exported to OTel
@@ -327,11 +725,13 @@ pub fn export_metric_event(event: &MetricEvent) {
     }
 }
 
-/// Extract common attributes from event attributes sparse array
+/// Extract common attributes from event attributes sparse array, redacted per the telemetry
+/// privacy settings
 #[cfg(feature = "otel")]
 fn extract_attributes(attrs: &crate::metrics::types::SparseArray) -> Vec<KeyValue> {
     use crate::metrics::attrs::attr_pos;
 
+    let attrs = super::privacy::TelemetryPrivacy::current().apply(attrs);
     let mut result = Vec::new();
 
     // Extract string attributes
@@ -364,58 +764,79 @@ fn export_committed_event(
     values: &crate::metrics::types::SparseArray,
     attrs: &[KeyValue],
 ) {
-    // Human additions
-    if let Some(value) = values.get(&committed_pos::HUMAN_ADDITIONS.to_string()) {
-        if let Some(n) = value.as_u64() {
-            metrics.committed_human_additions.add(n, attrs);
-        }
-    }
+    let commit = CommitBreakdown::from_values(values);
 
-    // Git diff added lines
-    if let Some(value) = values.get(&committed_pos::GIT_DIFF_ADDED_LINES.to_string()) {
-        if let Some(n) = value.as_u64() {
-            metrics.committed_diff_added.add(n, attrs);
-        }
+    metrics
+        .committed_human_additions
+        .add(commit.human_additions, attrs);
+    metrics.committed_diff_added.add(commit.diff_added, attrs);
+    metrics
+        .committed_diff_deleted
+        .add(commit.diff_deleted, attrs);
+    if commit.ai_additions > 0 {
+        metrics
+            .committed_ai_additions
+            .add(commit.ai_additions, attrs);
     }
-
-    // Git diff deleted lines
-    if let Some(value) = values.get(&committed_pos::GIT_DIFF_DELETED_LINES.to_string()) {
-        if let Some(n) = value.as_u64() {
-            metrics.committed_diff_deleted.add(n, attrs);
-        }
+    if commit.ai_accepted > 0 {
+        metrics.committed_ai_accepted.add(commit.ai_accepted, attrs);
+    }
+    if commit.ai_reverted > 0 {
+        metrics.committed_ai_reverted.add(commit.ai_reverted, attrs);
     }
 
-    // AI additions (array - sum all values for aggregate)
-    if let Some(value) = values.get(&committed_pos::AI_ADDITIONS.to_string()) {
-        if let Some(arr) = value.as_array() {
-            let total: u64 = arr
-                .iter()
-                .filter_map(|v| v.as_u64())
-                .sum();
-            if total > 0 {
-                metrics.committed_ai_additions.add(total, attrs);
-            }
-        }
+    if let Some(ratio) = commit.ai_ratio() {
+        metrics.commit_ai_ratio.record(ratio, attrs);
+        metrics.last_commit_ai_ratio.record(ratio, attrs);
     }
+    metrics.commit_size.record(commit.size(), attrs);
 
-    // AI accepted (array - sum all values for aggregate)
-    if let Some(value) = values.get(&committed_pos::AI_ACCEPTED.to_string()) {
-        if let Some(arr) = value.as_array() {
-            let total: u64 = arr
-                .iter()
-                .filter_map(|v| v.as_u64())
-                .sum();
-            if total > 0 {
-                metrics.committed_ai_accepted.add(total, attrs);
-            }
-        }
+    // Per-tool series carry the tool/model of the breakdown entry, not the event's own
+    let base_attrs: Vec<KeyValue> = attrs
+        .iter()
+        .filter(|kv| kv.key.as_str() != "tool" && kv.key.as_str() != "model")
+        .cloned()
+        .collect();
+    for tool in &commit.per_tool {
+        let mut tool_attrs = base_attrs.clone();
+        tool_attrs.push(KeyValue::new("tool", tool.tool.clone()));
+        tool_attrs.push(KeyValue::new("model", tool.model.clone()));
+        metrics
+            .tool_ai_additions
+            .add(tool.ai_additions, &tool_attrs);
+        metrics.tool_ai_accepted.add(tool.ai_accepted, &tool_attrs);
+        metrics
+            .tool_mixed_additions
+            .add(tool.mixed_additions, &tool_attrs);
     }
 }
 
 /// Export agent usage event metrics
 #[cfg(feature = "otel")]
-fn export_agent_usage_event(metrics: &OtelMetrics, attrs: &[KeyValue]) {
+fn export_agent_usage_event(
+    metrics: &OtelMetrics,
+    values: &crate::metrics::types::SparseArray,
+    attrs: &[KeyValue],
+) {
     metrics.agent_usage_count.add(1, attrs);
+
+    // Token counts and cost are only present when the agent exposes them
+    for (pos, token_type) in [
+        (agent_usage_pos::INPUT_TOKENS, "input"),
+        (agent_usage_pos::OUTPUT_TOKENS, "output"),
+    ] {
+        if let Some(n) = values.get(&pos.to_string()).and_then(|v| v.as_u64()) {
+            let mut token_attrs = attrs.to_vec();
+            token_attrs.push(KeyValue::new("token_type", token_type));
+            metrics.agent_usage_tokens.add(n, &token_attrs);
+        }
+    }
+    if let Some(cost) = values
+        .get(&agent_usage_pos::COST_USD.to_string())
+        .and_then(|v| v.as_f64())
+    {
+        metrics.agent_usage_cost_usd.add(cost, attrs);
+    }
 }
 
 /// Export checkpoint event metrics
@@ -442,12 +863,190 @@ fn export_checkpoint_event(
     }
 }
 
-/// Shutdown OpenTelemetry gracefully
+#[cfg(feature = "otel")]
+fn export_team_aggregate_event(
+    metrics: &OtelMetrics,
+    values: &crate::metrics::types::SparseArray,
+    attrs: &[KeyValue],
+) {
+    let totals = super::aggregation::TeamTotals::from_values(values);
+    metrics.team_ai_additions.add(totals.ai_additions, attrs);
+    metrics.team_human_additions.add(totals.human_additions, attrs);
+    for (bucket, commits) in totals.ratio_buckets {
+        if commits > 0 {
+            let mut bucket_attrs = attrs.to_vec();
+            bucket_attrs.push(KeyValue::new("ai_ratio_bucket", bucket));
+            metrics.team_commits.add(commits, &bucket_attrs);
+        }
+    }
+}
+
+#[cfg(feature = "otel")]
+fn export_pr_merged_event(
+    metrics: &OtelMetrics,
+    values: &crate::metrics::types::SparseArray,
+    attrs: &[KeyValue],
+) {
+    let pr = PrBreakdown::from_values(values);
+    let mut attrs = attrs.to_vec();
+    attrs.push(KeyValue::new("ai_ratio_bucket", pr.ratio_bucket()));
+
+    metrics.pr_merged_count.add(1, &attrs);
+    metrics.pr_size.record(pr.size(), &attrs);
+    if let Some(duration) = pr.review_duration {
+        metrics.pr_review_duration.record(duration, &attrs);
+    }
+    if pr.reverted_commits > 0 {
+        metrics.pr_reverted_commits.add(pr.reverted_commits, &attrs);
+    }
+}
+
+/// Global tracer provider, initialized on the first exported span
+#[cfg(feature = "otel")]
+static TRACER_PROVIDER: OnceLock<Option<SdkTracerProvider>> = OnceLock::new();
+
+#[cfg(feature = "otel")]
+fn init_tracer_internal(
+    config: &OtelConfig,
+) -> Result<SdkTracerProvider, Box<dyn std::error::Error>> {
+    use opentelemetry_otlp::SpanExporter;
+
+    let exporter = match config.protocol {
+        OtelProtocol::Http => {
+            let mut builder = SpanExporter::builder()
+                .with_http()
+                .with_endpoint(&config.endpoint)
+                .with_timeout(Duration::from_secs(10));
+            if let Some(auth) = &config.auth_header {
+                let mut headers = HashMap::new();
+                headers.insert("Authorization".to_string(), auth.clone());
+                builder = builder.with_headers(headers);
+            }
+            builder.build()?
+        }
+        OtelProtocol::Grpc => {
+            let mut builder = SpanExporter::builder()
+                .with_tonic()
+                .with_endpoint(&config.endpoint)
+                .with_timeout(Duration::from_secs(10));
+            if let Some(auth) = &config.auth_header {
+                let mut map = tonic::metadata::MetadataMap::new();
+                if let Ok(val) = auth.parse() {
+                    map.insert("authorization", val);
+                }
+                builder = builder.with_metadata(map);
+            }
+            builder.build()?
+        }
+    };
+
+    let resource = Resource::builder()
+        .with_attributes(vec![
+            KeyValue::new("service.name", SERVICE_NAME),
+            KeyValue::new("service.version", env!("CARGO_PKG_VERSION")),
+        ])
+        .build();
+
+    Ok(SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(resource)
+        .build())
+}
+
+#[cfg(feature = "otel")]
+fn tracer_provider() -> Option<&'static SdkTracerProvider> {
+    TRACER_PROVIDER
+        .get_or_init(|| {
+            let config = OtelConfig::from_env();
+            if !config.traces_enabled {
+                return None;
+            }
+            match init_tracer_internal(&config) {
+                Ok(provider) => Some(provider),
+                Err(e) => {
+                    logging::error("otel", &format!("Failed to initialize trace exporter: {}", e));
+                    None
+                }
+            }
+        })
+        .as_ref()
+}
+
+/// Export a finished hook span as an OTLP trace span.
+/// Spans are batched and flushed by `shutdown_traces`.
+#[cfg(feature = "otel")]
+pub fn export_span(span: &FinishedSpan) {
+    use opentelemetry::Context;
+    use opentelemetry::trace::{
+        Span, SpanContext, SpanId, SpanKind, Status, TraceContextExt, TraceFlags, TraceId,
+        TraceState, Tracer, TracerProvider,
+    };
+
+    let Some(provider) = tracer_provider() else {
+        return;
+    };
+    let tracer = provider.tracer(SERVICE_NAME);
+
+    let trace_id = TraceId::from_bytes(span.trace_id.to_be_bytes());
+    let parent_cx = match span.parent_span_id {
+        Some(parent_id) => Context::new().with_remote_span_context(SpanContext::new(
+            trace_id,
+            SpanId::from_bytes(parent_id.to_be_bytes()),
+            TraceFlags::SAMPLED,
+            false,
+            TraceState::default(),
+        )),
+        None => Context::new(),
+    };
+
+    let builder = tracer
+        .span_builder(span.name.clone())
+        .with_kind(SpanKind::Internal)
+        .with_trace_id(trace_id)
+        .with_span_id(SpanId::from_bytes(span.span_id.to_be_bytes()))
+        .with_start_time(span.start_time)
+        .with_attributes(
+            span.attributes
+                .iter()
+                .map(|(k, v)| KeyValue::new(k.clone(), v.clone()))
+                .collect::<Vec<_>>(),
+        );
+    let mut otel_span = tracer.build_with_context(builder, &parent_cx);
+    match &span.error {
+        Some(error) => otel_span.set_status(Status::error(error.clone())),
+        None => otel_span.set_status(Status::Ok),
+    }
+    otel_span.end_with_timestamp(span.end_time);
+}
+
+/// Shutdown OpenTelemetry at process exit, flushing any batched spans.
+/// Gives the metric exporter `shutdown_grace_ms` to finish, then spills whatever it hasn't
+/// delivered for the next git-ai process instead of blocking the exit on the network.
 #[cfg(feature = "otel")]
 pub fn shutdown_otel() {
-    if let Some(Some(state)) = OTEL_STATE.get() {
-        if let Err(e) = state._provider.shutdown() {
-            eprintln!("[OTel] Error during shutdown: {:?}", e);
+    if let Some(Some(worker)) = EXPORT_WORKER.get() {
+        worker.queue.close();
+        let handle = worker.handle.lock().ok().and_then(|mut h| h.take());
+        if let Some(handle) = handle {
+            let deadline = std::time::Instant::now() + worker.shutdown_grace;
+            while !handle.is_finished() && std::time::Instant::now() < deadline {
+                std::thread::sleep(Duration::from_millis(5));
+            }
+            if handle.is_finished() {
+                let _ = handle.join();
+            }
+        }
+        export_queue::spill(worker.queue.take_unexported());
+    }
+    shutdown_traces();
+}
+
+/// Flush batched spans and shut down the trace exporter, if it was started
+#[cfg(feature = "otel")]
+pub fn shutdown_traces() {
+    if let Some(Some(provider)) = TRACER_PROVIDER.get() {
+        if let Err(e) = provider.shutdown() {
+            logging::error("otel", &format!("Error during trace shutdown: {:?}", e));
         }
     }
 }
@@ -460,6 +1059,12 @@ pub fn export_metric_event(_event: &crate::metrics::types::MetricEvent) {
     // No-op when otel feature is disabled
 }
 
+/// Export a hook span (no-op when otel feature is disabled)
+#[cfg(not(feature = "otel"))]
+pub fn export_span(_span: &super::spans::FinishedSpan) {
+    // No-op when otel feature is disabled
+}
+
 /// Initialize OpenTelemetry (no-op when otel feature is disabled)
 #[cfg(not(feature = "otel"))]
 pub fn init_otel(_config: &OtelConfig) -> bool {
@@ -469,6 +1074,12 @@ pub fn init_otel(_config: &OtelConfig) -> bool {
 /// Shutdown OpenTelemetry (no-op when otel feature is disabled)
 #[cfg(not(feature = "otel"))]
 pub fn shutdown_otel() {
+    shutdown_traces();
+}
+
+/// Shutdown the trace exporter (no-op when otel feature is disabled)
+#[cfg(not(feature = "otel"))]
+pub fn shutdown_traces() {
     // No-op when otel feature is disabled
 }
 
@@ -491,6 +1102,7 @@ mod tests {
         assert_eq!(config.endpoint, DEFAULT_OTEL_ENDPOINT);
         assert!(config.auth_header.is_none());
         assert_eq!(config.protocol, OtelProtocol::Grpc);
+        assert!(!config.traces_enabled);
     }
 }
 
@@ -505,5 +1117,91 @@ mod tests_no_feature {
         assert!(!config.enabled);
         assert!(config.auth_header.is_none());
         assert_eq!(config.protocol, OtelProtocol::Grpc);
+        assert!(!config.traces_enabled);
+        assert_eq!(config.queue_capacity, DEFAULT_QUEUE_CAPACITY);
+        assert_eq!(config.shutdown_grace_ms, DEFAULT_SHUTDOWN_GRACE_MS);
+    }
+
+    fn committed_values(
+        pairs: &[&str],
+        ai_additions: &[u64],
+    ) -> crate::metrics::types::SparseArray {
+        use crate::metrics::events::committed_pos;
+        let mut values = crate::metrics::types::SparseArray::new();
+        values.insert(committed_pos::HUMAN_ADDITIONS.to_string(), 10.into());
+        values.insert(committed_pos::GIT_DIFF_ADDED_LINES.to_string(), 40.into());
+        values.insert(committed_pos::GIT_DIFF_DELETED_LINES.to_string(), 5.into());
+        values.insert(
+            committed_pos::TOOL_MODEL_PAIRS.to_string(),
+            serde_json::json!(pairs),
+        );
+        values.insert(
+            committed_pos::AI_ADDITIONS.to_string(),
+            serde_json::json!(ai_additions),
+        );
+        values.insert(
+            committed_pos::AI_ACCEPTED.to_string(),
+            serde_json::json!(ai_additions),
+        );
+        values
+    }
+
+    #[test]
+    fn test_commit_breakdown_uses_aggregate_and_splits_tools() {
+        let values = committed_values(
+            &["all", "cursor::gpt-5", "claude::claude-sonnet-4"],
+            &[30, 20, 10],
+        );
+        let commit = CommitBreakdown::from_values(&values);
+
+        // Index 0 is the aggregate; summing the array would double count
+        assert_eq!(commit.ai_additions, 30);
+        assert_eq!(commit.ai_accepted, 30);
+        assert_eq!(commit.size(), 45);
+        assert_eq!(commit.ai_ratio(), Some(0.75));
+        assert_eq!(
+            commit
+                .per_tool
+                .iter()
+                .map(|t| (t.tool.as_str(), t.model.as_str(), t.ai_additions))
+                .collect::<Vec<_>>(),
+            vec![("cursor", "gpt-5", 20), ("claude", "claude-sonnet-4", 10)]
+        );
+    }
+
+    #[test]
+    fn test_commit_breakdown_without_aggregate_entry() {
+        let values = committed_values(&["cursor:gpt-5"], &[8]);
+        let commit = CommitBreakdown::from_values(&values);
+        assert_eq!(commit.ai_additions, 8);
+        assert_eq!(commit.per_tool.len(), 1);
+        assert_eq!(commit.per_tool[0].tool, "cursor");
+
+        assert_eq!(
+            CommitBreakdown::from_values(&crate::metrics::types::SparseArray::new()).ai_ratio(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_pr_breakdown_buckets_by_ai_ratio() {
+        use crate::metrics::{PosEncoded, PrMergedValues};
+
+        let values = PrMergedValues::new()
+            .ai_additions(30)
+            .human_additions(10)
+            .git_diff_added_lines(40)
+            .git_diff_deleted_lines(2)
+            .review_duration(7200)
+            .reverted_commits(vec!["abc1234".to_string()]);
+        let pr = PrBreakdown::from_values(&PosEncoded::to_sparse(&values));
+        assert_eq!(pr.ratio_bucket(), "51-75");
+        assert_eq!(pr.size(), 42);
+        assert_eq!(pr.review_duration, Some(7200));
+        assert_eq!(pr.reverted_commits, 1);
+
+        let empty = PrBreakdown::from_values(&crate::metrics::types::SparseArray::new());
+        assert_eq!(empty.ratio_bucket(), "0");
+        assert_eq!(empty.review_duration, None);
     }
 }
@@ -0,0 +1,349 @@
+//! Redaction of metric event attributes before they leave the machine.
+//!
+//! Every external sink (OpenTelemetry, StatsD, Prometheus, webhook) passes event attributes
+//! through [`TelemetryPrivacy::apply`] first. Each attribute is kept, replaced by a salted
+//! SHA-256 hash (so it still groups and joins, but can't be read), or dropped.
+//!
+//! Configured with:
+//! - `GIT_AI_TELEMETRY_PRIVACY` / `telemetry_privacy`: `default` exports attributes verbatim,
+//!   `strict` hashes identities and drops branch names, `aggregate` replaces events with daily
+//!   per-repository totals (see [`super::aggregation`]), `off` disables every rule below.
+//! - `GIT_AI_TELEMETRY_REDACT` / `telemetry_redact`: per-attribute rules applied on top of the
+//!   preset, e.g. `author=hash,branch=drop,repo_url=keep`.
+//! - `GIT_AI_TELEMETRY_ALLOW` / `telemetry_allow`: when set, only these attributes are exported.
+//! - `GIT_AI_TELEMETRY_HASH_SALT` / `telemetry_hash_salt`: mixed into every hash, so values like
+//!   emails can't be recovered by hashing guesses.
+
+use crate::config::Config;
+use crate::metrics::attrs::attr_pos;
+use crate::metrics::types::{MetricEvent, SparseArray};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+
+/// Attribute names as they appear in exported metrics, by position
+const ATTRIBUTES: [(usize, &str); 10] = [
+    (attr_pos::GIT_AI_VERSION, "git_ai_version"),
+    (attr_pos::REPO_URL, "repo_url"),
+    (attr_pos::AUTHOR, "author"),
+    (attr_pos::COMMIT_SHA, "commit_sha"),
+    (attr_pos::BASE_COMMIT_SHA, "base_commit_sha"),
+    (attr_pos::BRANCH, "branch"),
+    (attr_pos::TOOL, "tool"),
+    (attr_pos::MODEL, "model"),
+    (attr_pos::PROMPT_ID, "prompt_id"),
+    (attr_pos::EXTERNAL_PROMPT_ID, "external_prompt_id"),
+];
+
+/// Hex digits kept from a hashed value: short enough for a tag, long enough not to collide
+const HASH_LEN: usize = 16;
+
+/// What happens to one attribute on export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Redaction {
+    Keep,
+    Hash,
+    Drop,
+}
+
+impl Redaction {
+    fn from_str(input: &str) -> Option<Self> {
+        match input.trim().to_lowercase().as_str() {
+            "keep" => Some(Redaction::Keep),
+            "hash" => Some(Redaction::Hash),
+            "drop" => Some(Redaction::Drop),
+            _ => None,
+        }
+    }
+}
+
+/// Starting point the per-attribute rules are applied on top of
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrivacyPreset {
+    /// Attributes are exported verbatim unless a rule says otherwise
+    Default,
+    /// Identities are hashed and branch names dropped
+    Strict,
+    /// No redaction at all; rules and the allowlist are ignored
+    Off,
+    /// Only daily per-repository aggregates are exported, with identities dropped
+    Aggregate,
+}
+
+impl PrivacyPreset {
+    fn from_str(input: &str) -> Option<Self> {
+        match input.trim().to_lowercase().as_str() {
+            "default" => Some(PrivacyPreset::Default),
+            "strict" => Some(PrivacyPreset::Strict),
+            "off" => Some(PrivacyPreset::Off),
+            "aggregate" => Some(PrivacyPreset::Aggregate),
+            _ => None,
+        }
+    }
+
+    fn rules(self) -> HashMap<&'static str, Redaction> {
+        match self {
+            PrivacyPreset::Default | PrivacyPreset::Off => HashMap::new(),
+            PrivacyPreset::Strict => HashMap::from([
+                ("repo_url", Redaction::Hash),
+                ("author", Redaction::Hash),
+                ("commit_sha", Redaction::Hash),
+                ("base_commit_sha", Redaction::Hash),
+                ("branch", Redaction::Drop),
+                ("prompt_id", Redaction::Hash),
+                ("external_prompt_id", Redaction::Hash),
+            ]),
+            PrivacyPreset::Aggregate => HashMap::from([
+                ("author", Redaction::Drop),
+                ("commit_sha", Redaction::Drop),
+                ("base_commit_sha", Redaction::Drop),
+                ("branch", Redaction::Drop),
+                ("prompt_id", Redaction::Drop),
+                ("external_prompt_id", Redaction::Drop),
+            ]),
+        }
+    }
+}
+
+/// Resolved redaction rules for exported attributes
+#[derive(Debug, Clone)]
+pub struct TelemetryPrivacy {
+    preset: PrivacyPreset,
+    rules: HashMap<&'static str, Redaction>,
+    /// Attributes allowed out; `None` allows all
+    allow: Option<HashSet<&'static str>>,
+    salt: String,
+}
+
+impl TelemetryPrivacy {
+    /// The rules from the global config, resolved once per process
+    pub fn current() -> &'static TelemetryPrivacy {
+        static PRIVACY: OnceLock<TelemetryPrivacy> = OnceLock::new();
+        PRIVACY.get_or_init(|| TelemetryPrivacy::from_config(Config::get()))
+    }
+
+    pub fn from_config(config: &Config) -> Self {
+        Self::from_settings(
+            config.telemetry_privacy(),
+            config.telemetry_redact(),
+            config.telemetry_allow(),
+            config.telemetry_hash_salt(),
+        )
+    }
+
+    /// Build from the raw setting values. Unknown presets, actions and attribute names are
+    /// reported and ignored.
+    pub fn from_settings(
+        preset: Option<&str>,
+        redact: Option<&str>,
+        allow: Option<&str>,
+        salt: Option<&str>,
+    ) -> Self {
+        let preset = match preset {
+            None => PrivacyPreset::Default,
+            Some(value) => PrivacyPreset::from_str(value).unwrap_or_else(|| {
+                eprintln!(
+                    "Warning: Unknown telemetry privacy preset '{}', using 'default'",
+                    value
+                );
+                PrivacyPreset::Default
+            }),
+        };
+
+        let mut rules = preset.rules();
+        for rule in split_list(redact) {
+            let parsed = rule.split_once('=').and_then(|(name, action)| {
+                Some((attribute_name(name)?, Redaction::from_str(action)?))
+            });
+            match parsed {
+                Some((name, action)) => {
+                    rules.insert(name, action);
+                }
+                None => eprintln!(
+                    "Warning: Invalid telemetry redaction rule '{}', expected <attribute>=keep|hash|drop",
+                    rule
+                ),
+            }
+        }
+
+        let allow = allow.map(|_| {
+            split_list(allow)
+                .filter_map(|name| {
+                    let known = attribute_name(name);
+                    if known.is_none() {
+                        eprintln!(
+                            "Warning: Unknown attribute '{}' in telemetry allowlist",
+                            name
+                        );
+                    }
+                    known
+                })
+                .collect()
+        });
+
+        Self {
+            preset,
+            rules,
+            allow,
+            salt: salt.unwrap_or_default().to_string(),
+        }
+    }
+
+    /// Whether sinks receive only team-level aggregates instead of individual events
+    pub fn aggregates(&self) -> bool {
+        self.preset == PrivacyPreset::Aggregate
+    }
+
+    /// What happens to the attribute called `name`
+    pub fn redaction(&self, name: &str) -> Redaction {
+        if self.preset == PrivacyPreset::Off {
+            return Redaction::Keep;
+        }
+        if let Some(allow) = &self.allow
+            && !allow.contains(name)
+        {
+            return Redaction::Drop;
+        }
+        self.rules.get(name).copied().unwrap_or(Redaction::Keep)
+    }
+
+    /// A copy of `attrs` with the rules applied. Null attributes carry nothing and are left
+    /// alone unless dropped.
+    pub fn apply(&self, attrs: &SparseArray) -> SparseArray {
+        let mut redacted = attrs.clone();
+        if self.preset == PrivacyPreset::Off {
+            return redacted;
+        }
+        for (pos, name) in ATTRIBUTES {
+            let key = pos.to_string();
+            match self.redaction(name) {
+                Redaction::Keep => {}
+                Redaction::Drop => {
+                    redacted.remove(&key);
+                }
+                Redaction::Hash => {
+                    if let Some(Value::String(value)) = redacted.get_mut(&key) {
+                        *value = self.hash(value);
+                    }
+                }
+            }
+        }
+        redacted
+    }
+
+    /// `event` with its attributes redacted
+    pub fn apply_to_event(&self, event: &MetricEvent) -> MetricEvent {
+        MetricEvent {
+            attrs: self.apply(&event.attrs),
+            ..event.clone()
+        }
+    }
+
+    fn hash(&self, value: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.salt.as_bytes());
+        hasher.update(value.as_bytes());
+        let digest = format!("{:x}", hasher.finalize());
+        digest[..HASH_LEN].to_string()
+    }
+}
+
+fn split_list(value: Option<&str>) -> impl Iterator<Item = &str> {
+    value
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+}
+
+fn attribute_name(name: &str) -> Option<&'static str> {
+    let name = name.trim();
+    ATTRIBUTES
+        .iter()
+        .map(|(_, known)| *known)
+        .find(|known| *known == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn attrs() -> SparseArray {
+        SparseArray::from([
+            (
+                attr_pos::REPO_URL.to_string(),
+                json!("https://github.com/acme/app"),
+            ),
+            (attr_pos::AUTHOR.to_string(), json!("dev@acme.com")),
+            (
+                attr_pos::BRANCH.to_string(),
+                json!("feature/secret-project"),
+            ),
+            (attr_pos::TOOL.to_string(), json!("cursor")),
+            (attr_pos::PROMPT_ID.to_string(), Value::Null),
+        ])
+    }
+
+    fn get(attrs: &SparseArray, pos: usize) -> Option<&Value> {
+        attrs.get(&pos.to_string())
+    }
+
+    #[test]
+    fn test_default_preset_keeps_everything() {
+        let privacy = TelemetryPrivacy::from_settings(None, None, None, None);
+        assert_eq!(privacy.apply(&attrs()), attrs());
+    }
+
+    #[test]
+    fn test_strict_preset_hashes_identities_and_drops_branch() {
+        let privacy = TelemetryPrivacy::from_settings(Some("strict"), None, None, None);
+        let redacted = privacy.apply(&attrs());
+
+        let author = get(&redacted, attr_pos::AUTHOR)
+            .and_then(Value::as_str)
+            .unwrap();
+        assert_eq!(author.len(), HASH_LEN);
+        assert_ne!(author, "dev@acme.com");
+        assert_eq!(get(&redacted, attr_pos::BRANCH), None);
+        assert_eq!(get(&redacted, attr_pos::TOOL), Some(&json!("cursor")));
+        assert_eq!(get(&redacted, attr_pos::PROMPT_ID), Some(&Value::Null));
+
+        // Hashes are stable, so hashed attributes still group
+        assert_eq!(privacy.apply(&attrs()), redacted);
+        let salted = TelemetryPrivacy::from_settings(Some("strict"), None, None, Some("pepper"));
+        assert_ne!(
+            get(&salted.apply(&attrs()), attr_pos::AUTHOR),
+            Some(&json!(author))
+        );
+    }
+
+    #[test]
+    fn test_rules_override_preset_and_allowlist_drops_the_rest() {
+        let privacy = TelemetryPrivacy::from_settings(
+            Some("strict"),
+            Some("repo_url=keep, tool=hash, bogus=drop"),
+            Some("repo_url,tool,author"),
+            None,
+        );
+        let redacted = privacy.apply(&attrs());
+
+        assert_eq!(
+            get(&redacted, attr_pos::REPO_URL),
+            Some(&json!("https://github.com/acme/app"))
+        );
+        assert_ne!(get(&redacted, attr_pos::TOOL), Some(&json!("cursor")));
+        assert!(get(&redacted, attr_pos::AUTHOR).is_some());
+        assert_eq!(get(&redacted, attr_pos::BRANCH), None);
+        assert_eq!(get(&redacted, attr_pos::PROMPT_ID), None);
+    }
+
+    #[test]
+    fn test_off_ignores_rules_and_allowlist() {
+        let privacy =
+            TelemetryPrivacy::from_settings(Some("off"), Some("author=drop"), Some("tool"), None);
+        assert_eq!(privacy.apply(&attrs()), attrs());
+        assert_eq!(privacy.redaction("author"), Redaction::Keep);
+    }
+}
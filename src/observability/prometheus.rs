@@ -0,0 +1,796 @@
+//! Prometheus metrics export module.
+//!
+//! git-ai runs as short-lived processes, so instead of holding metrics in memory this module
+//! keeps cumulative counters and histograms in a small state file and re-renders them in the
+//! Prometheus text exposition format after every event. The rendered file can be picked up by
+//! node_exporter's textfile collector, or served on `/metrics` by `git-ai prometheus serve`.
+//!
+//! The module is conditionally compiled only when the `prometheus` feature is enabled.
+
+#[cfg(feature = "prometheus")]
+use crate::metrics::events::{agent_usage_pos, checkpoint_pos, committed_pos};
+#[cfg(feature = "prometheus")]
+use crate::metrics::types::{MetricEvent, MetricEventId, SparseArray};
+#[cfg(feature = "prometheus")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "prometheus")]
+use std::collections::BTreeMap;
+#[cfg(feature = "prometheus")]
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+#[cfg(feature = "prometheus")]
+use std::time::{Duration, Instant};
+
+/// Default address for `git-ai prometheus serve`
+pub const DEFAULT_PROMETHEUS_LISTEN: &str = "127.0.0.1:9464";
+
+/// Name of the rendered exposition file inside the default metrics directory
+pub const DEFAULT_TEXTFILE_NAME: &str = "git_ai.prom";
+
+/// Upper bounds of the line-count histogram buckets (+Inf is implicit)
+#[cfg(feature = "prometheus")]
+const LINE_BUCKETS: [u64; 9] = [1, 5, 10, 25, 50, 100, 250, 500, 1000];
+
+/// Give up on the state lock after this long and skip the event
+#[cfg(feature = "prometheus")]
+const LOCK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A lock file older than this was left behind by a crashed process
+#[cfg(feature = "prometheus")]
+const STALE_LOCK_AGE: Duration = Duration::from_secs(30);
+
+/// Prometheus configuration
+#[derive(Debug, Clone)]
+pub struct PrometheusConfig {
+    /// Whether Prometheus export is enabled
+    pub enabled: bool,
+    /// Exposition file rewritten after each event. Point node_exporter's
+    /// `--collector.textfile.directory` at its parent directory.
+    pub textfile_path: PathBuf,
+    /// Address `git-ai prometheus serve` listens on
+    pub listen_addr: String,
+}
+
+impl Default for PrometheusConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            textfile_path: default_textfile_path(),
+            listen_addr: DEFAULT_PROMETHEUS_LISTEN.to_string(),
+        }
+    }
+}
+
+impl PrometheusConfig {
+    /// Create PrometheusConfig from environment variables. Whether it's enabled can also come
+    /// from `prometheus_enabled` in the config file (the env var takes precedence).
+    pub fn from_env() -> Self {
+        let enabled = crate::config::Config::get().is_prometheus_enabled();
+
+        let textfile_path = std::env::var("GIT_AI_PROMETHEUS_TEXTFILE")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(default_textfile_path);
+
+        let listen_addr = std::env::var("GIT_AI_PROMETHEUS_LISTEN")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| DEFAULT_PROMETHEUS_LISTEN.to_string());
+
+        Self {
+            enabled,
+            textfile_path,
+            listen_addr,
+        }
+    }
+
+    /// Cumulative values backing the exposition file, stored next to it
+    pub fn state_path(&self) -> PathBuf {
+        let mut path = self.textfile_path.clone().into_os_string();
+        path.push(".json");
+        PathBuf::from(path)
+    }
+}
+
+/// ~/.git-ai/internal/prometheus/git_ai.prom
+fn default_textfile_path() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_default()
+        .join(".git-ai")
+        .join("internal")
+        .join("prometheus")
+        .join(DEFAULT_TEXTFILE_NAME)
+}
+
+/// Counter names, in exposition order, with their help text
+#[cfg(feature = "prometheus")]
+const COUNTERS: [(&str, &str); 12] = [
+    (
+        "git_ai_committed_human_additions_total",
+        "Number of human-written lines committed",
+    ),
+    (
+        "git_ai_committed_ai_additions_total",
+        "Number of AI-generated lines committed",
+    ),
+    (
+        "git_ai_committed_diff_added_total",
+        "Total lines added in git diff",
+    ),
+    (
+        "git_ai_committed_diff_deleted_total",
+        "Total lines deleted in git diff",
+    ),
+    (
+        "git_ai_committed_ai_accepted_total",
+        "Number of AI-generated lines accepted into commit",
+    ),
+    (
+        "git_ai_committed_ai_reverted_total",
+        "Number of AI-generated lines removed by reverts",
+    ),
+    (
+        "git_ai_agent_usage_count_total",
+        "Number of AI agent usage events",
+    ),
+    (
+        "git_ai_agent_usage_tokens_total",
+        "Tokens spent by AI agents, by token_type (input or output)",
+    ),
+    (
+        "git_ai_checkpoint_count_total",
+        "Number of checkpoint events",
+    ),
+    (
+        "git_ai_team_commits_total",
+        "Commits in daily team aggregates, by ai_ratio_bucket",
+    ),
+    (
+        "git_ai_team_ai_additions_total",
+        "AI-generated lines in daily team aggregates",
+    ),
+    (
+        "git_ai_team_human_additions_total",
+        "Human-written lines in daily team aggregates",
+    ),
+];
+
+/// Counters with fractional values, rendered after the integer ones
+#[cfg(feature = "prometheus")]
+const FLOAT_COUNTERS: [(&str, &str); 1] = [(
+    "git_ai_agent_usage_cost_usd_total",
+    "Estimated cost of AI agent interactions in US dollars",
+)];
+
+/// Histogram names, in exposition order, with their help text
+#[cfg(feature = "prometheus")]
+const HISTOGRAMS: [(&str, &str); 2] = [
+    (
+        "git_ai_checkpoint_lines_added",
+        "Lines added per checkpoint",
+    ),
+    (
+        "git_ai_checkpoint_lines_deleted",
+        "Lines deleted per checkpoint",
+    ),
+];
+
+#[cfg(feature = "prometheus")]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HistogramState {
+    /// Non-cumulative count per bucket in LINE_BUCKETS, plus one for +Inf
+    pub buckets: Vec<u64>,
+    pub sum: u64,
+    pub count: u64,
+}
+
+#[cfg(feature = "prometheus")]
+impl HistogramState {
+    fn observe(&mut self, value: u64) {
+        if self.buckets.len() != LINE_BUCKETS.len() + 1 {
+            self.buckets = vec![0; LINE_BUCKETS.len() + 1];
+        }
+        let index = LINE_BUCKETS
+            .iter()
+            .position(|bound| value <= *bound)
+            .unwrap_or(LINE_BUCKETS.len());
+        self.buckets[index] += 1;
+        self.sum += value;
+        self.count += 1;
+    }
+}
+
+/// Cumulative metric values, keyed by metric name and then by rendered label set
+#[cfg(feature = "prometheus")]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PrometheusState {
+    pub counters: BTreeMap<String, BTreeMap<String, u64>>,
+    #[serde(default)]
+    pub float_counters: BTreeMap<String, BTreeMap<String, f64>>,
+    pub histograms: BTreeMap<String, BTreeMap<String, HistogramState>>,
+}
+
+#[cfg(feature = "prometheus")]
+impl PrometheusState {
+    fn add(&mut self, name: &str, labels: &str, value: u64) {
+        *self
+            .counters
+            .entry(name.to_string())
+            .or_default()
+            .entry(labels.to_string())
+            .or_default() += value;
+    }
+
+    fn add_float(&mut self, name: &str, labels: &str, value: f64) {
+        *self
+            .float_counters
+            .entry(name.to_string())
+            .or_default()
+            .entry(labels.to_string())
+            .or_default() += value;
+    }
+
+    fn observe(&mut self, name: &str, labels: &str, value: u64) {
+        self.histograms
+            .entry(name.to_string())
+            .or_default()
+            .entry(labels.to_string())
+            .or_default()
+            .observe(value);
+    }
+
+    /// Fold one metric event into the cumulative values, mirroring the OTel exporter
+    pub fn apply_event(&mut self, event: &MetricEvent) {
+        let labels = render_labels(&event.attrs);
+        match MetricEventId::try_from(event.event_id) {
+            Ok(MetricEventId::Committed) => {
+                let values = &event.values;
+                let counters = [
+                    (
+                        "git_ai_committed_human_additions_total",
+                        sparse_u64(values, committed_pos::HUMAN_ADDITIONS),
+                    ),
+                    (
+                        "git_ai_committed_diff_added_total",
+                        sparse_u64(values, committed_pos::GIT_DIFF_ADDED_LINES),
+                    ),
+                    (
+                        "git_ai_committed_diff_deleted_total",
+                        sparse_u64(values, committed_pos::GIT_DIFF_DELETED_LINES),
+                    ),
+                    (
+                        "git_ai_committed_ai_additions_total",
+                        sparse_sum(values, committed_pos::AI_ADDITIONS),
+                    ),
+                    (
+                        "git_ai_committed_ai_accepted_total",
+                        sparse_sum(values, committed_pos::AI_ACCEPTED),
+                    ),
+                    (
+                        "git_ai_committed_ai_reverted_total",
+                        sparse_u64(values, committed_pos::AI_REVERTED),
+                    ),
+                ];
+                for (name, value) in counters {
+                    if let Some(value) = value {
+                        self.add(name, &labels, value);
+                    }
+                }
+            }
+            Ok(MetricEventId::AgentUsage) => {
+                self.add("git_ai_agent_usage_count_total", &labels, 1);
+                for (pos, token_type) in [
+                    (agent_usage_pos::INPUT_TOKENS, "input"),
+                    (agent_usage_pos::OUTPUT_TOKENS, "output"),
+                ] {
+                    if let Some(n) = sparse_u64(&event.values, pos) {
+                        let token_type = format!("token_type=\"{}\"", token_type);
+                        let token_labels = if labels.is_empty() {
+                            token_type
+                        } else {
+                            format!("{},{}", labels, token_type)
+                        };
+                        self.add("git_ai_agent_usage_tokens_total", &token_labels, n);
+                    }
+                }
+                if let Some(cost) = event
+                    .values
+                    .get(&agent_usage_pos::COST_USD.to_string())
+                    .and_then(|v| v.as_f64())
+                {
+                    self.add_float("git_ai_agent_usage_cost_usd_total", &labels, cost);
+                }
+            }
+            Ok(MetricEventId::Checkpoint) => {
+                self.add("git_ai_checkpoint_count_total", &labels, 1);
+                if let Some(n) = sparse_u64(&event.values, checkpoint_pos::LINES_ADDED) {
+                    self.observe("git_ai_checkpoint_lines_added", &labels, n);
+                }
+                if let Some(n) = sparse_u64(&event.values, checkpoint_pos::LINES_DELETED) {
+                    self.observe("git_ai_checkpoint_lines_deleted", &labels, n);
+                }
+            }
+            Ok(MetricEventId::TeamAggregate) => {
+                let totals = super::aggregation::TeamTotals::from_values(&event.values);
+                self.add(
+                    "git_ai_team_ai_additions_total",
+                    &labels,
+                    totals.ai_additions,
+                );
+                self.add(
+                    "git_ai_team_human_additions_total",
+                    &labels,
+                    totals.human_additions,
+                );
+                for (bucket, commits) in totals.ratio_buckets {
+                    if commits > 0 {
+                        let bucket = format!("ai_ratio_bucket=\"{}\"", bucket);
+                        let bucket_labels = if labels.is_empty() {
+                            bucket
+                        } else {
+                            format!("{},{}", labels, bucket)
+                        };
+                        self.add("git_ai_team_commits_total", &bucket_labels, commits);
+                    }
+                }
+            }
+            Ok(MetricEventId::PrMerged) => {
+                // Pull request metrics are only exported to OTel
+            }
+            Ok(MetricEventId::InstallHooks) => {
+                // InstallHooks events are not exported to Prometheus
+            }
+            Err(_) => {
+                // Unknown event type, skip
+            }
+        }
+    }
+
+    /// Render in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for (name, help) in COUNTERS {
+            let Some(series) = self.counters.get(name) else {
+                continue;
+            };
+            out.push_str(&format!(
+                "# HELP {} {}\n# TYPE {} counter\n",
+                name, help, name
+            ));
+            for (labels, value) in series {
+                out.push_str(&format!("{}{} {}\n", name, braced(labels), value));
+            }
+        }
+
+        for (name, help) in FLOAT_COUNTERS {
+            let Some(series) = self.float_counters.get(name) else {
+                continue;
+            };
+            out.push_str(&format!(
+                "# HELP {} {}\n# TYPE {} counter\n",
+                name, help, name
+            ));
+            for (labels, value) in series {
+                out.push_str(&format!("{}{} {}\n", name, braced(labels), value));
+            }
+        }
+
+        for (name, help) in HISTOGRAMS {
+            let Some(series) = self.histograms.get(name) else {
+                continue;
+            };
+            out.push_str(&format!(
+                "# HELP {} {}\n# TYPE {} histogram\n",
+                name, help, name
+            ));
+            for (labels, histogram) in series {
+                let mut cumulative = 0;
+                let bounds = LINE_BUCKETS
+                    .iter()
+                    .map(|b| b.to_string())
+                    .chain(std::iter::once("+Inf".to_string()));
+                for (bound, count) in bounds.zip(histogram.buckets.iter()) {
+                    cumulative += count;
+                    let le = format!("le=\"{}\"", bound);
+                    let bucket_labels = if labels.is_empty() {
+                        le
+                    } else {
+                        format!("{},{}", labels, le)
+                    };
+                    out.push_str(&format!(
+                        "{}_bucket{{{}}} {}\n",
+                        name, bucket_labels, cumulative
+                    ));
+                }
+                out.push_str(&format!(
+                    "{}_sum{} {}\n",
+                    name,
+                    braced(labels),
+                    histogram.sum
+                ));
+                out.push_str(&format!(
+                    "{}_count{} {}\n",
+                    name,
+                    braced(labels),
+                    histogram.count
+                ));
+            }
+        }
+        out
+    }
+}
+
+#[cfg(feature = "prometheus")]
+fn braced(labels: &str) -> String {
+    if labels.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", labels)
+    }
+}
+
+/// Render the event attributes used as labels. Per-commit and per-prompt identifiers are left
+/// out: every value would start a new time series. Values are redacted per the telemetry privacy
+/// settings.
+#[cfg(feature = "prometheus")]
+fn render_labels(attrs: &SparseArray) -> String {
+    use crate::metrics::attrs::attr_pos;
+
+    let attrs = super::privacy::TelemetryPrivacy::current().apply(attrs);
+
+    let label_attrs = [
+        (attr_pos::REPO_URL, "repo_url"),
+        (attr_pos::AUTHOR, "author"),
+        (attr_pos::TOOL, "tool"),
+        (attr_pos::MODEL, "model"),
+    ];
+
+    label_attrs
+        .iter()
+        .filter_map(|(pos, name)| {
+            let value = attrs.get(&pos.to_string())?.as_str()?;
+            Some(format!("{}=\"{}\"", name, escape_label_value(value)))
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(feature = "prometheus")]
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(feature = "prometheus")]
+fn sparse_u64(values: &SparseArray, pos: usize) -> Option<u64> {
+    values.get(&pos.to_string())?.as_u64()
+}
+
+/// Sum of a per-tool array value
+#[cfg(feature = "prometheus")]
+fn sparse_sum(values: &SparseArray, pos: usize) -> Option<u64> {
+    let total: u64 = values
+        .get(&pos.to_string())?
+        .as_array()?
+        .iter()
+        .filter_map(|v| v.as_u64())
+        .sum();
+    (total > 0).then_some(total)
+}
+
+/// Export a metric event to the Prometheus state and exposition file.
+/// Failures are ignored so the existing metrics pipeline is never affected.
+#[cfg(feature = "prometheus")]
+pub fn export_metric_event(event: &MetricEvent) {
+    let config = PrometheusConfig::from_env();
+    if !config.enabled {
+        return;
+    }
+    if let Err(e) = record_events(&config, std::slice::from_ref(event)) {
+        crate::utils::debug_log(&format!("[Prometheus] Failed to record metric: {}", e));
+    }
+}
+
+/// Apply `events` to the stored state and rewrite the exposition file
+#[cfg(feature = "prometheus")]
+pub fn record_events(config: &PrometheusConfig, events: &[MetricEvent]) -> std::io::Result<()> {
+    if let Some(parent) = config.textfile_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let state_path = config.state_path();
+    let _lock = StateLock::acquire(&state_path)?;
+
+    let mut state = load_state(&state_path);
+    for event in events {
+        state.apply_event(event);
+    }
+
+    let json = serde_json::to_string(&state).map_err(std::io::Error::other)?;
+    write_atomically(&state_path, json.as_bytes())?;
+    // The textfile collector may read at any moment, so never expose a partial file
+    write_atomically(&config.textfile_path, state.render().as_bytes())
+}
+
+#[cfg(feature = "prometheus")]
+fn load_state(path: &std::path::Path) -> PrometheusState {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(feature = "prometheus")]
+fn write_atomically(path: &std::path::Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(format!(".{}.tmp", std::process::id()));
+    let tmp = PathBuf::from(tmp);
+    std::fs::write(&tmp, contents)?;
+    std::fs::rename(&tmp, path)
+}
+
+/// Serializes read-modify-write of the state file across concurrent git-ai processes
+#[cfg(feature = "prometheus")]
+struct StateLock {
+    path: PathBuf,
+}
+
+#[cfg(feature = "prometheus")]
+impl StateLock {
+    fn acquire(state_path: &std::path::Path) -> std::io::Result<Self> {
+        let mut path = state_path.as_os_str().to_owned();
+        path.push(".lock");
+        let path = PathBuf::from(path);
+
+        let started = Instant::now();
+        loop {
+            match std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+            {
+                Ok(_) => return Ok(Self { path }),
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    let stale = std::fs::metadata(&path)
+                        .and_then(|m| m.modified())
+                        .ok()
+                        .and_then(|modified| modified.elapsed().ok())
+                        .is_some_and(|age| age > STALE_LOCK_AGE);
+                    if stale {
+                        let _ = std::fs::remove_file(&path);
+                        continue;
+                    }
+                    if started.elapsed() > LOCK_TIMEOUT {
+                        return Err(e);
+                    }
+                    std::thread::sleep(Duration::from_millis(20));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "prometheus")]
+impl Drop for StateLock {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Serve the exposition file on `GET /metrics` until the process is killed
+#[cfg(feature = "prometheus")]
+pub fn serve(config: &PrometheusConfig) -> std::io::Result<()> {
+    let listener = std::net::TcpListener::bind(&config.listen_addr)?;
+    eprintln!(
+        "Serving git-ai metrics on http://{}/metrics",
+        listener.local_addr()?
+    );
+    for stream in listener.incoming() {
+        let Ok(mut stream) = stream else {
+            continue;
+        };
+        let mut request_line = String::new();
+        if BufReader::new(&stream)
+            .read_line(&mut request_line)
+            .is_err()
+        {
+            continue;
+        }
+
+        let path = request_line.split_whitespace().nth(1).unwrap_or("");
+        let response = if request_line.starts_with("GET ") && path == "/metrics" {
+            let body = std::fs::read_to_string(&config.textfile_path).unwrap_or_default();
+            format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+        } else {
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+        };
+        let _ = stream.write_all(response.as_bytes());
+    }
+    Ok(())
+}
+
+// Non-prometheus feature stubs - these are no-ops when the prometheus feature is disabled
+
+/// Export a metric event to Prometheus (no-op when prometheus feature is disabled)
+#[cfg(not(feature = "prometheus"))]
+pub fn export_metric_event(_event: &crate::metrics::types::MetricEvent) {
+    // No-op when prometheus feature is disabled
+}
+
+/// Serve metrics (unavailable when prometheus feature is disabled)
+#[cfg(not(feature = "prometheus"))]
+pub fn serve(_config: &PrometheusConfig) -> std::io::Result<()> {
+    Err(std::io::Error::other(
+        "git-ai was built without the prometheus feature",
+    ))
+}
+
+#[cfg(all(test, feature = "prometheus"))]
+mod tests {
+    use super::*;
+    use crate::metrics::PosEncoded;
+    use crate::metrics::attrs::EventAttributes;
+    use crate::metrics::events::{CheckpointValues, CommittedValues};
+
+    fn attrs() -> SparseArray {
+        EventAttributes::with_version("1.0.0")
+            .repo_url("https://github.com/org/repo")
+            .tool("claude")
+            .model("opus")
+            .commit_sha("abc123")
+            .to_sparse()
+    }
+
+    #[test]
+    fn test_committed_event_accumulates_counters() {
+        let values = CommittedValues::new()
+            .human_additions(3)
+            .git_diff_added_lines(10)
+            .git_diff_deleted_lines(2)
+            .ai_additions(vec![4, 3])
+            .ai_accepted(vec![5]);
+        let event = MetricEvent::new(&values, attrs());
+
+        let mut state = PrometheusState::default();
+        state.apply_event(&event);
+        state.apply_event(&event);
+
+        let text = state.render();
+        let labels = r#"{repo_url="https://github.com/org/repo",tool="claude",model="opus"}"#;
+        assert!(text.contains("# TYPE git_ai_committed_ai_additions_total counter"));
+        assert!(text.contains(&format!("git_ai_committed_ai_additions_total{} 14", labels)));
+        assert!(text.contains(&format!(
+            "git_ai_committed_human_additions_total{} 6",
+            labels
+        )));
+        // High-cardinality attributes never become labels
+        assert!(!text.contains("abc123"));
+    }
+
+    #[test]
+    fn test_checkpoint_histogram_buckets_are_cumulative() {
+        let mut state = PrometheusState::default();
+        for lines in [3, 40, 5000] {
+            let values = CheckpointValues::new().lines_added(lines).lines_deleted(0);
+            state.apply_event(&MetricEvent::new(&values, SparseArray::new()));
+        }
+
+        let text = state.render();
+        assert!(text.contains("git_ai_checkpoint_count_total 3"));
+        assert!(text.contains("git_ai_checkpoint_lines_added_bucket{le=\"1\"} 0"));
+        assert!(text.contains("git_ai_checkpoint_lines_added_bucket{le=\"5\"} 1"));
+        assert!(text.contains("git_ai_checkpoint_lines_added_bucket{le=\"50\"} 2"));
+        assert!(text.contains("git_ai_checkpoint_lines_added_bucket{le=\"+Inf\"} 3"));
+        assert!(text.contains("git_ai_checkpoint_lines_added_sum 5043"));
+        assert!(text.contains("git_ai_checkpoint_lines_added_count 3"));
+    }
+
+    #[test]
+    fn test_agent_usage_tokens_and_cost() {
+        let values = crate::metrics::events::AgentUsageValues::new()
+            .input_tokens(1000)
+            .output_tokens(200)
+            .cost_usd(0.25);
+        let event = MetricEvent::new(&values, attrs());
+
+        let mut state = PrometheusState::default();
+        state.apply_event(&event);
+        state.apply_event(&event);
+
+        let text = state.render();
+        let labels = r#"repo_url="https://github.com/org/repo",tool="claude",model="opus""#;
+        assert!(text.contains(&format!(
+            "git_ai_agent_usage_tokens_total{{{},token_type=\"input\"}} 2000",
+            labels
+        )));
+        assert!(text.contains(&format!(
+            "git_ai_agent_usage_tokens_total{{{},token_type=\"output\"}} 400",
+            labels
+        )));
+        assert!(text.contains(&format!(
+            "git_ai_agent_usage_cost_usd_total{{{}}} 0.5",
+            labels
+        )));
+    }
+
+    #[test]
+    fn test_team_aggregate_counts_commits_per_ratio_bucket() {
+        let values = crate::metrics::events::TeamAggregateValues::new()
+            .day("2026-03-02".to_string())
+            .commits(3)
+            .ai_additions(40)
+            .human_additions(12)
+            .ratio_buckets(vec![1, 0, 2, 0, 0]);
+        let attrs = EventAttributes::with_version("1.0.0")
+            .repo_url("https://github.com/org/repo")
+            .to_sparse();
+
+        let mut state = PrometheusState::default();
+        state.apply_event(&MetricEvent::new(&values, attrs));
+
+        let text = state.render();
+        let labels = r#"repo_url="https://github.com/org/repo""#;
+        assert!(text.contains(&format!("git_ai_team_ai_additions_total{{{}}} 40", labels)));
+        assert!(text.contains(&format!(
+            "git_ai_team_commits_total{{{},ai_ratio_bucket=\"26-50\"}} 2",
+            labels
+        )));
+        assert!(!text.contains("ai_ratio_bucket=\"1-25\""));
+    }
+
+    #[test]
+    fn test_label_values_are_escaped() {
+        assert_eq!(escape_label_value("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+
+    #[test]
+    fn test_record_events_persists_across_calls() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = PrometheusConfig {
+            enabled: true,
+            textfile_path: dir.path().join("git_ai.prom"),
+            listen_addr: DEFAULT_PROMETHEUS_LISTEN.to_string(),
+        };
+        let event = MetricEvent::new(
+            &crate::metrics::events::AgentUsageValues::new(),
+            SparseArray::new(),
+        );
+
+        record_events(&config, std::slice::from_ref(&event)).unwrap();
+        record_events(&config, std::slice::from_ref(&event)).unwrap();
+
+        let text = std::fs::read_to_string(&config.textfile_path).unwrap();
+        assert!(
+            text.contains("git_ai_agent_usage_count_total 2"),
+            "{}",
+            text
+        );
+        assert!(!config.state_path().with_extension("json.lock").exists());
+    }
+}
+
+#[cfg(test)]
+mod tests_no_feature {
+    use super::*;
+
+    #[test]
+    fn test_prometheus_config_default() {
+        let config = PrometheusConfig::default();
+        assert!(!config.enabled);
+        assert_eq!(config.listen_addr, DEFAULT_PROMETHEUS_LISTEN);
+        assert!(config.textfile_path.ends_with(DEFAULT_TEXTFILE_NAME));
+        assert!(
+            config
+                .state_path()
+                .to_string_lossy()
+                .ends_with("git_ai.prom.json")
+        );
+    }
+}
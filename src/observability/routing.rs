@@ -0,0 +1,160 @@
+//! Per-sink event routing (`metrics_routes`).
+//!
+//! Without routes, every sink exports every event type it knows how to export. Routes narrow
+//! that per sink: a sink with at least one route exports an event only when the first route
+//! covering the event's type keeps it. A route covers the types it lists, or all types when it
+//! lists none, and keeps `sample_rate` of them (all by default).
+//!
+//! Sampling is a hash of the sink name and the event, not a random draw, so an envelope that is
+//! retried or re-flushed makes the same decision, and different sinks sample independently.
+//!
+//! The local metrics store is not a sink here; it always keeps every event.
+
+use crate::config::{Config, MetricsRouteConfig};
+use crate::metrics::{MetricEvent, MetricEventId};
+use sha2::{Digest, Sha256};
+
+/// Whether `sink` should export `event` under the configured routes
+pub fn allows(sink: &str, event: &MetricEvent) -> bool {
+    allows_with(Config::get().metrics_routes(), sink, event)
+}
+
+/// The events `sink` should export, in order
+pub fn filter(sink: &str, events: &[MetricEvent]) -> Vec<MetricEvent> {
+    let routes = Config::get().metrics_routes();
+    events
+        .iter()
+        .filter(|event| allows_with(routes, sink, event))
+        .cloned()
+        .collect()
+}
+
+fn allows_with(routes: &[MetricsRouteConfig], sink: &str, event: &MetricEvent) -> bool {
+    let mut sink_routes = routes.iter().filter(|r| r.sink == sink).peekable();
+    if sink_routes.peek().is_none() {
+        return true;
+    }
+
+    let name = MetricEventId::try_from(event.event_id).map(MetricEventId::name);
+    let Some(route) = sink_routes
+        .find(|r| r.events.is_empty() || name.is_ok_and(|n| r.events.iter().any(|e| e == n)))
+    else {
+        return false;
+    };
+
+    match route.sample_rate {
+        None => true,
+        Some(rate) if rate >= 1.0 => true,
+        Some(rate) if rate <= 0.0 => false,
+        Some(rate) => sample_point(sink, event) < rate,
+    }
+}
+
+/// Maps the sink and event to a stable point in [0, 1)
+fn sample_point(sink: &str, event: &MetricEvent) -> f64 {
+    let mut hasher = Sha256::new();
+    hasher.update(sink.as_bytes());
+    hasher.update([0]);
+    // Canonical JSON, since the event's maps serialize in a per-process order
+    hasher.update(serde_json_canonicalizer::to_string(event).unwrap_or_default());
+    let digest = hasher.finalize();
+    let mut prefix = [0u8; 8];
+    prefix.copy_from_slice(&digest[..8]);
+    // The top 53 bits fill an f64 mantissa exactly
+    (u64::from_be_bytes(prefix) >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn event(id: MetricEventId, timestamp: u32) -> MetricEvent {
+        MetricEvent {
+            timestamp,
+            event_id: id as u16,
+            values: HashMap::new(),
+            attrs: HashMap::new(),
+        }
+    }
+
+    fn route(sink: &str, events: &[&str], sample_rate: Option<f64>) -> MetricsRouteConfig {
+        MetricsRouteConfig {
+            sink: sink.to_string(),
+            events: events.iter().map(|e| e.to_string()).collect(),
+            sample_rate,
+        }
+    }
+
+    #[test]
+    fn test_sinks_without_routes_export_everything() {
+        let routes = vec![route("otel", &["committed"], None)];
+        let checkpoint = event(MetricEventId::Checkpoint, 1);
+        assert!(allows_with(&[], "otel", &checkpoint));
+        assert!(allows_with(&routes, "statsd", &checkpoint));
+        assert!(allows_with(&routes, "api", &checkpoint));
+    }
+
+    #[test]
+    fn test_routes_select_event_types() {
+        let routes = vec![route("otel", &["committed", "agent_usage"], None)];
+        assert!(allows_with(
+            &routes,
+            "otel",
+            &event(MetricEventId::Committed, 1)
+        ));
+        assert!(allows_with(
+            &routes,
+            "otel",
+            &event(MetricEventId::AgentUsage, 1)
+        ));
+        assert!(!allows_with(
+            &routes,
+            "otel",
+            &event(MetricEventId::Checkpoint, 1)
+        ));
+        assert!(!allows_with(
+            &routes,
+            "otel",
+            &event(MetricEventId::InstallHooks, 1)
+        ));
+    }
+
+    #[test]
+    fn test_first_matching_route_decides() {
+        let routes = vec![
+            route("webhook", &["checkpoint"], Some(0.0)),
+            route("webhook", &[], None),
+        ];
+        assert!(!allows_with(
+            &routes,
+            "webhook",
+            &event(MetricEventId::Checkpoint, 1)
+        ));
+        assert!(allows_with(
+            &routes,
+            "webhook",
+            &event(MetricEventId::Committed, 1)
+        ));
+    }
+
+    #[test]
+    fn test_sampling_is_deterministic_and_roughly_proportional() {
+        let routes = vec![route("statsd", &["checkpoint"], Some(0.1))];
+        let events: Vec<MetricEvent> = (0..2000)
+            .map(|t| event(MetricEventId::Checkpoint, t))
+            .collect();
+        let kept: Vec<bool> = events
+            .iter()
+            .map(|e| allows_with(&routes, "statsd", e))
+            .collect();
+        let again: Vec<bool> = events
+            .iter()
+            .map(|e| allows_with(&routes, "statsd", e))
+            .collect();
+        assert_eq!(kept, again);
+
+        let count = kept.iter().filter(|k| **k).count();
+        assert!((120..=280).contains(&count), "kept {} of 2000", count);
+    }
+}
@@ -0,0 +1,199 @@
+//! Lightweight spans around git-ai hook work, exported as OTLP traces.
+//!
+//! Spans are recorded only when trace export is enabled (`GIT_AI_OTEL_TRACES_ENABLED` with the
+//! `otel` feature); otherwise `HookSpan` is an inert guard. Each span is handed to the exporter
+//! when it ends, with explicit trace/span IDs so children started on this thread (or on threads
+//! that adopt its context) nest under it in Tempo/Jaeger.
+
+use std::cell::RefCell;
+use std::sync::OnceLock;
+use std::time::SystemTime;
+
+use super::otel::{self, OtelConfig};
+
+thread_local! {
+    /// Spans currently open on this thread, innermost last
+    static ACTIVE_SPANS: RefCell<Vec<SpanContext>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Trace and span ID of an open span, for parenting work on other threads
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpanContext {
+    pub trace_id: u128,
+    pub span_id: u64,
+}
+
+/// A span that has ended and is ready for export
+#[derive(Debug, Clone)]
+pub struct FinishedSpan {
+    pub name: String,
+    pub trace_id: u128,
+    pub span_id: u64,
+    pub parent_span_id: Option<u64>,
+    pub start_time: SystemTime,
+    pub end_time: SystemTime,
+    pub attributes: Vec<(String, String)>,
+    pub error: Option<String>,
+}
+
+struct SpanData {
+    name: String,
+    context: SpanContext,
+    parent_span_id: Option<u64>,
+    start_time: SystemTime,
+    attributes: Vec<(String, String)>,
+    error: Option<String>,
+}
+
+/// Guard for one unit of hook work; the span ends when the guard is dropped
+pub struct HookSpan {
+    data: Option<SpanData>,
+}
+
+pub fn tracing_enabled() -> bool {
+    static ENABLED: OnceLock<bool> = OnceLock::new();
+    *ENABLED.get_or_init(|| cfg!(feature = "otel") && OtelConfig::from_env().traces_enabled)
+}
+
+impl HookSpan {
+    /// Start a span as a child of the innermost open span on this thread
+    pub fn start(name: impl Into<String>) -> Self {
+        if !tracing_enabled() {
+            return Self { data: None };
+        }
+        let parent = current_context();
+        Self::start_inner(name.into(), parent)
+    }
+
+    fn start_inner(name: String, parent: Option<SpanContext>) -> Self {
+        let context = SpanContext {
+            trace_id: parent
+                .map(|p| p.trace_id)
+                .unwrap_or_else(|| uuid::Uuid::new_v4().as_u128()),
+            span_id: uuid::Uuid::new_v4().as_u128() as u64,
+        };
+        ACTIVE_SPANS.with(|spans| spans.borrow_mut().push(context));
+        Self {
+            data: Some(SpanData {
+                name,
+                context,
+                parent_span_id: parent.map(|p| p.span_id),
+                start_time: SystemTime::now(),
+                attributes: Vec::new(),
+                error: None,
+            }),
+        }
+    }
+
+    pub fn set_attribute(&mut self, key: &str, value: impl ToString) {
+        if let Some(data) = &mut self.data {
+            data.attributes.push((key.to_string(), value.to_string()));
+        }
+    }
+
+    /// Mark the span as failed; the last error recorded wins
+    pub fn set_error(&mut self, error: impl ToString) {
+        if let Some(data) = &mut self.data {
+            data.error = Some(error.to_string());
+        }
+    }
+
+    /// Record the error of a result, passing it through unchanged
+    pub fn record_result<T, E: std::fmt::Display>(&mut self, result: Result<T, E>) -> Result<T, E> {
+        if let Err(e) = &result {
+            self.set_error(e);
+        }
+        result
+    }
+}
+
+impl Drop for HookSpan {
+    fn drop(&mut self) {
+        let Some(data) = self.data.take() else {
+            return;
+        };
+        ACTIVE_SPANS.with(|spans| {
+            let mut spans = spans.borrow_mut();
+            if let Some(pos) = spans.iter().rposition(|c| *c == data.context) {
+                spans.remove(pos);
+            }
+        });
+        otel::export_span(&FinishedSpan {
+            name: data.name,
+            trace_id: data.context.trace_id,
+            span_id: data.context.span_id,
+            parent_span_id: data.parent_span_id,
+            start_time: data.start_time,
+            end_time: SystemTime::now(),
+            attributes: data.attributes,
+            error: data.error,
+        });
+    }
+}
+
+/// The innermost open span on this thread, to hand to a spawned thread
+pub fn current_context() -> Option<SpanContext> {
+    ACTIVE_SPANS.with(|spans| spans.borrow().last().copied())
+}
+
+/// Run `f` with `parent` as the enclosing span, so spans started inside nest under it
+pub fn with_parent<T>(parent: Option<SpanContext>, f: impl FnOnce() -> T) -> T {
+    let Some(parent) = parent else {
+        return f();
+    };
+    ACTIVE_SPANS.with(|spans| spans.borrow_mut().push(parent));
+    let result = f();
+    ACTIVE_SPANS.with(|spans| {
+        let mut spans = spans.borrow_mut();
+        if let Some(pos) = spans.iter().rposition(|c| *c == parent) {
+            spans.remove(pos);
+        }
+    });
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spans_nest_on_thread_stack() {
+        let outer = HookSpan::start_inner("outer".to_string(), None);
+        let outer_ctx = outer.data.as_ref().unwrap().context;
+        assert_eq!(current_context(), Some(outer_ctx));
+
+        let inner = HookSpan::start_inner("inner".to_string(), current_context());
+        let inner_data = inner.data.as_ref().unwrap();
+        assert_eq!(inner_data.context.trace_id, outer_ctx.trace_id);
+        assert_eq!(inner_data.parent_span_id, Some(outer_ctx.span_id));
+
+        drop(inner);
+        assert_eq!(current_context(), Some(outer_ctx));
+        drop(outer);
+        assert_eq!(current_context(), None);
+    }
+
+    #[test]
+    fn test_with_parent_adopts_context_on_other_thread() {
+        let parent = SpanContext {
+            trace_id: 7,
+            span_id: 9,
+        };
+        let seen = std::thread::spawn(move || with_parent(Some(parent), current_context))
+            .join()
+            .unwrap();
+        assert_eq!(seen, Some(parent));
+    }
+
+    #[test]
+    fn test_disabled_span_is_inert() {
+        if tracing_enabled() {
+            return;
+        }
+        let mut span = HookSpan::start("noop");
+        span.set_attribute("key", "value");
+        span.set_error("boom");
+        assert!(span.data.is_none());
+        assert_eq!(current_context(), None);
+    }
+}
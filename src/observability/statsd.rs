@@ -0,0 +1,505 @@
+//! StatsD / DogStatsD metrics export module.
+//!
+//! Sends the same metrics as the OpenTelemetry exporter, under the same names, as UDP
+//! datagrams to a local StatsD or Datadog agent. Nothing is buffered: sending to a UDP socket
+//! never waits on the agent, so each event goes out as it is recorded.
+//!
+//! Enabled with `GIT_AI_METRICS_SINK=statsd` (or `metrics_sink` in the config file).
+
+use super::aggregation::TeamTotals;
+use super::otel::CommitBreakdown;
+use crate::metrics::events::{agent_usage_pos, checkpoint_pos};
+use crate::metrics::types::{MetricEvent, MetricEventId, SparseArray};
+use std::net::{ToSocketAddrs, UdpSocket};
+
+/// Default agent address (the DogStatsD and StatsD default port)
+pub const DEFAULT_STATSD_ADDR: &str = "127.0.0.1:8125";
+
+/// Keep datagrams under a typical MTU so metrics packed into one aren't fragmented
+const MAX_DATAGRAM_BYTES: usize = 1432;
+
+/// Wire format spoken by the agent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatsdFlavor {
+    /// Datadog's extension: `|#key:value` tags and `h` histograms
+    DogStatsd,
+    /// Plain StatsD: no tags, histograms sent as `ms` timers
+    Plain,
+}
+
+/// StatsD configuration
+#[derive(Debug, Clone)]
+pub struct StatsdConfig {
+    /// Whether StatsD export is enabled
+    pub enabled: bool,
+    /// host:port of the agent
+    pub addr: String,
+    pub flavor: StatsdFlavor,
+    /// Tags added to every metric, e.g. "team:platform" (DogStatsD only)
+    pub constant_tags: Vec<String>,
+}
+
+impl Default for StatsdConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            addr: DEFAULT_STATSD_ADDR.to_string(),
+            flavor: StatsdFlavor::DogStatsd,
+            constant_tags: Vec::new(),
+        }
+    }
+}
+
+impl StatsdConfig {
+    /// Create StatsdConfig from environment variables. The agent address falls back to the
+    /// Datadog agent's own `DD_AGENT_HOST`/`DD_DOGSTATSD_PORT` variables.
+    pub fn from_env() -> Self {
+        let enabled = crate::config::Config::get().is_metrics_sink_enabled("statsd");
+
+        let non_empty = |name: &str| std::env::var(name).ok().filter(|s| !s.is_empty());
+        let addr = non_empty("GIT_AI_STATSD_ADDR")
+            .or_else(|| {
+                non_empty("DD_AGENT_HOST").map(|host| {
+                    let port = non_empty("DD_DOGSTATSD_PORT").unwrap_or_else(|| "8125".into());
+                    format!("{}:{}", host, port)
+                })
+            })
+            .unwrap_or_else(|| DEFAULT_STATSD_ADDR.to_string());
+
+        let flavor = match non_empty("GIT_AI_STATSD_FLAVOR").as_deref() {
+            Some("statsd") => StatsdFlavor::Plain,
+            _ => StatsdFlavor::DogStatsd,
+        };
+
+        let constant_tags = non_empty("GIT_AI_STATSD_TAGS")
+            .map(|tags| {
+                tags.split(',')
+                    .map(|t| t.trim().to_string())
+                    .filter(|t| !t.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            enabled,
+            addr,
+            flavor,
+            constant_tags,
+        }
+    }
+}
+
+/// Export a metric event to StatsD.
+/// Failures are ignored so the existing metrics pipeline is never affected.
+pub fn export_metric_event(event: &MetricEvent) {
+    let config = StatsdConfig::from_env();
+    if !config.enabled {
+        return;
+    }
+    if let Err(e) = send_events(&config, std::slice::from_ref(event)) {
+        crate::utils::debug_log(&format!("[StatsD] Failed to send metric: {}", e));
+    }
+}
+
+/// Send `events` to the agent, packing as many metric lines per datagram as fit
+pub fn send_events(config: &StatsdConfig, events: &[MetricEvent]) -> std::io::Result<()> {
+    let lines: Vec<String> = events
+        .iter()
+        .flat_map(|event| render_event(config, event))
+        .collect();
+    if lines.is_empty() {
+        return Ok(());
+    }
+
+    let target = config
+        .addr
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| std::io::Error::other(format!("cannot resolve {}", config.addr)))?;
+    let socket = UdpSocket::bind(if target.is_ipv6() {
+        "[::]:0"
+    } else {
+        "0.0.0.0:0"
+    })?;
+    socket.connect(target)?;
+
+    for datagram in pack_datagrams(&lines) {
+        socket.send(datagram.as_bytes())?;
+    }
+    Ok(())
+}
+
+fn pack_datagrams(lines: &[String]) -> Vec<String> {
+    let mut datagrams = Vec::new();
+    let mut current = String::new();
+    for line in lines {
+        if !current.is_empty() && current.len() + 1 + line.len() > MAX_DATAGRAM_BYTES {
+            datagrams.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push('\n');
+        }
+        current.push_str(line);
+    }
+    if !current.is_empty() {
+        datagrams.push(current);
+    }
+    datagrams
+}
+
+/// The metric lines for one event, in the agent's wire format
+pub fn render_event(config: &StatsdConfig, event: &MetricEvent) -> Vec<String> {
+    let tags = event_tags(&event.attrs);
+    let mut out = Vec::new();
+    let mut emit = |name: &str, value: String, kind: &str, tags: &[String]| {
+        let kind = match (kind, config.flavor) {
+            ("h", StatsdFlavor::Plain) => "ms",
+            (kind, _) => kind,
+        };
+        let mut line = format!("git_ai.{}:{}|{}", name, value, kind);
+        if config.flavor == StatsdFlavor::DogStatsd {
+            let all_tags: Vec<&str> = config
+                .constant_tags
+                .iter()
+                .chain(tags)
+                .map(String::as_str)
+                .collect();
+            if !all_tags.is_empty() {
+                line.push_str("|#");
+                line.push_str(&all_tags.join(","));
+            }
+        }
+        out.push(line);
+    };
+
+    match MetricEventId::try_from(event.event_id) {
+        Ok(MetricEventId::Committed) => {
+            let commit = CommitBreakdown::from_values(&event.values);
+            let count = |n: u64| n.to_string();
+            emit(
+                "committed.human_additions",
+                count(commit.human_additions),
+                "c",
+                &tags,
+            );
+            emit("committed.diff_added", count(commit.diff_added), "c", &tags);
+            emit(
+                "committed.diff_deleted",
+                count(commit.diff_deleted),
+                "c",
+                &tags,
+            );
+            if commit.ai_additions > 0 {
+                emit(
+                    "committed.ai_additions",
+                    count(commit.ai_additions),
+                    "c",
+                    &tags,
+                );
+            }
+            if commit.ai_accepted > 0 {
+                emit(
+                    "committed.ai_accepted",
+                    count(commit.ai_accepted),
+                    "c",
+                    &tags,
+                );
+            }
+            if commit.ai_reverted > 0 {
+                emit(
+                    "committed.ai_reverted",
+                    count(commit.ai_reverted),
+                    "c",
+                    &tags,
+                );
+            }
+            if let Some(ratio) = commit.ai_ratio() {
+                emit("commit.ai_ratio", ratio.to_string(), "h", &tags);
+                emit("commit.last_ai_ratio", ratio.to_string(), "g", &tags);
+            }
+            emit("commit.size", count(commit.size()), "h", &tags);
+
+            // Per-tool series carry the tool/model of the breakdown entry, not the event's own
+            let base_tags: Vec<String> = tags
+                .iter()
+                .filter(|t| !t.starts_with("tool:") && !t.starts_with("model:"))
+                .cloned()
+                .collect();
+            for tool in &commit.per_tool {
+                let mut tool_tags = base_tags.clone();
+                tool_tags.push(tag("tool", &tool.tool));
+                tool_tags.push(tag("model", &tool.model));
+                emit(
+                    "committed.tool.ai_additions",
+                    count(tool.ai_additions),
+                    "c",
+                    &tool_tags,
+                );
+                emit(
+                    "committed.tool.ai_accepted",
+                    count(tool.ai_accepted),
+                    "c",
+                    &tool_tags,
+                );
+                emit(
+                    "committed.tool.mixed_additions",
+                    count(tool.mixed_additions),
+                    "c",
+                    &tool_tags,
+                );
+            }
+        }
+        Ok(MetricEventId::AgentUsage) => {
+            emit("agent_usage.count", "1".to_string(), "c", &tags);
+            for (pos, token_type) in [
+                (agent_usage_pos::INPUT_TOKENS, "input"),
+                (agent_usage_pos::OUTPUT_TOKENS, "output"),
+            ] {
+                if let Some(n) = event.values.get(&pos.to_string()).and_then(|v| v.as_u64()) {
+                    let mut token_tags = tags.clone();
+                    token_tags.push(tag("token_type", token_type));
+                    emit("agent_usage.tokens", n.to_string(), "c", &token_tags);
+                }
+            }
+            if let Some(cost) = event
+                .values
+                .get(&agent_usage_pos::COST_USD.to_string())
+                .and_then(|v| v.as_f64())
+            {
+                emit("agent_usage.cost_usd", cost.to_string(), "c", &tags);
+            }
+        }
+        Ok(MetricEventId::Checkpoint) => {
+            emit("checkpoint.count", "1".to_string(), "c", &tags);
+            let value = |pos: usize| event.values.get(&pos.to_string())?.as_u64();
+            if let Some(n) = value(checkpoint_pos::LINES_ADDED) {
+                emit("checkpoint.lines_added", n.to_string(), "h", &tags);
+            }
+            if let Some(n) = value(checkpoint_pos::LINES_DELETED) {
+                emit("checkpoint.lines_deleted", n.to_string(), "h", &tags);
+            }
+        }
+        Ok(MetricEventId::TeamAggregate) => {
+            let totals = TeamTotals::from_values(&event.values);
+            emit(
+                "team.ai_additions",
+                totals.ai_additions.to_string(),
+                "c",
+                &tags,
+            );
+            emit(
+                "team.human_additions",
+                totals.human_additions.to_string(),
+                "c",
+                &tags,
+            );
+            for (bucket, commits) in totals.ratio_buckets {
+                if commits > 0 {
+                    let mut bucket_tags = tags.clone();
+                    bucket_tags.push(tag("ai_ratio_bucket", bucket));
+                    emit("team.commits", commits.to_string(), "c", &bucket_tags);
+                }
+            }
+        }
+        Ok(MetricEventId::PrMerged) => {
+            // Pull request metrics are only exported to OTel
+        }
+        Ok(MetricEventId::InstallHooks) => {
+            // InstallHooks events are not exported to StatsD
+        }
+        Err(_) => {
+            // Unknown event type, skip
+        }
+    }
+    out
+}
+
+/// Tags for the event attributes. As with Prometheus labels, per-commit and per-prompt
+/// identifiers are left out: Datadog bills every distinct tag combination as a custom metric.
+/// Values are redacted per the telemetry privacy settings.
+fn event_tags(attrs: &SparseArray) -> Vec<String> {
+    use crate::metrics::attrs::attr_pos;
+
+    let attrs = super::privacy::TelemetryPrivacy::current().apply(attrs);
+
+    let tag_attrs = [
+        (attr_pos::REPO_URL, "repo_url"),
+        (attr_pos::AUTHOR, "author"),
+        (attr_pos::TOOL, "tool"),
+        (attr_pos::MODEL, "model"),
+    ];
+
+    tag_attrs
+        .iter()
+        .filter_map(|(pos, name)| {
+            let value = attrs.get(&pos.to_string())?.as_str()?;
+            Some(tag(name, value))
+        })
+        .collect()
+}
+
+/// `name:value`, with the characters that delimit the DogStatsD wire format replaced
+fn tag(name: &str, value: &str) -> String {
+    let value: String = value
+        .chars()
+        .map(|c| match c {
+            ',' | '|' | '#' | '\n' | '\r' => '_',
+            c => c,
+        })
+        .collect();
+    format!("{}:{}", name, value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::PosEncoded;
+    use crate::metrics::attrs::EventAttributes;
+    use crate::metrics::events::{
+        AgentUsageValues, CheckpointValues, CommittedValues, TeamAggregateValues,
+    };
+
+    fn attrs() -> SparseArray {
+        EventAttributes::with_version("1.0.0")
+            .repo_url("https://github.com/org/repo")
+            .tool("claude")
+            .model("opus")
+            .commit_sha("abc123")
+            .to_sparse()
+    }
+
+    fn dogstatsd() -> StatsdConfig {
+        StatsdConfig {
+            enabled: true,
+            constant_tags: vec!["team:platform".to_string()],
+            ..StatsdConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_committed_event_renders_tagged_metrics() {
+        let values = CommittedValues::new()
+            .human_additions(10)
+            .git_diff_added_lines(40)
+            .git_diff_deleted_lines(5)
+            .tool_model_pairs(vec!["all".to_string(), "cursor::gpt-5".to_string()])
+            .ai_additions(vec![30, 30])
+            .ai_accepted(vec![20, 20])
+            .mixed_additions(vec![10, 10]);
+        let lines = render_event(&dogstatsd(), &MetricEvent::new(&values, attrs()));
+
+        let tags = "team:platform,repo_url:https://github.com/org/repo,tool:claude,model:opus";
+        assert!(lines.contains(&format!("git_ai.committed.ai_additions:30|c|#{}", tags)));
+        assert!(lines.contains(&format!("git_ai.commit.ai_ratio:0.75|h|#{}", tags)));
+        assert!(lines.contains(&format!("git_ai.commit.size:45|h|#{}", tags)));
+        assert!(lines.contains(
+            &"git_ai.committed.tool.ai_additions:30|c|#team:platform,repo_url:https://github.com/org/repo,tool:cursor,model:gpt-5".to_string()
+        ));
+        // High-cardinality attributes never become tags
+        assert!(lines.iter().all(|line| !line.contains("abc123")));
+    }
+
+    #[test]
+    fn test_plain_statsd_drops_tags_and_uses_timers() {
+        let config = StatsdConfig {
+            flavor: StatsdFlavor::Plain,
+            ..dogstatsd()
+        };
+        let values = CheckpointValues::new().lines_added(3).lines_deleted(1);
+        let lines = render_event(&config, &MetricEvent::new(&values, attrs()));
+        assert_eq!(
+            lines,
+            vec![
+                "git_ai.checkpoint.count:1|c",
+                "git_ai.checkpoint.lines_added:3|ms",
+                "git_ai.checkpoint.lines_deleted:1|ms",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_team_aggregate_renders_commits_per_ratio_bucket() {
+        let values = TeamAggregateValues::new()
+            .day("2026-03-02".to_string())
+            .commits(3)
+            .ai_additions(40)
+            .human_additions(12)
+            .ratio_buckets(vec![1, 0, 0, 0, 2]);
+        let attrs = EventAttributes::with_version("1.0.0")
+            .repo_url("https://github.com/org/repo")
+            .to_sparse();
+        let lines = render_event(&dogstatsd(), &MetricEvent::new(&values, attrs));
+
+        let tags = "team:platform,repo_url:https://github.com/org/repo";
+        assert_eq!(
+            lines,
+            vec![
+                format!("git_ai.team.ai_additions:40|c|#{}", tags),
+                format!("git_ai.team.human_additions:12|c|#{}", tags),
+                format!("git_ai.team.commits:1|c|#{},ai_ratio_bucket:0", tags),
+                format!("git_ai.team.commits:2|c|#{},ai_ratio_bucket:76-100", tags),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_agent_usage_emits_tokens_and_cost_when_reported() {
+        let config = StatsdConfig {
+            flavor: StatsdFlavor::Plain,
+            ..dogstatsd()
+        };
+        let values = AgentUsageValues::new()
+            .input_tokens(1200)
+            .output_tokens(80)
+            .cost_usd(0.05);
+        let lines = render_event(&config, &MetricEvent::new(&values, attrs()));
+        assert_eq!(
+            lines,
+            vec![
+                "git_ai.agent_usage.count:1|c",
+                "git_ai.agent_usage.tokens:1200|c",
+                "git_ai.agent_usage.tokens:80|c",
+                "git_ai.agent_usage.cost_usd:0.05|c",
+            ]
+        );
+
+        let lines = render_event(&dogstatsd(), &MetricEvent::new(&values, attrs()));
+        assert!(lines[1].ends_with(",token_type:input"));
+        assert!(lines[2].ends_with(",token_type:output"));
+    }
+
+    #[test]
+    fn test_tag_values_cannot_break_the_wire_format() {
+        assert_eq!(tag("author", "a|b,c#d\ne"), "author:a_b_c_d_e");
+    }
+
+    #[test]
+    fn test_send_events_packs_lines_into_datagrams() {
+        let agent = UdpSocket::bind("127.0.0.1:0").unwrap();
+        agent
+            .set_read_timeout(Some(std::time::Duration::from_secs(5)))
+            .unwrap();
+        let config = StatsdConfig {
+            addr: agent.local_addr().unwrap().to_string(),
+            ..dogstatsd()
+        };
+        let event = MetricEvent::new(&AgentUsageValues::new(), SparseArray::new());
+
+        send_events(&config, &[event.clone(), event]).unwrap();
+
+        let mut buf = [0u8; MAX_DATAGRAM_BYTES];
+        let len = agent.recv(&mut buf).unwrap();
+        assert_eq!(
+            std::str::from_utf8(&buf[..len]).unwrap(),
+            "git_ai.agent_usage.count:1|c|#team:platform\ngit_ai.agent_usage.count:1|c|#team:platform"
+        );
+    }
+
+    #[test]
+    fn test_pack_datagrams_splits_at_the_size_limit() {
+        let line = "x".repeat(700);
+        let datagrams = pack_datagrams(&[line.clone(), line.clone(), line]);
+        assert_eq!(datagrams.len(), 2);
+        assert!(datagrams.iter().all(|d| d.len() <= MAX_DATAGRAM_BYTES));
+    }
+}
@@ -0,0 +1,417 @@
+//! Webhook metrics export module.
+//!
+//! POSTs metric events as JSON batches (the same `{"v": .., "events": [..]}` shape the metrics
+//! API accepts) to an internal endpoint. Delivery runs in the background `flush-logs` process,
+//! so retries with backoff never hold up a git command. Batches that still fail are kept under
+//! `~/.git-ai/internal/webhook-pending/` and retried by the next flush.
+//!
+//! Enabled with `GIT_AI_METRICS_SINK=webhook` plus `GIT_AI_WEBHOOK_URL` (or `metrics_sink` and
+//! `webhook_url` in the config file).
+//!
+//! With a secret configured, each request carries `X-Git-AI-Signature: sha256=<hex>`, an
+//! HMAC-SHA256 of `<X-Git-AI-Timestamp>.<body>`. Receivers should recompute it and reject stale
+//! timestamps. `X-Git-AI-Delivery` stays the same across retries of one batch.
+
+use super::MAX_METRICS_PER_ENVELOPE;
+use super::logging;
+use super::privacy::TelemetryPrivacy;
+use crate::error::GitAiError;
+use crate::metrics::{MetricEvent, MetricsBatch};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Default number of attempts per batch before it is set aside for the next flush
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 5;
+
+/// Delay before the first retry; doubled after each failed attempt
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Upper bound on a single wait, including one asked for with Retry-After
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+const REQUEST_TIMEOUT_SECS: u64 = 10;
+
+/// Undelivered batches kept on disk; beyond this new failures are dropped
+const MAX_PENDING_DELIVERIES: usize = 1000;
+
+/// Webhook configuration
+#[derive(Debug, Clone)]
+pub struct WebhookConfig {
+    /// Whether webhook export is enabled (requires a URL)
+    pub enabled: bool,
+    pub url: String,
+    /// HMAC-SHA256 signing key; requests are unsigned without one
+    pub secret: Option<String>,
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    /// Where batches that exhausted their attempts wait for the next flush
+    pub pending_dir: PathBuf,
+}
+
+impl WebhookConfig {
+    /// Create WebhookConfig from environment variables and the config file
+    pub fn from_env() -> Self {
+        let config = crate::config::Config::get();
+        let url = config.webhook_url().unwrap_or_default().to_string();
+
+        let max_attempts = std::env::var("GIT_AI_WEBHOOK_MAX_ATTEMPTS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|n| *n > 0)
+            .unwrap_or(DEFAULT_MAX_ATTEMPTS);
+
+        Self {
+            enabled: config.is_metrics_sink_enabled("webhook") && !url.is_empty(),
+            url,
+            secret: config.webhook_secret().map(str::to_string),
+            max_attempts,
+            initial_backoff: INITIAL_BACKOFF,
+            pending_dir: default_pending_dir(),
+        }
+    }
+}
+
+/// ~/.git-ai/internal/webhook-pending
+fn default_pending_dir() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_default()
+        .join(".git-ai")
+        .join("internal")
+        .join("webhook-pending")
+}
+
+/// One batch, as sent and as kept on disk between flushes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Delivery {
+    id: String,
+    body: String,
+}
+
+enum SendError {
+    /// Network errors, timeouts, 408, 429 and 5xx
+    Retryable {
+        message: String,
+        retry_after: Option<Duration>,
+    },
+    /// Any other non-2xx status: the endpoint rejected the batch and will do so again
+    Rejected(String),
+}
+
+/// Deliver events to the webhook, if it is enabled, with attributes redacted per the telemetry
+/// privacy settings. Failures are reported but never affect the rest of the metrics pipeline.
+pub fn export_metric_events(events: &[MetricEvent]) {
+    let config = WebhookConfig::from_env();
+    if !config.enabled {
+        return;
+    }
+    let privacy = TelemetryPrivacy::current();
+    let events: Vec<MetricEvent> = events.iter().map(|e| privacy.apply_to_event(e)).collect();
+    if let Err(e) = deliver_events(&config, &events) {
+        logging::error("webhook", &e.to_string());
+    }
+}
+
+/// POST `events` in batches of up to `MAX_METRICS_PER_ENVELOPE`, retrying with backoff.
+/// Batches that run out of attempts are saved for `retry_pending`.
+pub fn deliver_events(config: &WebhookConfig, events: &[MetricEvent]) -> Result<(), GitAiError> {
+    let mut failures = Vec::new();
+    for chunk in events.chunks(MAX_METRICS_PER_ENVELOPE) {
+        let delivery = Delivery {
+            id: uuid::Uuid::new_v4().to_string(),
+            body: serde_json::to_string(&MetricsBatch::new(chunk.to_vec()))?,
+        };
+        match send_with_retry(config, &delivery, config.max_attempts) {
+            Ok(()) => {}
+            Err(SendError::Retryable { message, .. }) => {
+                save_pending(config, &delivery)?;
+                failures.push(format!("{} (kept for the next flush)", message));
+            }
+            Err(SendError::Rejected(message)) => failures.push(message),
+        }
+    }
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(GitAiError::Generic(format!(
+            "Webhook delivery failed: {}",
+            failures.join("; ")
+        )))
+    }
+}
+
+/// Resend batches earlier flushes couldn't deliver, oldest first, stopping at the first one
+/// that fails again. Returns how many were delivered.
+pub fn retry_pending(config: &WebhookConfig) -> usize {
+    let mut delivered = 0;
+    for path in pending_files(config) {
+        let Some(delivery) = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|s| serde_json::from_str::<Delivery>(&s).ok())
+        else {
+            let _ = std::fs::remove_file(&path);
+            continue;
+        };
+        // One attempt each: the endpoint already had a full round of retries
+        match send_with_retry(config, &delivery, 1) {
+            Ok(()) => {
+                let _ = std::fs::remove_file(&path);
+                delivered += 1;
+            }
+            Err(SendError::Rejected(message)) => {
+                logging::warn(
+                    "webhook",
+                    &format!("Dropping pending batch {}: {}", delivery.id, message),
+                );
+                let _ = std::fs::remove_file(&path);
+            }
+            Err(SendError::Retryable { .. }) => break,
+        }
+    }
+    delivered
+}
+
+fn pending_files(config: &WebhookConfig) -> Vec<PathBuf> {
+    let mut files: Vec<(std::time::SystemTime, PathBuf)> = std::fs::read_dir(&config.pending_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .map(|path| {
+            let modified = std::fs::metadata(&path)
+                .and_then(|m| m.modified())
+                .unwrap_or(std::time::UNIX_EPOCH);
+            (modified, path)
+        })
+        .collect();
+    files.sort();
+    files.into_iter().map(|(_, path)| path).collect()
+}
+
+fn save_pending(config: &WebhookConfig, delivery: &Delivery) -> Result<(), GitAiError> {
+    if pending_files(config).len() >= MAX_PENDING_DELIVERIES {
+        return Err(GitAiError::Generic(format!(
+            "{} undelivered webhook batches already pending, dropping {}",
+            MAX_PENDING_DELIVERIES, delivery.id
+        )));
+    }
+    std::fs::create_dir_all(&config.pending_dir)?;
+    let path = config.pending_dir.join(format!("{}.json", delivery.id));
+    std::fs::write(path, serde_json::to_string(delivery)?)?;
+    Ok(())
+}
+
+fn send_with_retry(
+    config: &WebhookConfig,
+    delivery: &Delivery,
+    max_attempts: u32,
+) -> Result<(), SendError> {
+    let mut backoff = config.initial_backoff;
+    let mut attempt = 1;
+    loop {
+        match send_once(config, delivery) {
+            Err(SendError::Retryable {
+                message,
+                retry_after,
+            }) if attempt < max_attempts => {
+                crate::utils::debug_log(&format!(
+                    "[webhook] Attempt {}/{} failed: {}",
+                    attempt, max_attempts, message
+                ));
+                std::thread::sleep(retry_after.unwrap_or(backoff).min(MAX_BACKOFF));
+                backoff *= 2;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+fn send_once(config: &WebhookConfig, delivery: &Delivery) -> Result<(), SendError> {
+    let timestamp = chrono::Utc::now().timestamp().to_string();
+    let mut request = minreq::post(&config.url)
+        .with_header("Content-Type", "application/json")
+        .with_header(
+            "User-Agent",
+            format!("git-ai/{}", env!("CARGO_PKG_VERSION")),
+        )
+        .with_header("X-Git-AI-Delivery", delivery.id.as_str())
+        .with_header("X-Git-AI-Timestamp", timestamp.as_str())
+        .with_timeout(REQUEST_TIMEOUT_SECS);
+    if let Some(secret) = &config.secret {
+        request = request.with_header(
+            "X-Git-AI-Signature",
+            format!("sha256={}", sign(secret, &timestamp, &delivery.body)),
+        );
+    }
+
+    let response = request
+        .with_body(delivery.body.as_str())
+        .send()
+        .map_err(|e| SendError::Retryable {
+            message: e.to_string(),
+            retry_after: None,
+        })?;
+
+    match response.status_code {
+        200..=299 => Ok(()),
+        408 | 429 | 500..=599 => Err(SendError::Retryable {
+            message: format!("{} returned status {}", config.url, response.status_code),
+            retry_after: response
+                .headers
+                .get("retry-after")
+                .and_then(|v| v.trim().parse().ok())
+                .map(Duration::from_secs),
+        }),
+        status => Err(SendError::Rejected(format!(
+            "{} returned status {}",
+            config.url, status
+        ))),
+    }
+}
+
+/// Hex HMAC-SHA256 of `<timestamp>.<body>`, the value of `X-Git-AI-Signature` after `sha256=`
+pub fn sign(secret: &str, timestamp: &str, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::events::AgentUsageValues;
+    use crate::metrics::types::SparseArray;
+    use std::collections::HashMap;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+
+    struct Request {
+        headers: HashMap<String, String>,
+        body: String,
+    }
+
+    /// Answer one request per entry in `statuses`, reporting each request on the channel
+    fn serve(statuses: Vec<u16>) -> (String, mpsc::Receiver<Request>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/events", listener.local_addr().unwrap());
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            for status in statuses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut headers = HashMap::new();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+                    }
+                }
+                let length: usize = headers["content-length"].parse().unwrap();
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+                let _ = tx.send(Request {
+                    headers,
+                    body: String::from_utf8(body).unwrap(),
+                });
+                let mut stream = reader.into_inner();
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {} X\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    status
+                );
+            }
+        });
+        (url, rx)
+    }
+
+    fn config(url: String, pending_dir: PathBuf) -> WebhookConfig {
+        WebhookConfig {
+            enabled: true,
+            url,
+            secret: Some("s3cret".to_string()),
+            max_attempts: 2,
+            initial_backoff: Duration::from_millis(10),
+            pending_dir,
+        }
+    }
+
+    fn events() -> Vec<MetricEvent> {
+        vec![MetricEvent::new(
+            &AgentUsageValues::new(),
+            SparseArray::new(),
+        )]
+    }
+
+    #[test]
+    fn test_deliver_retries_and_signs_each_attempt() {
+        let dir = tempfile::tempdir().unwrap();
+        let (url, requests) = serve(vec![503, 200]);
+        let config = config(url, dir.path().to_path_buf());
+
+        deliver_events(&config, &events()).unwrap();
+
+        let first = requests.recv().unwrap();
+        let second = requests.recv().unwrap();
+        assert_eq!(
+            first.headers["x-git-ai-delivery"],
+            second.headers["x-git-ai-delivery"]
+        );
+        let batch: serde_json::Value = serde_json::from_str(&second.body).unwrap();
+        assert_eq!(batch["events"].as_array().unwrap().len(), 1);
+        assert_eq!(
+            second.headers["x-git-ai-signature"],
+            format!(
+                "sha256={}",
+                sign(
+                    "s3cret",
+                    &second.headers["x-git-ai-timestamp"],
+                    &second.body
+                )
+            )
+        );
+        assert!(pending_files(&config).is_empty());
+    }
+
+    #[test]
+    fn test_undelivered_batches_wait_for_the_next_flush() {
+        let dir = tempfile::tempdir().unwrap();
+        let (url, requests) = serve(vec![500, 500, 200]);
+        let config = config(url, dir.path().to_path_buf());
+
+        assert!(deliver_events(&config, &events()).is_err());
+        assert_eq!(pending_files(&config).len(), 1);
+        let failed = requests.recv().unwrap();
+
+        assert_eq!(retry_pending(&config), 1);
+        requests.recv().unwrap();
+        let retried = requests.recv().unwrap();
+        assert_eq!(retried.body, failed.body);
+        assert!(pending_files(&config).is_empty());
+    }
+
+    #[test]
+    fn test_rejected_batches_are_not_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        let (url, _requests) = serve(vec![400]);
+        let config = config(url, dir.path().to_path_buf());
+
+        assert!(deliver_events(&config, &events()).is_err());
+        assert!(pending_files(&config).is_empty());
+    }
+}
@@ -0,0 +1,639 @@
+//! Local SQLite store for metric events and checkpoints.
+//!
+//! Every metric event git-ai records and every checkpoint it writes is mirrored here, next to the
+//! observability log files and the per-repo working logs that remain the source of truth for
+//! uploads and attribution. Keeping a copy in one queryable place lets stats, export retries and
+//! offline analysis read history without replaying log files.
+
+use crate::authorship::working_log::Checkpoint;
+use crate::error::GitAiError;
+use crate::metrics::MetricEvent;
+use crate::utils::debug_log;
+use rusqlite::{Connection, params};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+/// Current schema version (must match MIGRATIONS.len())
+const SCHEMA_VERSION: usize = 2;
+
+/// Database migrations - each migration upgrades the schema by one version
+const MIGRATIONS: &[&str] = &[
+    // Migration 0 -> 1: metric events and checkpoints
+    r#"
+    CREATE TABLE metric_events (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        event_id INTEGER NOT NULL,
+        timestamp INTEGER NOT NULL,
+        event_json TEXT NOT NULL
+    );
+    CREATE INDEX idx_metric_events_timestamp ON metric_events(timestamp);
+    CREATE INDEX idx_metric_events_event_id ON metric_events(event_id, timestamp);
+
+    CREATE TABLE checkpoints (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        repo_workdir TEXT NOT NULL,
+        base_commit TEXT NOT NULL,
+        kind TEXT NOT NULL,
+        author TEXT NOT NULL,
+        tool TEXT,
+        model TEXT,
+        external_id TEXT,
+        files TEXT NOT NULL,
+        lines_added INTEGER NOT NULL,
+        lines_deleted INTEGER NOT NULL,
+        timestamp INTEGER NOT NULL
+    );
+    CREATE INDEX idx_checkpoints_repo ON checkpoints(repo_workdir, timestamp);
+    CREATE INDEX idx_checkpoints_timestamp ON checkpoints(timestamp);
+    "#,
+    // Migration 1 -> 2: metric events waiting to be exported over OTLP
+    r#"
+    CREATE TABLE otel_spill (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        event_json TEXT NOT NULL,
+        spilled_at INTEGER NOT NULL,
+        claimed_at INTEGER
+    );
+    CREATE INDEX idx_otel_spill_claimed ON otel_spill(claimed_at);
+    "#,
+];
+
+/// Global store singleton
+static LOCAL_STORE: OnceLock<Mutex<LocalStore>> = OnceLock::new();
+
+/// A checkpoint as stored locally: who wrote what, without the diff or transcript
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredCheckpoint {
+    pub repo_workdir: String,
+    pub base_commit: String,
+    pub kind: String,
+    pub author: String,
+    pub tool: Option<String>,
+    pub model: Option<String>,
+    pub external_id: Option<String>,
+    pub files: Vec<String>,
+    pub lines_added: u32,
+    pub lines_deleted: u32,
+    pub timestamp: u64,
+}
+
+impl StoredCheckpoint {
+    pub fn from_checkpoint(checkpoint: &Checkpoint, repo_workdir: &str, base_commit: &str) -> Self {
+        Self {
+            repo_workdir: repo_workdir.to_string(),
+            base_commit: base_commit.to_string(),
+            kind: checkpoint.kind.to_str(),
+            author: checkpoint.author.clone(),
+            tool: checkpoint.agent_id.as_ref().map(|a| a.tool.clone()),
+            model: checkpoint.agent_id.as_ref().map(|a| a.model.clone()),
+            external_id: checkpoint.agent_id.as_ref().map(|a| a.id.clone()),
+            files: checkpoint.entries.iter().map(|e| e.file.clone()).collect(),
+            lines_added: checkpoint.line_stats.additions,
+            lines_deleted: checkpoint.line_stats.deletions,
+            timestamp: checkpoint.timestamp,
+        }
+    }
+}
+
+/// Row counts and on-disk size, for `git-ai db info` and vacuum reporting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StoreStats {
+    pub metric_events: usize,
+    pub checkpoints: usize,
+    /// Metric events spilled to disk that no exporter has delivered yet
+    pub otel_spilled: usize,
+    pub size_bytes: u64,
+}
+
+/// Database wrapper for the local store
+pub struct LocalStore {
+    conn: Connection,
+    db_path: PathBuf,
+}
+
+impl LocalStore {
+    /// Get or initialize the global store
+    pub fn global() -> Result<&'static Mutex<LocalStore>, GitAiError> {
+        let db_mutex = LOCAL_STORE.get_or_init(|| {
+            match Self::database_path().and_then(|path| Self::open(&path)) {
+                Ok(db) => Mutex::new(db),
+                Err(e) => {
+                    eprintln!("[Error] Failed to initialize local store: {}", e);
+                    // Create a dummy connection that will fail on any operation
+                    let temp_path = std::env::temp_dir().join("git-ai-store-db-failed");
+                    let conn = Connection::open(&temp_path).expect("Failed to create temp DB");
+                    Mutex::new(LocalStore {
+                        conn,
+                        db_path: temp_path,
+                    })
+                }
+            }
+        });
+
+        Ok(db_mutex)
+    }
+
+    /// Open (creating and migrating if needed) a store at `db_path`
+    pub fn open(db_path: &Path) -> Result<Self, GitAiError> {
+        if let Some(parent) = db_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let conn = Connection::open(db_path)?;
+        conn.execute_batch(
+            r#"
+            PRAGMA journal_mode=WAL;
+            PRAGMA synchronous=NORMAL;
+            PRAGMA cache_size=-2000;
+            PRAGMA temp_store=MEMORY;
+            "#,
+        )?;
+
+        let mut db = Self {
+            conn,
+            db_path: db_path.to_path_buf(),
+        };
+        db.initialize_schema()?;
+
+        Ok(db)
+    }
+
+    /// Get database path: ~/.git-ai/internal/store-db
+    /// In test mode this sits next to the GIT_AI_TEST_DB_PATH database
+    pub fn database_path() -> Result<PathBuf, GitAiError> {
+        #[cfg(any(test, feature = "test-support"))]
+        if let Ok(test_path) = std::env::var("GIT_AI_TEST_DB_PATH") {
+            return Ok(PathBuf::from(format!("{}-store", test_path)));
+        }
+
+        let home = dirs::home_dir()
+            .ok_or_else(|| GitAiError::Generic("Could not determine home directory".to_string()))?;
+        Ok(home.join(".git-ai").join("internal").join("store-db"))
+    }
+
+    /// Initialize schema and handle migrations
+    fn initialize_schema(&mut self) -> Result<(), GitAiError> {
+        self.conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS schema_metadata (
+                key TEXT PRIMARY KEY NOT NULL,
+                value TEXT NOT NULL
+            );
+            "#,
+        )?;
+
+        let current_version: usize = self
+            .conn
+            .query_row(
+                "SELECT value FROM schema_metadata WHERE key = 'version'",
+                [],
+                |row| {
+                    let version_str: String = row.get(0)?;
+                    version_str
+                        .parse::<usize>()
+                        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+                },
+            )
+            .unwrap_or(0);
+
+        if current_version > SCHEMA_VERSION {
+            return Err(GitAiError::Generic(format!(
+                "Local store schema version {} is newer than supported version {}. \
+                 Please upgrade git-ai to the latest version.",
+                current_version, SCHEMA_VERSION
+            )));
+        }
+
+        // Each migration and its version bump commit together
+        for (from_version, migration_sql) in MIGRATIONS.iter().enumerate().skip(current_version) {
+            let tx = self.conn.transaction()?;
+            tx.execute_batch(migration_sql)?;
+            tx.execute(
+                "INSERT OR REPLACE INTO schema_metadata (key, value) VALUES ('version', ?1)",
+                params![(from_version + 1).to_string()],
+            )?;
+            tx.commit()?;
+        }
+
+        Ok(())
+    }
+
+    pub fn insert_metric_events(&mut self, events: &[MetricEvent]) -> Result<(), GitAiError> {
+        if events.is_empty() {
+            return Ok(());
+        }
+
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT INTO metric_events (event_id, timestamp, event_json) VALUES (?1, ?2, ?3)",
+            )?;
+            for event in events {
+                let event_json = serde_json::to_string(event)?;
+                stmt.execute(params![event.event_id, event.timestamp, event_json])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Metric events at or after `since` (unix seconds), oldest first
+    pub fn metric_events_since(
+        &self,
+        since: u32,
+        event_id: Option<u16>,
+    ) -> Result<Vec<MetricEvent>, GitAiError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT event_json FROM metric_events
+             WHERE timestamp >= ?1 AND (?2 IS NULL OR event_id = ?2)
+             ORDER BY timestamp ASC, id ASC",
+        )?;
+        let rows = stmt.query_map(params![since, event_id], |row| row.get::<_, String>(0))?;
+
+        let mut events = Vec::new();
+        for row in rows {
+            match serde_json::from_str(&row?) {
+                Ok(event) => events.push(event),
+                Err(e) => debug_log(&format!("Skipping unreadable stored metric event: {}", e)),
+            }
+        }
+        Ok(events)
+    }
+
+    pub fn insert_checkpoint(&mut self, checkpoint: &StoredCheckpoint) -> Result<(), GitAiError> {
+        self.conn.execute(
+            "INSERT INTO checkpoints (
+                repo_workdir, base_commit, kind, author, tool, model, external_id,
+                files, lines_added, lines_deleted, timestamp
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                checkpoint.repo_workdir,
+                checkpoint.base_commit,
+                checkpoint.kind,
+                checkpoint.author,
+                checkpoint.tool,
+                checkpoint.model,
+                checkpoint.external_id,
+                serde_json::to_string(&checkpoint.files)?,
+                checkpoint.lines_added,
+                checkpoint.lines_deleted,
+                checkpoint.timestamp as i64,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Checkpoints recorded for a repository at or after `since` (unix seconds), oldest first
+    pub fn checkpoints_for_repo(
+        &self,
+        repo_workdir: &str,
+        since: u64,
+    ) -> Result<Vec<StoredCheckpoint>, GitAiError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT repo_workdir, base_commit, kind, author, tool, model, external_id,
+                    files, lines_added, lines_deleted, timestamp
+             FROM checkpoints
+             WHERE repo_workdir = ?1 AND timestamp >= ?2
+             ORDER BY timestamp ASC, id ASC",
+        )?;
+        let rows = stmt.query_map(params![repo_workdir, since as i64], |row| {
+            let files: String = row.get(7)?;
+            Ok(StoredCheckpoint {
+                repo_workdir: row.get(0)?,
+                base_commit: row.get(1)?,
+                kind: row.get(2)?,
+                author: row.get(3)?,
+                tool: row.get(4)?,
+                model: row.get(5)?,
+                external_id: row.get(6)?,
+                files: serde_json::from_str(&files).unwrap_or_default(),
+                lines_added: row.get(8)?,
+                lines_deleted: row.get(9)?,
+                timestamp: row.get::<_, i64>(10)? as u64,
+            })
+        })?;
+
+        let mut checkpoints = Vec::new();
+        for row in rows {
+            checkpoints.push(row?);
+        }
+        Ok(checkpoints)
+    }
+
+    /// Delete events and checkpoints older than `cutoff` (unix seconds).
+    /// Returns the number of (metric events, checkpoints) removed.
+    pub fn prune_before(&mut self, cutoff: u64) -> Result<(usize, usize), GitAiError> {
+        let tx = self.conn.transaction()?;
+        let events = tx.execute(
+            "DELETE FROM metric_events WHERE timestamp < ?1",
+            params![cutoff as i64],
+        )?;
+        let checkpoints = tx.execute(
+            "DELETE FROM checkpoints WHERE timestamp < ?1",
+            params![cutoff as i64],
+        )?;
+        tx.commit()?;
+        Ok((events, checkpoints))
+    }
+
+    /// Delete every stored metric event, including ones spilled for a later export.
+    /// Returns the number of rows removed.
+    pub fn clear_metric_events(&mut self) -> Result<usize, GitAiError> {
+        let tx = self.conn.transaction()?;
+        let events = tx.execute("DELETE FROM metric_events", [])?;
+        let spilled = tx.execute("DELETE FROM otel_spill", [])?;
+        tx.commit()?;
+        Ok(events + spilled)
+    }
+
+    /// Park metric events that couldn't be exported in this process for a later one to deliver
+    #[cfg(any(feature = "otel", test))]
+    pub fn spill_otel_events(
+        &mut self,
+        events: &[MetricEvent],
+        now: u64,
+    ) -> Result<(), GitAiError> {
+        if events.is_empty() {
+            return Ok(());
+        }
+
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT INTO otel_spill (event_json, spilled_at) VALUES (?1, ?2)",
+            )?;
+            for event in events {
+                stmt.execute(params![serde_json::to_string(event)?, now as i64])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Claim up to `limit` spilled events, oldest first. A claim older than `lease_secs` is
+    /// treated as abandoned (its process died mid-export) and can be claimed again, so an
+    /// event only leaves the spill once `ack_spilled_otel_events` deletes it.
+    #[cfg(any(feature = "otel", test))]
+    pub fn claim_spilled_otel_events(
+        &mut self,
+        limit: usize,
+        now: u64,
+        lease_secs: u64,
+    ) -> Result<Vec<(i64, MetricEvent)>, GitAiError> {
+        let tx = self.conn.transaction()?;
+        let mut claimed = Vec::new();
+        {
+            let mut stmt = tx.prepare(
+                "SELECT id, event_json FROM otel_spill
+                 WHERE claimed_at IS NULL OR claimed_at < ?1
+                 ORDER BY id ASC LIMIT ?2",
+            )?;
+            let rows = stmt.query_map(
+                params![now.saturating_sub(lease_secs) as i64, limit as i64],
+                |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)),
+            )?;
+            let mut update =
+                tx.prepare_cached("UPDATE otel_spill SET claimed_at = ?1 WHERE id = ?2")?;
+            let mut unreadable = Vec::new();
+            for row in rows {
+                let (id, event_json) = row?;
+                match serde_json::from_str(&event_json) {
+                    Ok(event) => {
+                        update.execute(params![now as i64, id])?;
+                        claimed.push((id, event));
+                    }
+                    Err(e) => {
+                        debug_log(&format!("Dropping unreadable spilled metric event: {}", e));
+                        unreadable.push(id);
+                    }
+                }
+            }
+            for id in unreadable {
+                tx.execute("DELETE FROM otel_spill WHERE id = ?1", params![id])?;
+            }
+        }
+        tx.commit()?;
+        Ok(claimed)
+    }
+
+    /// Delete spilled events that have been exported
+    #[cfg(any(feature = "otel", test))]
+    pub fn ack_spilled_otel_events(&mut self, ids: &[i64]) -> Result<(), GitAiError> {
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare_cached("DELETE FROM otel_spill WHERE id = ?1")?;
+            for id in ids {
+                stmt.execute(params![id])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Give claimed events back so the next exporter picks them up without waiting out the lease
+    #[cfg(any(feature = "otel", test))]
+    pub fn release_spilled_otel_events(&mut self, ids: &[i64]) -> Result<(), GitAiError> {
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt =
+                tx.prepare_cached("UPDATE otel_spill SET claimed_at = NULL WHERE id = ?1")?;
+            for id in ids {
+                stmt.execute(params![id])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Rebuild the database file to reclaim space freed by deletes
+    pub fn vacuum(&mut self) -> Result<(), GitAiError> {
+        // Fold the WAL back in first so the reported size reflects the vacuumed file
+        self.conn
+            .execute_batch("PRAGMA wal_checkpoint(TRUNCATE); VACUUM;")?;
+        Ok(())
+    }
+
+    pub fn stats(&self) -> Result<StoreStats, GitAiError> {
+        let count = |table: &str| -> Result<usize, GitAiError> {
+            let count: i64 =
+                self.conn
+                    .query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| {
+                        row.get(0)
+                    })?;
+            Ok(count as usize)
+        };
+        Ok(StoreStats {
+            metric_events: count("metric_events")?,
+            checkpoints: count("checkpoints")?,
+            otel_spilled: count("otel_spill")?,
+            size_bytes: std::fs::metadata(&self.db_path)
+                .map(|m| m.len())
+                .unwrap_or(0),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.db_path
+    }
+}
+
+/// Mirror metric events into the store. Failures are logged and otherwise ignored so the
+/// store can never break metric recording.
+pub fn record_metric_events(events: &[MetricEvent]) {
+    let result = LocalStore::global().and_then(|db| {
+        let mut db = db
+            .lock()
+            .map_err(|e| GitAiError::Generic(format!("Local store lock poisoned: {}", e)))?;
+        db.insert_metric_events(events)
+    });
+    if let Err(e) = result {
+        debug_log(&format!("Failed to store metric events locally: {}", e));
+    }
+}
+
+/// Mirror a checkpoint into the store; best effort, like `record_metric_events`
+pub fn record_checkpoint(checkpoint: &StoredCheckpoint) {
+    let result = LocalStore::global().and_then(|db| {
+        let mut db = db
+            .lock()
+            .map_err(|e| GitAiError::Generic(format!("Local store lock poisoned: {}", e)))?;
+        db.insert_checkpoint(checkpoint)
+    });
+    if let Err(e) = result {
+        debug_log(&format!("Failed to store checkpoint locally: {}", e));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::types::SparseArray;
+    use tempfile::TempDir;
+
+    fn create_test_store() -> (LocalStore, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let store = LocalStore::open(&temp_dir.path().join("store-db")).unwrap();
+        (store, temp_dir)
+    }
+
+    fn event(timestamp: u32, event_id: u16) -> MetricEvent {
+        MetricEvent {
+            timestamp,
+            event_id,
+            values: SparseArray::new(),
+            attrs: SparseArray::new(),
+        }
+    }
+
+    fn checkpoint(repo: &str, timestamp: u64) -> StoredCheckpoint {
+        StoredCheckpoint {
+            repo_workdir: repo.to_string(),
+            base_commit: "abc123".to_string(),
+            kind: "ai_agent".to_string(),
+            author: "Test User".to_string(),
+            tool: Some("cursor".to_string()),
+            model: Some("gpt-5".to_string()),
+            external_id: Some("session-1".to_string()),
+            files: vec!["src/main.rs".to_string()],
+            lines_added: 4,
+            lines_deleted: 1,
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn test_migrations_are_recorded_and_idempotent() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("store-db");
+        drop(LocalStore::open(&path).unwrap());
+        let store = LocalStore::open(&path).unwrap();
+
+        let version: String = store
+            .conn
+            .query_row(
+                "SELECT value FROM schema_metadata WHERE key = 'version'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(version, SCHEMA_VERSION.to_string());
+    }
+
+    #[test]
+    fn test_metric_events_round_trip_and_filter() {
+        let (mut store, _temp_dir) = create_test_store();
+        store
+            .insert_metric_events(&[event(100, 1), event(200, 2), event(300, 1)])
+            .unwrap();
+
+        let since = store.metric_events_since(150, None).unwrap();
+        assert_eq!(
+            since.iter().map(|e| e.timestamp).collect::<Vec<_>>(),
+            vec![200, 300]
+        );
+        let committed = store.metric_events_since(0, Some(1)).unwrap();
+        assert_eq!(committed.len(), 2);
+    }
+
+    #[test]
+    fn test_checkpoints_round_trip_per_repo() {
+        let (mut store, _temp_dir) = create_test_store();
+        store.insert_checkpoint(&checkpoint("/repo/a", 10)).unwrap();
+        store.insert_checkpoint(&checkpoint("/repo/b", 20)).unwrap();
+
+        let stored = store.checkpoints_for_repo("/repo/a", 0).unwrap();
+        assert_eq!(stored, vec![checkpoint("/repo/a", 10)]);
+    }
+
+    #[test]
+    fn test_prune_and_vacuum() {
+        let (mut store, _temp_dir) = create_test_store();
+        store
+            .insert_metric_events(&[event(100, 1), event(300, 1)])
+            .unwrap();
+        store.insert_checkpoint(&checkpoint("/repo", 100)).unwrap();
+        store.insert_checkpoint(&checkpoint("/repo", 300)).unwrap();
+
+        assert_eq!(store.prune_before(200).unwrap(), (1, 1));
+        store.vacuum().unwrap();
+
+        let stats = store.stats().unwrap();
+        assert_eq!(stats.metric_events, 1);
+        assert_eq!(stats.checkpoints, 1);
+        assert!(stats.size_bytes > 0);
+    }
+
+    #[test]
+    fn test_otel_spill_claim_ack_and_lease() {
+        let (mut store, _temp_dir) = create_test_store();
+        store
+            .spill_otel_events(&[event(100, 1), event(200, 2), event(300, 3)], 1_000)
+            .unwrap();
+
+        let first = store.claim_spilled_otel_events(2, 1_000, 60).unwrap();
+        assert_eq!(
+            first.iter().map(|(_, e)| e.timestamp).collect::<Vec<_>>(),
+            vec![100, 200]
+        );
+        // Claimed rows are invisible to other exporters until the lease runs out
+        let second = store.claim_spilled_otel_events(10, 1_010, 60).unwrap();
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].1.timestamp, 300);
+
+        store.ack_spilled_otel_events(&[first[0].0]).unwrap();
+        store.release_spilled_otel_events(&[second[0].0]).unwrap();
+        let third = store.claim_spilled_otel_events(10, 1_020, 60).unwrap();
+        assert_eq!(
+            third.iter().map(|(_, e)| e.timestamp).collect::<Vec<_>>(),
+            vec![300]
+        );
+
+        // first[1] was never acked; once its lease expires it comes back
+        let expired = store.claim_spilled_otel_events(10, 1_100, 60).unwrap();
+        assert_eq!(
+            expired.iter().map(|(_, e)| e.timestamp).collect::<Vec<_>>(),
+            vec![200, 300]
+        );
+        assert_eq!(store.stats().unwrap().otel_spilled, 2);
+    }
+}
@@ -1,7 +1,9 @@
+use crate::config::Config;
 use crate::error::GitAiError;
 use crate::git::diff_tree_to_tree::Diff;
 use std::io::IsTerminal;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process::Command;
 
 /// Check if debug logging is enabled via environment variable
 ///
@@ -10,7 +12,7 @@ static DEBUG_ENABLED: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
 static DEBUG_PERFORMANCE_LEVEL: std::sync::OnceLock<u8> = std::sync::OnceLock::new();
 static IS_TERMINAL: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
 
-fn is_debug_enabled() -> bool {
+pub(crate) fn is_debug_enabled() -> bool {
     *DEBUG_ENABLED.get_or_init(|| {
         (cfg!(debug_assertions)
             || std::env::var("GIT_AI_DEBUG").unwrap_or_default() == "1"
@@ -53,9 +55,7 @@ pub fn debug_performance_log_structured(json: serde_json::Value) {
 ///
 /// * `msg` - The debug message to print
 pub fn debug_log(msg: &str) {
-    if is_debug_enabled() {
-        eprintln!("\x1b[1;33m[git-ai]\x1b[0m {}", msg);
-    }
+    crate::observability::logging::debug("git_ai", msg);
 }
 
 /// Print a git diff in a readable format
@@ -97,6 +97,30 @@ pub fn normalize_to_posix(path: &str) -> String {
     path.replace('\\', "/")
 }
 
+/// Strip the `\\?\` prefix `canonicalize()` adds on Windows, which git doesn't understand.
+/// `\\?\C:\repo` becomes `C:\repo` and `\\?\UNC\server\share\repo` becomes `\\server\share\repo`.
+pub fn strip_verbatim_prefix(path: &str) -> String {
+    if let Some(rest) = path.strip_prefix(r"\\?\UNC\") {
+        format!(r"\\{}", rest)
+    } else if let Some(rest) = path.strip_prefix(r"\\?\") {
+        rest.to_string()
+    } else {
+        path.to_string()
+    }
+}
+
+/// Single-quoted form of `path` for a `#!/bin/sh` hook script. Git for Windows runs hooks
+/// through its bundled sh whatever their extension, and that sh wants forward slashes.
+pub fn hook_script_path(path: &Path) -> String {
+    let path = strip_verbatim_prefix(&path.to_string_lossy());
+    let path = if cfg!(windows) {
+        normalize_to_posix(&path)
+    } else {
+        path
+    };
+    format!("'{}'", path.replace('\'', r"'\''"))
+}
+
 pub fn current_git_ai_exe() -> Result<PathBuf, GitAiError> {
     let path = std::env::current_exe()?;
 
@@ -127,10 +151,73 @@ pub fn current_git_ai_exe() -> Result<PathBuf, GitAiError> {
     Ok(path)
 }
 
+/// Command for a detached git-ai background process (log/CAS/metrics flushes, upgrades).
+/// It runs with the configured background priority, which its own git subprocesses inherit.
+pub fn background_git_ai_command() -> Result<Command, GitAiError> {
+    let mut cmd = Command::new(current_git_ai_exe()?);
+    lower_background_priority(&mut cmd);
+    Ok(cmd)
+}
+
+/// Lower the priority of `cmd` to the `background_nice` / `background_io_idle` settings
+pub fn lower_background_priority(cmd: &mut Command) {
+    let config = Config::get();
+    apply_background_priority(cmd, config.background_nice(), config.background_io_idle());
+}
+
+#[cfg(unix)]
+fn apply_background_priority(cmd: &mut Command, nice: i32, io_idle: bool) {
+    use std::os::unix::process::CommandExt;
+
+    if nice <= 0 && !io_idle {
+        return;
+    }
+    // SAFETY: the closure only makes async-signal-safe syscalls between fork and exec
+    unsafe {
+        cmd.pre_exec(move || {
+            if nice > 0 {
+                libc::nice(nice);
+            }
+            // ioprio_set(IOPRIO_WHO_PROCESS, self, IOPRIO_CLASS_IDLE); failure just means normal IO
+            #[cfg(target_os = "linux")]
+            if io_idle {
+                libc::syscall(libc::SYS_ioprio_set, 1, 0, 3 << 13);
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(windows)]
+fn apply_background_priority(cmd: &mut Command, nice: i32, _io_idle: bool) {
+    use std::os::windows::process::CommandExt;
+
+    const BELOW_NORMAL_PRIORITY_CLASS: u32 = 0x0000_4000;
+    if nice > 0 {
+        cmd.creation_flags(BELOW_NORMAL_PRIORITY_CLASS);
+    }
+}
+
 pub fn is_interactive_terminal() -> bool {
     *IS_TERMINAL.get_or_init(|| std::io::stdin().is_terminal())
 }
 
+/// Lowercase hex of `bytes`
+pub fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Bytes of a hex string, or `None` if it has an odd length or a non-hex digit
+pub fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
 /// Unescape a git-quoted path that may contain octal escape sequences.
 ///
 /// Git quotes filenames containing non-ASCII characters (and some special characters)
@@ -235,6 +322,43 @@ pub fn unescape_git_path(path: &str) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_hex_round_trip() {
+        assert_eq!(encode_hex(&[0x00, 0xab, 0x7f]), "00ab7f");
+        assert_eq!(decode_hex("00ab7f"), Some(vec![0x00, 0xab, 0x7f]));
+        assert_eq!(decode_hex("00AB7F"), Some(vec![0x00, 0xab, 0x7f]));
+        assert_eq!(decode_hex("abc"), None);
+        assert_eq!(decode_hex("zz"), None);
+    }
+
+    #[test]
+    fn test_strip_verbatim_prefix() {
+        assert_eq!(strip_verbatim_prefix(r"\\?\C:\repo"), r"C:\repo");
+        assert_eq!(
+            strip_verbatim_prefix(r"\\?\UNC\server\share\repo"),
+            r"\\server\share\repo"
+        );
+        assert_eq!(
+            strip_verbatim_prefix(r"\\server\share\repo"),
+            r"\\server\share\repo"
+        );
+        assert_eq!(strip_verbatim_prefix("/home/user/repo"), "/home/user/repo");
+    }
+
+    #[test]
+    fn test_hook_script_path_quotes_for_sh() {
+        assert_eq!(
+            hook_script_path(Path::new("/opt/it's here/git-ai")),
+            r"'/opt/it'\''s here/git-ai'"
+        );
+        if cfg!(windows) {
+            assert_eq!(
+                hook_script_path(Path::new(r"\\?\C:\Program Files\git-ai.exe")),
+                "'C:/Program Files/git-ai.exe'"
+            );
+        }
+    }
+
     #[test]
     fn test_unescape_git_path_simple() {
         // Unquoted path - no change
@@ -684,4 +808,22 @@ mod tests {
             "Ångström.txt"
         );
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_background_priority_applies_nice_level() {
+        let niceness = |cmd: &mut Command| -> i32 {
+            let output = cmd.output().expect("failed to run nice");
+            String::from_utf8_lossy(&output.stdout).trim().parse().unwrap()
+        };
+        let base = niceness(&mut Command::new("nice"));
+
+        let mut cmd = Command::new("nice");
+        apply_background_priority(&mut cmd, 5, false);
+        assert_eq!(niceness(&mut cmd), (base + 5).min(19));
+
+        let mut cmd = Command::new("nice");
+        apply_background_priority(&mut cmd, 0, false);
+        assert_eq!(niceness(&mut cmd), base);
+    }
 }
@@ -0,0 +1,77 @@
+#[macro_use]
+mod repos;
+use repos::test_repo::TestRepo;
+use std::fs;
+
+fn prompt_tools(repo: &TestRepo, message: &str) -> Vec<(String, String)> {
+    let commit = repo.stage_all_and_commit(message).unwrap();
+    commit
+        .authorship_log
+        .metadata
+        .prompts
+        .values()
+        .map(|prompt| (prompt.agent_id.tool.clone(), prompt.agent_id.model.clone()))
+        .collect()
+}
+
+#[test]
+fn test_checkpoint_auto_uses_agent_handshake() {
+    let repo = TestRepo::new();
+    fs::write(repo.path().join("README.md"), "base\n").unwrap();
+    repo.stage_all_and_commit("base").unwrap();
+
+    fs::write(repo.path().join("README.md"), "base\nfrom the plugin\n").unwrap();
+    repo.git_ai_with_env(
+        &["checkpoint", "auto", "README.md"],
+        &[
+            ("GIT_AI_AGENT", "Zed-Assistant"),
+            ("GIT_AI_AGENT_MODEL", "claude-sonnet-4"),
+        ],
+    )
+    .unwrap();
+
+    assert_eq!(
+        prompt_tools(&repo, "plugin edit"),
+        vec![("zed-assistant".to_string(), "claude-sonnet-4".to_string())]
+    );
+}
+
+#[test]
+fn test_checkpoint_auto_uses_configured_detector() {
+    let repo = TestRepo::new();
+    fs::write(repo.path().join("README.md"), "base\n").unwrap();
+    repo.stage_all_and_commit("base").unwrap();
+
+    let home = repo.path().join("agent-home");
+    fs::create_dir_all(home.join(".git-ai")).unwrap();
+    fs::write(
+        home.join(".git-ai").join("config.json"),
+        serde_json::json!({
+            "agent_detectors": [{
+                "tool": "cline",
+                "env_var": "CLINE_ACTIVE",
+                "env_value": "1",
+                "model_env": "CLINE_MODEL",
+            }]
+        })
+        .to_string(),
+    )
+    .unwrap();
+    let home = home.to_string_lossy().to_string();
+
+    fs::write(repo.path().join("README.md"), "base\nfrom cline\n").unwrap();
+    repo.git_ai_with_env(
+        &["checkpoint", "auto", "README.md"],
+        &[
+            ("HOME", &home),
+            ("CLINE_ACTIVE", "1"),
+            ("CLINE_MODEL", "GPT-5"),
+        ],
+    )
+    .unwrap();
+
+    assert_eq!(
+        prompt_tools(&repo, "cline edit"),
+        vec![("cline".to_string(), "gpt-5".to_string())]
+    );
+}
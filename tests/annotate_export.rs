@@ -0,0 +1,94 @@
+mod repos;
+
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+use std::fs;
+
+#[test]
+fn test_annotate_export_and_strip_round_trip() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("lib.rs");
+    file.set_contents(vec![
+        "fn human() {}".human(),
+        "fn ai_one() {}".ai(),
+        "fn ai_two() {}".ai(),
+        "fn human_two() {}".human(),
+    ]);
+    let commit = repo.stage_all_and_commit("mixed").unwrap().commit_sha;
+    let path = repo.path().join("lib.rs");
+    let original = fs::read_to_string(&path).unwrap();
+
+    let output = repo.git_ai(&["annotate-export", "lib.rs"]).unwrap();
+    assert!(output.contains("lib.rs: 1 annotation added"), "{}", output);
+    let annotated = fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = annotated.lines().collect();
+    assert_eq!(lines.len(), 5, "{}", annotated);
+    assert_eq!(lines[0], "fn human() {}");
+    assert!(
+        lines[1].starts_with("// ai-generated: tool="),
+        "{}",
+        annotated
+    );
+    assert!(
+        lines[1].ends_with(&format!("commit={} lines=2", &commit[..12])),
+        "{}",
+        annotated
+    );
+    assert_eq!(lines[2], "fn ai_one() {}");
+
+    // The working tree no longer matches HEAD, so a second in-place export is refused
+    assert!(repo.git_ai(&["annotate-export", "lib.rs"]).is_err());
+
+    let output = repo.git_ai(&["annotate-strip", "lib.rs"]).unwrap();
+    assert!(
+        output.contains("lib.rs: 1 annotation removed"),
+        "{}",
+        output
+    );
+    assert_eq!(fs::read_to_string(&path).unwrap(), original);
+}
+
+#[test]
+fn test_annotate_export_of_a_revision_writes_to_output_dir() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("script.py");
+    file.set_contents(vec!["print('ai')".ai()]);
+    repo.stage_all_and_commit("ai script").unwrap();
+    file.set_contents(vec!["print('human')".human()]);
+    repo.stage_all_and_commit("human rewrite").unwrap();
+
+    let out = tempfile::tempdir().unwrap();
+    assert!(
+        repo.git_ai(&["annotate-export", "--rev", "HEAD~1", "script.py"])
+            .is_err(),
+        "--rev without --output should be refused"
+    );
+    let output = repo
+        .git_ai(&[
+            "annotate-export",
+            "--rev",
+            "HEAD~1",
+            "--output",
+            out.path().to_str().unwrap(),
+            "--format",
+            "json",
+            "script.py",
+        ])
+        .unwrap();
+    let report: serde_json::Value = serde_json::from_str(output.lines().last().unwrap()).unwrap();
+    assert_eq!(report["files"][0]["annotations"], 1);
+    assert_eq!(report["files"][0]["ai_lines"], 1);
+
+    let exported = fs::read_to_string(out.path().join("script.py")).unwrap();
+    assert!(
+        exported.starts_with("# ai-generated: tool="),
+        "{}",
+        exported
+    );
+    assert!(exported.ends_with("\nprint('ai')"), "{}", exported);
+    // The working tree is untouched
+    assert_eq!(
+        fs::read_to_string(repo.path().join("script.py")).unwrap(),
+        "print('human')"
+    );
+}
@@ -0,0 +1,144 @@
+mod repos;
+use git_ai::authorship::authorship_log_serialization::AuthorshipLog;
+use repos::test_repo::TestRepo;
+use std::fs;
+
+/// Commit `contents` to `file` with plain git, as history made before git-ai was installed
+fn old_commit(repo: &TestRepo, file: &str, contents: &str, message: &str, author: &str) -> String {
+    fs::write(repo.path().join(file), contents).unwrap();
+    repo.git_og(&["add", file]).unwrap();
+    repo.git_og(&["commit", "-m", message, "--author", author])
+        .unwrap();
+    repo.git_og(&["rev-parse", "HEAD"])
+        .unwrap()
+        .trim()
+        .to_string()
+}
+
+fn note(repo: &TestRepo, sha: &str) -> Option<AuthorshipLog> {
+    let content = repo.git_og(&["notes", "--ref=ai", "show", sha]).ok()?;
+    Some(AuthorshipLog::deserialize_from_string(&content).unwrap())
+}
+
+fn ai_tool_and_lines(log: &AuthorshipLog) -> Option<(String, String, u32)> {
+    let prompt = log.metadata.prompts.values().next()?;
+    Some((
+        prompt.agent_id.tool.clone(),
+        prompt.agent_id.model.clone(),
+        prompt.accepted_lines,
+    ))
+}
+
+#[test]
+fn test_backfill_attributes_agent_commits_from_history() {
+    let repo = TestRepo::new();
+    let human = old_commit(
+        &repo,
+        "a.txt",
+        "one\ntwo\n",
+        "Human work",
+        "Jane Doe <jane@example.com>",
+    );
+    let trailer = old_commit(
+        &repo,
+        "b.txt",
+        "1\n2\n3\n",
+        "Add b\n\nCo-authored-by: aider (gpt-4o) <noreply@aider.chat>",
+        "Jane Doe <jane@example.com>",
+    );
+    let bot = old_commit(
+        &repo,
+        "c.txt",
+        "x\n",
+        "Fix c",
+        "devin-ai-integration[bot] <158243242+devin-ai-integration[bot]@users.noreply.github.com>",
+    );
+    let mapped = old_commit(
+        &repo,
+        "d.txt",
+        "p\nq\n",
+        "Add d",
+        "Jane Doe <jane@example.com>",
+    );
+
+    let mapping = repo.path().join("mapping.csv");
+    fs::write(
+        &mapping,
+        format!("commit,tool,model\n{},cursor,gpt-5\n", &mapped[..10]),
+    )
+    .unwrap();
+
+    let dry_run = repo
+        .git_ai(&[
+            "backfill",
+            "--dry-run",
+            "--mapping",
+            mapping.to_str().unwrap(),
+        ])
+        .unwrap();
+    assert!(
+        dry_run.contains("Would backfill 4 commits: 3 AI-assisted, 1 human"),
+        "{}",
+        dry_run
+    );
+    assert!(note(&repo, &human).is_none());
+
+    let output = repo
+        .git_ai(&["backfill", "--mapping", mapping.to_str().unwrap()])
+        .unwrap();
+    assert!(output.contains("Backfilled 4 commits"), "{}", output);
+
+    let human_note = note(&repo, &human).unwrap();
+    assert!(human_note.attestations.is_empty());
+    assert!(human_note.metadata.prompts.is_empty());
+    assert_eq!(
+        ai_tool_and_lines(&note(&repo, &trailer).unwrap()),
+        Some(("aider".to_string(), "gpt-4o".to_string(), 3))
+    );
+    assert_eq!(
+        ai_tool_and_lines(&note(&repo, &bot).unwrap()),
+        Some(("devin".to_string(), "unknown".to_string(), 1))
+    );
+    let mapped_note = note(&repo, &mapped).unwrap();
+    assert_eq!(
+        ai_tool_and_lines(&mapped_note),
+        Some(("cursor".to_string(), "gpt-5".to_string(), 2))
+    );
+    assert_eq!(mapped_note.attestations[0].file_path, "d.txt");
+
+    // Notes that exist are never rewritten
+    let output = repo.git_ai(&["backfill"]).unwrap();
+    assert!(output.contains("Backfilled 0 commits"), "{}", output);
+    assert!(output.contains("4 already had notes"), "{}", output);
+}
+
+#[test]
+fn test_backfill_ai_only_skips_human_commits() {
+    let repo = TestRepo::new();
+    let human = old_commit(
+        &repo,
+        "a.txt",
+        "one\n",
+        "Human work",
+        "Jane Doe <jane@example.com>",
+    );
+    let ai = old_commit(
+        &repo,
+        "b.txt",
+        "two\n",
+        "Add b\n\nCo-Authored-By: Claude <noreply@anthropic.com>",
+        "Jane Doe <jane@example.com>",
+    );
+
+    let output = repo.git_ai(&["backfill", "--ai-only"]).unwrap();
+    assert!(
+        output.contains("Skipped 1 commit with no sign of AI"),
+        "{}",
+        output
+    );
+    assert!(note(&repo, &human).is_none());
+    assert_eq!(
+        ai_tool_and_lines(&note(&repo, &ai).unwrap()).map(|(tool, _, _)| tool),
+        Some("claude".to_string())
+    );
+}
@@ -668,6 +668,51 @@ fn test_blame_with_ai_authorship() {
     );
 }
 
+#[test]
+fn test_blame_show_model() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("test.txt");
+
+    file.set_contents(lines!["Line 1", "Line 2".ai()]);
+    repo.stage_all_and_commit("Mixed authorship commit")
+        .unwrap();
+
+    let output = repo.git_ai(&["blame", "--show-model", "test.txt"]).unwrap();
+    let authors = extract_authors(&output);
+    assert_eq!(authors[1], "mock_ai/unknown", "Got: {:?}", authors);
+    assert_ne!(authors[0], "mock_ai/unknown");
+}
+
+#[test]
+fn test_blame_color_highlights_ai_lines() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("test.txt");
+
+    file.set_contents(lines!["Line 1", "Line 2".ai()]);
+    repo.stage_all_and_commit("Mixed authorship commit")
+        .unwrap();
+
+    let colored = repo
+        .git_ai(&["blame", "--color=always", "test.txt"])
+        .unwrap();
+    let lines: Vec<&str> = colored.lines().collect();
+    assert!(
+        !lines[0].contains("\x1b["),
+        "human line should not be colored"
+    );
+    assert!(
+        lines[1].contains("\x1b[35mmock_ai"),
+        "AI line should be highlighted: {:?}",
+        lines[1]
+    );
+
+    // Piped output is uncolored unless asked for
+    let plain = repo.git_ai(&["blame", "test.txt"]).unwrap();
+    assert!(!plain.contains("\x1b["));
+    let forced_off = repo.git_ai(&["blame", "--no-color", "test.txt"]).unwrap();
+    assert_eq!(plain, forced_off);
+}
+
 #[test]
 fn test_blame_contents_from_stdin() {
     let repo = TestRepo::new();
@@ -1128,6 +1173,8 @@ fn test_blame_ai_human_author() {
             accepted_lines: 1,
             overriden_lines: 0,
             messages_url: None,
+            token_usage: None,
+            turns: Vec::new(),
         },
     );
 
@@ -1149,6 +1196,8 @@ fn test_blame_ai_human_author() {
             accepted_lines: 1,
             overriden_lines: 0,
             messages_url: None,
+            token_usage: None,
+            turns: Vec::new(),
         },
     );
 
@@ -0,0 +1,71 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+#[test]
+fn test_branches_stale_lists_unmerged_ai_branch() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("main.txt");
+    file.set_contents(lines!["base"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+    let main_branch = repo.current_branch();
+
+    repo.git(&["checkout", "-b", "feature"]).unwrap();
+    let mut feature_file = repo.filename("feature.txt");
+    feature_file.set_contents(lines!["AI feature".ai()]);
+    repo.stage_all_and_commit("AI feature work").unwrap();
+    repo.git(&["checkout", &main_branch]).unwrap();
+
+    let output = repo.git_ai(&["branches", "--stale"]).unwrap();
+    assert!(
+        output.contains("feature"),
+        "expected feature branch: {}",
+        output
+    );
+    assert!(!output.contains(&format!("  {} ", main_branch)));
+
+    // Merging the branch makes its notes reachable from main, so it is no longer stale
+    repo.git(&["merge", "--no-ff", "feature", "-m", "Merge feature"])
+        .unwrap();
+    let output = repo.git_ai(&["branches", "--stale"]).unwrap();
+    assert!(
+        output.contains("No branches hold AI attribution"),
+        "expected no stale branches: {}",
+        output
+    );
+}
+
+#[test]
+fn test_branches_stale_archive_preserves_notes() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("main.txt");
+    file.set_contents(lines!["base"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+    let main_branch = repo.current_branch();
+
+    repo.git(&["checkout", "-b", "experiment"]).unwrap();
+    let mut feature_file = repo.filename("experiment.txt");
+    feature_file.set_contents(lines!["AI experiment".ai()]);
+    let ai_commit = repo.stage_all_and_commit("AI experiment").unwrap();
+    repo.git(&["checkout", &main_branch]).unwrap();
+
+    let output = repo
+        .git_ai(&["branches", "--stale", "--archive", "--json"])
+        .unwrap();
+    assert!(output.contains("refs/ai-archive/experiment"), "{}", output);
+    assert!(output.contains(&ai_commit.commit_sha), "{}", output);
+
+    let archived = repo
+        .git(&["rev-parse", "refs/ai-archive/experiment"])
+        .unwrap();
+    assert_eq!(archived.trim(), ai_commit.commit_sha);
+
+    // Archived commits no longer count as unreachable
+    let output = repo.git_ai(&["branches", "--stale"]).unwrap();
+    assert!(
+        output.contains("No branches hold AI attribution"),
+        "{}",
+        output
+    );
+}
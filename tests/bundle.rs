@@ -0,0 +1,90 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+#[test]
+fn test_bundle_carries_notes_to_another_repository() {
+    let sender = TestRepo::new();
+    let mut file = sender.filename("lib.rs");
+    file.set_contents(lines!["fn human() {}".human(), "fn ai() {}".ai()]);
+    let commit = sender.stage_all_and_commit("AI lib").unwrap();
+
+    let bundle = sender.path().join(".git").join("lib.bundle");
+    let bundle = bundle.to_str().unwrap();
+    let output = sender
+        .git_ai(&["bundle", "create", bundle, "HEAD"])
+        .unwrap();
+    assert!(
+        output.contains("Bundled 1 commit(s) with 1 authorship note(s)"),
+        "{}",
+        output
+    );
+    // The temporary notes ref doesn't outlive the command
+    assert!(
+        sender
+            .git_og(&["rev-parse", "--verify", "--quiet", "refs/notes/ai-bundle"])
+            .is_err()
+    );
+
+    let receiver = TestRepo::new();
+    receiver
+        .git_og(&["fetch", bundle, "HEAD:refs/heads/imported"])
+        .unwrap();
+    let output = receiver.git_ai(&["bundle", "apply", bundle]).unwrap();
+    assert!(
+        output.contains("Imported 1 authorship note(s)"),
+        "{}",
+        output
+    );
+    let note = receiver
+        .git_og(&["notes", "--ref=ai", "show", &commit.commit_sha])
+        .unwrap();
+    let original = sender
+        .git_og(&["notes", "--ref=ai", "show", &commit.commit_sha])
+        .unwrap();
+    assert_eq!(note, original);
+}
+
+#[test]
+fn test_bundle_patches_restore_notes_on_am() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("lib.rs");
+    file.set_contents(lines!["fn human() {}".human()]);
+    repo.stage_all_and_commit("Human lib").unwrap();
+    file.set_contents(lines!["fn human() {}".human(), "fn ai() {}".ai()]);
+    let commit = repo.stage_all_and_commit("AI lib").unwrap();
+
+    let patches = repo.path().join(".git").join("patches");
+    let patches = patches.to_str().unwrap();
+    let output = repo
+        .git_ai(&["bundle", "create", "--patches", patches, "HEAD~1..HEAD"])
+        .unwrap();
+    assert!(
+        output.contains("Wrote 1 patch(es), 1 with authorship notes"),
+        "{}",
+        output
+    );
+
+    // Throw the commit and its note away, then bring both back from the patch
+    let original = repo
+        .git_og(&["notes", "--ref=ai", "show", &commit.commit_sha])
+        .unwrap();
+    repo.git_og(&["reset", "--hard", "--quiet", "HEAD~1"])
+        .unwrap();
+    repo.git_og(&["notes", "--ref=ai", "remove", &commit.commit_sha])
+        .unwrap();
+    let output = repo.git_ai(&["bundle", "apply", patches]).unwrap();
+    assert!(
+        output.contains("Applied 1 patch(es), restored 1 authorship note(s)"),
+        "{}",
+        output
+    );
+
+    assert_eq!(
+        repo.git_og(&["log", "-1", "--format=%B"]).unwrap().trim(),
+        "AI lib"
+    );
+    let note = repo.git_og(&["notes", "--ref=ai", "show", "HEAD"]).unwrap();
+    assert_eq!(note, original);
+}
@@ -0,0 +1,101 @@
+mod repos;
+use filetime::{FileTime, set_file_mtime};
+use repos::test_repo::TestRepo;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+fn commit_file(repo: &TestRepo, file: &str, contents: &str) -> String {
+    fs::write(repo.path().join(file), contents).unwrap();
+    repo.stage_all_and_commit(&format!("Add {}", file)).unwrap();
+    repo.git(&["rev-parse", "HEAD"]).unwrap().trim().to_string()
+}
+
+/// A working log for `base` with `checkpoints` lines, last written `days_old` days ago
+fn working_log(repo: &TestRepo, base: &str, checkpoints: usize, days_old: u64) -> PathBuf {
+    let dir = repo.path().join(".git/ai/working_logs").join(base);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("checkpoints.jsonl"), "{}\n".repeat(checkpoints)).unwrap();
+    let time = FileTime::from_system_time(
+        SystemTime::now() - Duration::from_secs(days_old * 24 * 60 * 60),
+    );
+    set_file_mtime(dir.join("checkpoints.jsonl"), time).unwrap();
+    set_file_mtime(&dir, time).unwrap();
+    dir
+}
+
+fn exists(dir: &Path) -> bool {
+    dir.is_dir()
+}
+
+#[test]
+fn test_gc_prunes_stale_logs_and_keeps_head_and_branch_tips() {
+    let repo = TestRepo::new();
+    let first = commit_file(&repo, "a.txt", "one\n");
+    repo.git(&["branch", "feature"]).unwrap();
+    let head = commit_file(&repo, "b.txt", "two\n");
+
+    let head_log = working_log(&repo, &head, 0, 90);
+    let tip_log = working_log(&repo, &first, 2, 90);
+    let stale_log = working_log(&repo, &"a".repeat(40), 3, 45);
+    let empty_log = working_log(&repo, &"b".repeat(40), 0, 0);
+    let recent_log = working_log(&repo, &"c".repeat(40), 1, 1);
+
+    let output = repo
+        .git_ai(&["gc", "--dry-run", "--max-age", "30", "--max-count", "0"])
+        .unwrap();
+    assert!(
+        output.contains("Would remove working log aaaaaaaaaaaa (expired, 3 checkpoints"),
+        "{}",
+        output
+    );
+    assert!(
+        output.contains("Would remove working log bbbbbbbbbbbb (empty"),
+        "{}",
+        output
+    );
+    assert!(exists(&stale_log) && exists(&empty_log));
+
+    let output = repo
+        .git_ai(&["gc", "--max-age", "30", "--max-count", "0"])
+        .unwrap();
+    assert!(
+        output.contains("Removed working log bbbbbbbbbbbb (empty, 0 checkpoints"),
+        "{}",
+        output
+    );
+    assert!(!exists(&stale_log) && !exists(&empty_log));
+    assert!(exists(&head_log) && exists(&tip_log) && exists(&recent_log));
+
+    // The branch tip's checkpoints go once uncommitted work may be pruned; HEAD's never do
+    let output = repo
+        .git_ai(&[
+            "gc",
+            "--max-age",
+            "30",
+            "--max-count",
+            "0",
+            "--prune-uncommitted",
+        ])
+        .unwrap();
+    assert!(output.contains("(expired, 2 checkpoints"), "{}", output);
+    assert!(!exists(&tip_log));
+    assert!(exists(&head_log) && exists(&recent_log));
+}
+
+#[test]
+fn test_gc_max_count_removes_oldest_logs() {
+    let repo = TestRepo::new();
+    commit_file(&repo, "a.txt", "one\n");
+
+    let oldest = working_log(&repo, &"a".repeat(40), 1, 3);
+    let middle = working_log(&repo, &"b".repeat(40), 1, 2);
+    let newest = working_log(&repo, &"c".repeat(40), 1, 1);
+
+    let output = repo
+        .git_ai(&["gc", "--max-age", "0", "--max-count", "2"])
+        .unwrap();
+    assert!(output.contains("(over limit, 1 checkpoint"), "{}", output);
+    assert!(!exists(&oldest));
+    assert!(exists(&middle) && exists(&newest));
+}
@@ -0,0 +1,85 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+use std::fs;
+
+/// main with one human commit, and a feature branch adding AI and human lines on top
+fn repo_with_pr_branch() -> (TestRepo, String, String) {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("app.js");
+    file.set_contents(lines!["// app"]);
+    let base = repo.stage_all_and_commit("Initial commit").unwrap();
+
+    repo.git(&["checkout", "-b", "feature"]).unwrap();
+    file.set_contents(lines![
+        "// app",
+        "function ai() {".ai(),
+        "  return 1;".ai(),
+        "}".ai(),
+        "// reviewed by hand"
+    ]);
+    let head = repo.stage_all_and_commit("Add feature").unwrap();
+
+    (repo, base.commit_sha, head.commit_sha)
+}
+
+#[test]
+fn test_ci_github_summary_writes_step_summary_from_event() {
+    let (repo, base_sha, head_sha) = repo_with_pr_branch();
+
+    let event_path = repo.path().join("event.json");
+    fs::write(
+        &event_path,
+        serde_json::json!({
+            "pull_request": {
+                "number": 7,
+                "merged": false,
+                "merge_commit_sha": null,
+                "base": { "ref": "main", "sha": base_sha, "repo": { "clone_url": "" } },
+                "head": { "ref": "feature", "sha": head_sha, "repo": { "clone_url": "" } },
+            }
+        })
+        .to_string(),
+    )
+    .unwrap();
+    let summary_path = repo.path().join("step-summary.md");
+
+    repo.git_ai_with_env(
+        &["ci", "github", "summary"],
+        &[
+            ("GITHUB_EVENT_NAME", "pull_request"),
+            ("GITHUB_EVENT_PATH", event_path.to_str().unwrap()),
+            ("GITHUB_STEP_SUMMARY", summary_path.to_str().unwrap()),
+        ],
+    )
+    .unwrap();
+
+    let summary = fs::read_to_string(&summary_path).unwrap();
+    assert!(
+        summary.contains("<!-- git-ai:pr-summary -->"),
+        "{}",
+        summary
+    );
+    assert!(summary.contains("| AI | 3 | 60% |"), "{}", summary);
+    assert!(summary.contains("| Human | 2 | 40% |"), "{}", summary);
+    assert!(
+        summary.contains("Commits with authorship notes: **1** of **1**"),
+        "{}",
+        summary
+    );
+}
+
+#[test]
+fn test_ci_github_summary_prints_markdown_outside_actions() {
+    let (repo, base_sha, _) = repo_with_pr_branch();
+
+    let output = repo
+        .git_ai_with_env(
+            &["ci", "github", "summary", "--base", &base_sha],
+            &[("GITHUB_EVENT_NAME", ""), ("GITHUB_STEP_SUMMARY", "")],
+        )
+        .unwrap();
+    assert!(output.contains("### git-ai authorship"), "{}", output);
+    assert!(output.contains("| AI | 3 | 60% |"), "{}", output);
+}
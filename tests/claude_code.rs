@@ -43,6 +43,18 @@ fn test_parse_example_claude_code_jsonl_with_model() {
     }
 }
 
+#[test]
+fn test_claude_code_token_usage_counts_each_response_once() {
+    let fixture = fixture_path("example-claude-code.jsonl");
+    let usage = ClaudePreset::token_usage_from_claude_code_jsonl(fixture.to_str().unwrap())
+        .expect("fixture has usage");
+
+    // Responses split over several entries share one usage block; cache tokens count as input
+    assert_eq!(usage.input_tokens, 322437);
+    assert_eq!(usage.output_tokens, 468);
+    assert_eq!(usage.cost_usd, None);
+}
+
 #[test]
 fn test_claude_preset_extracts_edited_filepath() {
     let hook_input = r##"{
@@ -368,3 +380,19 @@ fn test_user_text_content_blocks_are_parsed_correctly() {
         "Second message should be Assistant"
     );
 }
+
+#[test]
+fn test_claude_preset_rejects_hostile_hook_input() {
+    let deeply_nested = format!("{}{}", "[".repeat(100_000), "]".repeat(100_000));
+    let oversized = format!(
+        r#"{{"cwd":"/tmp","transcript_path":"{}"}}"#,
+        "a".repeat(git_ai::commands::checkpoint_agent::agent_presets::MAX_HOOK_INPUT_BYTES)
+    );
+
+    for hook_input in [deeply_nested, oversized, "{\"cwd\":".to_string()] {
+        let result = ClaudePreset.run(AgentCheckpointFlags {
+            hook_input: Some(hook_input),
+        });
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,82 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+fn json_line(output: &str) -> serde_json::Value {
+    let line = output
+        .lines()
+        .find(|line| line.starts_with('{'))
+        .unwrap_or_else(|| panic!("no JSON object in {}", output));
+    serde_json::from_str(line).unwrap()
+}
+
+#[test]
+fn test_compare_range_reports_ai_share_agent_and_directory_changes() {
+    let repo = TestRepo::new();
+    let mut readme = repo.filename("README.md");
+    readme.set_contents(lines!["one", "two"]);
+    repo.stage_all_and_commit("docs").unwrap();
+    repo.git_og(&["tag", "before"]).unwrap();
+
+    let mut lib = repo.filename("src/lib.rs");
+    lib.set_contents(lines!["fn a() {}".ai(), "fn b() {}".ai()]);
+    repo.stage_all_and_commit("AI lib").unwrap();
+
+    let json = json_line(&repo.git_ai(&["compare", "before..HEAD", "--json"]).unwrap());
+    assert_eq!(json["schema_version"], 1);
+    assert_eq!(json["before"]["rev"], "before");
+    assert_eq!(json["before"]["ai_percent"], 0.0);
+    assert_eq!(json["after"]["ai_lines"], 2);
+    assert_eq!(json["after"]["ai_percent"], 50.0);
+    assert_eq!(json["ai_percent_change"], 50.0);
+    assert_eq!(json["range"]["rev"], "before..HEAD");
+    assert_eq!(json["range"]["commits"], 1);
+    assert_eq!(json["range"]["ai_percent"], 100.0);
+
+    assert_eq!(json["agents"][0]["before_lines"], 0);
+    assert_eq!(json["agents"][0]["change"], 2);
+    assert!(json["agents"][0].get("growth_percent").is_none());
+
+    assert_eq!(json["directories"][0]["directory"], "src/");
+    assert_eq!(json["directories"][0]["after_ai_percent"], 100.0);
+    assert_eq!(json["directories"][0]["change"], 100.0);
+
+    let text = repo.git_ai(&["compare", "before..HEAD"]).unwrap();
+    assert!(
+        text.contains("Comparing before (1 commits) with HEAD (2 commits)"),
+        "{}",
+        text
+    );
+    assert!(text.contains("+50.0 pp"), "{}", text);
+}
+
+#[test]
+fn test_compare_two_ranges_and_rejects_bad_input() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("app.txt");
+    file.set_contents(lines!["base"]);
+    repo.stage_all_and_commit("base").unwrap();
+    repo.git_og(&["tag", "v1"]).unwrap();
+    file.set_contents(lines!["base", "human"]);
+    repo.stage_all_and_commit("human").unwrap();
+    repo.git_og(&["tag", "v2"]).unwrap();
+    file.set_contents(lines!["base", "human", "ai".ai()]);
+    repo.stage_all_and_commit("ai").unwrap();
+
+    let json = json_line(
+        &repo
+            .git_ai(&["compare", "v1..v2", "v2..HEAD", "--format", "json"])
+            .unwrap(),
+    );
+    assert_eq!(json["before"]["commits"], 1);
+    assert_eq!(json["before"]["ai_percent"], 0.0);
+    assert_eq!(json["after"]["ai_lines"], 1);
+    assert!(json["ai_percent_change"].as_f64().unwrap() > 0.0);
+    assert!(json.get("range").is_none());
+
+    let err = repo.git_ai(&["compare", "HEAD"]).unwrap_err();
+    assert!(err.contains("HEAD is not a range"), "{}", err);
+    let err = repo.git_ai(&["compare", "nope..HEAD"]).unwrap_err();
+    assert!(err.contains("nope is not a commit"), "{}", err);
+}
@@ -0,0 +1,75 @@
+mod repos;
+
+use repos::test_repo::TestRepo;
+use std::fs;
+
+fn home_with_config(repo: &TestRepo, name: &str, config: serde_json::Value) -> String {
+    let home = repo.path().join(name);
+    fs::create_dir_all(home.join(".git-ai")).unwrap();
+    fs::write(
+        home.join(".git-ai").join("config.json"),
+        serde_json::to_string_pretty(&config).unwrap(),
+    )
+    .unwrap();
+    home.to_string_lossy().to_string()
+}
+
+fn read_config(home: &str) -> serde_json::Value {
+    let data = fs::read_to_string(format!("{}/.git-ai/config.json", home)).unwrap();
+    serde_json::from_str(&data).unwrap()
+}
+
+#[test]
+fn test_config_bundle_export_import_round_trip() {
+    let repo = TestRepo::new();
+    let lead_home = home_with_config(
+        &repo,
+        "lead-home",
+        serde_json::json!({
+            "exclude_repositories": ["https://github.com/acme/secret-*"],
+            "prompt_storage": "notes",
+            "api_key": "lead-personal-key",
+        }),
+    );
+    let new_hire_home = home_with_config(
+        &repo,
+        "new-hire-home",
+        serde_json::json!({ "api_key": "new-hire-key" }),
+    );
+    let bundle_path = repo.path().join("team-bundle.json");
+    let bundle_path = bundle_path.to_str().unwrap();
+
+    repo.git_ai_with_env(
+        &["config", "export-bundle", "--output", bundle_path],
+        &[("HOME", &lead_home), ("GIT_AI_BUNDLE_KEY", "team-secret")],
+    )
+    .unwrap();
+    let bundle = fs::read_to_string(bundle_path).unwrap();
+    assert!(!bundle.contains("lead-personal-key"), "{}", bundle);
+
+    // Wrong key is rejected and leaves the config untouched
+    let err = repo
+        .git_ai_with_env(
+            &["config", "import-bundle", bundle_path],
+            &[("HOME", &new_hire_home), ("GIT_AI_BUNDLE_KEY", "wrong")],
+        )
+        .unwrap_err();
+    assert!(err.contains("signature does not match"), "{}", err);
+    assert!(read_config(&new_hire_home).get("prompt_storage").is_none());
+
+    repo.git_ai_with_env(
+        &["config", "import-bundle", bundle_path],
+        &[
+            ("HOME", &new_hire_home),
+            ("GIT_AI_BUNDLE_KEY", "team-secret"),
+        ],
+    )
+    .unwrap();
+    let config = read_config(&new_hire_home);
+    assert_eq!(config["prompt_storage"], "notes");
+    assert_eq!(
+        config["exclude_repositories"],
+        serde_json::json!(["https://github.com/acme/secret-*"])
+    );
+    assert_eq!(config["api_key"], "new-hire-key");
+}
@@ -0,0 +1,111 @@
+mod repos;
+use repos::test_repo::TestRepo;
+use std::fs;
+
+#[test]
+fn test_repo_layer_overrides_system_and_env_overrides_both() {
+    let repo = TestRepo::new();
+    let system = repo.path().join("system-git-ai.toml");
+    fs::write(
+        &system,
+        "otel_endpoint = \"http://system:4317\"\notel_traces_enabled = true\n",
+    )
+    .unwrap();
+    let system_env = ("GIT_AI_SYSTEM_CONFIG", system.to_str().unwrap());
+
+    repo.git_ai(&[
+        "config",
+        "set",
+        "otel_endpoint",
+        "http://repo:4317",
+        "--repo",
+    ])
+    .unwrap();
+    let repo_file = repo.path().join(".git/git-ai.toml");
+    assert_eq!(
+        fs::read_to_string(&repo_file).unwrap(),
+        "otel_endpoint = \"http://repo:4317\"\n"
+    );
+
+    let output = repo
+        .git_ai_with_env(
+            &["config", "get", "otel_endpoint", "--show-origin"],
+            &[system_env],
+        )
+        .unwrap();
+    assert!(
+        output.contains(&format!("file:{}\thttp://repo:4317", repo_file.display())),
+        "{}",
+        output
+    );
+
+    let output = repo
+        .git_ai_with_env(
+            &["config", "get", "otel_endpoint", "--show-origin"],
+            &[system_env, ("GIT_AI_OTEL_ENDPOINT", "http://env:4317")],
+        )
+        .unwrap();
+    assert!(
+        output.contains("env:GIT_AI_OTEL_ENDPOINT\thttp://env:4317"),
+        "{}",
+        output
+    );
+
+    // Every layer's entries, in the order they're applied
+    let output = repo
+        .git_ai_with_env(&["config", "list", "--show-origin"], &[system_env])
+        .unwrap();
+    let system_line = format!(
+        "file:{}\totel_endpoint=http://system:4317",
+        system.display()
+    );
+    let repo_line = format!(
+        "file:{}\totel_endpoint=http://repo:4317",
+        repo_file.display()
+    );
+    let system_at = output.find(&system_line).expect(&output);
+    let repo_at = output.find(&repo_line).expect(&output);
+    assert!(system_at < repo_at, "{}", output);
+    assert!(output.contains("otel_traces_enabled=true"), "{}", output);
+
+    repo.git_ai(&["config", "unset", "otel_endpoint", "--repo"])
+        .unwrap();
+    let output = repo
+        .git_ai_with_env(
+            &["config", "get", "otel_endpoint", "--show-origin"],
+            &[system_env],
+        )
+        .unwrap();
+    assert!(output.contains("\thttp://system:4317"), "{}", output);
+}
+
+#[test]
+fn test_invalid_values_are_reported_with_fixes() {
+    let repo = TestRepo::new();
+
+    let err = repo
+        .git_ai(&["config", "set", "background_nice", "42", "--repo"])
+        .unwrap_err();
+    assert!(
+        err.contains("background_nice: must be between 0 and 19, got 42"),
+        "{}",
+        err
+    );
+    let err = repo
+        .git_ai(&["config", "set", "otel_endpiont", "x", "--repo"])
+        .unwrap_err();
+    assert!(err.contains("did you mean 'otel_endpoint'?"), "{}", err);
+
+    fs::write(
+        repo.path().join(".git/git-ai.toml"),
+        "sign_notes = \"yes\"\nverify_notes = \"strict\"\n",
+    )
+    .unwrap();
+    let err = repo.git_ai(&["config", "validate"]).unwrap_err();
+    assert!(
+        err.contains("git-ai.toml: sign_notes: expected true or false, got string \"yes\""),
+        "{}",
+        err
+    );
+    assert!(!err.contains("verify_notes"), "{}", err);
+}
@@ -0,0 +1,150 @@
+#[macro_use]
+mod repos;
+use repos::test_repo::TestRepo;
+use std::fs;
+
+struct RunningDaemon<'a> {
+    repo: &'a TestRepo,
+    socket: String,
+}
+
+impl<'a> RunningDaemon<'a> {
+    fn start(repo: &'a TestRepo, extra_args: &[&str]) -> Self {
+        let socket = std::env::temp_dir()
+            .join(format!(
+                "git-ai-daemon-{}.sock",
+                uuid::Uuid::new_v4().simple()
+            ))
+            .to_string_lossy()
+            .to_string();
+        let mut args = vec!["daemon", "start", "--socket", socket.as_str()];
+        args.extend_from_slice(extra_args);
+        repo.git_ai(&args).expect("daemon should start");
+        RunningDaemon { repo, socket }
+    }
+
+    fn call(&self, method: &str, params: &str) -> Result<String, String> {
+        self.repo
+            .git_ai(&["daemon", "call", method, params, "--socket", &self.socket])
+    }
+}
+
+impl Drop for RunningDaemon<'_> {
+    fn drop(&mut self) {
+        let _ = self
+            .repo
+            .git_ai(&["daemon", "stop", "--socket", &self.socket]);
+    }
+}
+
+fn ai_checkpoint(repo: &TestRepo, file: &str) -> String {
+    serde_json::json!({
+        "type": "ai_agent",
+        "repo_working_dir": repo.path().to_string_lossy(),
+        "edited_filepaths": [file],
+        "transcript": {"messages": []},
+        "agent_name": "zed",
+        "model": "claude-sonnet-4",
+        "conversation_id": "daemon-session",
+    })
+    .to_string()
+}
+
+#[test]
+fn test_daemon_checkpoints_land_in_authorship_note() {
+    let repo = TestRepo::new();
+    fs::write(repo.path().join("README.md"), "base\n").unwrap();
+    repo.stage_all_and_commit("base").unwrap();
+
+    let daemon = RunningDaemon::start(&repo, &[]);
+    fs::write(repo.path().join("README.md"), "base\nfrom the editor\n").unwrap();
+    let queued = daemon
+        .call("checkpoint", &ai_checkpoint(&repo, "README.md"))
+        .expect("checkpoint should be accepted");
+    assert!(queued.contains("\"pending\""), "{}", queued);
+    daemon.call("flush", "null").expect("flush should succeed");
+
+    let status = daemon.call("status", "null").unwrap();
+    let status: serde_json::Value = serde_json::from_str(&status).unwrap();
+    assert_eq!(status["pending"], 0);
+    assert_eq!(status["processed"], 1);
+
+    let commit = repo.stage_all_and_commit("editor change").unwrap();
+    let tools: Vec<(String, String)> = commit
+        .authorship_log
+        .metadata
+        .prompts
+        .values()
+        .map(|prompt| (prompt.agent_id.tool.clone(), prompt.agent_id.model.clone()))
+        .collect();
+    assert_eq!(
+        tools,
+        vec![("zed".to_string(), "claude-sonnet-4".to_string())]
+    );
+}
+
+#[test]
+fn test_daemon_rejects_checkpoints_for_unwatched_repositories() {
+    let repo = TestRepo::new();
+    let other = TestRepo::new();
+    let watched = repo.path().to_string_lossy().to_string();
+    let daemon = RunningDaemon::start(&repo, &["--repo", &watched]);
+
+    let err = daemon
+        .call("checkpoint", &ai_checkpoint(&other, "README.md"))
+        .expect_err("unwatched repository should be rejected");
+    assert!(
+        err.contains("not a repository this daemon watches"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_daemon_reports_staged_composition() {
+    let repo = TestRepo::new();
+    fs::write(repo.path().join("README.md"), "base\n").unwrap();
+    repo.stage_all_and_commit("base").unwrap();
+
+    let daemon = RunningDaemon::start(&repo, &[]);
+    let params = serde_json::json!({ "repo": repo.path().to_string_lossy() }).to_string();
+    let staged = || -> serde_json::Value {
+        serde_json::from_str(&daemon.call("staged", &params).unwrap()).unwrap()
+    };
+
+    fs::write(
+        repo.path().join("README.md"),
+        "base\nfrom the editor\nalso from the editor\n",
+    )
+    .unwrap();
+    daemon
+        .call("checkpoint", &ai_checkpoint(&repo, "README.md"))
+        .unwrap();
+    repo.git_og(&["add", "-A"]).unwrap();
+
+    // The queued checkpoint is run before answering, without an explicit flush
+    let composition = staged();
+    assert_eq!(composition["added_lines"], 2);
+    assert_eq!(composition["ai_lines"], 2);
+    assert_eq!(composition["agents"][0]["tool"], "zed");
+    assert_eq!(
+        composition["files"][0]["hunks"],
+        serde_json::json!([{
+            "start_line": 2,
+            "end_line": 3,
+            "prompt": composition["agents"][0]["prompt"],
+            "tool": "zed",
+            "model": "claude-sonnet-4",
+        }])
+    );
+
+    fs::write(repo.path().join("notes.txt"), "typed by hand\n").unwrap();
+    repo.git_og(&["add", "-A"]).unwrap();
+
+    let composition = staged();
+    assert_eq!(composition["added_lines"], 3);
+    assert_eq!(composition["ai_lines"], 2);
+    assert_eq!(composition["human_lines"], 1);
+    assert_eq!(composition["files"][1]["path"], "notes.txt");
+    assert_eq!(composition["files"][1]["human_lines"], 1);
+}
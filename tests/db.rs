@@ -0,0 +1,32 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+#[test]
+fn test_checkpoints_are_mirrored_into_local_store() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("notes.txt");
+    file.set_contents(lines!["human line", "ai line".ai()]);
+
+    let output = repo.git_ai(&["db", "checkpoints", "--json"]).unwrap();
+    let checkpoints: Vec<serde_json::Value> = output
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let ai_checkpoint = checkpoints
+        .iter()
+        .find(|c| c["tool"] == "mock_ai")
+        .unwrap_or_else(|| panic!("no mock_ai checkpoint in {}", output));
+    assert_eq!(ai_checkpoint["kind"], "ai_agent");
+    assert_eq!(ai_checkpoint["files"], serde_json::json!(["notes.txt"]));
+
+    let events = repo.git_ai(&["db", "events"]).unwrap();
+    assert!(!events.trim().is_empty(), "expected stored metric events");
+
+    repo.git_ai(&["db", "vacuum", "--older-than", "30"])
+        .unwrap();
+    let info: serde_json::Value =
+        serde_json::from_str(&repo.git_ai(&["db", "info", "--json"]).unwrap()).unwrap();
+    assert_eq!(info["checkpoints"], checkpoints.len());
+}
@@ -794,3 +794,109 @@ fn test_diff_range_multiple_commits() {
         "Should have attribution markers"
     );
 }
+
+#[test]
+fn test_diff_working_tree_uses_checkpoints() {
+    let repo = TestRepo::new();
+
+    let mut file = repo.filename("work.rs");
+    file.set_contents(lines!["fn old() {}".human()]);
+    repo.stage_all_and_commit("Initial").unwrap();
+
+    // Uncommitted edits, one of them in a new untracked file
+    file.set_contents(lines![
+        "fn old() {}".human(),
+        "fn generated() {}".ai(),
+        "fn typed() {}".human()
+    ]);
+    let mut new_file = repo.filename("new.rs");
+    new_file.set_contents(lines!["fn fresh() {}".ai()]);
+    repo.git_og(&["reset", "--quiet"]).unwrap();
+
+    let output = repo
+        .git_ai(&["diff", "--working"])
+        .expect("git-ai diff --working should succeed");
+    let lines = parse_diff_output(&output);
+
+    assert_diff_lines_exact(
+        &lines,
+        &[
+            ("+", "fn fresh()", Some("ai:mock_ai")),
+            // The committed file had no trailing newline, so its line is rewritten
+            ("-", "fn old()", None),
+            ("+", "fn old()", Some("human")),
+            ("+", "fn generated()", Some("ai:mock_ai")),
+            ("+", "fn typed()", Some("human")),
+        ],
+    );
+
+    // The index was left alone
+    let status = repo.git_og(&["status", "--porcelain"]).unwrap();
+    assert!(status.contains(" M work.rs"), "{}", status);
+    assert!(status.contains("?? new.rs"), "{}", status);
+}
+
+#[test]
+fn test_diff_staged_only_shows_index() {
+    let repo = TestRepo::new();
+
+    let mut staged = repo.filename("staged.rs");
+    staged.set_contents(lines!["fn base() {}".human()]);
+    let mut unstaged = repo.filename("unstaged.rs");
+    unstaged.set_contents(lines!["fn base() {}".human()]);
+    repo.stage_all_and_commit("Initial").unwrap();
+
+    staged.set_contents(lines!["fn base() {}".human(), "fn staged_ai() {}".ai()]);
+    unstaged.set_contents(lines!["fn base() {}".human(), "fn unstaged_ai() {}".ai()]);
+    repo.git_og(&["reset", "--quiet", "unstaged.rs"]).unwrap();
+
+    let output = repo
+        .git_ai(&["diff", "--staged"])
+        .expect("git-ai diff --staged should succeed");
+
+    assert!(output.contains("staged.rs"), "{}", output);
+    assert!(!output.contains("unstaged"), "{}", output);
+    let lines = parse_diff_output(&output);
+    assert_diff_lines_exact(
+        &lines,
+        &[
+            ("-", "fn base()", None),
+            ("+", "fn base()", Some("human")),
+            ("+", "fn staged_ai()", Some("ai:mock_ai")),
+        ],
+    );
+}
+
+#[test]
+fn test_diff_working_tree_json_annotations() {
+    let repo = TestRepo::new();
+
+    let mut file = repo.filename("lib.rs");
+    file.set_contents(lines!["fn base() {}".human()]);
+    repo.stage_all_and_commit("Initial").unwrap();
+
+    file.set_contents(lines![
+        "fn base() {}".human(),
+        "fn one() {}".ai(),
+        "fn two() {}".ai()
+    ]);
+
+    let output = repo
+        .git_ai(&["diff", "--working", "--json"])
+        .expect("git-ai diff --working --json should succeed");
+    let line = output
+        .lines()
+        .find(|line| line.starts_with('{'))
+        .expect("diff --json prints a JSON object");
+    let json: serde_json::Value = serde_json::from_str(line).expect("Output should be valid JSON");
+
+    let annotations = json["files"]["lib.rs"]["annotations"].as_object().unwrap();
+    assert_eq!(annotations.len(), 1, "{}", output);
+    let (hash, ranges) = annotations.iter().next().unwrap();
+    assert_eq!(ranges, &serde_json::json!([[2, 3]]));
+    assert_eq!(json["prompts"][hash]["agent_id"]["tool"], "mock_ai");
+    assert_eq!(
+        json["files"]["lib.rs"]["base_content"].as_str().unwrap(),
+        "fn base() {}"
+    );
+}
@@ -0,0 +1,78 @@
+#[macro_use]
+mod repos;
+
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+#[test]
+fn test_export_csv_tables() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("app.rs");
+
+    file.set_contents(lines!["fn main() {}"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+    file.insert_at(1, lines!["// AI line 1".ai(), "// AI line 2".ai()]);
+    let ai_commit = repo.stage_all_and_commit("AI commit").unwrap();
+
+    let out = repo.path().with_extension("export");
+    let stdout = repo
+        .git_ai(&["export", "--output", out.to_str().unwrap()])
+        .expect("export should succeed");
+    assert!(stdout.contains("commits.csv"), "{}", stdout);
+
+    let commits = std::fs::read_to_string(out.join("commits.csv")).unwrap();
+    let mut lines = commits.lines();
+    assert_eq!(
+        lines.next().unwrap(),
+        "commit_sha,author_name,author_email,committed_at,lines_added,lines_deleted,ai_lines,\
+         human_lines,has_authorship_note,schema_version,git_ai_version"
+    );
+    let ai_row = lines
+        .find(|l| l.starts_with(&ai_commit.commit_sha))
+        .expect("AI commit should be exported");
+    let fields: Vec<&str> = ai_row.split(',').collect();
+    // The base line gains a trailing newline, so git counts it as rewritten by a human
+    assert_eq!(fields[4..9], ["3", "1", "2", "1", "true"]);
+
+    let attributions = std::fs::read_to_string(out.join("attributions.csv")).unwrap();
+    assert!(
+        attributions.contains(&format!("{},app.rs,", ai_commit.commit_sha)),
+        "{}",
+        attributions
+    );
+    assert!(attributions.lines().nth(1).unwrap().ends_with(",2,3,2"));
+
+    for table in ["partial_lines", "prompts", "metric_events"] {
+        assert!(out.join(format!("{}.csv", table)).is_file(), "{}", table);
+    }
+}
+
+#[test]
+fn test_export_parquet_range() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("lib.rs");
+
+    file.set_contents(lines!["base"]);
+    let base = repo.stage_all_and_commit("Initial commit").unwrap();
+    file.insert_at(1, lines!["ai".ai()]);
+    repo.stage_all_and_commit("AI commit").unwrap();
+
+    let out = repo.path().with_extension("export");
+    let range = format!("{}..HEAD", base.commit_sha);
+    repo.git_ai(&[
+        "export",
+        &range,
+        "--format",
+        "parquet",
+        "--no-metrics",
+        "-o",
+        out.to_str().unwrap(),
+    ])
+    .expect("export should succeed");
+
+    let bytes = std::fs::read(out.join("commits.parquet")).unwrap();
+    assert_eq!(&bytes[..4], b"PAR1");
+    assert_eq!(&bytes[bytes.len() - 4..], b"PAR1");
+    assert!(!out.join("metric_events.parquet").exists());
+    assert!(!out.join("commits.csv").exists());
+}
@@ -0,0 +1,73 @@
+use super::github_test_harness::GitHubTestRepo;
+use crate::lines;
+use crate::repos::test_file::ExpectedLineExt;
+
+#[test]
+#[ignore] // Ignored by default - run with `cargo test --test github_integration -- --ignored`
+fn test_annotate_pr_comments_on_ai_hunks() {
+    let test_repo = match GitHubTestRepo::new("test_annotate_pr_comments_on_ai_hunks") {
+        Some(repo) => repo,
+        None => {
+            println!("⏭️  Test skipped - GitHub CLI not available");
+            return;
+        }
+    };
+
+    if let Err(e) = test_repo.create_on_github() {
+        panic!("Failed to create GitHub repository: {}", e);
+    }
+
+    test_repo
+        .create_branch("feature/annotate")
+        .expect("Failed to create feature branch");
+
+    let mut test_file = test_repo.repo.filename("main.rs");
+    test_file.set_contents(lines![
+        "fn main() {",
+        "    println!(\"Hello\");".ai(),
+        "    println!(\"from git-ai\");".ai(),
+        "}",
+    ]);
+    test_repo
+        .repo
+        .stage_all_and_commit("AI greeting")
+        .expect("Failed to create commit");
+
+    test_repo
+        .push_branch("feature/annotate")
+        .expect("Failed to push branch");
+    let pr_url = test_repo
+        .create_pr("Annotate test", "AI hunks should get review comments")
+        .expect("Failed to create PR");
+    let pr_number = test_repo
+        .extract_pr_number(&pr_url)
+        .expect("Failed to extract PR number");
+
+    let token = test_repo.github_token().expect("Failed to get gh token");
+    let output = test_repo
+        .repo
+        .git_ai_with_env(&["annotate-pr", &pr_number], &[("GITHUB_TOKEN", &token)])
+        .expect("annotate-pr should succeed");
+    assert!(
+        output.contains("Annotated 1 AI-authored hunk(s)"),
+        "{}",
+        output
+    );
+
+    let comments = test_repo
+        .get_pr_review_comments(&pr_number)
+        .expect("Failed to list review comments");
+    assert_eq!(comments.len(), 1, "{:?}", comments);
+    assert!(
+        comments[0].contains("2 AI-generated lines"),
+        "{:?}",
+        comments
+    );
+
+    // A second run finds the comment it already posted
+    let output = test_repo
+        .repo
+        .git_ai_with_env(&["annotate-pr", &pr_number], &[("GITHUB_TOKEN", &token)])
+        .expect("second annotate-pr should succeed");
+    assert!(output.contains("1 already annotated"), "{}", output);
+}
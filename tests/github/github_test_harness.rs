@@ -383,6 +383,39 @@ impl GitHubTestRepo {
         Ok(())
     }
 
+    /// Token of the authenticated gh CLI user, for git-ai commands that call the GitHub API
+    pub fn github_token(&self) -> Result<String, String> {
+        let output = Command::new("gh")
+            .args(&["auth", "token"])
+            .output()
+            .map_err(|e| format!("Failed to execute gh auth token: {}", e))?;
+        if !output.status.success() {
+            return Err(String::from_utf8_lossy(&output.stderr).to_string());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// Bodies of the review comments on a pull request
+    pub fn get_pr_review_comments(&self, pr_number: &str) -> Result<Vec<String>, String> {
+        let full_repo = format!("{}/{}", self.github_owner, self.github_repo_name);
+        let output = Command::new("gh")
+            .args(&[
+                "api",
+                &format!("repos/{}/pulls/{}/comments", full_repo, pr_number),
+                "--jq",
+                ".[].body | @json",
+            ])
+            .output()
+            .map_err(|e| format!("Failed to list review comments: {}", e))?;
+        if !output.status.success() {
+            return Err(String::from_utf8_lossy(&output.stderr).to_string());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| serde_json::from_str::<String>(line).ok())
+            .collect())
+    }
+
     /// Delete the GitHub repository
     pub fn delete_from_github(&self) -> Result<(), String> {
         let full_repo = format!("{}/{}", self.github_owner, self.github_repo_name);
@@ -1,3 +1,4 @@
+pub mod annotate_pr_test;
 pub mod basic_pr_test;
 pub mod deleted_branch_pr_test;
 pub mod github_test_harness;
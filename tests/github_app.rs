@@ -0,0 +1,68 @@
+mod repos;
+
+use git_ai::github_app::ingest::ingest_push;
+use git_ai::github_app::{GithubAppConfig, PushEvent};
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+fn push_event(branch: &str, before: &str, after: &str) -> PushEvent {
+    let payload = serde_json::json!({
+        "ref": format!("refs/heads/{}", branch),
+        "before": before,
+        "after": after,
+        "repository": {
+            "full_name": "org/repo",
+            // Ignored: mirrors fetch from the configured host
+            "clone_url": "https://evil.example/org/repo.git",
+        },
+        "commits": [{"id": after}],
+    });
+    PushEvent::parse(payload.to_string().as_bytes()).unwrap()
+}
+
+#[test]
+fn test_ingest_waits_for_notes_and_records_each_commit_once() {
+    let local = TestRepo::new();
+    let data_dir = tempfile::tempdir().unwrap();
+    let host = tempfile::tempdir().unwrap();
+    let upstream = host.path().join("org").join("repo.git");
+    std::fs::create_dir_all(&upstream).unwrap();
+    local
+        .git_og(&["init", "--bare", "--quiet", upstream.to_str().unwrap()])
+        .unwrap();
+    local
+        .git_og(&["remote", "add", "origin", upstream.to_str().unwrap()])
+        .unwrap();
+    let mut config = GithubAppConfig::from_env();
+    config.data_dir = data_dir.path().to_path_buf();
+    config.git_url = host.path().to_string_lossy().to_string();
+
+    let mut file = local.filename("lib.rs");
+    file.set_contents(vec!["human line".human(), "AI line".ai()]);
+    let sha = local.stage_all_and_commit("AI commit").unwrap().commit_sha;
+    let branch = local.current_branch();
+
+    // The branch arrives before its note
+    local.git_og(&["push", "origin", &branch]).unwrap();
+    let event = push_event(&branch, &"0".repeat(40), &sha);
+    let report = ingest_push(&config, &event, None).unwrap();
+    assert!(!report.notes_found);
+    assert!(report.recorded.is_empty());
+    assert_eq!(report.pending, vec![sha.clone()]);
+
+    // A later push brings the notes
+    local
+        .git_og(&["push", "origin", "refs/notes/ai:refs/notes/ai"])
+        .unwrap();
+    let event = push_event(&branch, &sha, &sha);
+    let report = ingest_push(&config, &event, None).unwrap();
+    assert!(report.notes_found);
+    assert_eq!(report.recorded, vec![sha.clone()]);
+    assert!(report.pending.is_empty());
+    assert_eq!(report.events.len(), 1);
+
+    // Redelivery of the same push records nothing new
+    let report = ingest_push(&config, &event, None).unwrap();
+    assert!(report.recorded.is_empty());
+    assert!(report.events.is_empty());
+}
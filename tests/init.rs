@@ -0,0 +1,81 @@
+#[macro_use]
+mod repos;
+
+use repos::test_repo::TestRepo;
+use std::path::{Path, PathBuf};
+
+/// A fresh HOME so the wizard's ~/.git-ai/config.json doesn't touch the real one
+fn temp_home(repo: &TestRepo) -> PathBuf {
+    let home = repo.path().with_extension("home");
+    std::fs::create_dir_all(&home).unwrap();
+    home
+}
+
+fn read_config(home: &Path) -> Option<serde_json::Value> {
+    let data = std::fs::read_to_string(home.join(".git-ai").join("config.json")).ok()?;
+    serde_json::from_str(&data).ok()
+}
+
+#[test]
+fn test_init_non_interactive_writes_repo_and_user_config() {
+    let repo = TestRepo::new();
+    let home = temp_home(&repo);
+    let args = [
+        "init",
+        "--yes",
+        "--no-agents",
+        "--notes-ref",
+        "ai",
+        "--otel-endpoint",
+        "http://collector:4317",
+        "--prometheus",
+    ];
+
+    repo.git_ai_with_env(&args, &[("HOME", home.to_str().unwrap())])
+        .expect("init should succeed");
+
+    assert!(repo.path().join(".git/hooks/post-rewrite").is_file());
+    let display_refs = repo
+        .git_og(&["config", "--get-all", "notes.displayRef"])
+        .unwrap();
+    assert_eq!(display_refs.trim(), "refs/notes/ai");
+
+    let config = read_config(&home).expect("config.json should be written");
+    assert_eq!(config["otel_enabled"], true);
+    assert_eq!(config["otel_endpoint"], "http://collector:4317");
+    assert_eq!(config["prometheus_enabled"], true);
+
+    // Running it again doesn't stack up duplicate settings
+    repo.git_ai_with_env(&args, &[("HOME", home.to_str().unwrap())])
+        .expect("second init should succeed");
+    let display_refs = repo
+        .git_og(&["config", "--get-all", "notes.displayRef"])
+        .unwrap();
+    assert_eq!(display_refs.trim(), "refs/notes/ai");
+}
+
+#[test]
+fn test_init_dry_run_changes_nothing() {
+    let repo = TestRepo::new();
+    let home = temp_home(&repo);
+
+    let output = repo
+        .git_ai_with_env(
+            &[
+                "init",
+                "--yes",
+                "--dry-run",
+                "--no-agents",
+                "--notes-ref",
+                "commits",
+                "--prometheus",
+            ],
+            &[("HOME", home.to_str().unwrap())],
+        )
+        .expect("init should succeed");
+
+    assert!(output.contains("Would install post-rewrite"), "{}", output);
+    assert!(output.contains("mirror_summary_notes: true"), "{}", output);
+    assert!(!repo.path().join(".git/hooks/post-rewrite").exists());
+    assert!(read_config(&home).is_none());
+}
@@ -64,6 +64,8 @@ fn test_initial_only_no_blame_data() {
             accepted_lines: 0,
             overriden_lines: 0,
             messages_url: None,
+            token_usage: None,
+            turns: Vec::new(),
         },
     );
 
@@ -153,6 +155,8 @@ fn test_initial_wins_overlaps() {
             accepted_lines: 0,
             overriden_lines: 0,
             messages_url: None,
+            token_usage: None,
+            turns: Vec::new(),
         },
     );
 
@@ -229,6 +233,8 @@ fn test_initial_and_blame_merge() {
             accepted_lines: 0,
             overriden_lines: 0,
             messages_url: None,
+            token_usage: None,
+            turns: Vec::new(),
         },
     );
     prompts.insert(
@@ -246,6 +252,8 @@ fn test_initial_and_blame_merge() {
             accepted_lines: 0,
             overriden_lines: 0,
             messages_url: None,
+            token_usage: None,
+            turns: Vec::new(),
         },
     );
 
@@ -317,6 +325,8 @@ fn test_partial_file_coverage() {
             accepted_lines: 0,
             overriden_lines: 0,
             messages_url: None,
+            token_usage: None,
+            turns: Vec::new(),
         },
     );
 
@@ -406,6 +416,8 @@ fn test_initial_attributions_in_subsequent_checkpoint() {
             accepted_lines: 0,
             overriden_lines: 0,
             messages_url: None,
+            token_usage: None,
+            turns: Vec::new(),
         },
     );
 
@@ -0,0 +1,32 @@
+#![cfg(feature = "libgit2")]
+
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+const LIBGIT2: [(&str, &str); 1] = [("GIT_AI_NOTES_TRANSPORT", "libgit2")];
+
+#[test]
+fn test_libgit2_transport_pushes_and_fetches_notes() {
+    let (local, upstream) = TestRepo::new_with_remote();
+    let mut file = local.filename("lib.rs");
+    file.set_contents(lines!["fn a() {}".ai()]);
+    local.stage_all_and_commit("AI lib").unwrap();
+    local.git(&["push", "-u", "origin", "HEAD"]).unwrap();
+
+    local.git_ai_with_env(&["sync"], &LIBGIT2).unwrap();
+    let head = local.git(&["rev-parse", "HEAD"]).unwrap();
+    let note = upstream
+        .git(&["notes", "--ref=ai", "show", head.trim()])
+        .unwrap();
+    assert!(note.contains("lib.rs"), "{}", note);
+
+    // Losing the local notes ref, sync fetches it back from the remote
+    local.git(&["update-ref", "-d", "refs/notes/ai"]).unwrap();
+    local.git_ai_with_env(&["sync"], &LIBGIT2).unwrap();
+    let note = local
+        .git(&["notes", "--ref=ai", "show", head.trim()])
+        .unwrap();
+    assert!(note.contains("lib.rs"), "{}", note);
+}
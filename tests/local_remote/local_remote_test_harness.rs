@@ -0,0 +1,79 @@
+use crate::lines;
+use crate::repos::test_file::ExpectedLineExt;
+use crate::repos::test_repo::TestRepo;
+use git_ai::git::test_utils::local_remote::{LOCAL_REMOTE_DEFAULT_BRANCH, LocalRemote};
+
+/// Local counterpart to `GitHubTestRepo`: a working clone of a [`LocalRemote`]. Every command on
+/// `repo` goes through the git-ai wrapper, so pushes and fetches run the same hooks they do
+/// against a hosted remote, without the GitHub CLI, network access or credentials.
+pub struct LocalRemoteTestRepo {
+    pub repo: TestRepo,
+    /// Bare repository behind `origin`
+    pub upstream: LocalRemote,
+}
+
+impl LocalRemoteTestRepo {
+    /// Clone a new bare upstream and push an initial commit to its default branch
+    pub fn new() -> Self {
+        let upstream = LocalRemote::new().expect("Failed to create upstream");
+        let repo = clone_of(&upstream);
+        std::fs::write(
+            repo.path().join("README.md"),
+            "# Local remote test repository\n",
+        )
+        .expect("Failed to create README");
+        repo.git_og(&["add", "."]).expect("Failed to add files");
+        repo.git_og(&["commit", "-m", "Initial commit"])
+            .expect("Failed to create initial commit");
+        repo.git_og(&[
+            "push",
+            "--quiet",
+            "--set-upstream",
+            "origin",
+            LOCAL_REMOTE_DEFAULT_BRANCH,
+        ])
+        .expect("Failed to push initial commit");
+        Self { repo, upstream }
+    }
+
+    /// Another working clone of the upstream, e.g. a teammate whose pushes leave `repo` behind
+    pub fn clone_upstream(&self) -> TestRepo {
+        clone_of(&self.upstream)
+    }
+
+    /// Fork the upstream and add the fork to `repo` as `name`
+    pub fn add_fork(&self, name: &str) -> LocalRemote {
+        let fork = self.upstream.fork().expect("Failed to fork upstream");
+        self.repo
+            .git_og(&["remote", "add", name, fork.url().as_str()])
+            .expect("Failed to add fork remote");
+        fork
+    }
+
+    pub fn create_branch(&self, branch_name: &str) -> Result<(), String> {
+        self.repo.git(&["checkout", "-b", branch_name]).map(|_| ())
+    }
+
+    /// Commit a new file with one AI-authored line, returning the commit SHA
+    pub fn commit_ai_file(&self, filename: &str, line: &str) -> String {
+        let mut file = self.repo.filename(filename);
+        file.set_contents(lines![line.ai()]);
+        self.repo
+            .stage_all_and_commit(&format!("AI adds {}", filename))
+            .expect("Failed to commit")
+            .commit_sha
+    }
+
+    /// Push through the git-ai wrapper
+    pub fn push(&self, args: &[&str]) -> Result<String, String> {
+        let mut full_args = vec!["push"];
+        full_args.extend(args);
+        self.repo.git(&full_args)
+    }
+}
+
+/// A `TestRepo` over a fresh clone of `remote`, so its commands run through the wrapper
+fn clone_of(remote: &LocalRemote) -> TestRepo {
+    let clone = remote.clone_repo().expect("Failed to clone upstream");
+    TestRepo::new_at_path(&clone.path().to_path_buf())
+}
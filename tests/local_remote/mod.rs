@@ -0,0 +1,2 @@
+pub mod local_remote_test_harness;
+pub mod refspec_push_test;
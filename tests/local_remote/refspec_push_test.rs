@@ -0,0 +1,166 @@
+use super::local_remote_test_harness::LocalRemoteTestRepo;
+use crate::lines;
+use crate::repos::test_file::ExpectedLineExt;
+use git_ai::git::test_utils::local_remote::LOCAL_REMOTE_DEFAULT_BRANCH;
+
+#[test]
+fn test_push_when_ahead_sends_notes() {
+    let test_repo = LocalRemoteTestRepo::new();
+    let main = LOCAL_REMOTE_DEFAULT_BRANCH;
+    let sha = test_repo.commit_ai_file("ahead.rs", "fn ahead() {}");
+
+    test_repo.push(&["origin", main]).unwrap();
+
+    assert_eq!(
+        test_repo
+            .upstream
+            .ref_target(&format!("refs/heads/{}", main))
+            .as_deref(),
+        Some(sha.as_str())
+    );
+    assert!(test_repo.upstream.note(&sha).is_some());
+}
+
+#[test]
+fn test_force_push_sends_notes_for_rewritten_commits() {
+    let test_repo = LocalRemoteTestRepo::new();
+    let main = LOCAL_REMOTE_DEFAULT_BRANCH;
+    let original = test_repo.commit_ai_file("force.rs", "fn force() {}");
+    test_repo.push(&["origin", main]).unwrap();
+
+    test_repo
+        .repo
+        .git(&["commit", "--amend", "-m", "Reworded"])
+        .unwrap();
+    let amended = test_repo
+        .repo
+        .git_og(&["rev-parse", "HEAD"])
+        .unwrap()
+        .trim()
+        .to_string();
+    assert_ne!(amended, original);
+    assert!(test_repo.push(&["origin", main]).is_err());
+    test_repo.push(&["--force", "origin", main]).unwrap();
+
+    assert_eq!(
+        test_repo
+            .upstream
+            .ref_target(&format!("refs/heads/{}", main))
+            .as_deref(),
+        Some(amended.as_str())
+    );
+    assert!(test_repo.upstream.note(&amended).is_some());
+
+    // A `+` refspec forces the same way
+    test_repo
+        .repo
+        .git(&["commit", "--amend", "-m", "Reworded again"])
+        .unwrap();
+    let again = test_repo
+        .repo
+        .git_og(&["rev-parse", "HEAD"])
+        .unwrap()
+        .trim()
+        .to_string();
+    test_repo
+        .push(&["origin", &format!("+HEAD:refs/heads/{}", main)])
+        .unwrap();
+    assert!(test_repo.upstream.note(&again).is_some());
+}
+
+#[test]
+fn test_push_when_behind_keeps_remote_notes() {
+    let test_repo = LocalRemoteTestRepo::new();
+    let main = LOCAL_REMOTE_DEFAULT_BRANCH;
+
+    let teammate = test_repo.clone_upstream();
+    let mut file = teammate.filename("teammate.rs");
+    file.set_contents(lines!["fn teammate() {}".ai()]);
+    let theirs = teammate
+        .stage_all_and_commit("Teammate work")
+        .unwrap()
+        .commit_sha;
+    teammate.git(&["push", "origin", main]).unwrap();
+    assert!(test_repo.upstream.note(&theirs).is_some());
+
+    test_repo.commit_ai_file("mine.rs", "fn mine() {}");
+    // Behind the remote: git rejects the branch, and the remote's notes must survive
+    assert!(test_repo.push(&["origin", main]).is_err());
+    assert!(test_repo.upstream.note(&theirs).is_some());
+
+    test_repo
+        .repo
+        .git(&["pull", "--rebase", "origin", main])
+        .unwrap();
+    let rebased = test_repo
+        .repo
+        .git_og(&["rev-parse", "HEAD"])
+        .unwrap()
+        .trim()
+        .to_string();
+    test_repo.push(&["origin", main]).unwrap();
+
+    assert!(test_repo.upstream.note(&theirs).is_some());
+    assert!(test_repo.upstream.note(&rebased).is_some());
+}
+
+#[test]
+fn test_push_to_fork_sends_notes_to_the_fork_only() {
+    let test_repo = LocalRemoteTestRepo::new();
+    let fork = test_repo.add_fork("fork");
+    test_repo.create_branch("feature").unwrap();
+    let sha = test_repo.commit_ai_file("feature.rs", "fn feature() {}");
+
+    test_repo
+        .push(&["--set-upstream", "fork", "feature"])
+        .unwrap();
+
+    assert!(fork.note(&sha).is_some());
+    assert!(test_repo.upstream.note(&sha).is_none());
+
+    // A bare `git push` follows the branch's upstream, which is the fork
+    let next = test_repo.commit_ai_file("feature_two.rs", "fn feature_two() {}");
+    test_repo.push(&[]).unwrap();
+    assert!(fork.note(&next).is_some());
+    assert!(test_repo.upstream.note(&next).is_none());
+}
+
+#[test]
+fn test_push_with_multiple_refspecs_sends_notes_for_each() {
+    let test_repo = LocalRemoteTestRepo::new();
+    let main = LOCAL_REMOTE_DEFAULT_BRANCH;
+    let on_main = test_repo.commit_ai_file("main.rs", "fn on_main() {}");
+    test_repo.create_branch("feature").unwrap();
+    let on_feature = test_repo.commit_ai_file("feature.rs", "fn on_feature() {}");
+
+    test_repo.push(&["origin", main, "feature"]).unwrap();
+
+    assert_eq!(
+        test_repo
+            .upstream
+            .ref_target("refs/heads/feature")
+            .as_deref(),
+        Some(on_feature.as_str())
+    );
+    assert!(test_repo.upstream.note(&on_main).is_some());
+    assert!(test_repo.upstream.note(&on_feature).is_some());
+
+    // Renaming refspecs, alongside one that already names the notes ref
+    let renamed = test_repo.commit_ai_file("renamed.rs", "fn renamed() {}");
+    test_repo
+        .push(&[
+            "origin",
+            "HEAD:refs/heads/renamed",
+            &format!("{}:refs/heads/release", main),
+            "refs/notes/ai",
+        ])
+        .unwrap();
+    assert_eq!(
+        test_repo
+            .upstream
+            .ref_target("refs/heads/release")
+            .as_deref(),
+        Some(on_main.as_str())
+    );
+    assert!(test_repo.upstream.note(&renamed).is_some());
+}
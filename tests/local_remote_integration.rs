@@ -0,0 +1,4 @@
+mod repos;
+
+#[path = "local_remote/mod.rs"]
+mod local_remote;
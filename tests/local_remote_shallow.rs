@@ -0,0 +1,133 @@
+use git_ai::git::sync_authorship::NotesExistence;
+use git_ai::git::test_utils::local_remote::{LOCAL_REMOTE_DEFAULT_BRANCH, LocalClone, LocalRemote};
+
+const MAIN: &str = LOCAL_REMOTE_DEFAULT_BRANCH;
+
+/// Push `count` noted commits to `remote` and return their shas, oldest first
+fn seed_noted_history(remote: &LocalRemote, count: usize) -> Vec<String> {
+    let alice = remote.clone_repo().unwrap();
+    let shas: Vec<String> = (1..=count)
+        .map(|i| {
+            let sha = alice
+                .commit(
+                    &format!("f{}.txt", i),
+                    &format!("{}\n", i),
+                    &format!("c{}", i),
+                )
+                .unwrap();
+            alice.add_note(&sha, &format!("note {}", i)).unwrap();
+            sha
+        })
+        .collect();
+    alice.push(MAIN).unwrap();
+    alice.push_notes("origin").unwrap();
+    shas
+}
+
+fn notes_commit_count(clone: &LocalClone) -> usize {
+    clone
+        .git(&["rev-list", "--count", "refs/notes/ai"])
+        .unwrap()
+        .trim()
+        .parse()
+        .unwrap()
+}
+
+/// Objects in the notes tip's tree that are not present locally
+fn missing_note_objects(clone: &LocalClone) -> Vec<String> {
+    clone
+        .git(&[
+            "rev-list",
+            "--objects",
+            "--no-walk",
+            "--missing=print",
+            "refs/notes/ai",
+        ])
+        .unwrap()
+        .lines()
+        .filter_map(|line| line.strip_prefix('?').map(str::to_string))
+        .collect()
+}
+
+#[test]
+fn test_shallow_clone_fetches_only_the_notes_tip() {
+    let remote = LocalRemote::new().unwrap();
+    let shas = seed_noted_history(&remote, 5);
+
+    let ci = remote.clone_repo_with(&["--depth=1"]).unwrap();
+    assert!(ci.gitai_repo().is_shallow());
+    assert!(!ci.gitai_repo().is_partial_clone_remote("origin"));
+
+    assert_eq!(ci.fetch_notes("origin").unwrap(), NotesExistence::Found);
+    assert_eq!(ci.note(&shas[4]).as_deref(), Some("note 5"));
+    assert_eq!(notes_commit_count(&ci), 1);
+
+    // Later fetches are incremental and fast-forward from the shallow tip
+    let alice = remote.clone_repo().unwrap();
+    alice.fetch_notes("origin").unwrap();
+    let sha = alice.commit("f6.txt", "6\n", "c6").unwrap();
+    alice.add_note(&sha, "note 6").unwrap();
+    alice.push(MAIN).unwrap();
+    alice.push_notes("origin").unwrap();
+
+    assert_eq!(ci.fetch_notes("origin").unwrap(), NotesExistence::Found);
+    assert_eq!(notes_commit_count(&ci), 2);
+    assert_eq!(ci.note(&sha).as_deref(), Some("note 6"));
+}
+
+#[test]
+fn test_shallow_clone_pushes_notes_without_losing_remote_history() {
+    let remote = LocalRemote::new().unwrap();
+    let shas = seed_noted_history(&remote, 3);
+
+    let ci = remote.clone_repo_with(&["--depth=1"]).unwrap();
+    let sha = ci.commit("ci.txt", "from ci\n", "ci commit").unwrap();
+    ci.add_note(&sha, "ci note").unwrap();
+    ci.push(MAIN).unwrap();
+    ci.push_notes("origin").unwrap();
+
+    assert_eq!(remote.note(&sha).as_deref(), Some("ci note"));
+    assert_eq!(remote.note(&shas[0]).as_deref(), Some("note 1"));
+    assert_eq!(remote.note(&shas[2]).as_deref(), Some("note 3"));
+}
+
+#[test]
+fn test_partial_clone_prefetches_note_blobs_for_local_commits() {
+    let remote = LocalRemote::new().unwrap();
+    let shas = seed_noted_history(&remote, 3);
+
+    // A noted commit on a branch the clone's HEAD doesn't contain
+    let bob = remote.clone_repo().unwrap();
+    bob.fetch_notes("origin").unwrap();
+    bob.git(&["checkout", "--quiet", "-b", "side"]).unwrap();
+    let side = bob.commit("side.txt", "side\n", "side").unwrap();
+    bob.add_note(&side, "side note").unwrap();
+    bob.git(&["push", "--quiet", "origin", "side"]).unwrap();
+    bob.push_notes("origin").unwrap();
+
+    let ci = remote.clone_repo_with(&["--filter=blob:none"]).unwrap();
+    assert!(ci.gitai_repo().is_partial_clone_remote("origin"));
+    assert!(!ci.gitai_repo().is_shallow());
+
+    assert_eq!(ci.fetch_notes("origin").unwrap(), NotesExistence::Found);
+    // Only the side branch's note is left to be fetched lazily
+    assert_eq!(missing_note_objects(&ci).len(), 1);
+    for (i, sha) in shas.iter().enumerate() {
+        assert_eq!(ci.note(sha), Some(format!("note {}", i + 1)));
+    }
+}
+
+#[test]
+fn test_shallow_partial_clone_fetches_only_the_head_note() {
+    let remote = LocalRemote::new().unwrap();
+    let shas = seed_noted_history(&remote, 4);
+
+    let ci = remote
+        .clone_repo_with(&["--depth=1", "--filter=blob:none"])
+        .unwrap();
+    assert_eq!(ci.fetch_notes("origin").unwrap(), NotesExistence::Found);
+    assert_eq!(notes_commit_count(&ci), 1);
+    // The three older commits aren't in the clone, so neither are their notes
+    assert_eq!(missing_note_objects(&ci).len(), 3);
+    assert_eq!(ci.note(&shas[3]).as_deref(), Some("note 4"));
+}
@@ -0,0 +1,430 @@
+use git_ai::authorship::authorship_log_serialization::AuthorshipLog;
+use git_ai::git::note_signing::{SignatureStatus, list_notes, verify_notes};
+use git_ai::git::pending_pushes::{pending_pushes, retry_pending_pushes};
+use git_ai::git::sync_authorship::{NotesExistence, RemoteProvider, sync_notes_with_remotes};
+use git_ai::git::test_utils::local_remote::{LOCAL_REMOTE_DEFAULT_BRANCH, LocalClone, LocalRemote};
+use git_ai::git::test_utils::synthetic_authorship_log;
+
+const MAIN: &str = LOCAL_REMOTE_DEFAULT_BRANCH;
+
+#[test]
+fn test_local_remote_push_when_ahead() {
+    let remote = LocalRemote::new().unwrap();
+    let alice = remote.clone_repo().unwrap();
+
+    let sha = alice.commit("a.txt", "one\n", "first").unwrap();
+    alice.add_note(&sha, "alice note").unwrap();
+    alice.push(MAIN).unwrap();
+    alice.push_notes("origin").unwrap();
+
+    assert_eq!(remote.note(&sha).as_deref(), Some("alice note"));
+}
+
+#[test]
+fn test_local_remote_fetch_when_behind() {
+    let remote = LocalRemote::new().unwrap();
+    let alice = remote.clone_repo().unwrap();
+    let bob = remote.clone_repo().unwrap();
+
+    // Nothing to fetch yet
+    assert_eq!(bob.fetch_notes("origin").unwrap(), NotesExistence::NotFound);
+
+    let sha = alice.commit("a.txt", "one\n", "first").unwrap();
+    alice.add_note(&sha, "alice note").unwrap();
+    alice.push(MAIN).unwrap();
+    alice.push_notes("origin").unwrap();
+
+    assert_eq!(bob.fetch_notes("origin").unwrap(), NotesExistence::Found);
+    assert_eq!(bob.note(&sha).as_deref(), Some("alice note"));
+}
+
+#[test]
+fn test_local_remote_diverged_notes_merge_on_push() {
+    let remote = LocalRemote::new().unwrap();
+    let alice = remote.clone_repo().unwrap();
+    let base = alice.commit("base.txt", "base\n", "base").unwrap();
+    alice.push(MAIN).unwrap();
+
+    let bob = remote.clone_repo().unwrap();
+
+    let alice_sha = alice.commit("a.txt", "alice\n", "alice work").unwrap();
+    alice.add_note(&alice_sha, "alice note").unwrap();
+    alice.add_note(&base, "base note").unwrap();
+    alice.push(MAIN).unwrap();
+    alice.push_notes("origin").unwrap();
+
+    // Bob's notes ref diverged from the remote's; pushing must merge rather than clobber
+    bob.git(&["checkout", "--quiet", "-b", "bob"]).unwrap();
+    let bob_sha = bob.commit("b.txt", "bob\n", "bob work").unwrap();
+    bob.add_note(&bob_sha, "bob note").unwrap();
+    bob.git(&["push", "--quiet", "origin", "bob"]).unwrap();
+    bob.push_notes("origin").unwrap();
+
+    assert_eq!(remote.note(&alice_sha).as_deref(), Some("alice note"));
+    assert_eq!(remote.note(&base).as_deref(), Some("base note"));
+    assert_eq!(remote.note(&bob_sha).as_deref(), Some("bob note"));
+
+    alice.fetch_notes("origin").unwrap();
+    assert_eq!(alice.note(&bob_sha).as_deref(), Some("bob note"));
+}
+
+#[test]
+fn test_local_remote_fork_keeps_notes_separate() {
+    let upstream = LocalRemote::new().unwrap();
+    let maintainer = upstream.clone_repo().unwrap();
+    let base = maintainer.commit("base.txt", "base\n", "base").unwrap();
+    maintainer.add_note(&base, "upstream note").unwrap();
+    maintainer.push(MAIN).unwrap();
+    maintainer.push_notes("origin").unwrap();
+
+    let fork = upstream.fork().unwrap();
+    assert_eq!(fork.note(&base).as_deref(), Some("upstream note"));
+
+    // A contributor works against the fork; upstream must not see their notes until merged
+    let contributor = fork.clone_repo().unwrap();
+    contributor.add_remote("upstream", &upstream).unwrap();
+    let sha = contributor.commit("c.txt", "fork\n", "fork work").unwrap();
+    contributor.add_note(&sha, "fork note").unwrap();
+    contributor.push(MAIN).unwrap();
+    contributor.push_notes("origin").unwrap();
+
+    assert_eq!(fork.note(&sha).as_deref(), Some("fork note"));
+    assert_eq!(upstream.note(&sha), None);
+
+    maintainer.add_remote("fork", &fork).unwrap();
+    assert_eq!(
+        maintainer.fetch_notes("fork").unwrap(),
+        NotesExistence::Found
+    );
+    assert_eq!(maintainer.note(&sha).as_deref(), Some("fork note"));
+    assert_eq!(maintainer.note(&base).as_deref(), Some("upstream note"));
+}
+
+#[test]
+fn test_local_remote_provider_override() {
+    let remote = LocalRemote::new().unwrap();
+    let alice = remote.clone_repo().unwrap();
+
+    // Local paths are generic unless the remote says otherwise
+    assert_eq!(
+        RemoteProvider::for_remote(alice.gitai_repo(), "origin"),
+        RemoteProvider::Generic
+    );
+    alice
+        .git(&["config", "remote.origin.gitaiProvider", "gitlab"])
+        .unwrap();
+    assert_eq!(
+        RemoteProvider::for_remote(alice.gitai_repo(), "origin"),
+        RemoteProvider::GitLab
+    );
+}
+
+#[test]
+fn test_local_remote_bitbucket_fetch_skips_ls_remote() {
+    let remote = LocalRemote::new().unwrap();
+    let alice = remote.clone_repo().unwrap();
+    let bob = remote.clone_repo().unwrap();
+    bob.git(&["config", "remote.origin.gitaiProvider", "bitbucket"])
+        .unwrap();
+
+    // The missing ref is only discovered by the fetch itself
+    assert_eq!(bob.fetch_notes("origin").unwrap(), NotesExistence::NotFound);
+
+    let sha = alice.commit("a.txt", "one\n", "first").unwrap();
+    alice.add_note(&sha, "alice note").unwrap();
+    alice.push(MAIN).unwrap();
+    alice.push_notes("origin").unwrap();
+
+    assert_eq!(bob.fetch_notes("origin").unwrap(), NotesExistence::Found);
+    assert_eq!(bob.note(&sha).as_deref(), Some("alice note"));
+}
+
+#[test]
+fn test_local_remote_concurrent_notes_on_same_commit_are_unioned() {
+    let remote = LocalRemote::new().unwrap();
+    let alice = remote.clone_repo().unwrap();
+    let sha = alice.commit("a.txt", "one\n", "first").unwrap();
+    alice.push(MAIN).unwrap();
+    let bob = remote.clone_repo().unwrap();
+
+    // Both machines attribute different files to the same commit without syncing first
+    let full = synthetic_authorship_log(2, 1);
+    let mut alice_log = full.clone();
+    alice_log.attestations.truncate(1);
+    let mut bob_log = full.clone();
+    bob_log.attestations.remove(0);
+
+    alice
+        .add_note(&sha, &alice_log.serialize_to_string().unwrap())
+        .unwrap();
+    bob.add_note(&sha, &bob_log.serialize_to_string().unwrap())
+        .unwrap();
+    alice.push_notes("origin").unwrap();
+    bob.push_notes("origin").unwrap();
+
+    let attested_files = |note: Option<String>| {
+        let log = AuthorshipLog::deserialize_from_string(&note.unwrap()).unwrap();
+        let mut files: Vec<String> = log.attestations.into_iter().map(|f| f.file_path).collect();
+        files.sort();
+        files
+    };
+    let expected = attested_files(Some(full.serialize_to_string().unwrap()));
+    assert_eq!(attested_files(remote.note(&sha)), expected);
+
+    // Alice picks up Bob's half on her next fetch
+    alice.fetch_notes("origin").unwrap();
+    assert_eq!(attested_files(alice.note(&sha)), expected);
+}
+
+#[test]
+fn test_local_remote_sync_policy_all_pushes_to_every_remote() {
+    let origin = LocalRemote::new().unwrap();
+    let alice = origin.clone_repo().unwrap();
+    let base = alice.commit("base.txt", "base\n", "base").unwrap();
+    alice.push(MAIN).unwrap();
+
+    let fork = origin.fork().unwrap();
+    let mirror = origin.fork().unwrap();
+    alice.add_remote("fork", &fork).unwrap();
+    alice.add_remote("mirror", &mirror).unwrap();
+    alice.add_note(&base, "alice note").unwrap();
+
+    // By default notes only follow the pushed remote
+    assert_eq!(alice.push_notes_per_policy("fork").unwrap(), vec!["fork"]);
+    assert_eq!(origin.note(&base), None);
+
+    alice.git(&["config", "git-ai.syncRemotes", "all"]).unwrap();
+    assert_eq!(
+        alice.push_notes_per_policy("fork").unwrap(),
+        vec!["fork", "mirror", "origin"]
+    );
+    for remote in [&origin, &fork, &mirror] {
+        assert_eq!(remote.note(&base).as_deref(), Some("alice note"));
+    }
+}
+
+#[test]
+fn test_local_remote_sync_policy_upstream_only_skips_forks() {
+    let upstream = LocalRemote::new().unwrap();
+    let maintainer = upstream.clone_repo().unwrap();
+    maintainer.commit("base.txt", "base\n", "base").unwrap();
+    maintainer.push(MAIN).unwrap();
+
+    // Fork workflow: origin is the contributor's fork, upstream the canonical repository
+    let fork = upstream.fork().unwrap();
+    let contributor = fork.clone_repo().unwrap();
+    contributor.add_remote("upstream", &upstream).unwrap();
+    contributor
+        .git(&["config", "git-ai.syncRemotes", "upstream-only"])
+        .unwrap();
+    let sha = contributor.commit("c.txt", "fork\n", "fork work").unwrap();
+    contributor.add_note(&sha, "fork note").unwrap();
+    contributor.push(MAIN).unwrap();
+
+    assert!(
+        contributor
+            .push_notes_per_policy("origin")
+            .unwrap()
+            .is_empty()
+    );
+    assert_eq!(fork.note(&sha), None);
+
+    // An explicit list works the same way
+    contributor
+        .git(&["config", "git-ai.syncRemotes", "upstream"])
+        .unwrap();
+    assert!(
+        contributor
+            .push_notes_per_policy("origin")
+            .unwrap()
+            .is_empty()
+    );
+    assert_eq!(
+        contributor.push_notes_per_policy("upstream").unwrap(),
+        vec!["upstream"]
+    );
+    assert_eq!(upstream.note(&sha).as_deref(), Some("fork note"));
+}
+
+#[test]
+fn test_local_remote_sync_all_remotes_reconciles_notes() {
+    let origin = LocalRemote::new().unwrap();
+    let alice = origin.clone_repo().unwrap();
+    let base = alice.commit("base.txt", "base\n", "base").unwrap();
+    alice.push(MAIN).unwrap();
+    let mirror = origin.fork().unwrap();
+
+    // Each remote learned about a different commit's note
+    let a_sha = alice.commit("a.txt", "a\n", "a").unwrap();
+    alice.add_note(&a_sha, "origin note").unwrap();
+    alice.push(MAIN).unwrap();
+    alice.push_notes("origin").unwrap();
+
+    let mirror_clone = mirror.clone_repo().unwrap();
+    mirror_clone.add_note(&base, "mirror note").unwrap();
+    mirror_clone.push_notes("origin").unwrap();
+
+    let bob = origin.clone_repo().unwrap();
+    bob.add_remote("mirror", &mirror).unwrap();
+    let remotes = vec!["origin".to_string(), "mirror".to_string()];
+    let outcomes = sync_notes_with_remotes(bob.gitai_repo(), &remotes);
+    for outcome in &outcomes {
+        assert!(
+            matches!(outcome.fetch, Ok(NotesExistence::Found)),
+            "{:?}",
+            outcome
+        );
+        assert!(matches!(outcome.push, Some(Ok(()))), "{:?}", outcome);
+    }
+
+    assert_eq!(origin.note(&base).as_deref(), Some("mirror note"));
+    assert_eq!(mirror.note(&base).as_deref(), Some("mirror note"));
+    assert_eq!(origin.note(&a_sha).as_deref(), Some("origin note"));
+    // The mirror never received commit a, but its notes ref now carries the note for it
+    assert_eq!(mirror.note(&a_sha).as_deref(), Some("origin note"));
+    assert_eq!(
+        mirror.notes_tip(),
+        origin.notes_tip(),
+        "both remotes should end on the same notes commit"
+    );
+}
+
+#[test]
+fn test_local_remote_failed_notes_push_is_queued_and_retried() {
+    let remote = LocalRemote::new().unwrap();
+    let alice = remote.clone_repo().unwrap();
+    let sha = alice.commit("a.txt", "one\n", "first").unwrap();
+    alice.add_note(&sha, "alice note").unwrap();
+    alice.push(MAIN).unwrap();
+
+    // The remote rejects notes refs, like a protected ref would
+    let hook = remote.path().join("hooks").join("pre-receive");
+    std::fs::write(
+        &hook,
+        "#!/bin/sh\nwhile read old new ref; do\n  case \"$ref\" in refs/notes/*) exit 1;; esac\ndone\n",
+    )
+    .unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    assert!(alice.push_notes("origin").is_err());
+    assert!(alice.push_notes("origin").is_err());
+    let pending = pending_pushes(alice.gitai_repo());
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].remote, "origin");
+    assert_eq!(pending[0].attempts, 2);
+    assert_eq!(remote.note(&sha), None);
+
+    std::fs::remove_file(&hook).unwrap();
+    let results = retry_pending_pushes(alice.gitai_repo(), &[]);
+    assert_eq!(results.len(), 1);
+    assert!(results[0].1.is_ok(), "{:?}", results[0].1);
+    assert!(pending_pushes(alice.gitai_repo()).is_empty());
+    assert_eq!(remote.note(&sha).as_deref(), Some("alice note"));
+}
+
+/// Generate an SSH signing key in `clone` and return its public key
+fn generate_signing_key(clone: &LocalClone) -> String {
+    let key = clone.path().join(".git").join("signing-key");
+    let status = std::process::Command::new("ssh-keygen")
+        .args(["-q", "-t", "ed25519", "-N", "", "-f"])
+        .arg(&key)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let public_key = key.with_extension("pub");
+    clone.git(&["config", "gpg.format", "ssh"]).unwrap();
+    clone
+        .git(&["config", "user.signingkey", public_key.to_str().unwrap()])
+        .unwrap();
+    std::fs::read_to_string(public_key).unwrap()
+}
+
+fn trust_signing_key(clone: &LocalClone, public_key: &str) {
+    let allowed_signers = clone.path().join(".git").join("allowed-signers");
+    std::fs::write(&allowed_signers, format!("* {}", public_key)).unwrap();
+    clone
+        .git(&[
+            "config",
+            "gpg.ssh.allowedSignersFile",
+            allowed_signers.to_str().unwrap(),
+        ])
+        .unwrap();
+}
+
+#[test]
+fn test_local_remote_signed_notes_verified_on_fetch() {
+    let remote = LocalRemote::new().unwrap();
+    let alice = remote.clone_repo().unwrap();
+    let bob = remote.clone_repo().unwrap();
+
+    let public_key = generate_signing_key(&alice);
+    alice.git(&["config", "git-ai.signNotes", "true"]).unwrap();
+    let sha = alice.commit("a.txt", "one\n", "first").unwrap();
+    alice.add_note(&sha, "alice note").unwrap();
+    alice.push(MAIN).unwrap();
+    alice.push_notes("origin").unwrap();
+
+    trust_signing_key(&bob, &public_key);
+    bob.git(&["config", "git-ai.verifyNotes", "strict"])
+        .unwrap();
+    assert_eq!(bob.fetch_notes("origin").unwrap(), NotesExistence::Found);
+    assert_eq!(bob.note(&sha).as_deref(), Some("alice note"));
+    let notes = list_notes(bob.gitai_repo(), "refs/notes/ai").unwrap();
+    let statuses = verify_notes(bob.gitai_repo(), "refs/notes/ai-signatures", &notes).unwrap();
+    assert!(
+        matches!(statuses.get(&sha), Some(SignatureStatus::Valid { .. })),
+        "{:?}",
+        statuses
+    );
+
+    // A note rewritten without the key no longer matches its signature, so strict
+    // verification keeps it out of bob's notes
+    alice.git(&["config", "git-ai.signNotes", "false"]).unwrap();
+    alice.add_note(&sha, "forged note").unwrap();
+    alice.push_notes("origin").unwrap();
+    assert_eq!(remote.note(&sha).as_deref(), Some("forged note"));
+
+    bob.fetch_notes("origin").unwrap();
+    assert_eq!(bob.note(&sha).as_deref(), Some("alice note"));
+}
+
+#[test]
+fn test_local_remote_notes_signed_by_an_untrusted_key_are_rejected() {
+    let remote = LocalRemote::new().unwrap();
+    let alice = remote.clone_repo().unwrap();
+    let mallory = remote.clone_repo().unwrap();
+    let bob = remote.clone_repo().unwrap();
+
+    // Bob trusts alice's key only; mallory signs with a key of her own
+    let alice_key = generate_signing_key(&alice);
+    generate_signing_key(&mallory);
+    mallory
+        .git(&["config", "git-ai.signNotes", "true"])
+        .unwrap();
+    let sha = mallory.commit("a.txt", "one\n", "first").unwrap();
+    mallory.add_note(&sha, "mallory note").unwrap();
+    mallory.push(MAIN).unwrap();
+    mallory.push_notes("origin").unwrap();
+
+    trust_signing_key(&bob, &alice_key);
+    bob.git(&["config", "git-ai.verifyNotes", "strict"])
+        .unwrap();
+    bob.fetch_notes("origin").unwrap();
+    assert_eq!(bob.note(&sha), None);
+
+    // Merged with a warning outside strict mode, but never reported as valid
+    bob.git(&["config", "git-ai.verifyNotes", "true"]).unwrap();
+    bob.fetch_notes("origin").unwrap();
+    assert_eq!(bob.note(&sha).as_deref(), Some("mallory note"));
+    let notes = list_notes(bob.gitai_repo(), "refs/notes/ai").unwrap();
+    let statuses = verify_notes(bob.gitai_repo(), "refs/notes/ai-signatures", &notes).unwrap();
+    assert!(
+        matches!(statuses.get(&sha), Some(SignatureStatus::Invalid(_))),
+        "{:?}",
+        statuses
+    );
+}
@@ -0,0 +1,49 @@
+#[macro_use]
+mod repos;
+use repos::test_repo::TestRepo;
+use std::fs;
+
+#[test]
+fn test_hook_activity_is_written_to_the_repository_log_file() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("a.txt");
+    file.set_contents(lines!["one"]);
+    repo.git_with_env(&["add", "a.txt"], &[("GIT_AI_LOG_FILE", "trace")], None)
+        .unwrap();
+    repo.git_with_env(
+        &["commit", "-m", "first"],
+        &[("GIT_AI_LOG_FILE", "trace"), ("GIT_AI_LOG_FORMAT", "json")],
+        None,
+    )
+    .unwrap();
+
+    let log = fs::read_to_string(repo.path().join(".git/ai/logs/git-ai.log")).unwrap();
+    let text_line = log
+        .lines()
+        .find(|line| line.contains("git: git ") && line.contains("add a.txt"))
+        .expect("text record for git add");
+    assert!(text_line.contains("TRACE"), "{}", text_line);
+
+    let json_record = log
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .find(|record| {
+            record["target"] == "git"
+                && record["message"]
+                    .as_str()
+                    .is_some_and(|message| message.contains("commit -m first"))
+        })
+        .expect("JSON record for git commit");
+    assert_eq!(json_record["level"], "trace");
+}
+
+#[test]
+fn test_log_file_defaults_to_info() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("a.txt");
+    file.set_contents(lines!["one"]);
+    repo.stage_all_and_commit("first").unwrap();
+
+    let log = fs::read_to_string(repo.path().join(".git/ai/logs/git-ai.log")).unwrap_or_default();
+    assert!(!log.contains("DEBUG") && !log.contains("TRACE"), "{}", log);
+}
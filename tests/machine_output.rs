@@ -0,0 +1,83 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+fn json_line(output: &str) -> serde_json::Value {
+    let line = output
+        .lines()
+        .find(|line| line.starts_with('{'))
+        .unwrap_or_else(|| panic!("no JSON object in {}", output));
+    serde_json::from_str(line).unwrap()
+}
+
+/// The value of `key` in porcelain output
+fn porcelain_value<'a>(output: &'a str, key: &str) -> Option<&'a str> {
+    output
+        .lines()
+        .find_map(|line| line.strip_prefix(key)?.strip_prefix('\t'))
+}
+
+#[test]
+fn test_stats_and_status_formats_share_a_versioned_schema() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("app.txt");
+    file.set_contents(lines!["human line"]);
+    repo.stage_all_and_commit("base").unwrap();
+    file.set_contents(lines!["human line", "ai line 1".ai(), "ai line 2".ai()]);
+    repo.stage_all_and_commit("ai lines").unwrap();
+
+    let json = json_line(&repo.git_ai(&["stats", "--format", "json"]).unwrap());
+    assert_eq!(json["schema_version"], 1);
+    assert_eq!(json["ai_additions"], 2);
+
+    let porcelain = repo.git_ai(&["stats", "HEAD", "--porcelain"]).unwrap();
+    assert!(
+        porcelain.starts_with("schema_version\t1\n"),
+        "{}",
+        porcelain
+    );
+    assert_eq!(porcelain_value(&porcelain, "ai_additions"), Some("2"));
+
+    file.set_contents(lines!["human line", "ai line 1", "ai line 2", "more".ai()]);
+    repo.git_ai(&["checkpoint", "mock_ai", "app.txt"]).unwrap();
+    let porcelain = repo.git_ai(&["status", "--format", "porcelain"]).unwrap();
+    assert_eq!(porcelain_value(&porcelain, "schema_version"), Some("1"));
+    assert_eq!(
+        porcelain_value(&porcelain, "checkpoints.0.is_human"),
+        Some("false"),
+        "{}",
+        porcelain
+    );
+    assert!(
+        porcelain_value(&porcelain, "checkpoints.0.timestamp").is_some(),
+        "{}",
+        porcelain
+    );
+
+    let err = repo.git_ai(&["status", "--format", "yaml"]).unwrap_err();
+    assert!(
+        err.contains("--format must be text, json or porcelain (got yaml)"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_sync_and_verify_report_per_remote_and_per_finding() {
+    let (local, _upstream) = TestRepo::new_with_remote();
+    let mut file = local.filename("lib.rs");
+    file.set_contents(lines!["fn a() {}".ai()]);
+    local.stage_all_and_commit("AI lib").unwrap();
+    local.git(&["push", "-u", "origin", "HEAD"]).unwrap();
+
+    let json = json_line(&local.git_ai(&["sync", "--json"]).unwrap());
+    assert_eq!(json["schema_version"], 1);
+    assert_eq!(json["ok"], true);
+    assert_eq!(json["remotes"][0]["remote"], "origin");
+    assert_eq!(json["remotes"][0]["fetch"], "fetched");
+
+    let porcelain = local.git_ai(&["verify", "HEAD", "--porcelain"]).unwrap();
+    assert_eq!(porcelain_value(&porcelain, "passed"), Some("true"));
+    assert_eq!(porcelain_value(&porcelain, "commits_checked"), Some("1"));
+}
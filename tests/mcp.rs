@@ -0,0 +1,109 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+use serde_json::{Value, json};
+
+/// Send one JSON-RPC session to `git-ai mcp` and return its responses, by id
+fn mcp_session(repo: &TestRepo, requests: &[Value]) -> Vec<Value> {
+    let mut input = String::new();
+    for request in requests {
+        input.push_str(&request.to_string());
+        input.push('\n');
+    }
+    let output = repo.git_ai_with_stdin(&["mcp"], input.as_bytes()).unwrap();
+    output
+        .lines()
+        .filter(|line| line.starts_with('{'))
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+fn call(id: u64, tool: &str, arguments: Value) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": "tools/call",
+        "params": { "name": tool, "arguments": arguments },
+    })
+}
+
+#[test]
+fn test_mcp_declared_edits_are_attributed_to_the_agent() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("lib.rs");
+    file.set_contents(lines!["fn base() {}".human()]);
+    repo.stage_all_and_commit("base").unwrap();
+
+    // The human edits first, then the agent checkpoints, edits and declares
+    let path = repo.path().join("lib.rs");
+    std::fs::write(&path, "fn base() {}\nfn human() {}\n").unwrap();
+    let responses = mcp_session(
+        &repo,
+        &[
+            json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": { "protocolVersion": "2025-06-18", "clientInfo": { "name": "test-agent" } },
+            }),
+            json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }),
+            call(2, "register_checkpoint", json!({ "paths": ["lib.rs"] })),
+        ],
+    );
+    assert_eq!(responses.len(), 2, "{:?}", responses);
+    assert_eq!(responses[1]["result"]["isError"], false, "{:?}", responses);
+
+    std::fs::write(
+        &path,
+        "fn base() {}\nfn human() {}\nfn ai_one() {}\nfn ai_two() {}\n",
+    )
+    .unwrap();
+    let responses = mcp_session(
+        &repo,
+        &[
+            json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": { "clientInfo": { "name": "test-agent" } },
+            }),
+            call(
+                2,
+                "declare_edits",
+                json!({ "paths": ["lib.rs"], "model": "test-model" }),
+            ),
+            call(3, "get_attribution", json!({ "path": "lib.rs" })),
+        ],
+    );
+    let declared = &responses[1]["result"];
+    assert_eq!(declared["isError"], false, "{:?}", responses);
+    assert_eq!(declared["structuredContent"]["agent"], "test-agent");
+
+    let attribution = &responses[2]["result"]["structuredContent"];
+    assert_eq!(attribution["ai_lines"], 2, "{}", attribution);
+    assert_eq!(attribution["agents"][0]["tool"], "test-agent");
+    assert_eq!(attribution["agents"][0]["model"], "test-model");
+
+    repo.stage_all_and_commit("agent edit").unwrap();
+    let blame = repo.git_ai(&["blame", "lib.rs"]).unwrap();
+    let authors: Vec<bool> = blame
+        .lines()
+        .map(|line| line.contains("(test-agent "))
+        .collect();
+    assert_eq!(authors, vec![false, false, true, true], "{}", blame);
+}
+
+#[test]
+fn test_mcp_tool_errors_are_reported_as_results() {
+    let repo = TestRepo::new();
+    let responses = mcp_session(
+        &repo,
+        &[
+            call(1, "declare_edits", json!({})),
+            json!({ "jsonrpc": "2.0", "id": 2, "method": "resources/list" }),
+        ],
+    );
+    assert_eq!(responses[0]["result"]["isError"], true, "{:?}", responses);
+    assert_eq!(responses[1]["error"]["code"], -32601);
+}
@@ -0,0 +1,102 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+const CURRENT_SCHEMA: &str = "\"schema_version\": \"authorship/3.0.0\"";
+
+#[test]
+fn test_migrate_notes_rewrites_legacy_notes_to_the_current_schema() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("lib.rs");
+    file.set_contents(lines!["fn a() {}".ai(), "fn b() {}".human()]);
+    let commit = repo.stage_all_and_commit("AI lib").unwrap();
+
+    // Rewrite the note the way a client from before the schema_version field would have
+    let note = repo
+        .git_og(&["notes", "--ref=ai", "show", &commit.commit_sha])
+        .unwrap();
+    assert!(note.contains(CURRENT_SCHEMA), "{}", note);
+    let legacy = note
+        .replace(&format!("  {},\n", CURRENT_SCHEMA), "")
+        .replace("\"overriden_lines\"", "\"overridden_lines\"");
+    let legacy_file = repo.path().join(".git").join("legacy-note");
+    std::fs::write(&legacy_file, &legacy).unwrap();
+    repo.git_og(&[
+        "notes",
+        "--ref=ai",
+        "add",
+        "-f",
+        "-F",
+        legacy_file.to_str().unwrap(),
+        &commit.commit_sha,
+    ])
+    .unwrap();
+
+    let output = repo.git_ai(&["migrate-notes", "--dry-run"]).unwrap();
+    assert!(
+        output.contains("Would migrate 1 of 1 note, 0 up to date, 0 skipped"),
+        "{}",
+        output
+    );
+    let unchanged = repo
+        .git_og(&["notes", "--ref=ai", "show", &commit.commit_sha])
+        .unwrap();
+    assert!(!unchanged.contains("schema_version"), "{}", unchanged);
+
+    let output = repo.git_ai(&["migrate-notes"]).unwrap();
+    assert!(
+        output.contains("(no schema_version -> authorship/3.0.0)"),
+        "{}",
+        output
+    );
+    let migrated = repo
+        .git_og(&["notes", "--ref=ai", "show", &commit.commit_sha])
+        .unwrap();
+    assert!(migrated.contains(CURRENT_SCHEMA), "{}", migrated);
+    assert!(migrated.contains("\"overriden_lines\""), "{}", migrated);
+
+    let output = repo.git_ai(&["migrate-notes"]).unwrap();
+    assert!(
+        output.contains("Migrated 0 of 1 note, 1 up to date, 0 skipped"),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn test_migrate_notes_leaves_notes_from_a_newer_major_version_alone() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("lib.rs");
+    file.set_contents(lines!["fn a() {}".ai()]);
+    let commit = repo.stage_all_and_commit("AI lib").unwrap();
+
+    let note = repo
+        .git_og(&["notes", "--ref=ai", "show", &commit.commit_sha])
+        .unwrap();
+    let future = note.replace("authorship/3.0.0", "authorship/4.0.0");
+    let future_file = repo.path().join(".git").join("future-note");
+    std::fs::write(&future_file, &future).unwrap();
+    repo.git_og(&[
+        "notes",
+        "--ref=ai",
+        "add",
+        "-f",
+        "-F",
+        future_file.to_str().unwrap(),
+        &commit.commit_sha,
+    ])
+    .unwrap();
+
+    let output = repo.git_ai(&["migrate-notes"]).unwrap();
+    assert!(
+        output.contains("Unsupported schema version 'authorship/4.0.0'"),
+        "{}",
+        output
+    );
+    assert!(output.contains("0 up to date, 1 skipped"), "{}", output);
+    let kept = repo
+        .git_og(&["notes", "--ref=ai", "show", &commit.commit_sha])
+        .unwrap();
+    assert_eq!(kept, future);
+}
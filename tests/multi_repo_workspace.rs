@@ -5,7 +5,7 @@
 //!
 //! 1. Detecting git repository from file paths when workspace root isn't a git repo
 //! 2. Grouping files by their containing repository
-//! 3. Handling submodules correctly (should be ignored in favor of parent repo)
+//! 3. Handling submodules and linked worktrees (each resolves to its own repository)
 //! 4. Edge cases with nested git directories
 
 use git_ai::error::GitAiError;
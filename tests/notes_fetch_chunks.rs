@@ -0,0 +1,50 @@
+use git_ai::git::sync_authorship::NotesExistence;
+use git_ai::git::test_utils::local_remote::{LOCAL_REMOTE_DEFAULT_BRANCH, LocalRemote};
+
+#[test]
+fn test_partial_clone_prefetches_note_blobs_in_chunks() {
+    // Its own test binary, since configuration is read once per process
+    unsafe {
+        std::env::set_var("GIT_AI_NOTES_FETCH_CHUNK_SIZE", "2");
+    }
+
+    let remote = LocalRemote::new().unwrap();
+    let alice = remote.clone_repo().unwrap();
+    let shas: Vec<String> = (1..=5)
+        .map(|i| {
+            let sha = alice
+                .commit(
+                    &format!("f{}.txt", i),
+                    &format!("{}\n", i),
+                    &format!("c{}", i),
+                )
+                .unwrap();
+            alice.add_note(&sha, &format!("note {}", i)).unwrap();
+            sha
+        })
+        .collect();
+    alice.push(LOCAL_REMOTE_DEFAULT_BRANCH).unwrap();
+    alice.push_notes("origin").unwrap();
+
+    let ci = remote.clone_repo_with(&["--filter=blob:none"]).unwrap();
+    assert_eq!(ci.fetch_notes("origin").unwrap(), NotesExistence::Found);
+
+    // Three requests of up to two blobs leave nothing to fetch lazily
+    let missing = ci
+        .git(&[
+            "rev-list",
+            "--objects",
+            "--no-walk",
+            "--missing=print",
+            "refs/notes/ai",
+        ])
+        .unwrap();
+    assert!(
+        !missing.lines().any(|line| line.starts_with('?')),
+        "{}",
+        missing
+    );
+    for (i, sha) in shas.iter().enumerate() {
+        assert_eq!(ci.note(sha), Some(format!("note {}", i + 1)));
+    }
+}
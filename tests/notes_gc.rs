@@ -0,0 +1,121 @@
+#[macro_use]
+mod repos;
+use git_ai::git::test_utils::local_remote::{LOCAL_REMOTE_DEFAULT_BRANCH, LocalRemote};
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+/// A commit with AI lines on a branch that is then deleted, leaving it reachable only from the
+/// reflog. Returns the dropped commit and the one still on main.
+fn drop_branch_commit(repo: &TestRepo) -> (String, String) {
+    let mut file = repo.filename("lib.rs");
+    file.set_contents(lines!["fn main() {}".ai()]);
+    let kept = repo.stage_all_and_commit("main").unwrap().commit_sha;
+
+    repo.git(&["checkout", "-b", "topic"]).unwrap();
+    file.set_contents(lines!["fn main() {}".ai(), "fn topic() {}".ai()]);
+    let dropped = repo.stage_all_and_commit("topic").unwrap().commit_sha;
+    repo.git(&["checkout", "-"]).unwrap();
+    repo.git(&["branch", "-D", "topic"]).unwrap();
+    (dropped, kept)
+}
+
+fn has_note(repo: &TestRepo, notes_ref: &str, commit: &str) -> bool {
+    repo.git_og(&["notes", &format!("--ref={}", notes_ref), "show", commit])
+        .is_ok()
+}
+
+#[test]
+fn test_gc_notes_prunes_unreachable_commits_after_the_reflog_grace_period() {
+    let repo = TestRepo::new();
+    let (dropped, kept) = drop_branch_commit(&repo);
+    assert!(has_note(&repo, "ai", &dropped));
+
+    // The reflog still reaches the dropped commit, within the default grace period
+    let output = repo.git_ai(&["gc", "--notes", "--dry-run"]).unwrap();
+    assert!(
+        output.contains("Would prune 0 notes of unreachable commits, kept 2"),
+        "{}",
+        output
+    );
+
+    let output = repo
+        .git_ai(&["gc", "--notes", "--grace", "0", "--dry-run"])
+        .unwrap();
+    assert!(
+        output.contains(&format!("Would prune note for {}", &dropped[..12])),
+        "{}",
+        output
+    );
+    assert!(has_note(&repo, "ai", &dropped));
+
+    let output = repo.git_ai(&["gc", "--notes", "--grace", "0"]).unwrap();
+    assert!(
+        output.contains("Pruned 1 note of unreachable commits, kept 1"),
+        "{}",
+        output
+    );
+    assert!(!has_note(&repo, "ai", &dropped));
+    assert!(has_note(&repo, "ai", &kept));
+}
+
+#[test]
+fn test_gc_notes_archive_moves_notes_aside() {
+    let repo = TestRepo::new();
+    let (dropped, kept) = drop_branch_commit(&repo);
+    let note = repo
+        .git_og(&["notes", "--ref=ai", "show", &dropped])
+        .unwrap();
+
+    let output = repo
+        .git_ai(&["gc", "--notes", "--archive", "--grace", "0"])
+        .unwrap();
+    assert!(output.contains("Archived 1 note"), "{}", output);
+    assert!(!has_note(&repo, "ai", &dropped));
+    assert!(has_note(&repo, "ai", &kept));
+    let archived = repo
+        .git_og(&["notes", "--ref=ai-archive", "show", &dropped])
+        .unwrap();
+    assert_eq!(archived, note);
+
+    // Working log flags don't apply to notes
+    let err = repo
+        .git_ai(&["gc", "--notes", "--max-age", "3"])
+        .unwrap_err();
+    assert!(err.contains("can't be combined with --notes"), "{}", err);
+}
+
+#[test]
+fn test_gc_notes_keeps_notes_of_commits_a_single_branch_clone_never_fetched() {
+    let remote = LocalRemote::new().unwrap();
+    let alice = remote.clone_repo().unwrap();
+    let main_sha = alice.commit("a.txt", "main\n", "main work").unwrap();
+    alice.add_note(&main_sha, "main note").unwrap();
+    alice.push(LOCAL_REMOTE_DEFAULT_BRANCH).unwrap();
+    alice
+        .git(&["checkout", "--quiet", "-b", "feature"])
+        .unwrap();
+    let feature_sha = alice.commit("b.txt", "feature\n", "feature work").unwrap();
+    alice.add_note(&feature_sha, "feature note").unwrap();
+    alice.push("feature").unwrap();
+    alice.push_notes("origin").unwrap();
+
+    let clone = remote
+        .clone_repo_with(&["--single-branch", "-b", LOCAL_REMOTE_DEFAULT_BRANCH])
+        .unwrap();
+    clone.fetch_notes("origin").unwrap();
+    assert_eq!(clone.note(&feature_sha).as_deref(), Some("feature note"));
+
+    let repo = TestRepo::new_at_path(&clone.path().to_path_buf());
+    let output = repo.git_ai(&["gc", "--notes", "--grace", "0"]).unwrap();
+    assert!(
+        output.contains("Pruned 0 notes of unreachable commits, kept 2"),
+        "{}",
+        output
+    );
+    assert!(has_note(&repo, "ai", &feature_sha));
+
+    // Syncing back must not remove the note from the shared remote
+    clone.push_notes("origin").unwrap();
+    assert_eq!(remote.note(&feature_sha).as_deref(), Some("feature note"));
+    assert_eq!(remote.note(&main_sha).as_deref(), Some("main note"));
+}
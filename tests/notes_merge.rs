@@ -0,0 +1,114 @@
+#[macro_use]
+mod repos;
+use git_ai::authorship::authorship_log::LineRange;
+use git_ai::authorship::authorship_log_serialization::{AttestationEntry, AuthorshipLog};
+use git_ai::git::test_utils::synthetic_authorship_log;
+use repos::test_repo::TestRepo;
+
+/// A commit whose refs/notes/ai and refs/notes/other each extended the same base note: ours
+/// attributes one more line of the first file, theirs attributes a second file.
+fn diverged_notes(repo: &TestRepo) -> (String, AuthorshipLog, AuthorshipLog) {
+    let mut file = repo.filename("main.txt");
+    file.set_contents(lines!["base"]);
+    let sha = repo
+        .stage_all_and_commit("Initial commit")
+        .unwrap()
+        .commit_sha;
+
+    let base = synthetic_authorship_log(1, 1);
+    add_note(repo, "ai", &sha, &base);
+    repo.git_og(&["update-ref", "refs/notes/other", "refs/notes/ai"])
+        .unwrap();
+
+    let mut ours = base.clone();
+    let hash = ours.attestations[0].entries[0].hash.clone();
+    ours.attestations[0]
+        .entries
+        .push(AttestationEntry::new(hash, vec![LineRange::Single(50)]));
+    add_note(repo, "ai", &sha, &ours);
+
+    let mut theirs = base.clone();
+    theirs
+        .attestations
+        .push(synthetic_authorship_log(2, 1).attestations[1].clone());
+    add_note(repo, "other", &sha, &theirs);
+
+    (sha, ours, theirs)
+}
+
+fn add_note(repo: &TestRepo, notes_ref: &str, sha: &str, log: &AuthorshipLog) {
+    repo.git_og(&[
+        "notes",
+        &format!("--ref={}", notes_ref),
+        "add",
+        "-f",
+        "-m",
+        &log.serialize_to_string().unwrap(),
+        sha,
+    ])
+    .unwrap();
+}
+
+fn assert_note_is_union(repo: &TestRepo, sha: &str, ours: &AuthorshipLog, theirs: &AuthorshipLog) {
+    let note = repo.git_og(&["notes", "--ref=ai", "show", sha]).unwrap();
+    let merged = AuthorshipLog::deserialize_from_string(&note).unwrap();
+    assert_eq!(merged.attestations.len(), 2);
+    assert_eq!(merged.attestations[0], ours.attestations[0]);
+    assert_eq!(merged.attestations[1], theirs.attestations[1]);
+}
+
+#[test]
+fn test_git_notes_merge_unions_authorship_notes() {
+    let repo = TestRepo::new();
+    let (sha, ours, theirs) = diverged_notes(&repo);
+
+    // Plain git would stop on a conflict here; through the wrapper the notes are combined
+    repo.git(&["notes", "--ref=ai", "merge", "other"]).unwrap();
+
+    assert_note_is_union(&repo, &sha, &ours, &theirs);
+    let parents = repo
+        .git_og(&["rev-list", "--parents", "-n", "1", "refs/notes/ai"])
+        .unwrap();
+    assert_eq!(parents.split_whitespace().count(), 3);
+}
+
+#[test]
+fn test_git_notes_merge_with_explicit_strategy_is_left_to_git() {
+    let repo = TestRepo::new();
+    let (sha, ours, _) = diverged_notes(&repo);
+
+    repo.git(&["notes", "--ref=ai", "merge", "-s", "ours", "other"])
+        .unwrap();
+
+    let note = repo.git_og(&["notes", "--ref=ai", "show", &sha]).unwrap();
+    assert_eq!(AuthorshipLog::deserialize_from_string(&note).unwrap(), ours);
+}
+
+#[test]
+fn test_notes_merge_driver_resolves_conflicted_merge() {
+    let repo = TestRepo::new();
+    let (sha, ours, theirs) = diverged_notes(&repo);
+
+    assert!(
+        repo.git_og(&["notes", "--ref=ai", "merge", "other"])
+            .is_err()
+    );
+    let output = repo.git_ai(&["notes-merge-driver"]).unwrap();
+    assert!(output.contains("Resolved 1 conflicting note"), "{}", output);
+
+    assert_note_is_union(&repo, &sha, &ours, &theirs);
+    assert!(repo.git_ai(&["notes-merge-driver"]).is_err());
+}
+
+#[test]
+fn test_notes_merge_driver_merges_named_ref() {
+    let repo = TestRepo::new();
+    let (sha, ours, theirs) = diverged_notes(&repo);
+
+    let output = repo.git_ai(&["notes-merge-driver", "other"]).unwrap();
+    assert!(output.contains("1 note combined"), "{}", output);
+    assert_note_is_union(&repo, &sha, &ours, &theirs);
+
+    let output = repo.git_ai(&["notes-merge-driver", "other"]).unwrap();
+    assert!(output.contains("Already up to date"), "{}", output);
+}
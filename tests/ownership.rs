@@ -0,0 +1,58 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+use std::fs;
+
+fn json_line(output: &str) -> serde_json::Value {
+    let line = output
+        .lines()
+        .find(|line| line.starts_with('{'))
+        .unwrap_or_else(|| panic!("no JSON object in {}", output));
+    serde_json::from_str(line).unwrap()
+}
+
+#[test]
+fn test_ownership_ranks_owned_areas_by_ai_share() {
+    let repo = TestRepo::new();
+    fs::create_dir_all(repo.path().join(".github")).unwrap();
+    fs::write(
+        repo.path().join(".github/CODEOWNERS"),
+        "* @org/everyone\n/api/ @org/api\n",
+    )
+    .unwrap();
+    let mut api = repo.filename("api/routes.rs");
+    api.set_contents(lines!["fn a() {}".ai(), "fn b() {}".ai(), "fn c() {}"]);
+    let mut web = repo.filename("web/app.ts");
+    web.set_contents(lines!["let a = 1;", "let b = 2;"]);
+    repo.stage_all_and_commit("api and web").unwrap();
+
+    let json = json_line(&repo.git_ai(&["ownership", "--json"]).unwrap());
+    assert_eq!(json["schema_version"], 1);
+    assert_eq!(json["codeowners"], ".github/CODEOWNERS");
+    assert_eq!(json["areas"][0]["path"], "api/");
+    assert_eq!(json["areas"][0]["ai_lines"], 2);
+    assert_eq!(json["areas"][0]["owners"][0], "@org/api");
+    assert_eq!(json["owners"][0]["owner"], "@org/api");
+
+    let text = repo.git_ai(&["ownership", "--files"]).unwrap();
+    assert!(text.contains("Owners from .github/CODEOWNERS"), "{}", text);
+    let api_row = text
+        .lines()
+        .find(|line| line.starts_with("api/routes.rs"))
+        .unwrap_or_else(|| panic!("no api/routes.rs row in {}", text));
+    assert!(api_row.contains("@org/api"), "{}", api_row);
+
+    // Removed files drop out of the report
+    repo.git(&["rm", "-q", "web/app.ts"]).unwrap();
+    repo.stage_all_and_commit("drop web").unwrap();
+    let json = json_line(&repo.git_ai(&["ownership", "--format", "json"]).unwrap());
+    let areas: Vec<&str> = json["areas"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|area| area["path"].as_str().unwrap())
+        .collect();
+    assert!(!areas.contains(&"web/"), "{:?}", areas);
+    assert!(areas.contains(&"api/"), "{:?}", areas);
+}
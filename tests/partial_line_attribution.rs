@@ -0,0 +1,110 @@
+#[macro_use]
+mod repos;
+
+use git_ai::authorship::authorship_log::CharRange;
+use git_ai::authorship::authorship_log_serialization::AuthorshipLog;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+#[test]
+fn test_ai_completing_a_human_line_records_char_ranges() {
+    let repo = TestRepo::new();
+    let path = repo.path().join("calc.rs");
+
+    std::fs::write(&path, "fn main() {\n}\n").unwrap();
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    // Human starts the line, AI finishes it
+    std::fs::write(&path, "fn main() {\n    let total = \n}\n").unwrap();
+    repo.git_ai(&["checkpoint"]).unwrap();
+    std::fs::write(&path, "fn main() {\n    let total = compute(a, b);\n}\n").unwrap();
+    repo.git_ai(&["checkpoint", "mock_ai"]).unwrap();
+
+    let commit = repo.stage_all_and_commit("Finish total").unwrap();
+
+    // The whole line is still attested to the AI for line-level readers
+    assert_eq!(commit.authorship_log.attestations.len(), 1);
+    let entry = &commit.authorship_log.attestations[0].entries[0];
+    assert!(entry.line_ranges.iter().any(|r| r.contains(2)));
+
+    let partial = commit
+        .authorship_log
+        .metadata
+        .partial_lines
+        .get("calc.rs")
+        .and_then(|by_hash| by_hash.get(&entry.hash))
+        .expect("partial line ranges should be recorded");
+    let ai_text: String = partial
+        .iter()
+        .map(|r| {
+            "    let total = compute(a, b);"
+                .chars()
+                .skip(r.start as usize)
+                .take((r.end - r.start) as usize)
+                .collect::<String>()
+        })
+        .collect();
+    assert!(partial.iter().all(|r| r.line == 2), "{:?}", partial);
+    assert!(ai_text.contains("compute(a, b);"), "{:?}", ai_text);
+    assert!(!ai_text.contains("total"), "{:?}", ai_text);
+}
+
+#[test]
+fn test_whole_ai_lines_have_no_char_ranges() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("whole.txt");
+
+    file.set_contents(lines!["Base"]);
+    repo.stage_all_and_commit("Initial commit").unwrap();
+
+    file.insert_at(1, lines!["AI line 1".ai(), "AI line 2".ai()]);
+    let commit = repo.stage_all_and_commit("AI lines").unwrap();
+
+    assert!(commit.authorship_log.metadata.partial_lines.is_empty());
+    let note = commit.authorship_log.serialize_to_string().unwrap();
+    assert!(!note.contains("partial_lines"), "{}", note);
+}
+
+#[test]
+fn test_line_only_and_partial_notes_both_parse() {
+    let line_only = r#"src/lib.rs
+  abcd123 1-3
+---
+{
+  "schema_version": "authorship/3.0.0",
+  "base_commit_sha": "",
+  "prompts": {}
+}"#;
+    let log = AuthorshipLog::deserialize_from_string(line_only).unwrap();
+    assert!(log.metadata.partial_lines.is_empty());
+
+    let with_partial = r#"src/lib.rs
+  abcd123 1-3
+---
+{
+  "schema_version": "authorship/3.0.0",
+  "base_commit_sha": "",
+  "prompts": {},
+  "partial_lines": {
+    "src/lib.rs": {
+      "abcd123": ["2:10-24"]
+    }
+  }
+}"#;
+    let log = AuthorshipLog::deserialize_from_string(with_partial).unwrap();
+    assert_eq!(
+        log.metadata.partial_lines["src/lib.rs"]["abcd123"],
+        vec![CharRange {
+            line: 2,
+            start: 10,
+            end: 24
+        }]
+    );
+
+    // Spans for lines the prompt no longer attests are dropped on write
+    let mut log = log;
+    log.attestations[0].entries[0].line_ranges =
+        vec![git_ai::authorship::authorship_log::LineRange::Single(1)];
+    let rewritten = log.serialize_to_string().unwrap();
+    assert!(!rewritten.contains("partial_lines"), "{}", rewritten);
+}
@@ -0,0 +1,193 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+use std::fs;
+
+fn write_policy(repo: &TestRepo, policy: &str) {
+    fs::write(repo.path().join("policy.toml"), policy).unwrap();
+}
+
+#[test]
+fn test_policy_blocks_commit_over_ai_threshold() {
+    let repo = TestRepo::new();
+    write_policy(
+        &repo,
+        r#"
+[[rules]]
+name = "security"
+paths = ["security/"]
+max_ai_percent = 50
+"#,
+    );
+    repo.stage_all_and_commit("Add policy").unwrap();
+
+    let mut file = repo.filename("security/auth.rs");
+    file.set_contents(lines![
+        "fn check() {}".human(),
+        "fn a() {}".ai(),
+        "fn b() {}".ai()
+    ]);
+    // AI lines elsewhere don't count towards the security/ limit
+    let mut other = repo.filename("src/lib.rs");
+    other.set_contents(lines!["fn c() {}".ai()]);
+    repo.git(&["add", "-A"]).unwrap();
+
+    let err = repo.git(&["commit", "-m", "AI auth"]).unwrap_err();
+    assert!(
+        err.contains(
+            "[security] staged changes: 67% of the lines added in security/ are AI-generated (2 of 3), above the 50% limit"
+        ),
+        "{}",
+        err
+    );
+    assert!(err.contains("GIT_AI_POLICY_OVERRIDE=1"), "{}", err);
+    assert!(
+        repo.git(&["log", "-1", "--format=%s"])
+            .unwrap()
+            .contains("Add policy")
+    );
+
+    let commit = repo
+        .commit_with_env("AI auth", &[("GIT_AI_POLICY_OVERRIDE", "1")], None)
+        .unwrap();
+    assert!(
+        commit
+            .stdout
+            .contains("commit allowed because GIT_AI_POLICY_OVERRIDE is set"),
+        "{}",
+        commit.stdout
+    );
+}
+
+#[test]
+fn test_policy_warn_rule_lets_commit_through() {
+    let repo = TestRepo::new();
+    write_policy(
+        &repo,
+        r#"
+[[rules]]
+name = "no-mock"
+deny_tools = ["mock_*"]
+enforcement = "warn"
+"#,
+    );
+
+    let mut file = repo.filename("lib.rs");
+    file.set_contents(lines!["fn a() {}".ai()]);
+    let commit = repo.stage_all_and_commit("AI lib").unwrap();
+    assert!(
+        commit
+            .stdout
+            .contains("git-ai policy warning: [no-mock] staged changes: lib.rs has lines written by tool mock_ai"),
+        "{}",
+        commit.stdout
+    );
+}
+
+#[test]
+fn test_invalid_policy_blocks_commit() {
+    let repo = TestRepo::new();
+    write_policy(&repo, "[[rules]]\nmax_ai_pct = 50\n");
+
+    let mut file = repo.filename("lib.rs");
+    file.set_contents(lines!["fn a() {}".human()]);
+    repo.git(&["add", "-A"]).unwrap();
+
+    let err = repo.git(&["commit", "-m", "Human lib"]).unwrap_err();
+    assert!(err.contains("unknown key `max_ai_pct`"), "{}", err);
+}
+
+#[test]
+fn test_policy_requires_review_trailer_on_push() {
+    let (local, _upstream) = TestRepo::new_with_remote();
+    write_policy(
+        &local,
+        r#"
+[[rules]]
+require_review = true
+hooks = ["push"]
+"#,
+    );
+    local.stage_all_and_commit("Add policy").unwrap();
+    local.git(&["push", "-u", "origin", "HEAD"]).unwrap();
+
+    let mut file = local.filename("lib.rs");
+    file.set_contents(lines!["fn a() {}".ai()]);
+    let commit = local.stage_all_and_commit("AI lib").unwrap();
+
+    let err = local.git(&["push", "origin", "HEAD"]).unwrap_err();
+    assert!(
+        err.contains(&format!(
+            "[rule 1] commit {}: AI-generated lines need human review (1 of 1 added); add `AI-Reviewed-by: <name>` to the commit message",
+            &commit.commit_sha[..7]
+        )),
+        "{}",
+        err
+    );
+
+    local
+        .git(&[
+            "commit",
+            "--amend",
+            "-m",
+            "AI lib\n\nAI-Reviewed-by: Test User <test@example.com>",
+        ])
+        .unwrap();
+    local.git(&["push", "origin", "HEAD"]).unwrap();
+}
+
+#[test]
+fn test_policy_check_reports_sarif_with_rule_ids_and_locations() {
+    let repo = TestRepo::new();
+    write_policy(
+        &repo,
+        r#"
+[[rules]]
+name = "no-mock"
+deny_tools = ["mock_*"]
+hooks = ["push"]
+"#,
+    );
+    repo.stage_all_and_commit("Add policy").unwrap();
+
+    let mut file = repo.filename("src/lib.rs");
+    file.set_contents(lines!["fn a() {}".human(), "fn b() {}".ai()]);
+    let commit = repo.stage_all_and_commit("AI lib").unwrap();
+
+    let sarif_path = repo.path().join("policy.sarif");
+    let err = repo
+        .git_ai(&[
+            "policy",
+            "check",
+            "HEAD",
+            "--format",
+            "sarif",
+            "-o",
+            sarif_path.to_str().unwrap(),
+        ])
+        .unwrap_err();
+    assert!(!err.contains("panicked"), "{}", err);
+
+    let sarif: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&sarif_path).unwrap()).unwrap();
+    let results = sarif["runs"][0]["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1, "{}", sarif);
+    assert_eq!(results[0]["ruleId"], "policy/denied-tool");
+    assert_eq!(results[0]["level"], "error");
+    let location = &results[0]["locations"][0]["physicalLocation"];
+    assert_eq!(location["artifactLocation"]["uri"], "src/lib.rs");
+    assert_eq!(location["region"]["startLine"], 2);
+    assert_eq!(
+        results[0]["partialFingerprints"]["commitSha"],
+        commit.commit_sha
+    );
+
+    // The commit before has no AI lines
+    let text = repo.git_ai(&["policy", "check", "HEAD~1"]).unwrap();
+    assert!(
+        text.contains("Checked 1 commit(s): no policy violations"),
+        "{}",
+        text
+    );
+}
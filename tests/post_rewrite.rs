@@ -0,0 +1,323 @@
+#[macro_use]
+mod repos;
+
+use filetime::{FileTime, set_file_mtime};
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+fn has_note(repo: &TestRepo, rev: &str) -> bool {
+    repo.git_og(&["notes", "--ref=ai", "show", rev]).is_ok()
+}
+
+#[test]
+fn test_post_rewrite_hook_keeps_notes_on_plain_git_amend() {
+    let repo = TestRepo::new();
+    repo.git_ai(&["post-rewrite", "install"])
+        .expect("install should succeed");
+
+    let mut file = repo.filename("app.txt");
+    file.set_contents(lines!["human line", "ai line".ai()]);
+    repo.stage_all_and_commit("AI commit").unwrap();
+
+    repo.git_og(&["commit", "--amend", "-m", "Reworded without git-ai"])
+        .unwrap();
+
+    assert!(
+        has_note(&repo, "HEAD"),
+        "amended commit should keep its note"
+    );
+    file.assert_lines_and_blame(lines!["human line".human(), "ai line".ai()]);
+}
+
+#[test]
+fn test_post_rewrite_hook_keeps_notes_on_plain_git_rebase() {
+    let repo = TestRepo::new();
+    repo.git_ai(&["post-rewrite", "install"])
+        .expect("install should succeed");
+
+    let mut base = repo.filename("base.txt");
+    base.set_contents(lines!["base"]);
+    repo.stage_all_and_commit("base").unwrap();
+    let default_branch = repo.current_branch();
+
+    repo.git(&["checkout", "-b", "feature"]).unwrap();
+    let mut file = repo.filename("feature.txt");
+    file.set_contents(lines!["ai line".ai(), "human line"]);
+    repo.stage_all_and_commit("AI feature").unwrap();
+
+    repo.git(&["checkout", &default_branch]).unwrap();
+    base.set_contents(lines!["base", "more base"]);
+    repo.stage_all_and_commit("main moves on").unwrap();
+
+    repo.git_og(&["checkout", "feature"]).unwrap();
+    repo.git_og(&["rebase", &default_branch]).unwrap();
+
+    assert!(has_note(&repo, "HEAD"), "rebased commit should have a note");
+    file.assert_lines_and_blame(lines!["ai line".ai(), "human line".human()]);
+}
+
+#[test]
+fn test_post_commit_hook_carries_notes_over_cherry_pick_x() {
+    let repo = TestRepo::new();
+    repo.git_ai(&["post-rewrite", "install"])
+        .expect("install should succeed");
+
+    let mut base = repo.filename("base.txt");
+    base.set_contents(lines!["base"]);
+    repo.stage_all_and_commit("base").unwrap();
+    let default_branch = repo.current_branch();
+
+    repo.git(&["checkout", "-b", "feature"]).unwrap();
+    let mut file = repo.filename("feature.txt");
+    file.set_contents(lines!["ai line".ai()]);
+    let source = repo.stage_all_and_commit("AI feature").unwrap();
+
+    repo.git_og(&["checkout", &default_branch]).unwrap();
+    repo.git_og(&["cherry-pick", "-x", &source.commit_sha])
+        .unwrap();
+
+    assert!(
+        has_note(&repo, "HEAD"),
+        "cherry-picked commit should have a note"
+    );
+    file.assert_lines_and_blame(lines!["ai line".ai()]);
+}
+
+#[test]
+fn test_post_rewrite_install_into_core_hooks_path() {
+    let repo = TestRepo::new();
+    let hooks_dir = repo.path().with_extension("hooks");
+    repo.git_ai(&[
+        "post-rewrite",
+        "install",
+        "--hooks-path",
+        hooks_dir.to_str().unwrap(),
+    ])
+    .expect("install should succeed");
+
+    assert!(hooks_dir.join("post-rewrite").is_file());
+    let configured = repo.git_og(&["config", "core.hooksPath"]).unwrap();
+    assert!(
+        configured.trim().ends_with(".hooks"),
+        "core.hooksPath should point at the hooks dir, got {}",
+        configured
+    );
+
+    let mut file = repo.filename("app.txt");
+    file.set_contents(lines!["human line", "ai line".ai()]);
+    repo.stage_all_and_commit("AI commit").unwrap();
+    repo.git_og(&["commit", "--amend", "-m", "Reworded without git-ai"])
+        .unwrap();
+
+    assert!(
+        has_note(&repo, "HEAD"),
+        "amended commit should keep its note"
+    );
+}
+
+#[cfg(unix)]
+fn write_hook(path: &std::path::Path, script: &str) {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::write(path, script).unwrap();
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_post_rewrite_install_chains_existing_hook() {
+    let repo = TestRepo::new();
+    let hooks_dir = repo.path().join(".git").join("hooks");
+    std::fs::create_dir_all(&hooks_dir).unwrap();
+    let log = repo.path().join(".git").join("existing-hook.log");
+    write_hook(
+        &hooks_dir.join("post-rewrite"),
+        &format!(
+            "#!/bin/sh\necho \"$1\" >> '{}'\ncat >> '{}'\n",
+            log.display(),
+            log.display()
+        ),
+    );
+
+    let output = repo
+        .git_ai(&["post-rewrite", "install"])
+        .expect("install should chain the existing hook");
+    assert!(output.contains("chained to existing hook"), "{}", output);
+    assert!(hooks_dir.join("post-rewrite.pre-git-ai").is_file());
+
+    let mut file = repo.filename("app.txt");
+    file.set_contents(lines!["human line", "ai line".ai()]);
+    let commit = repo.stage_all_and_commit("AI commit").unwrap();
+    repo.git_og(&["commit", "--amend", "-m", "Reworded without git-ai"])
+        .unwrap();
+
+    // Both the existing hook and git-ai saw the rewrite
+    let logged = std::fs::read_to_string(&log).unwrap();
+    assert!(logged.starts_with("amend\n"), "{}", logged);
+    assert!(logged.contains(&commit.commit_sha), "{}", logged);
+    assert!(
+        has_note(&repo, "HEAD"),
+        "amended commit should keep its note"
+    );
+
+    // Re-installing keeps the chain rather than chaining git-ai's own hook
+    let output = repo.git_ai(&["post-rewrite", "install"]).unwrap();
+    assert!(output.contains("post-rewrite.pre-git-ai"), "{}", output);
+}
+
+#[test]
+fn test_post_rewrite_install_appends_to_husky_hooks() {
+    let repo = TestRepo::new();
+    let husky_dir = repo.path().join(".husky");
+    std::fs::create_dir_all(&husky_dir).unwrap();
+    std::fs::write(husky_dir.join("post-commit"), "npm run notify\n").unwrap();
+
+    repo.git_ai(&["post-rewrite", "install"])
+        .expect("install should succeed");
+    repo.git_ai(&["post-rewrite", "install"])
+        .expect("re-install should succeed");
+
+    let post_commit = std::fs::read_to_string(husky_dir.join("post-commit")).unwrap();
+    assert!(
+        post_commit.starts_with("npm run notify\n"),
+        "{}",
+        post_commit
+    );
+    assert_eq!(
+        post_commit
+            .matches("git-ai post-rewrite cherry-pick")
+            .count(),
+        1
+    );
+    let post_rewrite = std::fs::read_to_string(husky_dir.join("post-rewrite")).unwrap();
+    assert!(
+        post_rewrite.contains("git-ai post-rewrite \"$@\""),
+        "{}",
+        post_rewrite
+    );
+    assert!(
+        !repo
+            .path()
+            .join(".git")
+            .join("hooks")
+            .join("post-rewrite")
+            .exists()
+    );
+}
+
+#[test]
+fn test_post_rewrite_install_defers_to_lefthook() {
+    let repo = TestRepo::new();
+    std::fs::write(
+        repo.path().join("lefthook.yml"),
+        "pre-commit:\n  commands: {}\n",
+    )
+    .unwrap();
+
+    let err = repo
+        .git_ai(&["post-rewrite", "install"])
+        .expect_err("install should defer to lefthook");
+    assert!(err.contains("run: git-ai post-rewrite {0}"), "{}", err);
+    assert!(err.contains("use_stdin: true"), "{}", err);
+    assert!(
+        !repo
+            .path()
+            .join(".git")
+            .join("hooks")
+            .join("post-rewrite")
+            .exists()
+    );
+}
+
+#[test]
+fn test_checkout_and_merge_hooks_fetch_notes_at_most_once_per_interval() {
+    let (local, _upstream) = TestRepo::new_with_remote();
+    let mut file = local.filename("app.txt");
+    file.set_contents(lines!["human line", "ai line".ai()]);
+    local.stage_all_and_commit("AI commit").unwrap();
+    local.git(&["push", "-u", "origin", "HEAD"]).unwrap();
+    local.git_ai(&["sync"]).unwrap();
+    let head = local
+        .git_og(&["rev-parse", "HEAD"])
+        .unwrap()
+        .trim()
+        .to_string();
+
+    local
+        .git_og(&["update-ref", "-d", "refs/notes/ai"])
+        .unwrap();
+    // File checkouts (flag 0) don't fetch
+    local
+        .git_ai(&["post-rewrite", "checkout", &head, &head, "0"])
+        .unwrap();
+    assert!(!has_note(&local, "HEAD"));
+
+    local
+        .git_ai(&["post-rewrite", "checkout", &head, &head, "1"])
+        .unwrap();
+    assert!(
+        has_note(&local, "HEAD"),
+        "branch checkout should fetch notes"
+    );
+
+    // A second fetch within the interval is skipped
+    local
+        .git_og(&["update-ref", "-d", "refs/notes/ai"])
+        .unwrap();
+    local.git_ai(&["post-rewrite", "merge", "0"]).unwrap();
+    assert!(!has_note(&local, "HEAD"));
+
+    let stamp = local
+        .path()
+        .join(".git")
+        .join("ai")
+        .join("last-notes-fetch");
+    let twenty_minutes_ago = FileTime::from_unix_time(FileTime::now().unix_seconds() - 20 * 60, 0);
+    set_file_mtime(&stamp, twenty_minutes_ago).unwrap();
+    local.git_ai(&["post-rewrite", "merge", "0"]).unwrap();
+    assert!(
+        has_note(&local, "HEAD"),
+        "merge should fetch once the interval passed"
+    );
+}
+
+#[test]
+fn test_notes_auto_fetch_can_be_disabled() {
+    let (local, _upstream) = TestRepo::new_with_remote();
+    let mut file = local.filename("app.txt");
+    file.set_contents(lines!["ai line".ai()]);
+    local.stage_all_and_commit("AI commit").unwrap();
+    local.git(&["push", "-u", "origin", "HEAD"]).unwrap();
+    local.git_ai(&["sync"]).unwrap();
+
+    local
+        .git_og(&["update-ref", "-d", "refs/notes/ai"])
+        .unwrap();
+    local
+        .git_ai_with_env(
+            &["post-rewrite", "merge", "0"],
+            &[("GIT_AI_NOTES_AUTO_FETCH_MINUTES", "0")],
+        )
+        .unwrap();
+    assert!(!has_note(&local, "HEAD"));
+}
+
+#[test]
+fn test_post_rewrite_install_adds_checkout_and_merge_hooks() {
+    let repo = TestRepo::new();
+    repo.git_ai(&["post-rewrite", "install"])
+        .expect("install should succeed");
+
+    let hooks = repo.path().join(".git").join("hooks");
+    let post_checkout = std::fs::read_to_string(hooks.join("post-checkout")).unwrap();
+    assert!(
+        post_checkout.contains("post-rewrite checkout \"$@\""),
+        "{}",
+        post_checkout
+    );
+    let post_merge = std::fs::read_to_string(hooks.join("post-merge")).unwrap();
+    assert!(
+        post_merge.contains("post-rewrite merge \"$@\""),
+        "{}",
+        post_merge
+    );
+}
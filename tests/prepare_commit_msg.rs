@@ -0,0 +1,61 @@
+#[macro_use]
+mod repos;
+
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+fn head_message(repo: &TestRepo) -> String {
+    repo.git_og(&["log", "-1", "--format=%B"]).unwrap()
+}
+
+#[test]
+fn test_prepare_commit_msg_hook_appends_ai_trailer() {
+    let repo = TestRepo::new();
+    repo.git_ai(&["prepare-commit-msg", "install"])
+        .expect("install should succeed");
+
+    let mut file = repo.filename("app.txt");
+    file.set_contents(lines!["human line"]);
+    repo.stage_all_and_commit("Human commit").unwrap();
+    assert!(
+        !head_message(&repo).contains("AI-Assisted:"),
+        "commits without AI lines get no trailer"
+    );
+
+    file.set_contents(lines!["human line", "ai line 1".ai(), "ai line 2".ai()]);
+    repo.stage_all_and_commit("AI commit").unwrap();
+
+    let message = head_message(&repo);
+    assert!(
+        message.contains("AI-Assisted: 2/3 lines (mock_ai, unknown)"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn test_prepare_commit_msg_hook_counts_only_staged_lines() {
+    let repo = TestRepo::new();
+    repo.git_ai(&["prepare-commit-msg", "install"])
+        .expect("install should succeed");
+
+    let mut base = repo.filename("base.txt");
+    base.set_contents(lines!["base"]);
+    repo.stage_all_and_commit("base").unwrap();
+
+    let mut staged = repo.filename("staged.txt");
+    staged.set_contents(lines!["ai staged".ai(), "human staged"]);
+    let mut unstaged = repo.filename("unstaged.txt");
+    unstaged.set_contents(lines!["ai unstaged".ai()]);
+
+    // The test helpers stage everything; leave the second file out of the commit
+    repo.git_og(&["reset", "--quiet", "unstaged.txt"]).unwrap();
+    repo.git_og(&["commit", "-m", "Plain git commit"]).unwrap();
+
+    let message = head_message(&repo);
+    assert!(
+        message.contains("AI-Assisted: 1/2 lines (mock_ai, unknown)"),
+        "{}",
+        message
+    );
+}
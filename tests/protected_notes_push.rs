@@ -0,0 +1,86 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+fn write_hook(hook: &std::path::Path, script: &str) {
+    std::fs::write(hook, script).unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(hook, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+}
+
+/// Reject the whole push when it updates any notes ref, as hosts that check every ref before
+/// accepting one do
+fn protect_notes_refs(upstream: &TestRepo) {
+    write_hook(
+        &upstream.path().join("hooks").join("pre-receive"),
+        "#!/bin/sh\nwhile read old new ref; do\n  case \"$ref\" in refs/notes/*) echo \"refs/notes/* is protected\" >&2; exit 1;; esac\ndone\n",
+    );
+}
+
+#[test]
+fn test_push_with_rejected_notes_refspec_falls_back_to_the_branch() {
+    let (local, upstream) = TestRepo::new_with_remote();
+    protect_notes_refs(&upstream);
+
+    let mut file = local.filename("lib.rs");
+    file.set_contents(lines!["fn ai() {}".ai()]);
+    let commit = local.stage_all_and_commit("AI lib").unwrap();
+
+    let output = local
+        .git(&["push", "origin", "HEAD:refs/heads/main", "refs/notes/ai"])
+        .unwrap();
+    assert!(
+        output.contains("did not accept refs/notes/ai; pushing again without it"),
+        "{}",
+        output
+    );
+    assert!(output.contains("git-ai: hint:"), "{}", output);
+
+    let pushed = upstream.git_og(&["rev-parse", "refs/heads/main"]).unwrap();
+    assert_eq!(pushed.trim(), commit.commit_sha);
+    assert!(upstream.git_og(&["rev-parse", "refs/notes/ai"]).is_err());
+    let queued = local.path().join(".git").join("ai").join("pending-pushes");
+    assert!(queued.join("origin.json").exists());
+}
+
+#[test]
+fn test_push_failing_for_another_reason_is_not_retried() {
+    let (local, upstream) = TestRepo::new_with_remote();
+    protect_notes_refs(&upstream);
+
+    let mut file = local.filename("lib.rs");
+    file.set_contents(lines!["fn ai() {}".ai()]);
+    local.stage_all_and_commit("AI lib").unwrap();
+
+    // Only a notes refspec: retrying without it would push the current branch instead
+    let output = local.git(&["push", "origin", "refs/notes/ai"]).unwrap_err();
+    assert!(!output.contains("pushing again"), "{}", output);
+    assert!(upstream.git_og(&["rev-parse", "refs/heads/main"]).is_err());
+}
+
+#[test]
+fn test_push_stopped_by_a_pre_push_hook_is_not_retried() {
+    let (local, upstream) = TestRepo::new_with_remote();
+    write_hook(
+        &local.path().join(".git").join("hooks").join("pre-push"),
+        "#!/bin/sh\necho \"pre-push: blocked\" >&2\nexit 1\n",
+    );
+
+    let mut file = local.filename("lib.rs");
+    file.set_contents(lines!["fn ai() {}".ai()]);
+    local.stage_all_and_commit("AI lib").unwrap();
+
+    // The remote never saw the notes, but it didn't reject them either
+    let output = local
+        .git(&["push", "origin", "HEAD:refs/heads/main", "refs/notes/ai"])
+        .unwrap_err();
+    assert!(output.contains("pre-push: blocked"), "{}", output);
+    assert!(!output.contains("pushing again"), "{}", output);
+    assert!(upstream.git_og(&["rev-parse", "refs/heads/main"]).is_err());
+    let queued = local.path().join(".git").join("ai").join("pending-pushes");
+    assert!(!queued.join("origin.json").exists());
+}
@@ -425,3 +425,68 @@ fn test_pull_rebase_committed_and_autostash_preserves_all_authorship() {
     // Verify uncommitted AI authorship survived the autostash cycle
     uncommitted_ai.assert_lines_and_blame(vec!["Uncommitted AI line".ai()]);
 }
+
+#[test]
+fn test_push_after_pull_rebase_retires_pre_rebase_notes_on_remote() {
+    let (local, upstream) = TestRepo::new_with_remote();
+
+    let mut readme = local.filename("README.md");
+    readme.set_contents(vec!["# Test Repo".to_string()]);
+    local.stage_all_and_commit("initial commit").unwrap();
+    local.git(&["push", "-u", "origin", "HEAD"]).unwrap();
+    let main_branch = local.current_branch();
+
+    // Push an AI commit on a feature branch so the remote holds its note
+    local.git(&["checkout", "-b", "feature"]).unwrap();
+    let mut ai_file = local.filename("ai_feature.txt");
+    ai_file.set_contents(vec!["AI feature line".ai()]);
+    let old_sha = local
+        .stage_all_and_commit("add AI feature")
+        .unwrap()
+        .commit_sha;
+    local.git(&["push", "-u", "origin", "feature"]).unwrap();
+    assert!(
+        upstream
+            .git(&["notes", "--ref=ai", "show", &old_sha])
+            .is_ok(),
+        "remote should have the note for the pushed AI commit"
+    );
+
+    // Main moves on, and the feature branch is rebased onto it
+    local.git(&["checkout", &main_branch]).unwrap();
+    let mut other = local.filename("other.txt");
+    other.set_contents(vec!["human change".to_string()]);
+    local.stage_all_and_commit("main moves on").unwrap();
+    local.git(&["push", "origin", &main_branch]).unwrap();
+    local.git(&["checkout", "feature"]).unwrap();
+    local
+        .git(&["pull", "--rebase", "origin", &main_branch])
+        .unwrap();
+    let new_sha = local
+        .git(&["rev-parse", "HEAD"])
+        .unwrap()
+        .trim()
+        .to_string();
+    assert_ne!(old_sha, new_sha);
+
+    local
+        .git(&["push", "--force", "origin", "feature"])
+        .unwrap();
+
+    assert!(
+        upstream
+            .git(&["notes", "--ref=ai", "show", &new_sha])
+            .is_ok(),
+        "remote should have the note for the rebased commit"
+    );
+    assert!(
+        upstream
+            .git(&["notes", "--ref=ai", "show", &old_sha])
+            .is_err(),
+        "remote note for the pre-rebase commit should be retired"
+    );
+    assert!(
+        local.git(&["notes", "--ref=ai", "show", &old_sha]).is_err(),
+        "local note for the pre-rebase commit should be retired"
+    );
+}
@@ -0,0 +1,30 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+#[test]
+fn test_concurrent_checkpoints_keep_every_file() {
+    let repo = TestRepo::new();
+    let mut base = repo.filename("README.md");
+    base.set_contents(lines!["base"]);
+    repo.stage_all_and_commit("base").unwrap();
+
+    let names: Vec<String> = (0..6).map(|i| format!("file{}.txt", i)).collect();
+    for name in &names {
+        std::fs::write(repo.path().join(name), format!("ai line in {}\n", name)).unwrap();
+    }
+    std::thread::scope(|scope| {
+        for name in &names {
+            let repo = &repo;
+            scope.spawn(move || repo.git_ai(&["checkpoint", "mock_ai", name]).unwrap());
+        }
+    });
+
+    repo.stage_all_and_commit("AI files").unwrap();
+    for name in &names {
+        let mut file = repo.filename(name);
+        file.assert_lines_and_blame(lines![format!("ai line in {}", name).ai()]);
+    }
+    assert!(repo.path().join(".git").join("ai").join("lock").is_file());
+}
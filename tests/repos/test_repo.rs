@@ -47,6 +47,11 @@ impl TestRepo {
         config
             .set_str("user.email", "test@example.com")
             .expect("failed to initialize git2 repository");
+        // Git for Windows defaults to autocrlf=true, which would make expected file contents
+        // and line attributions differ from every other platform
+        config
+            .set_bool("core.autocrlf", false)
+            .expect("failed to initialize git2 repository");
 
         let mut repo = Self {
             path,
@@ -144,6 +149,9 @@ impl TestRepo {
         config
             .set_str("user.email", "test@example.com")
             .expect("failed to set user.email in mirror");
+        config
+            .set_bool("core.autocrlf", false)
+            .expect("failed to set core.autocrlf in mirror");
 
         let mut mirror = Self {
             path: mirror_path,
@@ -171,6 +179,9 @@ impl TestRepo {
         config
             .set_str("user.email", "test@example.com")
             .expect("failed to initialize git2 repository");
+        config
+            .set_bool("core.autocrlf", false)
+            .expect("failed to initialize git2 repository");
         let mut repo = Self {
             path: path.clone(),
             feature_flags: FeatureFlags::default(),
@@ -630,8 +641,14 @@ fn compile_binary() -> PathBuf {
     println!("Compiling git-ai binary for tests...");
 
     let manifest_dir = env!("CARGO_MANIFEST_DIR");
+    // Build with the same optional transports as the tests, so they exercise them
+    let features = if cfg!(feature = "libgit2") {
+        "test-support,libgit2"
+    } else {
+        "test-support"
+    };
     let output = Command::new("cargo")
-        .args(&["build", "--bin", "git-ai", "--features", "test-support"])
+        .args(&["build", "--bin", "git-ai", "--features", features])
         .current_dir(manifest_dir)
         .output()
         .expect("Failed to compile git-ai binary");
@@ -644,7 +661,10 @@ fn compile_binary() -> PathBuf {
         );
     }
 
-    let binary_path = PathBuf::from(manifest_dir).join("target/debug/git-ai");
+    let binary_path = PathBuf::from(manifest_dir)
+        .join("target")
+        .join("debug")
+        .join(format!("git-ai{}", std::env::consts::EXE_SUFFIX));
     binary_path
 }
 
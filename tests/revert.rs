@@ -0,0 +1,96 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+/// The JSON metadata below the divider of `rev`'s note
+fn note_metadata(repo: &TestRepo, rev: &str) -> serde_json::Value {
+    let note = repo
+        .git_og(&["notes", "--ref=ai", "show", rev])
+        .unwrap_or_else(|e| panic!("{} should have a note: {}", rev, e));
+    let metadata: Vec<&str> = note
+        .lines()
+        .skip_while(|line| *line != "---")
+        .skip(1)
+        .collect();
+    serde_json::from_str(&metadata.join("\n")).unwrap()
+}
+
+fn json_line(output: &str) -> serde_json::Value {
+    let line = output
+        .lines()
+        .find(|line| line.starts_with('{'))
+        .unwrap_or_else(|| panic!("no JSON object in {}", output));
+    serde_json::from_str(line).unwrap()
+}
+
+#[test]
+fn test_revert_records_reverted_ai_lines_and_stats_reports_them() {
+    let repo = TestRepo::new();
+    let mut readme = repo.filename("README.md");
+    readme.set_contents(lines!["readme"]);
+    repo.stage_all_and_commit("base").unwrap();
+
+    let mut lib = repo.filename("lib.rs");
+    lib.set_contents(lines!["fn a() {}".ai(), "fn b() {}".ai(), "fn c() {}".ai()]);
+    let ai_commit = repo.stage_all_and_commit("AI lib").unwrap();
+
+    repo.git(&["revert", "--no-edit", "HEAD"]).unwrap();
+
+    let metadata = note_metadata(&repo, "HEAD");
+    assert_eq!(metadata["reverted"]["commit"], ai_commit.commit_sha);
+    assert_eq!(metadata["reverted"]["ai_lines"], 3);
+    assert_eq!(metadata["reverted"]["agents"][0]["lines"], 3);
+    // Ordinary commits don't carry the field
+    assert!(note_metadata(&repo, "HEAD~1").get("reverted").is_none());
+
+    let json = json_line(&repo.git_ai(&["stats", "--reverts", "--json"]).unwrap());
+    assert_eq!(json["ai_lines"], 3);
+    assert_eq!(json["ai_lines_reverted"], 3);
+    assert_eq!(json["reverted_percent"], 100.0);
+    assert_eq!(json["rows"][0]["ai_lines_reverted"], 3);
+    assert_eq!(json["reverts"][0]["reverted_commit"], ai_commit.commit_sha);
+    assert_eq!(json["reverts"][0]["subject"], "Revert \"AI lib\"");
+
+    let text = repo.git_ai(&["stats", "--reverts"]).unwrap();
+    assert!(text.contains("Revert \"AI lib\""), "{}", text);
+    assert!(text.contains("100.0%"), "{}", text);
+}
+
+#[test]
+fn test_revert_no_commit_is_recorded_by_the_finishing_commit() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("app.txt");
+    file.set_contents(lines!["base"]);
+    repo.stage_all_and_commit("base").unwrap();
+    file.set_contents(lines!["base", "ai one".ai(), "ai two".ai()]);
+    repo.stage_all_and_commit("AI change").unwrap();
+
+    repo.git(&["revert", "--no-commit", "HEAD"]).unwrap();
+    repo.git(&["commit", "--no-edit"]).unwrap();
+
+    let metadata = note_metadata(&repo, "HEAD");
+    assert_eq!(metadata["reverted"]["ai_lines"], 2);
+}
+
+#[test]
+fn test_plain_git_revert_records_reverted_ai_lines() {
+    let repo = TestRepo::new();
+    repo.git_ai(&["post-rewrite", "install"])
+        .expect("install should succeed");
+
+    let mut file = repo.filename("feature.txt");
+    file.set_contents(lines!["ai line".ai()]);
+    repo.stage_all_and_commit("AI feature").unwrap();
+
+    repo.git_og(&["revert", "--no-edit", "HEAD"]).unwrap();
+
+    let metadata = note_metadata(&repo, "HEAD");
+    assert_eq!(metadata["reverted"]["ai_lines"], 1);
+
+    // Stats still counts a revert whose note predates recording, from the reverted commit
+    repo.git_og(&["notes", "--ref=ai", "remove", "HEAD"])
+        .unwrap();
+    let json = json_line(&repo.git_ai(&["stats", "--reverts", "--json"]).unwrap());
+    assert_eq!(json["ai_lines_reverted"], 1);
+}
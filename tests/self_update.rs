@@ -0,0 +1,37 @@
+mod repos;
+
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+#[test]
+fn test_commit_warns_when_required_version_is_not_met() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("lib.rs");
+
+    file.set_contents(vec!["fn one() {}".human()]);
+    repo.git(&["add", "-A"]).unwrap();
+    let output = repo
+        .git_with_env(
+            &["commit", "-m", "first"],
+            &[("GIT_AI_REQUIRED_VERSION", ">=999.0.0")],
+            None,
+        )
+        .unwrap();
+    assert!(
+        output.contains("git-ai >=999.0.0 is required here"),
+        "{}",
+        output
+    );
+
+    file.set_contents(vec!["fn one() {}".human(), "fn two() {}".human()]);
+    repo.git(&["add", "-A"]).unwrap();
+    let current = format!("={}", env!("CARGO_PKG_VERSION"));
+    let output = repo
+        .git_with_env(
+            &["commit", "-m", "second"],
+            &[("GIT_AI_REQUIRED_VERSION", current.as_str())],
+            None,
+        )
+        .unwrap();
+    assert!(!output.contains("is required here"), "{}", output);
+}
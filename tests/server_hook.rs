@@ -0,0 +1,88 @@
+mod repos;
+
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+#[test]
+fn test_pre_receive_rejects_commits_without_notes() {
+    let (local, upstream) = TestRepo::new_with_remote();
+    upstream
+        .git_ai(&["server-hook", "install", "--mode", "reject", "--wait", "5"])
+        .expect("install should succeed");
+
+    // Commits made through git-ai carry notes, which are pushed alongside the branch
+    let mut file = local.filename("feature.txt");
+    file.set_contents(vec!["AI line".ai()]);
+    local.stage_all_and_commit("noted commit").unwrap();
+    local
+        .git(&["push", "-u", "origin", "HEAD"])
+        .expect("push with notes should be accepted");
+
+    // Plain git leaves no note behind
+    local
+        .git_og(&["commit", "--allow-empty", "-m", "unnoted commit"])
+        .unwrap();
+    let branch = local.current_branch();
+    let err = local
+        .git_og(&["push", "origin", &branch])
+        .expect_err("push without notes should be rejected");
+    assert!(err.contains("push rejected"), "{}", err);
+}
+
+#[test]
+fn test_post_receive_tags_unverified_commits_until_notes_arrive() {
+    let (local, upstream) = TestRepo::new_with_remote();
+    upstream
+        .git_ai(&["server-hook", "install", "--mode", "tag", "--wait", "0"])
+        .expect("install should succeed");
+
+    local
+        .git_og(&["commit", "--allow-empty", "-m", "unnoted commit"])
+        .unwrap();
+    let sha = local
+        .git_og(&["rev-parse", "HEAD"])
+        .unwrap()
+        .trim()
+        .to_string();
+    let branch = local.current_branch();
+    local
+        .git_og(&["push", "origin", &branch])
+        .expect("tag mode should accept the push");
+    assert!(
+        upstream
+            .git_og(&["notes", "--ref=ai-unverified", "show", &sha])
+            .is_ok(),
+        "commit should be marked unverified"
+    );
+
+    // A late notes push verifies the commit
+    local
+        .git_og(&["notes", "--ref=ai", "add", "-m", "late note", &sha])
+        .unwrap();
+    local
+        .git_og(&["push", "origin", "refs/notes/ai:refs/notes/ai"])
+        .unwrap();
+    assert!(
+        upstream
+            .git_og(&["notes", "--ref=ai-unverified", "show", &sha])
+            .is_err(),
+        "unverified marker should be cleared once the note arrives"
+    );
+}
+
+#[test]
+fn test_install_refuses_to_overwrite_foreign_hook() {
+    let (_local, upstream) = TestRepo::new_with_remote();
+    let hook_path = upstream.path().join("hooks").join("pre-receive");
+    std::fs::create_dir_all(hook_path.parent().unwrap()).unwrap();
+    std::fs::write(&hook_path, "#!/bin/sh\nexit 0\n").unwrap();
+
+    assert!(upstream.git_ai(&["server-hook", "install"]).is_err());
+    upstream
+        .git_ai(&["server-hook", "install", "--force"])
+        .expect("--force should replace the hook");
+    // Reinstalling over our own hook needs no --force
+    upstream
+        .git_ai(&["server-hook", "install"])
+        .expect("reinstall should succeed");
+}
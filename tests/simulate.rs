@@ -0,0 +1,121 @@
+#[macro_use]
+mod repos;
+use git_ai::commands::simulate::simulate_push;
+use git_ai::git::refs::tracking_ref_for_remote;
+use git_ai::git::test_utils::local_remote::{LOCAL_REMOTE_DEFAULT_BRANCH, LocalRemote};
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+#[test]
+fn test_simulate_commit_shows_the_note_and_event_without_writing_them() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("lib.rs");
+    file.set_contents(lines!["fn base() {}".human()]);
+    repo.stage_all_and_commit("base").unwrap();
+    let head = repo.git_og(&["rev-parse", "HEAD"]).unwrap();
+    let notes_before = repo.git_og(&["notes", "--ref=ai", "list"]).unwrap();
+
+    file.set_contents(lines!["fn base() {}".human(), "fn ai() {}".ai()]);
+    repo.git(&["add", "-A"]).unwrap();
+
+    let output = repo
+        .git_ai(&["simulate", "--format", "json", "commit", "-m", "Add ai"])
+        .unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(output.lines().last().unwrap()).expect(&output);
+    assert_eq!(json["staged"]["ai_lines"], 1, "{}", output);
+    assert_eq!(json["staged"]["files"][0]["path"], "lib.rs", "{}", output);
+    assert!(
+        json["note"].as_str().unwrap().starts_with("lib.rs"),
+        "{}",
+        output
+    );
+    assert_eq!(json["stats"]["ai_accepted"], 1, "{}", output);
+    // Event 1 is `committed`; position 11 of its values is the commit subject
+    assert_eq!(json["event"]["e"], 1, "{}", output);
+    assert_eq!(json["event"]["v"]["11"], "Add ai", "{}", output);
+
+    let text = repo.git_ai(&["simulate", "commit"]).unwrap();
+    assert!(text.contains("added lines: 1 AI,"), "{}", text);
+    assert!(text.contains("Note for refs/notes/ai:"), "{}", text);
+
+    assert_eq!(repo.git_og(&["rev-parse", "HEAD"]).unwrap(), head);
+    assert_eq!(
+        repo.git_og(&["notes", "--ref=ai", "list"]).unwrap(),
+        notes_before
+    );
+}
+
+#[test]
+fn test_simulate_push_reports_branch_and_notes_modes() {
+    let remote = LocalRemote::new().unwrap();
+    let alice = remote.clone_repo().unwrap();
+    let first = alice.commit("a.txt", "a\n", "a").unwrap();
+    alice.add_note(&first, "note a").unwrap();
+    alice.push(LOCAL_REMOTE_DEFAULT_BRANCH).unwrap();
+    alice.push_notes("origin").unwrap();
+    alice.fetch_notes("origin").unwrap();
+
+    let second = alice.commit("b.txt", "b\n", "b").unwrap();
+    alice.add_note(&second, "note b").unwrap();
+    let unnoted = alice.commit("c.txt", "c\n", "c").unwrap();
+
+    let args = vec![
+        "origin".to_string(),
+        LOCAL_REMOTE_DEFAULT_BRANCH.to_string(),
+    ];
+    let simulation = simulate_push(alice.gitai_repo(), &args).unwrap();
+    assert_eq!(simulation.remote.as_deref(), Some("origin"));
+    assert_eq!(simulation.refs.len(), 1);
+    assert_eq!(simulation.refs[0].destination, "refs/heads/main");
+    assert_eq!(simulation.refs[0].mode, "fast-forward");
+    assert_eq!(simulation.commits.len(), 2);
+    assert!(
+        simulation
+            .commits
+            .iter()
+            .any(|c| c.commit == unnoted && !c.noted)
+    );
+    assert_eq!(simulation.notes.len(), 1);
+    assert_eq!(simulation.notes[0].refspec, "refs/notes/ai:refs/notes/ai");
+    assert!(!simulation.notes[0].forced);
+    assert_eq!(simulation.notes[0].mode, "fast-forward");
+
+    // Bob pushes a commit and a note first, so both of Alice's refs have diverged
+    let bob = remote.clone_repo().unwrap();
+    bob.fetch_notes("origin").unwrap();
+    let bobs = bob.commit("d.txt", "d\n", "d").unwrap();
+    bob.add_note(&bobs, "note d").unwrap();
+    bob.push(LOCAL_REMOTE_DEFAULT_BRANCH).unwrap();
+    bob.push_notes("origin").unwrap();
+    alice.git(&["fetch", "--quiet", "origin"]).unwrap();
+    alice
+        .git(&[
+            "fetch",
+            "--quiet",
+            "origin",
+            &format!("+refs/notes/ai:{}", tracking_ref_for_remote("origin")),
+        ])
+        .unwrap();
+
+    let simulation = simulate_push(alice.gitai_repo(), &args).unwrap();
+    assert_eq!(simulation.refs[0].mode, "rejected");
+    assert_eq!(simulation.notes[0].mode, "merge");
+
+    let forced = vec![
+        "origin".to_string(),
+        format!("+{}", LOCAL_REMOTE_DEFAULT_BRANCH),
+    ];
+    let simulation = simulate_push(alice.gitai_repo(), &forced).unwrap();
+    assert_eq!(simulation.refs[0].mode, "forced");
+    assert!(!simulation.notes[0].forced);
+
+    let dry_run = vec!["--dry-run".to_string(), "origin".to_string()];
+    let simulation = simulate_push(alice.gitai_repo(), &dry_run).unwrap();
+    assert!(simulation.notes.is_empty());
+    assert!(simulation.notes_skipped.is_some());
+
+    // Nothing was pushed
+    assert_eq!(remote.note(&second), None);
+    assert_eq!(remote.note(&bobs), Some("note d".to_string()));
+}
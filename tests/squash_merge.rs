@@ -293,3 +293,38 @@ fn test_prepare_working_log_squash_with_mixed_additions() {
         "Sum of accepted_lines across prompts should match ai_accepted stat"
     );
 }
+
+/// Test that a squash merge done on the server (e.g. GitHub "Squash and merge")
+/// picks up the feature branch's AI attribution when it is pulled
+#[test]
+fn test_pulled_remote_squash_merge_inherits_branch_authorship() {
+    let (local, _upstream) = TestRepo::new_with_remote();
+    let mut file = local.filename("main.txt");
+
+    file.set_contents(lines!["line 1", "line 2", ""]);
+    local.stage_all_and_commit("Initial commit").unwrap();
+    local.git(&["push", "-u", "origin", "HEAD"]).unwrap();
+    let default_branch = local.current_branch();
+
+    local.git(&["checkout", "-b", "feature"]).unwrap();
+    file.insert_at(2, lines!["// AI added feature".ai()]);
+    local.stage_all_and_commit("Add AI feature").unwrap();
+    file.insert_at(3, lines!["// Human refinement"]);
+    local.stage_all_and_commit("Human refinement").unwrap();
+
+    // Squash the branch without git-ai, push it, then rewind so the pull brings it in
+    local.git_og(&["checkout", &default_branch]).unwrap();
+    local.git_og(&["merge", "--squash", "feature"]).unwrap();
+    local.git_og(&["commit", "-m", "Add feature (#1)"]).unwrap();
+    local.git_og(&["push", "origin", "HEAD"]).unwrap();
+    local.git_og(&["reset", "--hard", "HEAD~1"]).unwrap();
+
+    local.git(&["pull"]).unwrap();
+
+    file.assert_lines_and_blame(lines![
+        "line 1".human(),
+        "line 2".human(),
+        "// AI added feature".ai(),
+        "// Human refinement".human()
+    ]);
+}
@@ -4,6 +4,7 @@ use insta::assert_debug_snapshot;
 use repos::test_file::ExpectedLineExt;
 use repos::test_repo::TestRepo;
 use serde_json;
+use std::fs;
 
 /// Extract the first complete JSON object from mixed stdout/stderr output.
 fn extract_json_object(output: &str) -> String {
@@ -221,6 +222,7 @@ fn test_markdown_stats_deletion_only() {
         git_diff_deleted_lines: 5,
         git_diff_added_lines: 0,
         tool_model_breakdown: BTreeMap::new(),
+        token_usage: None,
     };
 
     let markdown = write_stats_to_markdown(&stats);
@@ -244,6 +246,7 @@ fn test_markdown_stats_all_human() {
         git_diff_deleted_lines: 0,
         git_diff_added_lines: 10,
         tool_model_breakdown: BTreeMap::new(),
+        token_usage: None,
     };
 
     let markdown = write_stats_to_markdown(&stats);
@@ -267,6 +270,7 @@ fn test_markdown_stats_all_ai() {
         git_diff_deleted_lines: 0,
         git_diff_added_lines: 15,
         tool_model_breakdown: BTreeMap::new(),
+        token_usage: None,
     };
 
     let markdown = write_stats_to_markdown(&stats);
@@ -290,6 +294,7 @@ fn test_markdown_stats_mixed() {
         git_diff_deleted_lines: 5,
         git_diff_added_lines: 30,
         tool_model_breakdown: BTreeMap::new(),
+        token_usage: None,
     };
 
     let markdown = write_stats_to_markdown(&stats);
@@ -313,6 +318,7 @@ fn test_markdown_stats_no_mixed() {
         git_diff_deleted_lines: 0,
         git_diff_added_lines: 20,
         tool_model_breakdown: BTreeMap::new(),
+        token_usage: None,
     };
 
     let markdown = write_stats_to_markdown(&stats);
@@ -337,6 +343,7 @@ fn test_markdown_stats_minimal_human() {
         git_diff_deleted_lines: 0,
         git_diff_added_lines: 100,
         tool_model_breakdown: BTreeMap::new(),
+        token_usage: None,
     };
 
     let markdown = write_stats_to_markdown(&stats);
@@ -373,9 +380,280 @@ fn test_markdown_stats_formatting() {
         git_diff_deleted_lines: 2,
         git_diff_added_lines: 13,
         tool_model_breakdown,
+        token_usage: None,
     };
 
     let markdown = write_stats_to_markdown(&stats);
     println!("{}", markdown);
     assert_debug_snapshot!(markdown);
 }
+
+#[test]
+fn test_stats_range_excludes_bot_commits_by_default() {
+    let repo = TestRepo::new();
+
+    let mut file = repo.filename("app.txt");
+    file.set_contents(lines!["Line 1".human()]);
+    let first = repo.stage_all_and_commit("Initial human").unwrap();
+
+    file.set_contents(lines!["Line 1".human(), "Line 2".ai()]);
+    repo.stage_all_and_commit("AI adds line").unwrap();
+
+    // A dependency bot bumps a lockfile with lots of lines
+    let mut lockfile = repo.filename("deps.lock");
+    lockfile.set_contents(lines!["a", "b", "c", "d", "e"]);
+    repo.git(&["add", "-A"]).unwrap();
+    repo.git(&[
+        "commit",
+        "--author=dependabot[bot] <49699333+dependabot[bot]@users.noreply.github.com>",
+        "-m",
+        "Bump deps",
+    ])
+    .unwrap();
+    let head = repo.git(&["rev-parse", "HEAD"]).unwrap().trim().to_string();
+    let range = format!("{}..{}", first.commit_sha, head);
+
+    let raw = repo.git_ai(&["stats", &range, "--json"]).unwrap();
+    let without_bots: git_ai::authorship::range_authorship::RangeAuthorshipStats =
+        serde_json::from_str(&extract_json_object(&raw)).unwrap();
+    assert_eq!(without_bots.authorship_stats.total_commits, 1);
+    assert_eq!(without_bots.authorship_stats.bot_commits_excluded, 1);
+
+    let raw = repo
+        .git_ai(&["stats", &range, "--json", "--include-bots"])
+        .unwrap();
+    let with_bots: git_ai::authorship::range_authorship::RangeAuthorshipStats =
+        serde_json::from_str(&extract_json_object(&raw)).unwrap();
+    assert_eq!(with_bots.authorship_stats.total_commits, 2);
+    assert_eq!(with_bots.authorship_stats.bot_commits_excluded, 0);
+
+    // The bot's 5 lockfile lines only show up when bots are included
+    assert_eq!(
+        with_bots.range_stats.git_diff_added_lines,
+        without_bots.range_stats.git_diff_added_lines + 5
+    );
+    assert_eq!(
+        with_bots.range_stats.human_additions,
+        without_bots.range_stats.human_additions + 5
+    );
+    assert_eq!(
+        with_bots.range_stats.ai_additions,
+        without_bots.range_stats.ai_additions
+    );
+}
+
+#[test]
+fn test_stats_range_counts_human_edits_to_bot_lines() {
+    let repo = TestRepo::new();
+
+    let mut file = repo.filename("app.txt");
+    file.set_contents(lines!["Line 1".human()]);
+    let first = repo.stage_all_and_commit("Initial human").unwrap();
+
+    let mut lockfile = repo.filename("deps.lock");
+    lockfile.set_contents(lines!["a", "b", "c", "d", "e"]);
+    repo.git(&["add", "-A"]).unwrap();
+    repo.git(&[
+        "commit",
+        "--author=dependabot[bot] <49699333+dependabot[bot]@users.noreply.github.com>",
+        "-m",
+        "Bump deps",
+    ])
+    .unwrap();
+
+    // A human rewrites every line the bot added
+    lockfile.set_contents(lines!["A", "B", "C", "D", "E"]);
+    repo.stage_all_and_commit("Pin deps by hand").unwrap();
+    let head = repo.git(&["rev-parse", "HEAD"]).unwrap().trim().to_string();
+    let range = format!("{}..{}", first.commit_sha, head);
+
+    let raw = repo.git_ai(&["stats", &range, "--json"]).unwrap();
+    let stats: git_ai::authorship::range_authorship::RangeAuthorshipStats =
+        serde_json::from_str(&extract_json_object(&raw)).unwrap();
+    assert_eq!(stats.authorship_stats.bot_commits_excluded, 1);
+    assert_eq!(stats.range_stats.git_diff_added_lines, 5);
+    assert_eq!(stats.range_stats.git_diff_deleted_lines, 5);
+    assert_eq!(stats.range_stats.human_additions, 5);
+}
+
+#[test]
+fn test_stats_range_respects_mailmap() {
+    let repo = TestRepo::new();
+
+    let mut file = repo.filename("app.txt");
+    file.set_contents(lines!["Line 1".human()]);
+    let first = repo.stage_all_and_commit("Initial human").unwrap();
+
+    std::fs::write(
+        repo.path().join(".mailmap"),
+        "Canonical Dev <dev@example.com> Old Alias <alias@example.com>\n",
+    )
+    .unwrap();
+    file.set_contents(lines!["Line 1".human(), "Line 2".human()]);
+    repo.git(&["add", "-A"]).unwrap();
+    repo.git(&[
+        "commit",
+        "--author=Old Alias <alias@example.com>",
+        "-m",
+        "Commit under old identity",
+    ])
+    .unwrap();
+    let head = repo.git(&["rev-parse", "HEAD"]).unwrap().trim().to_string();
+    let range = format!("{}..{}", first.commit_sha, head);
+
+    let raw = repo.git_ai(&["stats", &range, "--json"]).unwrap();
+    let stats: git_ai::authorship::range_authorship::RangeAuthorshipStats =
+        serde_json::from_str(&extract_json_object(&raw)).unwrap();
+    let authors: Vec<&String> = stats
+        .authorship_stats
+        .authors_committing_authorship
+        .iter()
+        .chain(stats.authorship_stats.authors_not_committing_authorship.iter())
+        .collect();
+    assert_eq!(authors, vec!["Canonical Dev <dev@example.com>"]);
+}
+
+#[test]
+fn test_stats_report_groups_history_by_author_and_tool() {
+    let repo = TestRepo::new();
+
+    let mut file = repo.filename("app.txt");
+    file.set_contents(lines!["Line 1".human(), "Line 2".human()]);
+    repo.stage_all_and_commit("Initial human").unwrap();
+
+    file.set_contents(lines![
+        "Line 1".human(),
+        "Line 2".human(),
+        "Line 3".ai(),
+        "Line 4".ai(),
+        "Line 5".ai(),
+    ]);
+    repo.stage_all_and_commit("AI adds lines").unwrap();
+
+    let raw = repo
+        .git_ai(&["stats", "report", "--format", "json"])
+        .unwrap();
+    let report: serde_json::Value = serde_json::from_str(&extract_json_object(&raw)).unwrap();
+    assert_eq!(report["by"], "author");
+    assert_eq!(report["total_commits"], 2);
+    assert_eq!(report["ai_lines"], 3);
+    // "Line 2" gains a trailing newline in the AI commit, so git counts it as re-added
+    assert_eq!(report["human_lines"], 3);
+    assert_eq!(report["rows"].as_array().unwrap().len(), 1);
+    assert_eq!(report["rows"][0]["commits"], 2);
+
+    let csv = repo
+        .git_ai(&["stats", "report", "--by", "tool", "--format", "csv"])
+        .unwrap();
+    let lines: Vec<&str> = csv.lines().filter(|l| l.contains(',')).collect();
+    assert_eq!(
+        lines,
+        vec![
+            "tool,commits,ai_lines,human_lines,ai_percent",
+            "mock_ai,1,3,0,100.0",
+            "human,2,0,3,0.0",
+        ]
+    );
+
+    let raw = repo
+        .git_ai(&[
+            "stats",
+            "report",
+            "--until",
+            "2000-01-01",
+            "--format",
+            "json",
+        ])
+        .unwrap();
+    let report: serde_json::Value = serde_json::from_str(&extract_json_object(&raw)).unwrap();
+    assert_eq!(report["total_commits"], 0);
+    assert_eq!(report["until"], "2000-01-01");
+}
+
+/// AI checkpoint through the agent-v1 preset, reporting running token totals for the session
+fn checkpoint_with_usage(repo: &TestRepo, file: &str, input_tokens: u64, output_tokens: u64) {
+    let hook_input = serde_json::json!({
+        "type": "ai_agent",
+        "repo_working_dir": repo.path().to_str().unwrap(),
+        "edited_filepaths": [file],
+        "transcript": {"messages": []},
+        "agent_name": "test-agent",
+        "model": "test-model",
+        "conversation_id": "usage-session",
+        "usage": {"input_tokens": input_tokens, "output_tokens": output_tokens, "cost_usd": 0.01 * input_tokens as f64 / 1000.0},
+    });
+    repo.git_ai(&[
+        "checkpoint",
+        "agent-v1",
+        "--hook-input",
+        &hook_input.to_string(),
+    ])
+    .unwrap();
+}
+
+#[test]
+fn test_stats_include_token_usage_spent_on_each_commit() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("app.txt");
+    file.set_contents(lines!["Line 1".human()]);
+    repo.stage_all_and_commit("Initial human").unwrap();
+
+    fs::write(repo.path().join("app.txt"), "Line 1\nLine 2\n").unwrap();
+    checkpoint_with_usage(&repo, "app.txt", 1000, 100);
+    fs::write(repo.path().join("app.txt"), "Line 1\nLine 2\nLine 3\n").unwrap();
+    checkpoint_with_usage(&repo, "app.txt", 1500, 160);
+    let first = repo.stage_all_and_commit("AI adds lines").unwrap();
+
+    let prompt = first
+        .authorship_log
+        .metadata
+        .prompts
+        .values()
+        .next()
+        .unwrap();
+    let usage = prompt.token_usage.expect("usage recorded in the note");
+    assert_eq!((usage.input_tokens, usage.output_tokens), (1500, 160));
+
+    // The session keeps going: the next commit only counts what was spent since
+    fs::write(
+        repo.path().join("app.txt"),
+        "Line 1\nLine 2\nLine 3\nLine 4\n",
+    )
+    .unwrap();
+    checkpoint_with_usage(&repo, "app.txt", 2000, 200);
+    repo.stage_all_and_commit("AI adds another line").unwrap();
+
+    let raw = repo.git_ai(&["stats", "--json"]).unwrap();
+    let stats: CommitStats = serde_json::from_str(&extract_json_object(&raw)).unwrap();
+    let usage = stats.token_usage.expect("usage in stats");
+    assert_eq!((usage.input_tokens, usage.output_tokens), (500, 40));
+    assert!((usage.cost_usd.unwrap() - 0.005).abs() < 1e-9);
+}
+
+#[test]
+fn test_stats_and_blame_skip_gitaiignore_and_generated_files() {
+    let repo = TestRepo::new();
+
+    let mut ignore = repo.filename(".gitaiignore");
+    ignore.set_contents(lines!["generated/".human()]);
+    repo.stage_all_and_commit("Ignore generated clients").unwrap();
+
+    let mut app = repo.filename("app.txt");
+    app.set_contents(lines!["Line 1".ai(), "Line 2".human()]);
+    let mut client = repo.filename("generated/client.ts");
+    client.set_contents(lines!["export const a = 1;".ai(), "export const b = 2;".ai()]);
+    let mut lockfile = repo.filename("package-lock.json");
+    lockfile.set_contents(lines!["{".ai(), "}".ai()]);
+    let commit = repo.stage_all_and_commit("AI adds code").unwrap();
+
+    let raw = repo
+        .git_ai(&["stats", &commit.commit_sha, "--json"])
+        .unwrap();
+    let stats: CommitStats = serde_json::from_str(&extract_json_object(&raw)).unwrap();
+    assert_eq!(stats.git_diff_added_lines, 2);
+    assert_eq!(stats.ai_additions, 1);
+    assert_eq!(stats.human_additions, 1);
+
+    let blame = repo.git_ai(&["blame", "generated/client.ts"]).unwrap();
+    assert!(!blame.contains("mock_ai"), "{}", blame);
+}
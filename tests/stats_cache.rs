@@ -0,0 +1,97 @@
+#[macro_use]
+mod repos;
+use git_ai::authorship::stats::CommitStats;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+use std::fs;
+use std::path::PathBuf;
+
+fn cache_path(repo: &TestRepo) -> PathBuf {
+    repo.path()
+        .join(".git")
+        .join("ai")
+        .join("cache")
+        .join("stats.json")
+}
+
+fn stats_for(repo: &TestRepo, rev: &str) -> CommitStats {
+    let raw = repo.git_ai(&["stats", rev, "--json"]).unwrap();
+    let json = &raw[raw.find('{').unwrap()..=raw.rfind('}').unwrap()];
+    serde_json::from_str(json).unwrap()
+}
+
+/// Overwrite the cached `ai_additions` for `sha` so a cache hit is observable
+fn tamper_cached_stats(repo: &TestRepo, sha: &str) {
+    let path = cache_path(repo);
+    let mut cache: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    // Entries are keyed by ignore patterns, which include the generated-file defaults
+    for stats in cache["commits"][sha]["stats"]
+        .as_object_mut()
+        .unwrap()
+        .values_mut()
+    {
+        stats["ai_additions"] = serde_json::json!(999);
+    }
+    fs::write(&path, cache.to_string()).unwrap();
+}
+
+fn ai_commit(repo: &TestRepo, name: &str) -> String {
+    let mut file = repo.filename(name);
+    file.set_contents(lines!["one".ai(), "two".ai()]);
+    repo.stage_all_and_commit(name).unwrap().commit_sha
+}
+
+#[test]
+fn test_stats_cache_is_reused_until_the_commit_note_changes() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "git-ai.statsCache", "true"]).unwrap();
+    let first = ai_commit(&repo, "a.txt");
+
+    assert_eq!(stats_for(&repo, &first).ai_additions, 2);
+    assert!(cache_path(&repo).exists());
+    tamper_cached_stats(&repo, &first);
+    assert_eq!(stats_for(&repo, &first).ai_additions, 999);
+
+    // Another commit moves the notes tip but leaves the first commit's entry alone
+    let second = ai_commit(&repo, "b.txt");
+    assert_eq!(stats_for(&repo, &first).ai_additions, 999);
+    assert_eq!(stats_for(&repo, &second).ai_additions, 2);
+
+    // Removing the first commit's note drops its entry
+    repo.git(&["notes", "--ref=ai", "remove", &first]).unwrap();
+    assert_eq!(stats_for(&repo, &first).ai_additions, 0);
+}
+
+#[test]
+fn test_stats_cache_disabled_writes_nothing() {
+    let repo = TestRepo::new();
+    repo.git(&["config", "git-ai.statsCache", "false"]).unwrap();
+    let sha = ai_commit(&repo, "a.txt");
+
+    assert_eq!(stats_for(&repo, &sha).ai_additions, 2);
+    assert!(!cache_path(&repo).exists());
+}
+
+#[test]
+fn test_stats_report_is_the_same_with_and_without_the_cache() {
+    let repo = TestRepo::new();
+    ai_commit(&repo, "a.txt");
+    let mut file = repo.filename("b.txt");
+    file.set_contents(lines!["human".human(), "ai".ai()]);
+    repo.stage_all_and_commit("mixed").unwrap();
+
+    let report = |repo: &TestRepo| -> serde_json::Value {
+        let raw = repo
+            .git_ai(&["stats", "report", "--by", "tool", "--format", "json"])
+            .unwrap();
+        serde_json::from_str(&raw[raw.find('{').unwrap()..=raw.rfind('}').unwrap()]).unwrap()
+    };
+    repo.git(&["config", "git-ai.statsCache", "false"]).unwrap();
+    let uncached = report(&repo);
+    repo.git(&["config", "git-ai.statsCache", "true"]).unwrap();
+    assert_eq!(report(&repo), uncached);
+    assert!(cache_path(&repo).exists());
+    // Served from the cache file on the second run
+    assert_eq!(report(&repo), uncached);
+}
@@ -0,0 +1,98 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+use std::net::UdpSocket;
+use std::time::Duration;
+
+/// Everything received on `agent` until it has been quiet for a second
+fn drain(agent: &UdpSocket) -> String {
+    agent
+        .set_read_timeout(Some(Duration::from_secs(1)))
+        .unwrap();
+    let mut received = String::new();
+    let mut buf = [0u8; 65536];
+    while let Ok(len) = agent.recv(&mut buf) {
+        received.push_str(&String::from_utf8_lossy(&buf[..len]));
+        received.push('\n');
+    }
+    received
+}
+
+#[test]
+fn test_commit_sends_committed_metrics_to_statsd() {
+    let agent = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let addr = agent.local_addr().unwrap().to_string();
+    let repo = TestRepo::new();
+
+    let mut file = repo.filename("a.txt");
+    file.set_contents(lines!["human".human(), "ai one".ai(), "ai two".ai()]);
+    repo.git(&["add", "-A"]).unwrap();
+    repo.commit_with_env(
+        "AI commit",
+        &[
+            ("GIT_AI_METRICS_SINK", "statsd"),
+            ("GIT_AI_STATSD_ADDR", &addr),
+            ("GIT_AI_STATSD_TAGS", "team:platform"),
+        ],
+        None,
+    )
+    .unwrap();
+
+    let received = drain(&agent);
+    assert!(
+        received
+            .lines()
+            .any(|line| line.starts_with("git_ai.committed.ai_additions:2|c|#team:platform")),
+        "{}",
+        received
+    );
+    assert!(received.contains("git_ai.commit.size:3|h"), "{}", received);
+}
+
+#[test]
+fn test_statsd_sink_is_off_by_default() {
+    let agent = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let addr = agent.local_addr().unwrap().to_string();
+    let repo = TestRepo::new();
+
+    let mut file = repo.filename("a.txt");
+    file.set_contents(lines!["ai".ai()]);
+    repo.git(&["add", "-A"]).unwrap();
+    repo.commit_with_env("AI commit", &[("GIT_AI_STATSD_ADDR", &addr)], None)
+        .unwrap();
+
+    assert_eq!(drain(&agent), "");
+}
+
+#[test]
+fn test_aggregate_privacy_withholds_per_commit_metrics() {
+    let agent = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let addr = agent.local_addr().unwrap().to_string();
+    let repo = TestRepo::new();
+
+    let mut file = repo.filename("a.txt");
+    file.set_contents(lines!["human".human(), "ai".ai()]);
+    repo.git(&["add", "-A"]).unwrap();
+    repo.commit_with_env(
+        "AI commit",
+        &[
+            ("GIT_AI_METRICS_SINK", "statsd"),
+            ("GIT_AI_STATSD_ADDR", &addr),
+            ("GIT_AI_TELEMETRY_PRIVACY", "aggregate"),
+        ],
+        None,
+    )
+    .unwrap();
+
+    // Today's commit only counts toward a total sent once the day is over; earlier days'
+    // totals may go out now, but never as per-commit or per-author series
+    let received = drain(&agent);
+    assert!(
+        received
+            .lines()
+            .all(|line| line.starts_with("git_ai.team.") && !line.contains("author:")),
+        "{}",
+        received
+    );
+}
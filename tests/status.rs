@@ -0,0 +1,62 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+fn staged_json(repo: &TestRepo) -> serde_json::Value {
+    let output = repo
+        .git_ai(&["status", "--staged", "--json"])
+        .expect("status --staged should succeed");
+    let line = output
+        .lines()
+        .find(|line| line.starts_with('{'))
+        .expect("status --staged --json prints a JSON object");
+    serde_json::from_str(line).unwrap()
+}
+
+#[test]
+fn test_status_staged_only_counts_staged_lines() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("app.txt");
+    file.set_contents(lines!["human line"]);
+    repo.stage_all_and_commit("base").unwrap();
+
+    file.set_contents(lines!["human line", "ai line 1".ai(), "ai line 2".ai()]);
+    let mut other = repo.filename("unstaged.txt");
+    other.set_contents(lines!["ai but not staged".ai()]);
+    repo.git_og(&["reset", "--quiet", "unstaged.txt"]).unwrap();
+
+    let staged = staged_json(&repo);
+    // The first line is rewritten too, since the committed file had no trailing newline
+    assert_eq!(staged["added_lines"], 3);
+    assert_eq!(staged["ai_lines"], 2);
+    assert_eq!(staged["human_lines"], 1);
+    assert_eq!(staged["agents"][0]["tool"], "mock_ai");
+    let files = staged["files"].as_array().unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0]["path"], "app.txt");
+    assert_eq!(files[0]["hunks"][0]["start_line"], 2);
+    assert_eq!(files[0]["hunks"][0]["end_line"], 3);
+}
+
+#[test]
+fn test_status_staged_with_nothing_staged() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("app.txt");
+    file.set_contents(lines!["human line"]);
+    repo.stage_all_and_commit("base").unwrap();
+
+    let staged = staged_json(&repo);
+    assert_eq!(staged["added_lines"], 0);
+    assert_eq!(staged["files"], serde_json::json!([]));
+    assert_eq!(staged["agents"], serde_json::json!([]));
+}
+
+#[test]
+fn test_status_watch_requires_json() {
+    let repo = TestRepo::new();
+    let err = repo
+        .git_ai(&["status", "--watch"])
+        .expect_err("--watch without --json should fail");
+    assert!(err.contains("requires --json"), "{}", err);
+}
@@ -0,0 +1,69 @@
+mod repos;
+
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+#[test]
+fn test_summary_note_mirrored_into_default_notes_ref() {
+    let mut repo = TestRepo::new();
+    repo.patch_git_ai_config(|patch| {
+        patch.mirror_summary_notes = Some(true);
+    });
+
+    let mut file = repo.filename("feature.txt");
+    file.set_contents(vec![
+        "human line".human(),
+        "AI line one".ai(),
+        "AI line two".ai(),
+    ]);
+    repo.stage_all_and_commit("add feature").unwrap();
+
+    let summary = repo.git(&["notes", "--ref=commits", "show", "HEAD"]).unwrap();
+    assert!(
+        summary
+            .lines()
+            .any(|line| line.starts_with("git-ai: 2 AI-generated lines in 1 file")),
+        "unexpected summary note: {}",
+        summary
+    );
+
+    // Plain `git log` shows refs/notes/commits without any configuration
+    let log = repo.git(&["log", "-1"]).unwrap();
+    assert!(log.contains("git-ai: 2 AI-generated lines"), "{}", log);
+
+    // The structured payload still lives in refs/notes/ai
+    assert!(repo.git(&["notes", "--ref=ai", "show", "HEAD"]).is_ok());
+}
+
+#[test]
+fn test_summary_notes_not_written_by_default() {
+    let repo = TestRepo::new();
+
+    let mut file = repo.filename("feature.txt");
+    file.set_contents(vec!["AI line".ai()]);
+    repo.stage_all_and_commit("add feature").unwrap();
+
+    assert!(repo.git(&["notes", "--ref=commits", "show", "HEAD"]).is_err());
+}
+
+#[test]
+fn test_summary_notes_pushed_alongside_ai_notes() {
+    let (mut local, upstream) = TestRepo::new_with_remote();
+    local.patch_git_ai_config(|patch| {
+        patch.mirror_summary_notes = Some(true);
+    });
+
+    let mut file = local.filename("feature.txt");
+    file.set_contents(vec!["AI line".ai()]);
+    let sha = local.stage_all_and_commit("add feature").unwrap().commit_sha;
+    local.git(&["push", "-u", "origin", "HEAD"]).unwrap();
+
+    let remote_summary = upstream
+        .git(&["notes", "--ref=commits", "show", &sha])
+        .expect("remote should have the summary note");
+    assert!(
+        remote_summary.contains("git-ai: 1 AI-generated line in 1 file"),
+        "{}",
+        remote_summary
+    );
+}
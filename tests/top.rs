@@ -0,0 +1,41 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+#[test]
+fn test_top_json_snapshot() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("app.txt");
+    file.set_contents(lines!["human line"]);
+    repo.stage_all_and_commit("base").unwrap();
+
+    file.set_contents(lines!["human line", "ai line 1".ai(), "ai line 2".ai()]);
+
+    let output = repo
+        .git_ai(&["top", "--json"])
+        .expect("top --json should succeed");
+    let line = output
+        .lines()
+        .find(|line| line.starts_with('{'))
+        .expect("top --json prints a JSON object");
+    let snapshot: serde_json::Value = serde_json::from_str(line).unwrap();
+
+    assert_eq!(snapshot["stats"]["ai_additions"], 2);
+    let agents = snapshot["agents"].as_array().unwrap();
+    assert_eq!(agents.len(), 1);
+    assert!(
+        agents[0]["agent"].as_str().unwrap().starts_with("Mock_ai"),
+        "{}",
+        agents[0]
+    );
+    assert!(
+        snapshot["checkpoints"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|cp| cp["is_human"] == false)
+    );
+    // TestRepo has no remotes
+    assert_eq!(snapshot["notes"], serde_json::json!([]));
+}
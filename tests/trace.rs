@@ -0,0 +1,83 @@
+mod repos;
+
+use git_ai::authorship::authorship_log_serialization::generate_prompt_hash;
+use git_ai::authorship::transcript::{AiTranscript, Message};
+use repos::test_repo::TestRepo;
+use std::fs;
+
+/// agent-v1 checkpoint whose transcript holds `prompts` as the user's turns so far
+fn checkpoint_after_prompts(repo: &TestRepo, prompts: &[&str]) {
+    let mut transcript = AiTranscript::new();
+    for prompt in prompts {
+        transcript.add_message(Message::user(prompt.to_string(), None));
+        transcript.add_message(Message::assistant("Done.".to_string(), None));
+    }
+
+    let hook_input = serde_json::json!({
+        "type": "ai_agent",
+        "repo_working_dir": repo.path().to_str().unwrap(),
+        "edited_filepaths": ["lib.rs"],
+        "transcript": transcript,
+        "agent_name": "test-agent",
+        "model": "test-model",
+        "conversation_id": "conversation-1",
+    });
+    repo.git_ai(&[
+        "checkpoint",
+        "agent-v1",
+        "--hook-input",
+        &serde_json::to_string(&hook_input).unwrap(),
+    ])
+    .expect("checkpoint should succeed");
+}
+
+fn trace(repo: &TestRepo, location: &str) -> serde_json::Value {
+    let output = repo
+        .git_ai(&["trace", "--format", "json", "HEAD", location])
+        .unwrap();
+    serde_json::from_str(output.lines().last().unwrap()).expect(&output)
+}
+
+#[test]
+fn test_trace_finds_the_prompt_behind_each_ai_line() {
+    let mut repo = TestRepo::new();
+    repo.patch_git_ai_config(|patch| {
+        patch.exclude_prompts_in_repositories = Some(vec![]);
+        patch.prompt_storage = Some("notes".to_string());
+    });
+    let path = repo.path().join("lib.rs");
+
+    fs::write(&path, "// base\n").unwrap();
+    repo.stage_all_and_commit("base").unwrap();
+
+    fs::write(&path, "// base\nfn a() {}\n").unwrap();
+    checkpoint_after_prompts(&repo, &["Add a"]);
+    fs::write(&path, "// base\nfn a() {}\nfn b() {}\n").unwrap();
+    checkpoint_after_prompts(&repo, &["Add a", "Add b"]);
+    let ai_commit = repo.stage_all_and_commit("AI functions").unwrap();
+
+    // A later human commit moves the AI lines down
+    fs::write(&path, "// header\n// base\nfn a() {}\nfn b() {}\n").unwrap();
+    repo.stage_all_and_commit("header").unwrap();
+
+    let b = trace(&repo, "lib.rs:4");
+    assert_eq!(b["commit"], ai_commit.commit_sha.as_str());
+    assert_eq!(b["commit_line"], 3);
+    assert_eq!(b["ai"], true);
+    assert_eq!(b["session"]["tool"], "test-agent");
+    assert_eq!(b["session"]["id"], "conversation-1");
+    assert_eq!(b["prompt_hash"], generate_prompt_hash("Add b").as_str());
+    assert_eq!(b["prompt"], "Add b");
+
+    let a = trace(&repo, "lib.rs:3");
+    assert_eq!(a["commit_line"], 2);
+    assert_eq!(a["prompt"], "Add a");
+
+    let header = trace(&repo, "lib.rs:1");
+    assert_eq!(header["ai"], false);
+    assert!(header.get("session").is_none());
+
+    let text = repo.git_ai(&["trace", "lib.rs:4"]).unwrap();
+    assert!(text.contains("session    conversation-1"), "{}", text);
+    assert!(text.contains("Add b"), "{}", text);
+}
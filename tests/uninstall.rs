@@ -0,0 +1,105 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+/// Leave the user-level agent hooks and metrics stores of the machine running the tests alone
+const REPO_ONLY: [&str; 3] = ["uninstall", "--keep-agent-hooks", "--keep-metrics"];
+
+fn uninstall(repo: &TestRepo, extra: &[&str]) -> String {
+    let args: Vec<&str> = REPO_ONLY.iter().chain(extra).copied().collect();
+    repo.git_ai(&args).expect("uninstall should succeed")
+}
+
+fn ref_exists(repo: &TestRepo, refname: &str) -> bool {
+    repo.git_og(&["rev-parse", "--verify", "--quiet", refname])
+        .is_ok_and(|sha| !sha.trim().is_empty())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_uninstall_restores_chained_hooks_and_removes_git_ai_hooks() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let repo = TestRepo::new();
+    let hooks_dir = repo.path().join(".git").join("hooks");
+    std::fs::create_dir_all(&hooks_dir).unwrap();
+    let existing = "#!/bin/sh\necho existing\n";
+    std::fs::write(hooks_dir.join("post-rewrite"), existing).unwrap();
+    std::fs::set_permissions(
+        hooks_dir.join("post-rewrite"),
+        std::fs::Permissions::from_mode(0o755),
+    )
+    .unwrap();
+
+    repo.git_ai(&["post-rewrite", "install"]).unwrap();
+    repo.git_ai(&["prepare-commit-msg", "install"]).unwrap();
+    assert!(hooks_dir.join("post-rewrite.pre-git-ai").is_file());
+    assert!(hooks_dir.join("prepare-commit-msg").is_file());
+
+    let dry_run = uninstall(&repo, &["--dry-run"]);
+    assert!(dry_run.contains("[dry run] Restored"), "{}", dry_run);
+    assert!(hooks_dir.join("post-rewrite.pre-git-ai").is_file());
+
+    let output = uninstall(&repo, &[]);
+    assert!(output.contains("Restored"), "{}", output);
+    assert_eq!(
+        std::fs::read_to_string(hooks_dir.join("post-rewrite")).unwrap(),
+        existing
+    );
+    assert!(!hooks_dir.join("post-rewrite.pre-git-ai").exists());
+    assert!(!hooks_dir.join("post-commit").exists());
+    assert!(!hooks_dir.join("prepare-commit-msg").exists());
+    assert!(!repo.path().join(".git").join("ai").exists());
+}
+
+#[test]
+fn test_uninstall_keeps_notes_by_default() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("a.txt");
+    file.set_contents(lines!["ai line".ai()]);
+    repo.stage_all_and_commit("AI commit").unwrap();
+
+    uninstall(&repo, &[]);
+    assert!(ref_exists(&repo, "refs/notes/ai"));
+}
+
+#[test]
+fn test_uninstall_archives_notes() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("a.txt");
+    file.set_contents(lines!["ai line".ai()]);
+    repo.stage_all_and_commit("AI commit").unwrap();
+    let notes_tip = repo.git_og(&["rev-parse", "refs/notes/ai"]).unwrap();
+
+    let output = uninstall(&repo, &["--notes", "archive"]);
+    assert!(output.contains("Archived refs/notes/ai"), "{}", output);
+    assert!(!ref_exists(&repo, "refs/notes/ai"));
+    assert_eq!(
+        repo.git_og(&["rev-parse", "refs/ai-archive/notes/ai"])
+            .unwrap(),
+        notes_tip
+    );
+}
+
+#[test]
+fn test_uninstall_deletes_notes_on_remote() {
+    let (local, upstream) = TestRepo::new_with_remote();
+    let mut file = local.filename("a.txt");
+    file.set_contents(lines!["ai line".ai()]);
+    local.stage_all_and_commit("AI commit").unwrap();
+    local.git(&["push", "-u", "origin", "HEAD"]).unwrap();
+    assert!(ref_exists(&upstream, "refs/notes/ai"));
+    let remote_tip = upstream.git_og(&["rev-parse", "refs/notes/ai"]).unwrap();
+
+    uninstall(&local, &["--notes", "archive", "--remote", "origin"]);
+    assert!(!ref_exists(&upstream, "refs/notes/ai"));
+    assert_eq!(
+        upstream
+            .git_og(&["rev-parse", "refs/ai-archive/notes/ai"])
+            .unwrap(),
+        remote_tip
+    );
+    assert!(!ref_exists(&local, "refs/notes/ai"));
+    assert!(!ref_exists(&local, "refs/notes/ai-remote/origin"));
+}
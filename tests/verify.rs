@@ -0,0 +1,221 @@
+#[macro_use]
+mod repos;
+
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+fn verify_to_file(repo: &TestRepo, args: &[&str]) -> (bool, serde_json::Value) {
+    let report_path = repo.path().join("verify-report.json");
+    let report_path = report_path.to_str().unwrap();
+    let mut full_args = vec!["verify"];
+    full_args.extend_from_slice(args);
+    full_args.extend_from_slice(&["--output", report_path]);
+    let passed = repo.git_ai(&full_args).is_ok();
+    let report = std::fs::read_to_string(report_path).unwrap();
+    std::fs::remove_file(report_path).unwrap();
+    (passed, serde_json::from_str(&report).unwrap())
+}
+
+#[test]
+fn test_verify_passes_for_commits_made_through_git_ai() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("app.txt");
+    file.set_contents(lines!["human line"]);
+    repo.stage_all_and_commit("base").unwrap();
+    let base = repo.git_og(&["rev-parse", "HEAD"]).unwrap();
+    file.set_contents(lines!["human line", "ai line".ai(), "another ai line".ai()]);
+    repo.stage_all_and_commit("AI commit").unwrap();
+
+    let range = format!("{}..HEAD", base.trim());
+    let (passed, report) = verify_to_file(&repo, &[&range, "--format", "json"]);
+    assert!(passed, "{}", report);
+    assert_eq!(report["commits_checked"], 1);
+    assert_eq!(report["findings"], serde_json::json!([]));
+}
+
+#[test]
+fn test_verify_reports_missing_and_corrupt_notes() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("app.txt");
+    file.set_contents(lines!["ai line".ai()]);
+    repo.stage_all_and_commit("AI commit").unwrap();
+    let noted = repo.git_og(&["rev-parse", "HEAD"]).unwrap();
+
+    // Committed behind git-ai's back: no note at all
+    std::fs::write(repo.path().join("plain.txt"), "plain\n").unwrap();
+    repo.git_og(&["add", "plain.txt"]).unwrap();
+    repo.git_og(&["commit", "-m", "plain git"]).unwrap();
+
+    let (passed, report) = verify_to_file(&repo, &["HEAD", "--format", "sarif"]);
+    assert!(!passed);
+    let results = report["runs"][0]["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["ruleId"], "verify/missing-note");
+    assert_eq!(results[0]["level"], "error");
+
+    // Hand-edited note that no longer parses
+    repo.git_og(&[
+        "notes",
+        "--ref=ai",
+        "add",
+        "-f",
+        "-m",
+        "garbage",
+        noted.trim(),
+    ])
+    .unwrap();
+    let (passed, report) = verify_to_file(&repo, &[noted.trim(), "--json"]);
+    assert!(!passed);
+    assert_eq!(report["findings"][0]["rule"], "verify/invalid-note");
+}
+
+#[test]
+fn test_verify_reports_lines_not_added_by_commit() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("app.txt");
+    file.set_contents(lines!["human line", "ai line".ai()]);
+    repo.stage_all_and_commit("AI commit").unwrap();
+
+    // Point the attestation at a line far past the end of the file
+    let note = repo.git_og(&["notes", "--ref=ai", "show", "HEAD"]).unwrap();
+    let (attestations, metadata) = note.split_once("\n---\n").unwrap();
+    let tampered: Vec<String> = attestations
+        .lines()
+        .map(|line| match line.strip_prefix("  ") {
+            Some(entry) => format!("  {} 40-41", entry.split_whitespace().next().unwrap()),
+            None => line.to_string(),
+        })
+        .collect();
+    let tampered = format!("{}\n---\n{}", tampered.join("\n"), metadata);
+    repo.git_og(&["notes", "--ref=ai", "add", "-f", "-m", &tampered, "HEAD"])
+        .unwrap();
+
+    let (passed, report) = verify_to_file(&repo, &["HEAD", "--json"]);
+    assert!(!passed);
+    let finding = &report["findings"][0];
+    assert_eq!(finding["rule"], "verify/line-mismatch");
+    assert_eq!(finding["path"], "app.txt");
+    assert_eq!(finding["lines"], serde_json::json!([40, 41]));
+}
+
+/// Sign notes with a throwaway SSH key that the repository also trusts for verification
+fn enable_note_signing(repo: &TestRepo) {
+    let key = repo.path().join(".git").join("signing-key");
+    let status = std::process::Command::new("ssh-keygen")
+        .args(["-q", "-t", "ed25519", "-N", "", "-f"])
+        .arg(&key)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let public_key = std::fs::read_to_string(key.with_extension("pub")).unwrap();
+    let allowed_signers = repo.path().join(".git").join("allowed-signers");
+    std::fs::write(&allowed_signers, format!("* {}", public_key)).unwrap();
+
+    let public_key_path = key.with_extension("pub");
+    for (name, value) in [
+        ("gpg.format", "ssh"),
+        ("user.signingkey", public_key_path.to_str().unwrap()),
+        (
+            "gpg.ssh.allowedSignersFile",
+            allowed_signers.to_str().unwrap(),
+        ),
+        ("git-ai.signNotes", "true"),
+    ] {
+        repo.git_og(&["config", name, value]).unwrap();
+    }
+}
+
+#[test]
+fn test_verify_checks_note_signatures() {
+    let repo = TestRepo::new();
+    enable_note_signing(&repo);
+    let mut file = repo.filename("app.txt");
+    file.set_contents(lines!["human line", "ai line".ai()]);
+    repo.stage_all_and_commit("AI commit").unwrap();
+
+    let (passed, report) = verify_to_file(&repo, &["HEAD", "--require-signed", "--json"]);
+    assert!(passed, "{}", report);
+    assert_eq!(report["findings"], serde_json::json!([]));
+
+    // Edit the note after the fact; it still parses but no longer matches its signature
+    let note = repo.git_og(&["notes", "--ref=ai", "show", "HEAD"]).unwrap();
+    let (attestations, metadata) = note.split_once("\n---\n").unwrap();
+    // Indenting a line of the JSON keeps it valid; `notes add` would strip trailing spaces
+    let metadata = metadata.replacen('\n', "\n ", 1);
+    let tampered = format!("{}\n---\n{}", attestations, metadata);
+    repo.git_og(&["notes", "--ref=ai", "add", "-f", "-m", &tampered, "HEAD"])
+        .unwrap();
+
+    let (passed, report) = verify_to_file(&repo, &["HEAD", "--json"]);
+    assert!(!passed);
+    let finding = &report["findings"][0];
+    assert_eq!(finding["rule"], "verify/bad-signature");
+    assert!(
+        finding["message"]
+            .as_str()
+            .unwrap()
+            .contains("changed after it was signed"),
+        "{}",
+        finding
+    );
+}
+
+#[test]
+fn test_verify_rejects_notes_signed_by_an_untrusted_key() {
+    let repo = TestRepo::new();
+    enable_note_signing(&repo);
+    // Sign with a second key that isn't in the allowed signers file
+    let other_key = repo.path().join(".git").join("other-key");
+    let status = std::process::Command::new("ssh-keygen")
+        .args(["-q", "-t", "ed25519", "-N", "", "-f"])
+        .arg(&other_key)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    repo.git_og(&[
+        "config",
+        "user.signingkey",
+        other_key.with_extension("pub").to_str().unwrap(),
+    ])
+    .unwrap();
+
+    let mut file = repo.filename("app.txt");
+    file.set_contents(lines!["human line", "ai line".ai()]);
+    repo.stage_all_and_commit("AI commit").unwrap();
+
+    for args in [
+        &["HEAD", "--json"][..],
+        &["HEAD", "--require-signed", "--json"][..],
+    ] {
+        let (passed, report) = verify_to_file(&repo, args);
+        assert!(!passed, "{}", report);
+        let finding = &report["findings"][0];
+        assert_eq!(finding["rule"], "verify/bad-signature");
+        assert_eq!(finding["level"], "error");
+        assert!(
+            finding["message"]
+                .as_str()
+                .unwrap()
+                .contains("untrusted key"),
+            "{}",
+            finding
+        );
+    }
+}
+
+#[test]
+fn test_verify_require_signed_rejects_unsigned_notes() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("app.txt");
+    file.set_contents(lines!["ai line".ai()]);
+    repo.stage_all_and_commit("AI commit").unwrap();
+
+    // Nothing is signed in this repository, so signatures aren't checked by default
+    let (passed, report) = verify_to_file(&repo, &["HEAD", "--json"]);
+    assert!(passed, "{}", report);
+
+    let (passed, report) = verify_to_file(&repo, &["HEAD", "--require-signed", "--json"]);
+    assert!(!passed);
+    assert_eq!(report["findings"][0]["rule"], "verify/unsigned-note");
+    assert_eq!(report["findings"][0]["level"], "error");
+}
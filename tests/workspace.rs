@@ -0,0 +1,147 @@
+#[macro_use]
+mod repos;
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+fn json(output: &str) -> serde_json::Value {
+    let start = output
+        .find('{')
+        .unwrap_or_else(|| panic!("no JSON in {}", output));
+    // stderr is appended after the JSON
+    serde_json::Deserializer::from_str(&output[start..])
+        .into_iter()
+        .next()
+        .unwrap()
+        .unwrap_or_else(|e| panic!("{}: {}", e, output))
+}
+
+#[test]
+fn test_workspace_aggregates_stats_and_syncs_every_repository() {
+    let (api, _api_upstream) = TestRepo::new_with_remote();
+    let (web, _web_upstream) = TestRepo::new_with_remote();
+    let workspace_file = format!("{}-workspace.json", api.path().display());
+    let env = [("GIT_AI_WORKSPACE", workspace_file.as_str())];
+
+    let mut file = api.filename("lib.rs");
+    file.set_contents(lines![
+        "fn a() {}".ai(),
+        "fn b() {}".ai(),
+        "fn c() {}".human()
+    ]);
+    api.stage_all_and_commit("AI lib").unwrap();
+    let mut file = web.filename("app.js");
+    file.set_contents(lines!["let a = 1;".ai(), "let b = 2;".human()]);
+    web.stage_all_and_commit("AI app").unwrap();
+
+    let added = api
+        .git_ai_with_env(
+            &[
+                "workspace",
+                "add",
+                api.path().to_str().unwrap(),
+                "--name",
+                "api",
+            ],
+            &env,
+        )
+        .unwrap();
+    assert!(added.contains("Added api"), "{}", added);
+    web.git_ai_with_env(&["workspace", "add", "--name", "web"], &env)
+        .unwrap();
+    // Adding the same repository again is a no-op
+    let again = web
+        .git_ai_with_env(&["workspace", "add", "."], &env)
+        .unwrap();
+    assert!(
+        again.contains("already in the workspace as web"),
+        "{}",
+        again
+    );
+
+    let list = json(
+        &api.git_ai_with_env(&["workspace", "list", "--json"], &env)
+            .unwrap(),
+    );
+    assert_eq!(list["repositories"].as_array().unwrap().len(), 2);
+    assert_eq!(list["repositories"][1]["name"], "web");
+    assert_eq!(list["repositories"][1]["available"], true);
+
+    let report = json(
+        &api.git_ai_with_env(
+            &["stats", "report", "--workspace", "--by", "tool", "--json"],
+            &env,
+        )
+        .unwrap(),
+    );
+    assert_eq!(report["ai_lines"], 3);
+    assert_eq!(report["human_lines"], 2);
+    assert_eq!(report["ai_percent"], 60.0);
+    assert_eq!(report["repositories"][0]["name"], "api");
+    assert_eq!(report["repositories"][0]["ai_lines"], 2);
+    assert_eq!(report["repositories"][1]["ai_lines"], 1);
+
+    let sync = json(
+        &api.git_ai_with_env(&["sync", "--workspace", "--json"], &env)
+            .unwrap(),
+    );
+    assert_eq!(sync["ok"], true);
+    assert_eq!(sync["repositories"][0]["remotes"][0]["remote"], "origin");
+    assert_eq!(sync["repositories"][1]["remotes"][0]["push"], "pushed");
+    assert_eq!(sync["repositories"][1]["pending_pushes"], 0);
+
+    api.git_ai_with_env(&["workspace", "remove", "web"], &env)
+        .unwrap();
+    let list = json(
+        &api.git_ai_with_env(&["workspace", "list", "--json"], &env)
+            .unwrap(),
+    );
+    assert_eq!(list["repositories"].as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn test_workspace_stats_report_lists_unavailable_repositories() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("lib.rs");
+    file.set_contents(lines!["fn a() {}".ai()]);
+    repo.stage_all_and_commit("AI lib").unwrap();
+
+    let workspace_file = format!("{}-workspace.json", repo.path().display());
+    std::fs::write(
+        &workspace_file,
+        serde_json::json!({
+            "repositories": [
+                {"name": "here", "path": repo.path()},
+                {"name": "gone", "path": format!("{}-missing", repo.path().display())},
+            ]
+        })
+        .to_string(),
+    )
+    .unwrap();
+    let env = [("GIT_AI_WORKSPACE", workspace_file.as_str())];
+
+    let report = json(
+        &repo
+            .git_ai_with_env(&["stats", "report", "--workspace", "--json"], &env)
+            .unwrap(),
+    );
+    assert_eq!(report["ai_lines"], 1);
+    assert!(report["repositories"][0].get("error").is_none());
+    assert!(
+        report["repositories"][1]["error"]
+            .as_str()
+            .unwrap()
+            .contains("does not exist")
+    );
+
+    let table = repo
+        .git_ai_with_env(&["stats", "report", "--workspace"], &env)
+        .unwrap();
+    assert!(table.contains("gone"), "{}", table);
+    assert!(table.contains("TOTAL"), "{}", table);
+
+    // Sync reports the broken repository and fails
+    assert!(
+        repo.git_ai_with_env(&["sync", "--workspace"], &env)
+            .is_err()
+    );
+}
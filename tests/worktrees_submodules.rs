@@ -0,0 +1,125 @@
+#[macro_use]
+mod repos;
+use git_ai::git::repository::{find_repository_for_file, find_repository_in_path};
+use repos::test_file::ExpectedLineExt;
+use repos::test_repo::TestRepo;
+
+/// Add `sub` as a submodule of `repo` at `path`
+fn add_submodule(repo: &TestRepo, sub: &TestRepo, path: &str) {
+    repo.git_og(&[
+        "-c",
+        "protocol.file.allow=always",
+        "submodule",
+        "add",
+        "--quiet",
+        sub.path().to_str().unwrap(),
+        path,
+    ])
+    .unwrap();
+    repo.git_og(&["commit", "-m", "Add submodule"]).unwrap();
+}
+
+#[test]
+fn test_linked_worktree_shares_config_and_keeps_own_checkpoints() {
+    let repo = TestRepo::new();
+    let mut file = repo.filename("lib.rs");
+    file.set_contents(lines!["fn main() {}".human()]);
+    repo.stage_all_and_commit("Initial").unwrap();
+    repo.git_og(&["config", "gitai.worktreetest", "shared"])
+        .unwrap();
+
+    let worktree = repo.path().with_extension("wt");
+    repo.git_og(&["worktree", "add", "--quiet", worktree.to_str().unwrap()])
+        .unwrap();
+
+    let main = find_repository_in_path(repo.path().to_str().unwrap()).unwrap();
+    let linked = find_repository_in_path(worktree.to_str().unwrap()).unwrap();
+    assert_eq!(
+        main.common_dir().canonicalize().unwrap(),
+        main.path().canonicalize().unwrap()
+    );
+    assert_ne!(
+        linked.path().canonicalize().unwrap(),
+        linked.common_dir().canonicalize().unwrap()
+    );
+    assert_eq!(
+        linked.common_dir().canonicalize().unwrap(),
+        main.path().canonicalize().unwrap()
+    );
+    assert_eq!(
+        linked.config_get_str("gitai.worktreetest").unwrap(),
+        Some("shared".to_string())
+    );
+
+    // Checkpoints follow each worktree's HEAD; the notes-derived cache is shared
+    assert!(linked.storage.working_logs.starts_with(linked.path()));
+    assert_ne!(linked.storage.working_logs, main.storage.working_logs);
+    assert!(linked.storage.cache.starts_with(linked.common_dir()));
+}
+
+#[test]
+fn test_file_in_submodule_resolves_to_submodule() {
+    let sub = TestRepo::new();
+    sub.filename("sub.rs").set_contents(lines!["fn sub() {}"]);
+    sub.stage_all_and_commit("Sub initial").unwrap();
+
+    let repo = TestRepo::new();
+    repo.filename("lib.rs").set_contents(lines!["fn lib() {}"]);
+    repo.stage_all_and_commit("Initial").unwrap();
+    add_submodule(&repo, &sub, "vendor/sub");
+
+    let file = repo.path().join("vendor").join("sub").join("sub.rs");
+    let found = find_repository_for_file(file.to_str().unwrap(), None).unwrap();
+    assert_eq!(
+        found.workdir().unwrap().canonicalize().unwrap(),
+        repo.path().join("vendor/sub").canonicalize().unwrap()
+    );
+    assert!(
+        found
+            .path()
+            .starts_with(repo.path().join(".git").join("modules"))
+    );
+
+    let top = find_repository_in_path(repo.path().to_str().unwrap()).unwrap();
+    let submodules = top.submodule_workdirs().unwrap();
+    assert_eq!(submodules.len(), 1);
+    assert_eq!(
+        submodules[0].canonicalize().unwrap(),
+        found.workdir().unwrap().canonicalize().unwrap()
+    );
+    assert!(found.submodule_workdirs().unwrap().is_empty());
+}
+
+#[test]
+fn test_install_hooks_recurses_into_submodules() {
+    let sub = TestRepo::new();
+    sub.filename("sub.rs").set_contents(lines!["fn sub() {}"]);
+    sub.stage_all_and_commit("Sub initial").unwrap();
+
+    let repo = TestRepo::new();
+    repo.filename("lib.rs").set_contents(lines!["fn lib() {}"]);
+    repo.stage_all_and_commit("Initial").unwrap();
+    add_submodule(&repo, &sub, "sub");
+
+    let submodule_hook = repo
+        .path()
+        .join(".git")
+        .join("modules")
+        .join("sub")
+        .join("hooks")
+        .join("post-rewrite");
+
+    repo.git_ai(&["post-rewrite", "install"]).unwrap();
+    assert!(repo.path().join(".git/hooks/post-rewrite").is_file());
+    assert!(!submodule_hook.exists());
+
+    let output = repo
+        .git_ai(&["post-rewrite", "install", "--recurse-submodules"])
+        .unwrap();
+    assert!(submodule_hook.is_file(), "{}", output);
+    assert!(
+        std::fs::read_to_string(&submodule_hook)
+            .unwrap()
+            .contains("# Installed by git-ai post-rewrite")
+    );
+}